@@ -78,8 +78,9 @@ if [ -d "dist" ]; then
     echo "   (Keeping old dist/ until new build verified)"
 fi
 
-# Run build and capture exit code
-trunk build --public-url /$REPO_NAME/
+# Run build and capture exit code; the app reads its base path from the
+# environment at compile time (src/utils.rs)
+TRUNK_BUILD_PUBLIC_URL=/$REPO_NAME/ trunk build --public-url /$REPO_NAME/
 BUILD_EXIT_CODE=$?
 
 # Check if build succeeded
@@ -111,6 +112,11 @@ fi
 echo "📝 Adding .nojekyll file..."
 touch dist/.nojekyll
 
+# GitHub Pages serves 404.html for unknown paths; the app routes page URLs
+# like /$REPO_NAME/PGM-XIII/3 itself
+echo "📝 Adding 404.html fallback for page URLs..."
+cp dist/index.html dist/404.html
+
 # Optional: Add CNAME file for custom domain
 # Uncomment and modify the following lines if you have a custom domain:
 # echo "www.yourdomain.com" > dist/CNAME
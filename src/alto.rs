@@ -0,0 +1,191 @@
+// src/alto.rs
+// ALTO, the OCR format many libraries publish next to their scans (often
+// linked from a IIIF canvas), read as the TEI it stands for: each
+// `<TextLine>` becomes a line with a zone of its bounding box, so the
+// transcription and its highlighting work as for a diplomatic edition.
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Whether `xml` is an ALTO document, judging by its root element.
+pub fn is_alto(xml: &str) -> bool {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => {
+                return e.local_name().as_ref().eq_ignore_ascii_case(b"alto")
+            }
+            Ok(Event::Eof) | Err(_) => return false,
+            _ => {}
+        }
+        buf.clear();
+    }
+}
+
+#[derive(Debug, Default)]
+struct TextLine {
+    id: String,
+    // Bounding box: HPOS, VPOS, WIDTH, HEIGHT
+    bounds: [f64; 4],
+    text: String,
+}
+
+/// TEI with the lines of an ALTO page. Coordinates are kept in the units
+/// of the ALTO file, which are also those of the declared page size.
+pub fn to_tei(xml: &str) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut file_name = String::new();
+    let mut in_file_name = false;
+    let mut page_size = (0.0, 0.0);
+    let mut lines: Vec<TextLine> = Vec::new();
+    let mut line: Option<TextLine> = None;
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) | Ok(Event::Empty(e)) => match e.local_name().as_ref() {
+                b"fileName" => in_file_name = true,
+                b"Page" => page_size = (number(&e, "WIDTH"), number(&e, "HEIGHT")),
+                b"TextLine" => {
+                    if let Some(done) = line.take() {
+                        lines.push(done);
+                    }
+                    line = Some(TextLine {
+                        id: attribute(&e, "ID").unwrap_or_default(),
+                        bounds: ["HPOS", "VPOS", "WIDTH", "HEIGHT"].map(|a| number(&e, a)),
+                        text: String::new(),
+                    });
+                }
+                b"String" | b"HYP" => {
+                    if let (Some(line), Some(content)) = (line.as_mut(), attribute(&e, "CONTENT")) {
+                        line.text.push_str(&content);
+                    }
+                }
+                b"SP" => {
+                    if let Some(line) = line.as_mut() {
+                        line.text.push(' ');
+                    }
+                }
+                _ => {}
+            },
+            Ok(Event::Text(e)) if in_file_name => {
+                file_name = e.unescape().unwrap_or_default().trim().to_string();
+            }
+            Ok(Event::End(e)) => match e.local_name().as_ref() {
+                b"fileName" => in_file_name = false,
+                b"TextLine" => lines.extend(line.take()),
+                _ => {}
+            },
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                return Err(format!(
+                    "ALTO parsing error at position {}: {:?}",
+                    reader.buffer_position(),
+                    e
+                ))
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    lines.extend(line);
+
+    let mut zones = String::new();
+    let mut body = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        let id = if line.id.is_empty() {
+            format!("line{}", i + 1)
+        } else {
+            line.id.clone()
+        };
+        let [x, y, w, h] = line.bounds;
+        zones.push_str(&format!(
+            "<zone xml:id=\"{}\" type=\"line\" points=\"{},{} {},{} {},{} {},{}\"/>",
+            escape(&id),
+            x,
+            y,
+            x + w,
+            y,
+            x + w,
+            y + h,
+            x,
+            y + h
+        ));
+        body.push_str(&format!(
+            "<lb n=\"{}\" facs=\"#{}\"/><ab>{}</ab>",
+            i + 1,
+            escape(&id),
+            escape(line.text.trim())
+        ));
+    }
+    Ok(format!(
+        "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\"><facsimile><surface>\
+         <graphic url=\"{}\" width=\"{}\" height=\"{}\"/>{}</surface></facsimile>\
+         <text><body>{}</body></text></TEI>",
+        escape(&file_name),
+        page_size.0.round(),
+        page_size.1.round(),
+        zones,
+        body
+    ))
+}
+
+fn attribute(e: &BytesStart, name: &str) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == name.as_bytes())
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.to_string())
+}
+
+fn number(e: &BytesStart, name: &str) -> f64 {
+    attribute(e, name)
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tei_data::Reading;
+    use crate::tei_parser::parse_tei_xml;
+
+    const ALTO: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<alto xmlns="http://www.loc.gov/standards/alto/ns-v4#">
+  <Description>
+    <MeasurementUnit>pixel</MeasurementUnit>
+    <sourceImageInformation><fileName>f12r.jpg</fileName></sourceImageInformation>
+  </Description>
+  <Layout><Page ID="p1" WIDTH="2000" HEIGHT="3000"><PrintSpace><TextBlock>
+    <TextLine ID="tl1" HPOS="100" VPOS="200" WIDTH="800" HEIGHT="50">
+      <String CONTENT="ἐπὶ"/><SP/><String CONTENT="τοῦ"/><HYP CONTENT="-"/>
+    </TextLine>
+    <TextLine HPOS="100" VPOS="260" WIDTH="700" HEIGHT="50">
+      <String CONTENT="A&amp;B"/>
+    </TextLine>
+  </TextBlock></PrintSpace></Page></Layout>
+</alto>"#;
+
+    #[test]
+    fn test_is_alto() {
+        assert!(is_alto(ALTO));
+        assert!(!is_alto("<TEI><text/></TEI>"));
+        assert!(!is_alto(""));
+    }
+
+    #[test]
+    fn test_to_tei() {
+        let doc = parse_tei_xml(&to_tei(ALTO).unwrap()).unwrap();
+        assert_eq!(doc.facsimile.image_url, "f12r.jpg");
+        assert_eq!((doc.facsimile.width, doc.facsimile.height), (2000, 3000));
+        assert_eq!(doc.lines.len(), 2);
+        assert_eq!(doc.lines[0].facs, "tl1");
+        assert_eq!(doc.lines[0].text(Reading::Diplomatic), "ἐπὶ τοῦ-");
+        assert_eq!(doc.lines[1].facs, "line2");
+        assert_eq!(doc.lines[1].text(Reading::Diplomatic), "A&B");
+        assert_eq!(
+            doc.facsimile.zones["tl1"].points,
+            vec![(100, 200), (900, 200), (900, 250), (100, 250)]
+        );
+    }
+}
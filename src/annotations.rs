@@ -0,0 +1,165 @@
+// src/annotations.rs
+// Private reader notes: coloured notes anchored to diplomatic lines (and
+// optionally a quoted passage), kept in localStorage per project and
+// exportable to/importable from JSON.
+use crate::persistence;
+use serde::{Deserialize, Serialize};
+
+/// Note colours offered to the reader: (label, CSS colour)
+pub const COLORS: [(&str, &str); 4] = [
+    ("Amarillo", "#f7d154"),
+    ("Verde", "#7bd389"),
+    ("Azul", "#6fb3ff"),
+    ("Rosa", "#f28bb5"),
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub id: String,
+    pub page: u32,
+    /// Index of the annotated line in the diplomatic edition
+    pub line: usize,
+    /// Facsimile zone of the line
+    #[serde(default)]
+    pub zone: String,
+    /// Passage of the line the note is about, when text was selected
+    #[serde(default)]
+    pub quote: Option<String>,
+    pub color: String,
+    pub text: String,
+    /// Creation time, in milliseconds since the epoch
+    pub created: f64,
+    /// Bounding box of the zone (x, y, width, height) in the facsimile's
+    /// declared pixel space, and the absolute URL of the page image
+    #[serde(default)]
+    pub region: Option<[u32; 4]>,
+    #[serde(default)]
+    pub image: Option<String>,
+}
+
+/// All notes of one project, as stored and exported.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnnotationSet {
+    pub project: String,
+    pub notes: Vec<Annotation>,
+}
+
+fn storage_key(project: &str) -> String {
+    format!("tei-viewer.notes.{}", project)
+}
+
+impl AnnotationSet {
+    pub fn new(project: &str) -> Self {
+        Self {
+            project: project.to_string(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn load(project: &str) -> Self {
+        persistence::load(&storage_key(project)).unwrap_or_else(|| Self::new(project))
+    }
+
+    pub fn save(&self) {
+        persistence::save(&storage_key(&self.project), self);
+    }
+
+    /// Notes of a page, in line order.
+    pub fn for_page(&self, page: u32) -> Vec<&Annotation> {
+        let mut notes: Vec<&Annotation> = self.notes.iter().filter(|n| n.page == page).collect();
+        notes.sort_by_key(|n| n.line);
+        notes
+    }
+
+    /// Add a note, or replace the note with the same id.
+    pub fn upsert(&mut self, note: Annotation) {
+        match self.notes.iter_mut().find(|n| n.id == note.id) {
+            Some(existing) => *existing = note,
+            None => self.notes.push(note),
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        let before = self.notes.len();
+        self.notes.retain(|n| n.id != id);
+        self.notes.len() != before
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Merge an exported set into this one; notes with a known id replace
+    /// the stored copy. Returns the number of notes read.
+    pub fn import_json(&mut self, json: &str) -> Result<usize, String> {
+        let imported: AnnotationSet =
+            serde_json::from_str(json).map_err(|e| format!("JSON no válido: {}", e))?;
+        if !imported.project.is_empty() && imported.project != self.project {
+            return Err(format!("Las notas son del proyecto «{}»", imported.project));
+        }
+        let count = imported.notes.len();
+        for note in imported.notes {
+            self.upsert(note);
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(id: &str, page: u32, line: usize, text: &str) -> Annotation {
+        Annotation {
+            id: id.to_string(),
+            page,
+            line,
+            zone: format!("z{}", line),
+            quote: None,
+            color: COLORS[0].1.to_string(),
+            text: text.to_string(),
+            created: 0.0,
+            region: None,
+            image: None,
+        }
+    }
+
+    #[test]
+    fn test_for_page_sorted() {
+        let mut set = AnnotationSet::new("TEST");
+        set.upsert(note("a", 1, 5, "cinco"));
+        set.upsert(note("b", 2, 0, "otra página"));
+        set.upsert(note("c", 1, 2, "dos"));
+        let lines: Vec<usize> = set.for_page(1).iter().map(|n| n.line).collect();
+        assert_eq!(lines, vec![2, 5]);
+    }
+
+    #[test]
+    fn test_upsert_and_remove() {
+        let mut set = AnnotationSet::new("TEST");
+        set.upsert(note("a", 1, 0, "old"));
+        set.upsert(note("a", 1, 0, "new"));
+        assert_eq!(set.notes.len(), 1);
+        assert_eq!(set.notes[0].text, "new");
+        assert!(set.remove("a"));
+        assert!(!set.remove("a"));
+    }
+
+    #[test]
+    fn test_export_import() {
+        let mut set = AnnotationSet::new("TEST");
+        set.upsert(note("a", 1, 0, "una"));
+        let json = set.to_json();
+
+        let mut other = AnnotationSet::new("TEST");
+        other.upsert(note("a", 1, 0, "local"));
+        other.upsert(note("b", 1, 1, "otra"));
+        assert_eq!(other.import_json(&json), Ok(1));
+        assert_eq!(other.notes.len(), 2);
+        assert_eq!(other.notes[0].text, "una");
+
+        let mut foreign = AnnotationSet::new("OTHER");
+        assert!(foreign.import_json(&json).is_err());
+        assert!(foreign.import_json("not json").is_err());
+    }
+}
@@ -0,0 +1,113 @@
+// src/bibliography.rs
+// The bibliography of a page kept in a file of its own rather than in the
+// manifest: a JSON list of entries written as in the manifest, or TEI (e.g. a
+// `<listBibl>`) whose `<bibl>`s are the entries, each linked to the first
+// `@target` it carries.
+use crate::commentary::Format;
+use crate::project_config::BiblEntry;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Entries of the bibliography file read from `path`.
+pub fn parse(text: &str, path: &str) -> Result<Vec<BiblEntry>, String> {
+    if Format::of(path) == Format::Tei {
+        from_tei(text)
+    } else {
+        serde_json::from_str(text).map_err(|e| format!("Invalid bibliography: {}", e))
+    }
+}
+
+fn target(e: &BytesStart) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.local_name().as_ref() == b"target")
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.to_string())
+}
+
+fn from_tei(xml: &str) -> Result<Vec<BiblEntry>, String> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(false);
+    let mut buf = Vec::new();
+    let mut entries = Vec::new();
+    // Depth within the `<bibl>` being read, with its text and link so far
+    let mut depth = 0;
+    let mut text = String::new();
+    let mut url: Option<String> = None;
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) if depth > 0 || e.local_name().as_ref() == b"bibl" => {
+                depth += 1;
+                url = url.or_else(|| target(e));
+            }
+            Ok(Event::Empty(ref e)) if depth > 0 => {
+                url = url.or_else(|| target(e));
+            }
+            Ok(Event::Text(e)) if depth > 0 => {
+                text.push_str(&e.unescape().unwrap_or_default());
+            }
+            Ok(Event::End(_)) if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    let citation = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                    if !citation.is_empty() {
+                        entries.push(match url.take() {
+                            Some(url) => BiblEntry::Linked {
+                                text: citation,
+                                url,
+                            },
+                            None => BiblEntry::Citation(citation),
+                        });
+                    }
+                    text.clear();
+                    url = None;
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => return Err(format!("Invalid bibliography: {}", e)),
+            _ => {}
+        }
+        buf.clear();
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json =
+            r#"["Betz 1986, p. 172", {"text": "Preisendanz 1931", "url": "https://x.org/pgm"}]"#;
+        assert_eq!(
+            parse(json, "bibl/p1.json").unwrap(),
+            vec![
+                BiblEntry::Citation("Betz 1986, p. 172".to_string()),
+                BiblEntry::Linked {
+                    text: "Preisendanz 1931".to_string(),
+                    url: "https://x.org/pgm".to_string()
+                }
+            ]
+        );
+        assert!(parse("{", "bibl/p1.json").is_err());
+
+        let xml = r#"<listBibl xmlns="http://www.tei-c.org/ns/1.0">
+            <head>Bibliografía</head>
+            <bibl><author>Betz</author>, <title>The Greek Magical Papyri</title>,
+                <date>1986</date></bibl>
+            <bibl><author>Preisendanz</author> <ptr target="https://x.org/pgm"/></bibl>
+            <bibl/>
+        </listBibl>"#;
+        assert_eq!(
+            parse(xml, "bibl/p1.xml").unwrap(),
+            vec![
+                BiblEntry::Citation("Betz, The Greek Magical Papyri, 1986".to_string()),
+                BiblEntry::Linked {
+                    text: "Preisendanz".to_string(),
+                    url: "https://x.org/pgm".to_string()
+                }
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// src/bookmarks.rs
+// Starred pages and lines across all projects, kept in localStorage as the
+// reader's personal reading list.
+use crate::persistence;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub project: String,
+    pub page: u32,
+    /// Page label as shown in the page selector
+    pub page_label: String,
+    /// Line reference, resolved like the "Ir a línea" box (the `@n` label
+    /// or the 1-based position); `None` for a whole page
+    #[serde(default)]
+    pub line: Option<String>,
+    /// Start of the line's text, to recognise it in the list
+    #[serde(default)]
+    pub excerpt: Option<String>,
+    /// Creation time, in milliseconds since the epoch
+    pub created: f64,
+}
+
+impl Bookmark {
+    fn same_target(&self, other: &Bookmark) -> bool {
+        self.project == other.project && self.page == other.page && self.line == other.line
+    }
+}
+
+/// All bookmarks, most recent first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    pub items: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        persistence::load(persistence::BOOKMARKS).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        persistence::save(persistence::BOOKMARKS, self);
+    }
+
+    pub fn contains(&self, project: &str, page: u32, line: Option<&str>) -> bool {
+        self.items
+            .iter()
+            .any(|b| b.project == project && b.page == page && b.line.as_deref() == line)
+    }
+
+    /// Star the bookmark's target, or unstar it if it already is.
+    /// Returns whether it is now bookmarked.
+    pub fn toggle(&mut self, bookmark: Bookmark) -> bool {
+        let before = self.items.len();
+        self.items.retain(|b| !b.same_target(&bookmark));
+        if self.items.len() < before {
+            return false;
+        }
+        self.items.insert(0, bookmark);
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.items.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(project: &str, page: u32, line: Option<&str>) -> Bookmark {
+        Bookmark {
+            project: project.to_string(),
+            page,
+            page_label: page.to_string(),
+            line: line.map(str::to_string),
+            excerpt: None,
+            created: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_toggle() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.toggle(bookmark("A", 1, None)));
+        assert!(bookmarks.toggle(bookmark("A", 1, Some("14"))));
+        assert!(bookmarks.toggle(bookmark("B", 1, None)));
+        // Most recent first
+        assert_eq!(bookmarks.items[0].project, "B");
+        assert!(bookmarks.contains("A", 1, Some("14")));
+        assert!(!bookmarks.contains("A", 1, Some("15")));
+
+        // Unstarring a line keeps its page bookmarked
+        assert!(!bookmarks.toggle(bookmark("A", 1, Some("14"))));
+        assert!(!bookmarks.contains("A", 1, Some("14")));
+        assert!(bookmarks.contains("A", 1, None));
+        assert_eq!(bookmarks.items.len(), 2);
+    }
+}
@@ -0,0 +1,301 @@
+// src/commentary.rs
+// A page's commentary, which the manifest may give as HTML, Markdown or TEI
+// (told apart by the extension of its file). Markdown and TEI are turned into
+// the HTML the commentary panel shows; only what commentaries commonly use
+// is rendered (headings, paragraphs, lists, emphasis, links), and the text
+// of anything else is kept.
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// Commentary of the pages that don't name their own
+pub const DEFAULT_PATH: &str = "commentary.html";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Html,
+    Markdown,
+    Tei,
+}
+
+impl Format {
+    /// Format of the file at `path`, HTML unless its extension says otherwise.
+    pub fn of(path: &str) -> Self {
+        let path = path.split(['?', '#']).next().unwrap_or(path);
+        let extension = path
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_ascii_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "md" | "markdown" => Format::Markdown,
+            "xml" | "tei" => Format::Tei,
+            _ => Format::Html,
+        }
+    }
+}
+
+/// The commentary read from `path`, as HTML.
+pub fn to_html(text: &str, path: &str) -> String {
+    match Format::of(path) {
+        Format::Html => text.to_string(),
+        Format::Markdown => markdown(text),
+        Format::Tei => tei(text),
+    }
+}
+
+#[derive(PartialEq)]
+enum Block {
+    None,
+    Paragraph,
+    Quote,
+    List(&'static str),
+}
+
+fn markdown(text: &str) -> String {
+    let mut html = String::new();
+    let mut block = Block::None;
+    let close = |html: &mut String, block: &Block| match block {
+        Block::None => {}
+        Block::Paragraph => html.push_str("</p>\n"),
+        Block::Quote => html.push_str("</p></blockquote>\n"),
+        Block::List(tag) => html.push_str(&format!("</{}>\n", tag)),
+    };
+    for line in text.lines() {
+        let line = line.trim();
+        let level = line.chars().take_while(|&c| c == '#').count();
+        let ordered = line
+            .split_once(". ")
+            .filter(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+        if line.is_empty() {
+            close(&mut html, &block);
+            block = Block::None;
+        } else if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            close(&mut html, &block);
+            block = Block::None;
+            html.push_str(&format!(
+                "<h{0}>{1}</h{0}>\n",
+                level,
+                inline(line[level..].trim())
+            ));
+        } else if let Some(item) = line.strip_prefix("- ").or(line.strip_prefix("* ")) {
+            if block != Block::List("ul") {
+                close(&mut html, &block);
+                block = Block::List("ul");
+                html.push_str("<ul>\n");
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline(item)));
+        } else if let Some((_, item)) = ordered {
+            if block != Block::List("ol") {
+                close(&mut html, &block);
+                block = Block::List("ol");
+                html.push_str("<ol>\n");
+            }
+            html.push_str(&format!("<li>{}</li>\n", inline(item)));
+        } else if let Some(quote) = line.strip_prefix('>') {
+            if block != Block::Quote {
+                close(&mut html, &block);
+                block = Block::Quote;
+                html.push_str("<blockquote><p>");
+            } else {
+                html.push('\n');
+            }
+            html.push_str(&inline(quote.trim()));
+        } else {
+            match block {
+                Block::Paragraph => html.push('\n'),
+                _ => {
+                    close(&mut html, &block);
+                    block = Block::Paragraph;
+                    html.push_str("<p>");
+                }
+            }
+            html.push_str(&inline(line));
+        }
+    }
+    close(&mut html, &block);
+    html
+}
+
+/// Emphasis (`*`, `**`), code and links within a line of Markdown
+fn inline(text: &str) -> String {
+    let mut html = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let span = |marker: &str| {
+            let after = rest.strip_prefix(marker)?;
+            let end = after.find(marker).filter(|&end| end > 0)?;
+            Some((&after[..end], &after[end + marker.len()..]))
+        };
+        if let Some((inner, after)) = span("**") {
+            html.push_str(&format!("<strong>{}</strong>", inline(inner)));
+            rest = after;
+        } else if let Some((inner, after)) = span("*") {
+            html.push_str(&format!("<em>{}</em>", inline(inner)));
+            rest = after;
+        } else if let Some((inner, after)) = span("`") {
+            html.push_str(&format!("<code>{}</code>", escape(inner)));
+            rest = after;
+        } else if let Some((label, url, after)) = link(rest) {
+            html.push_str(&format!(
+                "<a href=\"{}\">{}</a>",
+                escape(url),
+                inline(label)
+            ));
+            rest = after;
+        } else {
+            html.push_str(&escape(&rest[..c.len_utf8()]));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    html
+}
+
+/// `[label](url)` at the start of `text`, with what follows it
+fn link(text: &str) -> Option<(&str, &str, &str)> {
+    let after = text.strip_prefix('[')?;
+    let (label, after) = after.split_once("](")?;
+    let (url, after) = after.split_once(')')?;
+    Some((label, url.trim(), after))
+}
+
+/// HTML element standing for a TEI element, with its attributes; `None`
+/// keeps only the content.
+fn tei_element(e: &BytesStart) -> Option<(&'static str, String)> {
+    let attribute = |name: &str| {
+        e.attributes()
+            .flatten()
+            .find(|a| a.key.local_name().as_ref() == name.as_bytes())
+            .and_then(|a| a.unescape_value().ok())
+            .map(|v| v.to_string())
+    };
+    let element = match e.local_name().as_ref() {
+        b"head" => ("h3", String::new()),
+        b"p" | b"ab" => ("p", String::new()),
+        b"list" => ("ul", String::new()),
+        b"item" => ("li", String::new()),
+        b"quote" | b"q" => ("q", String::new()),
+        b"emph" | b"title" | b"term" => ("em", String::new()),
+        b"foreign" => (
+            "em",
+            attribute("lang")
+                .map(|lang| format!(" lang=\"{}\"", escape(&lang)))
+                .unwrap_or_default(),
+        ),
+        b"hi" => {
+            let rend = attribute("rend").unwrap_or_default();
+            if rend.contains("bold") {
+                ("strong", String::new())
+            } else if rend.contains("sup") {
+                ("sup", String::new())
+            } else {
+                ("em", String::new())
+            }
+        }
+        b"ref" => (
+            "a",
+            attribute("target")
+                .map(|target| format!(" href=\"{}\"", escape(&target)))
+                .unwrap_or_default(),
+        ),
+        _ => return None,
+    };
+    Some(element)
+}
+
+fn tei(xml: &str) -> String {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(false);
+    let mut buf = Vec::new();
+    let mut html = String::new();
+    // HTML element closing each open TEI element
+    let mut open: Vec<Option<&'static str>> = Vec::new();
+    // Depth within the header, which is not shown
+    let mut header = 0;
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                if header > 0 || e.local_name().as_ref() == b"teiHeader" {
+                    header += 1;
+                } else {
+                    let element = tei_element(e);
+                    if let Some((tag, attributes)) = &element {
+                        html.push_str(&format!("<{}{}>", tag, attributes));
+                    }
+                    open.push(element.map(|(tag, _)| tag));
+                }
+            }
+            Ok(Event::End(_)) => {
+                if header > 0 {
+                    header -= 1;
+                } else if let Some(Some(tag)) = open.pop() {
+                    html.push_str(&format!("</{}>", tag));
+                }
+            }
+            Ok(Event::Empty(ref e)) if header == 0 && e.local_name().as_ref() == b"lb" => {
+                html.push_str("<br>");
+            }
+            Ok(Event::Text(e)) if header == 0 => {
+                html.push_str(&escape(&e.unescape().unwrap_or_default()));
+            }
+            Ok(Event::CData(e)) if header == 0 => {
+                html.push_str(&escape(&String::from_utf8_lossy(&e)));
+            }
+            Ok(Event::Eof) => break,
+            Err(e) => {
+                log::warn!("Commentary is not well-formed TEI: {}", e);
+                break;
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    html.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(Format::of("commentary.html"), Format::Html);
+        assert_eq!(Format::of("notes/p3.MD?v=2"), Format::Markdown);
+        assert_eq!(Format::of("comm/p3.xml"), Format::Tei);
+        assert_eq!(Format::of("commentary"), Format::Html);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let md = "## Ceremonia\n\nEl *nombre* de **Helios**,\nvéase [PGM](https://x.org/a?b=1&c=2).\n\n- uno\n- dos <b>\n\n> cita\n> larga";
+        assert_eq!(
+            to_html(md, "p1.md"),
+            "<h2>Ceremonia</h2>\n\
+             <p>El <em>nombre</em> de <strong>Helios</strong>,\n\
+             véase <a href=\"https://x.org/a?b=1&amp;c=2\">PGM</a>.</p>\n\
+             <ul>\n<li>uno</li>\n<li>dos &lt;b&gt;</li>\n</ul>\n\
+             <blockquote><p>cita\nlarga</p></blockquote>\n"
+        );
+        assert_eq!(
+            to_html("1. a\n2. b", "p1.md"),
+            "<ol>\n<li>a</li>\n<li>b</li>\n</ol>\n"
+        );
+        assert_eq!(to_html("2 * 3 = 6", "p1.md"), "<p>2 * 3 = 6</p>\n");
+    }
+
+    #[test]
+    fn test_tei() {
+        let xml = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0">
+            <teiHeader><fileDesc><titleStmt><title>Comentario</title></titleStmt></fileDesc></teiHeader>
+            <text><body><div>
+                <head>Línea 3</head>
+                <p>La forma <foreign xml:lang="grc">ἰάω</foreign> es <hi rend="italic">rara</hi>;<lb/>
+                cf. <ref target="https://x.org">Betz</ref> &amp; <unclear>otros</unclear>.</p>
+            </div></body></text></TEI>"#;
+        let html = to_html(xml, "p3.xml");
+        assert!(!html.contains("Comentario"));
+        assert!(html.contains("<h3>Línea 3</h3>"));
+        assert!(html.contains(
+            "<p>La forma <em lang=\"grc\">ἰάω</em> es <em>rara</em>;<br>\n                cf. <a href=\"https://x.org\">Betz</a> &amp; otros.</p>"
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+// src/components/dts_browser.rs
+use crate::dts::{self, Member};
+use crate::i18n::Lang;
+use crate::net::FetchError;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct DtsBrowserProps {
+    /// Entry point of the DTS API
+    pub endpoint: AttrValue,
+    /// A resource was chosen: its id
+    pub on_open: Callback<String>,
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum DtsBrowserMsg {
+    /// Open a collection (the root one with `None`), with its title
+    Enter(Option<String>, String),
+    /// Go back to the collection at this depth of the trail
+    Back(usize),
+    Loaded(usize, Result<(String, Vec<Member>), FetchError>),
+}
+
+/// Dialog browsing the collections of a DTS repository, from its root
+/// collection down to the resources that can be read.
+pub struct DtsBrowser {
+    /// Collections opened, from the root: (id, title)
+    trail: Vec<(Option<String>, String)>,
+    /// Members of the last one, once they arrive
+    members: Option<Result<Vec<Member>, FetchError>>,
+}
+
+impl Component for DtsBrowser {
+    type Message = DtsBrowserMsg;
+    type Properties = DtsBrowserProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let mut browser = Self {
+            trail: Vec::new(),
+            members: None,
+        };
+        browser.enter(ctx, None, String::new());
+        browser
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            DtsBrowserMsg::Enter(id, title) => self.enter(ctx, id, title),
+            DtsBrowserMsg::Back(depth) => {
+                self.trail.truncate(depth + 1);
+                if let Some((id, title)) = self.trail.pop() {
+                    self.enter(ctx, id, title);
+                }
+            }
+            DtsBrowserMsg::Loaded(depth, result) => {
+                // A reply for a collection left since
+                if depth + 1 != self.trail.len() {
+                    return false;
+                }
+                self.members = Some(result.map(|(title, members)| {
+                    if let Some(last) = self.trail.last_mut().filter(|_| !title.is_empty()) {
+                        last.1 = title;
+                    }
+                    members
+                }));
+            }
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let on_close = props.on_close.reform(|_: MouseEvent| ());
+
+        html! {
+            <div class="metadata-popup-overlay dts-overlay">
+                <div class="metadata-popup dts-popup" role="dialog" aria-labelledby="dts-title">
+                    <div class="metadata-popup-header">
+                        <h2 id="dts-title">{ lang.t("Repositorio DTS") }</h2>
+                        <button class="close-btn" onclick={on_close} title={lang.t("Cerrar")}>{"×"}</button>
+                    </div>
+                    <div class="metadata-popup-content">
+                        <p class="dts-endpoint">{ props.endpoint.to_string() }</p>
+                        <nav class="dts-trail" aria-label={lang.t("Colecciones abiertas")}>
+                            { for self.trail.iter().enumerate().map(|(depth, (_, title))| {
+                                let title = if title.is_empty() { lang.t("Inicio").to_string() } else { title.clone() };
+                                html! {
+                                    <button onclick={ctx.link().callback(move |_| DtsBrowserMsg::Back(depth))}>
+                                        { title }
+                                    </button>
+                                }
+                            }) }
+                        </nav>
+                        { self.render_members(ctx) }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl DtsBrowser {
+    fn enter(&mut self, ctx: &Context<Self>, id: Option<String>, title: String) {
+        self.trail.push((id.clone(), title));
+        self.members = None;
+        let depth = self.trail.len() - 1;
+        let endpoint = ctx.props().endpoint.to_string();
+        ctx.link().send_future(async move {
+            DtsBrowserMsg::Loaded(depth, dts::browse(&endpoint, id).await)
+        });
+    }
+
+    fn render_members(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        match &self.members {
+            None => html! { <p class="dts-status">{ lang.t("Cargando…") }</p> },
+            Some(Err(e)) => html! {
+                <p class="dts-status failed">{ lang.tf("No se pudo leer el repositorio: {}", &[e]) }</p>
+            },
+            Some(Ok(members)) if members.is_empty() => html! {
+                <p class="dts-status">{ lang.t("La colección está vacía") }</p>
+            },
+            Some(Ok(members)) => html! {
+                <ul class="dts-members">
+                    { for members.iter().map(|member| self.render_member(ctx, member)) }
+                </ul>
+            },
+        }
+    }
+
+    fn render_member(&self, ctx: &Context<Self>, member: &Member) -> Html {
+        let lang = ctx.props().lang;
+        let (id, title) = (member.id.clone(), member.title.clone());
+        let (icon, onclick) = if member.is_collection {
+            (
+                "📁",
+                ctx.link()
+                    .callback(move |_| DtsBrowserMsg::Enter(Some(id.clone()), title.clone())),
+            )
+        } else {
+            ("📜", ctx.props().on_open.reform(move |_| id.clone()))
+        };
+        let hint = if member.is_collection {
+            lang.t("Abrir la colección")
+        } else {
+            lang.t("Leer el texto")
+        };
+        html! {
+            <li>
+                <button class="dts-member" {onclick} title={hint}>
+                    <span aria-hidden="true">{ icon }</span>
+                    <span class="dts-member-title">{ member.title.clone() }</span>
+                    { if member.description.is_empty() {
+                        html! {}
+                    } else {
+                        html! { <span class="dts-member-description">{ member.description.clone() }</span> }
+                    } }
+                </button>
+            </li>
+        }
+    }
+}
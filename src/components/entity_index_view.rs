@@ -0,0 +1,177 @@
+// src/components/entity_index_view.rs
+use crate::corpus::Corpus;
+use crate::entity_index::{self, Entity, EntityKind};
+use crate::i18n::Lang;
+use crate::project_config::ProjectConfig;
+use crate::tour::TourStop;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct EntityIndexViewProps {
+    pub projects: Vec<ProjectConfig>,
+    pub corpus: Corpus,
+    /// Go to a line naming the entity: (project id, stop)
+    pub on_open: Callback<(String, TourStop)>,
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum EntityIndexMsg {
+    SetKind(EntityKind),
+    SetFilter(String),
+}
+
+/// Overlay with the indices of persons, places and divine names of the
+/// whole collection, each entry linking to the lines that name it.
+pub struct EntityIndexView {
+    kind: EntityKind,
+    filter: String,
+    entities: Vec<Entity>,
+}
+
+impl Component for EntityIndexView {
+    type Message = EntityIndexMsg;
+    type Properties = EntityIndexViewProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            kind: EntityKind::Person,
+            filter: String::new(),
+            entities: Self::build(ctx),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            EntityIndexMsg::SetKind(kind) => self.kind = kind,
+            EntityIndexMsg::SetFilter(filter) => self.filter = filter,
+        }
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old: &Self::Properties) -> bool {
+        if ctx.props().corpus != old.corpus || ctx.props().projects != old.projects {
+            self.entities = Self::build(ctx);
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let on_close = props.on_close.reform(|_: MouseEvent| ());
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            EntityIndexMsg::SetFilter(
+                e.target_dyn_into::<HtmlInputElement>()
+                    .map(|input| input.value())
+                    .unwrap_or_default(),
+            )
+        });
+        let shown = entity_index::filter(&self.entities, self.kind, &self.filter);
+
+        html! {
+            <div class="metadata-popup-overlay entity-index-overlay">
+                <div class="metadata-popup entity-index-popup" role="dialog" aria-labelledby="entity-index-title">
+                    <div class="metadata-popup-header">
+                        <h2 id="entity-index-title">{ lang.t("Índices de la colección") }</h2>
+                        <button class="close-btn" onclick={on_close} title={lang.t("Cerrar")}>{"×"}</button>
+                    </div>
+                    <div class="entity-index-tabs" role="tablist">
+                        { for EntityKind::ALL.into_iter().map(|kind| {
+                            let count = self.entities.iter().filter(|e| e.kind == kind).count();
+                            let selected = kind == self.kind;
+                            html! {
+                                <button
+                                    class={classes!(selected.then_some("active"))}
+                                    role="tab"
+                                    aria-selected={selected.to_string()}
+                                    onclick={ctx.link().callback(move |_| EntityIndexMsg::SetKind(kind))}
+                                >
+                                    { format!("{} ({})", lang.t(kind.label()), lang.number(count)) }
+                                </button>
+                            }
+                        }) }
+                    </div>
+                    <input
+                        type="search"
+                        placeholder={lang.t("Filtrar nombres...")}
+                        aria-label={lang.t("Filtrar nombres")}
+                        value={self.filter.clone()}
+                        {oninput}
+                    />
+                    { if props.corpus.loading() {
+                        html! { <p class="note-hint">{ lang.tf("Leyendo las páginas de la colección... faltan {}", &[&lang.number(props.corpus.pending)]) }</p> }
+                    } else if shown.is_empty() {
+                        html! { <p class="note-hint">{ lang.t("No hay nombres etiquetados de este tipo (<persName>, <placeName>, <rs type=\"divine\">).") }</p> }
+                    } else {
+                        html! {}
+                    } }
+                    { if props.corpus.failed > 0 {
+                        html! { <p class="note-hint">{ lang.tf("{} páginas no se pudieron cargar.", &[&lang.number(props.corpus.failed)]) }</p> }
+                    } else {
+                        html! {}
+                    } }
+                    <dl class="entity-index">
+                        { for shown.into_iter().map(|entity| self.render_entity(ctx, entity)) }
+                    </dl>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl EntityIndexView {
+    fn build(ctx: &Context<Self>) -> Vec<Entity> {
+        let props = ctx.props();
+        entity_index::build(props.corpus.diplomatic(&props.projects))
+    }
+
+    fn render_entity(&self, ctx: &Context<Self>, entity: &Entity) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let mentions = match entity.occurrences.len() {
+            1 => lang.t("1 mención").to_string(),
+            n => lang.tf("{} menciones", &[&lang.number(n)]),
+        };
+        let summary = match entity.project_count() {
+            1 => mentions,
+            projects => lang.tf("{} en {} proyectos", &[&mentions, &projects]),
+        };
+
+        html! {
+            <>
+                <dt>
+                    { match &entity.ref_uri {
+                        Some(uri) if uri.starts_with("http://") || uri.starts_with("https://") => html! {
+                            <a href={uri.clone()} target="_blank" rel="noopener">{ entity.name.clone() }</a>
+                        },
+                        Some(uri) => html! { <span title={uri.clone()}>{ entity.name.clone() }</span> },
+                        None => html! { entity.name.clone() },
+                    } }
+                    <span class="entity-count">{ summary }</span>
+                </dt>
+                <dd>
+                    { for entity.occurrences.iter().map(|o| {
+                        let target = (
+                            o.project.clone(),
+                            TourStop {
+                                page: o.page,
+                                zone: None,
+                                line: Some(o.line.clone()),
+                                caption: String::new(),
+                            },
+                        );
+                        let onclick = props.on_open.reform(move |_: MouseEvent| target.clone());
+                        html! {
+                            <button {onclick} title={o.text.clone()}>
+                                { format!("{} · p. {}, l. {}", o.project_name, o.page_label, o.line) }
+                            </button>
+                        }
+                    }) }
+                </dd>
+            </>
+        }
+    }
+}
@@ -0,0 +1,101 @@
+// src/components/error_boundary.rs
+use crate::diagnostics::Failure;
+use crate::i18n::Lang;
+use yew::prelude::*;
+
+/// Handed down through a context to the wrapped components, which report
+/// through it the failures that leave them nothing to show.
+#[derive(Clone, PartialEq)]
+pub struct ErrorSink(Callback<(Option<String>, String)>);
+
+impl ErrorSink {
+    pub fn report(&self, resource: Option<&str>, message: &str) {
+        self.0
+            .emit((resource.map(str::to_string), message.to_string()));
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ErrorBoundaryProps {
+    /// What the wrapped component shows, in Spanish: "el documento"
+    pub name: &'static str,
+    /// What is being shown, e.g. "project/page"; a new scope clears the
+    /// failure and shows the component again
+    #[prop_or_default]
+    pub scope: AttrValue,
+    #[prop_or_default]
+    pub children: Html,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum ErrorBoundaryMsg {
+    Failed(Option<String>, String),
+}
+
+/// Wrapper replacing a major component with an error card once it reports
+/// a failure. Panics cannot be caught in WebAssembly; they stop the whole
+/// application and the panic hook in `diagnostics` shows the card instead.
+pub struct ErrorBoundary {
+    sink: ErrorSink,
+    failure: Option<Failure>,
+}
+
+impl Component for ErrorBoundary {
+    type Message = ErrorBoundaryMsg;
+    type Properties = ErrorBoundaryProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            sink: ErrorSink(
+                ctx.link()
+                    .callback(|(resource, message)| ErrorBoundaryMsg::Failed(resource, message)),
+            ),
+            failure: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ErrorBoundaryMsg::Failed(resource, message) => {
+                log::error!("{} failed: {}", ctx.props().name, message);
+                if self.failure.is_some() {
+                    return false;
+                }
+                self.failure = Some(Failure::new(
+                    Some(ctx.props().name),
+                    resource.as_deref(),
+                    &message,
+                ));
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old: &Self::Properties) -> bool {
+        if ctx.props().scope != old.scope {
+            self.failure = None;
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        match &self.failure {
+            Some(failure) => {
+                let link = web_sys::window()
+                    .and_then(|w| w.location().href().ok())
+                    .unwrap_or_default();
+                html! {
+                    <div class="error-boundary">
+                        { Html::from_html_unchecked(failure.card_html(ctx.props().lang, &link).into()) }
+                    </div>
+                }
+            }
+            None => html! {
+                <ContextProvider<ErrorSink> context={self.sink.clone()}>
+                    { ctx.props().children.clone() }
+                </ContextProvider<ErrorSink>>
+            },
+        }
+    }
+}
@@ -0,0 +1,225 @@
+// src/components/global_search.rs
+use crate::corpus::Corpus;
+use crate::corpus_search::{self, ProjectHits};
+use crate::i18n::Lang;
+use crate::project_config::ProjectConfig;
+use crate::tour::TourStop;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct GlobalSearchProps {
+    pub projects: Vec<ProjectConfig>,
+    pub corpus: Corpus,
+    /// Fetch the pages of every project, on the first query
+    pub on_load: Callback<()>,
+    /// Go to a matching line: (project id, stop)
+    pub on_open: Callback<(String, TourStop)>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum GlobalSearchMsg {
+    Toggle,
+    SetQuery(String),
+    ToggleDiacritics,
+    Open(String, u32, String),
+}
+
+/// Header menu searching the text of every project at once.
+pub struct GlobalSearch {
+    open: bool,
+    query: String,
+    ignore_diacritics: bool,
+    results: Vec<ProjectHits>,
+    truncated: bool,
+}
+
+impl Component for GlobalSearch {
+    type Message = GlobalSearchMsg;
+    type Properties = GlobalSearchProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            open: false,
+            query: String::new(),
+            ignore_diacritics: true,
+            results: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            GlobalSearchMsg::Toggle => {
+                self.open = !self.open;
+                true
+            }
+            GlobalSearchMsg::SetQuery(query) => {
+                self.query = query;
+                if !self.query.trim().is_empty() && !ctx.props().corpus.requested {
+                    ctx.props().on_load.emit(());
+                }
+                self.refresh(ctx);
+                true
+            }
+            GlobalSearchMsg::ToggleDiacritics => {
+                self.ignore_diacritics = !self.ignore_diacritics;
+                self.refresh(ctx);
+                true
+            }
+            GlobalSearchMsg::Open(project, page, line) => {
+                self.open = false;
+                let stop = TourStop {
+                    page,
+                    zone: None,
+                    line: Some(line),
+                    caption: String::new(),
+                };
+                ctx.props().on_open.emit((project, stop));
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, _old: &Self::Properties) -> bool {
+        // More pages arrived, or another project list
+        self.refresh(ctx);
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let on_toggle = ctx.link().callback(|_| GlobalSearchMsg::Toggle);
+        let lang = ctx.props().lang;
+        html! {
+            <div class="header-menu">
+                <button
+                    class={classes!("theme-toggle", self.open.then_some("active"))}
+                    onclick={on_toggle}
+                    title={lang.t("Buscar en todos los proyectos")}
+                    aria-expanded={self.open.to_string()}
+                >
+                    { lang.t("🔎 Buscar en la colección") }
+                </button>
+                { if self.open { self.render_panel(ctx) } else { html! {} } }
+            </div>
+        }
+    }
+}
+
+impl GlobalSearch {
+    fn refresh(&mut self, ctx: &Context<Self>) {
+        let (results, truncated) = corpus_search::search(
+            &ctx.props().projects,
+            &ctx.props().corpus,
+            &self.query,
+            self.ignore_diacritics,
+        );
+        self.results = results;
+        self.truncated = truncated;
+    }
+
+    fn render_panel(&self, ctx: &Context<Self>) -> Html {
+        let on_input = ctx.link().callback(|e: InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            GlobalSearchMsg::SetQuery(input.value())
+        });
+        let on_diacritics = ctx.link().callback(|_| GlobalSearchMsg::ToggleDiacritics);
+        let corpus = &ctx.props().corpus;
+        let lang = ctx.props().lang;
+        let searching = !self.query.trim().is_empty();
+        let hit_count: usize = self
+            .results
+            .iter()
+            .flat_map(|p| &p.pages)
+            .map(|page| page.hits.len())
+            .sum();
+
+        html! {
+            <div class="header-dropdown global-search-panel">
+                <div class="global-search-form">
+                    <input
+                        type="search"
+                        placeholder={lang.t("Buscar una fórmula en todos los proyectos...")}
+                        aria-label={lang.t("Buscar en todos los proyectos")}
+                        value={self.query.clone()}
+                        oninput={on_input}
+                    />
+                    <label class="search-option" title={lang.t("También ignora mayúsculas y sigma final/lunada")}>
+                        <input type="checkbox" checked={self.ignore_diacritics} onchange={on_diacritics} />
+                        { lang.t("Ignorar acentos/espíritus") }
+                    </label>
+                </div>
+                { if corpus.loading() {
+                    html! { <p class="header-dropdown-empty">{ lang.tf("Indexando páginas... faltan {}", &[&lang.number(corpus.pending)]) }</p> }
+                } else {
+                    html! {}
+                } }
+                { if corpus.failed > 0 {
+                    html! { <p class="header-dropdown-empty">{ lang.tf("{} páginas no se pudieron cargar.", &[&lang.number(corpus.failed)]) }</p> }
+                } else {
+                    html! {}
+                } }
+                { if !searching {
+                    html! {}
+                } else if self.results.is_empty() {
+                    if !corpus.loading() {
+                        html! { <p class="header-dropdown-empty">{ lang.t("Sin coincidencias.") }</p> }
+                    } else {
+                        html! {}
+                    }
+                } else {
+                    html! {
+                        <>
+                            <p class="global-search-count">
+                                { if self.truncated {
+                                    lang.tf("Primeras {} coincidencias", &[&lang.number(hit_count)])
+                                } else {
+                                    lang.tf("{} coincidencias", &[&lang.number(hit_count)])
+                                } }
+                            </p>
+                            { for self.results.iter().map(|project| self.render_project(ctx, project)) }
+                        </>
+                    }
+                } }
+            </div>
+        }
+    }
+
+    fn render_project(&self, ctx: &Context<Self>, project: &ProjectHits) -> Html {
+        let lang = ctx.props().lang;
+        html! {
+            <section class="global-search-project">
+                <h4>{ project.name.clone() }</h4>
+                { for project.pages.iter().map(|page| html! {
+                    <div class="global-search-page">
+                        <span class="header-dropdown-project">{ lang.tf("Página {}", &[&page.label]) }</span>
+                        <ul>
+                            { for page.hits.iter().map(|hit| {
+                                let target = (project.project.clone(), page.page, hit.line.clone());
+                                let on_open = ctx.link().callback(move |_| {
+                                    let (project, page, line) = target.clone();
+                                    GlobalSearchMsg::Open(project, page, line)
+                                });
+                                html! {
+                                    <li class="header-dropdown-item">
+                                        <button class="header-dropdown-link" onclick={on_open}>
+                                            <span>
+                                                <span class="global-search-line">{ format!("l. {}", hit.line) }</span>
+                                                { if hit.translation {
+                                                    html! { <span class="page-badge" title={lang.t("Traducción")}>{ lang.t("Trad.") }</span> }
+                                                } else {
+                                                    html! {}
+                                                } }
+                                            </span>
+                                            <span class="header-dropdown-excerpt">{ hit.text.clone() }</span>
+                                        </button>
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    </div>
+                }) }
+            </section>
+        }
+    }
+}
@@ -0,0 +1,105 @@
+// src/components/glossary_panel.rs
+use crate::glossary::GlossEntry;
+use crate::i18n::Lang;
+use yew::prelude::*;
+
+/// A place where the selected term is used.
+#[derive(Clone, PartialEq)]
+pub struct OccurrenceLink {
+    pub page: u32,
+    pub line: usize,
+    /// e.g. "p. 3, l. 12 · διπλῆς"
+    pub label: AttrValue,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GlossaryPanelProps {
+    pub entries: Vec<GlossEntry>,
+    pub selected: Option<usize>,
+    pub occurrences: Vec<OccurrenceLink>,
+    /// Clicked term without a glossary entry
+    #[prop_or_default]
+    pub missing: Option<AttrValue>,
+    /// Project pages are still being scanned for occurrences
+    #[prop_or_default]
+    pub loading: bool,
+    pub on_select: Callback<usize>,
+    /// Go to an occurrence: (page, line index)
+    pub on_open: Callback<(u32, usize)>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Sidebar listing the glossary; the selected entry shows its definition
+/// and every line of the project where the term appears.
+pub struct GlossaryPanel;
+
+impl Component for GlossaryPanel {
+    type Message = ();
+    type Properties = GlossaryPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let selected = props.selected.and_then(|i| props.entries.get(i));
+        let lang = props.lang;
+
+        html! {
+            <div class="text-panel glossary-panel">
+                <h3>{ lang.t("Glosario") }</h3>
+                { if let Some(term) = &props.missing {
+                    html! { <p class="note-hint">{ lang.tf("«{}» no tiene entrada en el glosario.", &[term]) }</p> }
+                } else {
+                    html! {}
+                } }
+                { if let Some(entry) = selected {
+                    html! {
+                        <div class="glossary-entry">
+                            <h4>{ entry.term.clone() }</h4>
+                            <p>{ entry.gloss.clone() }</p>
+                            <h5>{ lang.tf("Apariciones ({})", &[&lang.number(props.occurrences.len())]) }</h5>
+                            { if props.loading {
+                                html! { <p class="note-hint">{ lang.t("Buscando en las páginas del proyecto...") }</p> }
+                            } else {
+                                html! {}
+                            } }
+                            <ul class="glossary-occurrences">
+                                { for props.occurrences.iter().map(|o| {
+                                    let target = (o.page, o.line);
+                                    let onclick = props.on_open.reform(move |_: MouseEvent| target);
+                                    html! {
+                                        <li><button {onclick}>{ o.label.clone() }</button></li>
+                                    }
+                                }) }
+                            </ul>
+                        </div>
+                    }
+                } else {
+                    html! {}
+                } }
+                { if props.entries.is_empty() {
+                    html! { <p class="note-hint">{ lang.t("Este proyecto no define un glosario.") }</p> }
+                } else {
+                    html! {
+                        <ul class="glossary-terms">
+                            { for props.entries.iter().enumerate().map(|(index, entry)| {
+                                let active = props.selected == Some(index);
+                                let onclick = props.on_select.reform(move |_: MouseEvent| index);
+                                html! {
+                                    <li>
+                                        <button class={classes!(active.then_some("active"))} {onclick} aria-pressed={active.to_string()}>
+                                            { entry.term.clone() }
+                                        </button>
+                                    </li>
+                                }
+                            }) }
+                        </ul>
+                    }
+                } }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// src/components/help_dialog.rs
+use crate::i18n::Lang;
+use yew::prelude::*;
+
+/// (keys, action)
+const SHORTCUTS: &[(&str, &str)] = &[
+    ("Re Pág / Av Pág", "Página anterior / siguiente"),
+    ("?", "Abrir o cerrar esta ayuda"),
+    (
+        "← / → (↑ / ↓ si están apilados)",
+        "Redimensionar los paneles con el separador enfocado",
+    ),
+    ("Mayús + ← / →", "Redimensionar en pasos más grandes"),
+    ("Inicio / Fin", "Panel de imagen al mínimo / al máximo"),
+    ("Intro", "Ir a la línea escrita en «Ir a línea»"),
+    (
+        "← / → en la galería",
+        "Fotografía anterior / siguiente (Intro abre su transcripción)",
+    ),
+    (
+        "R / Mayús + R, H, V en la galería",
+        "Girar la fotografía 90°, voltearla en horizontal o en vertical",
+    ),
+];
+
+/// (gesture, action)
+const GESTURES: &[(&str, &str)] = &[
+    ("Pasar sobre una línea", "Resaltar su zona en el facsímil"),
+    (
+        "Clic en una línea",
+        "Fijar el resaltado (otro clic lo suelta)",
+    ),
+    ("Rueda del ratón sobre la imagen", "Acercar y alejar"),
+    ("Arrastrar la imagen", "Desplazar el facsímil"),
+    (
+        "Pellizcar con dos dedos",
+        "Acercar y alejar en pantallas táctiles",
+    ),
+    (
+        "Deslizar el texto a izquierda o derecha",
+        "Pasar de página (de pestaña en el móvil)",
+    ),
+    (
+        "Doble clic en el separador",
+        "Restablecer el tamaño de los paneles",
+    ),
+    (
+        "Clic en un término subrayado",
+        "Ver su entrada del glosario",
+    ),
+    (
+        "Mayús + clic en un vértice",
+        "Quitar el punto de la zona en el editor de zonas",
+    ),
+];
+
+/// (example, meaning) of the Leiden conventions found in the transcriptions
+const LEIDEN: &[(&str, &str)] = &[
+    (
+        "[αβγ]",
+        "Letras perdidas en una laguna y restituidas por el editor",
+    ),
+    ("[...]", "Laguna no restituida, un punto por letra perdida"),
+    ("(αβγ)", "Desarrollo de una abreviatura"),
+    (
+        "⟨αβγ⟩",
+        "Letras omitidas por el escriba y añadidas por el editor",
+    ),
+    ("{αβγ}", "Letras superfluas escritas por el escriba"),
+    ("⟦αβγ⟧", "Letras borradas o tachadas por el escriba"),
+    ("\\αβγ/", "Letras añadidas por el escriba sobre la línea"),
+    ("α̣β̣γ̣", "Letras dañadas o de lectura dudosa"),
+    ("α̅", "Trazo suprascrito: numeral o abreviatura"),
+    ("vac.", "Espacio dejado en blanco por el escriba"),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct HelpDialogProps {
+    pub on_close: Callback<()>,
+    /// Replay the first-visit tour
+    pub on_onboarding: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Dialog listing the keyboard shortcuts, the mouse and touch gestures and
+/// the editorial symbols of the transcriptions.
+pub struct HelpDialog;
+
+impl Component for HelpDialog {
+    type Message = ();
+    type Properties = HelpDialogProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let on_close = props.on_close.reform(|_: MouseEvent| ());
+
+        html! {
+            <div class="metadata-popup-overlay help-overlay">
+                <div class="metadata-popup help-popup" role="dialog" aria-labelledby="help-title">
+                    <div class="metadata-popup-header">
+                        <h2 id="help-title">{ lang.t("Ayuda") }</h2>
+                        <button class="close-btn" onclick={on_close} title={lang.t("Cerrar")}>{"×"}</button>
+                    </div>
+                    <div class="metadata-popup-content help-content">
+                        <h3>{ lang.t("Atajos de teclado") }</h3>
+                        { table(lang, SHORTCUTS, |keys| html! {
+                            <kbd>{ keys.to_string() }</kbd>
+                        }) }
+                        <h3>{ lang.t("Ratón y pantalla táctil") }</h3>
+                        { table(lang, GESTURES, |gesture| html! { gesture.to_string() }) }
+                        <h3>{ lang.t("Signos editoriales (sistema de Leiden)") }</h3>
+                        { table(lang, LEIDEN, |example| html! {
+                            <span class="leiden-example">{ example.to_string() }</span>
+                        }) }
+                        <p class="note-hint">{ lang.t("Los colores de las marcas del texto se explican en la leyenda (🎨).") }</p>
+                        <button class="help-onboarding" onclick={props.on_onboarding.reform(|_: MouseEvent| ())}>
+                            { lang.t("Ver de nuevo la visita de bienvenida") }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+/// Two-column table; the left cells go through `cell` after translation.
+fn table(lang: Lang, rows: &[(&str, &str)], cell: impl Fn(&str) -> Html) -> Html {
+    html! {
+        <table class="help-table">
+            <tbody>
+                { for rows.iter().map(|(left, right)| html! {
+                    <tr>
+                        <th scope="row">{ cell(lang.t(left)) }</th>
+                        <td>{ lang.t(right) }</td>
+                    </tr>
+                }) }
+            </tbody>
+        </table>
+    }
+}
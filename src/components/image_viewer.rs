@@ -0,0 +1,417 @@
+// src/components/image_viewer.rs
+use crate::gesture::{scaled_about, Gesture, Point, Pointers};
+use crate::i18n::Lang;
+use gloo_events::{EventListener, EventListenerOptions};
+use wasm_bindgen::JsCast;
+use yew::prelude::*;
+
+/// Zoom limits and the factor of one wheel step
+const MIN_SCALE: f32 = 0.2;
+const MAX_SCALE: f32 = 8.0;
+const WHEEL_FACTOR: f32 = 1.1;
+
+/// A photograph of the gallery
+#[derive(Debug, Clone, PartialEq)]
+pub struct GalleryImage {
+    pub page: u32,
+    pub label: String,
+    pub src: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ImageViewerProps {
+    pub images: Vec<GalleryImage>,
+    /// Page whose image is shown
+    pub current: u32,
+    pub on_select: Callback<u32>,
+    /// Read the page (its transcription) instead
+    pub on_open: Callback<u32>,
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum ImageViewerMsg {
+    Step(i32),
+    Zoom(f32),
+    ResetView,
+    /// Turn a quarter clockwise (1) or counterclockwise (-1)
+    Rotate(i8),
+    FlipHorizontal,
+    FlipVertical,
+    /// Pointer (id, x, y) pressed on the photograph, moved, or lifted
+    PointerDown(i32, i32, i32),
+    PointerMove(i32, i32, i32),
+    PointerUp(i32),
+    Open,
+    Close,
+}
+
+/// Turn and mirroring of the photograph shown, for versos photographed
+/// rotated or supplied mirrored
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Orientation {
+    /// Quarter turns clockwise, 0 to 3
+    quarter_turns: u8,
+    flip_horizontal: bool,
+    flip_vertical: bool,
+}
+
+impl Orientation {
+    fn rotate(&mut self, quarters: i8) {
+        self.quarter_turns = (self.quarter_turns as i8 + quarters).rem_euclid(4) as u8;
+    }
+
+    /// CSS transform functions, mirroring along the axes of the screen
+    fn css(self) -> String {
+        let sign = |flip: bool| if flip { -1 } else { 1 };
+        format!(
+            "scale({}, {}) rotate({}deg)",
+            sign(self.flip_horizontal),
+            sign(self.flip_vertical),
+            u32::from(self.quarter_turns) * 90
+        )
+    }
+}
+
+/// The photographs of a project one at a time, without its texts: steps
+/// with the arrows (buttons or keys), zooms with the wheel or by pinching,
+/// and pans by dragging with the mouse or a finger; it can be turned and
+/// mirrored.
+pub struct ImageViewer {
+    scale: f32,
+    offset: (f32, f32),
+    orientation: Orientation,
+    pointers: Pointers,
+    /// Last position of the pointer panning
+    last: Point,
+    stage_ref: NodeRef,
+    /// Photographs before and after the current one, fetched ahead
+    adjacent: Vec<(String, web_sys::HtmlImageElement)>,
+    _key_listener: Option<EventListener>,
+}
+
+impl ImageViewer {
+    fn index(ctx: &Context<Self>) -> Option<usize> {
+        let props = ctx.props();
+        props.images.iter().position(|i| i.page == props.current)
+    }
+
+    fn reset(&mut self) {
+        self.scale = 1.0;
+        self.offset = (0.0, 0.0);
+    }
+
+    /// Client coordinates of the middle of the stage, where the
+    /// photograph is centred before it is moved
+    fn stage_center(&self) -> (f32, f32) {
+        self.stage_ref
+            .cast::<web_sys::Element>()
+            .map(|stage| stage.get_bounding_client_rect())
+            .map_or((0.0, 0.0), |r| {
+                (
+                    (r.left() + r.width() / 2.0) as f32,
+                    (r.top() + r.height() / 2.0) as f32,
+                )
+            })
+    }
+
+    /// Start fetching the photographs on either side of the current one,
+    /// so that stepping to them never shows an empty frame. Those already
+    /// on their way are kept rather than requested again.
+    fn preload_adjacent(&mut self, ctx: &Context<Self>) {
+        let images = &ctx.props().images;
+        let wanted = Self::index(ctx)
+            .map(|index| [index.checked_sub(1), Some(index + 1)])
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|index| images.get(index));
+        let mut previous = std::mem::take(&mut self.adjacent);
+        self.adjacent = wanted
+            .filter_map(|image| {
+                if let Some(i) = previous.iter().position(|(src, _)| *src == image.src) {
+                    return Some(previous.swap_remove(i));
+                }
+                let element = web_sys::HtmlImageElement::new().ok()?;
+                element.set_src(&image.src);
+                Some((image.src.clone(), element))
+            })
+            .collect();
+    }
+}
+
+impl Component for ImageViewer {
+    type Message = ImageViewerMsg;
+    type Properties = ImageViewerProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let key_listener = web_sys::window()
+            .and_then(|w| w.document())
+            .map(|document| {
+                let link = ctx.link().clone();
+                let options = EventListenerOptions::enable_prevent_default();
+                EventListener::new_with_options(&document, "keydown", options, move |e| {
+                    let Some(e) = e.dyn_ref::<web_sys::KeyboardEvent>() else {
+                        return;
+                    };
+                    let msg = match e.key().as_str() {
+                        "ArrowLeft" => ImageViewerMsg::Step(-1),
+                        "ArrowRight" => ImageViewerMsg::Step(1),
+                        "+" => ImageViewerMsg::Zoom(WHEEL_FACTOR),
+                        "-" => ImageViewerMsg::Zoom(1.0 / WHEEL_FACTOR),
+                        "0" => ImageViewerMsg::ResetView,
+                        "r" => ImageViewerMsg::Rotate(1),
+                        "R" => ImageViewerMsg::Rotate(-1),
+                        "h" | "H" => ImageViewerMsg::FlipHorizontal,
+                        "v" | "V" => ImageViewerMsg::FlipVertical,
+                        "Enter" => ImageViewerMsg::Open,
+                        "Escape" => ImageViewerMsg::Close,
+                        _ => return,
+                    };
+                    let in_field = e
+                        .target()
+                        .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+                        .is_some_and(|el| {
+                            matches!(
+                                el.tag_name().as_str(),
+                                "INPUT" | "TEXTAREA" | "SELECT" | "BUTTON"
+                            ) || el.closest("[contenteditable]").ok().flatten().is_some()
+                        });
+                    if in_field || e.alt_key() || e.ctrl_key() || e.meta_key() {
+                        return;
+                    }
+                    e.prevent_default();
+                    link.send_message(msg);
+                })
+            });
+        let mut viewer = Self {
+            scale: 1.0,
+            offset: (0.0, 0.0),
+            orientation: Orientation::default(),
+            pointers: Pointers::default(),
+            last: (0, 0),
+            stage_ref: NodeRef::default(),
+            adjacent: Vec::new(),
+            _key_listener: key_listener,
+        };
+        viewer.preload_adjacent(ctx);
+        viewer
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old_props: &Self::Properties) -> bool {
+        if ctx.props().current != old_props.current {
+            self.reset();
+            self.orientation = Orientation::default();
+        }
+        self.preload_adjacent(ctx);
+        true
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let props = ctx.props();
+        match msg {
+            ImageViewerMsg::Step(step) => {
+                let last = props.images.len() as i32 - 1;
+                let target = match Self::index(ctx) {
+                    Some(index) => index as i32 + step,
+                    // A page without a photograph: start from either end
+                    None if step > 0 => 0,
+                    None => last,
+                };
+                if (0..=last).contains(&target) {
+                    props.on_select.emit(props.images[target as usize].page);
+                }
+                false
+            }
+            ImageViewerMsg::Zoom(factor) => {
+                self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+                true
+            }
+            ImageViewerMsg::ResetView => {
+                self.reset();
+                true
+            }
+            ImageViewerMsg::Rotate(quarters) => {
+                self.orientation.rotate(quarters);
+                true
+            }
+            ImageViewerMsg::FlipHorizontal => {
+                self.orientation.flip_horizontal = !self.orientation.flip_horizontal;
+                true
+            }
+            ImageViewerMsg::FlipVertical => {
+                self.orientation.flip_vertical = !self.orientation.flip_vertical;
+                true
+            }
+            ImageViewerMsg::PointerDown(id, x, y) => {
+                if let Some(at) = self.pointers.press(id, (x, y)) {
+                    self.last = at;
+                }
+                true
+            }
+            ImageViewerMsg::PointerMove(id, x, y) => match self.pointers.moved(id, (x, y)) {
+                Some(Gesture::Pan((x, y))) => {
+                    self.offset.0 += (x - self.last.0) as f32;
+                    self.offset.1 += (y - self.last.1) as f32;
+                    self.last = (x, y);
+                    true
+                }
+                Some(Gesture::Pinch { factor, center }) => {
+                    let old_scale = self.scale;
+                    self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+                    // The offset is measured from the middle of the stage
+                    let middle = self.stage_center();
+                    let change = self.scale / old_scale;
+                    self.offset.0 = scaled_about(self.offset.0, center.0 - middle.0, change);
+                    self.offset.1 = scaled_about(self.offset.1, center.1 - middle.1, change);
+                    true
+                }
+                None => false,
+            },
+            ImageViewerMsg::PointerUp(id) => {
+                if let Some(at) = self.pointers.release(id) {
+                    self.last = at;
+                }
+                true
+            }
+            ImageViewerMsg::Open => {
+                props.on_open.emit(props.current);
+                false
+            }
+            ImageViewerMsg::Close => {
+                props.on_close.emit(());
+                false
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let index = Self::index(ctx);
+        let image = index.map(|i| &props.images[i]);
+        let count = props.images.len();
+        let link = ctx.link();
+
+        let onwheel = link.callback(|e: WheelEvent| {
+            e.prevent_default();
+            if e.delta_y() < 0.0 {
+                ImageViewerMsg::Zoom(WHEEL_FACTOR)
+            } else {
+                ImageViewerMsg::Zoom(1.0 / WHEEL_FACTOR)
+            }
+        });
+        let onpointerdown = link.callback(|e: PointerEvent| {
+            e.prevent_default();
+            // Keep receiving the pointer's moves when it leaves the stage
+            if let Some(stage) = e
+                .current_target()
+                .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            {
+                let _ = stage.set_pointer_capture(e.pointer_id());
+            }
+            ImageViewerMsg::PointerDown(e.pointer_id(), e.client_x(), e.client_y())
+        });
+        let onpointermove = link.callback(|e: PointerEvent| {
+            ImageViewerMsg::PointerMove(e.pointer_id(), e.client_x(), e.client_y())
+        });
+        let onpointerup =
+            link.callback(|e: PointerEvent| ImageViewerMsg::PointerUp(e.pointer_id()));
+        let onpointercancel =
+            link.callback(|e: PointerEvent| ImageViewerMsg::PointerUp(e.pointer_id()));
+        let transform = format!(
+            "transform: translate({}px, {}px) scale({}) {};",
+            self.offset.0,
+            self.offset.1,
+            self.scale,
+            self.orientation.css()
+        );
+
+        html! {
+            <section class="gallery" aria-label={lang.t("Galería")}>
+                <div class="gallery-toolbar">
+                    <button
+                        onclick={link.callback(|_| ImageViewerMsg::Step(-1))}
+                        disabled={index == Some(0) || count == 0}
+                        title={lang.t("Imagen anterior (←)")}
+                        aria-label={lang.t("Imagen anterior")}
+                    >{"‹"}</button>
+                    <span class="gallery-position" aria-live="polite">
+                        { match image {
+                            Some(image) => lang.tf("{} · {} de {}", &[&image.label, &(index.unwrap_or(0) + 1), &count]),
+                            None => lang.t("Sin imagen").to_string(),
+                        } }
+                    </span>
+                    <button
+                        onclick={link.callback(|_| ImageViewerMsg::Step(1))}
+                        disabled={index.is_some_and(|i| i + 1 >= count) || count == 0}
+                        title={lang.t("Imagen siguiente (→)")}
+                        aria-label={lang.t("Imagen siguiente")}
+                    >{"›"}</button>
+                    <button
+                        onclick={link.callback(|_| ImageViewerMsg::ResetView)}
+                        title={lang.t("Restablecer el zoom (0)")}
+                    >{ lang.t("Ajustar") }</button>
+                    <span class="gallery-orientation" role="group" aria-label={lang.t("Orientación")}>
+                        <button
+                            onclick={link.callback(|_| ImageViewerMsg::Rotate(-1))}
+                            title={lang.t("Girar 90° a la izquierda (Mayús + R)")}
+                            aria-label={lang.t("Girar 90° a la izquierda")}
+                        >{"⟲"}</button>
+                        <button
+                            onclick={link.callback(|_| ImageViewerMsg::Rotate(1))}
+                            title={lang.t("Girar 90° a la derecha (R)")}
+                            aria-label={lang.t("Girar 90° a la derecha")}
+                        >{"⟳"}</button>
+                        <button
+                            onclick={link.callback(|_| ImageViewerMsg::FlipHorizontal)}
+                            aria-pressed={self.orientation.flip_horizontal.to_string()}
+                            title={lang.t("Voltear horizontalmente (H)")}
+                            aria-label={lang.t("Voltear horizontalmente")}
+                        >{"⇋"}</button>
+                        <button
+                            onclick={link.callback(|_| ImageViewerMsg::FlipVertical)}
+                            aria-pressed={self.orientation.flip_vertical.to_string()}
+                            title={lang.t("Voltear verticalmente (V)")}
+                            aria-label={lang.t("Voltear verticalmente")}
+                        >{"⇵"}</button>
+                    </span>
+                    <button
+                        onclick={link.callback(|_| ImageViewerMsg::Open)}
+                        title={lang.t("Leer esta página (Intro)")}
+                    >{ lang.t("Abrir la transcripción") }</button>
+                    <button
+                        class="close-btn"
+                        onclick={link.callback(|_| ImageViewerMsg::Close)}
+                        title={lang.t("Salir de la galería (Esc)")}
+                    >{"×"}</button>
+                </div>
+                <div
+                    class={classes!("gallery-stage", (!self.pointers.is_empty()).then_some("dragging"))}
+                    ref={self.stage_ref.clone()}
+                    {onwheel}
+                    {onpointerdown}
+                    {onpointermove}
+                    {onpointerup}
+                    {onpointercancel}
+                >
+                    { match image {
+                        Some(image) => html! {
+                            <img
+                                key={image.src.clone()}
+                                src={image.src.clone()}
+                                alt={lang.tf("Página {}", &[&image.label])}
+                                style={transform}
+                                draggable="false"
+                            />
+                        },
+                        None => html! {
+                            <p class="gallery-empty">{ lang.t("Esta página no tiene imagen.") }</p>
+                        },
+                    } }
+                </div>
+            </section>
+        }
+    }
+}
@@ -0,0 +1,94 @@
+// src/components/index_locorum_panel.rs
+use crate::i18n::Lang;
+use crate::index_locorum::{self, Locus};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct IndexLocorumPanelProps {
+    pub loci: Vec<Locus>,
+    /// Project pages are still being scanned
+    #[prop_or_default]
+    pub loading: bool,
+    /// Go to a citing line: (page, line index)
+    pub on_open: Callback<(u32, usize)>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Sidebar listing every passage cited in the project, each with links
+/// to the lines that cite it.
+pub struct IndexLocorumPanel {
+    filter: String,
+}
+
+impl Component for IndexLocorumPanel {
+    type Message = String;
+    type Properties = IndexLocorumPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            filter: String::new(),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, filter: Self::Message) -> bool {
+        self.filter = filter;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            e.target_dyn_into::<HtmlInputElement>()
+                .map(|input| input.value())
+                .unwrap_or_default()
+        });
+        let loci = index_locorum::filter(&props.loci, &self.filter);
+        let lang = props.lang;
+
+        html! {
+            <div class="text-panel index-locorum-panel">
+                <h3>{"Index locorum"}</h3>
+                <input
+                    type="search"
+                    placeholder={lang.t("Filtrar pasajes...")}
+                    aria-label={lang.t("Filtrar pasajes citados")}
+                    value={self.filter.clone()}
+                    {oninput}
+                />
+                { if props.loading {
+                    html! { <p class="note-hint">{ lang.t("Buscando en las páginas del proyecto...") }</p> }
+                } else if props.loci.is_empty() {
+                    html! { <p class="note-hint">{ lang.t("El proyecto no contiene referencias (<ref>).") }</p> }
+                } else {
+                    html! {}
+                } }
+                <dl class="loci">
+                    { for loci.into_iter().map(|locus| html! {
+                        <>
+                            <dt title={(!locus.ref_type.is_empty()).then(|| lang.tf("Tipo: {}", &[&locus.ref_type]))}>
+                                { if locus.is_url() {
+                                    html! { <a href={locus.target.clone()} target="_blank" rel="noopener">{ locus.target.clone() }</a> }
+                                } else {
+                                    html! { locus.target.clone() }
+                                } }
+                            </dt>
+                            <dd>
+                                { for locus.citations.iter().map(|c| {
+                                    let target = (c.page, c.line);
+                                    let onclick = props.on_open.reform(move |_: MouseEvent| target);
+                                    html! {
+                                        <button {onclick} title={c.text.clone()}>
+                                            { format!("p. {}, l. {}", c.page, c.label) }
+                                        </button>
+                                    }
+                                }) }
+                            </dd>
+                        </>
+                    }) }
+                </dl>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,400 @@
+// src/components/line_view.rs
+use crate::i18n::Lang;
+use crate::settings::Reading;
+use crate::tei_data::*;
+use std::rc::Rc;
+use web_sys::Element;
+use yew::prelude::*;
+
+#[derive(Properties)]
+pub struct LineViewProps {
+    pub doc: Rc<TeiDocument>,
+    pub index: usize,
+    pub is_active: bool,
+    /// Line matches the current text search
+    #[prop_or_default]
+    pub is_match: bool,
+    /// Edition-style gutter: only every 5th number is visible
+    #[prop_or_default]
+    pub sparse_numbering: bool,
+    /// Colour and text of the reader's notes on this line, if any
+    #[prop_or_default]
+    pub note: Option<(AttrValue, AttrValue)>,
+    pub on_hover: Callback<String>,
+    pub on_leave: Callback<()>,
+    pub on_click: Callback<String>,
+    /// Click on a `<term>`: its glossary key, if any, and its text
+    #[prop_or_default]
+    pub on_term: Callback<(Option<String>, String)>,
+    /// Readings of `<choice>` to show
+    #[prop_or_default]
+    pub reading: Reading,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+// Documents are compared by pointer: a parsed document never changes, so a
+// line only re-renders when its highlight state (or the document) changes.
+impl PartialEq for LineViewProps {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.doc, &other.doc)
+            && self.index == other.index
+            && self.is_active == other.is_active
+            && self.is_match == other.is_match
+            && self.sparse_numbering == other.sparse_numbering
+            && self.note == other.note
+            && self.on_hover == other.on_hover
+            && self.on_leave == other.on_leave
+            && self.on_click == other.on_click
+            && self.on_term == other.on_term
+            && self.reading == other.reading
+            && self.lang == other.lang
+    }
+}
+
+/// A single transcription line. Kept as its own component so hovering the
+/// text only re-renders the lines whose highlight state changed.
+pub struct LineView;
+
+impl Component for LineView {
+    type Message = ();
+    type Properties = LineViewProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let reading = props.reading;
+        let Some(line) = props.doc.lines.get(props.index) else {
+            return html! {};
+        };
+        let zone_id = line.facs.clone();
+        let onmouseenter = {
+            let zid = zone_id.clone();
+            props.on_hover.reform(move |_: MouseEvent| zid.clone())
+        };
+        let onmouseleave = props.on_leave.reform(|_: MouseEvent| ());
+        let onclick = {
+            let on_click = props.on_click.clone();
+            let on_term = props.on_term.clone();
+            move |e: MouseEvent| {
+                let term = e
+                    .target_dyn_into::<Element>()
+                    .and_then(|t| t.closest(".gloss-term").ok().flatten());
+                match term {
+                    Some(term) => on_term.emit((
+                        term.get_attribute("data-term"),
+                        term.text_content().unwrap_or_default(),
+                    )),
+                    None => on_click.emit(zone_id.clone()),
+                }
+            }
+        };
+        let class = classes!(
+            "line",
+            props.is_active.then_some("active"),
+            props.is_match.then_some("search-match"),
+            props.note.is_some().then_some("has-note")
+        );
+
+        // Hidden numbers stay in the flow (and readable by screen readers)
+        let number = props.index + 1;
+        let number_class = classes!(
+            "line-number",
+            (props.sparse_numbering && !number.is_multiple_of(5)).then_some("line-number-muted")
+        );
+
+        html! {
+            <div {class} data-line={props.index.to_string()} {onmouseenter} {onmouseleave} {onclick}>
+                <span class={number_class} title={lang.tf("Línea {}", &[&number])}>{ number }</span>
+                { if let Some((color, text)) = &props.note {
+                    html! {
+                        <span class="note-marker" style={format!("background: {};", color)} title={text.clone()} aria-label={lang.t("Nota")} />
+                    }
+                } else {
+                    html! {}
+                } }
+                <span class="line-content">{ for line.content.iter().map(|n| render_text_node(lang, reading, n)) }</span>
+            </div>
+        }
+    }
+}
+
+pub fn render_text_node(lang: Lang, reading: Reading, node: &TextNode) -> Html {
+    match node {
+        TextNode::Text { content } => html! { <>{content}</> },
+        TextNode::Abbr { abbr, expan } if reading.expansions => html! {
+            <span title={lang.tf("[Abreviatura] Original: {}", &[&abbr])} class="abbreviation expanded" data-tooltip-type="abbr">{ expan }</span>
+        },
+        TextNode::Choice { sic, corr } if reading.corrections => html! {
+            <span class="correction corrected" title={lang.tf("[Corrección] Original: {}", &[&sic])}>{ corr }</span>
+        },
+        TextNode::Abbr { abbr, expan } => html! {
+            <abbr title={lang.tf("[Abreviatura] {}", &[&expan])} class="abbreviation" data-tooltip-type="abbr">{ abbr }</abbr>
+        },
+        TextNode::Choice { sic, corr } => html! {
+            <span class="correction" title={lang.tf("[Corrección] Lectura: {}", &[&corr])}>{ sic }</span>
+        },
+        TextNode::Regularised { orig, reg } => html! {
+            <span class="regularised" title={lang.tf("[Regularización] Regularizado: {}", &[&reg])}>{ orig }</span>
+        },
+        TextNode::Num { value, tipo, text } => html! {
+            <span class="number" title={lang.tf("[Número] Valor: {} | Tipo: {}", &[&value, &tipo])}>{ text }</span>
+        },
+        TextNode::PersName {
+            content,
+            tipo,
+            firstname,
+            continued,
+            ref_uri,
+        } => {
+            // Build a descriptive title from available attributes
+            let mut title_parts: Vec<String> = Vec::new();
+            if !tipo.is_empty() {
+                title_parts.push(lang.tf("[Persona] Tipo: {}", &[&tipo]));
+            } else {
+                title_parts.push(lang.t("[Persona]").to_string());
+            }
+            if let Some(fnme) = firstname {
+                title_parts.push(lang.tf("Nombre: {}", &[&fnme]));
+            }
+            if continued.unwrap_or(false) {
+                title_parts.push(lang.t("Continúa").to_string());
+            }
+            if let Some(r) = ref_uri {
+                title_parts.push(lang.tf("Ref: {}", &[&r]));
+            }
+
+            // Check for nested abbreviations and add their info to the combined title
+            for node in content {
+                if let TextNode::Abbr { abbr, expan } = node {
+                    title_parts.push(lang.tf("[Abreviatura] {}: {}", &[&abbr, &expan]));
+                }
+            }
+
+            let title = title_parts.join(" | ");
+
+            html! {
+                <span class="person-name" title={title} data-tooltip-type="person">
+                    { for content.iter().map(|n| render_text_node_no_abbr_tooltip(lang, reading, n)) }
+                </span>
+            }
+        }
+        TextNode::PlaceName {
+            name,
+            attrs,
+            ref_uri,
+        } => {
+            // Show only the visible place name inline. Ancillary attributes
+            // (e.g., country, region) are exposed via the element's title so
+            // they appear when hovering. This keeps the inline flow intact.
+            let mut title_parts: Vec<String> = Vec::new();
+            for (k, v) in attrs.iter() {
+                // Normalize key names for display (optional)
+                title_parts.push(format!("{}: {}", k, v));
+            }
+            if let Some(r) = ref_uri {
+                title_parts.push(lang.tf("Ref: {}", &[&r]));
+            }
+            let title = if title_parts.is_empty() {
+                lang.tf("[Lugar]: {}", &[&name])
+            } else {
+                format!("{} — {}", title_parts.join("; "), name)
+            };
+            html! {
+                <span class="place-name" title={title.clone()}>{ name }</span>
+            }
+        }
+        TextNode::Ref {
+            ref_type,
+            target,
+            content,
+        } => html! {
+            <span class="ref" title={lang.tf("[Referencia] Tipo: {} | Destino: {}", &[&ref_type, &target])}>{ content }</span>
+        },
+        TextNode::Unclear { reason, content } => html! {
+            <span class="unclear" title={lang.tf("[Incierto] Razón: {}", &[&reason])}>{ content }</span>
+        },
+        TextNode::RsType { rs_type, content } => html! {
+            <span class={format!("rs-type rs-{}", rs_type)} title={lang.tf("[Cadena de Referencia] Tipo: {}", &[&rs_type])}>{ content }</span>
+        },
+        TextNode::Term { key, content } => html! {
+            <span class="gloss-term" data-term={key.clone()} title={lang.t("[Glosario] Ver la definición")}>{ content }</span>
+        },
+        TextNode::Date { from, to, content } => html! {
+            <span class="date" title={date_title(lang, from.as_deref(), to.as_deref())}>{ content }</span>
+        },
+        TextNode::NoteRef { note_id, n } => html! {
+            <sup class="footnote-ref" title={lang.t("[Nota al pie]")}>
+                <a id={format!("ref_{}", note_id)} href={format!("#{}", note_id)}>{ n }</a>
+            </sup>
+        },
+        TextNode::InlineNote { content, n } => html! {
+            <sup class="footnote-ref" title={lang.tf("[Nota al pie] {}", &[&content])}>{ n }</sup>
+        },
+        TextNode::Hi { rend, content } => {
+            // Handle multiple rend values (e.g., "bold italic")
+            // Render nested nodes instead of a single string content.
+            // We rely on text nodes to carry their own leading/trailing space,
+            // so simply rendering nested nodes in order preserves spacing.
+            let classes = rend
+                .split_whitespace()
+                .map(|r| format!("hi-{}", r))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            // Only show titles for non-basic formatting to avoid clustering
+            // Basic formatting (bold, italic, underline) is visually obvious
+            let basic_formatting = ["bold", "italic", "underline", "superscript", "subscript"];
+            let show_title = !rend
+                .split_whitespace()
+                .all(|r| basic_formatting.contains(&r));
+
+            if show_title {
+                html! {
+                    <span class={classes} title={lang.tf("[Resaltado] Estilo: {}", &[&rend])}>
+                        { for content.iter().map(|n| render_text_node(lang, reading, n)) }
+                    </span>
+                }
+            } else {
+                html! {
+                    <span class={classes}>
+                        { for content.iter().map(|n| render_text_node(lang, reading, n)) }
+                    </span>
+                }
+            }
+        }
+    }
+}
+
+fn render_text_node_no_abbr_tooltip(lang: Lang, reading: Reading, node: &TextNode) -> Html {
+    match node {
+        TextNode::Text { content } => html! { <>{content}</> },
+        TextNode::Abbr { abbr: _, expan } if reading.expansions => html! {
+            <span class="abbreviation expanded">{ expan }</span>
+        },
+        TextNode::Choice { sic, corr } if reading.corrections => html! {
+            <span class="correction corrected" title={lang.tf("[Corrección] Original: {}", &[&sic])}>{ corr }</span>
+        },
+        TextNode::Abbr { abbr, expan: _ } => html! {
+            <abbr class="abbreviation">{ abbr }</abbr>
+        },
+        TextNode::Choice { sic, corr } => html! {
+            <span class="correction" title={lang.tf("[Corrección] Lectura: {}", &[&corr])}>{ sic }</span>
+        },
+        TextNode::Regularised { orig, reg } => html! {
+            <span class="regularised" title={lang.tf("[Regularización] Original: {}", &[&orig])}>{ reg }</span>
+        },
+        TextNode::Num { value, tipo, text } => html! {
+            <span class="number" title={lang.tf("[Número] Valor: {} | Tipo: {}", &[&value, &tipo])}>{ text }</span>
+        },
+        TextNode::PersName {
+            content,
+            tipo,
+            firstname,
+            continued,
+            ref_uri,
+        } => {
+            // Nested person names should use regular rendering
+            render_text_node(
+                lang,
+                reading,
+                &TextNode::PersName {
+                    content: content.clone(),
+                    tipo: tipo.clone(),
+                    firstname: firstname.clone(),
+                    continued: *continued,
+                    ref_uri: ref_uri.clone(),
+                },
+            )
+        }
+        TextNode::PlaceName {
+            name,
+            attrs,
+            ref_uri,
+        } => {
+            let mut title_parts: Vec<String> = Vec::new();
+            for (k, v) in attrs.iter() {
+                title_parts.push(format!("{}: {}", k, v));
+            }
+            if let Some(r) = ref_uri {
+                title_parts.push(lang.tf("Ref: {}", &[&r]));
+            }
+            let title = if title_parts.is_empty() {
+                lang.tf("[Lugar]: {}", &[&name])
+            } else {
+                format!("{} — {}", title_parts.join("; "), name)
+            };
+            html! {
+                <span class="place-name" title={title}>{ name }</span>
+            }
+        }
+        TextNode::Ref {
+            ref_type,
+            target,
+            content,
+        } => html! {
+            <span class="ref" title={lang.tf("[Referencia] Tipo: {} | Destino: {}", &[&ref_type, &target])}>{ content }</span>
+        },
+        TextNode::Unclear { reason, content } => html! {
+            <span class="unclear" title={lang.tf("[Incierto] Razón: {}", &[&reason])}>{ content }</span>
+        },
+        TextNode::RsType { rs_type, content } => html! {
+            <span class={format!("rs-type rs-{}", rs_type)} title={lang.tf("[Cadena de Referencia] Tipo: {}", &[&rs_type])}>{ content }</span>
+        },
+        TextNode::Term { key, content } => html! {
+            <span class="gloss-term" data-term={key.clone()} title={lang.t("[Glosario] Ver la definición")}>{ content }</span>
+        },
+        TextNode::Date { from, to, content } => html! {
+            <span class="date" title={date_title(lang, from.as_deref(), to.as_deref())}>{ content }</span>
+        },
+        TextNode::NoteRef { note_id, n } => html! {
+            <sup class="footnote-ref" title={lang.t("[Nota al pie]")}>
+                <a id={format!("ref_{}", note_id)} href={format!("#{}", note_id)}>{ n }</a>
+            </sup>
+        },
+        TextNode::InlineNote { content, n } => html! {
+            <sup class="footnote-ref" title={lang.tf("[Nota al pie] {}", &[&content])}>{ n }</sup>
+        },
+        TextNode::Hi { rend, content } => {
+            let classes = rend
+                .split_whitespace()
+                .map(|r| format!("hi-{}", r))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let basic_formatting = ["bold", "italic", "underline", "superscript", "subscript"];
+            let show_title = !rend
+                .split_whitespace()
+                .all(|r| basic_formatting.contains(&r));
+
+            if show_title {
+                html! {
+                    <span class={classes} title={lang.tf("[Resaltado] Estilo: {}", &[&rend])}>
+                        { for content.iter().map(|n| render_text_node_no_abbr_tooltip(lang, reading, n)) }
+                    </span>
+                }
+            } else {
+                html! {
+                    <span class={classes}>
+                        { for content.iter().map(|n| render_text_node_no_abbr_tooltip(lang, reading, n)) }
+                    </span>
+                }
+            }
+        }
+    }
+}
+
+/// Tooltip of a `<date>`: its normalized value or range.
+fn date_title(lang: Lang, from: Option<&str>, to: Option<&str>) -> String {
+    match (from, to) {
+        (Some(from), Some(to)) if from == to => lang.tf("[Fecha] {}", &[&from]),
+        (Some(from), Some(to)) => lang.tf("[Fecha] {} – {}", &[&from, &to]),
+        (Some(from), None) => lang.tf("[Fecha] Desde {}", &[&from]),
+        (None, Some(to)) => lang.tf("[Fecha] Hasta {}", &[&to]),
+        (None, None) => lang.t("[Fecha]").to_string(),
+    }
+}
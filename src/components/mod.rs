@@ -1,4 +1,24 @@
 // src/components/mod.rs
 // Central components module. Removed unused components and keep the
 // main `tei_viewer` module exported.
+pub mod dts_browser;
+pub mod entity_index_view;
+pub mod error_boundary;
+pub mod global_search;
+pub mod glossary_panel;
+pub mod help_dialog;
+pub mod image_viewer;
+pub mod index_locorum_panel;
+pub mod line_view;
+pub mod notes_panel;
+pub mod onboarding;
+pub mod page_grid;
+pub mod perf_overlay;
+pub mod place_map_panel;
+pub mod settings_dialog;
 pub mod tei_viewer;
+pub mod tile_layer;
+pub mod timeline_panel;
+pub mod toc_panel;
+pub mod word_frequency_panel;
+pub mod zone_editor_panel;
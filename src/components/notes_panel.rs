@@ -0,0 +1,231 @@
+// src/components/notes_panel.rs
+use crate::annotations::{Annotation, COLORS};
+use crate::i18n::Lang;
+use web_sys::{HtmlInputElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+/// A note as submitted by the panel: a new one (`id: None`) for the
+/// selected line, or an edit of an existing note.
+#[derive(Clone, PartialEq)]
+pub struct NoteDraft {
+    pub id: Option<String>,
+    pub text: String,
+    pub color: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct NotesPanelProps {
+    /// Notes of the current page, in line order
+    pub notes: Vec<Annotation>,
+    /// Line selected in the text (index), which a new note attaches to
+    pub selected_line: Option<usize>,
+    /// Text selected within that line
+    #[prop_or_default]
+    pub quote: Option<String>,
+    /// Result of the last import, shown under the list
+    #[prop_or_default]
+    pub status: Option<String>,
+    pub on_save: Callback<NoteDraft>,
+    pub on_delete: Callback<String>,
+    /// Scroll to and highlight the note's line
+    pub on_select: Callback<usize>,
+    pub on_export: Callback<()>,
+    /// Export as W3C Web Annotation JSON-LD
+    pub on_export_w3c: Callback<()>,
+    pub on_import: Callback<web_sys::File>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum NotesPanelMsg {
+    SetText(String),
+    SetColor(String),
+    Edit(Annotation),
+    Cancel,
+    Save,
+}
+
+/// Sidebar listing the reader's notes for the page, with a form to add or
+/// edit one.
+pub struct NotesPanel {
+    text: String,
+    color: String,
+    editing: Option<String>,
+}
+
+impl Component for NotesPanel {
+    type Message = NotesPanelMsg;
+    type Properties = NotesPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            text: String::new(),
+            color: COLORS[0].1.to_string(),
+            editing: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            NotesPanelMsg::SetText(text) => {
+                self.text = text;
+                false
+            }
+            NotesPanelMsg::SetColor(color) => {
+                self.color = color;
+                true
+            }
+            NotesPanelMsg::Edit(note) => {
+                self.text = note.text;
+                self.color = note.color;
+                self.editing = Some(note.id);
+                true
+            }
+            NotesPanelMsg::Cancel => {
+                self.text.clear();
+                self.editing = None;
+                true
+            }
+            NotesPanelMsg::Save => {
+                if self.text.trim().is_empty() {
+                    return false;
+                }
+                ctx.props().on_save.emit(NoteDraft {
+                    id: self.editing.take(),
+                    text: self.text.trim().to_string(),
+                    color: self.color.clone(),
+                });
+                self.text.clear();
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let value = e
+                .target_dyn_into::<HtmlTextAreaElement>()
+                .map(|t| t.value())
+                .unwrap_or_default();
+            NotesPanelMsg::SetText(value)
+        });
+        let onsubmit = ctx.link().callback(|e: SubmitEvent| {
+            e.prevent_default();
+            NotesPanelMsg::Save
+        });
+        let on_import = props.on_import.clone();
+        let onchange_file = Callback::from(move |e: Event| {
+            if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                    on_import.emit(file);
+                }
+                // Allow importing the same file again
+                input.set_value("");
+            }
+        });
+        let on_export = props.on_export.reform(|_: MouseEvent| ());
+        let on_export_w3c = props.on_export_w3c.reform(|_: MouseEvent| ());
+
+        let lang = props.lang;
+        let target = match (&self.editing, props.selected_line) {
+            (Some(_), _) => Some(lang.t("Editar nota").to_string()),
+            (None, Some(line)) => Some(lang.tf("Nueva nota · línea {}", &[&(line + 1)])),
+            (None, None) => None,
+        };
+
+        html! {
+            <div class="text-panel notes-panel">
+                <h3>{ lang.t("Mis notas") }</h3>
+                { if let Some(heading) = target {
+                    html! {
+                        <form class="note-form" {onsubmit}>
+                            <label for="note-text">{ heading }</label>
+                            { match (&self.editing, &props.quote) {
+                                (None, Some(quote)) => html! { <blockquote class="note-quote">{ quote.clone() }</blockquote> },
+                                _ => html! {},
+                            } }
+                            <textarea id="note-text" rows="3" value={self.text.clone()} {oninput} />
+                            <div class="note-colors" role="radiogroup" aria-label={lang.t("Color")}>
+                                { for COLORS.iter().map(|(label, color)| {
+                                    let selected = self.color == *color;
+                                    let onclick = ctx.link().callback(move |_| NotesPanelMsg::SetColor(color.to_string()));
+                                    html! {
+                                        <button
+                                            type="button"
+                                            class={classes!("note-swatch", selected.then_some("selected"))}
+                                            style={format!("background: {};", color)}
+                                            title={lang.t(label)}
+                                            role="radio"
+                                            aria-checked={selected.to_string()}
+                                            {onclick}
+                                        />
+                                    }
+                                }) }
+                            </div>
+                            <div class="note-actions">
+                                <button type="submit">{ lang.t("Guardar") }</button>
+                                { if self.editing.is_some() {
+                                    html! { <button type="button" onclick={ctx.link().callback(|_| NotesPanelMsg::Cancel)}>{ lang.t("Cancelar") }</button> }
+                                } else {
+                                    html! {}
+                                } }
+                            </div>
+                        </form>
+                    }
+                } else {
+                    html! { <p class="note-hint">{ lang.t("Haga clic en una línea (o seleccione un pasaje) para anotarla.") }</p> }
+                } }
+                <ul class="note-list">
+                    { for props.notes.iter().map(|note| {
+                        let line = note.line;
+                        let on_select = props.on_select.reform(move |_: MouseEvent| line);
+                        let on_edit = {
+                            let note = note.clone();
+                            ctx.link().callback(move |_| NotesPanelMsg::Edit(note.clone()))
+                        };
+                        let on_delete = {
+                            let id = note.id.clone();
+                            props.on_delete.reform(move |_: MouseEvent| id.clone())
+                        };
+                        html! {
+                            <li class="note-item" style={format!("border-left-color: {};", note.color)}>
+                                <button class="note-line" onclick={on_select} title={lang.t("Ir a la línea")}>
+                                    { lang.tf("Línea {}", &[&(note.line + 1)]) }
+                                </button>
+                                { if let Some(quote) = &note.quote {
+                                    html! { <blockquote class="note-quote">{ quote.clone() }</blockquote> }
+                                } else {
+                                    html! {}
+                                } }
+                                <p class="note-text">{ note.text.clone() }</p>
+                                <div class="note-actions">
+                                    <button onclick={on_edit}>{ lang.t("Editar") }</button>
+                                    <button onclick={on_delete}>{ lang.t("Eliminar") }</button>
+                                </div>
+                            </li>
+                        }
+                    }) }
+                </ul>
+                { if props.notes.is_empty() {
+                    html! { <p class="note-hint">{ lang.t("No hay notas en esta página.") }</p> }
+                } else {
+                    html! {}
+                } }
+                <div class="note-transfer">
+                    <button onclick={on_export} title={lang.t("Descargar todas las notas del proyecto")}>{ lang.t("⬇ Exportar") }</button>
+                    <button onclick={on_export_w3c} title={lang.t("Descargar las notas y la selección como W3C Web Annotation (Recogito, Mirador…)")}>{"⬇ Web Annotation"}</button>
+                    <label class="note-import" title={lang.t("Cargar notas exportadas (JSON)")}>
+                        { lang.t("⬆ Importar") }
+                        <input type="file" accept="application/json,.json" onchange={onchange_file} />
+                    </label>
+                </div>
+                { if let Some(status) = &props.status {
+                    html! { <p class="note-status">{ status.clone() }</p> }
+                } else {
+                    html! {}
+                } }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,200 @@
+// src/components/onboarding.rs
+use crate::i18n::Lang;
+use gloo::timers::callback::Interval;
+use yew::prelude::*;
+
+/// Room between a highlighted element and its ring
+const RING_PADDING: f64 = 6.0;
+/// The page keeps loading and can be scrolled or resized under the
+/// overlay, so the ring follows its element every this many milliseconds
+const REMEASURE_MS: u32 = 400;
+
+struct Step {
+    /// Element pointed out; the card is centred when it is not on screen
+    selector: &'static str,
+    title: &'static str,
+    text: &'static str,
+}
+
+const STEPS: &[Step] = &[
+    Step {
+        selector: ".image-panel",
+        title: "El facsímil",
+        text: "La imagen del manuscrito. Acérquela con la rueda del ratón o pellizcando, y arrástrela para desplazarla.",
+    },
+    Step {
+        selector: ".text-panels",
+        title: "Texto e imagen enlazados",
+        text: "Pase el ratón sobre una línea de la transcripción para resaltar su zona en el facsímil; un clic la deja fijada.",
+    },
+    Step {
+        selector: ".view-toggles",
+        title: "Vistas y paneles",
+        text: "Elija la edición diplomática, la traducción, ambas o sus diferencias, y abra las notas, el índice, el glosario y los demás paneles.",
+    },
+    Step {
+        selector: ".legend-toggle",
+        title: "Leyenda",
+        text: "Los colores del texto marcan abreviaturas, correcciones, nombres y otras intervenciones; la leyenda explica cada uno.",
+    },
+];
+
+#[derive(Properties, PartialEq)]
+pub struct OnboardingProps {
+    /// Finished or skipped
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum OnboardingMsg {
+    // Move to the previous (-1) or next (1) step
+    Step(i32),
+    Measure,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+struct Rect {
+    left: f64,
+    top: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Step-by-step overlay for a first visit: rings the image panel, the
+/// text panels, the view toggles and the legend in turn, with a card
+/// explaining each.
+pub struct Onboarding {
+    step: usize,
+    // Viewport box of the current step's element, if on screen
+    target: Option<Rect>,
+    _remeasure: Interval,
+}
+
+impl Component for Onboarding {
+    type Message = OnboardingMsg;
+    type Properties = OnboardingProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
+        Self {
+            step: 0,
+            target: None,
+            _remeasure: Interval::new(REMEASURE_MS, move || {
+                link.send_message(OnboardingMsg::Measure)
+            }),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            OnboardingMsg::Step(step) => {
+                let next = self.step as i32 + step;
+                if next >= STEPS.len() as i32 {
+                    ctx.props().on_close.emit(());
+                    return false;
+                }
+                self.step = next.max(0) as usize;
+                if let Some(el) = Self::element(self.step) {
+                    el.scroll_into_view();
+                }
+                true
+            }
+            OnboardingMsg::Measure => {
+                let target = self.measure();
+                let changed = target != self.target;
+                self.target = target;
+                changed
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if self.measure() != self.target {
+            ctx.link().send_message(OnboardingMsg::Measure);
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let step = &STEPS[self.step];
+        let last = self.step + 1 == STEPS.len();
+        let on_skip = ctx.props().on_close.reform(|_: MouseEvent| ());
+        let on_prev = ctx.link().callback(|_| OnboardingMsg::Step(-1));
+        let on_next = ctx.link().callback(|_| OnboardingMsg::Step(1));
+
+        let (ring, card_style) = match self.target {
+            Some(t) => {
+                let ring = html! {
+                    <div
+                        class="onboarding-ring"
+                        style={format!(
+                            "left: {}px; top: {}px; width: {}px; height: {}px;",
+                            t.left - RING_PADDING,
+                            t.top - RING_PADDING,
+                            t.width + 2.0 * RING_PADDING,
+                            t.height + 2.0 * RING_PADDING
+                        )}
+                    />
+                };
+                // Below the element when there is room, otherwise above it
+                let viewport = web_sys::window()
+                    .and_then(|w| w.inner_height().ok())
+                    .and_then(|h| h.as_f64())
+                    .unwrap_or(0.0);
+                let below = t.top + t.height + RING_PADDING;
+                let style = if below + 200.0 < viewport {
+                    format!("left: {}px; top: {}px;", t.left.max(8.0), below + 8.0)
+                } else {
+                    format!(
+                        "left: {}px; bottom: {}px;",
+                        t.left.max(8.0),
+                        (viewport - t.top + RING_PADDING + 8.0).max(8.0)
+                    )
+                };
+                (ring, style)
+            }
+            None => (html! {}, String::new()),
+        };
+
+        html! {
+            <div class={classes!("onboarding", self.target.is_none().then_some("centered"))}>
+                { ring }
+                <div class="onboarding-card" role="dialog" aria-labelledby="onboarding-title" style={card_style}>
+                    <span class="onboarding-count">{ lang.tf("{} de {}", &[&(self.step + 1), &STEPS.len()]) }</span>
+                    <h3 id="onboarding-title">{ lang.t(step.title) }</h3>
+                    <p>{ lang.t(step.text) }</p>
+                    <div class="onboarding-actions">
+                        <button class="onboarding-skip" onclick={on_skip}>{ lang.t("Saltar") }</button>
+                        <button onclick={on_prev} disabled={self.step == 0}>{ lang.t("← Anterior") }</button>
+                        <button class="onboarding-next" onclick={on_next}>
+                            { lang.t(if last { "Empezar" } else { "Siguiente →" }) }
+                        </button>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+impl Onboarding {
+    fn measure(&self) -> Option<Rect> {
+        Self::element(self.step).and_then(|el| {
+            let rect = el.get_bounding_client_rect();
+            (rect.width() > 0.0 && rect.height() > 0.0).then(|| Rect {
+                left: rect.left(),
+                top: rect.top(),
+                width: rect.width(),
+                height: rect.height(),
+            })
+        })
+    }
+
+    fn element(step: usize) -> Option<web_sys::Element> {
+        web_sys::window()?
+            .document()?
+            .query_selector(STEPS[step].selector)
+            .ok()
+            .flatten()
+    }
+}
@@ -0,0 +1,98 @@
+// src/components/page_grid.rs
+use crate::i18n::Lang;
+use crate::local_document;
+use crate::project_config::{group_pages, PageInfo, ProjectConfig};
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct PageGridProps {
+    /// Project whose pages are shown, under the headings of its sections
+    pub config: ProjectConfig,
+    pub current: u32,
+    pub on_select: Callback<u32>,
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Overlay with a thumbnail of every page of the project, in manifest
+/// order and under the headings of their sections; clicking one opens that
+/// page.
+pub struct PageGrid;
+
+impl Component for PageGrid {
+    type Message = ();
+    type Properties = PageGridProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let on_close = props.on_close.reform(|_: MouseEvent| ());
+        let lang = props.lang;
+
+        html! {
+            <div class="metadata-popup-overlay page-grid-overlay">
+                <div class="metadata-popup page-grid-popup" role="dialog" aria-labelledby="page-grid-title">
+                    <div class="metadata-popup-header">
+                        <h2 id="page-grid-title">{ lang.tf("Todas las páginas ({})", &[&props.config.pages.len()]) }</h2>
+                        <button class="close-btn" onclick={on_close} title={lang.t("Cerrar")}>{"×"}</button>
+                    </div>
+                    { for group_pages(&props.config.pages, &props.config.sections).into_iter().map(|(label, pages)| html! {
+                        <>
+                            { for label.map(|label| html! { <h3 class="page-grid-section">{ label }</h3> }) }
+                            <ul class="page-grid">
+                                { for pages.into_iter().map(|page| self.render_page(ctx, page)) }
+                            </ul>
+                        </>
+                    }) }
+                </div>
+            </div>
+        }
+    }
+}
+
+impl PageGrid {
+    fn render_page(&self, ctx: &Context<Self>, page: &PageInfo) -> Html {
+        let props = ctx.props();
+        let number = page.number;
+        let onclick = props.on_select.reform(move |_: MouseEvent| number);
+        let current = number == props.current;
+        let lang = props.lang;
+        let badge = |available: bool, label: &'static str, title: &'static str| {
+            let (label, title) = (lang.t(label), lang.t(title));
+            html! {
+                <span class={classes!("page-badge", (!available).then_some("missing"))} {title}>{ label }</span>
+            }
+        };
+
+        html! {
+            <li>
+                <button
+                    class={classes!("page-grid-item", current.then_some("active"))}
+                    {onclick}
+                    aria-current={current.then_some("page")}
+                >
+                    { match page.thumbnail_path() {
+                        Some(path) => html! {
+                            <img
+                                src={local_document::resolve(&props.config.file_url(&path))}
+                                alt={lang.tf("Página {}", &[&page.label])}
+                                loading="lazy"
+                            />
+                        },
+                        None => html! { <span class="page-grid-blank">{ lang.t("Sin imagen") }</span> },
+                    } }
+                    <span class="page-grid-label">{ page.label.clone() }</span>
+                    <span class="page-badges">
+                        { badge(page.has_diplomatic, "Dip.", "Edición diplomática") }
+                        { badge(page.has_translation, "Trad.", "Traducción") }
+                        { badge(page.has_image, "Img.", "Imagen") }
+                    </span>
+                </button>
+            </li>
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// src/components/perf_overlay.rs
+use crate::i18n::Lang;
+use crate::perf::{self, Entry};
+use gloo::timers::callback::Interval;
+use yew::prelude::*;
+
+/// How often the timings are read again
+const REFRESH_MS: u32 = 1000;
+
+#[derive(Properties, PartialEq)]
+pub struct PerfOverlayProps {
+    pub lang: Lang,
+}
+
+pub enum PerfOverlayMsg {
+    Refresh,
+    ToggleOpen,
+}
+
+/// Corner table of the timings `perf` records, newest first, for
+/// diagnosing slow projects (`?perf=1`).
+pub struct PerfOverlay {
+    entries: Vec<Entry>,
+    open: bool,
+    _refresh: Interval,
+}
+
+fn ms(value: Option<f64>) -> String {
+    value.map_or_else(String::new, |ms| format!("{:.0} ms", ms))
+}
+
+impl Component for PerfOverlay {
+    type Message = PerfOverlayMsg;
+    type Properties = PerfOverlayProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
+        Self {
+            entries: perf::entries(),
+            open: true,
+            _refresh: Interval::new(REFRESH_MS, move || {
+                link.send_message(PerfOverlayMsg::Refresh)
+            }),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            PerfOverlayMsg::Refresh => {
+                let entries = perf::entries();
+                let changed = entries != self.entries;
+                self.entries = entries;
+                changed && self.open
+            }
+            PerfOverlayMsg::ToggleOpen => {
+                self.open = !self.open;
+                true
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let (hits, fetched) = perf::hits(&self.entries);
+        let on_toggle = ctx.link().callback(|_| PerfOverlayMsg::ToggleOpen);
+        html! {
+            <aside class="perf-overlay" aria-label={lang.t("Rendimiento")}>
+                <button class="perf-title" onclick={on_toggle} aria-expanded={self.open.to_string()}>
+                    { lang.t("Rendimiento") }
+                    <span class="perf-hits">
+                        { lang.tf("Caché: {} de {}", &[&hits, &fetched]) }
+                    </span>
+                </button>
+                { if self.open {
+                    html! {
+                        <table>
+                            <thead>
+                                <tr>
+                                    <th>{ lang.t("Recurso") }</th>
+                                    <th>{ lang.t("Descarga") }</th>
+                                    <th>{ lang.t("Origen") }</th>
+                                    <th>{ lang.t("Análisis") }</th>
+                                    <th>{ lang.t("Renderizado") }</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                                { for self.entries.iter().rev().map(|entry| {
+                                    let name = entry.resource.rsplit('/').next().unwrap_or_default();
+                                    let class = match entry.source {
+                                        Some(source) if source.is_hit() => "hit",
+                                        Some(_) => "miss",
+                                        None => "",
+                                    };
+                                    html! {
+                                        <tr class={class}>
+                                            <td title={entry.resource.clone()}>{ name }</td>
+                                            <td>{ ms(entry.fetch_ms) }</td>
+                                            <td>{ entry.source.map(|s| lang.t(s.label())).unwrap_or_default() }</td>
+                                            <td>{ ms(entry.parse_ms) }</td>
+                                            <td>{ ms(entry.render_ms) }</td>
+                                        </tr>
+                                    }
+                                }) }
+                            </tbody>
+                        </table>
+                    }
+                } else {
+                    html! {}
+                } }
+            </aside>
+        }
+    }
+}
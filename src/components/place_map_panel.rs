@@ -0,0 +1,150 @@
+// src/components/place_map_panel.rs
+use crate::i18n::Lang;
+use crate::places::{MapPlace, MapView, TILE_SIZE};
+use yew::prelude::*;
+
+const MAP_WIDTH: f64 = 320.0;
+const MAP_HEIGHT: f64 = 240.0;
+/// Room around the outermost markers
+const MAP_PADDING: f64 = 24.0;
+
+#[derive(Properties, PartialEq)]
+pub struct PlaceMapPanelProps {
+    pub places: Vec<MapPlace>,
+    /// Place names found without coordinates
+    #[prop_or_default]
+    pub unlocated: Vec<String>,
+    /// Project pages are still being scanned
+    #[prop_or_default]
+    pub loading: bool,
+    /// Go to an attesting line: (page, line index)
+    pub on_open: Callback<(u32, usize)>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Sidebar with a map of the places named in the project; clicking a
+/// marker lists the lines that name it.
+pub struct PlaceMapPanel {
+    // Name of the selected place, which keeps it selected while pages are
+    // still being scanned
+    selected: Option<String>,
+}
+
+impl Component for PlaceMapPanel {
+    /// Select the place with this name
+    type Message = String;
+    type Properties = PlaceMapPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self { selected: None }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, name: Self::Message) -> bool {
+        self.selected = Some(name);
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+
+        html! {
+            <div class="text-panel place-map-panel">
+                <h3>{ lang.t("Mapa de lugares") }</h3>
+                { if props.loading {
+                    html! { <p class="note-hint">{ lang.t("Buscando en las páginas del proyecto...") }</p> }
+                } else if props.places.is_empty() {
+                    html! { <p class="note-hint">{ lang.t("Ningún <placeName> tiene coordenadas: añada <geo> o un places.json al proyecto.") }</p> }
+                } else {
+                    html! {}
+                } }
+                { if props.places.is_empty() { html! {} } else { self.render_map(ctx) } }
+                { self.render_selected(ctx) }
+                { if props.unlocated.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <p class="note-hint" title={props.unlocated.join(", ")}>
+                            { lang.tf("{} lugares sin coordenadas", &[&props.unlocated.len()]) }
+                        </p>
+                    }
+                } }
+            </div>
+        }
+    }
+}
+
+impl PlaceMapPanel {
+    fn render_map(&self, ctx: &Context<Self>) -> Html {
+        let places = &ctx.props().places;
+        let view = MapView::fit(places, MAP_WIDTH, MAP_HEIGHT, MAP_PADDING);
+        let lang = ctx.props().lang;
+
+        html! {
+            <div class="place-map">
+                <svg
+                    viewBox={format!("0 0 {} {}", MAP_WIDTH, MAP_HEIGHT)}
+                    role="img"
+                    aria-label={lang.t("Mapa de los lugares del proyecto")}
+                >
+                    { for view.tiles().into_iter().map(|(tx, ty, x, y)| html! {
+                        <image
+                            href={format!("https://tile.openstreetmap.org/{}/{}/{}.png", view.zoom, tx, ty)}
+                            x={x.to_string()}
+                            y={y.to_string()}
+                            width={TILE_SIZE.to_string()}
+                            height={TILE_SIZE.to_string()}
+                        />
+                    }) }
+                    { for places.iter().map(|place| {
+                        let (x, y) = view.project(place.lat, place.lon);
+                        let selected = self.selected.as_ref() == Some(&place.name);
+                        let name = place.name.clone();
+                        html! {
+                            <circle
+                                class={classes!("place-marker", selected.then_some("active"))}
+                                cx={x.to_string()}
+                                cy={y.to_string()}
+                                r={if selected { "7" } else { "5" }}
+                                onclick={ctx.link().callback(move |_| name.clone())}
+                            >
+                                <title>{ format!("{} ({})", place.name, place.attestations.len()) }</title>
+                            </circle>
+                        }
+                    }) }
+                </svg>
+                <span class="place-map-attribution">
+                    {"© "}
+                    <a href="https://www.openstreetmap.org/copyright" target="_blank" rel="noopener">{"OpenStreetMap"}</a>
+                </span>
+            </div>
+        }
+    }
+
+    fn render_selected(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let Some(place) = self
+            .selected
+            .as_ref()
+            .and_then(|name| props.places.iter().find(|p| p.name == *name))
+        else {
+            return html! {};
+        };
+
+        html! {
+            <div class="place-attestations">
+                <h4>{ place.name.clone() }</h4>
+                <div>
+                    { for place.attestations.iter().map(|a| {
+                        let target = (a.page, a.line);
+                        let onclick = props.on_open.reform(move |_: MouseEvent| target);
+                        html! {
+                            <button {onclick}>{ format!("p. {}, l. {}", a.page, a.label) }</button>
+                        }
+                    }) }
+                </div>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,218 @@
+// src/components/settings_dialog.rs
+use crate::i18n::Lang;
+use crate::settings::{
+    Font, Layout, OverlayColor, Settings, MAX_TEXT_SCALE, MIN_TEXT_SCALE, TEXT_SCALE_STEP,
+};
+use crate::theme::Theme;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct SettingsDialogProps {
+    pub theme: Theme,
+    pub settings: Settings,
+    pub on_theme: Callback<Theme>,
+    pub on_lang: Callback<Lang>,
+    /// The settings with one option changed
+    pub on_change: Callback<Settings>,
+    pub on_close: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Dialog gathering the display options: theme, typeface, text size,
+/// overlay colour, readings shown, panel layout and interface language.
+pub struct SettingsDialog;
+
+impl Component for SettingsDialog {
+    type Message = ();
+    type Properties = SettingsDialogProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let settings = &props.settings;
+        let on_close = props.on_close.reform(|_: MouseEvent| ());
+
+        let on_font = change(props, |s, e: Event| {
+            let value = select_value(&e);
+            if let Some(font) = Font::ALL.into_iter().find(|f| f.as_str() == value) {
+                s.font = font;
+            }
+        });
+        let on_text_scale = change(props, |s, e: InputEvent| {
+            if let Some(scale) = e
+                .target_dyn_into::<web_sys::HtmlInputElement>()
+                .and_then(|input| input.value().parse().ok())
+            {
+                s.text_scale = scale;
+            }
+        });
+        let on_lang = props
+            .on_lang
+            .reform(|e: Event| Lang::parse(&select_value(&e)).unwrap_or_default());
+
+        html! {
+            <div class="metadata-popup-overlay settings-overlay">
+                <div class="metadata-popup settings-popup" role="dialog" aria-labelledby="settings-title">
+                    <div class="metadata-popup-header">
+                        <h2 id="settings-title">{ lang.t("Ajustes") }</h2>
+                        <button class="close-btn" onclick={on_close} title={lang.t("Cerrar")}>{"×"}</button>
+                    </div>
+                    <div class="metadata-popup-content settings-content">
+                        <fieldset>
+                            <legend>{ lang.t("Apariencia") }</legend>
+                            <div class="settings-row">
+                                <span class="settings-label">{ lang.t("Tema") }</span>
+                                <div class="settings-choices" role="radiogroup" aria-label={lang.t("Tema")}>
+                                    { for Theme::ALL.into_iter().map(|theme| {
+                                        let selected = theme == props.theme;
+                                        html! {
+                                            <button
+                                                role="radio"
+                                                class={classes!(selected.then_some("active"))}
+                                                aria-checked={selected.to_string()}
+                                                onclick={props.on_theme.reform(move |_: MouseEvent| theme)}
+                                            >
+                                                { lang.t(theme.label()) }
+                                            </button>
+                                        }
+                                    }) }
+                                </div>
+                            </div>
+                            <div class="settings-row">
+                                <label class="settings-label" for="settings-font">{ lang.t("Tipografía") }</label>
+                                <select id="settings-font" onchange={on_font}>
+                                    { for Font::ALL.into_iter().map(|font| html! {
+                                        <option value={font.as_str()} selected={font == settings.font}>{ font.label() }</option>
+                                    }) }
+                                </select>
+                            </div>
+                            <div class="settings-row">
+                                <label class="settings-label" for="settings-text-scale">{ lang.t("Tamaño del texto") }</label>
+                                <input
+                                    id="settings-text-scale"
+                                    type="range"
+                                    min={MIN_TEXT_SCALE.to_string()}
+                                    max={MAX_TEXT_SCALE.to_string()}
+                                    step={TEXT_SCALE_STEP.to_string()}
+                                    value={settings.text_scale.to_string()}
+                                    oninput={on_text_scale}
+                                />
+                                <span class="settings-value">{ format!("{} %", settings.text_scale) }</span>
+                            </div>
+                            <div class="settings-row">
+                                <span class="settings-label">{ lang.t("Color de resaltado") }</span>
+                                <div class="settings-choices" role="radiogroup" aria-label={lang.t("Color de resaltado")}>
+                                    { for OverlayColor::ALL.into_iter().map(|overlay| {
+                                        let selected = overlay == settings.overlay;
+                                        let onclick = change(props, move |s, _: MouseEvent| s.overlay = overlay);
+                                        html! {
+                                            <button
+                                                role="radio"
+                                                class={classes!("overlay-swatch", format!("overlay-{}", overlay.as_str()), selected.then_some("active"))}
+                                                aria-checked={selected.to_string()}
+                                                title={lang.t(overlay.label())}
+                                                {onclick}
+                                            >
+                                                { if overlay == OverlayColor::Theme { lang.t(overlay.label()) } else { "" } }
+                                            </button>
+                                        }
+                                    }) }
+                                </div>
+                            </div>
+                        </fieldset>
+                        <fieldset>
+                            <legend>{ lang.t("Texto") }</legend>
+                            { checkbox(
+                                lang.t("Mostrar las abreviaturas desarrolladas (<expan>)"),
+                                settings.expansions,
+                                change(props, |s, checked: bool| s.expansions = checked),
+                            ) }
+                            { checkbox(
+                                lang.t("Mostrar las correcciones (<corr>) en lugar de los errores (<sic>)"),
+                                settings.corrections,
+                                change(props, |s, checked: bool| s.corrections = checked),
+                            ) }
+                            { checkbox(
+                                lang.t("Numerar solo cada 5 líneas, como en las ediciones impresas"),
+                                settings.sparse_line_numbers,
+                                change(props, |s, checked: bool| s.sparse_line_numbers = checked),
+                            ) }
+                        </fieldset>
+                        <fieldset>
+                            <legend>{ lang.t("Red") }</legend>
+                            { checkbox(
+                                lang.t("Cargar por adelantado la imagen de la página siguiente"),
+                                settings.prefetch_next,
+                                change(props, |s, checked: bool| s.prefetch_next = checked),
+                            ) }
+                        </fieldset>
+                        <fieldset>
+                            <legend>{ lang.t("Disposición") }</legend>
+                            <div class="settings-choices" role="radiogroup" aria-label={lang.t("Disposición de los paneles")}>
+                                { for [(Layout::SideBySide, "⬌ Lado a lado"), (Layout::Stacked, "⬍ Apilados")].into_iter().map(|(layout, label)| {
+                                    let selected = layout == settings.layout;
+                                    let onclick = change(props, move |s, _: MouseEvent| s.layout = layout);
+                                    html! {
+                                        <button
+                                            role="radio"
+                                            class={classes!(selected.then_some("active"))}
+                                            aria-checked={selected.to_string()}
+                                            {onclick}
+                                        >
+                                            { lang.t(label) }
+                                        </button>
+                                    }
+                                }) }
+                            </div>
+                        </fieldset>
+                        <fieldset>
+                            <legend>{ lang.t("Idioma de la interfaz") }</legend>
+                            <select aria-label={lang.t("Idioma de la interfaz")} onchange={on_lang}>
+                                { for Lang::ALL.into_iter().map(|l| html! {
+                                    <option value={l.as_str()} selected={l == lang}>{ l.name() }</option>
+                                }) }
+                            </select>
+                        </fieldset>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+}
+
+/// Callback emitting the current settings with `edit` applied
+fn change<E: 'static>(
+    props: &SettingsDialogProps,
+    edit: impl Fn(&mut Settings, E) + 'static,
+) -> Callback<E> {
+    let settings = props.settings.clone();
+    props.on_change.reform(move |e: E| {
+        let mut settings = settings.clone();
+        edit(&mut settings, e);
+        settings
+    })
+}
+
+fn checkbox(label: &str, checked: bool, onchange: Callback<bool>) -> Html {
+    let onchange = onchange.reform(|e: Event| {
+        e.target_dyn_into::<web_sys::HtmlInputElement>()
+            .is_some_and(|input| input.checked())
+    });
+    html! {
+        <label class="settings-check">
+            <input type="checkbox" {checked} {onchange} />
+            { label.to_string() }
+        </label>
+    }
+}
+
+fn select_value(e: &Event) -> String {
+    e.target_dyn_into::<web_sys::HtmlSelectElement>()
+        .map(|select| select.value())
+        .unwrap_or_default()
+}
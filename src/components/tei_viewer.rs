@@ -1,29 +1,220 @@
 // src/components/tei_viewer.rs
+use crate::annotations::{Annotation, AnnotationSet};
+use crate::bibliography;
+use crate::bookmarks::{Bookmark, Bookmarks};
+use crate::commentary;
+use crate::components::error_boundary::ErrorSink;
+use crate::components::glossary_panel::{GlossaryPanel, OccurrenceLink};
+use crate::components::index_locorum_panel::IndexLocorumPanel;
+use crate::components::line_view::LineView;
+use crate::components::notes_panel::{NoteDraft, NotesPanel};
+use crate::components::place_map_panel::PlaceMapPanel;
+use crate::components::tile_layer::TileLayer;
+use crate::components::timeline_panel::TimelinePanel;
+use crate::components::toc_panel::TocPanel;
+use crate::components::word_frequency_panel::WordFrequencyPanel;
+use crate::components::zone_editor_panel::ZoneEditorPanel;
+use crate::deep_zoom::{DziInfo, IiifInfo, TileSource};
+use crate::diagnostics;
+use crate::doc_cache::{CacheKey, DocumentCache};
+use crate::doc_diff::{diff_documents, diff_readings, DiffOp, LineDiff};
+use crate::embed::Panels;
+use crate::feedback::{self, Report};
+use crate::gesture::{scaled_about, Gesture, Pointers};
+use crate::glossary::{self, GlossEntry};
+use crate::i18n::Lang;
+use crate::index_locorum;
+use crate::legend::{self, LegendItem};
+use crate::local_document;
+use crate::net::{self, FetchError};
+use crate::offline;
+use crate::perf;
+use crate::persistence::{self, PageView};
+use crate::places::{self, GazetteerEntry};
+use crate::polytonic;
+use crate::project_config::{
+    BiblEntry, EditionInfo, Fit, LegendEntry, OutlineEntry, PageBibliography, PageImage, PageInfo,
+    ProjectConfig, ProjectMetadata, ViewDefaults,
+};
+use crate::remote_project;
+use crate::route::Route;
+use crate::settings::{Layout, Settings};
 use crate::tei_data::*;
-use crate::utils::resource_url;
-use gloo_net::http::Request;
+use crate::tei_source;
+use crate::timeline;
+use crate::toc;
+use crate::tour::TourStop;
+use crate::url_state::{self, UrlState};
+use crate::utils::{
+    absolute_url, download, fit_image, fit_rect, pixels_per_cm, scale_bar_cm, swipe_step,
+    visible_range, Url,
+};
+use crate::web_annotation;
+use crate::window_sync::{self, Position, WindowSync};
+use crate::word_frequency;
+use crate::zone_editor::{Grab, ZoneEditor};
+use futures::future::{self, FutureExt, LocalBoxFuture};
+use gloo::file::{callbacks::FileReader, Blob, ObjectUrl};
+use gloo::render::{request_animation_frame, AnimationFrame};
+use gloo_events::EventListener;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::spawn_local;
-use web_sys::{Event, HtmlImageElement, MouseEvent, PointerEvent, WheelEvent};
+use web_sys::{
+    Event, HtmlImageElement, HtmlInputElement, HtmlMediaElement, HtmlTextAreaElement,
+    KeyboardEvent, MouseEvent, PointerEvent, WheelEvent,
+};
 use yew::{prelude::*, AttrValue};
+use yew_router::prelude::*;
+
+// Image panel width (percent of the viewer) and its allowed range
+const DEFAULT_PANEL_WIDTH: f64 = 45.0;
+const MIN_PANEL_WIDTH: f64 = 20.0;
+const MAX_PANEL_WIDTH: f64 = 80.0;
+
+// Viewport width (px) at or below which panels are shown as tabs; keep in
+// sync with the mobile breakpoint in styles.css
+const MOBILE_BREAKPOINT: f64 = 768.0;
+// Minimum horizontal travel (px) for a touch gesture to switch tabs
+const SWIPE_MIN_DISTANCE: i32 = 60;
+
+// Continuous scroll loads the next page when the reader gets this close
+// (px) to the bottom of what is loaded
+const CONTINUOUS_PRELOAD_MARGIN: f64 = 800.0;
+
+// The scale bar switches from 1 cm to 5 cm below this on-screen length (px)
+const SCALE_BAR_MIN_PX: f64 = 40.0;
+
+// Transcriptions longer than this are rendered through a scroll window
+const VIRTUALIZE_THRESHOLD: usize = 200;
+// Estimated rendered height of one `.line` (font, padding and margin)
+const LINE_HEIGHT_ESTIMATE: f64 = 48.0;
+// Lines rendered above and below the visible window
+const LINE_OVERSCAN: usize = 20;
+
+/// What a request for the page on screen fetches: a request reissued
+/// replaces, and so aborts, the one before
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum PageLoad {
+    /// The page in an edition ("dip", "trad", "edition-…")
+    Text(String),
+    Commentary,
+    Bibliography,
+    /// IIIF `info.json` or Deep Zoom descriptor of the facsimile
+    TileSource,
+    /// The XML shown in the source editor
+    Source,
+    /// The next page of the continuous scroll
+    Continuous,
+}
+
+/// Another project's transcription of the same folio, aligned by page label
+#[derive(Clone, PartialEq)]
+pub struct Comparison {
+    /// The other project's manifest
+    pub config: ProjectConfig,
+    /// Matching page there, if any page carries the same label
+    pub page: Option<u32>,
+}
 
 #[derive(Properties, PartialEq)]
 pub struct TeiViewerProps {
     pub project: String,
     pub page: u32,
+    /// The project's manifest, through which the files of its pages are
+    /// resolved
+    pub config: ProjectConfig,
+    /// Manifest entry for the current page, when the project declares one
+    #[prop_or_default]
+    pub page_info: Option<PageInfo>,
+    /// Asks the parent to move `step` pages back (-1) or forward (1)
+    #[prop_or_default]
+    pub on_turn_page: Callback<i32>,
+    /// Page numbers of the project in manifest order (continuous scroll)
+    #[prop_or_default]
+    pub pages: Vec<u32>,
+    /// Presentation mode: hide the controls and legend
+    #[prop_or_default]
+    pub kiosk: bool,
+    /// Zone or line to bring into view once the page has loaded: the
+    /// current guided tour stop, or an opened bookmark
+    #[prop_or_default]
+    pub focus: Option<TourStop>,
+    /// The reader's bookmarks, to show whether this page or line is starred
+    #[prop_or_default]
+    pub bookmarks: Bookmarks,
+    #[prop_or_default]
+    pub on_toggle_bookmark: Callback<Bookmark>,
+    /// Open a line on another page of the project (glossary occurrences,
+    /// index locorum, table of contents)
+    #[prop_or_default]
+    pub on_open_line: Callback<TourStop>,
+    /// Editions of the project besides the diplomatic one and the translation
+    #[prop_or_default]
+    pub editions: Vec<EditionInfo>,
+    /// The project's colors and labels for the legend
+    #[prop_or_default]
+    pub legend: Vec<LegendEntry>,
+    /// How the project is first shown: view, panel split, zoom, commentary
+    #[prop_or_default]
+    pub default_view: ViewDefaults,
+    /// Another window of the viewer moved to this project and line
+    #[prop_or_default]
+    pub on_follow: Callback<(String, TourStop)>,
+    /// Table of contents declared in the manifest; without one the panel
+    /// lists the `<div>` sections of the pages
+    #[prop_or_default]
+    pub outline: Vec<OutlineEntry>,
+    /// Free-text dating of the project from the manifest, the first row of
+    /// the timeline
+    #[prop_or_default]
+    pub date_range: String,
+    /// Name and metadata of the project, for its license and citation
+    #[prop_or_default]
+    pub project_name: String,
+    #[prop_or_default]
+    pub project_metadata: ProjectMetadata,
+    /// Cross-project comparison replacing the text panels
+    #[prop_or_default]
+    pub compare: Option<Comparison>,
+    #[prop_or_default]
+    pub lang: Lang,
+    /// Display options of the settings dialog
+    #[prop_or_default]
+    pub settings: Settings,
+    /// Bumped whenever files are opened from the reader's computer: the
+    /// page reloads if it is one of theirs
+    #[prop_or_default]
+    pub local_revision: u32,
+    /// Panels on screen: an embedding may ask for the image or the text only
+    #[prop_or_default]
+    pub panels: Panels,
 }
 
 pub enum TeiViewerMsg {
-    LoadDiplomatic(String),
-    LoadTranslation(String),
-    LoadCommentary(String),
-    DiplomaticLoaded(Result<TeiDocument, String>),
-    TranslationLoaded(Result<TeiDocument, String>),
-    CommentaryLoaded(Result<String, String>),
+    // The page's diplomatic edition, translation and commentary
+    LoadPage,
+    // The page's bibliography, from the manifest or its own file
+    LoadBibliography,
+    // Loaded messages carry the load generation they were started in, so
+    // responses that arrive after a page/project change can be discarded.
+    PageLoaded(u32, Box<PageData>),
+    // The translation alone, in another language
+    TranslationLoaded(u32, Result<TeiDocument, String>),
+    EditionLoaded(u32, String, Result<TeiDocument, String>),
+    BibliographyLoaded(u32, Result<Vec<BiblEntry>, String>),
+    LoadIiifInfo(String),
+    LoadDzi(String),
+    TileSourceLoaded(u32, Result<TileSource, String>),
+    ViewportMeasured(f64, f64),
+    // Scroll position (scroll_top, client_height) of a text panel
+    TextScrolled(ViewType, f64, f64),
     HoverLine(String),
     ClickLine(String),
     ClearHover,
     ToggleView(ViewType),
+    SetTranslationLang(String),
     ToggleCommentary,
     UpdateImageScale(f64),
     StartDrag(MouseEvent),
@@ -33,16 +224,136 @@ pub enum TeiViewerMsg {
     ToggleMetadataDip,
     ToggleMetadataTrad,
     ToggleLegend,
-    ImageLoaded(Event),
     ImageLoadedWithDimensions(u32, u32),
+    /// Fit the facsimile to its panel, once both are measured
+    FitImage(Fit),
+    ImageFailed,
+    /// Show the facsimile (0) or one of the page's further images
+    ShowImage(usize),
     StartSplitterDrag(MouseEvent),
     SplitterDrag(MouseEvent),
     EndSplitterDrag,
+    // Image panel share (percent) along the current layout's axis
+    SetPanelSize(f64),
+    ToggleRuler,
+    ToggleScaleBar,
+    ToggleHeatmap,
+    // Click on the facsimile at (client_x, client_y)
+    ImageClicked(i32, i32),
+    // Window resized; re-evaluate the mobile breakpoint
+    WindowResized,
+    SelectTab(MobileTab),
+    SwipeStart(i32, i32),
+    SwipeEnd(i32, i32),
+    ToggleContinuous,
+    // Continuous panel scrolled: (scroll_top, client_height, scroll_height)
+    // and the page section at the middle of the panel
+    ContinuousScrolled(f64, f64, f64, Option<u32>),
+    ContinuousPageLoaded(u32, u32, Result<TeiDocument, String>),
+    // Line reference typed in the "Ir a línea" box (source @n or position)
+    JumpToLine(String),
+    // Zoom to the zone or line of the pending tour stop
+    ApplyFocus,
+    // Playback position (seconds) of the page's audio reading
+    AudioTime(f64),
+    // Back/forward moved to another history entry
+    HistoryMoved,
+    PopOutText,
+    Synced(Position),
+    // reader notes
+    ToggleNotes,
+    ToggleZoneEditor,
+    /// Press on an edited zone (index), or on one of its vertices
+    ZoneEditPress(usize, Option<usize>, i32, i32),
+    ZoneEditRemovePoint(usize, usize),
+    ZoneEditSelect(usize),
+    ZoneEditAdd,
+    ZoneEditRemove(usize),
+    ZoneEditLink((usize, Option<usize>)),
+    ExportZones,
+    ToggleSourceEditor,
+    ToggleFeedback,
+    SetFeedbackComment(String),
+    SourceLoaded(u32, Result<String, String>),
+    EditSource(String),
+    ExportSource,
+    ToggleToc,
+    // Go to where a table of contents entry or a timeline event starts:
+    // (page, line reference)
+    OpenTocEntry((u32, Option<String>)),
+    ToggleGlossary,
+    // project glossary.xml of (project)
+    GlossaryLoaded(String, Result<String, String>),
+    ToggleIndexLocorum,
+    ToggleMap,
+    // project places.json of (project)
+    PlacesLoaded(String, Result<String, String>),
+    ToggleTimeline,
+    ToggleWordFrequency,
+    // word frequencies of the whole project (true) or the page
+    SetWordFrequencyScope(bool),
+    // diplomatic edition of a project page scanned for the project-wide
+    // panels (glossary, index locorum, word frequencies)
+    ProjectPageLoaded(String, u32, Result<TeiDocument, String>),
+    // clicked <term>: glossary key and text
+    SelectTerm((Option<String>, String)),
+    SelectGlossEntry(usize),
+    // go to (page, line index)
+    OpenProjectLine((u32, usize)),
+    SaveNote(NoteDraft),
+    DeleteNote(String),
+    // Scroll to and lock a diplomatic line (index)
+    FocusLine(usize),
+    ExportNotes,
+    ExportWebAnnotations,
+    ImportNotes(web_sys::File),
+    NotesImported(Result<String, String>),
+    // Diplomatic edition of the compared project's (project, page)
+    ComparisonLoaded(String, u32, Result<TeiDocument, String>),
+    SetSearchQuery(String),
+    ToggleSearchDiacritics,
+
+    // Write the current pan/zoom straight to the DOM (animation frame)
+    ApplyTransform,
 
     PointerDown(i32, i32, i32),
     PointerMove(i32, i32, i32),
-    PointerUp(i32, i32, i32),
-    PointerLeave(i32, i32, i32),
+    PointerUp(i32),
+    PointerLeave(i32),
+}
+
+/// Panel shown by the tabbed interface on narrow screens
+#[derive(Clone, Copy, PartialEq)]
+pub enum MobileTab {
+    Image,
+    Diplomatic,
+    Translation,
+    Commentary,
+}
+
+impl MobileTab {
+    const ALL: [MobileTab; 4] = [
+        MobileTab::Image,
+        MobileTab::Diplomatic,
+        MobileTab::Translation,
+        MobileTab::Commentary,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MobileTab::Image => "Imagen",
+            MobileTab::Diplomatic => "Diplomática",
+            MobileTab::Translation => "Traducción",
+            MobileTab::Commentary => "Comentario",
+        }
+    }
+
+    /// Neighbouring tab `step` positions away, stopping at either end
+    fn offset(self, step: i32) -> MobileTab {
+        let pos = Self::ALL.iter().position(|t| *t == self).unwrap_or(0) as i32;
+        let next = (pos + step).clamp(0, Self::ALL.len() as i32 - 1);
+        Self::ALL[next as usize]
+    }
 }
 
 #[derive(Clone, PartialEq)]
@@ -50,19 +361,101 @@ pub enum ViewType {
     Diplomatic,
     Translation,
     Both,
-    Commentary,
+    /// Diplomatic vs normalized reading of the diplomatic edition
+    Diff,
+    /// A further edition declared in the manifest, by its id
+    Edition(String),
+}
+
+impl ViewType {
+    fn name(&self) -> String {
+        match self {
+            ViewType::Diplomatic => "diplomatic".to_string(),
+            ViewType::Translation => "translation".to_string(),
+            ViewType::Both => "both".to_string(),
+            ViewType::Diff => "diff".to_string(),
+            ViewType::Edition(id) => format!("edition-{}", id),
+        }
+    }
+
+    fn from_str(value: &str) -> Option<Self> {
+        match value {
+            "diplomatic" => Some(ViewType::Diplomatic),
+            "translation" => Some(ViewType::Translation),
+            "both" => Some(ViewType::Both),
+            "diff" => Some(ViewType::Diff),
+            _ => value
+                .strip_prefix("edition-")
+                .filter(|id| !id.is_empty())
+                .map(|id| ViewType::Edition(id.to_string())),
+        }
+    }
+}
+
+/// Where one of the page's resources has got. They arrive at very
+/// different speeds, so each placeholder follows its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Progress {
+    #[default]
+    Loading,
+    Done,
+    Failed,
+}
+
+/// What loading a page brought, each part failing on its own
+pub struct PageData {
+    diplomatic: Result<TeiDocument, String>,
+    translation: Result<TeiDocument, String>,
+    commentary: Result<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PageProgress {
+    diplomatic: Progress,
+    translation: Progress,
+    edition: Progress,
+    commentary: Progress,
+    image: Progress,
+}
+
+/// Raw TEI of the diplomatic page open in the source editor, with the
+/// outcome of its last parse.
+#[derive(Default)]
+struct SourceEditor {
+    text: String,
+    error: Option<String>,
+    warnings: Vec<String>,
 }
 
 pub struct TeiViewer {
-    diplomatic: Option<TeiDocument>,
-    translation: Option<TeiDocument>,
+    diplomatic: Option<Rc<TeiDocument>>,
+    translation: Option<Rc<TeiDocument>>,
+    // further edition on screen, and the one requested for this page
+    edition: Option<Rc<TeiDocument>>,
+    edition_requested: Option<String>,
     commentary: Option<String>,
+    bibliography: Vec<BiblEntry>,
     hovered_zone: Option<String>,
     locked_zone: Option<String>,
     active_view: ViewType,
     show_image: bool,
     loading: bool,
+    progress: PageProgress,
     error: Option<String>,
+    // bumped on every page/project change; stale responses are dropped
+    load_generation: u32,
+    // requests for the page on screen, one of each kind, aborted (by
+    // dropping them) when it changes or the viewer goes away; the
+    // project-wide scans are aborted when the project changes
+    page_loads: HashMap<PageLoad, net::Abort>,
+    project_loads: Vec<net::Abort>,
+    comparison_load: Option<net::Abort>,
+    // when the page's texts came, until it is rendered (`?perf=1`)
+    render_start: Option<f64>,
+    // the next page's facsimile, fetched ahead once this page's has come
+    next_image: Option<web_sys::HtmlImageElement>,
+    // recently parsed documents, keyed by (project, page, edition)
+    doc_cache: DocumentCache,
     // commentary popup
     show_commentary: bool,
     commentary_first_load: bool,
@@ -74,8 +467,13 @@ pub struct TeiViewer {
     dragging: bool,
     last_mouse_x: i32,
     last_mouse_y: i32,
-    pointers: Vec<(i32, (i32, i32))>,
-    last_pointer_distance: f64,
+    pointers: Pointers,
+    // where the last press started, to tell clicks from drags
+    press_origin: (i32, i32),
+    // while dragging, the transform is written to this node on animation
+    // frames and only committed to the VDOM when the gesture ends
+    image_transform_ref: NodeRef,
+    pending_frame: Option<AnimationFrame>,
     // metadata popup
     show_metadata_popup: bool,
     metadata_selected: Option<ViewType>,
@@ -83,14 +481,123 @@ pub struct TeiViewer {
     current_project: String,
     // legend
     show_legend: bool,
+    // shade zones by how much editorial markup their line carries
+    show_heatmap: bool,
     // image intrinsic dimensions (natural)
     image_nat_w: u32,
     image_nat_h: u32,
-    // splitter state
+    // fit the project asks for, applied once the image is laid out
+    pending_fit: Option<Fit>,
+    // image of the page on screen: 0 the facsimile, then its further images
+    image_choice: usize,
+    // deep zoom (IIIF or DZI): pyramid description and measured panel size
+    tile_source: Option<TileSource>,
+    image_container_ref: NodeRef,
+    viewport_w: f64,
+    viewport_h: f64,
+    // stable line callbacks, so unchanged lines skip re-rendering
+    on_line_hover: Callback<String>,
+    on_line_leave: Callback<()>,
+    on_line_click: Callback<String>,
+    on_term_click: Callback<(Option<String>, String)>,
+    // text panel scroll positions (scroll_top, client_height) for windowing
+    dip_scroll: (f64, f64),
+    trad_scroll: (f64, f64),
+    // layout (from the settings) and splitter state (sizes are
+    // percentages of the viewer)
+    layout: Layout,
     image_panel_width: f64,
+    image_panel_height: f64,
     splitter_dragging: bool,
     splitter_start_x: f64,
     splitter_start_width: f64,
+    // tabbed interface below the mobile breakpoint
+    is_mobile: bool,
+    mobile_tab: MobileTab,
+    swipe_origin: Option<(i32, i32)>,
+    // direction of a swipe-initiated page change, played as an animation
+    // once the new page renders
+    pending_page_turn: Option<i32>,
+    page_turn: Option<i32>,
+    _resize_listener: Option<EventListener>,
+    _popstate_listener: Option<EventListener>,
+    // continuous scroll: pages loaded after the current one, in order,
+    // and the page centred in the text panel (drives the facsimile)
+    continuous: bool,
+    continuous_docs: Vec<(u32, Rc<TeiDocument>)>,
+    continuous_loading: bool,
+    centered_page: Option<u32>,
+    // ruler: measured points in image layout pixels (at most two)
+    ruler_active: bool,
+    ruler_points: Vec<(f64, f64)>,
+    // scale bar in a corner of the image panel, resized live while zooming
+    show_scale_bar: bool,
+    // language of the translation the reader last picked, for pages that
+    // have several
+    translation_lang: Option<String>,
+    scale_bar_ref: NodeRef,
+    // jump to line: box input, line to scroll into view after rendering,
+    // and whether the last reference could not be resolved
+    goto_line_ref: NodeRef,
+    pending_scroll_line: Option<usize>,
+    // line being read in the page's audio recording
+    audio_line: Option<usize>,
+    goto_line_missing: bool,
+    // focus (tour stop, bookmark) waiting for its page to finish loading
+    pending_focus: Option<TourStop>,
+    // diplomatic edition of the compared project's matching page
+    compare_doc: Option<Rc<TeiDocument>>,
+    // page view last restored from or written to storage
+    saved_view: Option<PageView>,
+    // view last written to the URL hash
+    url_state: UrlState,
+    // other windows of the viewer: this one's position when last seen,
+    // and the last one announced by another window
+    window_sync: Option<WindowSync>,
+    sync_position: Option<Position>,
+    followed: Option<Position>,
+    // reader notes of the project; the passage selected with the last line
+    // click; import/export in flight
+    annotations: AnnotationSet,
+    show_notes: bool,
+    // zone editor: editable copy of the page's facsimile while active
+    zone_editor: Option<ZoneEditor>,
+    _zones_export: Option<ObjectUrl>,
+    // "Reportar un problema" dialog
+    show_feedback: bool,
+    feedback_comment: String,
+    // TEI source editor for the diplomatic page
+    source_editor: Option<SourceEditor>,
+    source_highlight_ref: NodeRef,
+    _source_export: Option<ObjectUrl>,
+    // glossary panel: entries of the project's glossary.xml (None until
+    // fetched) and the selected <term> as (key, text)
+    show_toc: bool,
+    show_glossary: bool,
+    project_glossary: Option<Vec<GlossEntry>>,
+    glossary_term: Option<(Option<String>, String)>,
+    show_index_locorum: bool,
+    // map panel: coordinates from the project's places.json (None until
+    // fetched)
+    show_map: bool,
+    project_places: Option<Vec<GazetteerEntry>>,
+    show_timeline: bool,
+    // word frequency panel, and whether it counts the whole project
+    show_word_frequency: bool,
+    word_frequency_project: bool,
+    // diplomatic pages of the project, scanned on demand for the
+    // project-wide panels, and how many are still being fetched
+    corpus: BTreeMap<u32, Rc<TeiDocument>>,
+    corpus_pending: usize,
+    note_quote: Option<String>,
+    notes_status: Option<String>,
+    _notes_reader: Option<FileReader>,
+    _notes_export: Option<ObjectUrl>,
+    // text search; hits are line indices, sorted
+    search_query: String,
+    search_ignore_diacritics: bool,
+    dip_hits: Vec<usize>,
+    trad_hits: Vec<usize>,
 }
 
 impl Component for TeiViewer {
@@ -100,95 +607,276 @@ impl Component for TeiViewer {
     fn create(ctx: &Context<Self>) -> Self {
         let project = ctx.props().project.clone();
         let page = ctx.props().page;
+        diagnostics::set_page(&project, page, ctx.props().config.issues_url.as_deref());
 
         // Kick off loads
-        let dip_path = resource_url(&format!("public/projects/{}/p{}_dip.xml", project, page));
-        ctx.link()
-            .send_message(TeiViewerMsg::LoadDiplomatic(dip_path));
-        let trad_path = resource_url(&format!("public/projects/{}/p{}_trad.xml", project, page));
-        ctx.link()
-            .send_message(TeiViewerMsg::LoadTranslation(trad_path));
-        let commentary_path = resource_url(&format!("public/projects/{}/commentary.html", project));
-        ctx.link()
-            .send_message(TeiViewerMsg::LoadCommentary(commentary_path));
+        let translation_lang = persistence::load::<String>(persistence::TRANSLATION_LANG);
+        ctx.link().send_message(TeiViewerMsg::LoadPage);
+        ctx.link().send_message(TeiViewerMsg::LoadBibliography);
+        Self::load_tile_source(ctx);
 
-        Self {
+        let resize_listener = web_sys::window().map(|window| {
+            let link = ctx.link().clone();
+            EventListener::new(&window, "resize", move |_| {
+                link.send_message(TeiViewerMsg::WindowResized);
+            })
+        });
+
+        let popstate_listener = web_sys::window().map(|window| {
+            let link = ctx.link().clone();
+            EventListener::new(&window, "popstate", move |_| {
+                link.send_message(TeiViewerMsg::HistoryMoved);
+            })
+        });
+
+        let mut viewer = Self {
             diplomatic: None,
             translation: None,
+            edition: None,
+            edition_requested: None,
             commentary: None,
+            bibliography: Vec::new(),
             hovered_zone: None,
             locked_zone: None,
-            active_view: ViewType::Both,
+            active_view: Self::default_view_type(ctx).unwrap_or(ViewType::Both),
             show_image: true,
             loading: true,
+            progress: PageProgress::default(),
             error: None,
+            load_generation: 0,
+            page_loads: HashMap::new(),
+            project_loads: Vec::new(),
+            comparison_load: None,
+            render_start: None,
+            next_image: None,
+            doc_cache: DocumentCache::default(),
             show_commentary: false, // Will be set to true when commentary loads successfully
-            commentary_first_load: true,
-            image_scale: 1.0, // Start at normal size
+            commentary_first_load: ctx.props().default_view.opens_commentary(),
+            image_scale: Self::default_zoom(ctx).unwrap_or(1.0), // Start at normal size
             image_offset_x: 0.0,
             image_offset_y: 0.0,
             dragging: false,
             last_mouse_x: 0,
             last_mouse_y: 0,
-            pointers: Vec::new(),
-            last_pointer_distance: 0.0,
+            pointers: Pointers::default(),
+            press_origin: (0, 0),
+            image_transform_ref: NodeRef::default(),
+            pending_frame: None,
             show_metadata_popup: false,
             metadata_selected: None,
             current_page: page,
             current_project: project,
             show_legend: false,
+            show_heatmap: persistence::load(persistence::HEATMAP).unwrap_or(false),
             image_nat_w: 0,
+            pending_fit: ctx.props().default_view.fit,
+            image_choice: 0,
             image_nat_h: 0,
-            image_panel_width: 45.0,
+            tile_source: None,
+            image_container_ref: NodeRef::default(),
+            viewport_w: 0.0,
+            viewport_h: 0.0,
+            dip_scroll: (0.0, 0.0),
+            trad_scroll: (0.0, 0.0),
+            on_line_hover: ctx.link().callback(TeiViewerMsg::HoverLine),
+            on_line_leave: ctx.link().callback(|_| TeiViewerMsg::ClearHover),
+            on_line_click: ctx.link().callback(TeiViewerMsg::ClickLine),
+            on_term_click: ctx.link().callback(TeiViewerMsg::SelectTerm),
+            layout: ctx.props().settings.layout,
+            image_panel_width: persistence::load::<f64>(persistence::PANEL_WIDTH)
+                .or(ctx.props().default_view.image_panel)
+                .map(|w| w.clamp(MIN_PANEL_WIDTH, MAX_PANEL_WIDTH))
+                .unwrap_or(DEFAULT_PANEL_WIDTH),
+            image_panel_height: persistence::load::<f64>(persistence::PANEL_HEIGHT)
+                .or(ctx.props().default_view.image_panel)
+                .map(|h| h.clamp(MIN_PANEL_WIDTH, MAX_PANEL_WIDTH))
+                .unwrap_or(DEFAULT_PANEL_WIDTH),
             splitter_dragging: false,
             splitter_start_x: 0.0,
-            splitter_start_width: 45.0,
-        }
+            splitter_start_width: DEFAULT_PANEL_WIDTH,
+            is_mobile: Self::is_narrow_viewport(),
+            mobile_tab: if ctx.props().panels == Panels::Image {
+                MobileTab::Image
+            } else {
+                MobileTab::Diplomatic
+            },
+            swipe_origin: None,
+            pending_page_turn: None,
+            page_turn: None,
+            _resize_listener: resize_listener,
+            _popstate_listener: popstate_listener,
+            continuous: false,
+            continuous_docs: Vec::new(),
+            continuous_loading: false,
+            centered_page: None,
+            ruler_active: false,
+            ruler_points: Vec::new(),
+            show_scale_bar: persistence::load(persistence::SCALE_BAR).unwrap_or(false),
+            translation_lang,
+            scale_bar_ref: NodeRef::default(),
+            goto_line_ref: NodeRef::default(),
+            pending_scroll_line: None,
+            audio_line: None,
+            goto_line_missing: false,
+            pending_focus: ctx.props().focus.clone(),
+            compare_doc: None,
+            saved_view: None,
+            url_state: UrlState::default(),
+            window_sync: WindowSync::open(ctx.link().callback(TeiViewerMsg::Synced)),
+            sync_position: None,
+            followed: None,
+            annotations: AnnotationSet::load(&ctx.props().project),
+            show_notes: false,
+            zone_editor: None,
+            _zones_export: None,
+            show_feedback: false,
+            feedback_comment: String::new(),
+            source_editor: None,
+            source_highlight_ref: NodeRef::default(),
+            _source_export: None,
+            show_glossary: false,
+            project_glossary: None,
+            glossary_term: None,
+            show_index_locorum: false,
+            show_map: false,
+            project_places: None,
+            show_timeline: false,
+            show_toc: false,
+            show_word_frequency: false,
+            word_frequency_project: false,
+            corpus: BTreeMap::new(),
+            corpus_pending: 0,
+            note_quote: None,
+            notes_status: None,
+            _notes_reader: None,
+            _notes_export: None,
+            search_query: String::new(),
+            search_ignore_diacritics: true,
+            dip_hits: Vec::new(),
+            trad_hits: Vec::new(),
+        };
+        viewer.load_comparison(ctx);
+        viewer.restore_page_view();
+        viewer.restore_url_state();
+        viewer
     }
 
-    fn changed(&mut self, ctx: &Context<Self>, _old: &Self::Properties) -> bool {
+    fn changed(&mut self, ctx: &Context<Self>, old: &Self::Properties) -> bool {
         let new_page = ctx.props().page;
         let new_project = ctx.props().project.clone();
+        if ctx.props().focus != old.focus {
+            self.pending_focus = ctx.props().focus.clone();
+        }
+        let compare_changed = ctx.props().compare != old.compare;
+        self.layout = ctx.props().settings.layout;
+        if compare_changed {
+            self.load_comparison(ctx);
+        }
+
+        // Files opened again replace the local page in place; pages parsed
+        // before may be of a served project the files now stand for
+        let local_changed = ctx.props().local_revision != old.local_revision;
+        if local_changed {
+            self.doc_cache = DocumentCache::default();
+        }
+        let local_changed = local_changed && local_document::is_open(&new_project);
 
         // Check if either page or project changed
-        if new_page != self.current_page || new_project != self.current_project {
+        if new_page != self.current_page || new_project != self.current_project || local_changed {
+            if new_project != self.current_project {
+                if let Some(view) = Self::default_view_type(ctx) {
+                    self.active_view = view;
+                }
+                // Each project opens its commentary as its manifest says
+                self.commentary_first_load = ctx.props().default_view.opens_commentary();
+                self.show_commentary = false;
+            }
             self.current_page = new_page;
             self.current_project = new_project.clone();
+            diagnostics::set_page(
+                &new_project,
+                new_page,
+                ctx.props().config.issues_url.as_deref(),
+            );
+            self.load_generation = self.load_generation.wrapping_add(1);
+            self.page_loads.clear();
+            self.next_image = None;
+            self.page_turn = self.pending_page_turn.take();
             self.diplomatic = None;
             self.translation = None;
+            self.edition = None;
+            self.edition_requested = None;
             self.commentary = None;
+            self.bibliography = Vec::new();
             self.loading = true;
+            self.progress = PageProgress::default();
             self.error = None;
             self.hovered_zone = None;
             self.locked_zone = None;
-            self.image_scale = 0.3;
+            self.image_scale = Self::default_zoom(ctx).unwrap_or(0.3);
             self.image_offset_x = 0.0;
             self.image_offset_y = 0.0;
             self.image_nat_w = 0;
             self.image_nat_h = 0;
-            // reload
-            let cache_bust = js_sys::Date::now() as u64;
-            let dip_path = format!(
-                "public/projects/{}/p{}_dip.xml?v={}",
-                new_project, new_page, cache_bust
-            );
-            ctx.link()
-                .send_message(TeiViewerMsg::LoadDiplomatic(dip_path));
-            let trad_path = format!(
-                "public/projects/{}/p{}_trad.xml?v={}",
-                new_project, new_page, cache_bust
-            );
-            ctx.link()
-                .send_message(TeiViewerMsg::LoadTranslation(trad_path));
-            let commentary_path = format!(
-                "public/projects/{}/commentary.html?v={}",
-                new_project, cache_bust
-            );
-            ctx.link()
-                .send_message(TeiViewerMsg::LoadCommentary(commentary_path));
+            self.pending_fit = ctx.props().default_view.fit;
+            self.image_choice = 0;
+            self.tile_source = None;
+            self.dip_scroll.0 = 0.0;
+            self.trad_scroll.0 = 0.0;
+            self.continuous_docs.clear();
+            self.ruler_points.clear();
+            self.zone_editor = None;
+            self.source_editor = None;
+            self.audio_line = None;
+            self.continuous_loading = false;
+            self.centered_page = None;
+            if self.continuous {
+                self.load_next_continuous_page(ctx);
+            }
+            self.refresh_search();
+            self.restore_page_view();
+            // Back/forward onto a page brings back the view it was left in
+            self.restore_url_state();
+            self.note_quote = None;
+            if self.annotations.project != self.current_project {
+                self.annotations = AnnotationSet::load(&self.current_project);
+                self.project_loads.clear();
+                self.notes_status = None;
+                self.project_glossary = None;
+                self.glossary_term = None;
+                self.project_places = None;
+                self.corpus.clear();
+                self.corpus_pending = 0;
+                if self.show_glossary {
+                    self.load_glossary(ctx);
+                }
+                if self.show_map {
+                    self.load_places(ctx);
+                }
+                if self.show_index_locorum
+                    || self.show_map
+                    || self.show_timeline
+                    || (self.show_toc && ctx.props().outline.is_empty())
+                    || (self.show_word_frequency && self.word_frequency_project)
+                {
+                    self.load_corpus(ctx);
+                }
+            }
+            self.load_page(ctx);
+            ctx.link().send_message(TeiViewerMsg::LoadBibliography);
+            Self::load_tile_source(ctx);
             true
         } else {
-            false
+            // Same page: a new focus is applied after this render
+            compare_changed
+                || ctx.props().kiosk != old.kiosk
+                || ctx.props().panels != old.panels
+                || ctx.props().lang != old.lang
+                || ctx.props().settings != old.settings
+                || ctx.props().bookmarks != old.bookmarks
+                || ctx.props().editions != old.editions
+                || ctx.props().legend != old.legend
+                || self.pending_focus.is_some()
         }
     }
 
@@ -197,133 +885,203 @@ impl Component for TeiViewer {
             TeiViewerMsg::ImageLoadedWithDimensions(width, height) => {
                 self.image_nat_w = width;
                 self.image_nat_h = height;
+                self.progress.image = Progress::Done;
+                self.preload_next_image(ctx);
+                true
+            }
+            TeiViewerMsg::FitImage(fit) => {
+                let Some(doc) = self.image_doc(ctx).cloned() else {
+                    return false;
+                };
+                let (use_w, use_h) = self.image_layout_size(ctx, &doc);
+                let (scale, offset_x, offset_y) = fit_image(
+                    fit,
+                    use_w as f32,
+                    use_h as f32,
+                    self.viewport_w as f32,
+                    self.viewport_h as f32,
+                );
+                self.image_scale = scale;
+                self.image_offset_x = offset_x;
+                self.image_offset_y = offset_y;
+                true
+            }
+            TeiViewerMsg::ImageFailed => {
+                self.progress.image = Progress::Failed;
+                self.preload_next_image(ctx);
+                true
+            }
+            TeiViewerMsg::ShowImage(choice) => {
+                if choice == self.image_choice {
+                    return false;
+                }
+                self.image_choice = choice;
+                // The new image is measured on load; zones, rulers and
+                // drawings of the facsimile may not fit it
+                self.image_nat_w = 0;
+                self.image_nat_h = 0;
+                self.progress.image = Progress::Loading;
+                self.ruler_points.clear();
+                self.zone_editor = None;
                 true
             }
-            TeiViewerMsg::LoadDiplomatic(path) => {
+            TeiViewerMsg::LoadPage => {
+                self.load_page(ctx);
+                false
+            }
+            TeiViewerMsg::LoadBibliography => {
+                let file = match ctx.props().page_info.as_ref().map(|info| &info.bibl) {
+                    Some(PageBibliography::File(file)) => file,
+                    Some(PageBibliography::Entries(entries)) => {
+                        self.bibliography = entries.clone();
+                        return true;
+                    }
+                    None => return false,
+                };
+                let path = ctx.props().config.versioned_file_url(file);
                 let link = ctx.link().clone();
+                let generation = self.load_generation;
+                if let Some(text) = local_document::read(&path) {
+                    let entries = text.and_then(|text| bibliography::parse(&text, &path));
+                    link.send_message(TeiViewerMsg::BibliographyLoaded(generation, entries));
+                    return false;
+                }
+                let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+                self.page_loads.insert(PageLoad::Bibliography, load);
                 spawn_local(async move {
-                    let result = match Request::get(&path).send().await {
-                        Ok(resp) => match resp.text().await {
-                            Ok(xml) => crate::tei_parser::parse_tei_xml(&xml),
-                            Err(e) => Err(format!("Failed to read response text: {:?}", e)),
-                        },
-                        Err(e) => Err(format!("Failed to load diplomatic: {:?}", e)),
+                    let result = match text.await {
+                        Err(FetchError::Aborted { .. }) => return,
+                        Ok(text) => {
+                            offline::keep_text(&path, &text);
+                            bibliography::parse(&text, &path)
+                        }
+                        Err(e) => Err(format!("Failed to load bibliography: {}", e)),
                     };
-                    link.send_message(TeiViewerMsg::DiplomaticLoaded(result));
+                    link.send_message(TeiViewerMsg::BibliographyLoaded(generation, result));
                 });
                 false
             }
-            TeiViewerMsg::LoadTranslation(path) => {
+            TeiViewerMsg::BibliographyLoaded(generation, res) => {
+                if generation != self.load_generation {
+                    return false;
+                }
+                match res {
+                    Ok(entries) => {
+                        self.bibliography = entries;
+                        true
+                    }
+                    Err(e) => {
+                        log::warn!("{}", e);
+                        false
+                    }
+                }
+            }
+            TeiViewerMsg::LoadIiifInfo(service) => {
                 let link = ctx.link().clone();
+                let generation = self.load_generation;
+                let info_url = Url::new(&service).segment("info.json").to_string();
+                let (load, text) = net::get_text_abortable(info_url.into());
+                self.page_loads.insert(PageLoad::TileSource, load);
                 spawn_local(async move {
-                    let result = match Request::get(&path).send().await {
-                        Ok(resp) => match resp.text().await {
-                            Ok(xml) => crate::tei_parser::parse_tei_xml(&xml),
-                            Err(e) => Err(format!("Failed to read response text: {:?}", e)),
-                        },
-                        Err(e) => Err(format!("Failed to load translation: {:?}", e)),
+                    let result = match text.await {
+                        Err(FetchError::Aborted { .. }) => return,
+                        Ok(json) => IiifInfo::from_json(&service, &json).map(TileSource::Iiif),
+                        Err(e) => Err(format!("Failed to load info.json: {}", e)),
                     };
-                    link.send_message(TeiViewerMsg::TranslationLoaded(result));
+                    link.send_message(TeiViewerMsg::TileSourceLoaded(generation, result));
                 });
                 false
             }
-            TeiViewerMsg::LoadCommentary(path) => {
+            TeiViewerMsg::LoadDzi(dzi_url) => {
                 let link = ctx.link().clone();
+                let generation = self.load_generation;
+                let (load, text) = net::get_text_abortable(dzi_url.as_str().into());
+                self.page_loads.insert(PageLoad::TileSource, load);
                 spawn_local(async move {
-                    let result = match Request::get(&path).send().await {
-                        Ok(resp) => match resp.text().await {
-                            Ok(html) => Ok(html),
-                            Err(e) => Err(format!("Failed to read commentary text: {:?}", e)),
-                        },
-                        Err(e) => Err(format!("Failed to load commentary: {:?}", e)),
+                    let result = match text.await {
+                        Err(FetchError::Aborted { .. }) => return,
+                        Ok(xml) => DziInfo::from_xml(&dzi_url, &xml).map(TileSource::Dzi),
+                        Err(e) => Err(format!("Failed to load DZI descriptor: {}", e)),
                     };
-                    link.send_message(TeiViewerMsg::CommentaryLoaded(result));
+                    link.send_message(TeiViewerMsg::TileSourceLoaded(generation, result));
                 });
                 false
             }
-            TeiViewerMsg::CommentaryLoaded(res) => {
+            TeiViewerMsg::TileSourceLoaded(generation, res) => {
+                if generation != self.load_generation {
+                    return false;
+                }
                 match res {
-                    Ok(html) => {
-                        self.commentary = Some(html);
-                        // Auto-show only on first load if commentary exists
-                        if self.commentary_first_load {
-                            self.show_commentary = true;
-                        }
+                    Ok(source) => {
+                        self.tile_source = Some(source);
+                        true
                     }
                     Err(e) => {
-                        log::warn!("Failed to load commentary: {:?}", e);
-                        // Set fallback message instead of None
-                        self.commentary =
-                            Some("<p class=\"sin-comentario\">Sin comentario</p>".to_string());
-                        // Auto-show fallback message on first load
-                        if self.commentary_first_load {
-                            self.show_commentary = true;
-                        }
+                        // Keep showing the static image as a fallback
+                        log::warn!("Tiled image unavailable: {}", e);
+                        false
                     }
                 }
+            }
+            TeiViewerMsg::TextScrolled(panel, scroll_top, client_h) => {
+                let (doc, scroll) = match panel {
+                    ViewType::Translation => (self.translation.as_ref(), &mut self.trad_scroll),
+                    ViewType::Edition(_) => (self.edition.as_ref(), &mut self.dip_scroll),
+                    _ => (self.diplomatic.as_ref(), &mut self.dip_scroll),
+                };
+                let total = doc.map(|d| d.lines.len()).unwrap_or(0);
+                let before = Self::line_window(total, *scroll);
+                *scroll = (scroll_top, client_h);
+                // Only re-render when the window of rendered lines moves
+                total > VIRTUALIZE_THRESHOLD && Self::line_window(total, *scroll) != before
+            }
+            TeiViewerMsg::ViewportMeasured(w, h) => {
+                self.viewport_w = w;
+                self.viewport_h = h;
+                self.tile_source.is_some()
+            }
+            TeiViewerMsg::PageLoaded(generation, data) => {
+                if generation != self.load_generation {
+                    log::debug!("Discarding stale page response");
+                    return false;
+                }
+                let PageData {
+                    diplomatic,
+                    translation,
+                    commentary,
+                } = *data;
+                self.apply_diplomatic(diplomatic);
+                self.apply_translation(ctx, translation);
+                self.apply_commentary(ctx, commentary);
+                self.loading = false;
+                self.render_start = perf::start();
                 true
             }
-            TeiViewerMsg::DiplomaticLoaded(res) => {
+            TeiViewerMsg::EditionLoaded(generation, id, res) => {
+                if generation != self.load_generation
+                    || self.active_view != ViewType::Edition(id.clone())
+                {
+                    return false;
+                }
                 match res {
                     Ok(doc) => {
-                        self.diplomatic = Some(doc);
-                        if self.translation.is_some() {
-                            self.loading = false;
-                        }
-                        if self.show_metadata_popup {
-                            self.metadata_selected = Some(ViewType::Diplomatic);
-                        }
+                        self.cache_document(&id, &doc);
+                        self.edition = Some(Rc::new(doc));
+                        self.progress.edition = Progress::Done;
                     }
                     Err(e) => {
-                        // If fetching/parsing fails (for example the XML file is missing or a network error),
-                        // treat it as an empty document so the viewer can still display the image and UI.
-                        log::warn!("Failed to load diplomatic: {:?}", e);
-                        self.diplomatic = Some(TeiDocument::new());
-                        // If we already have the translation loaded (even if empty), stop the loading spinner.
-                        if self.translation.is_some() {
-                            self.loading = false;
-                        }
-                        // Preserve existing behavior for metadata popup selection.
-                        if self.show_metadata_popup {
-                            self.metadata_selected = Some(ViewType::Diplomatic);
-                        }
+                        log::warn!("Failed to load edition {}: {:?}", id, e);
+                        self.progress.edition = Progress::Failed;
                     }
                 }
                 true
             }
-            TeiViewerMsg::TranslationLoaded(res) => {
-                match res {
-                    Ok(doc) => {
-                        self.translation = Some(doc);
-                        if self.diplomatic.is_some() {
-                            self.loading = false;
-                        }
-                        if self.show_metadata_popup {
-                            if self.diplomatic.is_some() {
-                                self.metadata_selected = Some(ViewType::Diplomatic);
-                            } else {
-                                self.metadata_selected = Some(ViewType::Translation);
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        // If translation fetch/parsing fails, treat as empty translation so images still show.
-                        log::warn!("Failed to load translation: {:?}", e);
-                        self.translation = Some(TeiDocument::new());
-                        // If we already have the diplomatic loaded (even if empty), stop the loading spinner.
-                        if self.diplomatic.is_some() {
-                            self.loading = false;
-                        }
-                        // Preserve existing behavior for metadata popup selection.
-                        if self.show_metadata_popup {
-                            if self.diplomatic.is_some() {
-                                self.metadata_selected = Some(ViewType::Diplomatic);
-                            } else {
-                                self.metadata_selected = Some(ViewType::Translation);
-                            }
-                        }
-                    }
+            TeiViewerMsg::TranslationLoaded(generation, res) => {
+                if generation != self.load_generation {
+                    log::debug!("Discarding stale translation response");
+                    return false;
                 }
+                self.apply_translation(ctx, res);
                 true
             }
             TeiViewerMsg::HoverLine(zone) => {
@@ -340,6 +1098,13 @@ impl Component for TeiViewer {
                 } else {
                     self.locked_zone = Some(zone);
                 }
+                // A passage selected within the line becomes the quote of
+                // the next note
+                self.note_quote = web_sys::window()
+                    .and_then(|w| w.get_selection().ok().flatten())
+                    .and_then(|s| s.to_string().as_string())
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty());
                 true
             }
             TeiViewerMsg::ClearHover => {
@@ -350,7 +1115,19 @@ impl Component for TeiViewer {
                     false
                 }
             }
+            TeiViewerMsg::SetTranslationLang(lang) => {
+                persistence::save(persistence::TRANSLATION_LANG, &lang);
+                self.translation_lang = Some(lang);
+                self.translation = None;
+                self.progress.translation = Progress::Loading;
+                self.load_translation(ctx);
+                true
+            }
             TeiViewerMsg::ToggleView(view) => {
+                if view != self.active_view {
+                    self.edition = None;
+                    self.edition_requested = None;
+                }
                 self.active_view = view;
                 true
             }
@@ -367,6 +1144,7 @@ impl Component for TeiViewer {
                 true
             }
             TeiViewerMsg::StartDrag(event) => {
+                self.press_origin = (event.client_x(), event.client_y());
                 self.dragging = true;
                 self.last_mouse_x = event.client_x();
                 self.last_mouse_y = event.client_y();
@@ -382,100 +1160,101 @@ impl Component for TeiViewer {
                     self.image_offset_y += dy as f32;
                     self.last_mouse_x = x;
                     self.last_mouse_y = y;
-                    true
-                } else {
-                    false
+                    self.schedule_transform(ctx);
                 }
+                false
             }
             TeiViewerMsg::EndDrag => {
+                // Commit the gesture to the rendered state (also refreshes tiles)
+                let was_dragging = self.dragging;
                 self.dragging = false;
-                true
+                self.pending_frame = None;
+                was_dragging
+            }
+            TeiViewerMsg::ApplyTransform => {
+                self.pending_frame = None;
+                if let Some(el) = self.image_transform_ref.cast::<web_sys::HtmlElement>() {
+                    let _ = el.style().set_property(
+                        "transform",
+                        &format!(
+                            "translate({}px, {}px) scale({})",
+                            self.image_offset_x, self.image_offset_y, self.image_scale
+                        ),
+                    );
+                }
+                self.update_scale_bar(ctx);
+                false
             }
             TeiViewerMsg::PointerDown(id, x, y) => {
-                self.pointers.push((id, (x, y)));
-                if self.pointers.len() == 1 {
+                self.press_origin = (x, y);
+                if let Some((x, y)) = self.pointers.press(id, (x, y)) {
                     // Single pointer - initialize drag position
                     self.last_mouse_x = x;
                     self.last_mouse_y = y;
-                } else if self.pointers.len() == 2 {
-                    // Two pointers - initialize pinch zoom
-                    let p1 = self.pointers[0].1;
-                    let p2 = self.pointers[1].1;
-                    self.last_pointer_distance =
-                        f64::sqrt(((p1.0 - p2.0).pow(2) + (p1.1 - p2.1).pow(2)) as f64);
                 }
                 self.dragging = true;
                 false
             }
             TeiViewerMsg::PointerMove(id, x, y) => {
-                if let Some(pointer) = self.pointers.iter_mut().find(|(p_id, _)| *p_id == id) {
-                    pointer.1 = (x, y);
+                if self.zone_editor.as_ref().is_some_and(|e| e.grab.is_some()) {
+                    let point = self.client_to_facsimile(ctx, x, y);
+                    return match (point, self.zone_editor.as_mut()) {
+                        (Some(point), Some(editor)) => editor.drag_to(point),
+                        _ => false,
+                    };
                 }
-
-                if self.pointers.len() == 2 {
-                    // Two-finger pinch zoom
-                    let p1 = self.pointers[0].1;
-                    let p2 = self.pointers[1].1;
-                    let new_dist = f64::sqrt(((p1.0 - p2.0).pow(2) + (p1.1 - p2.1).pow(2)) as f64);
-
-                    // Calculate zoom center (midpoint between two pointers)
-                    let center_x = (p1.0 + p2.0) as f32 / 2.0;
-                    let center_y = (p1.1 + p2.1) as f32 / 2.0;
-
-                    if self.last_pointer_distance > 0.0 {
-                        let scale_factor = (new_dist / self.last_pointer_distance) as f32;
+                match self.pointers.moved(id, (x, y)) {
+                    Some(Gesture::Pinch { factor, center }) => {
                         let old_scale = self.image_scale;
-                        self.image_scale = (self.image_scale * scale_factor).clamp(0.1, 8.0);
+                        self.image_scale = (self.image_scale * factor).clamp(0.1, 8.0);
 
                         // Adjust offset so zoom occurs around the gesture center
-                        let scale_change = self.image_scale / old_scale;
-                        self.image_offset_x =
-                            center_x + (self.image_offset_x - center_x) * scale_change;
-                        self.image_offset_y =
-                            center_y + (self.image_offset_y - center_y) * scale_change;
+                        let change = self.image_scale / old_scale;
+                        self.image_offset_x = scaled_about(self.image_offset_x, center.0, change);
+                        self.image_offset_y = scaled_about(self.image_offset_y, center.1, change);
                     }
-
-                    self.last_pointer_distance = new_dist;
-                } else if self.pointers.len() == 1 {
-                    // Single-finger pan
-                    let dx = x - self.last_mouse_x;
-                    let dy = y - self.last_mouse_y;
-                    self.image_offset_x += dx as f32;
-                    self.image_offset_y += dy as f32;
-                    self.last_mouse_x = x;
-                    self.last_mouse_y = y;
+                    Some(Gesture::Pan((x, y))) => {
+                        let dx = x - self.last_mouse_x;
+                        let dy = y - self.last_mouse_y;
+                        self.image_offset_x += dx as f32;
+                        self.image_offset_y += dy as f32;
+                        self.last_mouse_x = x;
+                        self.last_mouse_y = y;
+                    }
+                    None => {}
                 }
 
-                true
+                if !self.pointers.is_empty() {
+                    self.schedule_transform(ctx);
+                }
+                false
             }
-            TeiViewerMsg::PointerUp(id, _, _) => {
-                self.pointers.retain(|(p_id, _)| *p_id != id);
-
-                // Reset distance when transitioning from 2 to 1 pointer
-                if self.pointers.len() == 1 {
-                    let p = self.pointers[0].1;
-                    self.last_mouse_x = p.0;
-                    self.last_mouse_y = p.1;
-                    self.last_pointer_distance = 0.0;
+            TeiViewerMsg::PointerUp(id) => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.grab = None;
+                }
+                // The pointer left pans from where it is
+                if let Some((x, y)) = self.pointers.release(id) {
+                    self.last_mouse_x = x;
+                    self.last_mouse_y = y;
                 } else if self.pointers.is_empty() {
                     self.dragging = false;
-                    self.last_pointer_distance = 0.0;
+                    self.pending_frame = None;
                 }
 
                 true
             }
-            TeiViewerMsg::PointerLeave(id, _, _) => {
-                self.pointers.retain(|(p_id, _)| *p_id != id);
-
-                // Reset distance when transitioning from 2 to 1 pointer
-                if self.pointers.len() == 1 {
-                    let p = self.pointers[0].1;
-                    self.last_mouse_x = p.0;
-                    self.last_mouse_y = p.1;
-                    self.last_pointer_distance = 0.0;
+            TeiViewerMsg::PointerLeave(id) => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.grab = None;
+                }
+                // The pointer left pans from where it is
+                if let Some((x, y)) = self.pointers.release(id) {
+                    self.last_mouse_x = x;
+                    self.last_mouse_y = y;
                 } else if self.pointers.is_empty() {
                     self.dragging = false;
-                    self.last_pointer_distance = 0.0;
+                    self.pending_frame = None;
                 }
 
                 true
@@ -484,7 +1263,9 @@ impl Component for TeiViewer {
                 self.show_metadata_popup = !self.show_metadata_popup;
                 if self.show_metadata_popup {
                     let preferred = match self.active_view {
-                        ViewType::Diplomatic => Some(ViewType::Diplomatic),
+                        ViewType::Diplomatic | ViewType::Diff | ViewType::Edition(_) => {
+                            Some(ViewType::Diplomatic)
+                        }
                         ViewType::Translation => Some(ViewType::Translation),
                         ViewType::Both => {
                             if self.diplomatic.is_some() {
@@ -495,7 +1276,6 @@ impl Component for TeiViewer {
                                 None
                             }
                         }
-                        ViewType::Commentary => Some(ViewType::Diplomatic), // Default to diplomatic for commentary
                     };
                     self.metadata_selected = preferred;
                 } else {
@@ -519,14 +1299,18 @@ impl Component for TeiViewer {
                 self.show_legend = !self.show_legend;
                 true
             }
-            TeiViewerMsg::ImageLoaded(_event) => {
-                // Image dimensions will be handled via other means
+            TeiViewerMsg::ToggleHeatmap => {
+                self.show_heatmap = !self.show_heatmap;
+                persistence::save(persistence::HEATMAP, self.show_heatmap);
                 true
             }
             TeiViewerMsg::StartSplitterDrag(event) => {
                 self.splitter_dragging = true;
-                self.splitter_start_x = event.client_x() as f64;
-                self.splitter_start_width = self.image_panel_width;
+                self.splitter_start_x = match self.layout {
+                    Layout::SideBySide => event.client_x() as f64,
+                    Layout::Stacked => event.client_y() as f64,
+                };
+                self.splitter_start_width = self.panel_size();
                 event.prevent_default();
 
                 // Add global mouse listeners for proper drag behavior
@@ -567,17 +1351,26 @@ impl Component for TeiViewer {
             }
             TeiViewerMsg::SplitterDrag(event) => {
                 if self.splitter_dragging {
-                    let current_x = event.client_x() as f64;
+                    let stacked = self.layout == Layout::Stacked;
+                    let current_x = if stacked {
+                        event.client_y() as f64
+                    } else {
+                        event.client_x() as f64
+                    };
                     let dx = current_x - self.splitter_start_x;
 
-                    // Get actual container width from the DOM
+                    // Get actual container size along the split axis from the DOM
                     let container_width =
                         if let Some(document) = web_sys::window().and_then(|w| w.document()) {
                             if let Some(container) =
                                 document.query_selector(".viewer-content").ok().flatten()
                             {
                                 if let Ok(element) = container.dyn_into::<web_sys::HtmlElement>() {
-                                    element.client_width() as f64
+                                    if stacked {
+                                        element.client_height() as f64
+                                    } else {
+                                        element.client_width() as f64
+                                    }
                                 } else {
                                     1000.0
                                 }
@@ -590,13 +1383,16 @@ impl Component for TeiViewer {
 
                     let dx_percent = (dx / container_width) * 100.0;
                     let new_width = self.splitter_start_width + dx_percent;
-                    self.image_panel_width = new_width.max(20.0).min(80.0);
+                    self.set_panel_size(new_width);
                     true
                 } else {
                     false
                 }
             }
             TeiViewerMsg::EndSplitterDrag => {
+                if self.splitter_dragging {
+                    self.persist_panel_size();
+                }
                 self.splitter_dragging = false;
 
                 // Clean up global listeners
@@ -608,156 +1404,2603 @@ impl Component for TeiViewer {
 
                 true
             }
-        }
-    }
-
-    fn view(&self, ctx: &Context<Self>) -> Html {
-        if self.loading {
-            return html! {
-                <div class="loading"><p>{"Cargando documentos TEI..."}</p></div>
-            };
-        }
-        if let Some(err) = &self.error {
-            return html! {
-                <div class="error"><p>{format!("Error: {}", err)}</p></div>
-            };
-        }
-
-        // Set CSS custom property for dynamic column sizing
-        if let Some(window) = web_sys::window() {
-            if let Some(document) = window.document() {
-                if let Some(body) = document.body() {
-                    let _ = body.style().set_property(
-                        "--image-panel-width",
-                        &format!("{}%", self.image_panel_width),
-                    );
+            TeiViewerMsg::SetPanelSize(size) => {
+                self.set_panel_size(size);
+                self.persist_panel_size();
+                true
+            }
+            TeiViewerMsg::ToggleRuler => {
+                self.ruler_active = !self.ruler_active;
+                self.ruler_points.clear();
+                true
+            }
+            TeiViewerMsg::ToggleScaleBar => {
+                self.show_scale_bar = !self.show_scale_bar;
+                persistence::save(persistence::SCALE_BAR, self.show_scale_bar);
+                true
+            }
+            TeiViewerMsg::ImageClicked(x, y) => {
+                let (x0, y0) = self.press_origin;
+                // Ignore the click that ends a pan
+                if (x - x0).abs() + (y - y0).abs() > 4 {
+                    return false;
+                }
+                if self.zone_editor.is_some() {
+                    let point = self.client_to_facsimile(ctx, x, y);
+                    return match (point, self.zone_editor.as_mut()) {
+                        (Some(point), Some(editor)) => editor.add_point(point),
+                        _ => false,
+                    };
                 }
+                if !self.ruler_active {
+                    return false;
+                }
+                let Some(el) = self.image_transform_ref.cast::<web_sys::Element>() else {
+                    return false;
+                };
+                let rect = el.get_bounding_client_rect();
+                let scale = self.image_scale.max(0.01) as f64;
+                let point = (
+                    (x as f64 - rect.left()) / scale,
+                    (y as f64 - rect.top()) / scale,
+                );
+                if self.ruler_points.len() >= 2 {
+                    self.ruler_points.clear();
+                }
+                self.ruler_points.push(point);
+                true
             }
-        }
-
-        html! {
-            <div class="tei-viewer-container">
-                { self.render_controls(ctx) }
-                { self.render_legend(ctx) }
-                <div class="viewer-content">
-                    { self.render_image_panel(ctx) }
-                    { self.render_splitter(ctx) }
-                    { self.render_text_panels(ctx) }
-                    { self.render_metadata_popup(ctx) }
-                    { self.render_commentary_popup(ctx) }
-                </div>
-            </div>
-        }
-    }
-}
-
-impl TeiViewer {
-    fn render_controls(&self, ctx: &Context<Self>) -> Html {
-        let toggle_dip = ctx
-            .link()
-            .callback(|_| TeiViewerMsg::ToggleView(ViewType::Diplomatic));
-        let toggle_trad = ctx
-            .link()
-            .callback(|_| TeiViewerMsg::ToggleView(ViewType::Translation));
-        let toggle_both = ctx
-            .link()
-            .callback(|_| TeiViewerMsg::ToggleView(ViewType::Both));
-        let toggle_commentary = ctx.link().callback(|_| TeiViewerMsg::ToggleCommentary);
-        let zoom_in = ctx.link().callback(|_| TeiViewerMsg::UpdateImageScale(1.2));
-        let zoom_out = ctx.link().callback(|_| TeiViewerMsg::UpdateImageScale(0.8));
-        let toggle_meta = ctx.link().callback(|_| TeiViewerMsg::ToggleMetadata);
-        let toggle_legend = ctx.link().callback(|_| TeiViewerMsg::ToggleLegend);
-
-        html! {
-            <div class="controls-panel">
-                <div class="view-toggles">
-                    <button class={if self.active_view == ViewType::Diplomatic { "active" } else { "" }} onclick={toggle_dip}>{"Edición diplomática"}</button>
-                    <button class={if self.active_view == ViewType::Translation { "active" } else { "" }} onclick={toggle_trad}>{"Traducción"}</button>
-                    <button class={if self.active_view == ViewType::Both { "active" } else { "" }} onclick={toggle_both}>{"Ambas"}</button>
-                    <button class={if self.show_commentary { "active" } else { "" }} onclick={toggle_commentary}>{"Comentario"}</button>
-                </div>
-                <div class="image-controls">
-                    <button onclick={zoom_in}>{"🔍 +"}</button>
-                    <button onclick={zoom_out}>{"🔍 -"}</button>
-                    <span class="zoom-level">{format!("{}%", (self.image_scale * 100.0) as i32)}</span>
-                    <button onclick={toggle_meta} title="Toggle Metadata">{ if self.show_metadata_popup { "Ocultar metadata" } else { "Mostrar metadata" } }</button>
-                    <button onclick={toggle_legend} title="Toggle Color Legend">{ if self.show_legend { "🎨 Ocultar leyenda" } else { "🎨 Mostrar leyenda" } }</button>
-                </div>
-            </div>
-        }
-    }
-
-    fn render_image_panel(&self, ctx: &Context<Self>) -> Html {
-        if !self.show_image {
-            return html! {};
-        }
-        let doc = self.diplomatic.as_ref().or(self.translation.as_ref());
-        if let Some(doc) = doc {
-            // resolve image URL (robust): derive filename and prefer serving from project's images/ directory.
-            // If the TEI already contains a public path, use it as-is (but ensure it is an absolute path).
-            // If the facsimile image_url is empty, fall back to a page-based filename (e.g. "p1.jpg")
-            // derived from the current page prop.
-            let image_filename = if doc.facsimile.image_url.trim().is_empty() {
-                // use page-based fallback like "p1.jpg"
-                format!("p{}.jpg", ctx.props().page)
-            } else {
-                doc.facsimile
-                    .image_url
-                    .rsplit('/')
-                    .next()
-                    .unwrap_or(doc.facsimile.image_url.as_str())
-                    .to_string()
-            };
-
-            // Use natural image dimensions for display, fall back to declared if not loaded
-            let declared_w = doc.facsimile.width;
-            let declared_h = doc.facsimile.height;
-            let use_w = if self.image_nat_w > 0 {
-                self.image_nat_w
-            } else {
-                declared_w
-            };
-            let use_h = if self.image_nat_h > 0 {
-                self.image_nat_h
-            } else {
-                declared_h
-            };
-
-            // Build an absolute URL (leading slash) for browser requests.
-            // Cases handled:
-            // - If TEI provides a full http(s) URL, use it as-is.
-            // - If TEI provides a path starting with '/', use it as-is (already absolute).
-            // - If TEI provides a path starting with 'public/', prefix with '/' to make '/public/...'.
-            // - Otherwise, construct '/public/projects/{project}/images/{image_filename}'.
-            let image_url = {
-                let raw = doc.facsimile.image_url.trim();
-                if raw.is_empty() {
-                    // TEI didn't specify; use page-based fallback under project images
-                    resource_url(&format!(
-                        "public/projects/{}/images/{}",
-                        ctx.props().project,
-                        image_filename
-                    ))
-                } else if raw.starts_with("http://") || raw.starts_with("https://") {
-                    // external absolute URL, use directly
-                    raw.to_string()
-                } else if raw.starts_with('/') {
-                    // already absolute path, use directly
-                    raw.to_string()
-                } else if raw.starts_with("public/") {
-                    // make absolute by adding leading slash
-                    format!("/{}", raw)
-                } else {
-                    // treat as filename or relative path -> place under project images and make absolute
-                    resource_url(&format!(
-                        "public/projects/{}/images/{}",
-                        ctx.props().project,
-                        image_filename
-                    ))
+            TeiViewerMsg::HistoryMoved => {
+                // Entries of other pages arrive as new props instead
+                let same_page = ctx.link().route::<Route>().is_some_and(|route| {
+                    route.project() == Some(self.current_project.as_str())
+                        && route.page() == Some(self.current_page)
+                });
+                if !same_page || self.loading {
+                    return false;
                 }
-            };
-
-            let onwheel = ctx.link().callback(|e: WheelEvent| {
+                self.locked_zone = None;
+                self.restore_url_state();
+                true
+            }
+            TeiViewerMsg::PopOutText => {
+                let Some(window) = web_sys::window() else {
+                    return false;
+                };
+                let state = UrlState {
+                    view: Some(self.active_view.name()),
+                    zone: self.locked_zone.clone(),
+                    ..UrlState::default()
+                };
+                let url =
+                    window_sync::popout_url(&window.location().href().unwrap_or_default(), &state);
+                let _ = window.open_with_url_and_target_and_features(
+                    &url,
+                    window_sync::POPOUT_TARGET,
+                    "popup,width=720,height=900",
+                );
+                false
+            }
+            TeiViewerMsg::Synced(position) => {
+                self.followed = Some(position.clone());
+                if position.project != self.current_project || position.page != self.current_page {
+                    ctx.props().on_follow.emit((
+                        position.project,
+                        TourStop {
+                            page: position.page,
+                            zone: position.zone,
+                            line: None,
+                            caption: String::new(),
+                        },
+                    ));
+                    return false;
+                }
+                if position.zone == self.locked_zone {
+                    return false;
+                }
+                let index = position.zone.as_ref().and_then(|zone| {
+                    self.diplomatic
+                        .as_ref()?
+                        .lines
+                        .iter()
+                        .position(|l| &l.facs == zone)
+                });
+                if let Some(index) = index {
+                    self.scroll_to_line(index);
+                }
+                self.locked_zone = position.zone;
+                true
+            }
+            TeiViewerMsg::WindowResized => {
+                let is_mobile = Self::is_narrow_viewport();
+                if is_mobile != self.is_mobile {
+                    self.is_mobile = is_mobile;
+                    true
+                } else {
+                    false
+                }
+            }
+            TeiViewerMsg::ToggleContinuous => {
+                self.continuous = !self.continuous;
+                if self.continuous {
+                    // Preload one page so short pages can still be scrolled past
+                    self.load_next_continuous_page(ctx);
+                } else if self.centered_page.take().is_some() {
+                    self.image_nat_w = 0;
+                    self.image_nat_h = 0;
+                }
+                true
+            }
+            TeiViewerMsg::ContinuousScrolled(scroll_top, client_h, scroll_h, centered) => {
+                if scroll_top + client_h + CONTINUOUS_PRELOAD_MARGIN >= scroll_h {
+                    self.load_next_continuous_page(ctx);
+                }
+                let centered = centered.filter(|p| *p != ctx.props().page);
+                if centered != self.centered_page {
+                    self.centered_page = centered;
+                    // The facsimile changes; its natural size is re-measured on load
+                    self.image_nat_w = 0;
+                    self.image_nat_h = 0;
+                    self.locked_zone = None;
+                    self.hovered_zone = None;
+                    self.ruler_points.clear();
+                    true
+                } else {
+                    false
+                }
+            }
+            TeiViewerMsg::ContinuousPageLoaded(generation, page, res) => {
+                if generation != self.load_generation {
+                    return false;
+                }
+                self.continuous_loading = false;
+                match res {
+                    Ok(doc) => {
+                        let key = CacheKey::new(&self.current_project, page, "dip");
+                        self.doc_cache.insert(key, doc.clone());
+                        self.continuous_docs.push((page, Rc::new(doc)));
+                        true
+                    }
+                    Err(e) => {
+                        log::warn!("Failed to load page {} for continuous scroll: {}", page, e);
+                        false
+                    }
+                }
+            }
+            TeiViewerMsg::ComparisonLoaded(project, page, res) => {
+                let current = ctx.props().compare.as_ref();
+                if current.map(|c| (c.config.id.as_str(), c.page)) != Some((&project, Some(page))) {
+                    return false;
+                }
+                match res {
+                    Ok(doc) => {
+                        self.doc_cache
+                            .insert(CacheKey::new(&project, page, "dip"), doc.clone());
+                        self.compare_doc = Some(Rc::new(doc));
+                    }
+                    Err(e) => {
+                        log::warn!(
+                            "Failed to load {} page {} for comparison: {}",
+                            project,
+                            page,
+                            e
+                        );
+                        self.compare_doc = Some(Rc::new(TeiDocument::new()));
+                    }
+                }
+                true
+            }
+            TeiViewerMsg::JumpToLine(reference) => {
+                let doc = self.diplomatic.as_ref().or(self.translation.as_ref());
+                let Some((index, zone)) = doc.and_then(|d| {
+                    let index = d.find_line(&reference)?;
+                    Some((index, d.lines[index].facs.clone()))
+                }) else {
+                    self.goto_line_missing = true;
+                    return true;
+                };
+                self.goto_line_missing = false;
+                self.focus_line(ctx, index, zone);
+                true
+            }
+            TeiViewerMsg::FocusLine(index) => {
+                let Some(zone) = self
+                    .diplomatic
+                    .as_ref()
+                    .and_then(|d| d.lines.get(index))
+                    .map(|l| l.facs.clone())
+                else {
+                    return false;
+                };
+                self.focus_line(ctx, index, zone);
+                true
+            }
+            TeiViewerMsg::AudioTime(time) => {
+                let (Some(audio), Some(doc)) = (
+                    ctx.props()
+                        .page_info
+                        .as_ref()
+                        .and_then(|p| p.audio.as_ref()),
+                    &self.diplomatic,
+                ) else {
+                    return false;
+                };
+                let index = audio.line_at(time).and_then(|line| doc.find_line(line));
+                if index == self.audio_line {
+                    return false;
+                }
+                self.audio_line = index;
+                if let Some(index) = index {
+                    self.locked_zone = Some(doc.lines[index].facs.clone());
+                    self.scroll_to_line(index);
+                }
+                true
+            }
+            TeiViewerMsg::ToggleNotes => {
+                self.show_notes = !self.show_notes;
+                true
+            }
+            TeiViewerMsg::ToggleFeedback => {
+                self.show_feedback = !self.show_feedback;
+                true
+            }
+            TeiViewerMsg::SetFeedbackComment(comment) => {
+                self.feedback_comment = comment;
+                true
+            }
+            TeiViewerMsg::ToggleSourceEditor => {
+                if self.source_editor.take().is_some() {
+                    self.page_loads.remove(&PageLoad::Source);
+                    return true;
+                }
+                self.source_editor = Some(SourceEditor::default());
+                let path = ctx.props().config.get_diplomatic_path(self.current_page);
+                let link = ctx.link().clone();
+                let generation = self.load_generation;
+                if let Some(text) = local_document::read(&path) {
+                    link.send_message(TeiViewerMsg::SourceLoaded(generation, text));
+                    return true;
+                }
+                let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+                self.page_loads.insert(PageLoad::Source, load);
+                spawn_local(async move {
+                    let result = match text.await {
+                        Err(FetchError::Aborted { .. }) => return,
+                        result => result.map_err(|e| format!("Failed to load diplomatic: {}", e)),
+                    };
+                    link.send_message(TeiViewerMsg::SourceLoaded(generation, result));
+                });
+                true
+            }
+            TeiViewerMsg::SourceLoaded(generation, res) => {
+                if generation != self.load_generation || self.source_editor.is_none() {
+                    return false;
+                }
+                match res {
+                    Ok(text) => self.edit_source(ctx.props().lang, text),
+                    Err(e) => {
+                        if let Some(editor) = self.source_editor.as_mut() {
+                            editor.error = Some(e);
+                        }
+                    }
+                }
+                true
+            }
+            TeiViewerMsg::EditSource(text) => {
+                self.edit_source(ctx.props().lang, text);
+                true
+            }
+            TeiViewerMsg::ExportSource => {
+                let Some(editor) = &self.source_editor else {
+                    return false;
+                };
+                // The image credit goes along, as in the exported zones
+                let xml = match ctx.props().config.image_attribution(self.current_page) {
+                    Some(credit) => tei_source::with_image_credit(&editor.text, &credit),
+                    None => editor.text.clone(),
+                };
+                let blob = Blob::new_with_options(xml.as_str(), Some("application/xml"));
+                let url = ObjectUrl::from(blob);
+                download(&url, &self.source_file_name(ctx));
+                self._source_export = Some(url);
+                false
+            }
+            TeiViewerMsg::ToggleGlossary => {
+                self.show_glossary = !self.show_glossary;
+                if self.show_glossary {
+                    self.load_glossary(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::GlossaryLoaded(project, res) => {
+                if project != self.current_project {
+                    return false;
+                }
+                self.project_glossary = Some(match res {
+                    Ok(xml) => glossary::parse_entries(&xml),
+                    Err(e) => {
+                        log::debug!("No project glossary: {}", e);
+                        Vec::new()
+                    }
+                });
+                true
+            }
+            TeiViewerMsg::ProjectPageLoaded(project, page, res) => {
+                if project != self.current_project {
+                    return false;
+                }
+                self.corpus_pending = self.corpus_pending.saturating_sub(1);
+                match res {
+                    Ok(doc) => {
+                        self.doc_cache
+                            .insert(CacheKey::new(&project, page, "dip"), doc.clone());
+                        self.corpus.insert(page, Rc::new(doc));
+                    }
+                    Err(e) => log::warn!("Failed to scan page {}: {}", page, e),
+                }
+                true
+            }
+            TeiViewerMsg::ToggleToc => {
+                self.show_toc = !self.show_toc;
+                if self.show_toc && ctx.props().outline.is_empty() {
+                    self.load_corpus(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::OpenTocEntry((page, line)) => {
+                if page != self.current_page {
+                    ctx.props().on_open_line.emit(TourStop {
+                        page,
+                        zone: None,
+                        line,
+                        caption: String::new(),
+                    });
+                } else if let Some(line) = line {
+                    ctx.link().send_message(TeiViewerMsg::JumpToLine(line));
+                }
+                false
+            }
+            TeiViewerMsg::ToggleIndexLocorum => {
+                self.show_index_locorum = !self.show_index_locorum;
+                if self.show_index_locorum {
+                    self.load_corpus(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::ToggleMap => {
+                self.show_map = !self.show_map;
+                if self.show_map {
+                    self.load_places(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::ToggleTimeline => {
+                self.show_timeline = !self.show_timeline;
+                if self.show_timeline {
+                    self.load_corpus(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::PlacesLoaded(project, res) => {
+                if project != self.current_project {
+                    return false;
+                }
+                self.project_places =
+                    Some(match res.and_then(|json| places::parse_gazetteer(&json)) {
+                        Ok(entries) => entries,
+                        Err(e) => {
+                            log::debug!("No project places: {}", e);
+                            Vec::new()
+                        }
+                    });
+                true
+            }
+            TeiViewerMsg::ToggleWordFrequency => {
+                self.show_word_frequency = !self.show_word_frequency;
+                if self.show_word_frequency && self.word_frequency_project {
+                    self.load_corpus(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::SetWordFrequencyScope(project) => {
+                self.word_frequency_project = project;
+                if project {
+                    self.load_corpus(ctx);
+                }
+                true
+            }
+            TeiViewerMsg::SelectTerm(term) => {
+                if !self.show_glossary {
+                    self.show_glossary = true;
+                    self.load_glossary(ctx);
+                }
+                self.glossary_term = Some(term);
+                true
+            }
+            TeiViewerMsg::SelectGlossEntry(index) => {
+                let entries = self.glossary_entries(ctx);
+                let Some(entry) = entries.get(index) else {
+                    return false;
+                };
+                self.glossary_term = Some((entry.id.clone(), entry.term.clone()));
+                true
+            }
+            TeiViewerMsg::OpenProjectLine((page, line)) => {
+                if page == self.current_page {
+                    ctx.link().send_message(TeiViewerMsg::FocusLine(line));
+                    return false;
+                }
+                let label = self.corpus.get(&page).and_then(|d| d.line_label(line));
+                ctx.props().on_open_line.emit(TourStop {
+                    page,
+                    zone: None,
+                    line: label,
+                    caption: String::new(),
+                });
+                false
+            }
+            TeiViewerMsg::ToggleZoneEditor => {
+                self.zone_editor = match (&self.zone_editor, &self.diplomatic) {
+                    (None, Some(doc)) => Some(ZoneEditor::new(&doc.facsimile, &doc.lines)),
+                    _ => None,
+                };
+                true
+            }
+            TeiViewerMsg::ZoneEditPress(index, vertex, x, y) => {
+                let point = self.client_to_facsimile(ctx, x, y);
+                let Some(editor) = self.zone_editor.as_mut() else {
+                    return false;
+                };
+                editor.selected = Some(index);
+                editor.grab = match (vertex, point) {
+                    (Some(vertex), _) => Some(Grab::Vertex(index, vertex)),
+                    (None, Some(point)) => Some(Grab::Zone(index, point)),
+                    (None, None) => None,
+                };
+                true
+            }
+            TeiViewerMsg::ZoneEditRemovePoint(index, vertex) => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.remove_point(index, vertex);
+                }
+                true
+            }
+            TeiViewerMsg::ZoneEditSelect(index) => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.selected = Some(index);
+                }
+                true
+            }
+            TeiViewerMsg::ZoneEditAdd => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.add_zone();
+                }
+                true
+            }
+            TeiViewerMsg::ZoneEditRemove(index) => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.remove_zone(index);
+                }
+                true
+            }
+            TeiViewerMsg::ZoneEditLink((index, line)) => {
+                if let Some(editor) = self.zone_editor.as_mut() {
+                    editor.link_line(index, line);
+                }
+                true
+            }
+            TeiViewerMsg::ExportZones => {
+                let (Some(editor), Some(doc)) = (&self.zone_editor, &self.diplomatic) else {
+                    return false;
+                };
+                let credit = ctx.props().config.image_attribution(self.current_page);
+                let xml = editor.to_xml(&doc.lines, credit.as_deref());
+                let blob = Blob::new_with_options(xml.as_str(), Some("application/xml"));
+                let url = ObjectUrl::from(blob);
+                download(&url, &format!("p{}_facsimile.xml", self.current_page));
+                self._zones_export = Some(url);
+                false
+            }
+            TeiViewerMsg::SaveNote(draft) => {
+                let existing = draft
+                    .id
+                    .as_ref()
+                    .and_then(|id| self.annotations.notes.iter().find(|n| &n.id == id))
+                    .cloned();
+                let note = match existing {
+                    Some(note) => Annotation {
+                        text: draft.text,
+                        color: draft.color,
+                        ..note
+                    },
+                    None => {
+                        let Some(line) = self.selected_line() else {
+                            return false;
+                        };
+                        let created = js_sys::Date::now();
+                        let zone = self.locked_zone.clone().unwrap_or_default();
+                        let (region, image) = self.zone_region(ctx, &zone);
+                        Annotation {
+                            id: format!("n{}", created as u64),
+                            page: self.current_page,
+                            line,
+                            zone,
+                            quote: self.note_quote.take(),
+                            color: draft.color,
+                            text: draft.text,
+                            created,
+                            region,
+                            image,
+                        }
+                    }
+                };
+                self.annotations.upsert(note);
+                self.annotations.save();
+                true
+            }
+            TeiViewerMsg::DeleteNote(id) => {
+                if !self.annotations.remove(&id) {
+                    return false;
+                }
+                self.annotations.save();
+                true
+            }
+            TeiViewerMsg::ExportNotes => {
+                let blob = Blob::new_with_options(
+                    self.annotations.to_json().as_str(),
+                    Some("application/json"),
+                );
+                let url = ObjectUrl::from(blob);
+                download(&url, &format!("notas-{}.json", self.current_project));
+                self._notes_export = Some(url);
+                false
+            }
+            TeiViewerMsg::ExportWebAnnotations => {
+                // The locked zone goes along as a highlight, so a selection
+                // can be shared without writing a note for it
+                let selection = self.locked_zone.clone().and_then(|zone| {
+                    let line = self.selected_line()?;
+                    let (region, image) = self.zone_region(ctx, &zone);
+                    Some(Annotation {
+                        id: format!("p{}-{}", self.current_page, zone),
+                        page: self.current_page,
+                        line,
+                        zone,
+                        quote: self.note_quote.clone(),
+                        color: String::new(),
+                        text: String::new(),
+                        created: js_sys::Date::now(),
+                        region,
+                        image,
+                    })
+                });
+                let collection = web_annotation::collection(
+                    &self.annotations,
+                    &ctx.props().config,
+                    selection.as_ref(),
+                );
+                let json = serde_json::to_string_pretty(&collection).unwrap_or_default();
+                let blob = Blob::new_with_options(json.as_str(), Some("application/ld+json"));
+                let url = ObjectUrl::from(blob);
+                download(
+                    &url,
+                    &format!("anotaciones-{}.jsonld", self.current_project),
+                );
+                self._notes_export = Some(url);
+                false
+            }
+            TeiViewerMsg::ImportNotes(file) => {
+                let link = ctx.link().clone();
+                let file = gloo::file::File::from(file);
+                self._notes_reader = Some(gloo::file::callbacks::read_as_text(&file, move |res| {
+                    link.send_message(TeiViewerMsg::NotesImported(res.map_err(|e| e.to_string())))
+                }));
+                false
+            }
+            TeiViewerMsg::NotesImported(res) => {
+                self._notes_reader = None;
+                let result = res.and_then(|json| self.annotations.import_json(&json));
+                self.notes_status = Some(match result {
+                    Ok(count) => {
+                        self.annotations.save();
+                        ctx.props().lang.tf("{} notas importadas", &[&count])
+                    }
+                    Err(e) => ctx
+                        .props()
+                        .lang
+                        .tf("No se pudieron importar las notas: {}", &[&e]),
+                });
+                true
+            }
+            TeiViewerMsg::ApplyFocus => {
+                let Some(stop) = self.pending_focus.take() else {
+                    return false;
+                };
+                if let Some(zone) = stop.zone {
+                    self.locked_zone = Some(zone.clone());
+                    self.zoom_to_zone(ctx, &zone);
+                    true
+                } else if let Some(line) = stop.line {
+                    ctx.link().send_message(TeiViewerMsg::JumpToLine(line));
+                    false
+                } else {
+                    false
+                }
+            }
+            TeiViewerMsg::SetSearchQuery(query) => {
+                self.search_query = query;
+                self.refresh_search();
+                true
+            }
+            TeiViewerMsg::ToggleSearchDiacritics => {
+                self.search_ignore_diacritics = !self.search_ignore_diacritics;
+                self.refresh_search();
+                true
+            }
+            TeiViewerMsg::SelectTab(tab) => {
+                self.mobile_tab = tab;
+                true
+            }
+            TeiViewerMsg::SwipeStart(x, y) => {
+                // The image (panel or tab) uses drags for panning
+                if !self.is_mobile || self.mobile_tab != MobileTab::Image {
+                    self.swipe_origin = Some((x, y));
+                }
+                false
+            }
+            TeiViewerMsg::SwipeEnd(x, y) => {
+                let Some((x0, y0)) = self.swipe_origin.take() else {
+                    return false;
+                };
+                let step = swipe_step(x - x0, y - y0, SWIPE_MIN_DISTANCE);
+                // Column layouts turn pages; the tabbed layout switches tabs
+                if !self.is_mobile {
+                    if step != 0 {
+                        self.pending_page_turn = Some(step);
+                        ctx.props().on_turn_page.emit(step);
+                    }
+                    return false;
+                }
+                let tab = self.mobile_tab.offset(step);
+                let shown = match ctx.props().panels {
+                    Panels::All => true,
+                    Panels::Image => tab == MobileTab::Image,
+                    Panels::Text => tab != MobileTab::Image,
+                };
+                if tab != self.mobile_tab && shown {
+                    self.mobile_tab = tab;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        if let Some(start) = self.render_start.take() {
+            let page = format!("{} · {}", self.current_project, self.current_page);
+            perf::rendered(&page, Some(start));
+        }
+        if let Some(index) = self.pending_scroll_line.take() {
+            if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+                for panel in [".diplomatic-panel", ".translation-panel"] {
+                    let selector = format!("{} .line[data-line=\"{}\"]", panel, index);
+                    if let Ok(Some(line)) = document.query_selector(&selector) {
+                        line.scroll_into_view();
+                    }
+                }
+            }
+        }
+        // Track the image panel size so deep-zoom tiles cover the viewport
+        if let Some(el) = self.image_container_ref.cast::<web_sys::HtmlElement>() {
+            let w = el.client_width() as f64;
+            let h = el.client_height() as f64;
+            if w != self.viewport_w || h != self.viewport_h {
+                ctx.link()
+                    .send_message(TeiViewerMsg::ViewportMeasured(w, h));
+            }
+        }
+        // The project's fit, once the facsimile and its panel have a size
+        if !self.loading && self.viewport_w > 0.0 && self.viewport_h > 0.0 {
+            let laid_out = self
+                .image_doc(ctx)
+                .is_some_and(|doc| self.image_layout_size(ctx, doc).0 > 0);
+            if let Some(fit) = self.pending_fit.filter(|_| laid_out) {
+                self.pending_fit = None;
+                ctx.link().send_message(TeiViewerMsg::FitImage(fit));
+            }
+        }
+        // Remember how the reader leaves this page once it has loaded
+        if !self.loading {
+            let view = self.page_view();
+            if self.saved_view.as_ref() != Some(&view) {
+                persistence::save_page_view(&self.current_project, self.current_page, &view);
+                self.saved_view = Some(view);
+            }
+            let state = self.current_url_state();
+            if state != self.url_state {
+                // Switching view or locking a line gets its own history
+                // entry so back retraces it; zoom and pan only update the
+                // entry, as does the first state written for a page
+                let significant = self.url_state.view.is_some()
+                    && (state.view != self.url_state.view || state.zone != self.url_state.zone);
+                if significant {
+                    url_state::push(&state);
+                } else {
+                    url_state::replace(&state);
+                }
+                self.url_state = state;
+            }
+            // Announce moves made here, not those following another window
+            let position = Position {
+                project: self.current_project.clone(),
+                page: self.current_page,
+                zone: self.locked_zone.clone(),
+            };
+            if self.sync_position.as_ref() != Some(&position) {
+                if let Some(sync) = self
+                    .window_sync
+                    .as_ref()
+                    .filter(|_| self.followed.as_ref() != Some(&position))
+                {
+                    sync.post(&position);
+                }
+                self.sync_position = Some(position);
+            }
+        }
+        if self.pending_focus.is_some() && !self.loading {
+            ctx.link().send_message(TeiViewerMsg::ApplyFocus);
+        }
+        // Further editions are fetched once shown, whichever way their view
+        // was chosen (toggle, link, the view the page was left in)
+        if let ViewType::Edition(id) = &self.active_view {
+            if self.edition_requested.as_ref() != Some(id) {
+                let id = id.clone();
+                self.edition_requested = Some(id.clone());
+                self.load_edition(ctx, id);
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        // Each panel shows a placeholder until its own resource arrives
+        if let Some(err) = &self.error {
+            return html! {
+                <div class="error"><p>{ctx.props().lang.tf("Error: {}", &[err])}</p></div>
+            };
+        }
+
+        // Set CSS custom property for dynamic column sizing
+        if let Some(window) = web_sys::window() {
+            if let Some(document) = window.document() {
+                if let Some(body) = document.body() {
+                    let _ = body.style().set_property(
+                        "--image-panel-width",
+                        &format!("{}%", self.image_panel_width),
+                    );
+                    let _ = body.style().set_property(
+                        "--image-panel-height",
+                        &format!("{}%", self.image_panel_height),
+                    );
+                }
+            }
+        }
+
+        if self.is_mobile {
+            return self.render_mobile(ctx);
+        }
+
+        let panels = ctx.props().panels;
+        html! {
+            <div class="tei-viewer-container">
+                { self.render_controls(ctx) }
+                { self.render_legend(ctx) }
+                { self.render_legend_style(ctx) }
+                <div class={classes!(
+                    "viewer-content",
+                    (self.layout == Layout::Stacked).then_some("layout-stacked"),
+                    (panels != Panels::All).then_some("single-panel"),
+                )}>
+                    if panels != Panels::Text {
+                        { self.render_image_panel(ctx) }
+                    }
+                    if panels == Panels::All {
+                        { self.render_splitter(ctx) }
+                    }
+                    if panels != Panels::Image {
+                        { self.render_text_panels(ctx) }
+                    }
+                    { self.render_metadata_popup(ctx) }
+                    { self.render_commentary_popup(ctx) }
+                    { self.render_feedback_popup(ctx) }
+                </div>
+            </div>
+        }
+    }
+}
+
+impl TeiViewer {
+    /// The enclosing error boundary, if any
+    fn error_sink(ctx: &Context<Self>) -> Option<ErrorSink> {
+        ctx.link()
+            .context::<ErrorSink>(Callback::noop())
+            .map(|(sink, _)| sink)
+    }
+
+    /// Image panel share along the current layout's split axis
+    fn panel_size(&self) -> f64 {
+        match self.layout {
+            Layout::SideBySide => self.image_panel_width,
+            Layout::Stacked => self.image_panel_height,
+        }
+    }
+
+    fn set_panel_size(&mut self, size: f64) {
+        let size = size.clamp(MIN_PANEL_WIDTH, MAX_PANEL_WIDTH);
+        match self.layout {
+            Layout::SideBySide => self.image_panel_width = size,
+            Layout::Stacked => self.image_panel_height = size,
+        }
+    }
+
+    fn default_zoom(ctx: &Context<Self>) -> Option<f32> {
+        ctx.props().default_view.zoom.map(|z| z.clamp(0.2, 8.0))
+    }
+
+    /// Text view the manifest opens the project in, if any.
+    fn default_view_type(ctx: &Context<Self>) -> Option<ViewType> {
+        let view = ctx.props().default_view.view.as_deref()?;
+        ViewType::from_str(view).or_else(|| {
+            log::warn!("Unknown default view in the manifest: {}", view);
+            None
+        })
+    }
+
+    fn page_view(&self) -> PageView {
+        PageView {
+            scale: self.image_scale,
+            offset_x: self.image_offset_x,
+            offset_y: self.image_offset_y,
+            view: self.active_view.name(),
+            show_legend: self.show_legend,
+        }
+    }
+
+    /// Bring back the zoom, view and legend the reader last used on the
+    /// current page.
+    fn restore_page_view(&mut self) {
+        let saved = persistence::load_page_view(&self.current_project, self.current_page);
+        if let Some(view) = &saved {
+            self.pending_fit = None;
+            self.image_scale = view.scale.clamp(0.2, 8.0);
+            self.image_offset_x = view.offset_x;
+            self.image_offset_y = view.offset_y;
+            if let Some(active) = ViewType::from_str(&view.view) {
+                self.active_view = active;
+            }
+            self.show_legend = view.show_legend;
+        }
+        self.saved_view = saved;
+    }
+
+    /// Scroll a line into view in the text panels, lock it and zoom the
+    /// facsimile to its zone.
+    fn focus_line(&mut self, ctx: &Context<Self>, index: usize, zone: String) {
+        self.scroll_to_line(index);
+        self.locked_zone = Some(zone.clone());
+        self.zoom_to_zone(ctx, &zone);
+    }
+
+    /// Whether the manifest lists `edition` ("dip" or "trad") for the page
+    /// on screen; those it says are missing are not requested. Files
+    /// registered for the page, such as the stub edition of an image-only
+    /// IIIF canvas, are always read.
+    fn listed(&self, ctx: &Context<Self>, edition: &str) -> bool {
+        let Some(info) = ctx
+            .props()
+            .page_info
+            .as_ref()
+            .filter(|p| p.number == self.current_page)
+        else {
+            return true;
+        };
+        let has = match edition {
+            "dip" => info.has_diplomatic,
+            _ => info.has_translation,
+        };
+        has || ctx
+            .props()
+            .config
+            .has_file(&ProjectConfig::page_file(self.current_page, edition))
+    }
+
+    /// Edition of the translation to load for `page` ("trad", or "trad_en"
+    /// for one of several languages), per the manifest and `lang`.
+    fn translation_edition_of(ctx: &Context<Self>, page: u32, lang: Option<&str>) -> String {
+        ctx.props()
+            .page_info
+            .as_ref()
+            .filter(|p| p.number == page)
+            .map_or_else(|| "trad".to_string(), |p| p.translation_edition(lang))
+    }
+
+    fn translation_edition(&self, ctx: &Context<Self>) -> String {
+        Self::translation_edition_of(ctx, self.current_page, self.translation_lang.as_deref())
+    }
+
+    /// `srcset` of the resolutions the manifest declares for the image of
+    /// `page`.
+    fn image_srcset(ctx: &Context<Self>, page: &PageInfo) -> String {
+        let config = &ctx.props().config;
+        page.image_variants
+            .iter()
+            .map(|v| {
+                let url = local_document::resolve(&config.file_url(&v.path));
+                format!("{} {}w", config.sources_for(&url).first(), v.width)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Start fetching the next page's facsimile, once, so that turning the
+    /// page shows it at once.
+    fn preload_next_image(&mut self, ctx: &Context<Self>) {
+        if self.next_image.is_some() || !ctx.props().settings.prefetch_next {
+            return;
+        }
+        let config = &ctx.props().config;
+        let Some(next) = config.next_image_page(self.current_page) else {
+            return;
+        };
+        let Ok(image) = web_sys::HtmlImageElement::new() else {
+            return;
+        };
+        if next.max_variant_width().is_some() {
+            // The variant the browser will pick for a page fitted to the panel
+            image.set_sizes(&format!("{}px", self.viewport_w.ceil().max(1.0)));
+            image.set_srcset(&Self::image_srcset(ctx, next));
+        }
+        // A file named by the next page's TEI is not known before it loads
+        let src = config.get_facsimile_path(next.number, "");
+        image.set_src(config.sources_for(&local_document::resolve(&src)).first());
+        self.next_image = Some(image);
+    }
+
+    /// Load the page's diplomatic edition, translation and commentary
+    /// together: the page is shown once all three have come back, each one
+    /// empty if it failed.
+    fn load_page(&mut self, ctx: &Context<Self>) {
+        let diplomatic = self.load_document(ctx, "dip");
+        let translation = self.load_document(ctx, &self.translation_edition(ctx));
+        let commentary = self.load_commentary(ctx);
+        let link = ctx.link().clone();
+        let generation = self.load_generation;
+        spawn_local(async move {
+            let (diplomatic, translation, commentary) =
+                futures::join!(diplomatic, translation, commentary);
+            let data = PageData {
+                diplomatic,
+                translation,
+                commentary,
+            };
+            link.send_message(TeiViewerMsg::PageLoaded(generation, Box::new(data)));
+        });
+    }
+
+    /// Load the page's translation alone, in the reader's language.
+    fn load_translation(&mut self, ctx: &Context<Self>) {
+        let translation = self.load_document(ctx, &self.translation_edition(ctx));
+        let link = ctx.link().clone();
+        let generation = self.load_generation;
+        spawn_local(async move {
+            link.send_message(TeiViewerMsg::TranslationLoaded(
+                generation,
+                translation.await,
+            ));
+        });
+    }
+
+    /// The page's `edition` ("dip", or the translation's): from the cache
+    /// when it was parsed recently, from the reader's files when they stand
+    /// for the project, else fetched.
+    fn load_document(
+        &mut self,
+        ctx: &Context<Self>,
+        edition: &str,
+    ) -> LocalBoxFuture<'static, Result<TeiDocument, String>> {
+        let path = ctx
+            .props()
+            .config
+            .get_edition_path(self.current_page, edition);
+        let key = CacheKey::new(&self.current_project, self.current_page, edition);
+        if let Some(doc) = self.doc_cache.get(&key) {
+            perf::remembered(&path);
+            return future::ready(Ok(doc)).boxed_local();
+        }
+        let (kind, name) = match edition {
+            "dip" => ("dip", "diplomatic"),
+            _ => ("trad", "translation"),
+        };
+        if !self.listed(ctx, kind) {
+            let e = format!("No {} in the manifest", name);
+            return future::ready(Err(e)).boxed_local();
+        }
+        diagnostics::set_resource(&path);
+        if let Some(xml) = local_document::read(&path) {
+            let result = xml.and_then(|xml| crate::tei_parser::parse_tei_xml(&xml));
+            return future::ready(result).boxed_local();
+        }
+        let sink = Self::error_sink(ctx);
+        let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+        self.page_loads
+            .insert(PageLoad::Text(edition.to_string()), load);
+        async move {
+            match text.await {
+                Ok(xml) => {
+                    offline::keep_text(&path, &xml);
+                    let start = perf::start();
+                    let doc = crate::tei_parser::parse_tei_xml(&xml);
+                    perf::parsed(&path, start);
+                    doc
+                }
+                Err(
+                    e @ (FetchError::NotFound { .. }
+                    | FetchError::Decode { .. }
+                    | FetchError::Aborted { .. }),
+                ) => Err(format!("Failed to load {}: {}", name, e)),
+                Err(e) => {
+                    // The request never got a useful answer (offline,
+                    // server down): unlike a missing file, nothing on
+                    // the page can be shown
+                    let message = format!("Failed to load {}: {}", name, e);
+                    if let Some(sink) = sink {
+                        sink.report(Some(&path), &message);
+                    }
+                    Err(message)
+                }
+            }
+        }
+        .boxed_local()
+    }
+
+    /// The page's commentary, as HTML.
+    fn load_commentary(
+        &mut self,
+        ctx: &Context<Self>,
+    ) -> LocalBoxFuture<'static, Result<String, String>> {
+        let path = ctx.props().config.get_commentary_path(self.current_page);
+        if let Some(text) = local_document::read(&path) {
+            let html = text.map(|text| commentary::to_html(&text, &path));
+            return future::ready(html).boxed_local();
+        }
+        let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+        self.page_loads.insert(PageLoad::Commentary, load);
+        async move {
+            match text.await {
+                Ok(text) => {
+                    offline::keep_text(&path, &text);
+                    // Markdown and TEI commentaries are shown as HTML
+                    Ok(commentary::to_html(&text, &path))
+                }
+                Err(e) => Err(format!("Failed to load commentary: {}", e)),
+            }
+        }
+        .boxed_local()
+    }
+
+    fn apply_diplomatic(&mut self, res: Result<TeiDocument, String>) {
+        match res {
+            Ok(doc) => {
+                self.cache_document("dip", &doc);
+                self.diplomatic = Some(Rc::new(doc));
+                self.progress.diplomatic = Progress::Done;
+            }
+            Err(e) => {
+                // If fetching/parsing fails (for example the XML file is missing or a network error),
+                // treat it as an empty document so the viewer can still display the image and UI.
+                log::warn!("Failed to load diplomatic: {:?}", e);
+                self.progress.diplomatic = Progress::Failed;
+                self.diplomatic = Some(Rc::new(TeiDocument::new()));
+            }
+        }
+        self.refresh_search();
+        if self.show_metadata_popup {
+            self.metadata_selected = Some(ViewType::Diplomatic);
+        }
+    }
+
+    fn apply_translation(&mut self, ctx: &Context<Self>, res: Result<TeiDocument, String>) {
+        match res {
+            Ok(doc) => {
+                self.cache_document(&self.translation_edition(ctx), &doc);
+                self.translation = Some(Rc::new(doc));
+                self.progress.translation = Progress::Done;
+            }
+            Err(e) => {
+                // If translation fetch/parsing fails, treat as empty translation so images still show.
+                log::warn!("Failed to load translation: {:?}", e);
+                self.progress.translation = Progress::Failed;
+                self.translation = Some(Rc::new(TeiDocument::new()));
+            }
+        }
+        self.refresh_search();
+        if self.show_metadata_popup {
+            self.metadata_selected = Some(if self.diplomatic.is_some() {
+                ViewType::Diplomatic
+            } else {
+                ViewType::Translation
+            });
+        }
+    }
+
+    fn apply_commentary(&mut self, ctx: &Context<Self>, res: Result<String, String>) {
+        match res {
+            Ok(html) => {
+                // Markup from another host may carry scripts
+                let html = if ctx.props().config.is_remote() {
+                    remote_project::sanitize(&html)
+                } else {
+                    html
+                };
+                self.commentary = Some(html);
+                self.progress.commentary = Progress::Done;
+            }
+            Err(e) => {
+                log::warn!("Failed to load commentary: {:?}", e);
+                self.progress.commentary = Progress::Failed;
+                // Leave it unset: commentary_html shows the fallback message
+                self.commentary = None;
+            }
+        }
+        // Auto-show on first load: the commentary, or the fallback message
+        if self.commentary_first_load {
+            self.show_commentary = true;
+        }
+    }
+
+    /// Heading of the translation panel, with a choice of language when
+    /// the page has translations in several.
+    fn render_translation_heading(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let languages = ctx
+            .props()
+            .page_info
+            .as_ref()
+            .filter(|p| p.number == self.current_page && p.translations.len() > 1)
+            .map(|p| p.translations.clone());
+        let Some(languages) = languages else {
+            return html! { <h3>{ lang.t("Traducción") }</h3> };
+        };
+        let current = self.translation_edition(ctx);
+        let onchange = ctx.link().batch_callback(|e: Event| {
+            e.target_dyn_into::<web_sys::HtmlSelectElement>()
+                .map(|select| TeiViewerMsg::SetTranslationLang(select.value()))
+        });
+        html! {
+            <div class="translation-heading">
+                <h3>{ lang.t("Traducción") }</h3>
+                <select {onchange} title={lang.t("Idioma de la traducción")} aria-label={lang.t("Idioma de la traducción")}>
+                    { for languages.iter().map(|code| html! {
+                        <option value={code.clone()} selected={current == format!("trad_{}", code)}>
+                            { code.to_uppercase() }
+                        </option>
+                    }) }
+                </select>
+            </div>
+        }
+    }
+
+    /// File name of the page's diplomatic edition, per the project's
+    /// file patterns.
+    fn source_file_name(&self, ctx: &Context<Self>) -> String {
+        let file = ProjectConfig::page_file(self.current_page, "dip");
+        let path = ctx
+            .props()
+            .config
+            .file_patterns
+            .resolve(&file)
+            .unwrap_or(file);
+        path.rsplit('/').next().unwrap_or(&path).to_string()
+    }
+
+    fn scroll_to_line(&mut self, index: usize) {
+        // Move the line windows so the line is rendered, then scroll it
+        // into view once it is in the DOM
+        let top = index as f64 * LINE_HEIGHT_ESTIMATE;
+        self.dip_scroll.0 = top;
+        self.trad_scroll.0 = top;
+        self.pending_scroll_line = Some(index);
+    }
+
+    /// Playback controls for the page's audio reading, if it has one.
+    fn render_audio(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let Some(audio) = ctx
+            .props()
+            .page_info
+            .as_ref()
+            .and_then(|p| p.audio.as_ref())
+        else {
+            return html! {};
+        };
+        let ontimeupdate = ctx.link().batch_callback(|e: Event| {
+            e.target_dyn_into::<HtmlMediaElement>()
+                .map(|media| TeiViewerMsg::AudioTime(media.current_time()))
+        });
+        html! {
+            <audio
+                class="page-audio"
+                controls=true
+                preload="metadata"
+                src={local_document::resolve(&ctx.props().config.file_url(&audio.path))}
+                aria-label={lang.t("Lectura en voz alta de la página")}
+                {ontimeupdate}
+            />
+        }
+    }
+
+    /// Diplomatic line of the locked zone, which new notes attach to.
+    fn selected_line(&self) -> Option<usize> {
+        let zone = self.locked_zone.as_ref()?;
+        self.diplomatic
+            .as_ref()?
+            .lines
+            .iter()
+            .position(|l| &l.facs == zone)
+    }
+
+    /// Bounding box of a diplomatic zone and the absolute URL of the image
+    /// it lies on, for annotations that target the facsimile.
+    fn zone_region(&self, ctx: &Context<Self>, zone: &str) -> (Option<[u32; 4]>, Option<String>) {
+        let Some(doc) = self.diplomatic.as_ref() else {
+            return (None, None);
+        };
+        let region = doc.facsimile.zones.get(zone).map(|z| {
+            let (x0, y0, x1, y1) = z.get_bounding_box();
+            [x0, y0, x1 - x0, y1 - y0]
+        });
+        let image = absolute_url(
+            &ctx.props()
+                .config
+                .get_facsimile_path(self.current_page, &doc.facsimile.image_url),
+        );
+        (region, Some(image))
+    }
+
+    /// Star button for the locked line, or for the page when no line is
+    /// locked.
+    fn render_bookmark_button(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let doc = self.diplomatic.as_ref();
+        let line = self
+            .selected_line()
+            .and_then(|index| Some((index, doc?.line_label(index)?)));
+        let starred = ctx.props().bookmarks.contains(
+            &self.current_project,
+            self.current_page,
+            line.as_ref().map(|(_, label)| label.as_str()),
+        );
+        let page_label = ctx
+            .props()
+            .page_info
+            .as_ref()
+            .map(|p| p.label.clone())
+            .unwrap_or_else(|| self.current_page.to_string());
+        let bookmark = Bookmark {
+            project: self.current_project.clone(),
+            page: self.current_page,
+            page_label,
+            line: line.as_ref().map(|(_, label)| label.clone()),
+            excerpt: line.as_ref().and_then(|(index, _)| {
+                let text = doc?.lines[*index].text(Reading::Diplomatic);
+                Some(text.trim().chars().take(60).collect())
+            }),
+            created: js_sys::Date::now(),
+        };
+        let onclick = ctx
+            .props()
+            .on_toggle_bookmark
+            .reform(move |_| bookmark.clone());
+        let label = match (&line, starred) {
+            (Some((_, n)), true) => lang.tf("★ Línea {}", &[n]),
+            (Some((_, n)), false) => lang.tf("☆ Marcar línea {}", &[n]),
+            (None, true) => lang.t("★ Página marcada").to_string(),
+            (None, false) => lang.t("☆ Marcar página").to_string(),
+        };
+        html! {
+            <button
+                class={classes!("bookmark-toggle", starred.then_some("active"))}
+                {onclick}
+                title={lang.t("Añadir a (o quitar de) sus marcadores")}
+                aria-pressed={starred.to_string()}
+            >
+                { label }
+            </button>
+        }
+    }
+
+    /// Take edited TEI source: re-parse it and, when it parses, show it in
+    /// place of the diplomatic page. A parse error keeps the last good
+    /// rendering on screen.
+    fn edit_source(&mut self, lang: Lang, text: String) {
+        let Some(editor) = self.source_editor.as_mut() else {
+            return;
+        };
+        match crate::tei_parser::parse_tei_xml(&text) {
+            Ok(doc) => {
+                editor.error = None;
+                editor.warnings = tei_source::warnings(lang, &doc);
+                self.diplomatic = Some(Rc::new(doc));
+                self.refresh_search();
+            }
+            Err(e) => editor.error = Some(e),
+        }
+        if let Some(editor) = self.source_editor.as_mut() {
+            editor.text = text;
+        }
+    }
+
+    fn render_source_editor(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let Some(editor) = &self.source_editor else {
+            return html! {};
+        };
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let value = e
+                .target_dyn_into::<HtmlTextAreaElement>()
+                .map(|t| t.value())
+                .unwrap_or_default();
+            TeiViewerMsg::EditSource(value)
+        });
+        // Keep the highlighted copy under the textarea aligned with it
+        let onscroll = {
+            let highlight_ref = self.source_highlight_ref.clone();
+            Callback::from(move |e: Event| {
+                let (Some(input), Some(highlight)) = (
+                    e.target_dyn_into::<web_sys::Element>(),
+                    highlight_ref.cast::<web_sys::Element>(),
+                ) else {
+                    return;
+                };
+                highlight.set_scroll_top(input.scroll_top());
+                highlight.set_scroll_left(input.scroll_left());
+            })
+        };
+        let on_export = ctx.link().callback(|_| TeiViewerMsg::ExportSource);
+
+        html! {
+            <div class="text-panel source-panel">
+                <h3>{ lang.tf("Fuente TEI · {}", &[&self.source_file_name(ctx)]) }</h3>
+                <div class="source-editor">
+                    <pre class="source-highlight" ref={self.source_highlight_ref.clone()} aria-hidden="true">
+                        { for tei_source::highlight(&editor.text).into_iter().map(|(token, text)| html! {
+                            <span class={token.class()}>{ text }</span>
+                        }) }
+                        // A trailing newline in the textarea needs a line to scroll to
+                        { "\n" }
+                    </pre>
+                    <textarea
+                        class="source-input"
+                        spellcheck="false"
+                        aria-label={lang.t("Código TEI de la página")}
+                        value={editor.text.clone()}
+                        {oninput}
+                        {onscroll}
+                    />
+                </div>
+                { match &editor.error {
+                    Some(error) => html! { <p class="source-error" role="alert">{ error.clone() }</p> },
+                    None if editor.text.is_empty() => html! { <p class="note-hint">{ lang.t("Cargando…") }</p> },
+                    None => html! { <p class="source-ok">{ lang.t("✓ El documento se analiza sin errores") }</p> },
+                } }
+                { if editor.warnings.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <ul class="source-warnings">
+                            { for editor.warnings.iter().map(|w| html! { <li>{ w.clone() }</li> }) }
+                        </ul>
+                    }
+                } }
+                <div class="note-transfer">
+                    <button onclick={on_export} title={lang.t("Descargar el XML editado")}>{ lang.t("⬇ Descargar XML") }</button>
+                </div>
+            </div>
+        }
+    }
+
+    /// Point under the cursor in the facsimile's declared coordinates.
+    fn client_to_facsimile(&self, ctx: &Context<Self>, x: i32, y: i32) -> Option<(u32, u32)> {
+        let doc = self.image_doc(ctx)?;
+        let el = self.image_transform_ref.cast::<web_sys::Element>()?;
+        let (use_w, use_h) = self.image_layout_size(ctx, doc);
+        if use_w == 0 || use_h == 0 {
+            return None;
+        }
+        let (src_w, src_h) = (doc.facsimile.width.max(1), doc.facsimile.height.max(1));
+        let rect = el.get_bounding_client_rect();
+        let scale = self.image_scale.max(0.01) as f64;
+        let layout_x = (x as f64 - rect.left()) / scale;
+        let layout_y = (y as f64 - rect.top()) / scale;
+        Some((
+            (layout_x * src_w as f64 / use_w as f64).round().max(0.0) as u32,
+            (layout_y * src_h as f64 / use_h as f64).round().max(0.0) as u32,
+        ))
+    }
+
+    /// Every zone of the editor as a draggable polygon, with vertex handles
+    /// on the selected one.
+    fn render_zone_editor(&self, ctx: &Context<Self>, display_w: u32, display_h: u32) -> Html {
+        let Some(editor) = &self.zone_editor else {
+            return html! {};
+        };
+        if display_w == 0 || display_h == 0 {
+            return html! {};
+        }
+        let factor_x = display_w as f32 / editor.width.max(1) as f32;
+        let factor_y = display_h as f32 / editor.height.max(1) as f32;
+        // Handles keep the same on-screen size at any zoom
+        let radius = 6.0 / self.image_scale.max(0.01);
+        let press = |e: &PointerEvent| {
+            e.prevent_default();
+            e.stop_propagation();
+            if let Some(element) = e
+                .target()
+                .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+            {
+                let _ = element.set_pointer_capture(e.pointer_id());
+            }
+        };
+        // Keep handle clicks from adding a vertex underneath
+        let stop_click = Callback::from(|e: MouseEvent| e.stop_propagation());
+
+        html! {
+            <svg
+                class="zone-editor-svg"
+                style={format!("position: absolute; top: 0; left: 0; width: {}px; height: {}px;", display_w, display_h)}
+                viewBox={format!("0 0 {} {}", display_w, display_h)}
+                preserveAspectRatio="none"
+                xmlns="http://www.w3.org/2000/svg"
+            >
+                { for editor.zones.iter().enumerate().map(|(index, zone)| {
+                    let selected = editor.selected == Some(index);
+                    let points = zone
+                        .points
+                        .iter()
+                        .map(|(x, y)| format!("{:.2},{:.2}", *x as f32 * factor_x, *y as f32 * factor_y))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    let onpointerdown = ctx.link().callback(move |e: PointerEvent| {
+                        press(&e);
+                        TeiViewerMsg::ZoneEditPress(index, None, e.client_x(), e.client_y())
+                    });
+                    html! {
+                        <g>
+                            <polygon
+                                class={classes!("zone-edit", selected.then_some("selected"), zone.line.is_none().then_some("unlinked"))}
+                                points={points}
+                                {onpointerdown}
+                                onclick={stop_click.clone()}
+                            >
+                                <title>{ zone.id.clone() }</title>
+                            </polygon>
+                            { for zone.points.iter().enumerate().filter(|_| selected).map(|(vertex, (x, y))| {
+                                let onpointerdown = ctx.link().callback(move |e: PointerEvent| {
+                                    press(&e);
+                                    TeiViewerMsg::ZoneEditPress(index, Some(vertex), e.client_x(), e.client_y())
+                                });
+                                let onclick = ctx.link().batch_callback(move |e: MouseEvent| {
+                                    e.stop_propagation();
+                                    e.shift_key().then_some(TeiViewerMsg::ZoneEditRemovePoint(index, vertex))
+                                });
+                                html! {
+                                    <circle
+                                        class="zone-edit-vertex"
+                                        cx={format!("{:.2}", *x as f32 * factor_x)}
+                                        cy={format!("{:.2}", *y as f32 * factor_y)}
+                                        r={format!("{:.2}", radius)}
+                                        {onpointerdown}
+                                        {onclick}
+                                    />
+                                }
+                            }) }
+                        </g>
+                    }
+                }) }
+            </svg>
+        }
+    }
+
+    fn render_zone_editor_panel(&self, ctx: &Context<Self>) -> Html {
+        let (Some(editor), Some(doc)) = (&self.zone_editor, &self.diplomatic) else {
+            return html! {};
+        };
+        let lines: Vec<AttrValue> = doc
+            .lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let label = doc.line_label(index).unwrap_or_default();
+                let text: String = line.text(Reading::Diplomatic).chars().take(24).collect();
+                AttrValue::from(format!("{} · {}", label, text))
+            })
+            .collect();
+        html! {
+            <ZoneEditorPanel
+                lang={ctx.props().lang}
+                zones={editor.zones.clone()}
+                selected={editor.selected}
+                {lines}
+                xml={editor.to_xml(
+                    &doc.lines,
+                    ctx.props().config.image_attribution(self.current_page).as_deref(),
+                )}
+                on_select={ctx.link().callback(TeiViewerMsg::ZoneEditSelect)}
+                on_add={ctx.link().callback(|_| TeiViewerMsg::ZoneEditAdd)}
+                on_remove={ctx.link().callback(TeiViewerMsg::ZoneEditRemove)}
+                on_link={ctx.link().callback(TeiViewerMsg::ZoneEditLink)}
+                on_export={ctx.link().callback(|_| TeiViewerMsg::ExportZones)}
+            />
+        }
+    }
+
+    /// Fetch the project's glossary.xml, once per project, and scan its
+    /// pages for `<term>` occurrences.
+    fn load_glossary(&mut self, ctx: &Context<Self>) {
+        self.load_corpus(ctx);
+        if self.project_glossary.is_none() {
+            let project = self.current_project.clone();
+            self.project_glossary = Some(Vec::new());
+            let path = ctx.props().config.versioned_file_url("glossary.xml");
+            let link = ctx.link().clone();
+            if let Some(text) = local_document::read(&path) {
+                link.send_message(TeiViewerMsg::GlossaryLoaded(project, text));
+                return;
+            }
+            let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+            self.project_loads.push(load);
+            spawn_local(async move {
+                let result = match text.await {
+                    Err(FetchError::Aborted { .. }) => return,
+                    result => result.map_err(|e| format!("Failed to load glossary: {}", e)),
+                };
+                link.send_message(TeiViewerMsg::GlossaryLoaded(project, result));
+            });
+        }
+    }
+
+    /// Fetch the project's places.json, once per project, and scan its
+    /// pages for `<placeName>`s.
+    fn load_places(&mut self, ctx: &Context<Self>) {
+        self.load_corpus(ctx);
+        if self.project_places.is_none() {
+            let project = self.current_project.clone();
+            self.project_places = Some(Vec::new());
+            let path = ctx.props().config.versioned_file_url("places.json");
+            let link = ctx.link().clone();
+            if let Some(text) = local_document::read(&path) {
+                link.send_message(TeiViewerMsg::PlacesLoaded(project, text));
+                return;
+            }
+            let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+            self.project_loads.push(load);
+            spawn_local(async move {
+                let result = match text.await {
+                    Err(FetchError::Aborted { .. }) => return,
+                    result => result.map_err(|e| format!("Failed to load places: {}", e)),
+                };
+                link.send_message(TeiViewerMsg::PlacesLoaded(project, result));
+            });
+        }
+    }
+
+    /// Load the diplomatic edition of every page of the project, once per
+    /// project, serving pages from the document cache when possible.
+    fn load_corpus(&mut self, ctx: &Context<Self>) {
+        if !self.corpus.is_empty() || self.corpus_pending > 0 {
+            return;
+        }
+        let project = self.current_project.clone();
+        for &page in &ctx.props().pages {
+            if let Some(doc) = self.doc_cache.get(&CacheKey::new(&project, page, "dip")) {
+                self.corpus.insert(page, Rc::new(doc));
+                continue;
+            }
+            self.corpus_pending += 1;
+            let path = ctx.props().config.get_diplomatic_path(page);
+            let link = ctx.link().clone();
+            let project = project.clone();
+            if let Some(xml) = local_document::read(&path) {
+                let result = xml.and_then(|xml| crate::tei_parser::parse_tei_xml(&xml));
+                link.send_message(TeiViewerMsg::ProjectPageLoaded(project, page, result));
+                continue;
+            }
+            let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+            self.project_loads.push(load);
+            spawn_local(async move {
+                let result = match text.await {
+                    Err(FetchError::Aborted { .. }) => return,
+                    Ok(xml) => crate::tei_parser::parse_tei_xml(&xml),
+                    Err(e) => Err(format!("Failed to load diplomatic: {}", e)),
+                };
+                link.send_message(TeiViewerMsg::ProjectPageLoaded(project, page, result));
+            });
+        }
+    }
+
+    /// Diplomatic pages of the project in manifest order, as scanned so
+    /// far; the page on screen is read as displayed.
+    fn corpus_docs(&self, ctx: &Context<Self>) -> Vec<(u32, Rc<TeiDocument>)> {
+        let current = self.diplomatic.clone().map(|doc| (self.current_page, doc));
+        let pages = &ctx.props().pages;
+        if !pages.contains(&self.current_page) {
+            return current.into_iter().collect();
+        }
+        pages
+            .iter()
+            .filter_map(|&page| {
+                if page == self.current_page {
+                    current.clone()
+                } else {
+                    self.corpus.get(&page).map(|doc| (page, doc.clone()))
+                }
+            })
+            .collect()
+    }
+
+    /// The project's glossary.xml, followed by entries declared in the
+    /// pages themselves.
+    fn glossary_entries(&self, ctx: &Context<Self>) -> Vec<GlossEntry> {
+        let mut entries = self.project_glossary.clone().unwrap_or_default();
+        for (_, doc) in self.corpus_docs(ctx) {
+            glossary::merge(&mut entries, &doc.glossary);
+        }
+        entries
+    }
+
+    fn render_glossary_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_glossary {
+            return html! {};
+        }
+        let docs = self.corpus_docs(ctx);
+        let entries = self.glossary_entries(ctx);
+        let (selected, missing) = match &self.glossary_term {
+            Some((key, text)) => match glossary::lookup(&entries, key.as_deref(), text) {
+                Some(index) => (Some(index), None),
+                None => (None, Some(AttrValue::from(text.clone()))),
+            },
+            None => (None, None),
+        };
+        let occurrences = selected
+            .map(|index| {
+                glossary::occurrences(docs.iter().map(|(p, d)| (*p, d.as_ref())), &entries, index)
+            })
+            .unwrap_or_default()
+            .into_iter()
+            .map(|o| {
+                let line = docs
+                    .iter()
+                    .find(|(p, _)| *p == o.page)
+                    .and_then(|(_, d)| d.line_label(o.line))
+                    .unwrap_or_default();
+                OccurrenceLink {
+                    page: o.page,
+                    line: o.line,
+                    label: format!("p. {}, l. {} · {}", o.page, line, o.text).into(),
+                }
+            })
+            .collect::<Vec<_>>();
+        html! {
+            <GlossaryPanel
+                lang={ctx.props().lang}
+                {entries}
+                {selected}
+                {occurrences}
+                {missing}
+                loading={self.corpus_pending > 0}
+                on_select={ctx.link().callback(TeiViewerMsg::SelectGlossEntry)}
+                on_open={ctx.link().callback(TeiViewerMsg::OpenProjectLine)}
+            />
+        }
+    }
+
+    fn render_toc_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_toc {
+            return html! {};
+        }
+        let outline = &ctx.props().outline;
+        let (entries, loading) = if outline.is_empty() {
+            let docs = self.corpus_docs(ctx);
+            let entries = toc::from_sections(docs.iter().map(|(p, d)| (*p, d.as_ref())));
+            (entries, self.corpus_pending > 0)
+        } else {
+            (toc::from_outline(outline), false)
+        };
+        html! {
+            <TocPanel
+                lang={ctx.props().lang}
+                {entries}
+                current_page={self.current_page}
+                {loading}
+                on_open={ctx.link().callback(TeiViewerMsg::OpenTocEntry)}
+            />
+        }
+    }
+
+    fn render_index_locorum_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_index_locorum {
+            return html! {};
+        }
+        let docs = self.corpus_docs(ctx);
+        let loci = index_locorum::build(docs.iter().map(|(p, d)| (*p, d.as_ref())));
+        html! {
+            <IndexLocorumPanel
+                lang={ctx.props().lang}
+                {loci}
+                loading={self.corpus_pending > 0}
+                on_open={ctx.link().callback(TeiViewerMsg::OpenProjectLine)}
+            />
+        }
+    }
+
+    fn render_map_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_map {
+            return html! {};
+        }
+        let docs = self.corpus_docs(ctx);
+        let gazetteer = self.project_places.as_deref().unwrap_or_default();
+        let (places, unlocated) =
+            places::locate(docs.iter().map(|(p, d)| (*p, d.as_ref())), gazetteer);
+        html! {
+            <PlaceMapPanel
+                lang={ctx.props().lang}
+                {places}
+                {unlocated}
+                loading={self.corpus_pending > 0}
+                on_open={ctx.link().callback(TeiViewerMsg::OpenProjectLine)}
+            />
+        }
+    }
+
+    fn render_timeline_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_timeline {
+            return html! {};
+        }
+        let docs = self.corpus_docs(ctx);
+        let events = timeline::build(
+            &ctx.props().date_range,
+            docs.iter().map(|(p, d)| (*p, d.as_ref())),
+        );
+        html! {
+            <TimelinePanel
+                lang={ctx.props().lang}
+                {events}
+                loading={self.corpus_pending > 0}
+                current_page={self.current_page}
+                on_open={ctx.link().callback(TeiViewerMsg::OpenTocEntry)}
+            />
+        }
+    }
+
+    fn render_word_frequency_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_word_frequency {
+            return html! {};
+        }
+        let project = self.word_frequency_project;
+        let docs = if project {
+            self.corpus_docs(ctx)
+        } else {
+            self.diplomatic
+                .clone()
+                .map(|doc| (self.current_page, doc))
+                .into_iter()
+                .collect()
+        };
+        let texts: Vec<String> = docs
+            .iter()
+            .flat_map(|(_, doc)| doc.lines.iter().map(|l| l.text(Reading::Diplomatic)))
+            .collect();
+        let words = word_frequency::count(texts.iter().map(String::as_str));
+        html! {
+            <WordFrequencyPanel
+                lang={ctx.props().lang}
+                {words}
+                {project}
+                loading={project && self.corpus_pending > 0}
+                on_scope={ctx.link().callback(TeiViewerMsg::SetWordFrequencyScope)}
+                on_search={ctx.link().callback(TeiViewerMsg::SetSearchQuery)}
+            />
+        }
+    }
+
+    fn render_notes_panel(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_notes {
+            return html! {};
+        }
+        html! {
+            <NotesPanel
+                lang={ctx.props().lang}
+                notes={self.annotations.for_page(self.current_page).into_iter().cloned().collect::<Vec<_>>()}
+                selected_line={self.selected_line()}
+                quote={self.note_quote.clone()}
+                status={self.notes_status.clone()}
+                on_save={ctx.link().callback(TeiViewerMsg::SaveNote)}
+                on_delete={ctx.link().callback(TeiViewerMsg::DeleteNote)}
+                on_select={ctx.link().callback(TeiViewerMsg::FocusLine)}
+                on_export={ctx.link().callback(|_| TeiViewerMsg::ExportNotes)}
+                on_export_w3c={ctx.link().callback(|_| TeiViewerMsg::ExportWebAnnotations)}
+                on_import={ctx.link().callback(TeiViewerMsg::ImportNotes)}
+            />
+        }
+    }
+
+    /// Apply a shared link's view when it points at the page being opened.
+    fn restore_url_state(&mut self) {
+        let linked = url_state::current();
+        if linked
+            .project
+            .as_ref()
+            .is_some_and(|p| *p != self.current_project)
+            || linked.page.is_some_and(|n| n != self.current_page)
+        {
+            return;
+        }
+        if let Some(view) = linked.view.as_deref().and_then(ViewType::from_str) {
+            self.active_view = view;
+        }
+        if let Some(zone) = &linked.zone {
+            self.locked_zone = Some(zone.clone());
+        }
+        if let Some(zoom) = linked.zoom {
+            self.pending_fit = None;
+            self.image_scale = zoom.clamp(0.2, 8.0);
+        }
+        if let Some(x) = linked.offset_x {
+            self.image_offset_x = x;
+        }
+        if let Some(y) = linked.offset_y {
+            self.image_offset_y = y;
+        }
+        self.url_state = linked;
+    }
+
+    fn current_url_state(&self) -> UrlState {
+        // The project and page are in the route
+        UrlState {
+            project: None,
+            page: None,
+            view: Some(self.active_view.name()),
+            zone: self.locked_zone.clone(),
+            zoom: Some(self.image_scale),
+            offset_x: Some(self.image_offset_x),
+            offset_y: Some(self.image_offset_y),
+        }
+    }
+
+    fn persist_panel_size(&self) {
+        match self.layout {
+            Layout::SideBySide => {
+                persistence::save(persistence::PANEL_WIDTH, self.image_panel_width)
+            }
+            Layout::Stacked => {
+                persistence::save(persistence::PANEL_HEIGHT, self.image_panel_height)
+            }
+        }
+    }
+
+    /// Request an animation frame that applies the current pan/zoom to the
+    /// DOM directly, coalescing all pointer moves within a frame.
+    fn schedule_transform(&mut self, ctx: &Context<Self>) {
+        if self.pending_frame.is_none() {
+            let link = ctx.link().clone();
+            self.pending_frame = Some(request_animation_frame(move |_| {
+                link.send_message(TeiViewerMsg::ApplyTransform);
+            }));
+        }
+    }
+
+    /// Request the deep-zoom description declared for the page, if any.
+    /// A IIIF service takes precedence over a local DZI pyramid.
+    fn load_tile_source(ctx: &Context<Self>) {
+        let Some(page_info) = ctx.props().page_info.as_ref() else {
+            return;
+        };
+        if let Some(service) = &page_info.iiif_service {
+            ctx.link()
+                .send_message(TeiViewerMsg::LoadIiifInfo(service.clone()));
+        } else if let Some(dzi) = &page_info.dzi {
+            let dzi_url = ctx.props().config.file_url(dzi);
+            ctx.link().send_message(TeiViewerMsg::LoadDzi(dzi_url));
+        }
+    }
+
+    fn is_narrow_viewport() -> bool {
+        web_sys::window()
+            .and_then(|w| w.inner_width().ok())
+            .and_then(|w| w.as_f64())
+            .is_some_and(|w| w <= MOBILE_BREAKPOINT)
+    }
+
+    /// Page whose facsimile is shown: in continuous scroll, the page centred
+    /// in the text panel.
+    fn image_page(&self, ctx: &Context<Self>) -> u32 {
+        match self.centered_page {
+            Some(page) if self.continuous => page,
+            _ => ctx.props().page,
+        }
+    }
+
+    /// Further image of the page chosen instead of the facsimile, if any.
+    fn extra_image<'a>(&self, ctx: &'a Context<Self>) -> Option<&'a PageImage> {
+        let index = self.image_choice.checked_sub(1)?;
+        if self.image_page(ctx) != ctx.props().page {
+            return None;
+        }
+        ctx.props().page_info.as_ref()?.images.get(index)
+    }
+
+    fn image_doc(&self, ctx: &Context<Self>) -> Option<&Rc<TeiDocument>> {
+        let page = self.image_page(ctx);
+        if page != ctx.props().page {
+            if let Some((_, doc)) = self.continuous_docs.iter().find(|(p, _)| *p == page) {
+                return Some(doc);
+            }
+        }
+        self.diplomatic.as_ref().or(self.translation.as_ref())
+    }
+
+    /// Zoom and pan the facsimile so the zone fills most of the panel.
+    fn zoom_to_zone(&mut self, ctx: &Context<Self>, zone_id: &str) {
+        // Zones don't map onto further images in other coordinates
+        if self.extra_image(ctx).is_some_and(|image| !image.zones) {
+            return;
+        }
+        let Some(doc) = self.image_doc(ctx).cloned() else {
+            return;
+        };
+        let Some(zone) = doc.facsimile.zones.get(zone_id) else {
+            return;
+        };
+        if zone.points.is_empty() || self.viewport_w <= 0.0 || self.viewport_h <= 0.0 {
+            return;
+        }
+        // Zone coordinates are in the declared facsimile space
+        let (use_w, use_h) = self.image_layout_size(ctx, &doc);
+        let factor_x = if doc.facsimile.width > 0 {
+            use_w as f32 / doc.facsimile.width as f32
+        } else {
+            1.0
+        };
+        let factor_y = if doc.facsimile.height > 0 {
+            use_h as f32 / doc.facsimile.height as f32
+        } else {
+            1.0
+        };
+        let (min_x, min_y, max_x, max_y) = zone.get_bounding_box();
+        let rect = (
+            min_x as f32 * factor_x,
+            min_y as f32 * factor_y,
+            (max_x - min_x) as f32 * factor_x,
+            (max_y - min_y) as f32 * factor_y,
+        );
+        let (scale, offset_x, offset_y) =
+            fit_rect(rect, self.viewport_w as f32, self.viewport_h as f32, 0.8);
+        self.image_scale = scale;
+        self.image_offset_x = offset_x;
+        self.image_offset_y = offset_y;
+    }
+
+    /// Image layout pixels per centimetre of the object, when the manifest
+    /// declares a scan DPI or the TEI gives msDesc dimensions.
+    fn layout_pixels_per_cm(&self, ctx: &Context<Self>) -> Option<f64> {
+        let doc = self.image_doc(ctx)?;
+        let (use_w, _) = self.image_layout_size(ctx, doc);
+        let declared_w = if doc.facsimile.width > 0 {
+            doc.facsimile.width
+        } else {
+            use_w
+        };
+        if declared_w == 0 {
+            return None;
+        }
+        let dpi = ctx
+            .props()
+            .page_info
+            .as_ref()
+            .filter(|_| self.image_page(ctx) == ctx.props().page)
+            .and_then(|p| p.dpi);
+        let per_cm = pixels_per_cm(dpi, doc.metadata.width_cm, declared_w)?;
+        Some(per_cm * use_w as f64 / declared_w as f64)
+    }
+
+    fn ruler_distance_cm(&self, ctx: &Context<Self>) -> Option<f64> {
+        let [(x1, y1), (x2, y2)] = self.ruler_points[..] else {
+            return None;
+        };
+        let per_cm = self.layout_pixels_per_cm(ctx)?;
+        Some((x2 - x1).hypot(y2 - y1) / per_cm)
+    }
+
+    /// Ruler line and end points, drawn in image layout pixels so they
+    /// follow pan and zoom.
+    fn render_ruler(&self, ctx: &Context<Self>, use_w: u32, use_h: u32) -> Html {
+        if !self.ruler_active || self.ruler_points.is_empty() {
+            return html! {};
+        }
+        // Keep strokes and labels the same size on screen at any zoom
+        let px = 1.0 / self.image_scale.max(0.01) as f64;
+        let (x1, y1) = self.ruler_points[0];
+        let (x2, y2) = self.ruler_points.last().copied().unwrap_or((x1, y1));
+        let label = self
+            .ruler_distance_cm(ctx)
+            .map(|cm| format!("{:.1} cm", cm))
+            .unwrap_or_default();
+
+        html! {
+            <svg
+                class="ruler-svg"
+                style={format!("position: absolute; top: 0; left: 0; width: {}px; height: {}px; pointer-events: none;", use_w, use_h)}
+                viewBox={format!("0 0 {} {}", use_w, use_h)}
+                xmlns="http://www.w3.org/2000/svg"
+            >
+                <line
+                    x1={x1.to_string()} y1={y1.to_string()}
+                    x2={x2.to_string()} y2={y2.to_string()}
+                    stroke-width={(2.0 * px).to_string()}
+                />
+                { for self.ruler_points.iter().map(|(x, y)| html! {
+                    <circle cx={x.to_string()} cy={y.to_string()} r={(4.0 * px).to_string()} />
+                }) }
+                <text
+                    x={((x1 + x2) / 2.0 + 6.0 * px).to_string()}
+                    y={((y1 + y2) / 2.0 - 6.0 * px).to_string()}
+                    font-size={(14.0 * px).to_string()}
+                >
+                    { label }
+                </text>
+            </svg>
+        }
+    }
+
+    /// Length of the scale bar in cm and its width in screen pixels at the
+    /// current zoom.
+    fn scale_bar_metrics(&self, ctx: &Context<Self>) -> Option<(f64, f64)> {
+        let per_cm = self.layout_pixels_per_cm(ctx)? * self.image_scale as f64;
+        let cm = scale_bar_cm(per_cm, SCALE_BAR_MIN_PX);
+        Some((cm, cm * per_cm))
+    }
+
+    /// Scale bar pinned to the bottom-left corner of the image panel,
+    /// outside the pan/zoom transform.
+    fn render_scale_bar(&self, ctx: &Context<Self>) -> Html {
+        if !self.show_scale_bar {
+            return html! {};
+        }
+        let Some((cm, width)) = self.scale_bar_metrics(ctx) else {
+            return html! {};
+        };
+        html! {
+            <div class="scale-bar" ref={self.scale_bar_ref.clone()} aria-hidden="true">
+                <div class="scale-bar-line" style={format!("width: {:.1}px;", width)}></div>
+                <span class="scale-bar-label">{ format!("{} cm", cm) }</span>
+            </div>
+        }
+    }
+
+    /// Resize the scale bar in place, for zoom gestures that write the
+    /// transform straight to the DOM instead of re-rendering.
+    fn update_scale_bar(&self, ctx: &Context<Self>) {
+        let Some(bar) = self.scale_bar_ref.cast::<web_sys::Element>() else {
+            return;
+        };
+        let Some((cm, width)) = self.scale_bar_metrics(ctx) else {
+            return;
+        };
+        if let Ok(Some(line)) = bar.query_selector(".scale-bar-line") {
+            if let Ok(line) = line.dyn_into::<web_sys::HtmlElement>() {
+                let _ = line
+                    .style()
+                    .set_property("width", &format!("{:.1}px", width));
+            }
+        }
+        if let Ok(Some(label)) = bar.query_selector(".scale-bar-label") {
+            label.set_text_content(Some(&format!("{} cm", cm)));
+        }
+    }
+
+    /// Size the facsimile is laid out at inside the pan/zoom container.
+    fn image_layout_size(&self, ctx: &Context<Self>, doc: &TeiDocument) -> (u32, u32) {
+        let declared_w = doc.facsimile.width;
+        let declared_h = doc.facsimile.height;
+        let on_prop_page = self.image_page(ctx) == ctx.props().page;
+
+        // A further image is laid out at its own size
+        if self.extra_image(ctx).is_some() {
+            return (self.image_nat_w, self.image_nat_h);
+        }
+
+        // Deep zoom has no single image to measure: lay out at the declared
+        // size, or the pyramid's full resolution.
+        if let Some(source) = self.tile_source.as_ref().filter(|_| on_prop_page) {
+            return if declared_w > 0 && declared_h > 0 {
+                (declared_w, declared_h)
+            } else {
+                (source.width(), source.height())
+            };
+        }
+
+        // Manifest-declared resolutions: lay the image out at its full size,
+        // since the natural size differs per variant.
+        let max_variant_w = ctx
+            .props()
+            .page_info
+            .as_ref()
+            .filter(|_| on_prop_page)
+            .and_then(|p| p.max_variant_width());
+        if let Some(max_w) = max_variant_w {
+            let use_w = if declared_w > 0 { declared_w } else { max_w };
+            let use_h = if declared_w > 0 && declared_h > 0 {
+                declared_h
+            } else {
+                (self.image_nat_h * use_w)
+                    .checked_div(self.image_nat_w)
+                    .unwrap_or(0)
+            };
+            return (use_w, use_h);
+        }
+
+        // Natural image dimensions, falling back to declared if not loaded
+        let use_w = if self.image_nat_w > 0 {
+            self.image_nat_w
+        } else {
+            declared_w
+        };
+        let use_h = if self.image_nat_h > 0 {
+            self.image_nat_h
+        } else {
+            declared_h
+        };
+        (use_w, use_h)
+    }
+
+    /// Append the page following the last loaded one to the continuous
+    /// scroll, from the cache when possible.
+    fn load_next_continuous_page(&mut self, ctx: &Context<Self>) {
+        if self.continuous_loading {
+            return;
+        }
+        let last = self
+            .continuous_docs
+            .last()
+            .map(|(p, _)| *p)
+            .unwrap_or(ctx.props().page);
+        let pages = &ctx.props().pages;
+        let Some(next) = pages
+            .iter()
+            .position(|p| *p == last)
+            .and_then(|i| pages.get(i + 1))
+            .copied()
+        else {
+            return;
+        };
+        let generation = self.load_generation;
+        self.continuous_loading = true;
+        if let Some(doc) = self
+            .doc_cache
+            .get(&CacheKey::new(&self.current_project, next, "dip"))
+        {
+            ctx.link().send_message(TeiViewerMsg::ContinuousPageLoaded(
+                generation,
+                next,
+                Ok(doc),
+            ));
+            return;
+        }
+        let path = ctx.props().config.get_diplomatic_path(next);
+        let link = ctx.link().clone();
+        if let Some(xml) = local_document::read(&path) {
+            let result = xml.and_then(|xml| crate::tei_parser::parse_tei_xml(&xml));
+            link.send_message(TeiViewerMsg::ContinuousPageLoaded(generation, next, result));
+            return;
+        }
+        let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+        self.page_loads.insert(PageLoad::Continuous, load);
+        spawn_local(async move {
+            let result = match text.await {
+                Err(FetchError::Aborted { .. }) => return,
+                Ok(xml) => crate::tei_parser::parse_tei_xml(&xml),
+                Err(e) => Err(format!("Failed to load diplomatic: {}", e)),
+            };
+            link.send_message(TeiViewerMsg::ContinuousPageLoaded(generation, next, result));
+        });
+    }
+
+    /// Fetch the compared project's diplomatic edition of the aligned page.
+    fn load_comparison(&mut self, ctx: &Context<Self>) {
+        self.compare_doc = None;
+        self.comparison_load = None;
+        let Some(Comparison {
+            config,
+            page: Some(page),
+        }) = ctx.props().compare.clone()
+        else {
+            return;
+        };
+        let project = config.id.clone();
+        if let Some(doc) = self.doc_cache.get(&CacheKey::new(&project, page, "dip")) {
+            ctx.link()
+                .send_message(TeiViewerMsg::ComparisonLoaded(project, page, Ok(doc)));
+            return;
+        }
+        let path = config.get_diplomatic_path(page);
+        let link = ctx.link().clone();
+        if let Some(xml) = local_document::read(&path) {
+            let result = xml.and_then(|xml| crate::tei_parser::parse_tei_xml(&xml));
+            link.send_message(TeiViewerMsg::ComparisonLoaded(project, page, result));
+            return;
+        }
+        let (load, text) = net::get_text_abortable(config.sources_for(&path));
+        self.comparison_load = Some(load);
+        spawn_local(async move {
+            let result = match text.await {
+                Err(FetchError::Aborted { .. }) => return,
+                Ok(xml) => crate::tei_parser::parse_tei_xml(&xml),
+                Err(e) => Err(format!("Failed to load diplomatic: {}", e)),
+            };
+            link.send_message(TeiViewerMsg::ComparisonLoaded(project, page, result));
+        });
+    }
+
+    /// Recompute which lines of each edition match the search query.
+    fn refresh_search(&mut self) {
+        let query = self.search_query.trim();
+        let ignore = self.search_ignore_diacritics;
+        let hits = |doc: &Option<Rc<TeiDocument>>| -> Vec<usize> {
+            match doc {
+                Some(doc) if !query.is_empty() => doc
+                    .lines
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, line)| {
+                        polytonic::contains(&line.text(Reading::Diplomatic), query, ignore)
+                    })
+                    .map(|(i, _)| i)
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+        self.dip_hits = hits(&self.diplomatic);
+        self.trad_hits = hits(&self.translation);
+    }
+
+    fn cache_document(&mut self, edition: &str, doc: &TeiDocument) {
+        // The reader's files are kept by `local_document`, and may change
+        if local_document::is_open(&self.current_project) {
+            return;
+        }
+        let key = CacheKey::new(&self.current_project, self.current_page, edition);
+        self.doc_cache.insert(key, doc.clone());
+        log::debug!("Document cache holds {} entries", self.doc_cache.len());
+    }
+
+    fn render_controls(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        if ctx.props().kiosk {
+            return html! {};
+        }
+        // (view, label, hint): the built-in views, with the editions of the
+        // manifest after the translation
+        let mut views = vec![
+            (
+                ViewType::Diplomatic,
+                lang.t("Edición diplomática").to_string(),
+                None,
+            ),
+            (
+                ViewType::Translation,
+                lang.t("Traducción").to_string(),
+                None,
+            ),
+        ];
+        views.extend(ctx.props().editions.iter().map(|e| {
+            let hint = (!e.kind.is_empty()).then(|| e.kind.clone());
+            (ViewType::Edition(e.id.clone()), e.label.clone(), hint)
+        }));
+        views.push((ViewType::Both, lang.t("Ambas").to_string(), None));
+        views.push((
+            ViewType::Diff,
+            lang.t("Diferencias").to_string(),
+            Some(lang.t("Diplomática frente a normalizada").to_string()),
+        ));
+        let toggle_continuous = ctx.link().callback(|_| TeiViewerMsg::ToggleContinuous);
+        let toggle_commentary = ctx.link().callback(|_| TeiViewerMsg::ToggleCommentary);
+        let zoom_in = ctx.link().callback(|_| TeiViewerMsg::UpdateImageScale(1.2));
+        let zoom_out = ctx.link().callback(|_| TeiViewerMsg::UpdateImageScale(0.8));
+        let toggle_meta = ctx.link().callback(|_| TeiViewerMsg::ToggleMetadata);
+        let toggle_legend = ctx.link().callback(|_| TeiViewerMsg::ToggleLegend);
+        let toggle_heatmap = ctx.link().callback(|_| TeiViewerMsg::ToggleHeatmap);
+        let toggle_notes = ctx.link().callback(|_| TeiViewerMsg::ToggleNotes);
+        let toggle_ruler = ctx.link().callback(|_| TeiViewerMsg::ToggleRuler);
+        let toggle_scale_bar = ctx.link().callback(|_| TeiViewerMsg::ToggleScaleBar);
+        let ruler = if self.layout_pixels_per_cm(ctx).is_some() {
+            html! {
+                <>
+                    <button
+                        class={classes!(self.show_scale_bar.then_some("active"))}
+                        onclick={toggle_scale_bar}
+                        title={lang.t("Mostrar una barra de escala sobre el facsímil")}
+                        aria-pressed={self.show_scale_bar.to_string()}
+                    >
+                        { lang.t("📐 Escala") }
+                    </button>
+                    <button
+                        class={classes!(self.ruler_active.then_some("active"))}
+                        onclick={toggle_ruler}
+                        title={lang.t("Medir distancias sobre el facsímil")}
+                        aria-pressed={self.ruler_active.to_string()}
+                    >
+                        { lang.t("📏 Regla") }
+                    </button>
+                    { if self.ruler_active {
+                        let readout = self
+                            .ruler_distance_cm(ctx)
+                            .map(|cm| format!("{:.1} cm", cm))
+                            .unwrap_or_else(|| lang.t("Haga clic en dos puntos").to_string());
+                        html! { <span class="ruler-readout">{ readout }</span> }
+                    } else {
+                        html! {}
+                    } }
+                </>
+            }
+        } else {
+            html! {}
+        };
+        let on_search = ctx.link().callback(|e: InputEvent| {
+            let value = e
+                .target_dyn_into::<HtmlInputElement>()
+                .map(|input| input.value())
+                .unwrap_or_default();
+            TeiViewerMsg::SetSearchQuery(value)
+        });
+        let toggle_diacritics = ctx
+            .link()
+            .callback(|_| TeiViewerMsg::ToggleSearchDiacritics);
+        let hit_count = self.dip_hits.len() + self.trad_hits.len();
+        let on_goto_line = {
+            let input_ref = self.goto_line_ref.clone();
+            ctx.link().callback(move |e: SubmitEvent| {
+                e.prevent_default();
+                let value = input_ref
+                    .cast::<HtmlInputElement>()
+                    .map(|input| input.value())
+                    .unwrap_or_default();
+                TeiViewerMsg::JumpToLine(value)
+            })
+        };
+
+        html! {
+            <div class="controls-panel">
+                <div class="view-toggles">
+                    { for views.into_iter().map(|(view, label, hint)| {
+                        let class = classes!((self.active_view == view).then_some("active"));
+                        let onclick = ctx.link().callback(move |_| TeiViewerMsg::ToggleView(view.clone()));
+                        html! { <button {class} {onclick} title={hint}>{ label }</button> }
+                    }) }
+                    <button class={if self.continuous { "active" } else { "" }} onclick={toggle_continuous} title={lang.t("Encadenar las páginas del proyecto")}>{ lang.t("Scroll continuo") }</button>
+                    if ctx.props().panels == Panels::All {
+                        <button
+                            onclick={ctx.link().callback(|_| TeiViewerMsg::PopOutText)}
+                            title={lang.t("Abrir el texto en otra ventana, que sigue a esta página y línea")}
+                        >
+                            { lang.t("⧉ Otra ventana") }
+                        </button>
+                    }
+                    <button class={if self.show_commentary { "active" } else { "" }} onclick={toggle_commentary}>
+                        { lang.t("Comentario") }
+                        { if self.progress.commentary == Progress::Loading {
+                            html! { <span class="spinner" title={lang.t("Cargando el comentario…")} /> }
+                        } else {
+                            html! {}
+                        } }
+                    </button>
+                    <button class={if self.show_notes { "active" } else { "" }} onclick={toggle_notes} title={lang.t("Notas personales, guardadas en este navegador")}>{ lang.t("📝 Notas") }</button>
+                    <button
+                        class={classes!(self.show_toc.then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleToc)}
+                        title={lang.t("Secciones del proyecto y dónde empiezan")}
+                    >
+                        { lang.t("☰ Índice") }
+                    </button>
+                    <button
+                        class={classes!(self.show_glossary.then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleGlossary)}
+                        title={lang.t("Términos del glosario y sus apariciones en el proyecto")}
+                    >
+                        { lang.t("📖 Glosario") }
+                    </button>
+                    <button
+                        class={classes!(self.show_index_locorum.then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleIndexLocorum)}
+                        title={lang.t("Pasajes citados en el proyecto y las líneas que los citan")}
+                    >
+                        {"Index locorum"}
+                    </button>
+                    <button
+                        class={classes!(self.show_map.then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleMap)}
+                        title={lang.t("Mapa de los lugares con coordenadas y las líneas que los nombran")}
+                    >
+                        { lang.t("🗺 Mapa") }
+                    </button>
+                    <button
+                        class={classes!(self.show_timeline.then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleTimeline)}
+                        title={lang.t("Datación, procedencia y fechas mencionadas en el texto")}
+                    >
+                        { lang.t("⏳ Cronología") }
+                    </button>
+                    <button
+                        class={classes!(self.show_word_frequency.then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleWordFrequency)}
+                        title={lang.t("Frecuencia de las palabras griegas de la página o del proyecto")}
+                    >
+                        { lang.t("Frecuencias") }
+                    </button>
+                    { self.render_bookmark_button(ctx) }
+                    <button
+                        class={classes!(self.source_editor.is_some().then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleSourceEditor)}
+                        title={lang.t("Editar el TEI de la página con vista previa inmediata")}
+                    >
+                        {"</> TEI"}
+                    </button>
+                </div>
+                { self.render_audio(ctx) }
+                <div class="search-box">
+                    <input
+                        type="search"
+                        placeholder={lang.t("Buscar en el texto...")}
+                        aria-label={lang.t("Buscar en el texto")}
+                        value={self.search_query.clone()}
+                        oninput={on_search}
+                    />
+                    <label class="search-option" title={lang.t("También ignora mayúsculas y sigma final/lunada")}>
+                        <input
+                            type="checkbox"
+                            checked={self.search_ignore_diacritics}
+                            onchange={toggle_diacritics}
+                        />
+                        { lang.t("Ignorar acentos/espíritus") }
+                    </label>
+                    { if self.search_query.trim().is_empty() {
+                        html! {}
+                    } else {
+                        html! { <span class="search-count">{lang.tf("{} coincidencias", &[&lang.number(hit_count)])}</span> }
+                    } }
+                </div>
+                <form class="goto-line" onsubmit={on_goto_line}>
+                    <input
+                        ref={self.goto_line_ref.clone()}
+                        type="text"
+                        inputmode="numeric"
+                        size="6"
+                        placeholder={lang.t("Ir a línea")}
+                        aria-label={lang.t("Ir a línea (número de la fuente o posición)")}
+                        aria-invalid={self.goto_line_missing.to_string()}
+                        class={classes!(self.goto_line_missing.then_some("invalid"))}
+                        title={lang.t(if self.goto_line_missing { "Línea no encontrada" } else { "Número de línea de la fuente (@n) o posición" })}
+                    />
+                    <button type="submit">{ lang.t("Ir") }</button>
+                </form>
+                <div class="image-controls">
+                    <button onclick={zoom_in}>{"🔍 +"}</button>
+                    <button onclick={zoom_out}>{"🔍 -"}</button>
+                    <span class="zoom-level">{format!("{}%", (self.image_scale * 100.0) as i32)}</span>
+                    <button onclick={toggle_meta} title={lang.t("Mostrar u ocultar los metadatos")}>{ lang.t(if self.show_metadata_popup { "Ocultar metadata" } else { "Mostrar metadata" }) }</button>
+                    { if feedback::issues_url(ctx.props().config.issues_url.as_deref()).is_some() {
+                        html! { <button onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleFeedback)} title={lang.t("Abrir un issue en GitHub sobre esta página")}>{ lang.t("⚑ Reportar un problema") }</button> }
+                    } else {
+                        html! {}
+                    } }
+                    <button class="legend-toggle" onclick={toggle_legend} title={lang.t("Mostrar u ocultar la leyenda de colores")}>{ lang.t(if self.show_legend { "🎨 Ocultar leyenda" } else { "🎨 Mostrar leyenda" }) }</button>
+                    <button
+                        class={classes!(self.show_heatmap.then_some("active"))}
+                        onclick={toggle_heatmap}
+                        title={lang.t("Sombrear cada zona según la cantidad de lecturas dudosas, correcciones y notas de su línea")}
+                        aria-pressed={self.show_heatmap.to_string()}
+                    >
+                        { lang.t("🔥 Densidad") }
+                    </button>
+                    <button
+                        class={classes!(self.zone_editor.is_some().then_some("active"))}
+                        onclick={ctx.link().callback(|_| TeiViewerMsg::ToggleZoneEditor)}
+                        title={lang.t("Dibujar y ajustar las zonas del facsímil y exportar el <facsimile> resultante")}
+                        aria-pressed={self.zone_editor.is_some().to_string()}
+                    >
+                        { lang.t("✏️ Zonas") }
+                    </button>
+                    { ruler }
+                </div>
+            </div>
+        }
+    }
+
+    fn render_image_panel(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        if !self.show_image {
+            return html! {};
+        }
+        let image_page = self.image_page(ctx);
+        // Page-specific extras (deep zoom, variants) only describe the prop page
+        let on_prop_page = image_page == ctx.props().page;
+        let extra_image = self.extra_image(ctx);
+        // Only images sharing the facsimile's coordinates get its overlays
+        let zoned = extra_image.is_none_or(|image| image.zones);
+        let tile_source = self
+            .tile_source
+            .as_ref()
+            .filter(|_| on_prop_page && extra_image.is_none());
+        if let Some(doc) = self.image_doc(ctx) {
+            // resolve image URL (robust): derive filename and prefer serving from project's images/ directory.
+            // If the TEI already contains a public path, use it as-is (but ensure it is an absolute path).
+            // If the facsimile image_url is empty, fall back to a page-based filename (e.g. "p1.jpg")
+            // derived from the current page prop.
+            let declared_w = doc.facsimile.width;
+            let declared_h = doc.facsimile.height;
+            let (use_w, use_h) = self.image_layout_size(ctx, doc);
+
+            // Manifest-declared resolutions: let the browser pick the variant
+            // matching the rendered, zoomed width via srcset/sizes.
+            let page_info = ctx.props().page_info.as_ref().filter(|p| {
+                on_prop_page && extra_image.is_none() && p.max_variant_width().is_some()
+            });
+            let (srcset, sizes) = match page_info {
+                Some(p) => {
+                    let srcset = Self::image_srcset(ctx, p);
+                    let rendered_w = ((use_w as f32) * self.image_scale).ceil().max(1.0);
+                    (Some(srcset), Some(format!("{}px", rendered_w as u32)))
+                }
+                None => (None, None),
+            };
+
+            let image_url = local_document::resolve(&match extra_image {
+                Some(image) => ctx.props().config.file_url(&image.path),
+                None => ctx
+                    .props()
+                    .config
+                    .get_facsimile_path(image_page, &doc.facsimile.image_url),
+            });
+            let image_sources = ctx.props().config.sources_for(&image_url);
+            // The page's thumbnail stands in for the facsimile while it loads
+            let preview = ctx
+                .props()
+                .page_info
+                .as_ref()
+                .filter(|_| {
+                    on_prop_page
+                        && extra_image.is_none()
+                        && self.progress.image == Progress::Loading
+                })
+                .and_then(|p| p.thumbnail.as_ref())
+                .map(|path| local_document::resolve(&ctx.props().config.file_url(path)));
+            // Credit the holding institution asks for, over the facsimile
+            let credit = ctx
+                .props()
+                .config
+                .image_attribution(image_page)
+                .filter(|_| extra_image.is_none());
+            // An image of the project folder the manifest says is missing
+            // is not requested
+            let declared = doc.facsimile.image_url.trim();
+            let image_listed = !on_prop_page
+                || extra_image.is_some()
+                || ctx.props().page_info.as_ref().is_none_or(|p| p.has_image)
+                || declared.starts_with("http://")
+                || declared.starts_with("https://")
+                || declared.starts_with("data:");
+
+            let onwheel = ctx.link().callback(|e: WheelEvent| {
                 e.prevent_default();
                 let delta = -e.delta_y() as f32;
                 let factor = if delta > 0.0 { 1.1 } else { 0.9 };
@@ -809,26 +4052,27 @@ impl TeiViewer {
                             let _ = element.release_pointer_capture(e.pointer_id());
                         }
                     }
-                    link.send_message(TeiViewerMsg::PointerUp(
-                        e.pointer_id(),
-                        e.client_x(),
-                        e.client_y(),
-                    ))
+                    link.send_message(TeiViewerMsg::PointerUp(e.pointer_id()))
                 })
             };
             let onpointerleave = ctx.link().callback(|e: PointerEvent| {
                 e.prevent_default();
-                TeiViewerMsg::PointerLeave(e.pointer_id(), e.client_x(), e.client_y())
+                TeiViewerMsg::PointerLeave(e.pointer_id())
             });
 
+            let onclick = ctx
+                .link()
+                .callback(|e: MouseEvent| TeiViewerMsg::ImageClicked(e.client_x(), e.client_y()));
+
             // onload captures intrinsic natural size
             let onload = {
                 let link = ctx.link().clone();
                 Callback::from(move |e: Event| {
                     if let Some(t) = e.target() {
                         if let Ok(img) = t.dyn_into::<HtmlImageElement>() {
-                            let nat_w = img.natural_width() as u32;
-                            let nat_h = img.natural_height() as u32;
+                            let nat_w = img.natural_width();
+                            let nat_h = img.natural_height();
+                            offline::keep(vec![img.current_src()]);
 
                             // Send message with natural dimensions
                             link.send_message(TeiViewerMsg::ImageLoadedWithDimensions(
@@ -856,7 +4100,12 @@ impl TeiViewer {
             html! {
                 <div class="image-panel">
                     <div
-                        class="image-container"
+                        class={classes!(
+                            "image-container",
+                            self.ruler_active.then_some("ruler-active"),
+                            self.zone_editor.is_some().then_some("zone-editing")
+                        )}
+                        ref={self.image_container_ref.clone()}
                         {onwheel}
                         {onmousedown}
                         {onmousemove}
@@ -866,23 +4115,211 @@ impl TeiViewer {
                         {onpointermove}
                         {onpointerup}
                         {onpointerleave}
+                        {onclick}
                         style="position: relative; overflow: hidden; touch-action: none;"
                     >
-                        <div class="image-and-overlay" style={transform_style}>
-                            <img
-                                src={image_url.clone()}
-                                onload={onload}
-                                style={format!("display:block; width: {}px; height: {}px; max-width: none; max-height: none;", use_w, use_h)}
-                            />
-                            { self.render_zone_overlays(&doc.facsimile, active_zone, use_w, use_h, declared_w, declared_h) }
+                        <div class="image-and-overlay" ref={self.image_transform_ref.clone()} style={transform_style}>
+                            { if let Some(source) = tile_source {
+                                html! {
+                                    <TileLayer
+                                        source={source.clone()}
+                                        layout_w={use_w}
+                                        layout_h={use_h}
+                                        scale={self.image_scale}
+                                        offset_x={self.image_offset_x}
+                                        offset_y={self.image_offset_y}
+                                        viewport_w={self.viewport_w}
+                                        viewport_h={self.viewport_h}
+                                    />
+                                }
+                            } else if image_listed {
+                                html! {
+                                    <>
+                                    <img
+                                        src={image_sources.first().to_string()}
+                                        srcset={srcset}
+                                        sizes={sizes}
+                                        onload={onload}
+                                        onerror={ctx.link().batch_callback(move |e: Event| {
+                                            // Another source of the image is tried
+                                            // before it is given up, without variants
+                                            let img = e.target_dyn_into::<web_sys::HtmlImageElement>()?;
+                                            let failed = img.get_attribute("src").unwrap_or_default();
+                                            match image_sources.after(&failed) {
+                                                Some(next) => {
+                                                    log::warn!("{} failed; trying {}", failed, next);
+                                                    let _ = img.remove_attribute("srcset");
+                                                    img.set_src(next);
+                                                    None
+                                                }
+                                                None => Some(TeiViewerMsg::ImageFailed),
+                                            }
+                                        })}
+                                        style={format!("display:block; width: {}px; height: {}px; max-width: none; max-height: none;", use_w, use_h)}
+                                    />
+                                    { for preview.as_ref().map(|src| html! {
+                                        <img
+                                            class="facsimile-preview"
+                                            src={src.clone()}
+                                            alt=""
+                                            style={format!("width: {}px; height: {}px;", use_w, use_h)}
+                                        />
+                                    }) }
+                                    </>
+                                }
+                            } else {
+                                html! {}
+                            } }
+                            if zoned {
+                                { self.render_heatmap(ctx.props().lang, doc, use_w, use_h, declared_w, declared_h) }
+                                { self.render_zone_overlays(&doc.facsimile, active_zone, use_w, use_h, declared_w, declared_h) }
+                                { self.render_ruler(ctx, use_w, use_h) }
+                                { if on_prop_page { self.render_zone_editor(ctx, use_w, use_h) } else { html! {} } }
+                            }
                         </div>
                     </div>
+                    { match self.progress.image {
+                        _ if tile_source.is_some() => html! {},
+                        _ if !image_listed => html! {
+                            <div class="image-skeleton failed"><p>{ lang.t("Esta página no tiene imagen") }</p></div>
+                        },
+                        Progress::Loading if preview.is_some() => html! {},
+                        Progress::Loading => html! {
+                            <div class="image-skeleton">{ skeleton(lang.t("Cargando la imagen…"), 0) }</div>
+                        },
+                        Progress::Failed if local_document::is_open(&self.current_project) => html! {
+                            <div class="image-skeleton failed">
+                                <p>{ lang.t("Imagen no disponible: suelte la imagen del facsímil junto con el XML") }</p>
+                            </div>
+                        },
+                        Progress::Failed => html! {
+                            <div class="image-skeleton failed"><p>{ lang.t("Imagen no disponible") }</p></div>
+                        },
+                        Progress::Done => html! {},
+                    } }
+                    { if zoned { self.render_scale_bar(ctx) } else { html! {} } }
+                    { for credit.map(|credit| html! { <div class="image-credit">{ credit }</div> }) }
+                    { self.render_image_selector(ctx) }
+                </div>
+            }
+        } else if self.loading {
+            // The image is named by the facsimile of the documents
+            html! {
+                <div class="image-panel">
+                    <div class="image-skeleton">{ skeleton(lang.t("Localizando la imagen…"), 0) }</div>
                 </div>
             }
         } else {
-            html! {
-                <div class="image-panel"><p>{"No image available"}</p></div>
-            }
+            html! {
+                <div class="image-panel"><p>{ lang.t("Imagen no disponible") }</p></div>
+            }
+        }
+    }
+
+    /// Choice between the facsimile and the page's further images, when it
+    /// has any.
+    fn render_image_selector(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let images = match ctx.props().page_info.as_ref() {
+            Some(p) if !p.images.is_empty() && self.image_page(ctx) == ctx.props().page => {
+                &p.images
+            }
+            _ => return html! {},
+        };
+        let labels = std::iter::once((lang.t("Facsímil").to_string(), true)).chain(
+            images
+                .iter()
+                .map(|image| (image.label.clone(), image.zones)),
+        );
+        html! {
+            <div class="image-selector" role="group" aria-label={lang.t("Imágenes de la página")}>
+                { for labels.enumerate().map(|(choice, (label, zoned))| html! {
+                    <button
+                        class={classes!((choice == self.image_choice).then_some("active"))}
+                        onclick={ctx.link().callback(move |_| TeiViewerMsg::ShowImage(choice))}
+                        title={(!zoned).then(|| lang.t("Sin las zonas de las líneas").to_string())}
+                    >
+                        { label }
+                    </button>
+                }) }
+            </div>
+        }
+    }
+
+    /// Shade every line's zone in proportion to its editorial markup, relative
+    /// to the most annotated line on the page.
+    fn render_heatmap(
+        &self,
+        lang: Lang,
+        doc: &TeiDocument,
+        display_w: u32,
+        display_h: u32,
+        declared_w: u32,
+        declared_h: u32,
+    ) -> Html {
+        if !self.show_heatmap || display_w == 0 || display_h == 0 {
+            return html! {};
+        }
+        let facsimile = &doc.facsimile;
+        let counts: Vec<(&Zone, usize)> = doc
+            .lines
+            .iter()
+            .filter_map(|line| {
+                let zone = facsimile.zones.get(&line.facs)?;
+                Some((zone, line.markup_count()))
+            })
+            .filter(|(zone, count)| *count > 0 && !zone.points.is_empty())
+            .collect();
+        let max = counts.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        if max == 0 {
+            return html! {};
+        }
+
+        let src_w = if declared_w > 0 {
+            declared_w
+        } else {
+            facsimile.width
+        };
+        let src_h = if declared_h > 0 {
+            declared_h
+        } else {
+            facsimile.height
+        };
+        let factor_x = if src_w > 0 {
+            display_w as f32 / src_w as f32
+        } else {
+            1.0
+        };
+        let factor_y = if src_h > 0 {
+            display_h as f32 / src_h as f32
+        } else {
+            1.0
+        };
+
+        html! {
+            <svg
+                class="heatmap-svg"
+                style={format!("position: absolute; top: 0; left: 0; width: {}px; height: {}px; pointer-events: none;", display_w, display_h)}
+                viewBox={format!("0 0 {} {}", display_w, display_h)}
+                preserveAspectRatio="none"
+                xmlns="http://www.w3.org/2000/svg"
+            >
+                { for counts.iter().map(|(zone, count)| {
+                    let points = zone
+                        .points
+                        .iter()
+                        .map(|(x, y)| format!("{:.2},{:.2}", *x as f32 * factor_x, *y as f32 * factor_y))
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    // Keep even a single intervention visible
+                    let opacity = 0.15 + 0.5 * *count as f32 / max as f32;
+                    html! {
+                        <polygon class="heatmap-zone" points={points} fill-opacity={format!("{:.2}", opacity)}>
+                            <title>{ lang.tf("{} intervenciones editoriales", &[count]) }</title>
+                        </polygon>
+                    }
+                }) }
+            </svg>
         }
     }
 
@@ -954,12 +4391,7 @@ impl TeiViewer {
                         preserveAspectRatio="none"
                         xmlns="http://www.w3.org/2000/svg"
                     >
-                        <polygon
-                            points={points_str}
-                            fill="rgba(255, 255, 0, 0.35)"
-                            stroke="yellow"
-                            stroke-width="2"
-                        />
+                        <polygon class="zone-highlight" points={points_str} />
                     </svg>
                 };
             }
@@ -969,15 +4401,44 @@ impl TeiViewer {
     }
 
     fn render_splitter(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
         let onmousedown = ctx
             .link()
             .callback(|e: MouseEvent| TeiViewerMsg::StartSplitterDrag(e));
+        let ondblclick = ctx
+            .link()
+            .callback(|_| TeiViewerMsg::SetPanelSize(DEFAULT_PANEL_WIDTH));
+        let width = self.panel_size();
+        let stacked = self.layout == Layout::Stacked;
+        let onkeydown = ctx.link().batch_callback(move |e: KeyboardEvent| {
+            // Shift moves in larger steps
+            let step = if e.shift_key() { 10.0 } else { 2.0 };
+            let new_width = match (e.key().as_str(), stacked) {
+                ("ArrowLeft", false) | ("ArrowUp", true) => width - step,
+                ("ArrowRight", false) | ("ArrowDown", true) => width + step,
+                ("Home", _) => MIN_PANEL_WIDTH,
+                ("End", _) => MAX_PANEL_WIDTH,
+                ("Enter", _) => DEFAULT_PANEL_WIDTH,
+                _ => return None,
+            };
+            e.prevent_default();
+            Some(TeiViewerMsg::SetPanelSize(new_width))
+        });
 
         html! {
             <div
-                class="splitter"
-                onmousedown={onmousedown}
-                title="Drag to resize panels"
+                class={classes!("splitter", stacked.then_some("splitter-horizontal"))}
+                role="separator"
+                tabindex="0"
+                aria-orientation={if stacked { "horizontal" } else { "vertical" }}
+                aria-label={lang.t(if stacked { "Alto del panel de imagen" } else { "Ancho del panel de imagen" })}
+                aria-valuemin={MIN_PANEL_WIDTH.to_string()}
+                aria-valuemax={MAX_PANEL_WIDTH.to_string()}
+                aria-valuenow={format!("{:.0}", width)}
+                {onmousedown}
+                {ondblclick}
+                {onkeydown}
+                title={lang.t("Arrastrar o usar ←/→ para redimensionar; doble clic para restablecer")}
             >
                 <div class="splitter-handle"></div>
             </div>
@@ -985,8 +4446,56 @@ impl TeiViewer {
     }
 
     fn render_text_panels(&self, ctx: &Context<Self>) -> Html {
+        let onpointerdown = ctx.link().batch_callback(|e: PointerEvent| {
+            (e.pointer_type() == "touch")
+                .then(|| TeiViewerMsg::SwipeStart(e.client_x(), e.client_y()))
+        });
+        let onpointerup = ctx.link().batch_callback(|e: PointerEvent| {
+            (e.pointer_type() == "touch")
+                .then(|| TeiViewerMsg::SwipeEnd(e.client_x(), e.client_y()))
+        });
+        let class = classes!(
+            "text-panels",
+            match self.page_turn {
+                Some(step) if step > 0 => Some("page-turn-next"),
+                Some(_) => Some("page-turn-prev"),
+                None => None,
+            }
+        );
+
+        if let Some(compare) = &ctx.props().compare {
+            return html! {
+                <div {class} {onpointerdown} {onpointerup}>
+                    { self.render_compare_panel(ctx.props().lang, compare) }
+                </div>
+            };
+        }
+        if self.continuous {
+            return html! {
+                <div {class}>
+                    { self.render_continuous_panel(ctx) }
+                </div>
+            };
+        }
+        if self.active_view == ViewType::Diff {
+            return html! {
+                <div {class} {onpointerdown} {onpointerup}>
+                    { self.render_diff_panel(ctx.props().lang) }
+                </div>
+            };
+        }
+        if let ViewType::Edition(id) = &self.active_view {
+            return html! {
+                <div {class} {onpointerdown} {onpointerup}>
+                    { self.render_edition_panel(ctx, id) }
+                    { self.render_notes_panel(ctx) }
+                    { self.render_toc_panel(ctx) }
+                    { self.render_glossary_panel(ctx) }
+                </div>
+            };
+        }
         html! {
-            <div class="text-panels">
+            <div {class} {onpointerdown} {onpointerup}>
                 { if self.active_view == ViewType::Diplomatic || self.active_view == ViewType::Both {
                     self.render_diplomatic_panel(ctx)
                 } else {
@@ -997,380 +4506,419 @@ impl TeiViewer {
                 } else {
                     html!{}
                 } }
+                { self.render_notes_panel(ctx) }
+                { self.render_toc_panel(ctx) }
+                { self.render_glossary_panel(ctx) }
+                { self.render_index_locorum_panel(ctx) }
+                { self.render_map_panel(ctx) }
+                { self.render_timeline_panel(ctx) }
+                { self.render_word_frequency_panel(ctx) }
+                { self.render_zone_editor_panel(ctx) }
+                { self.render_source_editor(ctx) }
             </div>
         }
     }
 
     fn render_diplomatic_panel(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
         if let Some(doc) = &self.diplomatic {
             html! {
                 <div class="text-panel diplomatic-panel">
-                    <h3>{"Edición diplomática"}</h3>
-                    <div class="text-content">
-                        { for doc.lines.iter().enumerate().map(|(idx, line)| self.render_line(ctx, line, idx)) }
-                        { self.render_footnotes(&doc.footnotes) }
-                    </div>
+                    <h3>{ lang.t("Edición diplomática") }</h3>
+                    { self.render_text_content(ctx, doc, ViewType::Diplomatic) }
                 </div>
             }
         } else {
             html! {
                 <div class="text-panel diplomatic-panel">
-                    <h3>{"Edición diplomática"}</h3>
-                    <p>{"Cargando..."}</p>
+                    <h3>{ lang.t("Edición diplomática") }</h3>
+                    { skeleton(lang.t("Cargando la edición diplomática…"), 12) }
                 </div>
             }
         }
     }
 
     fn render_translation_panel(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
         if let Some(doc) = &self.translation {
             html! {
                 <div class="text-panel translation-panel">
-                    <h3>{"Traducción"}</h3>
-                    <div class="text-content">
-                        { for doc.lines.iter().enumerate().map(|(idx, line)| self.render_line(ctx, line, idx)) }
-                        { self.render_footnotes(&doc.footnotes) }
-                    </div>
+                    { self.render_translation_heading(ctx) }
+                    { self.render_text_content(ctx, doc, ViewType::Translation) }
                 </div>
             }
         } else {
             html! {
                 <div class="text-panel translation-panel">
-                    <h3>{"Traducción"}</h3>
-                    <p>{"Cargando..."}</p>
+                    { self.render_translation_heading(ctx) }
+                    { skeleton(lang.t("Cargando la traducción…"), 12) }
                 </div>
             }
         }
     }
 
-    fn render_line(&self, ctx: &Context<Self>, line: &Line, idx: usize) -> Html {
-        let zone_id = line.facs.clone();
-        let is_active = self.locked_zone.as_ref() == Some(&zone_id)
-            || self.hovered_zone.as_ref() == Some(&zone_id);
-        let onmouseenter = {
-            let zid = zone_id.clone();
-            ctx.link()
-                .callback(move |_| TeiViewerMsg::HoverLine(zid.clone()))
-        };
-        let onmouseleave = ctx.link().callback(|_| TeiViewerMsg::ClearHover);
-        let onclick = {
-            let zid = zone_id.clone();
-            ctx.link()
-                .callback(move |_| TeiViewerMsg::ClickLine(zid.clone()))
+    /// Panel of a further edition of the page, named as in the manifest.
+    fn render_edition_panel(&self, ctx: &Context<Self>, id: &str) -> Html {
+        let lang = ctx.props().lang;
+        let label = ctx
+            .props()
+            .editions
+            .iter()
+            .find(|e| e.id == id)
+            .map_or_else(|| id.to_string(), |e| e.label.clone());
+        let content = match (&self.edition, self.progress.edition) {
+            (Some(doc), _) => self.render_text_content(ctx, doc, ViewType::Edition(id.to_string())),
+            (None, Progress::Failed) => html! {
+                <p class="panel-empty">{ lang.t("Esta edición no está disponible para esta página") }</p>
+            },
+            (None, _) => skeleton(&lang.tf("Cargando {}…", &[&label]), 12),
         };
-        let class = if is_active { "line active" } else { "line" };
-
         html! {
-            <div class={class} {onmouseenter} {onmouseleave} {onclick}>
-                <span class="line-number">{ idx + 1 }</span>
-                <span class="line-content">{ for line.content.iter().map(|n| self.render_text_node(n)) }</span>
+            <div class="text-panel edition-panel">
+                <h3>{ label }</h3>
+                { content }
             </div>
         }
     }
 
-    fn render_text_node(&self, node: &TextNode) -> Html {
-        match node {
-            TextNode::Text { content } => html! { <>{content}</> },
-            TextNode::Abbr { abbr, expan } => html! {
-                <abbr title={format!("[Abreviatura] {}", expan)} class="abbreviation" data-tooltip-type="abbr">{ abbr }</abbr>
-            },
-            TextNode::Choice { sic, corr } => html! {
-                <span class="correction" title={format!("[Corrección] Lectura: {}", corr)}>{ sic }</span>
-            },
-            TextNode::Regularised { orig, reg } => html! {
-                <span class="regularised" title={format!("[Regularización] Regularizado: {}", reg)}>{ orig }</span>
-            },
-            TextNode::Num { value, tipo, text } => html! {
-                <span class="number" title={format!("[Número] Valor: {} | Tipo: {}", value, tipo)}>{ text }</span>
-            },
-            TextNode::PersName {
-                content,
-                tipo,
-                firstname,
-                continued,
-                ref_uri,
-            } => {
-                // Build a descriptive title from available attributes
-                let mut title_parts: Vec<String> = Vec::new();
-                if !tipo.is_empty() {
-                    title_parts.push(format!("[Persona] Tipo: {}", tipo));
-                } else {
-                    title_parts.push("[Persona]".to_string());
-                }
-                if let Some(fnme) = firstname {
-                    title_parts.push(format!("Nombre: {}", fnme));
-                }
-                if continued.unwrap_or(false) {
-                    title_parts.push("Continúa".to_string());
-                }
-                if let Some(r) = ref_uri {
-                    title_parts.push(format!("Ref: {}", r));
-                }
-
-                // Check for nested abbreviations and add their info to the combined title
-                for node in content {
-                    if let TextNode::Abbr { abbr, expan } = node {
-                        title_parts.push(format!("[Abreviatura] {}: {}", abbr, expan));
-                    }
+    /// Fetch the page in the further edition `id`, from the cache when it
+    /// was parsed recently.
+    fn load_edition(&mut self, ctx: &Context<Self>, id: String) {
+        self.progress.edition = Progress::Loading;
+        let generation = self.load_generation;
+        let link = ctx.link().clone();
+        let key = CacheKey::new(&self.current_project, self.current_page, &id);
+        if let Some(doc) = self.doc_cache.get(&key) {
+            link.send_message(TeiViewerMsg::EditionLoaded(generation, id, Ok(doc)));
+            return;
+        }
+        let path = ctx.props().config.get_edition_path(self.current_page, &id);
+        if let Some(xml) = local_document::read(&path) {
+            let result = xml.and_then(|xml| crate::tei_parser::parse_tei_xml(&xml));
+            link.send_message(TeiViewerMsg::EditionLoaded(generation, id, result));
+            return;
+        }
+        let (load, text) = net::get_text_abortable(ctx.props().config.sources_for(&path));
+        self.page_loads.insert(PageLoad::Text(id.clone()), load);
+        spawn_local(async move {
+            let result = match text.await {
+                Err(FetchError::Aborted { .. }) => return,
+                Ok(xml) => {
+                    offline::keep_text(&path, &xml);
+                    crate::tei_parser::parse_tei_xml(&xml)
                 }
+                Err(e) => Err(e.to_string()),
+            };
+            link.send_message(TeiViewerMsg::EditionLoaded(generation, id, result));
+        });
+    }
 
-                let title = title_parts.join(" | ");
-
-                html! {
-                    <span class="person-name" title={title} data-tooltip-type="person">
-                        { for content.iter().map(|n| self.render_text_node_no_abbr_tooltip(n)) }
-                    </span>
-                }
-            }
-            TextNode::PlaceName { name, attrs } => {
-                // Show only the visible place name inline. Ancillary attributes
-                // (e.g., country, region) are exposed via the element's title so
-                // they appear when hovering. This keeps the inline flow intact.
-                let mut title_parts: Vec<String> = Vec::new();
-                for (k, v) in attrs.iter() {
-                    // Normalize key names for display (optional)
-                    title_parts.push(format!("{}: {}", k, v));
-                }
-                let title = if title_parts.is_empty() {
-                    format!("[Lugar]: {}", name)
-                } else {
-                    format!("{} — {}", title_parts.join("; "), name)
+    /// Diplomatic text of the current page followed by the next pages of
+    /// the project, appended as the reader nears the bottom.
+    fn render_continuous_panel(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let Some(first) = &self.diplomatic else {
+            return html! {
+                <div class="text-panel diplomatic-panel">
+                    <h3>{ lang.t("Edición diplomática") }</h3>
+                    { skeleton(lang.t("Cargando la edición diplomática…"), 12) }
+                </div>
+            };
+        };
+        let onscroll = ctx.link().callback(|e: Event| {
+            let Some(el) = e.target_dyn_into::<web_sys::HtmlElement>() else {
+                return TeiViewerMsg::ContinuousScrolled(0.0, 0.0, 0.0, None);
+            };
+            let top = el.scroll_top() as f64;
+            let height = el.client_height() as f64;
+            // Last page section starting above the middle of the panel
+            let middle = top + height / 2.0;
+            let sections = el.get_elements_by_class_name("continuous-page");
+            let mut centered = None;
+            for i in 0..sections.length() {
+                let Some(section) = sections
+                    .item(i)
+                    .and_then(|s| s.dyn_into::<web_sys::HtmlElement>().ok())
+                else {
+                    continue;
                 };
-                html! {
-                    <span class="place-name" title={title.clone()}>{ name }</span>
+                if section.offset_top() as f64 > middle {
+                    break;
                 }
+                centered = section
+                    .get_attribute("data-page")
+                    .and_then(|p| p.parse().ok());
             }
-            TextNode::Ref {
-                ref_type,
-                target,
-                content,
-            } => html! {
-                <span class="ref" title={format!("[Referencia] Tipo: {} | Destino: {}", ref_type, target)}>{ content }</span>
-            },
-            TextNode::Unclear { reason, content } => html! {
-                <span class="unclear" title={format!("[Incierto] Razón: {}", reason)}>{ content }</span>
-            },
-            TextNode::RsType { rs_type, content } => html! {
-                <span class={format!("rs-type rs-{}", rs_type)} title={format!("[Cadena de Referencia] Tipo: {}", rs_type)}>{ content }</span>
-            },
-            TextNode::NoteRef { note_id, n } => html! {
-                <sup class="footnote-ref" title="[Nota al pie]">
-                    <a id={format!("ref_{}", note_id)} href={format!("#{}", note_id)}>{ n }</a>
-                </sup>
-            },
-            TextNode::InlineNote { content, n } => html! {
-                <sup class="footnote-ref" title={format!("[Nota al pie] {}", content)}>{ n }</sup>
-            },
-            TextNode::Hi { rend, content } => {
-                // Handle multiple rend values (e.g., "bold italic")
-                // Render nested nodes instead of a single string content.
-                // We rely on text nodes to carry their own leading/trailing space,
-                // so simply rendering nested nodes in order preserves spacing.
-                let classes = rend
-                    .split_whitespace()
-                    .map(|r| format!("hi-{}", r))
-                    .collect::<Vec<_>>()
-                    .join(" ");
+            TeiViewerMsg::ContinuousScrolled(top, height, el.scroll_height() as f64, centered)
+        });
+        let image_page = self.image_page(ctx);
+        let pages = std::iter::once((ctx.props().page, first))
+            .chain(self.continuous_docs.iter().map(|(p, d)| (*p, d)));
 
-                // Only show titles for non-basic formatting to avoid clustering
-                // Basic formatting (bold, italic, underline) is visually obvious
-                let basic_formatting = ["bold", "italic", "underline", "superscript", "subscript"];
-                let show_title = !rend
-                    .split_whitespace()
-                    .all(|r| basic_formatting.contains(&r));
+        html! {
+            <div class="text-panel diplomatic-panel continuous-panel">
+                <h3>{ lang.t("Edición diplomática — scroll continuo") }</h3>
+                <div class="text-content" {onscroll}>
+                    { for pages.map(|(page, doc)| html! {
+                        <section class="continuous-page" key={page} data-page={page.to_string()}>
+                            <h4 class="continuous-page-title">{lang.tf("Página {}", &[&page])}</h4>
+                            { for (0..doc.lines.len()).map(|index| {
+                                let zone_id = &doc.lines[index].facs;
+                                let is_active = page == image_page
+                                    && (self.locked_zone.as_ref() == Some(zone_id)
+                                        || self.hovered_zone.as_ref() == Some(zone_id));
+                                html! {
+                                    <LineView
+                                        lang={ctx.props().lang}
+                                        key={index}
+                                        doc={doc.clone()}
+                                        {index}
+                                        {is_active}
+                                        sparse_numbering={ctx.props().settings.sparse_line_numbers}
+                                        reading={ctx.props().settings.reading()}
+                                        on_hover={self.on_line_hover.clone()}
+                                        on_leave={self.on_line_leave.clone()}
+                                        on_click={self.on_line_click.clone()}
+                                        on_term={self.on_term_click.clone()}
+                                    />
+                                }
+                            }) }
+                        </section>
+                    }) }
+                    { if self.continuous_loading {
+                        html! { <p class="continuous-loading">{ lang.t("Cargando la página siguiente...") }</p> }
+                    } else {
+                        html! {}
+                    } }
+                </div>
+            </div>
+        }
+    }
 
-                if show_title {
-                    html! {
-                        <span class={classes} title={format!("[Resaltado] Estilo: {}", rend)}>
-                            { for content.iter().map(|n| self.render_text_node(n)) }
-                        </span>
-                    }
-                } else {
-                    html! {
-                        <span class={classes}>
-                            { for content.iter().map(|n| self.render_text_node(n)) }
-                        </span>
-                    }
-                }
-            }
+    /// Word-level differences between the diplomatic reading (abbr/sic/orig)
+    /// and the normalized one (expan/corr/reg), line by line.
+    /// This project's diplomatic edition against another project's edition
+    /// of the same folio: words only here are struck, words only there are
+    /// inserted.
+    fn render_compare_panel(&self, lang: Lang, compare: &Comparison) -> Html {
+        let heading = lang.tf("Esta edición → {}", &[&compare.config.name]);
+        if compare.page.is_none() {
+            return html! {
+                <div class="text-panel diff-panel">
+                    <h3>{ heading }</h3>
+                    <p>{ lang.tf("{} no tiene una página con la misma etiqueta.", &[&compare.config.name]) }</p>
+                </div>
+            };
         }
+        let (Some(doc), Some(other)) = (&self.diplomatic, &self.compare_doc) else {
+            return html! {
+                <div class="text-panel diff-panel">
+                    <h3>{ heading }</h3>
+                    <p>{ lang.t("Cargando...") }</p>
+                </div>
+            };
+        };
+        let diff = diff_documents(doc, other, Reading::Diplomatic);
+        Self::render_line_diffs(lang, &heading, &diff)
     }
 
-    fn render_text_node_no_abbr_tooltip(&self, node: &TextNode) -> Html {
-        match node {
-            TextNode::Text { content } => html! { <>{content}</> },
-            TextNode::Abbr { abbr, expan: _ } => html! {
-                <abbr class="abbreviation">{ abbr }</abbr>
-            },
-            TextNode::Choice { sic, corr } => html! {
-                <span class="correction" title={format!("[Corrección] Lectura: {}", corr)}>{ sic }</span>
-            },
-            TextNode::Regularised { orig, reg } => html! {
-                <span class="regularised" title={format!("[Regularización] Original: {}", orig)}>{ reg }</span>
-            },
-            TextNode::Num { value, tipo, text } => html! {
-                <span class="number" title={format!("[Número] Valor: {} | Tipo: {}", value, tipo)}>{ text }</span>
-            },
-            TextNode::PersName {
-                content,
-                tipo,
-                firstname,
-                continued,
-                ref_uri,
-            } => {
-                // Nested person names should use regular rendering
-                self.render_text_node(&TextNode::PersName {
-                    content: content.clone(),
-                    tipo: tipo.clone(),
-                    firstname: firstname.clone(),
-                    continued: *continued,
-                    ref_uri: ref_uri.clone(),
-                })
-            }
-            TextNode::PlaceName { name, attrs } => {
-                let mut title_parts: Vec<String> = Vec::new();
-                for (k, v) in attrs.iter() {
-                    title_parts.push(format!("{}: {}", k, v));
-                }
-                let title = if title_parts.is_empty() {
-                    format!("[Lugar]: {}", name)
-                } else {
-                    format!("{} — {}", title_parts.join("; "), name)
-                };
+    fn render_diff_panel(&self, lang: Lang) -> Html {
+        let Some(doc) = &self.diplomatic else {
+            return html! {
+                <div class="text-panel diff-panel">
+                    <h3>{ lang.t("Diferencias") }</h3>
+                    { skeleton(lang.t("Cargando la edición diplomática…"), 12) }
+                </div>
+            };
+        };
+        let diff = diff_readings(doc, Reading::Diplomatic, Reading::Normalized);
+        Self::render_line_diffs(lang, lang.t("Diplomática → Normalizada"), &diff)
+    }
+
+    fn render_line_diffs(lang: Lang, heading: &str, diff: &[LineDiff]) -> Html {
+        let changed = diff.iter().filter(|l| l.is_changed()).count();
+
+        html! {
+            <div class="text-panel diff-panel">
+                <h3>{ heading.to_string() }</h3>
+                <p class="diff-summary">{ lang.tf("{} de {} líneas con cambios", &[&changed, &diff.len()]) }</p>
+                <div class="text-content">
+                    { for diff.iter().map(|line| html! {
+                        <div class={classes!("line", "diff-line", line.is_changed().then_some("diff-changed"))}>
+                            <span class="line-number">{ line.index + 1 }</span>
+                            <span class="line-content">
+                                { for line.ops.iter().map(|op| match op {
+                                    DiffOp::Same(text) => html! { <span>{format!("{} ", text)}</span> },
+                                    DiffOp::Deleted(text) => html! { <><del class="diff-del">{text}</del>{" "}</> },
+                                    DiffOp::Inserted(text) => html! { <><ins class="diff-ins">{text}</ins>{" "}</> },
+                                }) }
+                            </span>
+                        </div>
+                    }) }
+                </div>
+            </div>
+        }
+    }
+
+    /// Lines to render for a panel with `total` lines at the given scroll
+    /// position. Short transcriptions are always rendered in full.
+    fn line_window(total: usize, (scroll_top, client_h): (f64, f64)) -> (usize, usize) {
+        if total <= VIRTUALIZE_THRESHOLD {
+            return (0, total);
+        }
+        // Before the first scroll event assume a tall panel
+        let viewport_h = if client_h > 0.0 { client_h } else { 1200.0 };
+        visible_range(
+            total,
+            scroll_top,
+            viewport_h,
+            LINE_HEIGHT_ESTIMATE,
+            LINE_OVERSCAN,
+        )
+    }
+
+    /// Scrollable line list of a text panel. Long transcriptions only render
+    /// the lines around the visible window, padded by spacers so the
+    /// scrollbar keeps its full extent.
+    fn render_text_content(
+        &self,
+        ctx: &Context<Self>,
+        doc: &Rc<TeiDocument>,
+        panel: ViewType,
+    ) -> Html {
+        let scroll = match panel {
+            ViewType::Translation => self.trad_scroll,
+            _ => self.dip_scroll,
+        };
+        let no_hits = Vec::new();
+        let hits = match panel {
+            ViewType::Translation => &self.trad_hits,
+            ViewType::Edition(_) => &no_hits,
+            _ => &self.dip_hits,
+        };
+        // Notes are anchored to diplomatic lines
+        let notes = if panel == ViewType::Diplomatic {
+            self.annotations.for_page(self.current_page)
+        } else {
+            Vec::new()
+        };
+        let total = doc.lines.len();
+        let (start, end) = Self::line_window(total, scroll);
+        let bibliography = self.render_bibliography(ctx.props().lang, &panel);
+        let onscroll = ctx.link().callback(move |e: Event| {
+            let (top, height) = e
+                .target_dyn_into::<web_sys::Element>()
+                .map(|el| (el.scroll_top() as f64, el.client_height() as f64))
+                .unwrap_or((0.0, 0.0));
+            TeiViewerMsg::TextScrolled(panel.clone(), top, height)
+        });
+        let spacer = |lines: usize| {
+            if lines == 0 {
+                html! {}
+            } else {
                 html! {
-                    <span class="place-name" title={title}>{ name }</span>
+                    <div
+                        class="line-spacer"
+                        aria-hidden="true"
+                        style={format!("height: {}px;", lines as f64 * LINE_HEIGHT_ESTIMATE)}
+                    />
                 }
             }
-            TextNode::Ref {
-                ref_type,
-                target,
-                content,
-            } => html! {
-                <span class="ref" title={format!("[Referencia] Tipo: {} | Destino: {}", ref_type, target)}>{ content }</span>
-            },
-            TextNode::Unclear { reason, content } => html! {
-                <span class="unclear" title={format!("[Incierto] Razón: {}", reason)}>{ content }</span>
-            },
-            TextNode::RsType { rs_type, content } => html! {
-                <span class={format!("rs-type rs-{}", rs_type)} title={format!("[Cadena de Referencia] Tipo: {}", rs_type)}>{ content }</span>
-            },
-            TextNode::NoteRef { note_id, n } => html! {
-                <sup class="footnote-ref" title="[Nota al pie]">
-                    <a id={format!("ref_{}", note_id)} href={format!("#{}", note_id)}>{ n }</a>
-                </sup>
-            },
-            TextNode::InlineNote { content, n } => html! {
-                <sup class="footnote-ref" title={format!("[Nota al pie] {}", content)}>{ n }</sup>
-            },
-            TextNode::Hi { rend, content } => {
-                let classes = rend
-                    .split_whitespace()
-                    .map(|r| format!("hi-{}", r))
-                    .collect::<Vec<_>>()
-                    .join(" ");
-
-                let basic_formatting = ["bold", "italic", "underline", "superscript", "subscript"];
-                let show_title = !rend
-                    .split_whitespace()
-                    .all(|r| basic_formatting.contains(&r));
+        };
 
-                if show_title {
-                    html! {
-                        <span class={classes} title={format!("[Resaltado] Estilo: {}", rend)}>
-                            { for content.iter().map(|n| self.render_text_node_no_abbr_tooltip(n)) }
-                        </span>
-                    }
-                } else {
+        html! {
+            <div class="text-content" {onscroll}>
+                { spacer(start) }
+                { for (start..end).map(|index| {
+                    let zone_id = &doc.lines[index].facs;
+                    let is_active = self.locked_zone.as_ref() == Some(zone_id)
+                        || self.hovered_zone.as_ref() == Some(zone_id);
+                    let is_match = hits.binary_search(&index).is_ok();
+                    let line_notes: Vec<_> = notes.iter().filter(|n| n.line == index).collect();
+                    let note = line_notes.first().map(|first| {
+                        let text = line_notes.iter().map(|n| n.text.as_str()).collect::<Vec<_>>().join("\n");
+                        (AttrValue::from(first.color.clone()), AttrValue::from(text))
+                    });
                     html! {
-                        <span class={classes}>
-                            { for content.iter().map(|n| self.render_text_node_no_abbr_tooltip(n)) }
-                        </span>
+                        <LineView
+                            lang={ctx.props().lang}
+                            key={index}
+                            doc={doc.clone()}
+                            {index}
+                            {is_active}
+                            {is_match}
+                            {note}
+                            sparse_numbering={ctx.props().settings.sparse_line_numbers}
+                            reading={ctx.props().settings.reading()}
+                            on_hover={self.on_line_hover.clone()}
+                            on_leave={self.on_line_leave.clone()}
+                            on_click={self.on_line_click.clone()}
+                            on_term={self.on_term_click.clone()}
+                        />
                     }
-                }
-            }
+                }) }
+                { spacer(total - end) }
+                { self.render_footnotes(ctx.props().lang, &doc.footnotes) }
+                { bibliography }
+            </div>
         }
     }
 
     fn render_legend(&self, ctx: &Context<Self>) -> Html {
-        if !self.show_legend {
+        let lang = ctx.props().lang;
+        if !self.show_legend || ctx.props().kiosk {
             return html! {};
         }
 
         let on_close = ctx.link().callback(|_| TeiViewerMsg::ToggleLegend);
 
+        let defaults = vec![
+            LegendItem::new("abbreviation", "Ab", lang.t("Abreviatura")),
+            LegendItem::new("correction", "Co", lang.t("Corrección")),
+            LegendItem::new("regularised", "Rg", lang.t("Regularización")),
+            LegendItem::new("number", "12", lang.t("Número")),
+            LegendItem::new("person-name", "Pe", lang.t("Persona")),
+            LegendItem::new("place-name", lang.t("Lu"), lang.t("Lugar")),
+            LegendItem::new("ref", "Rf", lang.t("Referencia")),
+            LegendItem::new("unclear", "??", lang.t("Texto incierto")),
+            LegendItem::new("rs-divine", "Dv", lang.t("Entidad divina")),
+            LegendItem::new("rs-astral", "As", lang.t("Entidad astral")),
+            LegendItem::new("footnote-ref", "1", lang.t("Nota al pie")),
+            LegendItem::new("hi-bold", lang.t("N"), lang.t("Negrita")),
+            LegendItem::new("hi-italic", lang.t("C"), lang.t("Cursiva")),
+            LegendItem::new("hi-superscript", "x²", lang.t("Superíndice")),
+            LegendItem::new("hi-subscript", "H₂O", lang.t("Subíndice")),
+        ];
+        let items = legend::merge(defaults, &ctx.props().legend);
+
         html! {
             <div class="legend-panel">
                 <div class="legend-header">
-                    <h3>{"Leyenda de Colores"}</h3>
+                    <h3>{ lang.t("Leyenda de Colores") }</h3>
                     <button class="close-btn" onclick={on_close}>{"×"}</button>
                 </div>
                 <div class="legend-items">
-                    <div class="legend-item">
-                        <span class="legend-swatch abbreviation">{"Ab"}</span>
-                        <span class="legend-label">{"Abreviatura"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch correction">{"Co"}</span>
-                        <span class="legend-label">{"Corrección"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch regularised">{"Rg"}</span>
-                        <span class="legend-label">{"Regularización"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch number">{"12"}</span>
-                        <span class="legend-label">{"Número"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch person-name">{"Pe"}</span>
-                        <span class="legend-label">{"Persona"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch place-name">{"Lu"}</span>
-                        <span class="legend-label">{"Lugar"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch ref">{"Rf"}</span>
-                        <span class="legend-label">{"Referencia"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch unclear">{"??"}</span>
-                        <span class="legend-label">{"Texto incierto"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch rs-divine">{"Dv"}</span>
-                        <span class="legend-label">{"Entidad divina"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch rs-astral">{"As"}</span>
-                        <span class="legend-label">{"Entidad astral"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch footnote-ref">{"1"}</span>
-                        <span class="legend-label">{"Nota al pie"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch hi-bold">{"N"}</span>
-                        <span class="legend-label">{"Negrita"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch hi-italic">{"C"}</span>
-                        <span class="legend-label">{"Cursiva"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch hi-superscript">{"x²"}</span>
-                        <span class="legend-label">{"Superíndice"}</span>
-                    </div>
-                    <div class="legend-item">
-                        <span class="legend-swatch hi-subscript">{"H₂O"}</span>
-                        <span class="legend-label">{"Subíndice"}</span>
-                    </div>
+                    { for items.into_iter().map(|item| html! {
+                        <div class="legend-item">
+                            <span class={classes!("legend-swatch", item.class)}>{ item.swatch }</span>
+                            <span class="legend-label">{ item.label }</span>
+                        </div>
+                    }) }
                 </div>
             </div>
         }
     }
 
-    fn render_footnotes(&self, footnotes: &[Footnote]) -> Html {
+    /// The project's colors for the text's categories.
+    fn render_legend_style(&self, ctx: &Context<Self>) -> Html {
+        let css = legend::style_sheet(&ctx.props().legend);
+        if css.is_empty() {
+            return html! {};
+        }
+        html! { <style>{ css }</style> }
+    }
+
+    fn render_footnotes(&self, lang: Lang, footnotes: &[Footnote]) -> Html {
         if footnotes.is_empty() {
             return html! {};
         }
@@ -1378,7 +4926,7 @@ impl TeiViewer {
         html! {
             <div class="footnotes-section">
                 <hr class="footnotes-divider" />
-                <h4>{"Notas"}</h4>
+                <h4>{ lang.t("Notas") }</h4>
                 <ol class="footnotes-list">
                     { for footnotes.iter().map(|note| {
                         let note_num = note.n.clone();
@@ -1395,12 +4943,41 @@ impl TeiViewer {
         }
     }
 
+    /// Works the manifest cites for the page, under the notes; with both
+    /// texts on screen, under the diplomatic one only.
+    fn render_bibliography(&self, lang: Lang, panel: &ViewType) -> Html {
+        if self.bibliography.is_empty()
+            || (*panel == ViewType::Translation && self.active_view == ViewType::Both)
+        {
+            return html! {};
+        }
+
+        html! {
+            <div class="footnotes-section page-bibliography">
+                <h4>{ lang.t("Bibliografía de esta página") }</h4>
+                <ul class="page-bibliography-list">
+                    { for self.bibliography.iter().map(|entry| html! {
+                        <li>
+                            { match entry.url() {
+                                Some(url) => html! {
+                                    <a href={url.to_string()} target="_blank" rel="noopener noreferrer">{ entry.text() }</a>
+                                },
+                                None => html! { entry.text() },
+                            } }
+                        </li>
+                    }) }
+                </ul>
+            </div>
+        }
+    }
+
     fn render_metadata_popup(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
         if !self.show_metadata_popup {
             return html! {};
         }
-        let dip = self.diplomatic.as_ref();
-        let trad = self.translation.as_ref();
+        let dip = self.diplomatic.as_deref();
+        let trad = self.translation.as_deref();
         let on_close = ctx.link().callback(|_| TeiViewerMsg::ToggleMetadata);
         let on_toggle_dip = ctx.link().callback(|_| TeiViewerMsg::ToggleMetadataDip);
         let on_toggle_trad = ctx.link().callback(|_| TeiViewerMsg::ToggleMetadataTrad);
@@ -1409,30 +4986,31 @@ impl TeiViewer {
             <div class="metadata-popup-overlay">
                 <div class="metadata-popup">
                     <div class="metadata-popup-header">
-                        <h2>{"Metadatos"}</h2>
+                        <h2>{ lang.t("Metadatos") }</h2>
                         <button class="close-btn" onclick={on_close}>{"×"}</button>
                     </div>
+                    { self.render_project_credits(ctx) }
                     <div class="metadata-popup-selectors">
                         <label>
                             <input type="radio" name="metadata-select"
                                 checked={matches!(self.metadata_selected, Some(ViewType::Diplomatic))}
                                 onclick={on_toggle_dip} />
-                            {"Diplomática"}
+                            { lang.t("Diplomática") }
                         </label>
                         <label>
                             <input type="radio" name="metadata-select"
                                 checked={matches!(self.metadata_selected, Some(ViewType::Translation))}
                                 onclick={on_toggle_trad} />
-                            {"Traducción"}
+                            { lang.t("Traducción") }
                         </label>
                     </div>
                     <div class="metadata-popup-content">
                         { if matches!(self.metadata_selected, Some(ViewType::Diplomatic)) && dip.is_some() {
-                            self.render_metadata_panel_for(dip, "Edición Diplomática")
+                            self.render_metadata_panel_for(lang, dip, lang.t("Edición diplomática"))
                         } else if matches!(self.metadata_selected, Some(ViewType::Translation)) && trad.is_some() {
-                            self.render_metadata_panel_for(trad, "Traducción")
+                            self.render_metadata_panel_for(lang, trad, lang.t("Traducción"))
                         } else {
-                            html!{ <p>{"No hay metadatos disponibles para la edición seleccionada."}</p> }
+                            html!{ <p>{ lang.t("No hay metadatos disponibles para la edición seleccionada.") }</p> }
                         } }
                     </div>
                 </div>
@@ -1440,31 +5018,67 @@ impl TeiViewer {
         }
     }
 
-    fn render_metadata_panel_for(&self, doc_opt: Option<&TeiDocument>, label: &str) -> Html {
+    /// License, DOI and citation of the project.
+    fn render_project_credits(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        let metadata = &ctx.props().project_metadata;
+        let url = web_sys::window()
+            .and_then(|w| w.location().href().ok())
+            .unwrap_or_default();
+        let citation = metadata.citation(&ctx.props().project_name, &url);
+        let link = |text: &str| {
+            if text.starts_with("http://") || text.starts_with("https://") {
+                html! { <a href={text.to_string()} target="_blank" rel="noopener">{ text }</a> }
+            } else {
+                html! { { text } }
+            }
+        };
+        html! {
+            <div class="metadata-project">
+                <dl>
+                    if let Some(license) = &metadata.license {
+                        <dt>{ lang.t("Licencia:") }</dt><dd>{ link(license) }</dd>
+                    }
+                    if let Some(doi) = metadata.doi_url() {
+                        <dt>{"DOI:"}</dt><dd>{ link(&doi) }</dd>
+                    }
+                </dl>
+                <h4>{ lang.t("Cómo citar") }</h4>
+                <p class="citation">{ citation }</p>
+            </div>
+        }
+    }
+
+    fn render_metadata_panel_for(
+        &self,
+        lang: Lang,
+        doc_opt: Option<&TeiDocument>,
+        label: &str,
+    ) -> Html {
         if let Some(doc) = doc_opt {
             html! {
                 <>
                     <h3>{ label }</h3>
                     <dl>
-                        <dt>{"Título:"}</dt><dd>{ &doc.metadata.title }</dd>
-                        <dt>{"Autor:"}</dt><dd>{ &doc.metadata.author }</dd>
-                        <dt>{"Editor:"}</dt><dd>{ &doc.metadata.editor }</dd>
-                        <dt>{"Tipo de Edición:"}</dt><dd>{ &doc.metadata.edition_type }</dd>
-                        <dt>{"Idioma:"}</dt><dd>{ &doc.metadata.language }</dd>
-                        { if let Some(c) = &doc.metadata.country { html!{<><dt>{"País:"}</dt><dd>{c}</dd></>} } else { html!{} } }
-                        { if let Some(s) = &doc.metadata.settlement { html!{<><dt>{"Ciudad:"}</dt><dd>{s}</dd></>} } else { html!{} } }
-                        { if let Some(i) = &doc.metadata.institution { html!{<><dt>{"Institución:"}</dt><dd>{i}</dd></>} } else { html!{} } }
-                        { if let Some(col) = &doc.metadata.collection { html!{<><dt>{"Colección:"}</dt><dd>{col}</dd></>} } else { html!{} } }
-                        { if let Some(sig) = &doc.metadata.siglum { html!{<><dt>{"Sigla:"}</dt><dd>{sig}</dd></>} } else { html!{} } }
+                        <dt>{ lang.t("Título:") }</dt><dd>{ &doc.metadata.title }</dd>
+                        <dt>{ lang.t("Autor:") }</dt><dd>{ &doc.metadata.author }</dd>
+                        <dt>{ lang.t("Editor:") }</dt><dd>{ &doc.metadata.editor }</dd>
+                        <dt>{ lang.t("Tipo de Edición:") }</dt><dd>{ &doc.metadata.edition_type }</dd>
+                        <dt>{ lang.t("Idioma:") }</dt><dd>{ &doc.metadata.language }</dd>
+                        { if let Some(c) = &doc.metadata.country { html!{<><dt>{ lang.t("País:") }</dt><dd>{c}</dd></>} } else { html!{} } }
+                        { if let Some(s) = &doc.metadata.settlement { html!{<><dt>{ lang.t("Ciudad:") }</dt><dd>{s}</dd></>} } else { html!{} } }
+                        { if let Some(i) = &doc.metadata.institution { html!{<><dt>{ lang.t("Institución:") }</dt><dd>{i}</dd></>} } else { html!{} } }
+                        { if let Some(col) = &doc.metadata.collection { html!{<><dt>{ lang.t("Colección:") }</dt><dd>{col}</dd></>} } else { html!{} } }
+                        { if let Some(sig) = &doc.metadata.siglum { html!{<><dt>{ lang.t("Sigla:") }</dt><dd>{sig}</dd></>} } else { html!{} } }
                     </dl>
-                    <h4>{"Información de Imagen"}</h4>
+                    <h4>{ lang.t("Información de Imagen") }</h4>
                     <dl>
-                        <dt>{"ID de Superficie:"}</dt><dd>{ &doc.facsimile.surface_id }</dd>
-                        <dt>{"Archivo de Imagen:"}</dt><dd>{ &doc.facsimile.image_url }</dd>
-                        <dt>{"Dimensiones Declaradas:"}</dt><dd>{ format!("{} × {} píxeles", doc.facsimile.width, doc.facsimile.height) }</dd>
-                        <dt>{"Dimensiones Intrínsecas (cargadas):"}</dt><dd>{ format!("{} × {} píxeles", self.image_nat_w, self.image_nat_h) }</dd>
-                        <dt>{"Zonas:"}</dt><dd>{ format!("{} zonas", doc.facsimile.zones.len()) }</dd>
-                        <dt>{"Líneas:"}</dt><dd>{ format!("{} líneas", doc.lines.len()) }</dd>
+                        <dt>{ lang.t("ID de Superficie:") }</dt><dd>{ &doc.facsimile.surface_id }</dd>
+                        <dt>{ lang.t("Archivo de Imagen:") }</dt><dd>{ &doc.facsimile.image_url }</dd>
+                        <dt>{ lang.t("Dimensiones Declaradas:") }</dt><dd>{ lang.tf("{} × {} píxeles", &[&doc.facsimile.width, &doc.facsimile.height]) }</dd>
+                        <dt>{ lang.t("Dimensiones Intrínsecas (cargadas):") }</dt><dd>{ lang.tf("{} × {} píxeles", &[&self.image_nat_w, &self.image_nat_h]) }</dd>
+                        <dt>{ lang.t("Zonas:") }</dt><dd>{ lang.tf("{} zonas", &[&doc.facsimile.zones.len()]) }</dd>
+                        <dt>{ lang.t("Líneas:") }</dt><dd>{ lang.tf("{} líneas", &[&doc.lines.len()]) }</dd>
                     </dl>
                 </>
             }
@@ -1473,29 +5087,202 @@ impl TeiViewer {
         }
     }
 
+    /// Single-panel interface for phones: a tab bar plus the selected
+    /// panel, switched by tapping a tab or swiping horizontally.
+    fn render_mobile(&self, ctx: &Context<Self>) -> Html {
+        let onpointerdown = ctx.link().batch_callback(|e: PointerEvent| {
+            (e.pointer_type() == "touch")
+                .then(|| TeiViewerMsg::SwipeStart(e.client_x(), e.client_y()))
+        });
+        let onpointerup = ctx.link().batch_callback(|e: PointerEvent| {
+            (e.pointer_type() == "touch")
+                .then(|| TeiViewerMsg::SwipeEnd(e.client_x(), e.client_y()))
+        });
+
+        let content = match self.mobile_tab {
+            MobileTab::Image => self.render_image_panel(ctx),
+            MobileTab::Diplomatic => self.render_diplomatic_panel(ctx),
+            MobileTab::Translation => self.render_translation_panel(ctx),
+            MobileTab::Commentary => html! {
+                <div class="text-panel commentary-panel">
+                    { self.render_commentary_content(ctx.props().lang) }
+                </div>
+            },
+        };
+
+        html! {
+            <div class="tei-viewer-container mobile">
+                { self.render_controls(ctx) }
+                { self.render_legend(ctx) }
+                { self.render_legend_style(ctx) }
+                if ctx.props().panels != Panels::Image {
+                <div class="mobile-tabs" role="tablist">
+                    { for MobileTab::ALL.iter().filter(|&&tab| {
+                        ctx.props().panels == Panels::All || tab != MobileTab::Image
+                    }).map(|&tab| {
+                        let onclick = ctx.link().callback(move |_| TeiViewerMsg::SelectTab(tab));
+                        let selected = tab == self.mobile_tab;
+                        html! {
+                            <button
+                                role="tab"
+                                class={classes!("mobile-tab", selected.then_some("active"))}
+                                aria-selected={selected.to_string()}
+                                {onclick}
+                            >
+                                { ctx.props().lang.t(tab.label()) }
+                            </button>
+                        }
+                    }) }
+                </div>
+                }
+                <div
+                    class={classes!("mobile-tab-content", (self.mobile_tab == MobileTab::Image).then_some("image-tab"))}
+                    role="tabpanel"
+                    {onpointerdown}
+                    {onpointerup}
+                >
+                    { content }
+                </div>
+                { self.render_metadata_popup(ctx) }
+                { self.render_feedback_popup(ctx) }
+            </div>
+        }
+    }
+
+    fn render_commentary_content(&self, lang: Lang) -> Html {
+        if self.progress.commentary == Progress::Loading {
+            return skeleton(lang.t("Cargando el comentario…"), 6);
+        }
+        html! {
+            <div class="commentary-html-content">
+                { Html::from_html_unchecked(self.commentary_html(lang)) }
+            </div>
+        }
+    }
+
+    fn commentary_html(&self, lang: Lang) -> AttrValue {
+        match &self.commentary {
+            Some(html) => AttrValue::from(html.clone()),
+            None => AttrValue::from(format!(
+                "<p class=\"sin-comentario\">{}</p>",
+                lang.t("Sin comentario")
+            )),
+        }
+    }
+
+    /// Dialog that prefills a GitHub issue with the page, the link to the
+    /// current view and, when a line is locked, that line.
+    fn render_feedback_popup(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
+        if !self.show_feedback {
+            return html! {};
+        }
+        let Some(issues) = feedback::issues_url(ctx.props().config.issues_url.as_deref()) else {
+            return html! {};
+        };
+        let on_close = ctx.link().callback(|_| TeiViewerMsg::ToggleFeedback);
+        let oninput = ctx.link().callback(|e: InputEvent| {
+            let value = e
+                .target_dyn_into::<HtmlTextAreaElement>()
+                .map(|t| t.value())
+                .unwrap_or_default();
+            TeiViewerMsg::SetFeedbackComment(value)
+        });
+
+        let link = web_sys::window()
+            .and_then(|w| w.location().href().ok())
+            .unwrap_or_default();
+        let line = self.selected_line().and_then(|index| {
+            let doc = self.diplomatic.as_ref()?;
+            Some((
+                doc.line_label(index)?,
+                doc.lines[index].text(Reading::Diplomatic),
+            ))
+        });
+        let report = Report {
+            issues,
+            project: &self.current_project,
+            page: self.current_page,
+            link: &link,
+            line: line
+                .as_ref()
+                .map(|(label, text)| (label.as_str(), text.as_str())),
+            comment: &self.feedback_comment,
+        };
+
+        html! {
+            <div class="metadata-popup-overlay">
+                <div class="metadata-popup feedback-popup" role="dialog" aria-labelledby="feedback-title">
+                    <div class="metadata-popup-header">
+                        <h2 id="feedback-title">{ lang.t("Reportar un problema") }</h2>
+                        <button class="close-btn" onclick={on_close.clone()}>{"×"}</button>
+                    </div>
+                    <div class="metadata-popup-content">
+                        <p class="note-hint">{ match &line {
+                            Some((label, _)) => lang.tf("Página {} · línea {} (seleccionada)", &[&self.current_page, label]),
+                            None => lang.tf("Página {} · haga clic en una línea para incluirla", &[&self.current_page]),
+                        } }</p>
+                        <label for="feedback-comment">{ lang.t("¿Qué está mal? (opcional)") }</label>
+                        <textarea
+                            id="feedback-comment"
+                            rows="4"
+                            value={self.feedback_comment.clone()}
+                            {oninput}
+                        />
+                        <div class="note-transfer">
+                            <a
+                                class="feedback-submit"
+                                href={report.issue_url()}
+                                target="_blank"
+                                rel="noopener noreferrer"
+                                onclick={on_close}
+                            >
+                                { lang.t("Abrir issue en GitHub ↗") }
+                            </a>
+                        </div>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
     fn render_commentary_popup(&self, ctx: &Context<Self>) -> Html {
+        let lang = ctx.props().lang;
         if !self.show_commentary {
             return html! {};
         }
 
         let on_close = ctx.link().callback(|_| TeiViewerMsg::ToggleCommentary);
-        let fallback_message = "<p class=\"sin-comentario\">Sin comentario</p>".to_string();
-        let commentary_html = self.commentary.as_ref().unwrap_or(&fallback_message);
 
         html! {
             <div class="commentary-popup-overlay">
                 <div class="commentary-popup">
                     <div class="commentary-popup-header">
-                        <h2>{"Comentario"}</h2>
+                        <h2>{ lang.t("Comentario") }</h2>
                         <button class="close-btn" onclick={on_close}>{"×"}</button>
                     </div>
                     <div class="commentary-popup-content">
-                        <div class="commentary-html-content">
-                            { Html::from_html_unchecked(AttrValue::from(commentary_html.clone())) }
-                        </div>
+                        { self.render_commentary_content(ctx.props().lang) }
                     </div>
                 </div>
             </div>
         }
     }
 }
+
+/// Placeholder for a resource on its way: a spinner with what is loading
+/// above `lines` shimmering bars shaped like text.
+fn skeleton(status: &str, lines: usize) -> Html {
+    html! {
+        <div class="skeleton" aria-busy="true">
+            <p class="skeleton-status" role="status">
+                <span class="spinner" aria-hidden="true" />
+                { status.to_string() }
+            </p>
+            { for (0..lines).map(|i| html! {
+                // Ragged right edge, like the lines of a page
+                <div class="skeleton-line" style={format!("width: {}%;", 55 + (i * 37) % 45)} />
+            }) }
+        </div>
+    }
+}
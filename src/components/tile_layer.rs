@@ -0,0 +1,81 @@
+// src/components/tile_layer.rs
+use crate::deep_zoom::{TileSource, Viewport};
+use yew::prelude::*;
+
+/// Width of the low-resolution backdrop shown while tiles load
+const PREVIEW_WIDTH: u32 = 1024;
+
+#[derive(Properties, PartialEq)]
+pub struct TileLayerProps {
+    pub source: TileSource,
+    /// Size the image is laid out at inside the pan/zoom container
+    pub layout_w: u32,
+    pub layout_h: u32,
+    /// Current pan/zoom transform of the container
+    pub scale: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    /// Size of the visible image panel in screen pixels
+    pub viewport_w: f64,
+    pub viewport_h: f64,
+}
+
+/// Replaces the single facsimile `<img>` with the tiles of a deep-zoom
+/// pyramid (IIIF image service or pre-tiled DZI folder) that intersect the
+/// viewport, at a resolution matching the zoom.
+pub struct TileLayer;
+
+impl Component for TileLayer {
+    type Message = ();
+    type Properties = TileLayerProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let source = &props.source;
+        if props.layout_w == 0 || props.layout_h == 0 || props.scale <= 0.0 {
+            return html! {};
+        }
+
+        // Layout pixels per full-resolution image pixel
+        let ratio = props.layout_w as f64 / source.width().max(1) as f64;
+        let scale = props.scale as f64;
+        let view = Viewport {
+            x: (-props.offset_x as f64 / scale) / ratio,
+            y: (-props.offset_y as f64 / scale) / ratio,
+            w: (props.viewport_w / scale) / ratio,
+            h: (props.viewport_h / scale) / ratio,
+        };
+        let device_ratio = web_sys::window()
+            .map(|w| w.device_pixel_ratio())
+            .unwrap_or(1.0);
+        let tiles = source.visible_tiles(&view, scale * ratio * device_ratio);
+
+        html! {
+            <div
+                class="tile-layer"
+                style={format!("position: relative; width: {}px; height: {}px;", props.layout_w, props.layout_h)}
+            >
+                <img
+                    class="tile-preview"
+                    src={source.preview_url(PREVIEW_WIDTH)}
+                    style="position: absolute; top: 0; left: 0; width: 100%; height: 100%; max-width: none;"
+                />
+                { for tiles.into_iter().map(|tile| html! {
+                    <img
+                        key={tile.url.clone()}
+                        class="tile"
+                        src={tile.url}
+                        style={format!(
+                            "position: absolute; left: {:.2}px; top: {:.2}px; width: {:.2}px; height: {:.2}px; max-width: none;",
+                            tile.x * ratio, tile.y * ratio, tile.w * ratio, tile.h * ratio
+                        )}
+                    />
+                }) }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,177 @@
+// src/components/timeline_panel.rs
+use crate::i18n::Lang;
+use crate::timeline::{self, EventKind, TimelineEvent};
+use yew::prelude::*;
+
+const AXIS_WIDTH: f64 = 320.0;
+/// Room left of the axis for the row labels
+const LABEL_WIDTH: f64 = 84.0;
+const ROW_HEIGHT: f64 = 26.0;
+/// Room below the rows for the year ticks
+const TICKS_HEIGHT: f64 = 20.0;
+const MARGIN: f64 = 8.0;
+
+#[derive(Properties, PartialEq)]
+pub struct TimelinePanelProps {
+    pub events: Vec<TimelineEvent>,
+    /// Project pages are still being scanned
+    #[prop_or_default]
+    pub loading: bool,
+    pub current_page: u32,
+    /// Go to the page (and line) stating an event
+    pub on_open: Callback<(u32, Option<String>)>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Sidebar with the chronology of the project: the dating of the object,
+/// its provenance and the dates mentioned in the text, on one axis.
+pub struct TimelinePanel;
+
+impl Component for TimelinePanel {
+    type Message = ();
+    type Properties = TimelinePanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+
+        html! {
+            <div class="text-panel timeline-panel">
+                <h3>{ lang.t("Cronología") }</h3>
+                { if props.loading {
+                    html! { <p class="note-hint">{ lang.t("Buscando en las páginas del proyecto...") }</p> }
+                } else if props.events.is_empty() {
+                    html! { <p class="note-hint">{ lang.t("No hay fechas: añada date_range al manifiesto, <origDate>/<provenance> al msDesc o <date when=\"...\"> al texto.") }</p> }
+                } else {
+                    html! {}
+                } }
+                { self.render_axis(ctx) }
+                <ol class="timeline-events">
+                    { for props.events.iter().map(|event| self.render_event(ctx, event)) }
+                </ol>
+            </div>
+        }
+    }
+}
+
+impl TimelinePanel {
+    fn render_axis(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let Some((first, last)) = timeline::bounds(&props.events) else {
+            return html! {};
+        };
+        let span = f64::from((last - first).max(1));
+        let plot = AXIS_WIDTH - LABEL_WIDTH - 2.0 * MARGIN;
+        let x = |year: i32| LABEL_WIDTH + MARGIN + f64::from(year - first) / span * plot;
+        let height = ROW_HEIGHT * EventKind::ALL.len() as f64 + TICKS_HEIGHT;
+        let axis_y = ROW_HEIGHT * EventKind::ALL.len() as f64;
+
+        html! {
+            <svg
+                class="timeline-axis"
+                viewBox={format!("0 0 {} {}", AXIS_WIDTH, height)}
+                role="img"
+                aria-label={lang.t("Cronología del proyecto")}
+            >
+                { for EventKind::ALL.into_iter().enumerate().map(|(row, kind)| {
+                    let y = ROW_HEIGHT * row as f64 + ROW_HEIGHT / 2.0;
+                    html! {
+                        <g class={classes!("timeline-row", row_class(kind))}>
+                            <text class="timeline-row-label" x="0" y={(y + 4.0).to_string()}>{ lang.t(kind.label()) }</text>
+                            { for props.events.iter().filter(|e| e.kind == kind).map(|event| {
+                                let (x1, x2) = (x(event.from), x(event.to));
+                                let title = format!(
+                                    "{} ({})",
+                                    lang.t(&event.label),
+                                    timeline::format_range(lang, event.from, event.to)
+                                );
+                                let current = event.page == Some(props.current_page);
+                                let onclick = event.page.map(|page| {
+                                    let line = event.line.clone();
+                                    props.on_open.reform(move |_: MouseEvent| (page, line.clone()))
+                                });
+                                let class = classes!(
+                                    "timeline-mark",
+                                    onclick.is_some().then_some("clickable"),
+                                    current.then_some("current"),
+                                );
+                                if x2 - x1 < 4.0 {
+                                    html! {
+                                        <circle {class} cx={((x1 + x2) / 2.0).to_string()} cy={y.to_string()} r="4" {onclick}>
+                                            <title>{ title }</title>
+                                        </circle>
+                                    }
+                                } else {
+                                    html! {
+                                        <rect {class} x={x1.to_string()} y={(y - 4.0).to_string()}
+                                            width={(x2 - x1).to_string()} height="8" rx="3" {onclick}>
+                                            <title>{ title }</title>
+                                        </rect>
+                                    }
+                                }
+                            }) }
+                        </g>
+                    }
+                }) }
+                <line
+                    class="timeline-baseline"
+                    x1={x(first).to_string()}
+                    x2={x(last).to_string()}
+                    y1={axis_y.to_string()}
+                    y2={axis_y.to_string()}
+                />
+                <text class="timeline-tick" x={x(first).to_string()} y={(axis_y + 14.0).to_string()} text-anchor="start">
+                    { timeline::format_year(lang, first) }
+                </text>
+                { if last != first {
+                    html! {
+                        <text class="timeline-tick" x={x(last).to_string()} y={(axis_y + 14.0).to_string()} text-anchor="end">
+                            { timeline::format_year(lang, last) }
+                        </text>
+                    }
+                } else {
+                    html! {}
+                } }
+            </svg>
+        }
+    }
+
+    fn render_event(&self, ctx: &Context<Self>, event: &TimelineEvent) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+        let place = match (event.page, &event.line) {
+            (Some(page), Some(line)) => Some(format!("p. {}, l. {}", page, line)),
+            (Some(page), None) => Some(format!("p. {}", page)),
+            (None, _) => None,
+        };
+
+        html! {
+            <li class={classes!(row_class(event.kind), (event.page == Some(props.current_page)).then_some("current"))}>
+                <span class="timeline-years">{ timeline::format_range(lang, event.from, event.to) }</span>
+                <span class="timeline-label">{ lang.t(&event.label).to_string() }</span>
+                { match (event.page, place) {
+                    (Some(page), Some(place)) => {
+                        let line = event.line.clone();
+                        let onclick = props.on_open.reform(move |_: MouseEvent| (page, line.clone()));
+                        html! { <button {onclick}>{ place }</button> }
+                    }
+                    _ => html! {},
+                } }
+            </li>
+        }
+    }
+}
+
+fn row_class(kind: EventKind) -> &'static str {
+    match kind {
+        EventKind::Dating => "timeline-dating",
+        EventKind::Provenance => "timeline-provenance",
+        EventKind::Mention => "timeline-mention",
+    }
+}
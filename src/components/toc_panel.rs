@@ -0,0 +1,113 @@
+// src/components/toc_panel.rs
+use crate::i18n::Lang;
+use crate::toc::{self, TocEntry};
+use std::collections::HashSet;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct TocPanelProps {
+    pub entries: Vec<TocEntry>,
+    /// Page on screen, whose entries are highlighted
+    pub current_page: u32,
+    /// Project pages are still being scanned for sections
+    #[prop_or_default]
+    pub loading: bool,
+    /// Go to an entry: (page, line reference)
+    pub on_open: Callback<(u32, Option<String>)>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Sidebar with the sections of the project, nested and collapsible, each
+/// linking to the page and line where it starts.
+pub struct TocPanel {
+    collapsed: HashSet<usize>,
+}
+
+impl Component for TocPanel {
+    /// Collapse or expand the entry at this index
+    type Message = usize;
+    type Properties = TocPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            collapsed: HashSet::new(),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, index: Self::Message) -> bool {
+        if !self.collapsed.remove(&index) {
+            self.collapsed.insert(index);
+        }
+        true
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>, old: &Self::Properties) -> bool {
+        if ctx.props().entries != old.entries {
+            self.collapsed.clear();
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let lang = props.lang;
+
+        html! {
+            <div class="text-panel toc-panel">
+                <h3>{ lang.t("Índice") }</h3>
+                { if props.loading {
+                    html! { <p class="note-hint">{ lang.t("Buscando en las páginas del proyecto...") }</p> }
+                } else if props.entries.is_empty() {
+                    html! { <p class="note-hint">{ lang.t("El proyecto no declara secciones (<div>) ni un índice en el manifiesto.") }</p> }
+                } else {
+                    html! {}
+                } }
+                <ul class="toc">
+                    { for toc::visible(&props.entries, &self.collapsed).into_iter().map(|index| {
+                        self.render_entry(ctx, index)
+                    }) }
+                </ul>
+            </div>
+        }
+    }
+}
+
+impl TocPanel {
+    fn render_entry(&self, ctx: &Context<Self>, index: usize) -> Html {
+        let props = ctx.props();
+        let entry = &props.entries[index];
+        let target = (entry.page, entry.line.clone());
+        let onclick = props.on_open.reform(move |_: MouseEvent| target.clone());
+        let current = entry.page == props.current_page;
+        let lang = props.lang;
+        let location = match &entry.line {
+            Some(line) => format!("p. {}, l. {}", entry.page, line),
+            None => format!("p. {}", entry.page),
+        };
+
+        html! {
+            <li style={format!("padding-left: {}rem;", entry.depth as f32 * 0.9)}>
+                { if toc::has_children(&props.entries, index) {
+                    let collapsed = self.collapsed.contains(&index);
+                    html! {
+                        <button
+                            class="toc-toggle"
+                            onclick={ctx.link().callback(move |_| index)}
+                            aria-expanded={(!collapsed).to_string()}
+                            title={lang.t(if collapsed { "Mostrar subsecciones" } else { "Ocultar subsecciones" })}
+                        >
+                            { if collapsed { "▸" } else { "▾" } }
+                        </button>
+                    }
+                } else {
+                    html! { <span class="toc-toggle" /> }
+                } }
+                <button class={classes!("toc-link", current.then_some("active"))} {onclick}>
+                    <span class="toc-title">{ entry.title.clone() }</span>
+                    <span class="toc-location">{ location }</span>
+                </button>
+            </li>
+        }
+    }
+}
@@ -0,0 +1,180 @@
+// src/components/word_frequency_panel.rs
+use crate::i18n::Lang;
+use crate::word_frequency::{self, SortBy, WordCount};
+use yew::prelude::*;
+
+// Rows shown in the table and words in the cloud
+const MAX_ROWS: usize = 300;
+const MAX_CLOUD_WORDS: usize = 80;
+
+#[derive(Properties, PartialEq)]
+pub struct WordFrequencyPanelProps {
+    /// Counts for the current scope, most frequent first
+    pub words: Vec<WordCount>,
+    /// Counting the whole project rather than the page
+    pub project: bool,
+    /// Project pages are still being scanned
+    #[prop_or_default]
+    pub loading: bool,
+    pub on_scope: Callback<bool>,
+    /// Search the text for a word
+    pub on_search: Callback<String>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+pub enum WordFrequencyPanelMsg {
+    Sort(SortBy),
+    ToggleCloud,
+}
+
+/// Sidebar with the Greek word frequencies of the page or the project, as
+/// a sortable table or a word cloud. Clicking a word searches for it.
+pub struct WordFrequencyPanel {
+    sort: SortBy,
+    cloud: bool,
+}
+
+impl Component for WordFrequencyPanel {
+    type Message = WordFrequencyPanelMsg;
+    type Properties = WordFrequencyPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            sort: SortBy::Count,
+            cloud: false,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            WordFrequencyPanelMsg::Sort(sort) => self.sort = sort,
+            WordFrequencyPanelMsg::ToggleCloud => self.cloud = !self.cloud,
+        }
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let total: usize = props.words.iter().map(|w| w.count).sum();
+        let lang = props.lang;
+        let scope = |project: bool, label: &'static str| {
+            let label = lang.t(label);
+            let onclick = props.on_scope.reform(move |_: MouseEvent| project);
+            html! {
+                <button class={classes!((props.project == project).then_some("active"))} {onclick}>{ label }</button>
+            }
+        };
+
+        html! {
+            <div class="text-panel word-frequency-panel">
+                <h3>{ lang.t("Frecuencia de palabras") }</h3>
+                <div class="note-transfer">
+                    { scope(false, "Página") }
+                    { scope(true, "Proyecto") }
+                    <button
+                        class={classes!(self.cloud.then_some("active"))}
+                        onclick={ctx.link().callback(|_| WordFrequencyPanelMsg::ToggleCloud)}
+                    >
+                        { lang.t("Nube") }
+                    </button>
+                </div>
+                <p class="note-hint">
+                    { lang.tf("{} palabras, {} formas distintas.", &[&lang.number(total), &lang.number(props.words.len())]) }
+                    { if props.loading { format!(" {}", lang.t("Buscando en las páginas del proyecto...")) } else { String::new() } }
+                </p>
+                { if self.cloud {
+                    self.render_cloud(ctx)
+                } else {
+                    self.render_table(ctx)
+                } }
+            </div>
+        }
+    }
+}
+
+impl WordFrequencyPanel {
+    fn render_table(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let mut words: Vec<WordCount> = props.words.iter().take(MAX_ROWS).cloned().collect();
+        word_frequency::sort(&mut words, self.sort);
+        let lang = props.lang;
+        let header = |sort: SortBy, label: &'static str| {
+            let label = lang.t(label);
+            let active = self.sort == sort;
+            html! {
+                <th aria-sort={match (active, sort) {
+                    (false, _) => "none",
+                    (true, SortBy::Count) => "descending",
+                    (true, SortBy::Word) => "ascending",
+                }}>
+                    <button
+                        class={classes!(active.then_some("active"))}
+                        onclick={ctx.link().callback(move |_| WordFrequencyPanelMsg::Sort(sort))}
+                    >
+                        { label }
+                    </button>
+                </th>
+            }
+        };
+
+        html! {
+            <>
+                <table class="word-frequency-table">
+                    <thead>
+                        <tr>
+                            { header(SortBy::Word, "Palabra") }
+                            { header(SortBy::Count, "Frecuencia") }
+                        </tr>
+                    </thead>
+                    <tbody>
+                        { for words.into_iter().map(|w| {
+                            let word = w.word.clone();
+                            let onclick = props.on_search.reform(move |_: MouseEvent| word.clone());
+                            html! {
+                                <tr>
+                                    <td><button class="word-link" {onclick}>{ w.word }</button></td>
+                                    <td>{ lang.number(w.count) }</td>
+                                </tr>
+                            }
+                        }) }
+                    </tbody>
+                </table>
+                { if props.words.len() > MAX_ROWS {
+                    html! { <p class="note-hint">{ lang.tf("Se muestran las {} formas más frecuentes.", &[&MAX_ROWS]) }</p> }
+                } else {
+                    html! {}
+                } }
+            </>
+        }
+    }
+
+    fn render_cloud(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let mut words: Vec<&WordCount> = props.words.iter().take(MAX_CLOUD_WORDS).collect();
+        let max = words.first().map(|w| w.count).unwrap_or(1) as f64;
+        let lang = props.lang;
+        words.sort_by(|a, b| a.key.cmp(&b.key));
+
+        html! {
+            <div class="word-cloud">
+                { for words.into_iter().map(|w| {
+                    // 0.8em for a single occurrence up to 2.4em for the most frequent
+                    let size = 0.8 + 1.6 * (w.count as f64).ln() / max.ln().max(1.0);
+                    let word = w.word.clone();
+                    let onclick = props.on_search.reform(move |_: MouseEvent| word.clone());
+                    html! {
+                        <button
+                            class="word-link"
+                            style={format!("font-size: {:.2}em;", size)}
+                            title={lang.tf("{} apariciones", &[&lang.number(w.count)])}
+                            {onclick}
+                        >
+                            { w.word.clone() }
+                        </button>
+                    }
+                }) }
+            </div>
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// src/components/zone_editor_panel.rs
+use crate::i18n::Lang;
+use crate::zone_editor::EditZone;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+#[derive(Properties, PartialEq)]
+pub struct ZoneEditorPanelProps {
+    pub zones: Vec<EditZone>,
+    pub selected: Option<usize>,
+    /// Short label of each diplomatic line, offered as link targets
+    pub lines: Vec<AttrValue>,
+    /// The `<facsimile>` markup for the current state
+    pub xml: AttrValue,
+    pub on_select: Callback<usize>,
+    pub on_add: Callback<()>,
+    pub on_remove: Callback<usize>,
+    /// Link a zone (first) to a line index, or unlink it
+    pub on_link: Callback<(usize, Option<usize>)>,
+    pub on_export: Callback<()>,
+    #[prop_or_default]
+    pub lang: Lang,
+}
+
+/// Sidebar of the zone editor: the page's zones with their line links,
+/// and the resulting `<facsimile>` section.
+pub struct ZoneEditorPanel;
+
+impl Component for ZoneEditorPanel {
+    type Message = ();
+    type Properties = ZoneEditorPanelProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let on_add = props.on_add.reform(|_: MouseEvent| ());
+        let on_export = props.on_export.reform(|_: MouseEvent| ());
+        let lang = props.lang;
+
+        html! {
+            <div class="text-panel zone-editor-panel">
+                <h3>{ lang.t("Editor de zonas") }</h3>
+                <p class="note-hint">
+                    { lang.t("Seleccione una zona y haga clic en la imagen para añadir vértices. Arrastre un vértice para moverlo (Mayús+clic lo elimina) o el interior de la zona para desplazarla.") }
+                </p>
+                <ol class="zone-edit-list">
+                    { for props.zones.iter().enumerate().map(|(index, zone)| {
+                        let selected = props.selected == Some(index);
+                        let on_select = props.on_select.reform(move |_: MouseEvent| index);
+                        let on_remove = props.on_remove.reform(move |_: MouseEvent| index);
+                        let on_link = props.on_link.reform(move |e: Event| {
+                            let line = e
+                                .target_dyn_into::<HtmlSelectElement>()
+                                .and_then(|s| s.value().parse::<usize>().ok());
+                            (index, line)
+                        });
+                        html! {
+                            <li class={classes!("zone-edit-item", selected.then_some("selected"))}>
+                                <button class="zone-edit-id" onclick={on_select} aria-pressed={selected.to_string()}>
+                                    { lang.tf("{} · {} vértices", &[&zone.id, &zone.points.len()]) }
+                                </button>
+                                <select onchange={on_link} aria-label={lang.tf("Línea de {}", &[&zone.id])}>
+                                    <option value="" selected={zone.line.is_none()}>{ lang.t("— sin línea —") }</option>
+                                    { for props.lines.iter().enumerate().map(|(line, label)| html! {
+                                        <option value={line.to_string()} selected={zone.line == Some(line)}>
+                                            { label.clone() }
+                                        </option>
+                                    }) }
+                                </select>
+                                <button onclick={on_remove} title={lang.t("Eliminar la zona")}>{"✕"}</button>
+                            </li>
+                        }
+                    }) }
+                </ol>
+                <div class="note-transfer">
+                    <button onclick={on_add}>{ lang.t("＋ Nueva zona") }</button>
+                    <button onclick={on_export} title={lang.t("Descargar la sección <facsimile> de la página")}>{ lang.t("⬇ Exportar XML") }</button>
+                </div>
+                <pre class="zone-edit-xml">{ props.xml.clone() }</pre>
+            </div>
+        }
+    }
+}
@@ -0,0 +1,113 @@
+// src/corpus.rs
+// The pages of every project, for the collection-wide views (search,
+// entity indices). The app fetches them once, the first time one of those
+// views needs them, and keeps them for the rest of the session.
+use crate::project_config::ProjectConfig;
+use crate::tei_data::TeiDocument;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// (project id, page number, edition: "dip" or "trad")
+pub type DocKey = (String, u32, &'static str);
+
+/// Pages loaded so far. Cheap to clone and compare, so it can be passed
+/// down as a prop while pages keep arriving.
+#[derive(Debug, Clone, Default)]
+pub struct Corpus {
+    docs: Rc<HashMap<DocKey, Rc<TeiDocument>>>,
+    /// The pages have been requested
+    pub requested: bool,
+    /// Requests still in flight
+    pub pending: usize,
+    /// Pages that could not be fetched or parsed
+    pub failed: usize,
+}
+
+impl PartialEq for Corpus {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.docs, &other.docs)
+            && self.requested == other.requested
+            && self.pending == other.pending
+            && self.failed == other.failed
+    }
+}
+
+impl Corpus {
+    /// Every (project, page, edition) to fetch, per the manifests.
+    pub fn documents(projects: &[ProjectConfig]) -> Vec<DocKey> {
+        let mut out = Vec::new();
+        for project in projects {
+            for page in &project.pages {
+                if page.has_diplomatic {
+                    out.push((project.id.clone(), page.number, "dip"));
+                }
+                if page.has_translation {
+                    out.push((project.id.clone(), page.number, "trad"));
+                }
+            }
+        }
+        out
+    }
+
+    pub fn get(&self, project: &str, page: u32, edition: &'static str) -> Option<&TeiDocument> {
+        self.docs
+            .get(&(project.to_string(), page, edition))
+            .map(|doc| doc.as_ref())
+    }
+
+    pub fn insert(&mut self, key: DocKey, doc: TeiDocument) {
+        Rc::make_mut(&mut self.docs).insert(key, Rc::new(doc));
+    }
+
+    /// Still fetching pages.
+    pub fn loading(&self) -> bool {
+        self.pending > 0
+    }
+
+    /// Diplomatic pages of `projects` loaded so far, in manifest order.
+    pub fn diplomatic<'a>(
+        &'a self,
+        projects: &'a [ProjectConfig],
+    ) -> impl Iterator<Item = (&'a ProjectConfig, u32, &'a TeiDocument)> + 'a {
+        projects.iter().flat_map(move |project| {
+            project.pages.iter().filter_map(move |page| {
+                self.get(&project.id, page.number, "dip")
+                    .map(|doc| (project, page.number, doc))
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::PageInfo;
+
+    #[test]
+    fn test_documents_and_sharing() {
+        let mut project = ProjectConfig::new("A".to_string(), "A".to_string());
+        project.pages = vec![PageInfo::new(1), PageInfo::new(2)];
+        project.pages[1].has_translation = false;
+        assert_eq!(
+            Corpus::documents(&[project]),
+            [
+                ("A".to_string(), 1, "dip"),
+                ("A".to_string(), 1, "trad"),
+                ("A".to_string(), 2, "dip"),
+            ]
+        );
+
+        let mut corpus = Corpus::default();
+        let shared = corpus.clone();
+        assert_eq!(corpus, shared);
+        let doc = crate::tei_parser::parse_tei_xml(
+            r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><body><lb/><ab>α</ab></body></text></TEI>"#,
+        )
+        .unwrap();
+        corpus.insert(("A".to_string(), 1, "dip"), doc);
+        // The copy handed out before keeps its pages and no longer compares equal
+        assert!(corpus.get("A", 1, "dip").is_some());
+        assert!(shared.get("A", 1, "dip").is_none());
+        assert_ne!(corpus, shared);
+    }
+}
@@ -0,0 +1,172 @@
+// src/corpus_search.rs
+// Search across every project of the collection, so a formula can be
+// traced from one papyrus to the next. Matches are grouped by project and
+// page in manifest order.
+use crate::corpus::Corpus;
+use crate::polytonic;
+use crate::project_config::ProjectConfig;
+use crate::tei_data::{Reading, TeiDocument};
+
+/// Stop listing matches past this many lines, so a one-letter query
+/// doesn't render the whole collection
+pub const MAX_HITS: usize = 300;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineHit {
+    /// Line reference (`@n` label or 1-based position)
+    pub line: String,
+    pub text: String,
+    /// Found in the translation rather than the diplomatic edition
+    pub translation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageHits {
+    pub page: u32,
+    pub label: String,
+    pub hits: Vec<LineHit>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectHits {
+    pub project: String,
+    pub name: String,
+    pub pages: Vec<PageHits>,
+}
+
+/// Lines of `doc` containing `query`, as (line reference, text).
+fn line_hits(doc: &TeiDocument, query: &str, ignore_diacritics: bool) -> Vec<(String, String)> {
+    doc.lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let text = line.text(Reading::Diplomatic);
+            if !polytonic::contains(&text, query, ignore_diacritics) {
+                return None;
+            }
+            Some((doc.line_label(index)?, text.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Matches of `query` in the pages of `corpus` loaded so far, grouped by project and page.
+/// Returns the groups and whether the list was cut at `MAX_HITS`.
+pub fn search(
+    projects: &[ProjectConfig],
+    corpus: &Corpus,
+    query: &str,
+    ignore_diacritics: bool,
+) -> (Vec<ProjectHits>, bool) {
+    let query = query.trim();
+    let mut results = Vec::new();
+    if query.is_empty() {
+        return (results, false);
+    }
+    let mut total = 0;
+    for project in projects {
+        let mut pages = Vec::new();
+        for page in &project.pages {
+            let mut hits = Vec::new();
+            for (edition, translation) in [("dip", false), ("trad", true)] {
+                let Some(doc) = corpus.get(&project.id, page.number, edition) else {
+                    continue;
+                };
+                for (line, text) in line_hits(doc, query, ignore_diacritics) {
+                    if total == MAX_HITS {
+                        break;
+                    }
+                    total += 1;
+                    hits.push(LineHit {
+                        line,
+                        text,
+                        translation,
+                    });
+                }
+            }
+            if !hits.is_empty() {
+                pages.push(PageHits {
+                    page: page.number,
+                    label: page.label.clone(),
+                    hits,
+                });
+            }
+        }
+        if !pages.is_empty() {
+            results.push(ProjectHits {
+                project: project.id.clone(),
+                name: project.name.clone(),
+                pages,
+            });
+        }
+        if total == MAX_HITS {
+            return (results, true);
+        }
+    }
+    (results, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::PageInfo;
+
+    fn project(id: &str, pages: &[u32]) -> ProjectConfig {
+        let mut config = ProjectConfig::new(id.to_string(), format!("Proyecto {}", id));
+        config.pages = pages
+            .iter()
+            .map(|&n| {
+                let mut page = PageInfo::new(n);
+                page.label = format!("{}r", n);
+                page.has_translation = n == 1;
+                page
+            })
+            .collect();
+        config
+    }
+
+    fn doc(lines: &[&str]) -> TeiDocument {
+        let body: String = lines
+            .iter()
+            .enumerate()
+            .map(|(i, text)| format!("<lb n=\"{}\"/><ab>{}</ab>", i + 1, text))
+            .collect();
+        let xml = format!(
+            r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><body>{}</body></text></TEI>"#,
+            body
+        );
+        crate::tei_parser::parse_tei_xml(&xml).unwrap()
+    }
+
+    #[test]
+    fn test_search_groups_by_project_and_page() {
+        let projects = vec![project("A", &[1, 2]), project("B", &[1])];
+        let mut corpus = Corpus::default();
+        corpus.insert(("A".into(), 1, "dip"), doc(&["ἐπικαλοῦμαί σε", "ὁ θεός"]));
+        corpus.insert(("A".into(), 1, "trad"), doc(&["te invoco"]));
+        corpus.insert(("A".into(), 2, "dip"), doc(&["οὐδέν"]));
+        corpus.insert(("B".into(), 1, "dip"), doc(&["καὶ ἐπικαλοῦμαι"]));
+
+        let (results, truncated) = search(&projects, &corpus, "επικαλουμαι", true);
+        assert!(!truncated);
+        let flat: Vec<_> = results
+            .iter()
+            .flat_map(|p| {
+                p.pages.iter().flat_map(move |page| {
+                    page.hits
+                        .iter()
+                        .map(move |hit| (p.project.as_str(), page.page, hit.line.as_str()))
+                })
+            })
+            .collect();
+        assert_eq!(flat, [("A", 1, "1"), ("B", 1, "1")]);
+        assert_eq!(results[0].pages[0].label, "1r");
+
+        // Exact matching keeps the accents
+        assert!(search(&projects, &corpus, "επικαλουμαι", false)
+            .0
+            .is_empty());
+        let (results, _) = search(&projects, &corpus, "invoco", false);
+        assert!(results[0].pages[0].hits[0].translation);
+        assert!(search(&projects, &corpus, "  ", true).0.is_empty());
+    }
+}
@@ -0,0 +1,502 @@
+// src/deep_zoom.rs
+// Tile pyramid arithmetic for deep-zoom images. Everything here works in
+// full-resolution image pixels; the viewer maps the results onto its own
+// layout size.
+use crate::utils::Url;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::Deserialize;
+
+/// Region of the full-resolution image currently visible in the viewport
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+/// A single tile to draw: where it goes (full-resolution coordinates) and
+/// where to fetch it from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    pub url: String,
+    pub x: f64,
+    pub y: f64,
+    pub w: f64,
+    pub h: f64,
+}
+
+/// IIIF Image API request for `region` of the image of `service` at
+/// `size`, unrotated, in its default quality as JPEG.
+pub fn iiif_image_url(service: &str, region: &str, size: &str) -> String {
+    Url::new(service)
+        .segment(region)
+        .segment(size)
+        .segment("0")
+        .segment("default.jpg")
+        .to_string()
+}
+
+/// Subset of a IIIF Image API `info.json` needed for tiling
+#[derive(Debug, Clone, PartialEq)]
+pub struct IiifInfo {
+    /// Image service base URL (without trailing slash)
+    pub base: String,
+    pub width: u32,
+    pub height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// Supported downsampling factors, ascending (1 = full resolution)
+    pub scale_factors: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+struct RawInfo {
+    #[serde(rename = "@id")]
+    id_v2: Option<String>,
+    id: Option<String>,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    tiles: Vec<RawTiles>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTiles {
+    width: u32,
+    height: Option<u32>,
+    #[serde(default)]
+    scale_factors: Vec<u32>,
+}
+
+impl IiifInfo {
+    /// Parse an `info.json` document (Image API 2 or 3). `service` is the URL
+    /// the document was requested from and is used when it declares no id.
+    pub fn from_json(service: &str, json: &str) -> Result<Self, String> {
+        let raw: RawInfo =
+            serde_json::from_str(json).map_err(|e| format!("Invalid IIIF info.json: {}", e))?;
+        if raw.width == 0 || raw.height == 0 {
+            return Err("IIIF info.json declares an empty image".to_string());
+        }
+        let base = raw
+            .id
+            .or(raw.id_v2)
+            .unwrap_or_else(|| service.to_string())
+            .trim_end_matches('/')
+            .to_string();
+
+        // Without a tiles declaration the server can still serve arbitrary
+        // regions; fall back to 512px tiles over power-of-two factors.
+        let (tile_width, tile_height, mut scale_factors) = match raw.tiles.into_iter().next() {
+            Some(t) => (t.width, t.height.unwrap_or(t.width), t.scale_factors),
+            None => (512, 512, Vec::new()),
+        };
+        if scale_factors.is_empty() {
+            scale_factors = power_of_two_factors(raw.width.max(raw.height), tile_width);
+        }
+        scale_factors.sort_unstable();
+        scale_factors.dedup();
+
+        Ok(Self {
+            base,
+            width: raw.width,
+            height: raw.height,
+            tile_width: tile_width.max(1),
+            tile_height: tile_height.max(1),
+            scale_factors,
+        })
+    }
+
+    /// URL of a downscaled rendition of the whole image, used as a backdrop
+    /// while tiles load.
+    pub fn preview_url(&self, width: u32) -> String {
+        iiif_image_url(&self.base, "full", &format!("{},", width.min(self.width)))
+    }
+
+    /// Tiles covering `view` at a resolution suited to `display_scale`
+    /// (screen pixels per full-resolution image pixel).
+    pub fn visible_tiles(&self, view: &Viewport, display_scale: f64) -> Vec<Tile> {
+        let factor = pick_scale_factor(&self.scale_factors, display_scale);
+        let region_w = self.tile_width * factor;
+        let region_h = self.tile_height * factor;
+
+        tile_grid(self.width, self.height, region_w, region_h, view)
+            .into_iter()
+            .map(|(x, y, w, h)| {
+                let out_w = w.div_ceil(factor);
+                Tile {
+                    url: iiif_image_url(
+                        &self.base,
+                        &format!("{},{},{},{}", x, y, w, h),
+                        &format!("{},", out_w),
+                    ),
+                    x: x as f64,
+                    y: y as f64,
+                    w: w as f64,
+                    h: h as f64,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Deep Zoom Image (DZI) pyramid generated offline, e.g. by `vips dzsave`:
+/// `page.dzi` next to a `page_files/{level}/{col}_{row}.{format}` folder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DziInfo {
+    /// URL of the `_files` folder (without trailing slash)
+    pub files_url: String,
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    pub overlap: u32,
+    pub format: String,
+}
+
+impl DziInfo {
+    /// Parse a `.dzi` descriptor fetched from `dzi_url`
+    pub fn from_xml(dzi_url: &str, xml: &str) -> Result<Self, String> {
+        let mut reader = Reader::from_str(xml);
+        let mut buf = Vec::new();
+        let mut tile_size = 0;
+        let mut overlap = 0;
+        let mut format = String::from("jpg");
+        let mut width = 0;
+        let mut height = 0;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(ref e)) | Ok(Event::Empty(ref e)) => {
+                    let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                    for attr in e.attributes().flatten() {
+                        let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                        let value = String::from_utf8_lossy(&attr.value).to_string();
+                        match (name.as_str(), key.as_str()) {
+                            ("Image", "TileSize") => tile_size = value.parse().unwrap_or(0),
+                            ("Image", "Overlap") => overlap = value.parse().unwrap_or(0),
+                            ("Image", "Format") => format = value,
+                            ("Size", "Width") => width = value.parse().unwrap_or(0),
+                            ("Size", "Height") => height = value.parse().unwrap_or(0),
+                            _ => {}
+                        }
+                    }
+                }
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(format!("Invalid DZI descriptor: {:?}", e)),
+                _ => {}
+            }
+            buf.clear();
+        }
+
+        if width == 0 || height == 0 || tile_size == 0 {
+            return Err("DZI descriptor is missing TileSize or Size".to_string());
+        }
+        let stem = dzi_url.strip_suffix(".dzi").unwrap_or(dzi_url);
+        Ok(Self {
+            files_url: format!("{}_files", stem),
+            width,
+            height,
+            tile_size,
+            overlap,
+            format,
+        })
+    }
+
+    /// Level holding the full-resolution image; level 0 is 1×1 pixel
+    pub fn max_level(&self) -> u32 {
+        let longest = self.width.max(self.height).max(1);
+        u32::BITS - (longest - 1).leading_zeros()
+    }
+
+    /// First level small enough to fit in a single tile
+    pub fn preview_url(&self) -> String {
+        let max_level = self.max_level();
+        let level = (0..=max_level)
+            .rev()
+            .find(|l| self.width.max(self.height) >> (max_level - l) <= self.tile_size)
+            .unwrap_or(0);
+        format!("{}/{}/0_0.{}", self.files_url, level, self.format)
+    }
+
+    pub fn visible_tiles(&self, view: &Viewport, display_scale: f64) -> Vec<Tile> {
+        let max_level = self.max_level();
+        let factors: Vec<u32> = (0..=max_level).map(|l| 1u32 << l).collect();
+        let factor = pick_scale_factor(&factors, display_scale);
+        let level = max_level - factor.trailing_zeros();
+        let level_w = self.width.div_ceil(factor);
+        let level_h = self.height.div_ceil(factor);
+        let level_view = Viewport {
+            x: view.x / factor as f64,
+            y: view.y / factor as f64,
+            w: view.w / factor as f64,
+            h: view.h / factor as f64,
+        };
+
+        let ts = self.tile_size;
+        tile_grid(level_w, level_h, ts, ts, &level_view)
+            .into_iter()
+            .map(|(x, y, w, h)| {
+                // Files include `overlap` extra pixels on every inner edge
+                let left = if x > 0 { self.overlap.min(x) } else { 0 };
+                let top = if y > 0 { self.overlap.min(y) } else { 0 };
+                let right = self.overlap.min(level_w - (x + w));
+                let bottom = self.overlap.min(level_h - (y + h));
+                let f = factor as f64;
+                let x0 = ((x - left) as f64 * f).min(self.width as f64);
+                let y0 = ((y - top) as f64 * f).min(self.height as f64);
+                let x1 = ((x + w + right) as f64 * f).min(self.width as f64);
+                let y1 = ((y + h + bottom) as f64 * f).min(self.height as f64);
+                Tile {
+                    url: format!(
+                        "{}/{}/{}_{}.{}",
+                        self.files_url,
+                        level,
+                        x / ts,
+                        y / ts,
+                        self.format
+                    ),
+                    x: x0,
+                    y: y0,
+                    w: x1 - x0,
+                    h: y1 - y0,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Any tiled image the viewer knows how to draw
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileSource {
+    Iiif(IiifInfo),
+    Dzi(DziInfo),
+}
+
+impl TileSource {
+    pub fn width(&self) -> u32 {
+        match self {
+            TileSource::Iiif(info) => info.width,
+            TileSource::Dzi(info) => info.width,
+        }
+    }
+
+    pub fn height(&self) -> u32 {
+        match self {
+            TileSource::Iiif(info) => info.height,
+            TileSource::Dzi(info) => info.height,
+        }
+    }
+
+    /// Low-resolution rendition of the whole image, shown while tiles load
+    pub fn preview_url(&self, width: u32) -> String {
+        match self {
+            TileSource::Iiif(info) => info.preview_url(width),
+            TileSource::Dzi(info) => info.preview_url(),
+        }
+    }
+
+    pub fn visible_tiles(&self, view: &Viewport, display_scale: f64) -> Vec<Tile> {
+        match self {
+            TileSource::Iiif(info) => info.visible_tiles(view, display_scale),
+            TileSource::Dzi(info) => info.visible_tiles(view, display_scale),
+        }
+    }
+}
+
+/// Largest available downsampling factor that still provides at least one
+/// image pixel per screen pixel.
+pub fn pick_scale_factor(factors: &[u32], display_scale: f64) -> u32 {
+    let wanted = if display_scale > 0.0 {
+        1.0 / display_scale
+    } else {
+        1.0
+    };
+    factors
+        .iter()
+        .copied()
+        .filter(|f| (*f as f64) <= wanted)
+        .max()
+        .or_else(|| factors.iter().copied().min())
+        .unwrap_or(1)
+        .max(1)
+}
+
+/// Power-of-two factors until the whole image fits in a single tile
+fn power_of_two_factors(longest_side: u32, tile: u32) -> Vec<u32> {
+    let mut factors = vec![1];
+    let mut f = 1u32;
+    while longest_side.div_ceil(f) > tile.max(1) && f < (1 << 16) {
+        f *= 2;
+        factors.push(f);
+    }
+    factors
+}
+
+/// Regions `(x, y, w, h)` of a regular grid of `cell_w`×`cell_h` cells over a
+/// `width`×`height` image that intersect `view`, clipped to the image.
+pub fn tile_grid(
+    width: u32,
+    height: u32,
+    cell_w: u32,
+    cell_h: u32,
+    view: &Viewport,
+) -> Vec<(u32, u32, u32, u32)> {
+    if width == 0 || height == 0 || cell_w == 0 || cell_h == 0 {
+        return Vec::new();
+    }
+    let x0 = view.x.max(0.0);
+    let y0 = view.y.max(0.0);
+    let x1 = (view.x + view.w).min(width as f64);
+    let y1 = (view.y + view.h).min(height as f64);
+    if x1 <= x0 || y1 <= y0 {
+        return Vec::new();
+    }
+
+    let first_col = (x0 / cell_w as f64).floor() as u32;
+    let last_col = ((x1 / cell_w as f64).ceil() as u32).min(width.div_ceil(cell_w));
+    let first_row = (y0 / cell_h as f64).floor() as u32;
+    let last_row = ((y1 / cell_h as f64).ceil() as u32).min(height.div_ceil(cell_h));
+
+    let mut cells = Vec::new();
+    for row in first_row..last_row {
+        for col in first_col..last_col {
+            let x = col * cell_w;
+            let y = row * cell_h;
+            cells.push((x, y, cell_w.min(width - x), cell_h.min(height - y)));
+        }
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO_V3: &str = r#"{
+        "id": "https://iiif.example.org/iiif/p1/",
+        "width": 4000,
+        "height": 3000,
+        "tiles": [{ "width": 1024, "scaleFactors": [1, 2, 4, 8] }]
+    }"#;
+
+    #[test]
+    fn test_parse_info() {
+        let info = IiifInfo::from_json("https://fallback", INFO_V3).unwrap();
+        assert_eq!(info.base, "https://iiif.example.org/iiif/p1");
+        assert_eq!((info.width, info.height), (4000, 3000));
+        assert_eq!((info.tile_width, info.tile_height), (1024, 1024));
+        assert_eq!(info.scale_factors, vec![1, 2, 4, 8]);
+
+        let v2 = r#"{"@id": "https://x/iiif/2/p1", "width": 600, "height": 400}"#;
+        let info = IiifInfo::from_json("https://fallback", v2).unwrap();
+        assert_eq!(info.base, "https://x/iiif/2/p1");
+        assert_eq!(info.scale_factors, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_pick_scale_factor() {
+        let factors = [1, 2, 4, 8];
+        assert_eq!(pick_scale_factor(&factors, 1.0), 1);
+        assert_eq!(pick_scale_factor(&factors, 0.5), 2);
+        assert_eq!(pick_scale_factor(&factors, 0.3), 2);
+        assert_eq!(pick_scale_factor(&factors, 0.01), 8);
+        assert_eq!(pick_scale_factor(&factors, 4.0), 1);
+    }
+
+    #[test]
+    fn test_tile_grid_clips_to_image_and_view() {
+        let view = Viewport {
+            x: 900.0,
+            y: 0.0,
+            w: 200.0,
+            h: 100.0,
+        };
+        let cells = tile_grid(1500, 1000, 1024, 1024, &view);
+        assert_eq!(cells, vec![(0, 0, 1024, 1000), (1024, 0, 476, 1000)]);
+
+        let outside = Viewport {
+            x: 2000.0,
+            y: 0.0,
+            w: 10.0,
+            h: 10.0,
+        };
+        assert!(tile_grid(1500, 1000, 1024, 1024, &outside).is_empty());
+    }
+
+    #[test]
+    fn test_visible_tiles_urls() {
+        let info = IiifInfo::from_json("", INFO_V3).unwrap();
+        let whole = Viewport {
+            x: 0.0,
+            y: 0.0,
+            w: 4000.0,
+            h: 3000.0,
+        };
+        // At 1/8 scale a single 8192px region covers the whole image
+        let tiles = info.visible_tiles(&whole, 0.125);
+        assert_eq!(tiles.len(), 1);
+        assert_eq!(
+            tiles[0].url,
+            "https://iiif.example.org/iiif/p1/0,0,4000,3000/500,/0/default.jpg"
+        );
+
+        let tiles = info.visible_tiles(&whole, 1.0);
+        assert_eq!(tiles.len(), 4 * 3);
+    }
+
+    const DZI: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Image xmlns="http://schemas.microsoft.com/deepzoom/2008"
+               Format="jpeg" Overlap="1" TileSize="254">
+            <Size Width="1000" Height="600"/>
+        </Image>"#;
+
+    #[test]
+    fn test_parse_dzi() {
+        let info = DziInfo::from_xml("/public/projects/P/images/p1.dzi", DZI).unwrap();
+        assert_eq!(info.files_url, "/public/projects/P/images/p1_files");
+        assert_eq!((info.width, info.height), (1000, 600));
+        assert_eq!((info.tile_size, info.overlap), (254, 1));
+        assert_eq!(info.format, "jpeg");
+        // 1000px needs 10 halvings to reach a single pixel
+        assert_eq!(info.max_level(), 10);
+        // 1000 >> 2 = 250 fits in one 254px tile
+        assert_eq!(
+            info.preview_url(),
+            "/public/projects/P/images/p1_files/8/0_0.jpeg"
+        );
+
+        assert!(DziInfo::from_xml("x.dzi", "<Image/>").is_err());
+    }
+
+    #[test]
+    fn test_dzi_visible_tiles() {
+        let info = DziInfo::from_xml("p1.dzi", DZI).unwrap();
+        let corner = Viewport {
+            x: 0.0,
+            y: 0.0,
+            w: 100.0,
+            h: 100.0,
+        };
+        let tiles = info.visible_tiles(&corner, 1.0);
+        assert_eq!(tiles.len(), 1);
+        assert_eq!(tiles[0].url, "p1_files/10/0_0.jpeg");
+        // First tile carries one overlap pixel on its right and bottom edges
+        assert_eq!((tiles[0].w, tiles[0].h), (255.0, 255.0));
+
+        let whole = Viewport {
+            x: 0.0,
+            y: 0.0,
+            w: 1000.0,
+            h: 600.0,
+        };
+        // Half resolution: level 9 is 500×300, i.e. a 2×2 grid
+        let tiles = info.visible_tiles(&whole, 0.5);
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles.iter().all(|t| t.url.starts_with("p1_files/9/")));
+        let second = &tiles[1];
+        assert_eq!(second.url, "p1_files/9/1_0.jpeg");
+        assert_eq!(second.x, 253.0 * 2.0);
+    }
+}
@@ -0,0 +1,242 @@
+// src/diagnostics.rs
+// What the viewer was showing when something failed. The project, page and
+// last resource requested are noted as they change, so a panic or a failed
+// load can be described on an error card, with links to reload the page and
+// to report the failure, instead of leaving a blank screen.
+use crate::feedback::{self, Report};
+use crate::i18n::Lang;
+use crate::utils::window;
+use quick_xml::escape::escape;
+use std::cell::RefCell;
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::default());
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Context {
+    project: String,
+    page: u32,
+    resource: Option<String>,
+    issues_url: Option<String>,
+}
+
+/// Note the page being shown, and where the project takes reports; it
+/// starts without a resource.
+pub fn set_page(project: &str, page: u32, issues_url: Option<&str>) {
+    CONTEXT.with(|c| {
+        *c.borrow_mut() = Context {
+            project: project.to_string(),
+            page,
+            resource: None,
+            issues_url: issues_url.map(str::to_string),
+        }
+    });
+}
+
+/// Note a resource as it is requested. The cache-busting query is dropped.
+pub fn set_resource(url: &str) {
+    let url = url.split('?').next().unwrap_or(url);
+    CONTEXT.with(|c| c.borrow_mut().resource = Some(url.to_string()));
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// Part of the interface that failed (Spanish, translated when shown);
+    /// unknown for a panic, which stops the whole application
+    pub area: Option<&'static str>,
+    pub project: String,
+    pub page: u32,
+    pub resource: Option<String>,
+    /// The project's tracker, if its manifest names one
+    pub issues_url: Option<String>,
+    pub message: String,
+}
+
+impl Failure {
+    /// A failure in the current context; `resource` overrides the last one
+    /// noted.
+    pub fn new(area: Option<&'static str>, resource: Option<&str>, message: &str) -> Self {
+        let context = CONTEXT.with(|c| c.try_borrow().map(|c| c.clone()).unwrap_or_default());
+        Self {
+            area,
+            project: context.project,
+            page: context.page,
+            resource: resource
+                .map(|r| r.split('?').next().unwrap_or(r).to_string())
+                .or(context.resource),
+            issues_url: context.issues_url,
+            message: message.to_string(),
+        }
+    }
+
+    /// Prefilled issue quoting the failure, when there is a tracker to file
+    /// it at; `link` is the current page URL.
+    pub fn report_url(&self, link: &str) -> Option<String> {
+        let issues = feedback::issues_url(self.issues_url.as_deref())?;
+        let mut comment = format!("```\n{}\n```\n", self.message);
+        if let Some(resource) = &self.resource {
+            comment.push_str(&format!("\n**Recurso:** {}\n", resource));
+        }
+        let report = Report {
+            issues,
+            project: if self.project.is_empty() {
+                "tei-viewer"
+            } else {
+                &self.project
+            },
+            page: self.page,
+            link,
+            line: None,
+            comment: &comment,
+        };
+        Some(report.issue_url())
+    }
+
+    /// Markup of the error card. Its actions are plain links, so they still
+    /// work after a panic has stopped the application.
+    pub fn card_html(&self, lang: Lang, link: &str) -> String {
+        let title = match self.area {
+            Some(area) => lang.tf("No se pudo mostrar {}", &[&lang.t(area)]),
+            None => lang
+                .t("El visor se ha detenido por un error inesperado")
+                .to_string(),
+        };
+        let mut details = String::new();
+        let mut row = |label: &str, value: &str| {
+            details.push_str(&format!(
+                "<dt>{}</dt><dd>{}</dd>",
+                escape(lang.t(label)),
+                escape(value)
+            ));
+        };
+        if !self.project.is_empty() {
+            row("Proyecto", &self.project);
+            row("Página", &self.page.to_string());
+        }
+        if let Some(resource) = &self.resource {
+            row("Recurso", resource);
+        }
+        let report = self.report_url(link).map(|url| {
+            format!(
+                "<a class=\"error-report\" href=\"{}\" target=\"_blank\" rel=\"noopener\">{}</a>",
+                escape(&url),
+                escape(lang.t("Reportar el problema"))
+            )
+        });
+        format!(
+            "<div class=\"error-card\" role=\"alert\">\
+             <h2>{}</h2>\
+             <p>{}</p>\
+             <dl class=\"error-details\">{}</dl>\
+             <details><summary>{}</summary><pre>{}</pre></details>\
+             <div class=\"error-actions\">\
+             <a class=\"error-reload\" href=\"{}\">{}</a>{}\
+             </div>\
+             </div>",
+            escape(&title),
+            escape(lang.t(
+                "Recargue la página para volver a intentarlo. Si el error se repite, repórtelo: el informe ya incluye estos datos."
+            )),
+            details,
+            escape(lang.t("Detalles técnicos")),
+            escape(&self.message),
+            escape(link),
+            escape(lang.t("⟳ Recargar la página")),
+            report.unwrap_or_default(),
+        )
+    }
+}
+
+/// Log panics and replace the halted application with an error card.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|l| format!(" ({}:{})", l.file(), l.line()))
+            .unwrap_or_default();
+        let payload = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        let message = format!("{}{}", payload, location);
+        log::error!("panic: {}", message);
+
+        let Some(document) = window().and_then(|w| w.document()) else {
+            return;
+        };
+        let lang = document
+            .document_element()
+            .and_then(|root| root.get_attribute("lang"))
+            .and_then(|tag| Lang::parse(&tag))
+            .unwrap_or_default();
+        let link = document
+            .location()
+            .and_then(|l| l.href().ok())
+            .unwrap_or_default();
+        let failure = Failure::new(None, None, &message);
+        if let (Some(body), Ok(overlay)) = (document.body(), document.create_element("div")) {
+            overlay.set_class_name("error-overlay");
+            overlay.set_inner_html(&failure.card_html(lang, &link));
+            let _ = body.append_child(&overlay);
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_context() {
+        let issues = "https://github.com/user/editions/issues/new";
+        set_page("PGM-XIII", 3, Some(issues));
+        set_resource("public/projects/PGM-XIII/p3_dip.xml?v=123");
+        let failure = Failure::new(None, None, "boom");
+        assert_eq!(failure.project, "PGM-XIII");
+        assert_eq!(failure.page, 3);
+        assert_eq!(failure.issues_url.as_deref(), Some(issues));
+        assert_eq!(
+            failure.resource.as_deref(),
+            Some("public/projects/PGM-XIII/p3_dip.xml")
+        );
+
+        let failure = Failure::new(Some("el visor"), Some("a.xml?v=1"), "boom");
+        assert_eq!(failure.resource.as_deref(), Some("a.xml"));
+
+        // A new page forgets the previous resource
+        set_page("PGM-XIII", 4, Some(issues));
+        assert_eq!(Failure::new(None, None, "").resource, None);
+    }
+
+    #[test]
+    fn test_card_html() {
+        let failure = Failure {
+            area: None,
+            project: "P".to_string(),
+            page: 2,
+            resource: Some("p2_dip.xml".to_string()),
+            issues_url: Some("https://github.com/user/editions/issues/new".to_string()),
+            message: "index out of bounds: <3>".to_string(),
+        };
+        let html = failure.card_html(Lang::Es, "https://example.org/P/2?a=1&b=2");
+        assert!(html.contains("<dt>Página</dt><dd>2</dd>"));
+        assert!(html.contains("<dt>Recurso</dt><dd>p2_dip.xml</dd>"));
+        assert!(html.contains("index out of bounds: &lt;3&gt;"));
+        assert!(html.contains("href=\"https://example.org/P/2?a=1&amp;b=2\""));
+        assert!(html.contains("href=\"https://github.com/user/editions/issues/new?title="));
+
+        // Without a tracker there is no report link
+        let failure = Failure {
+            issues_url: None,
+            ..failure
+        };
+        let html = failure.card_html(Lang::Es, "https://example.org/P/2");
+        assert_eq!(
+            html.contains("error-report"),
+            feedback::issues_url(None).is_some()
+        );
+    }
+}
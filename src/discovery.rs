@@ -0,0 +1,82 @@
+// src/discovery.rs
+// Finding the project folders when `public/projects/index.json` is absent:
+// a discovery endpoint set in index.html with
+// `<meta name="tei-viewer-discovery" content="...">`, else the directory
+// listing (autoindex) of `public/projects/` that nginx, Apache or
+// `python -m http.server` produce.
+use crate::url_state;
+use crate::utils::{resource_url, window};
+
+const DISCOVERY_META: &str = "tei-viewer-discovery";
+
+/// URL to probe for the project folders.
+pub fn discovery_url() -> String {
+    configured_url().unwrap_or_else(|| resource_url("public/projects/"))
+}
+
+fn configured_url() -> Option<String> {
+    let document = window()?.document()?;
+    let meta = document
+        .query_selector(&format!("meta[name=\"{}\"]", DISCOVERY_META))
+        .ok()??;
+    let content = meta.get_attribute("content")?;
+    let content = content.trim();
+    if content.is_empty() {
+        return None;
+    }
+    if content.starts_with("http://") || content.starts_with("https://") {
+        Some(content.to_string())
+    } else {
+        Some(resource_url(content))
+    }
+}
+
+/// Folder names linked from an HTML directory listing. Parent, sorting
+/// and file links are skipped.
+pub fn parse_listing(html: &str) -> Vec<String> {
+    let mut folders: Vec<String> = Vec::new();
+    for chunk in html.split("href=").skip(1) {
+        let Some(quote) = chunk.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        let Some(href) = chunk[1..].split(quote).next() else {
+            continue;
+        };
+        let Some(name) = href.strip_prefix("./").unwrap_or(href).strip_suffix('/') else {
+            continue;
+        };
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '?', ':', '#']) {
+            continue;
+        }
+        let name = url_state::decode(name);
+        if !folders.contains(&name) {
+            folders.push(name);
+        }
+    }
+    folders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_listing() {
+        // nginx autoindex
+        let nginx = r#"<html><head><title>Index of /public/projects/</title></head>
+            <body><h1>Index of /public/projects/</h1><hr><pre><a href="../">../</a>
+            <a href="PGM-XIII/">PGM-XIII/</a>                 12-Mar-2025 10:00       -
+            <a href="Tractatus%20Fascinatione/">Tractatus Fascinatione/</a> 12-Mar-2025 10:00 -
+            <a href="index.html">index.html</a>
+            </pre><hr></body></html>"#;
+        assert_eq!(parse_listing(nginx), ["PGM-XIII", "Tractatus Fascinatione"]);
+
+        // Apache, with sorting links and an absolute parent link
+        let apache = r#"<tr><th><a href="?C=N;O=D">Name</a></th></tr>
+            <tr><td><a href="/public/">Parent Directory</a></td></tr>
+            <tr><td><a href='Chanca/'>Chanca/</a></td></tr>
+            <tr><td><a href="./.git/">.git/</a></td></tr>"#;
+        assert_eq!(parse_listing(apache), ["Chanca"]);
+        assert!(parse_listing("<p>Forbidden</p>").is_empty());
+    }
+}
@@ -0,0 +1,120 @@
+// src/doc_cache.rs
+use crate::tei_data::TeiDocument;
+
+/// Number of parsed documents kept in memory by default.
+pub const DEFAULT_CAPACITY: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey {
+    pub project: String,
+    pub page: u32,
+    /// Edition identifier, e.g. "dip" or "trad"
+    pub edition: String,
+}
+
+impl CacheKey {
+    pub fn new(project: &str, page: u32, edition: &str) -> Self {
+        Self {
+            project: project.to_string(),
+            page,
+            edition: edition.to_string(),
+        }
+    }
+}
+
+/// Small least-recently-used cache of parsed TEI documents, so navigating
+/// back to a recently viewed page doesn't re-download or re-parse its XML.
+pub struct DocumentCache {
+    capacity: usize,
+    // Ordered from least to most recently used
+    entries: Vec<(CacheKey, TeiDocument)>,
+}
+
+impl DocumentCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Look up a document, marking it as most recently used.
+    pub fn get(&mut self, key: &CacheKey) -> Option<TeiDocument> {
+        let pos = self.entries.iter().position(|(k, _)| k == key)?;
+        let entry = self.entries.remove(pos);
+        let doc = entry.1.clone();
+        self.entries.push(entry);
+        Some(doc)
+    }
+
+    /// Insert (or refresh) a document, evicting the least recently used
+    /// entry when the cache is full.
+    pub fn insert(&mut self, key: CacheKey, doc: TeiDocument) {
+        self.entries.retain(|(k, _)| k != &key);
+        if self.entries.len() >= self.capacity {
+            self.entries.remove(0);
+        }
+        self.entries.push((key, doc));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl Default for DocumentCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc_with_title(title: &str) -> TeiDocument {
+        let mut doc = TeiDocument::new();
+        doc.metadata.title = title.to_string();
+        doc
+    }
+
+    #[test]
+    fn test_get_and_insert() {
+        let mut cache = DocumentCache::new(2);
+        let key = CacheKey::new("TEST", 1, "dip");
+        assert!(cache.get(&key).is_none());
+
+        cache.insert(key.clone(), doc_with_title("p1"));
+        assert_eq!(cache.get(&key).unwrap().metadata.title, "p1");
+        assert!(cache.get(&CacheKey::new("TEST", 1, "trad")).is_none());
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = DocumentCache::new(2);
+        let k1 = CacheKey::new("TEST", 1, "dip");
+        let k2 = CacheKey::new("TEST", 2, "dip");
+        let k3 = CacheKey::new("TEST", 3, "dip");
+
+        cache.insert(k1.clone(), doc_with_title("p1"));
+        cache.insert(k2.clone(), doc_with_title("p2"));
+        // Touch p1 so p2 becomes the eviction candidate
+        assert!(cache.get(&k1).is_some());
+        cache.insert(k3.clone(), doc_with_title("p3"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&k1).is_some());
+        assert!(cache.get(&k2).is_none());
+        assert!(cache.get(&k3).is_some());
+    }
+
+    #[test]
+    fn test_reinsert_replaces_entry() {
+        let mut cache = DocumentCache::new(2);
+        let key = CacheKey::new("TEST", 1, "dip");
+        cache.insert(key.clone(), doc_with_title("old"));
+        cache.insert(key.clone(), doc_with_title("new"));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&key).unwrap().metadata.title, "new");
+    }
+}
@@ -0,0 +1,188 @@
+// src/doc_diff.rs
+// Word-level comparison of two renderings of a transcription, such as the
+// diplomatic and normalized readings of one page, or two editions of the
+// same folio. Lines are paired by position.
+use crate::tei_data::{Reading, TeiDocument};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffOp {
+    Same(String),
+    Inserted(String),
+    Deleted(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineDiff {
+    pub index: usize,
+    pub ops: Vec<DiffOp>,
+}
+
+impl LineDiff {
+    pub fn is_changed(&self) -> bool {
+        self.ops.iter().any(|op| !matches!(op, DiffOp::Same(_)))
+    }
+}
+
+/// Compare two readings of the same document line by line.
+pub fn diff_readings(doc: &TeiDocument, old: Reading, new: Reading) -> Vec<LineDiff> {
+    let old_lines: Vec<String> = doc.lines.iter().map(|l| l.text(old)).collect();
+    let new_lines: Vec<String> = doc.lines.iter().map(|l| l.text(new)).collect();
+    diff_lines(&old_lines, &new_lines)
+}
+
+/// Compare two transcriptions of the same folio (e.g. by different
+/// editors) in the given reading.
+pub fn diff_documents(old: &TeiDocument, new: &TeiDocument, reading: Reading) -> Vec<LineDiff> {
+    let old_lines: Vec<String> = old.lines.iter().map(|l| l.text(reading)).collect();
+    let new_lines: Vec<String> = new.lines.iter().map(|l| l.text(reading)).collect();
+    diff_lines(&old_lines, &new_lines)
+}
+
+/// Diff two lists of lines paired by position; a line missing on one side
+/// shows up as fully inserted or deleted.
+pub fn diff_lines(old: &[String], new: &[String]) -> Vec<LineDiff> {
+    (0..old.len().max(new.len()))
+        .map(|index| {
+            let a = old.get(index).map(String::as_str).unwrap_or("");
+            let b = new.get(index).map(String::as_str).unwrap_or("");
+            LineDiff {
+                index,
+                ops: diff_words(a, b),
+            }
+        })
+        .collect()
+}
+
+/// Longest-common-subsequence diff over whitespace-separated words.
+/// Consecutive words with the same status are merged into one op.
+pub fn diff_words(old: &str, new: &str) -> Vec<DiffOp> {
+    let a: Vec<&str> = old.split_whitespace().collect();
+    let b: Vec<&str> = new.split_whitespace().collect();
+
+    // lcs[i][j] = length of the LCS of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push_word(&mut ops, DiffOp::Same(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push_word(&mut ops, DiffOp::Deleted(a[i].to_string()));
+            i += 1;
+        } else {
+            push_word(&mut ops, DiffOp::Inserted(b[j].to_string()));
+            j += 1;
+        }
+    }
+    ops
+}
+
+fn push_word(ops: &mut Vec<DiffOp>, op: DiffOp) {
+    match (ops.last_mut(), op) {
+        (Some(DiffOp::Same(text)), DiffOp::Same(word))
+        | (Some(DiffOp::Inserted(text)), DiffOp::Inserted(word))
+        | (Some(DiffOp::Deleted(text)), DiffOp::Deleted(word)) => {
+            text.push(' ');
+            text.push_str(&word);
+        }
+        (_, op) => ops.push(op),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tei_data::{Line, TextNode};
+
+    #[test]
+    fn test_diff_words() {
+        assert_eq!(
+            diff_words("a b c", "a x c"),
+            vec![
+                DiffOp::Same("a".into()),
+                DiffOp::Deleted("b".into()),
+                DiffOp::Inserted("x".into()),
+                DiffOp::Same("c".into()),
+            ]
+        );
+        assert_eq!(
+            diff_words("a b", "a b c d"),
+            vec![DiffOp::Same("a b".into()), DiffOp::Inserted("c d".into())]
+        );
+        assert!(diff_words("", "").is_empty());
+    }
+
+    #[test]
+    fn test_diff_lines_unequal_length() {
+        let old = vec!["a".to_string()];
+        let new = vec!["a".to_string(), "b".to_string()];
+        let diff = diff_lines(&old, &new);
+        assert_eq!(diff.len(), 2);
+        assert!(!diff[0].is_changed());
+        assert_eq!(diff[1].ops, vec![DiffOp::Inserted("b".into())]);
+    }
+
+    #[test]
+    fn test_diff_documents() {
+        let doc = |text: &str| {
+            let mut doc = TeiDocument::new();
+            doc.lines.push(Line {
+                facs: "z1".into(),
+                n: None,
+                content: vec![TextNode::Text {
+                    content: text.into(),
+                }],
+            });
+            doc
+        };
+        let diff = diff_documents(&doc("ἐν ἀρχῇ ἦν"), &doc("ἐν ἀρχῆι ἦν"), Reading::Diplomatic);
+        assert_eq!(
+            diff[0].ops,
+            vec![
+                DiffOp::Same("ἐν".into()),
+                DiffOp::Deleted("ἀρχῇ".into()),
+                DiffOp::Inserted("ἀρχῆι".into()),
+                DiffOp::Same("ἦν".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_readings() {
+        let mut doc = TeiDocument::new();
+        doc.lines.push(Line {
+            facs: "z1".into(),
+            n: None,
+            content: vec![
+                TextNode::Text {
+                    content: "τοῦ ".into(),
+                },
+                TextNode::Abbr {
+                    abbr: "θυ".into(),
+                    expan: "θεοῦ".into(),
+                },
+            ],
+        });
+        let diff = diff_readings(&doc, Reading::Diplomatic, Reading::Normalized);
+        assert_eq!(
+            diff[0].ops,
+            vec![
+                DiffOp::Same("τοῦ".into()),
+                DiffOp::Deleted("θυ".into()),
+                DiffOp::Inserted("θεοῦ".into()),
+            ]
+        );
+    }
+}
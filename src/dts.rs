@@ -0,0 +1,409 @@
+// src/dts.rs
+// Client of the Distributed Text Services API (DTS 1.0, and the earlier
+// draft still served by many repositories), so the viewer can read the texts
+// of a DTS repository rather than only static files. The collections are
+// browsed from the endpoint's entry point; a resource opens as a project
+// with a page per top-level citable unit (book, chapter, column…), each
+// fetched as a TEI passage through the documents API. Passages come wrapped
+// in `<dts:wrapper>` and are rewritten into the line layout the viewer reads.
+use crate::iiif::text;
+use crate::net::{self, FetchError};
+use crate::project_config::{PageInfo, ProjectConfig};
+use crate::remote_project::folder_of;
+use crate::utils::{slug, Url};
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+use serde_json::Value;
+
+/// Namespaces of DTS 1.0 and of the draft
+const NAMESPACES: [&str; 2] = ["w3id.org/api/dts", "w3id.org/dts/api"];
+
+/// The three endpoints of a DTS API, from its entry point
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    collection: String,
+    navigation: String,
+    document: String,
+    /// The draft names resources `id` and asks for levels, not `down`
+    draft: bool,
+}
+
+impl Endpoint {
+    /// Endpoints listed by the entry point at `url`.
+    pub fn from_json(url: &str, json: &Value) -> Result<Self, String> {
+        let draft = json.get("collection").is_none();
+        let template = |v1: &str, draft_key: &str| {
+            let key = if draft { draft_key } else { v1 };
+            json[key]
+                .as_str()
+                .map(|t| resolve(url, t.split('{').next().unwrap_or(t)))
+                .ok_or_else(|| format!("no {} endpoint", key))
+        };
+        Ok(Self {
+            collection: template("collection", "collections")?,
+            navigation: template("navigation", "navigation")?,
+            document: template("document", "documents")?,
+            draft,
+        })
+    }
+
+    /// Members of a collection, or of the root collection.
+    pub fn collection_url(&self, id: Option<&str>) -> String {
+        match (id, self.draft) {
+            (None, _) => self.collection.clone(),
+            (Some(id), true) => with_query(&self.collection, &[("id", id)]),
+            (Some(id), false) => with_query(&self.collection, &[("id", id), ("nav", "children")]),
+        }
+    }
+
+    /// Top-level citable units of a resource.
+    pub fn navigation_url(&self, resource: &str) -> String {
+        if self.draft {
+            with_query(&self.navigation, &[("id", resource), ("level", "1")])
+        } else {
+            with_query(&self.navigation, &[("resource", resource), ("down", "1")])
+        }
+    }
+
+    /// TEI of a passage, or of the whole resource.
+    pub fn document_url(&self, resource: &str, reference: Option<&str>) -> String {
+        let key = if self.draft { "id" } else { "resource" };
+        match reference {
+            Some(reference) => with_query(&self.document, &[(key, resource), ("ref", reference)]),
+            None => with_query(&self.document, &[(key, resource)]),
+        }
+    }
+}
+
+/// A collection or a readable resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    pub is_collection: bool,
+}
+
+/// Title and members of a collection response.
+pub fn members_of(json: &Value) -> (String, Vec<Member>) {
+    let members = list(json)
+        .iter()
+        .filter_map(|member| {
+            let id = member["@id"].as_str()?.to_string();
+            let title = text(&member["title"]);
+            Some(Member {
+                title: if title.is_empty() { id.clone() } else { title },
+                description: text(&member["description"]),
+                is_collection: text(&member["@type"]).ends_with("Collection"),
+                id,
+            })
+        })
+        .collect();
+    (text(&json["title"]), members)
+}
+
+/// (reference, label) of the citable units of a navigation response.
+pub fn references_of(json: &Value) -> Vec<(String, String)> {
+    list(json)
+        .iter()
+        .filter_map(|unit| {
+            let reference = ["identifier", "ref", "dts:ref"]
+                .iter()
+                .find_map(|key| unit[*key].as_str())
+                .or(unit.as_str())?
+                .to_string();
+            let title = text(&unit["dublinCore"]["title"]);
+            let label = if title.is_empty() {
+                reference.clone()
+            } else {
+                title
+            };
+            Some((reference, label))
+        })
+        .collect()
+}
+
+/// Members of a response, under `member` or the draft's `hydra:member`.
+fn list(json: &Value) -> Vec<Value> {
+    json.get("member")
+        .or(json.get("hydra:member"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// The API at an entry point URL.
+pub async fn connect(url: &str) -> Result<Endpoint, FetchError> {
+    let json = net::get_json(url).await?;
+    Endpoint::from_json(url, &json).map_err(|reason| FetchError::Decode {
+        url: url.to_string(),
+        reason,
+    })
+}
+
+/// Title and members of a collection of the API at `url`.
+pub async fn browse(
+    url: &str,
+    collection: Option<String>,
+) -> Result<(String, Vec<Member>), FetchError> {
+    let endpoint = connect(url).await?;
+    let json = net::get_json(&endpoint.collection_url(collection.as_deref())).await?;
+    Ok(members_of(&json))
+}
+
+/// A resource of the API at `url` as a project, with a page per top-level
+/// citable unit; a resource without a citation tree is a single page.
+pub async fn load(url: &str, resource: &str) -> Result<ProjectConfig, FetchError> {
+    let endpoint = connect(url).await?;
+    let id = format!("dts-{}", slug(resource, 40));
+    let mut project = ProjectConfig::new(id.clone(), resource.to_string());
+    // The resource's own title and description, when the API gives them
+    if let Ok(json) = net::get_json::<Value>(&endpoint.collection_url(Some(resource))).await {
+        let title = text(&json["title"]);
+        if !title.is_empty() {
+            project.name = title;
+        }
+        project.description = text(&json["description"]);
+    }
+    let references = match net::get_json(&endpoint.navigation_url(resource)).await {
+        Ok(json) => references_of(&json),
+        Err(e) => {
+            log::warn!("No citation tree for {}: {}", resource, e);
+            Vec::new()
+        }
+    };
+    let pages: Vec<(Option<String>, String)> = if references.is_empty() {
+        vec![(None, project.name.clone())]
+    } else {
+        references
+            .into_iter()
+            .map(|(reference, label)| (Some(reference), label))
+            .collect()
+    };
+    for (i, (reference, label)) in pages.into_iter().enumerate() {
+        let number = i as u32 + 1;
+        project.files.insert(
+            ProjectConfig::page_file(number, "dip"),
+            endpoint.document_url(resource, reference.as_deref()),
+        );
+        project.pages.push(
+            PageInfo::new(number)
+                .with_label(label)
+                .with_translation(false)
+                .with_image(false),
+        );
+    }
+    project.folder = Some(folder_of(url));
+    log::info!("Loaded DTS resource {} from {}", resource, url);
+    Ok(project)
+}
+
+/// Whether `xml` is a passage returned by a DTS documents endpoint.
+pub fn is_passage(xml: &str) -> bool {
+    NAMESPACES.iter().any(|ns| xml.contains(ns))
+        && (xml.contains(":wrapper") || xml.contains(":fragment"))
+}
+
+/// Elements of a passage read as lines
+const LINE_ELEMENTS: [&[u8]; 3] = [b"l", b"p", b"ab"];
+
+/// TEI of a passage in the layout of the diplomatic editions: each verse,
+/// paragraph or block (`<l>`, `<p>`, `<ab>`) becomes a line, its markup kept.
+/// A passage without any is read as a single line of text.
+pub fn passage_to_tei(xml: &str) -> Result<String, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut writer = Writer::new(Vec::new());
+    let mut plain = String::new();
+    // Line element being copied, and how deep inside it
+    let mut open: Option<(Vec<u8>, usize)> = None;
+    let mut count = 0;
+    let write = |writer: &mut Writer<Vec<u8>>, event: Event| {
+        writer.write_event(event).map_err(|e| e.to_string())
+    };
+    loop {
+        let event = reader
+            .read_event_into(&mut buf)
+            .map_err(|e| format!("DTS passage error at {}: {:?}", reader.buffer_position(), e))?;
+        match (event, open.as_mut()) {
+            (Event::Eof, _) => break,
+            (Event::Start(e), None) if LINE_ELEMENTS.contains(&e.local_name().as_ref()) => {
+                count += 1;
+                let n = e
+                    .try_get_attribute("n")
+                    .ok()
+                    .flatten()
+                    .and_then(|a| a.unescape_value().ok())
+                    .map_or_else(|| count.to_string(), |n| n.to_string());
+                let lb = format!("<lb n=\"{}\"/><ab>", escape(&n));
+                writer.get_mut().extend_from_slice(lb.as_bytes());
+                open = Some((e.local_name().as_ref().to_vec(), 0));
+            }
+            (Event::Start(e), Some((name, depth))) => {
+                if e.local_name().as_ref() == name.as_slice() {
+                    *depth += 1;
+                }
+                write(&mut writer, Event::Start(e))?;
+            }
+            (Event::End(e), Some((name, depth))) if e.local_name().as_ref() == name.as_slice() => {
+                if *depth == 0 {
+                    writer.get_mut().extend_from_slice(b"</ab>");
+                    open = None;
+                } else {
+                    *depth -= 1;
+                    write(&mut writer, Event::End(e))?;
+                }
+            }
+            (Event::Text(e), None) => {
+                plain.push_str(&e.unescape().unwrap_or_default());
+            }
+            (
+                event @ (Event::End(_) | Event::Empty(_) | Event::Text(_) | Event::CData(_)),
+                Some(_),
+            ) => {
+                write(&mut writer, event)?;
+            }
+            _ => {}
+        }
+        buf.clear();
+    }
+    let mut lines = String::from_utf8(writer.into_inner()).map_err(|e| e.to_string())?;
+    if count == 0 {
+        let plain = plain.split_whitespace().collect::<Vec<_>>().join(" ");
+        lines = format!("<lb n=\"1\"/><ab>{}</ab>", escape(&plain));
+    }
+    Ok(format!(
+        "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\"><text><body>{}</body></text></TEI>",
+        lines
+    ))
+}
+
+/// `path` (absolute, site-absolute or relative) as seen from `base`.
+fn resolve(base: &str, path: &str) -> String {
+    if path.starts_with("http://") || path.starts_with("https://") {
+        return path.to_string();
+    }
+    if let Some(path) = path.strip_prefix('/') {
+        let host_end = base
+            .find("://")
+            .and_then(|s| base[s + 3..].find('/').map(|e| s + 3 + e))
+            .unwrap_or(base.len());
+        return format!("{}/{}", &base[..host_end], path);
+    }
+    format!("{}{}", folder_of(base), path)
+}
+
+fn with_query(base: &str, pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .fold(Url::new(base), |url, (key, value)| url.param(key, value))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tei_data::Reading;
+    use crate::tei_parser::parse_tei_xml;
+
+    #[test]
+    fn test_endpoint() {
+        let v1 = serde_json::json!({
+            "@type": "EntryPoint",
+            "collection": "/api/dts/collection{?id,page,nav}",
+            "navigation": "/api/dts/navigation{?resource,ref,start,end,down,tree,page}",
+            "document": "/api/dts/document{?resource,ref,start,end,tree,mediaType}"
+        });
+        let endpoint = Endpoint::from_json("https://texts.example.org/api/dts", &v1).unwrap();
+        assert_eq!(
+            endpoint.collection_url(Some("urn:cts:greekLit")),
+            "https://texts.example.org/api/dts/collection?id=urn%3Acts%3AgreekLit&nav=children"
+        );
+        assert_eq!(
+            endpoint.navigation_url("urn:x"),
+            "https://texts.example.org/api/dts/navigation?resource=urn%3Ax&down=1"
+        );
+        assert_eq!(
+            endpoint.document_url("urn:x", Some("1")),
+            "https://texts.example.org/api/dts/document?resource=urn%3Ax&ref=1"
+        );
+
+        let draft = serde_json::json!({
+            "collections": "collections/",
+            "navigation": "navigation/",
+            "documents": "documents/"
+        });
+        let endpoint = Endpoint::from_json("https://texts.example.org/dts/", &draft).unwrap();
+        assert_eq!(
+            endpoint.navigation_url("urn:x"),
+            "https://texts.example.org/dts/navigation/?id=urn%3Ax&level=1"
+        );
+        assert_eq!(
+            endpoint.document_url("urn:x", None),
+            "https://texts.example.org/dts/documents/?id=urn%3Ax"
+        );
+        assert!(Endpoint::from_json("https://x/", &serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_members_of() {
+        let json = serde_json::json!({
+            "@id": "greekLit", "title": "Greek Texts",
+            "member": [
+                { "@id": "urn:tlg0012", "@type": "Collection", "title": "Homer" },
+                { "@id": "urn:tlg0012.tlg001", "@type": "Resource", "title": "Iliad",
+                  "description": "Books 1–24" },
+                { "@id": "urn:untitled", "@type": "Resource" }
+            ]
+        });
+        let (title, members) = members_of(&json);
+        assert_eq!(title, "Greek Texts");
+        assert!(members[0].is_collection);
+        assert!(!members[1].is_collection);
+        assert_eq!(members[1].description, "Books 1–24");
+        assert_eq!(members[2].title, "urn:untitled");
+    }
+
+    #[test]
+    fn test_references_of() {
+        let v1 = serde_json::json!({ "member": [
+            { "identifier": "1", "@type": "CitableUnit", "dublinCore": { "title": "Book 1" } },
+            { "identifier": "2", "@type": "CitableUnit" }
+        ] });
+        assert_eq!(
+            references_of(&v1),
+            vec![
+                ("1".to_string(), "Book 1".to_string()),
+                ("2".to_string(), "2".to_string())
+            ]
+        );
+        let draft = serde_json::json!({ "hydra:member": [{ "ref": "1.1" }] });
+        assert_eq!(references_of(&draft)[0].0, "1.1");
+    }
+
+    #[test]
+    fn test_passage_to_tei() {
+        let passage = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><dts:wrapper xmlns:dts="https://w3id.org/api/dts#">
+            <div type="book" n="1"><head>Book 1</head>
+              <l n="1">μῆνιν ἄειδε <persName>θεὰ</persName></l>
+              <l>Πηληϊάδεω &amp; Ἀχιλῆος</l>
+            </div></dts:wrapper></TEI>"#;
+        assert!(is_passage(passage));
+        let doc = parse_tei_xml(passage).unwrap();
+        assert_eq!(doc.lines.len(), 2);
+        assert_eq!(doc.lines[0].n.as_deref(), Some("1"));
+        assert_eq!(doc.lines[0].text(Reading::Diplomatic), "μῆνιν ἄειδε θεὰ");
+        assert_eq!(doc.lines[1].n.as_deref(), Some("2"));
+        assert_eq!(
+            doc.lines[1].text(Reading::Diplomatic),
+            "Πηληϊάδεω & Ἀχιλῆος"
+        );
+
+        let prose = r#"<TEI><dts:fragment xmlns:dts="https://w3id.org/dts/api#"><div>In <hi>principio</hi></div></dts:fragment></TEI>"#;
+        let doc = parse_tei_xml(prose).unwrap();
+        assert_eq!(doc.lines.len(), 1);
+        assert_eq!(doc.lines[0].text(Reading::Diplomatic), "In principio");
+        assert!(!is_passage("<TEI><text><body/></text></TEI>"));
+    }
+}
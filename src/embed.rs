@@ -0,0 +1,133 @@
+// src/embed.rs
+// The viewer embedded in another site through an iframe:
+// `?embed=1&project=PGM-XIII&page=3` shows the viewer alone, without the
+// header, selectors and footer of the app, so a museum or a course page can
+// show one folio. `&view=image` or `&view=text` narrows it further to the
+// facsimile or to the text.
+use crate::route::Route;
+use crate::url_state::query_param;
+use crate::utils::{window, Url};
+
+pub const PARAM: &str = "embed";
+const VIEW_PARAM: &str = "view";
+
+/// Panels of the viewer on screen
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Panels {
+    #[default]
+    All,
+    Image,
+    Text,
+}
+
+impl Panels {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "image" => Some(Panels::Image),
+            "text" => Some(Panels::Text),
+            "all" => Some(Panels::All),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Panels::All => "all",
+            Panels::Image => "image",
+            Panels::Text => "text",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embed {
+    /// Page to open, for a link to the root of the viewer
+    pub project: Option<String>,
+    pub page: Option<u32>,
+    pub panels: Panels,
+}
+
+impl Embed {
+    /// The embedding asked for by a query string, if any.
+    pub fn parse(search: &str) -> Option<Self> {
+        query_param(search, PARAM).filter(|v| v == "1" || v == "true")?;
+        Some(Self {
+            project: query_param(search, "project").filter(|p| !p.is_empty()),
+            page: query_param(search, "page").and_then(|p| p.parse().ok()),
+            panels: query_param(search, VIEW_PARAM)
+                .and_then(|v| Panels::parse(&v))
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Route of the page named in the query.
+    pub fn route(&self) -> Option<Route> {
+        let project = self.project.clone()?;
+        Some(match self.page {
+            Some(page) => Route::Page { project, page },
+            None => Route::Project { project },
+        })
+    }
+
+    /// Query kept in the address as pages turn; the page itself is then
+    /// in the path.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![(PARAM, "1".to_string())];
+        if self.panels != Panels::All {
+            query.push((VIEW_PARAM, self.panels.as_str().to_string()));
+        }
+        query
+    }
+}
+
+/// Embedding named in the address of the page, if any.
+pub fn requested() -> Option<Embed> {
+    let search = window()?.location().search().ok()?;
+    Embed::parse(&search)
+}
+
+/// The same address opened in the full viewer: `url` without the
+/// embedding parameters.
+pub fn full_view_url(url: &str) -> String {
+    Url::new(url).without(PARAM).without(VIEW_PARAM).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let embed = Embed::parse("?embed=1&project=PGM-XIII&page=3&view=image").unwrap();
+        assert_eq!(
+            embed.route(),
+            Some(Route::Page {
+                project: "PGM-XIII".to_string(),
+                page: 3
+            })
+        );
+        assert_eq!(embed.panels, Panels::Image);
+        assert_eq!(
+            embed.query(),
+            vec![(PARAM, "1".to_string()), (VIEW_PARAM, "image".to_string())]
+        );
+
+        let embed = Embed::parse("?embed=true").unwrap();
+        assert_eq!(embed.route(), None);
+        assert_eq!(embed.panels, Panels::All);
+        assert_eq!(Embed::parse("?project=PGM-XIII"), None);
+        assert_eq!(Embed::parse("?embed=0"), None);
+    }
+
+    #[test]
+    fn test_full_view_url() {
+        assert_eq!(
+            full_view_url("https://x.org/PGM-XIII/3?embed=1&view=text#zoom=2.00"),
+            "https://x.org/PGM-XIII/3#zoom=2.00"
+        );
+        assert_eq!(
+            full_view_url("https://x.org/P/1?embed=1&gh=a%2Fb"),
+            "https://x.org/P/1?gh=a%2Fb"
+        );
+    }
+}
@@ -0,0 +1,237 @@
+// src/entity_index.rs
+// Indices nominum et rerum of the whole collection: the persons
+// (`<persName>`), places (`<placeName>`) and divine names
+// (`<persName type="divine">`, `<rs type="divine">`) tagged on every page,
+// each with the lines that name it.
+use crate::polytonic;
+use crate::project_config::ProjectConfig;
+use crate::tei_data::{Reading, TeiDocument, TextNode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Person,
+    Place,
+    Divine,
+}
+
+impl EntityKind {
+    pub const ALL: [EntityKind; 3] = [EntityKind::Person, EntityKind::Place, EntityKind::Divine];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EntityKind::Person => "Personas",
+            EntityKind::Place => "Lugares",
+            EntityKind::Divine => "Entidades divinas",
+        }
+    }
+
+    /// `@type` values of `<persName>`/`<rs>` naming a deity
+    fn is_divine(tipo: &str) -> bool {
+        matches!(
+            tipo.trim().to_lowercase().as_str(),
+            "divine" | "deity" | "god" | "goddess" | "divinity" | "divino" | "dios" | "diosa"
+        )
+    }
+}
+
+/// A line naming an entity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub project: String,
+    pub project_name: String,
+    pub page: u32,
+    pub page_label: String,
+    /// How the line is referred to (see `TeiDocument::line_label`)
+    pub line: String,
+    /// The name as written on the line
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entity {
+    pub kind: EntityKind,
+    /// First spelling met, normalized reading
+    pub name: String,
+    /// `@ref` of the name (e.g. a Wikidata URI), when tagged with one
+    pub ref_uri: Option<String>,
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl Entity {
+    /// Number of projects naming the entity.
+    pub fn project_count(&self) -> usize {
+        let mut projects: Vec<&str> = Vec::new();
+        for o in &self.occurrences {
+            if !projects.contains(&o.project.as_str()) {
+                projects.push(&o.project);
+            }
+        }
+        projects.len()
+    }
+}
+
+/// Collect the named entities of `docs` (diplomatic pages in manifest
+/// order). Names tagged with the same `@ref` are one entity; untagged ones
+/// are merged when they match ignoring accents and case. Entries are sorted
+/// by name.
+pub fn build<'a>(
+    docs: impl IntoIterator<Item = (&'a ProjectConfig, u32, &'a TeiDocument)>,
+) -> Vec<Entity> {
+    let mut entities: Vec<Entity> = Vec::new();
+    // Grouping key of each entity, parallel to `entities`
+    let mut keys: Vec<(EntityKind, String)> = Vec::new();
+    for (project, page, doc) in docs {
+        let page_label = project
+            .pages
+            .iter()
+            .find(|p| p.number == page)
+            .map(|p| p.label.clone())
+            .unwrap_or_else(|| page.to_string());
+        for (index, line) in doc.lines.iter().enumerate() {
+            let mut names = Vec::new();
+            collect_names(&line.content, &mut names);
+            for (kind, name, ref_uri) in names {
+                let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+                if name.is_empty() {
+                    continue;
+                }
+                let key = match &ref_uri {
+                    Some(uri) => (kind, uri.clone()),
+                    None => (kind, polytonic::fold(&name)),
+                };
+                let occurrence = Occurrence {
+                    project: project.id.clone(),
+                    project_name: project.name.clone(),
+                    page,
+                    page_label: page_label.clone(),
+                    line: doc.line_label(index).unwrap_or_default(),
+                    text: name.clone(),
+                };
+                match keys.iter().position(|k| *k == key) {
+                    Some(i) => entities[i].occurrences.push(occurrence),
+                    None => {
+                        keys.push(key);
+                        entities.push(Entity {
+                            kind,
+                            name,
+                            ref_uri,
+                            occurrences: vec![occurrence],
+                        });
+                    }
+                }
+            }
+        }
+    }
+    entities.sort_by_cached_key(|e| polytonic::fold(&e.name));
+    entities
+}
+
+fn collect_names(nodes: &[TextNode], out: &mut Vec<(EntityKind, String, Option<String>)>) {
+    for node in nodes {
+        match node {
+            TextNode::PersName { tipo, ref_uri, .. } => {
+                let kind = if EntityKind::is_divine(tipo) {
+                    EntityKind::Divine
+                } else {
+                    EntityKind::Person
+                };
+                let ref_uri = ref_uri.as_ref().map(|r| r.trim().to_string());
+                out.push((kind, node.text(Reading::Normalized), ref_uri));
+            }
+            TextNode::PlaceName { name, ref_uri, .. } => {
+                out.push((EntityKind::Place, name.clone(), ref_uri.clone()));
+            }
+            TextNode::RsType { rs_type, content } if EntityKind::is_divine(rs_type) => {
+                out.push((EntityKind::Divine, content.clone(), None));
+            }
+            TextNode::Hi { content, .. } => collect_names(content, out),
+            _ => {}
+        }
+    }
+}
+
+/// Entities of `kind` whose name, or a spelling on the page, contains
+/// `query`, ignoring accents and case.
+pub fn filter<'a>(entities: &'a [Entity], kind: EntityKind, query: &str) -> Vec<&'a Entity> {
+    let query = query.trim();
+    entities
+        .iter()
+        .filter(|e| e.kind == kind)
+        .filter(|e| {
+            query.is_empty()
+                || polytonic::contains(&e.name, query, true)
+                || e.occurrences
+                    .iter()
+                    .any(|o| polytonic::contains(&o.text, query, true))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::PageInfo;
+
+    fn page(xml_body: &str) -> TeiDocument {
+        let xml = format!(
+            "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\"><text><body>{}</body></text></TEI>",
+            xml_body
+        );
+        crate::tei_parser::parse_tei_xml(&xml).unwrap()
+    }
+
+    fn project(id: &str) -> ProjectConfig {
+        let mut config = ProjectConfig::new(id.to_string(), format!("Proyecto {}", id));
+        config.pages = vec![PageInfo::new(1), PageInfo::new(2)];
+        config.pages[1].label = "2v".to_string();
+        config
+    }
+
+    #[test]
+    fn test_build_groups_by_kind_and_name() {
+        let a = project("A");
+        let b = project("B");
+        let a1 = page(
+            r#"<lb n="1"/><ab><persName type="divine">Ἑρμῆς</persName> καὶ <persName>Μωϋσῆς</persName></ab>
+               <lb n="2"/><ab>ἐν <placeName>Αἰγύπτῳ</placeName> <rs type="divine">Ἑρμῆς</rs></ab>"#,
+        );
+        let b2 = page(
+            r#"<lb n="7"/><ab><persName type="divine">ἙΡΜΗΣ</persName> <rs type="astral">Ἥλιος</rs></ab>"#,
+        );
+        let entities = build([(&a, 1, &a1), (&b, 2, &b2)]);
+
+        let summary: Vec<_> = entities
+            .iter()
+            .map(|e| (e.kind, e.name.as_str(), e.occurrences.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (EntityKind::Place, "Αἰγύπτῳ", 1),
+                (EntityKind::Divine, "Ἑρμῆς", 3),
+                (EntityKind::Person, "Μωϋσῆς", 1),
+            ]
+        );
+        let hermes = &entities[1];
+        assert_eq!(hermes.project_count(), 2);
+        assert_eq!(hermes.occurrences[2].page_label, "2v");
+        assert_eq!(hermes.occurrences[2].line, "7");
+        assert_eq!(hermes.occurrences[2].text, "ἙΡΜΗΣ");
+
+        assert_eq!(filter(&entities, EntityKind::Divine, "ερμ").len(), 1);
+        assert!(filter(&entities, EntityKind::Person, "ερμ").is_empty());
+        assert_eq!(filter(&entities, EntityKind::Place, "").len(), 1);
+    }
+
+    #[test]
+    fn test_build_merges_by_ref() {
+        let a = project("A");
+        let doc = page(
+            r#"<lb/><ab><persName ref="wd:Q5">Ἰάω</persName> <persName ref="wd:Q5">Ιαω</persName> <persName ref="wd:Q6">Ιαω</persName></ab>"#,
+        );
+        let entities = build([(&a, 1, &doc)]);
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0].ref_uri.as_deref(), Some("wd:Q5"));
+        assert_eq!(entities[0].occurrences.len(), 2);
+    }
+}
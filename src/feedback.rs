@@ -0,0 +1,137 @@
+// src/feedback.rs
+// "Reportar un problema": builds a prefilled GitHub issue describing where
+// the reader is in the viewer, so transcription or display errors can be
+// reported without leaving the page. Reports go to the project's
+// `issues_url`, else to the one the viewer was built with; without either
+// there is nowhere to report to.
+use crate::utils::Url;
+
+/// "New issue" page of the viewer's own tracker, from the build environment
+const BUILD_ISSUES_URL: Option<&str> = option_env!("TEI_VIEWER_ISSUES_URL");
+
+/// Where reports are filed: `project`'s tracker, else the viewer's.
+pub fn issues_url(project: Option<&str>) -> Option<&str> {
+    [project, BUILD_ISSUES_URL]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .find(|url| !url.is_empty())
+}
+
+/// Longest line excerpt quoted in a report, in characters
+const MAX_EXCERPT: usize = 200;
+
+pub struct Report<'a> {
+    /// "New issue" page the report is filed at
+    pub issues: &'a str,
+    pub project: &'a str,
+    pub page: u32,
+    /// Link to the exact viewer state (the page URL with its hash)
+    pub link: &'a str,
+    /// Label and text of the locked line, when there is one
+    pub line: Option<(&'a str, &'a str)>,
+    pub comment: &'a str,
+}
+
+impl Report<'_> {
+    pub fn title(&self) -> String {
+        match self.line {
+            Some((label, _)) => format!(
+                "[{}] Problema en la página {}, línea {}",
+                self.project, self.page, label
+            ),
+            None => format!("[{}] Problema en la página {}", self.project, self.page),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        let mut body = format!(
+            "**Proyecto:** {}\n**Página:** {}\n**Enlace:** {}\n",
+            self.project, self.page, self.link
+        );
+        if let Some((label, text)) = self.line {
+            let mut excerpt: String = text.chars().take(MAX_EXCERPT).collect();
+            if excerpt.len() < text.len() {
+                excerpt.push('…');
+            }
+            body.push_str(&format!("**Línea:** {}\n\n> {}\n", label, excerpt));
+        }
+        let comment = self.comment.trim();
+        body.push_str("\n### Descripción\n\n");
+        body.push_str(if comment.is_empty() {
+            "_Describa el problema: qué se ve y qué debería verse._"
+        } else {
+            comment
+        });
+        body.push('\n');
+        body
+    }
+
+    /// The "new issue" URL with title and body filled in.
+    pub fn issue_url(&self) -> String {
+        Url::new(self.issues)
+            .param("title", &self.title())
+            .param("body", &self.body())
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_with_line() {
+        let report = Report {
+            issues: "https://github.com/user/editions/issues/new",
+            project: "PGM-XIII",
+            page: 3,
+            link: "https://example.org/#project=PGM-XIII&page=3",
+            line: Some(("14", "καὶ λέγε")),
+            comment: " Falta una letra ",
+        };
+        assert_eq!(
+            report.title(),
+            "[PGM-XIII] Problema en la página 3, línea 14"
+        );
+        let body = report.body();
+        assert!(body.contains("**Enlace:** https://example.org/#project=PGM-XIII&page=3"));
+        assert!(body.contains("> καὶ λέγε\n"));
+        assert!(body.ends_with("Falta una letra\n"));
+
+        let url = report.issue_url();
+        assert!(url.starts_with("https://github.com/user/editions/issues/new?"));
+        assert!(url.contains("title=%5BPGM-XIII%5D%20Problema"));
+        // The link's own '&' must not split the query
+        assert_eq!(url.matches('&').count(), 1);
+    }
+
+    #[test]
+    fn test_report_without_line() {
+        let long = "α".repeat(300);
+        let report = Report {
+            issues: "https://example.org/issues/new",
+            project: "P",
+            page: 1,
+            link: "",
+            line: None,
+            comment: "",
+        };
+        assert_eq!(report.title(), "[P] Problema en la página 1");
+        assert!(report.body().contains("_Describa el problema"));
+
+        let report = Report {
+            line: Some(("2", &long)),
+            ..report
+        };
+        assert!(report.body().contains(&format!("{}…", "α".repeat(200))));
+    }
+
+    #[test]
+    fn test_issues_url() {
+        let project = "https://github.com/user/editions/issues/new";
+        assert_eq!(issues_url(Some(project)), Some(project));
+        // A blank one is as good as none
+        assert_eq!(issues_url(Some("  ")), issues_url(None));
+    }
+}
@@ -0,0 +1,115 @@
+// src/gesture.rs
+// Pointer bookkeeping for dragging and pinch-zooming an image with a mouse,
+// a pen or fingers (pointer events), shared by the facsimile and the
+// gallery: one pointer down pans, two pinch.
+
+/// Client coordinates of a pointer
+pub type Point = (i32, i32);
+
+/// What moving a pointer amounts to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gesture {
+    /// The only pointer down, now at this point
+    Pan(Point),
+    /// Two pointers moved apart (`factor` > 1) or together, about `center`
+    Pinch { factor: f32, center: (f32, f32) },
+}
+
+/// Pointers pressed on an image
+#[derive(Debug, Default)]
+pub struct Pointers {
+    down: Vec<(i32, Point)>,
+    /// Distance between the two pointers of a pinch, 0 without one
+    distance: f64,
+}
+
+impl Pointers {
+    pub fn is_empty(&self) -> bool {
+        self.down.is_empty()
+    }
+
+    fn spread(&self) -> f64 {
+        match self.down.as_slice() {
+            [(_, a), (_, b)] => f64::from(a.0 - b.0).hypot(f64::from(a.1 - b.1)),
+            _ => 0.0,
+        }
+    }
+
+    /// Pointer `id` pressed at `at`; the point to pan from if it is the
+    /// only one down.
+    pub fn press(&mut self, id: i32, at: Point) -> Option<Point> {
+        self.down.push((id, at));
+        self.distance = self.spread();
+        (self.down.len() == 1).then_some(at)
+    }
+
+    /// Pointer `id` moved to `at`. Pointers not pressed (a mouse hovering)
+    /// do nothing.
+    pub fn moved(&mut self, id: i32, at: Point) -> Option<Gesture> {
+        let pointer = self.down.iter_mut().find(|(p, _)| *p == id)?;
+        pointer.1 = at;
+        match self.down.as_slice() {
+            [_] => Some(Gesture::Pan(at)),
+            [(_, a), (_, b)] => {
+                let center = ((a.0 + b.0) as f32 / 2.0, (a.1 + b.1) as f32 / 2.0);
+                let distance = self.spread();
+                let previous = std::mem::replace(&mut self.distance, distance);
+                (previous > 0.0).then(|| Gesture::Pinch {
+                    factor: (distance / previous) as f32,
+                    center,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Pointer `id` lifted, cancelled or gone; the point of the one left
+    /// down, which pans from there on.
+    pub fn release(&mut self, id: i32) -> Option<Point> {
+        self.down.retain(|(p, _)| *p != id);
+        self.distance = self.spread();
+        match self.down.as_slice() {
+            [(_, at)] => Some(*at),
+            _ => None,
+        }
+    }
+}
+
+/// Offset of an image scaled by `change` about `center`, so that the point
+/// under `center` stays there.
+pub fn scaled_about(offset: f32, center: f32, change: f32) -> f32 {
+    center + (offset - center) * change
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pointers() {
+        let mut pointers = Pointers::default();
+        assert_eq!(pointers.moved(1, (5, 5)), None);
+        assert_eq!(pointers.press(1, (0, 0)), Some((0, 0)));
+        assert_eq!(pointers.moved(1, (10, 0)), Some(Gesture::Pan((10, 0))));
+        // A second finger starts a pinch
+        assert_eq!(pointers.press(2, (10, 100)), None);
+        assert_eq!(
+            pointers.moved(2, (10, 200)),
+            Some(Gesture::Pinch {
+                factor: 2.0,
+                center: (10.0, 100.0)
+            })
+        );
+        // Lifting one pans from the other
+        assert_eq!(pointers.release(2), Some((10, 0)));
+        assert_eq!(pointers.moved(1, (20, 0)), Some(Gesture::Pan((20, 0))));
+        assert_eq!(pointers.release(1), None);
+        assert!(pointers.is_empty());
+    }
+
+    #[test]
+    fn test_scaled_about() {
+        assert_eq!(scaled_about(0.0, 100.0, 2.0), -100.0);
+        assert_eq!(scaled_about(40.0, 40.0, 3.0), 40.0);
+    }
+}
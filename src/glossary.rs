@@ -0,0 +1,277 @@
+// src/glossary.rs
+// Glossary of editorial terms: entries from `<list type="gloss">` (in a
+// page's TEI or the project's `glossary.xml`) and the `<term>` occurrences
+// in the transcription that point at them.
+use crate::polytonic;
+use crate::tei_data::{TeiDocument, TextNode};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GlossEntry {
+    /// `@xml:id` of the `<label>` or its `<term>`, the target of `<term ref>`
+    pub id: Option<String>,
+    pub term: String,
+    pub gloss: String,
+}
+
+/// A `<term>` in the transcription resolved to a glossary entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Occurrence {
+    pub page: u32,
+    /// Line index in the page's diplomatic edition
+    pub line: usize,
+    /// The term as written on the line
+    pub text: String,
+}
+
+/// Read the entries of every `<list type="gloss">` in `xml`: each
+/// `<label>` (or `<term>`) names a term and the following `<item>` (or
+/// `<gloss>`) defines it. Malformed XML yields the entries read so far.
+pub fn parse_entries(xml: &str) -> Vec<GlossEntry> {
+    let mut reader = Reader::from_str(xml);
+    let mut buf = Vec::new();
+    let mut entries = Vec::new();
+    let mut list_depth = 0usize;
+    // Element currently collecting text: "term" or "gloss"
+    let mut collecting: Option<&str> = None;
+    let mut text = String::new();
+    let mut pending: Option<(Option<String>, String)> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(ref e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if name == "list" {
+                    let is_gloss = e
+                        .attributes()
+                        .flatten()
+                        .any(|a| a.key.as_ref() == b"type" && a.value.as_ref() == b"gloss");
+                    if list_depth > 0 || is_gloss {
+                        list_depth += 1;
+                    }
+                    continue;
+                }
+                if list_depth == 0 {
+                    continue;
+                }
+                let id = e
+                    .attributes()
+                    .flatten()
+                    .find(|a| a.key.as_ref() == b"xml:id")
+                    .map(|a| String::from_utf8_lossy(&a.value).to_string());
+                match (collecting, name.as_str()) {
+                    // <term> inside <label>: only its id matters
+                    (Some("term"), "term") => {
+                        if let Some((slot @ None, _)) = pending.as_mut() {
+                            *slot = id;
+                        }
+                    }
+                    (Some(_), _) => {}
+                    (None, "label" | "term") => {
+                        pending = Some((id, String::new()));
+                        collecting = Some("term");
+                        text.clear();
+                    }
+                    (None, "item" | "gloss") if pending.is_some() => {
+                        collecting = Some("gloss");
+                        text.clear();
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Text(e)) if collecting.is_some() => {
+                text.push_str(&e.unescape().unwrap_or_default());
+            }
+            Ok(Event::End(ref e)) => {
+                let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                if name == "list" && list_depth > 0 {
+                    list_depth -= 1;
+                    continue;
+                }
+                match (collecting, name.as_str()) {
+                    (Some("term"), "label" | "term") => {
+                        if let Some((_, term)) = pending.as_mut() {
+                            *term = collapse(&text);
+                        }
+                        collecting = None;
+                    }
+                    (Some("gloss"), "item" | "gloss") => {
+                        if let Some((id, term)) = pending.take() {
+                            if !term.is_empty() {
+                                entries.push(GlossEntry {
+                                    id,
+                                    term,
+                                    gloss: collapse(&text),
+                                });
+                            }
+                        }
+                        collecting = None;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) | Err(_) => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+    entries
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Add `extra` entries not already present (same id, or same term when
+/// neither has an id). Project glossaries are merged into page ones.
+pub fn merge(entries: &mut Vec<GlossEntry>, extra: &[GlossEntry]) {
+    for entry in extra {
+        let known = entries.iter().any(|e| match (&e.id, &entry.id) {
+            (Some(a), Some(b)) => a == b,
+            _ => polytonic::fold(&e.term) == polytonic::fold(&entry.term),
+        });
+        if !known {
+            entries.push(entry.clone());
+        }
+    }
+}
+
+/// Entry a `<term>` refers to: by its `@ref`/`@key` against entry ids
+/// and terms, or else by its own text, ignoring accents and case.
+pub fn lookup(entries: &[GlossEntry], key: Option<&str>, content: &str) -> Option<usize> {
+    if let Some(key) = key {
+        if let Some(index) = entries.iter().position(|e| e.id.as_deref() == Some(key)) {
+            return Some(index);
+        }
+    }
+    let folded = polytonic::fold(key.unwrap_or(content).trim());
+    entries
+        .iter()
+        .position(|e| polytonic::fold(&e.term) == folded)
+}
+
+/// Every `<term>` across `docs` that resolves to `entries[index]`, in
+/// page and line order.
+pub fn occurrences<'a>(
+    docs: impl IntoIterator<Item = (u32, &'a TeiDocument)>,
+    entries: &[GlossEntry],
+    index: usize,
+) -> Vec<Occurrence> {
+    let mut found = Vec::new();
+    for (page, doc) in docs {
+        for (line, l) in doc.lines.iter().enumerate() {
+            let mut terms = Vec::new();
+            collect_terms(&l.content, &mut terms);
+            for (key, content) in terms {
+                if lookup(entries, key, content) == Some(index) {
+                    found.push(Occurrence {
+                        page,
+                        line,
+                        text: content.to_string(),
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+fn collect_terms<'a>(nodes: &'a [TextNode], out: &mut Vec<(Option<&'a str>, &'a str)>) {
+    for node in nodes {
+        match node {
+            TextNode::Term { key, content } => out.push((key.as_deref(), content)),
+            TextNode::PersName { content, .. } | TextNode::Hi { content, .. } => {
+                collect_terms(content, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLOSSARY: &str = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><back>
+        <list type="gloss">
+            <label xml:id="g-diple">διπλῆ</label>
+            <item>Signo crítico en forma de
+                ángulo (&gt;) en el margen.</item>
+            <label><term xml:id="g-koronis">κορωνίς</term></label>
+            <item><gloss>Signo que marca el final de una sección.</gloss></item>
+        </list>
+        <list><label>ignorada</label><item>fuera del glosario</item></list>
+    </back></text></TEI>"#;
+
+    #[test]
+    fn test_parse_entries() {
+        let entries = parse_entries(GLOSSARY);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id.as_deref(), Some("g-diple"));
+        assert_eq!(entries[0].term, "διπλῆ");
+        assert_eq!(
+            entries[0].gloss,
+            "Signo crítico en forma de ángulo (>) en el margen."
+        );
+        assert_eq!(entries[1].id.as_deref(), Some("g-koronis"));
+        assert_eq!(entries[1].term, "κορωνίς");
+        assert_eq!(entries[1].gloss, "Signo que marca el final de una sección.");
+    }
+
+    #[test]
+    fn test_lookup_and_merge() {
+        let mut entries = parse_entries(GLOSSARY);
+        assert_eq!(lookup(&entries, Some("g-diple"), "x"), Some(0));
+        // Text match ignores accents and case
+        assert_eq!(lookup(&entries, None, "Κορωνις"), Some(1));
+        assert_eq!(lookup(&entries, Some("κορωνίς"), "κορωνίδα"), Some(1));
+        assert_eq!(lookup(&entries, None, "ἄλλο"), None);
+
+        merge(
+            &mut entries,
+            &[
+                GlossEntry {
+                    id: None,
+                    term: "ΚΟΡΩΝΙΣ".to_string(),
+                    gloss: "dup".to_string(),
+                },
+                GlossEntry {
+                    id: Some("g-obelos".to_string()),
+                    term: "ὀβελός".to_string(),
+                    gloss: "Signo de atétesis.".to_string(),
+                },
+            ],
+        );
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].term, "ὀβελός");
+    }
+
+    #[test]
+    fn test_occurrences() {
+        let xml = r##"<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><body>
+            <lb n="1"/><ab>α <term ref="#g-diple">διπλῆς</term></ab>
+            <lb n="2"/><ab><hi rend="bold"><term>κορωνὶς</term></hi> <term key="g-diple">διπλῇ</term></ab>
+        </body></text></TEI>"##;
+        let doc = crate::tei_parser::parse_tei_xml(xml).unwrap();
+        let entries = parse_entries(GLOSSARY);
+        let found = occurrences([(4, &doc)], &entries, 0);
+        assert_eq!(
+            found,
+            vec![
+                Occurrence {
+                    page: 4,
+                    line: 0,
+                    text: "διπλῆς".to_string()
+                },
+                Occurrence {
+                    page: 4,
+                    line: 1,
+                    text: "διπλῇ".to_string()
+                },
+            ]
+        );
+        assert_eq!(occurrences([(4, &doc)], &entries, 1).len(), 1);
+    }
+}
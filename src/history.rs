@@ -0,0 +1,104 @@
+// src/history.rs
+// Recently viewed pages across projects, newest first, kept in localStorage
+// so readers can go back to where they left off.
+use crate::i18n::Lang;
+use crate::persistence;
+use serde::{Deserialize, Serialize};
+
+/// Number of visits remembered
+pub const MAX_VISITS: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Visit {
+    pub project: String,
+    pub page: u32,
+    pub page_label: String,
+    /// Time of the visit, in milliseconds since the epoch
+    pub visited: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub visits: Vec<Visit>,
+}
+
+impl History {
+    pub fn load() -> Self {
+        persistence::load(persistence::HISTORY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        persistence::save(persistence::HISTORY, self);
+    }
+
+    /// Whether `project`/`page` is already the latest visit.
+    pub fn is_latest(&self, project: &str, page: u32) -> bool {
+        self.visits
+            .first()
+            .is_some_and(|v| v.project == project && v.page == page)
+    }
+
+    /// Put a visit first, dropping an earlier visit to the same page and
+    /// the oldest ones beyond `MAX_VISITS`.
+    pub fn record(&mut self, visit: Visit) {
+        self.visits
+            .retain(|v| !(v.project == visit.project && v.page == visit.page));
+        self.visits.insert(0, visit);
+        self.visits.truncate(MAX_VISITS);
+    }
+}
+
+/// Relative time of `then`, seen at `now` (both in ms).
+pub fn time_ago(lang: Lang, now: f64, then: f64) -> String {
+    let minutes = ((now - then).max(0.0) / 60_000.0) as u64;
+    match minutes {
+        0 => lang.t("ahora").to_string(),
+        1..=59 => lang.tf("hace {} min", &[&minutes]),
+        60..=1439 => lang.tf("hace {} h", &[&(minutes / 60)]),
+        1440..=2879 => lang.t("ayer").to_string(),
+        _ => lang.tf("hace {} días", &[&(minutes / 1440)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visit(project: &str, page: u32) -> Visit {
+        Visit {
+            project: project.to_string(),
+            page,
+            page_label: page.to_string(),
+            visited: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut history = History::default();
+        history.record(visit("A", 1));
+        history.record(visit("A", 2));
+        history.record(visit("A", 1));
+        assert_eq!(history.visits.len(), 2);
+        assert!(history.is_latest("A", 1));
+        assert!(!history.is_latest("A", 2));
+
+        for page in 10..40 {
+            history.record(visit("B", page));
+        }
+        assert_eq!(history.visits.len(), MAX_VISITS);
+        assert!(history.is_latest("B", 39));
+    }
+
+    #[test]
+    fn test_time_ago() {
+        let minute = 60_000.0;
+        let es = Lang::Es;
+        assert_eq!(time_ago(es, 30_000.0, 0.0), "ahora");
+        assert_eq!(time_ago(es, 5.0 * minute, 0.0), "hace 5 min");
+        assert_eq!(time_ago(es, 150.0 * minute, 0.0), "hace 2 h");
+        assert_eq!(time_ago(es, 30.0 * 60.0 * minute, 0.0), "ayer");
+        assert_eq!(time_ago(es, 4.0 * 1440.0 * minute, 0.0), "hace 4 días");
+        assert_eq!(time_ago(Lang::En, 4.0 * 1440.0 * minute, 0.0), "4 days ago");
+    }
+}
@@ -0,0 +1,655 @@
+// src/i18n.rs
+// Interface language. Strings are written in Spanish in the components and
+// looked up, gettext-style, in the catalog of the selected language; text
+// missing from a catalog is shown in Spanish.
+use crate::persistence;
+use crate::utils::window;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Lang {
+    #[default]
+    Es,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Es, Lang::En];
+
+    /// BCP 47 tag, used for `<html lang>` and locale-aware formatting
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lang::Es => "es",
+            Lang::En => "en",
+        }
+    }
+
+    /// Name of the language in itself, for the selector
+    pub fn name(self) -> &'static str {
+        match self {
+            Lang::Es => "Español",
+            Lang::En => "English",
+        }
+    }
+
+    /// Language of a tag such as "en", "en-GB" or "es-419".
+    pub fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim().to_lowercase();
+        match primary.as_str() {
+            "es" => Some(Lang::Es),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// Language picked by the reader in a previous session, if any
+    pub fn stored() -> Option<Self> {
+        persistence::load::<String>(persistence::LANG).and_then(|l| Self::parse(&l))
+    }
+
+    /// First browser language with a catalog; Spanish when none has one
+    pub fn preferred() -> Self {
+        let Some(navigator) = window().map(|w| w.navigator()) else {
+            return Lang::Es;
+        };
+        navigator
+            .languages()
+            .iter()
+            .filter_map(|l| l.as_string())
+            .chain(navigator.language())
+            .find_map(|tag| Self::parse(&tag))
+            .unwrap_or(Lang::Es)
+    }
+
+    /// Stored choice, falling back to the browser languages
+    pub fn current() -> Self {
+        Self::stored().unwrap_or_else(Self::preferred)
+    }
+
+    /// Mark the document with the language, for screen readers and
+    /// hyphenation.
+    pub fn apply(self) {
+        if let Some(root) = window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.document_element())
+        {
+            let _ = root.set_attribute("lang", self.as_str());
+        }
+    }
+
+    /// Apply the language and remember it across sessions.
+    pub fn select(self) {
+        self.apply();
+        persistence::save(persistence::LANG, self.as_str());
+    }
+
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Lang::Es => &[],
+            Lang::En => EN,
+        }
+    }
+
+    /// Translation of an interface string written in Spanish.
+    pub fn t(self, es: &str) -> &str {
+        self.catalog()
+            .iter()
+            .find(|(key, _)| *key == es)
+            .map_or(es, |(_, text)| *text)
+    }
+
+    /// Translation of a message with `{}` placeholders, filled in order.
+    pub fn tf(self, es: &str, args: &[&dyn Display]) -> String {
+        let mut out = String::new();
+        let mut args = args.iter();
+        let mut parts = self.t(es).split("{}").peekable();
+        while let Some(part) = parts.next() {
+            out.push_str(part);
+            if parts.peek().is_some() {
+                if let Some(arg) = args.next() {
+                    out.push_str(&arg.to_string());
+                }
+            }
+        }
+        out
+    }
+
+    /// A count with the thousands separator of the language: "12.345",
+    /// "12,345". Spanish leaves four-digit numbers ungrouped.
+    pub fn number(self, n: usize) -> String {
+        let digits = n.to_string();
+        let separator = match self {
+            Lang::Es if digits.len() <= 4 => return digits,
+            Lang::Es => '.',
+            Lang::En => ',',
+        };
+        let mut out = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(separator);
+            }
+            out.push(c);
+        }
+        out
+    }
+
+    /// Date and time of a timestamp (ms since the epoch) as written in the
+    /// language, e.g. "3/10/2026, 14:05:00".
+    pub fn date(self, ms: f64) -> String {
+        js_sys::Date::new(&ms.into())
+            .to_locale_string(self.as_str(), &wasm_bindgen::JsValue::UNDEFINED)
+            .into()
+    }
+}
+
+/// English catalog: (Spanish source, English)
+static EN: &[(&str, &str)] = &[
+    ("Repositorio DTS", "DTS repository"),
+    ("Cerrar", "Close"),
+    ("Colecciones abiertas", "Open collections"),
+    ("Inicio", "Home"),
+    ("Cargando…", "Loading…"),
+    ("No se pudo leer el repositorio: {}", "Could not read the repository: {}"),
+    ("La colección está vacía", "The collection is empty"),
+    ("Abrir la colección", "Open the collection"),
+    ("Leer el texto", "Read the text"),
+    ("Índices de la colección", "Collection indices"),
+    ("Filtrar nombres...", "Filter names..."),
+    ("Filtrar nombres", "Filter names"),
+    ("Leyendo las páginas de la colección... faltan {}", "Reading the collection's pages... {} left"),
+    ("No hay nombres etiquetados de este tipo (<persName>, <placeName>, <rs type=\"divine\">).", "There are no tagged names of this kind (<persName>, <placeName>, <rs type=\"divine\">)."),
+    ("{} páginas no se pudieron cargar.", "{} pages could not be loaded."),
+    ("1 mención", "1 mention"),
+    ("{} menciones", "{} mentions"),
+    ("{} en {} proyectos", "{} in {} projects"),
+    ("Buscar en todos los proyectos", "Search every project"),
+    ("🔎 Buscar en la colección", "🔎 Search the collection"),
+    ("Buscar una fórmula en todos los proyectos...", "Search a formula in every project..."),
+    ("También ignora mayúsculas y sigma final/lunada", "Also ignores case and final/lunate sigma"),
+    ("Ignorar acentos/espíritus", "Ignore accents/breathings"),
+    ("Indexando páginas... faltan {}", "Indexing pages... {} left"),
+    ("Sin coincidencias.", "No matches."),
+    ("Primeras {} coincidencias", "First {} matches"),
+    ("{} coincidencias", "{} matches"),
+    ("Página {}", "Page {}"),
+    ("Traducción", "Translation"),
+    ("Trad.", "Transl."),
+    ("Glosario", "Glossary"),
+    ("«{}» no tiene entrada en el glosario.", "“{}” has no glossary entry."),
+    ("Apariciones ({})", "Occurrences ({})"),
+    ("Buscando en las páginas del proyecto...", "Searching the project's pages..."),
+    ("Este proyecto no define un glosario.", "This project defines no glossary."),
+    ("Ayuda", "Help"),
+    ("Atajos de teclado", "Keyboard shortcuts"),
+    ("Ratón y pantalla táctil", "Mouse and touch screen"),
+    ("Signos editoriales (sistema de Leiden)", "Editorial symbols (Leiden system)"),
+    ("Los colores de las marcas del texto se explican en la leyenda (🎨).", "The colors of the text markup are explained in the legend (🎨)."),
+    ("Ver de nuevo la visita de bienvenida", "Replay the welcome tour"),
+    ("Galería", "Gallery"),
+    ("Imagen anterior (←)", "Previous image (←)"),
+    ("Imagen anterior", "Previous image"),
+    ("{} · {} de {}", "{} · {} of {}"),
+    ("Sin imagen", "No image"),
+    ("Imagen siguiente (→)", "Next image (→)"),
+    ("Imagen siguiente", "Next image"),
+    ("Restablecer el zoom (0)", "Reset the zoom (0)"),
+    ("Ajustar", "Fit"),
+    ("Orientación", "Orientation"),
+    ("Girar 90° a la izquierda (Mayús + R)", "Rotate 90° left (Shift + R)"),
+    ("Girar 90° a la izquierda", "Rotate 90° left"),
+    ("Girar 90° a la derecha (R)", "Rotate 90° right (R)"),
+    ("Girar 90° a la derecha", "Rotate 90° right"),
+    ("Voltear horizontalmente (H)", "Flip horizontally (H)"),
+    ("Voltear horizontalmente", "Flip horizontally"),
+    ("Voltear verticalmente (V)", "Flip vertically (V)"),
+    ("Voltear verticalmente", "Flip vertically"),
+    ("Leer esta página (Intro)", "Read this page (Enter)"),
+    ("Abrir la transcripción", "Open the transcription"),
+    ("Salir de la galería (Esc)", "Leave the gallery (Esc)"),
+    ("Esta página no tiene imagen.", "This page has no image."),
+    ("Filtrar pasajes...", "Filter passages..."),
+    ("Filtrar pasajes citados", "Filter cited passages"),
+    ("El proyecto no contiene referencias (<ref>).", "The project contains no references (<ref>)."),
+    ("Tipo: {}", "Type: {}"),
+    ("Línea {}", "Line {}"),
+    ("Nota", "Note"),
+    ("[Abreviatura] Original: {}", "[Abbreviation] Original: {}"),
+    ("[Corrección] Original: {}", "[Correction] Original: {}"),
+    ("[Abreviatura] {}", "[Abbreviation] {}"),
+    ("[Corrección] Lectura: {}", "[Correction] Reading: {}"),
+    ("[Regularización] Regularizado: {}", "[Regularization] Regularized: {}"),
+    ("[Número] Valor: {} | Tipo: {}", "[Number] Value: {} | Type: {}"),
+    ("[Persona] Tipo: {}", "[Person] Type: {}"),
+    ("[Persona]", "[Person]"),
+    ("Nombre: {}", "First name: {}"),
+    ("Continúa", "Continued"),
+    ("[Abreviatura] {}: {}", "[Abbreviation] {}: {}"),
+    ("[Lugar]: {}", "[Place]: {}"),
+    ("[Referencia] Tipo: {} | Destino: {}", "[Reference] Type: {} | Target: {}"),
+    ("[Incierto] Razón: {}", "[Unclear] Reason: {}"),
+    ("[Cadena de Referencia] Tipo: {}", "[Referencing string] Type: {}"),
+    ("[Glosario] Ver la definición", "[Glossary] See the definition"),
+    ("[Nota al pie]", "[Footnote]"),
+    ("[Nota al pie] {}", "[Footnote] {}"),
+    ("[Resaltado] Estilo: {}", "[Highlight] Style: {}"),
+    ("[Regularización] Original: {}", "[Regularization] Original: {}"),
+    ("[Fecha] {}", "[Date] {}"),
+    ("[Fecha] {} – {}", "[Date] {} – {}"),
+    ("[Fecha] Desde {}", "[Date] From {}"),
+    ("[Fecha] Hasta {}", "[Date] Until {}"),
+    ("[Fecha]", "[Date]"),
+    ("Editar nota", "Edit note"),
+    ("Nueva nota · línea {}", "New note · line {}"),
+    ("Mis notas", "My notes"),
+    ("Color", "Colour"),
+    ("Guardar", "Save"),
+    ("Cancelar", "Cancel"),
+    ("Haga clic en una línea (o seleccione un pasaje) para anotarla.", "Click a line (or select a passage) to annotate it."),
+    ("Ir a la línea", "Go to the line"),
+    ("Editar", "Edit"),
+    ("Eliminar", "Delete"),
+    ("No hay notas en esta página.", "There are no notes on this page."),
+    ("Descargar todas las notas del proyecto", "Download every note of the project"),
+    ("⬇ Exportar", "⬇ Export"),
+    ("Descargar las notas y la selección como W3C Web Annotation (Recogito, Mirador…)", "Download the notes and the selection as W3C Web Annotation (Recogito, Mirador…)"),
+    ("Cargar notas exportadas (JSON)", "Load exported notes (JSON)"),
+    ("⬆ Importar", "⬆ Import"),
+    ("{} de {}", "{} of {}"),
+    ("Saltar", "Skip"),
+    ("← Anterior", "← Previous"),
+    ("Empezar", "Get started"),
+    ("Siguiente →", "Next →"),
+    ("Todas las páginas ({})", "All pages ({})"),
+    ("Edición diplomática", "Diplomatic edition"),
+    ("Imagen", "Image"),
+    ("Rendimiento", "Performance"),
+    ("Caché: {} de {}", "Cache: {} of {}"),
+    ("Recurso", "Resource"),
+    ("Descarga", "Fetch"),
+    ("Origen", "Source"),
+    ("Análisis", "Parse"),
+    ("Renderizado", "Render"),
+    ("Mapa de lugares", "Place map"),
+    ("Ningún <placeName> tiene coordenadas: añada <geo> o un places.json al proyecto.", "No <placeName> has coordinates: add <geo> or a places.json to the project."),
+    ("{} lugares sin coordenadas", "{} places without coordinates"),
+    ("Mapa de los lugares del proyecto", "Map of the project's places"),
+    ("Ajustes", "Settings"),
+    ("Apariencia", "Appearance"),
+    ("Tema", "Theme"),
+    ("Tipografía", "Typeface"),
+    ("Tamaño del texto", "Text size"),
+    ("Color de resaltado", "Highlight color"),
+    ("Texto", "Text"),
+    ("Mostrar las abreviaturas desarrolladas (<expan>)", "Show abbreviations expanded (<expan>)"),
+    ("Mostrar las correcciones (<corr>) en lugar de los errores (<sic>)", "Show corrections (<corr>) instead of errors (<sic>)"),
+    ("Numerar solo cada 5 líneas, como en las ediciones impresas", "Number only every 5th line, as in printed editions"),
+    ("Red", "Network"),
+    ("Cargar por adelantado la imagen de la página siguiente", "Fetch the next page's image ahead of time"),
+    ("Disposición", "Layout"),
+    ("Disposición de los paneles", "Panel layout"),
+    ("Idioma de la interfaz", "Interface language"),
+    ("{} notas importadas", "{} notes imported"),
+    ("No se pudieron importar las notas: {}", "Could not import the notes: {}"),
+    ("Idioma de la traducción", "Language of the translation"),
+    ("Lectura en voz alta de la página", "Read the page aloud"),
+    ("★ Línea {}", "★ Line {}"),
+    ("☆ Marcar línea {}", "☆ Bookmark line {}"),
+    ("★ Página marcada", "★ Page bookmarked"),
+    ("☆ Marcar página", "☆ Bookmark page"),
+    ("Añadir a (o quitar de) sus marcadores", "Add to (or remove from) your bookmarks"),
+    ("Fuente TEI · {}", "TEI source · {}"),
+    ("Código TEI de la página", "TEI code of the page"),
+    ("✓ El documento se analiza sin errores", "✓ The document parses without errors"),
+    ("Descargar el XML editado", "Download the edited XML"),
+    ("⬇ Descargar XML", "⬇ Download XML"),
+    ("Ambas", "Both"),
+    ("Diferencias", "Differences"),
+    ("Diplomática frente a normalizada", "Diplomatic against normalized"),
+    ("Mostrar una barra de escala sobre el facsímil", "Show a scale bar over the facsimile"),
+    ("📐 Escala", "📐 Scale"),
+    ("Medir distancias sobre el facsímil", "Measure distances on the facsimile"),
+    ("📏 Regla", "📏 Ruler"),
+    ("Haga clic en dos puntos", "Click two points"),
+    ("Encadenar las páginas del proyecto", "Chain the pages of the project"),
+    ("Scroll continuo", "Continuous scroll"),
+    ("Abrir el texto en otra ventana, que sigue a esta página y línea", "Open the text in another window that follows this page and line"),
+    ("⧉ Otra ventana", "⧉ New window"),
+    ("Comentario", "Commentary"),
+    ("Cargando el comentario…", "Loading the commentary…"),
+    ("Notas personales, guardadas en este navegador", "Personal notes, saved in this browser"),
+    ("📝 Notas", "📝 Notes"),
+    ("Secciones del proyecto y dónde empiezan", "Sections of the project and where they begin"),
+    ("☰ Índice", "☰ Contents"),
+    ("Términos del glosario y sus apariciones en el proyecto", "Glossary terms and where they occur in the project"),
+    ("📖 Glosario", "📖 Glossary"),
+    ("Pasajes citados en el proyecto y las líneas que los citan", "Passages cited in the project and the lines citing them"),
+    ("Mapa de los lugares con coordenadas y las líneas que los nombran", "Map of the places with coordinates and the lines naming them"),
+    ("🗺 Mapa", "🗺 Map"),
+    ("Datación, procedencia y fechas mencionadas en el texto", "Dating, provenance and dates mentioned in the text"),
+    ("⏳ Cronología", "⏳ Timeline"),
+    ("Frecuencia de las palabras griegas de la página o del proyecto", "Frequency of the Greek words of the page or the project"),
+    ("Frecuencias", "Frequencies"),
+    ("Editar el TEI de la página con vista previa inmediata", "Edit the TEI of the page with a live preview"),
+    ("Buscar en el texto...", "Search the text..."),
+    ("Buscar en el texto", "Search the text"),
+    ("Ir a línea", "Go to line"),
+    ("Ir a línea (número de la fuente o posición)", "Go to line (source number or position)"),
+    ("Línea no encontrada", "Line not found"),
+    ("Número de línea de la fuente (@n) o posición", "Source line number (@n) or position"),
+    ("Ir", "Go"),
+    ("Mostrar u ocultar los metadatos", "Show or hide the metadata"),
+    ("Ocultar metadata", "Hide metadata"),
+    ("Mostrar metadata", "Show metadata"),
+    ("Abrir un issue en GitHub sobre esta página", "Open a GitHub issue about this page"),
+    ("⚑ Reportar un problema", "⚑ Report a problem"),
+    ("Mostrar u ocultar la leyenda de colores", "Show or hide the color legend"),
+    ("🎨 Ocultar leyenda", "🎨 Hide legend"),
+    ("🎨 Mostrar leyenda", "🎨 Show legend"),
+    ("Sombrear cada zona según la cantidad de lecturas dudosas, correcciones y notas de su línea", "Shade each zone by the number of doubtful readings, corrections and notes in its line"),
+    ("🔥 Densidad", "🔥 Density"),
+    ("Dibujar y ajustar las zonas del facsímil y exportar el <facsimile> resultante", "Draw and adjust the zones of the facsimile and export the resulting <facsimile>"),
+    ("✏️ Zonas", "✏️ Zones"),
+    ("Esta página no tiene imagen", "This page has no image"),
+    ("Cargando la imagen…", "Loading the image…"),
+    ("Imagen no disponible: suelte la imagen del facsímil junto con el XML", "Image unavailable: drop the facsimile image together with the XML"),
+    ("Imagen no disponible", "No image available"),
+    ("Localizando la imagen…", "Finding the image…"),
+    ("Facsímil", "Facsimile"),
+    ("Imágenes de la página", "Page images"),
+    ("Sin las zonas de las líneas", "Without the line zones"),
+    ("{} intervenciones editoriales", "{} editorial interventions"),
+    ("Alto del panel de imagen", "Height of the image panel"),
+    ("Ancho del panel de imagen", "Width of the image panel"),
+    ("Arrastrar o usar ←/→ para redimensionar; doble clic para restablecer", "Drag or use ←/→ to resize; double-click to reset"),
+    ("Cargando la edición diplomática…", "Loading the diplomatic edition…"),
+    ("Cargando la traducción…", "Loading the translation…"),
+    ("Esta edición no está disponible para esta página", "This edition is not available for this page"),
+    ("Cargando {}…", "Loading {}…"),
+    ("Edición diplomática — scroll continuo", "Diplomatic edition — continuous scroll"),
+    ("Cargando la página siguiente...", "Loading the next page..."),
+    ("Esta edición → {}", "This edition → {}"),
+    ("{} no tiene una página con la misma etiqueta.", "{} has no page with the same label."),
+    ("Cargando...", "Loading..."),
+    ("Diplomática → Normalizada", "Diplomatic → Normalized"),
+    ("{} de {} líneas con cambios", "{} of {} lines changed"),
+    ("Abreviatura", "Abbreviation"),
+    ("Corrección", "Correction"),
+    ("Regularización", "Regularization"),
+    ("Número", "Number"),
+    ("Persona", "Person"),
+    ("Lu", "Pl"),
+    ("Lugar", "Place"),
+    ("Referencia", "Reference"),
+    ("Texto incierto", "Uncertain text"),
+    ("Entidad divina", "Divine entity"),
+    ("Entidad astral", "Astral entity"),
+    ("Nota al pie", "Footnote"),
+    ("N", "B"),
+    ("Negrita", "Bold"),
+    ("C", "I"),
+    ("Cursiva", "Italic"),
+    ("Superíndice", "Superscript"),
+    ("Subíndice", "Subscript"),
+    ("Leyenda de Colores", "Color Legend"),
+    ("Notas", "Notes"),
+    ("Bibliografía de esta página", "Bibliography of this page"),
+    ("Metadatos", "Metadata"),
+    ("Diplomática", "Diplomatic"),
+    ("No hay metadatos disponibles para la edición seleccionada.", "No metadata available for the selected edition."),
+    ("Licencia:", "License:"),
+    ("Cómo citar", "How to cite"),
+    ("Título:", "Title:"),
+    ("Autor:", "Author:"),
+    ("Tipo de Edición:", "Edition Type:"),
+    ("Idioma:", "Language:"),
+    ("País:", "Country:"),
+    ("Ciudad:", "City:"),
+    ("Institución:", "Institution:"),
+    ("Colección:", "Collection:"),
+    ("Sigla:", "Siglum:"),
+    ("Información de Imagen", "Image Information"),
+    ("ID de Superficie:", "Surface ID:"),
+    ("Archivo de Imagen:", "Image File:"),
+    ("Dimensiones Declaradas:", "Declared Dimensions:"),
+    ("{} × {} píxeles", "{} × {} pixels"),
+    ("Dimensiones Intrínsecas (cargadas):", "Intrinsic Dimensions (loaded):"),
+    ("Zonas:", "Zones:"),
+    ("{} zonas", "{} zones"),
+    ("Líneas:", "Lines:"),
+    ("{} líneas", "{} lines"),
+    ("Sin comentario", "No commentary"),
+    ("Reportar un problema", "Report a problem"),
+    ("Página {} · línea {} (seleccionada)", "Page {} · line {} (selected)"),
+    ("Página {} · haga clic en una línea para incluirla", "Page {} · click a line to include it"),
+    ("¿Qué está mal? (opcional)", "What is wrong? (optional)"),
+    ("Abrir issue en GitHub ↗", "Open issue on GitHub ↗"),
+    ("Cronología", "Timeline"),
+    ("No hay fechas: añada date_range al manifiesto, <origDate>/<provenance> al msDesc o <date when=\"...\"> al texto.", "There are no dates: add date_range to the manifest, <origDate>/<provenance> to the msDesc or <date when=\"...\"> to the text."),
+    ("Cronología del proyecto", "Timeline of the project"),
+    ("Índice", "Contents"),
+    ("El proyecto no declara secciones (<div>) ni un índice en el manifiesto.", "The project declares no sections (<div>) nor an outline in its manifest."),
+    ("Mostrar subsecciones", "Show subsections"),
+    ("Ocultar subsecciones", "Hide subsections"),
+    ("Frecuencia de palabras", "Word frequency"),
+    ("Nube", "Cloud"),
+    ("{} palabras, {} formas distintas.", "{} words, {} distinct forms."),
+    ("Se muestran las {} formas más frecuentes.", "Showing the {} most frequent forms."),
+    ("{} apariciones", "{} occurrences"),
+    ("Página", "Page"),
+    ("Proyecto", "Project"),
+    ("Palabra", "Word"),
+    ("Frecuencia", "Frequency"),
+    ("Editor de zonas", "Zone editor"),
+    ("Seleccione una zona y haga clic en la imagen para añadir vértices. Arrastre un vértice para moverlo (Mayús+clic lo elimina) o el interior de la zona para desplazarla.", "Select a zone and click the image to add vertices. Drag a vertex to move it (Shift+click deletes it) or the inside of the zone to shift it."),
+    ("{} · {} vértices", "{} · {} vertices"),
+    ("Línea de {}", "Line of {}"),
+    ("— sin línea —", "— no line —"),
+    ("Eliminar la zona", "Delete the zone"),
+    ("＋ Nueva zona", "＋ New zone"),
+    ("Descargar la sección <facsimile> de la página", "Download the page's <facsimile> section"),
+    ("⬇ Exportar XML", "⬇ Export XML"),
+    ("No se pudo mostrar {}", "Could not show {}"),
+    ("El visor se ha detenido por un error inesperado", "The viewer stopped because of an unexpected error"),
+    ("Recargue la página para volver a intentarlo. Si el error se repite, repórtelo: el informe ya incluye estos datos.", "Reload the page to try again. If the error comes back, please report it: the report already includes these details."),
+    ("Detalles técnicos", "Technical details"),
+    ("⟳ Recargar la página", "⟳ Reload the page"),
+    ("Reportar el problema", "Report the problem"),
+    ("ahora", "just now"),
+    ("hace {} min", "{} min ago"),
+    ("hace {} h", "{} h ago"),
+    ("ayer", "yesterday"),
+    ("hace {} días", "{} days ago"),
+    ("Manifiestos no válidos: {}", "Invalid manifests: {}"),
+    ("Suelte un archivo TEI XML y, si lo desea, la imagen de su página.", "Drop a TEI XML file and, if you wish, the image of its page."),
+    ("No se pudo abrir {}: {}", "Could not open {}: {}"),
+    ("No se pudo cargar el proyecto de {}: {}", "Could not load the project from {}: {}"),
+    ("No se pudo abrir la carpeta: {}", "Could not open the folder: {}"),
+    ("Visualizador TEI-XML", "TEI-XML Viewer"),
+    ("Cargando proyectos...", "Loading projects..."),
+    ("No se encontraron proyectos. Por favor, asegúrese de que public/projects/index.json liste las carpetas de los proyectos y que cada una tenga su manifest.json.", "No projects found. Please make sure public/projects/index.json lists the project folders and that each has its manifest.json."),
+    ("{} · Abrir en el visor ↗", "{} · Open in the viewer ↗"),
+    ("Salir de la presentación", "Leave the presentation"),
+    ("Personas, lugares y entidades divinas de todos los proyectos", "Persons, places and divine names of every project"),
+    ("📇 Índices", "📇 Indices"),
+    ("Tema, tipografía, lecturas, disposición e idioma", "Theme, typeface, readings, layout and language"),
+    ("⚙ Ajustes", "⚙ Settings"),
+    ("Ayuda: atajos de teclado, gestos y signos editoriales (?)", "Help: keyboard shortcuts, gestures and editorial symbols (?)"),
+    ("Proyecto: ", "Project: "),
+    ("Elegir otro texto del repositorio DTS", "Choose another text from the DTS repository"),
+    ("📚 Repositorio", "📚 Repository"),
+    ("Abrir una carpeta de proyecto de este ordenador (manifest.json, XML e imágenes) sin subirla", "Open a project folder from this computer (manifest.json, XML and images) without uploading it"),
+    ("📂 Abrir carpeta", "📂 Open folder"),
+    ("Comparar con: ", "Compare with: "),
+    ("Página: ", "Page: "),
+    ("Página anterior (RePág)", "Previous page (PageUp)"),
+    ("Página anterior", "Previous page"),
+    ("Página siguiente (AvPág)", "Next page (PageDown)"),
+    ("Página siguiente", "Next page"),
+    ("Tras la última página, volver a la primera", "After the last page, go back to the first"),
+    ("Circular", "Wrap around"),
+    ("Ver todas las páginas", "View all pages"),
+    ("▦ Ver todas las páginas", "▦ View all pages"),
+    ("Hojear las fotografías de todas las páginas, sin sus textos", "Flip through the photographs of every page, without their texts"),
+    ("🖼 Galería", "🖼 Gallery"),
+    ("Segundos por página: ", "Seconds per page: "),
+    ("Recorrer las páginas a pantalla completa, sin controles", "Go through the pages full-screen, without controls"),
+    ("▶ Presentación", "▶ Presentation"),
+    ("Suelte el archivo TEI para previsualizarlo", "Drop the TEI file to preview it"),
+    ("Puede soltar también la imagen de la página. El archivo no se sube a ningún servidor.", "You can also drop the image of the page. The file is not uploaded to any server."),
+    ("Guardar todas las páginas, imágenes y el comentario del proyecto en este navegador", "Save every page, image and the commentary of the project in this browser"),
+    ("⬇ Descargar para offline", "⬇ Download for offline use"),
+    ("Descargando {} de {}", "Downloading {} of {}"),
+    ("Descarga incompleta: faltan {} archivos", "Incomplete download: {} files missing"),
+    ("Reintentar", "Retry"),
+    ("Este proyecto se puede leer sin conexión en este navegador", "This project can be read offline in this browser"),
+    ("✓ Disponible sin conexión", "✓ Available offline"),
+    ("Visualizador interactivo - {}", "Interactive viewer - {}"),
+    ("Logotipo de {}", "Logo of {}"),
+    ("Portada de {}", "Cover of {}"),
+    ("Ubicación", "Location"),
+    ("Primer proyecto de la colección", "First project of the collection"),
+    ("Primera página del proyecto", "First page of the project"),
+    ("Inicio de la sección", "Start of the section"),
+    ("Páginas vistas recientemente", "Recently viewed pages"),
+    ("🕘 Recientes", "🕘 Recent"),
+    ("Aún no hay páginas recientes.", "No recent pages yet."),
+    ("Páginas y líneas marcadas", "Bookmarked pages and lines"),
+    ("★ Marcadores ({})", "★ Bookmarks ({})"),
+    ("Aún no hay marcadores. Use «☆ Marcar página» en el visor.", "No bookmarks yet. Use “☆ Bookmark page” in the viewer."),
+    ("Página {} · línea {}", "Page {} · line {}"),
+    ("Quitar el marcador", "Remove the bookmark"),
+    ("Visita guiada", "Guided tour"),
+    ("▶ Iniciar visita guiada", "▶ Start guided tour"),
+    ("Parada {} de {}", "Stop {} of {}"),
+    ("◀ Anterior", "◀ Previous"),
+    ("Fin de la visita", "End of the tour"),
+    ("Siguiente parada ▶", "Next stop ▶"),
+    ("Salir de la visita guiada", "Leave the guided tour"),
+    ("Línea {}: <lb> sin @facs", "Line {}: <lb> without @facs"),
+    ("Línea {}: la zona #{} no existe en <facsimile>", "Line {}: zone #{} does not exist in <facsimile>"),
+    ("Línea {}: la zona #{} ya está asignada a otra línea", "Line {}: zone #{} is already assigned to another line"),
+    ("Zona #{}: sin @points", "Zone #{}: without @points"),
+    ("Zona #{}: ninguna línea la referencia", "Zone #{}: no line refers to it"),
+    ("a. C.", "BCE"),
+    ("d. C.", "CE"),
+    ("Re Pág / Av Pág", "Page Up / Page Down"),
+    ("Página anterior / siguiente", "Previous / next page"),
+    ("Abrir o cerrar esta ayuda", "Open or close this help"),
+    ("← / → (↑ / ↓ si están apilados)", "← / → (↑ / ↓ when stacked)"),
+    ("Redimensionar los paneles con el separador enfocado", "Resize the panels with the splitter focused"),
+    ("Mayús + ← / →", "Shift + ← / →"),
+    ("Redimensionar en pasos más grandes", "Resize in larger steps"),
+    ("Inicio / Fin", "Home / End"),
+    ("Panel de imagen al mínimo / al máximo", "Image panel at its smallest / largest"),
+    ("Intro", "Enter"),
+    ("Ir a la línea escrita en «Ir a línea»", "Go to the line typed in “Go to line”"),
+    ("← / → en la galería", "← / → in the gallery"),
+    ("Fotografía anterior / siguiente (Intro abre su transcripción)", "Previous / next photograph (Enter opens its transcription)"),
+    ("R / Mayús + R, H, V en la galería", "R / Shift + R, H, V in the gallery"),
+    ("Girar la fotografía 90°, voltearla en horizontal o en vertical", "Rotate the photograph 90°, flip it horizontally or vertically"),
+    ("Pasar sobre una línea", "Hover a line"),
+    ("Resaltar su zona en el facsímil", "Highlight its zone on the facsimile"),
+    ("Clic en una línea", "Click a line"),
+    ("Fijar el resaltado (otro clic lo suelta)", "Lock the highlight (another click releases it)"),
+    ("Rueda del ratón sobre la imagen", "Mouse wheel over the image"),
+    ("Acercar y alejar", "Zoom in and out"),
+    ("Arrastrar la imagen", "Drag the image"),
+    ("Desplazar el facsímil", "Pan the facsimile"),
+    ("Pellizcar con dos dedos", "Pinch with two fingers"),
+    ("Acercar y alejar en pantallas táctiles", "Zoom in and out on touch screens"),
+    ("Deslizar el texto a izquierda o derecha", "Swipe the text left or right"),
+    ("Pasar de página (de pestaña en el móvil)", "Turn the page (the tab on phones)"),
+    ("Doble clic en el separador", "Double-click the splitter"),
+    ("Restablecer el tamaño de los paneles", "Reset the panel sizes"),
+    ("Clic en un término subrayado", "Click an underlined term"),
+    ("Ver su entrada del glosario", "See its glossary entry"),
+    ("Mayús + clic en un vértice", "Shift + click a vertex"),
+    ("Quitar el punto de la zona en el editor de zonas", "Remove the point from the zone in the zone editor"),
+    ("Letras perdidas en una laguna y restituidas por el editor", "Letters lost in a gap and restored by the editor"),
+    ("Laguna no restituida, un punto por letra perdida", "Unrestored gap, one dot per lost letter"),
+    ("Desarrollo de una abreviatura", "Expansion of an abbreviation"),
+    ("Letras omitidas por el escriba y añadidas por el editor", "Letters omitted by the scribe and added by the editor"),
+    ("Letras superfluas escritas por el escriba", "Superfluous letters written by the scribe"),
+    ("Letras borradas o tachadas por el escriba", "Letters erased or struck out by the scribe"),
+    ("Letras añadidas por el escriba sobre la línea", "Letters added by the scribe above the line"),
+    ("Letras dañadas o de lectura dudosa", "Damaged or doubtful letters"),
+    ("Trazo suprascrito: numeral o abreviatura", "Supralinear stroke: numeral or abbreviation"),
+    ("Espacio dejado en blanco por el escriba", "Space left blank by the scribe"),
+    ("El facsímil", "The facsimile"),
+    ("La imagen del manuscrito. Acérquela con la rueda del ratón o pellizcando, y arrástrela para desplazarla.", "The image of the manuscript. Zoom in with the mouse wheel or by pinching, and drag it to pan."),
+    ("Texto e imagen enlazados", "Linked text and image"),
+    ("Pase el ratón sobre una línea de la transcripción para resaltar su zona en el facsímil; un clic la deja fijada.", "Hover a line of the transcription to highlight its zone on the facsimile; a click keeps it locked."),
+    ("Vistas y paneles", "Views and panels"),
+    ("Elija la edición diplomática, la traducción, ambas o sus diferencias, y abra las notas, el índice, el glosario y los demás paneles.", "Choose the diplomatic edition, the translation, both or their differences, and open the notes, the contents, the glossary and the other panels."),
+    ("Leyenda", "Legend"),
+    ("Los colores del texto marcan abreviaturas, correcciones, nombres y otras intervenciones; la leyenda explica cada uno.", "The colors of the text mark abbreviations, corrections, names and other interventions; the legend explains each one."),
+    ("Personas", "Persons"),
+    ("Lugares", "Places"),
+    ("Entidades divinas", "Divine names"),
+    ("Datación", "Dating"),
+    ("Procedencia", "Provenance"),
+    ("Fechas en el texto", "Dates in the text"),
+    ("Fecha de origen", "Date of origin"),
+    ("Adquisición", "Acquisition"),
+    ("Amarillo", "Yellow"),
+    ("Verde", "Green"),
+    ("Azul", "Blue"),
+    ("Rosa", "Pink"),
+    ("☀️ Claro", "☀️ Light"),
+    ("🌙 Oscuro", "🌙 Dark"),
+    ("◐ Alto contraste", "◐ High contrast"),
+    ("Del tema", "Theme default"),
+    ("Cian", "Cyan"),
+    ("⬌ Lado a lado", "⬌ Side by side"),
+    ("⬍ Apilados", "⬍ Stacked"),
+    ("Sin cambios (304)", "Unchanged (304)"),
+    ("Copia anterior", "Older copy"),
+    ("Memoria", "Memory"),
+    ("el documento", "the document"),
+    ("la cuadrícula de páginas", "the page grid"),
+    ("los índices de la colección", "the collection indices"),
+    ("la galería", "the gallery"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Lang::parse("en-GB"), Some(Lang::En));
+        assert_eq!(Lang::parse("es_AR"), Some(Lang::Es));
+        assert_eq!(Lang::parse("de"), None);
+        for lang in Lang::ALL {
+            assert_eq!(Lang::parse(lang.as_str()), Some(lang));
+        }
+    }
+
+    #[test]
+    fn test_catalog() {
+        for (i, (es, en)) in EN.iter().enumerate() {
+            assert!(
+                EN[..i].iter().all(|(other, _)| other != es),
+                "duplicate entry {:?}",
+                es
+            );
+            assert_eq!(
+                es.matches("{}").count(),
+                en.matches("{}").count(),
+                "placeholders of {:?}",
+                es
+            );
+        }
+        assert_eq!(Lang::En.t("texto sin traducir"), "texto sin traducir");
+        assert_eq!(Lang::Es.tf("hace {} min", &[&5]), "hace 5 min");
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(Lang::Es.number(1234), "1234");
+        assert_eq!(Lang::Es.number(12345), "12.345");
+        assert_eq!(Lang::En.number(1234), "1,234");
+        assert_eq!(Lang::En.number(1234567), "1,234,567");
+        assert_eq!(Lang::En.number(12), "12");
+    }
+}
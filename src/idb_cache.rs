@@ -0,0 +1,179 @@
+// src/idb_cache.rs
+// Fetched texts kept in IndexedDB under their URL and `?v=` version, with
+// their `ETag` and `Last-Modified` for revalidation.
+use crate::net::Validators;
+use crate::offline::cache_key;
+use crate::utils::window;
+use js_sys::Promise;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{IdbDatabase, IdbRequest, IdbTransactionMode};
+
+const DATABASE: &str = "tei-viewer";
+const STORE: &str = "texts";
+
+thread_local! {
+    static DB: RefCell<Option<IdbDatabase>> = const { RefCell::new(None) };
+}
+
+/// A kept text and the version it was fetched in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Entry {
+    version: Option<String>,
+    text: String,
+    #[serde(default, flatten)]
+    validators: Validators,
+}
+
+/// A text found in the cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cached {
+    pub text: String,
+    /// Whether it is the version the URL asks for
+    pub current: bool,
+    pub validators: Validators,
+}
+
+/// Key a URL is kept under, and the version it asks for.
+fn key(url: &str) -> (String, Option<String>) {
+    let version = url
+        .split_once('?')
+        .and_then(|(_, query)| query.split('&').find_map(|pair| pair.strip_prefix("v=")))
+        .map(str::to_string);
+    (cache_key(url), version)
+}
+
+/// Settle once `request` succeeds or fails, with its result.
+async fn settle(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise).await?;
+    request.result()
+}
+
+/// The database, opened (and its store created) the first time.
+async fn database() -> Option<IdbDatabase> {
+    let window = window()?;
+    if let Some(db) = DB.with(|db| db.borrow().clone()) {
+        return Some(db);
+    }
+    let factory = window.indexed_db().ok()??;
+    let request = factory.open_with_u32(DATABASE, 1).ok()?;
+    let upgrade = Closure::once_into_js(|event: web_sys::Event| {
+        let db = event
+            .target()
+            .and_then(|t| t.dyn_into::<IdbRequest>().ok())
+            .and_then(|r| r.result().ok())
+            .and_then(|r| r.dyn_into::<IdbDatabase>().ok());
+        if let Some(db) = db.filter(|db| !db.object_store_names().contains(STORE)) {
+            let _ = db.create_object_store(STORE);
+        }
+    });
+    request.set_onupgradeneeded(Some(upgrade.unchecked_ref()));
+    let db: IdbDatabase = match settle(&request).await {
+        Ok(db) => db.dyn_into().ok()?,
+        Err(e) => {
+            // Private browsing may refuse it: texts are then not kept
+            log::warn!("IndexedDB is not available: {:?}", e);
+            return None;
+        }
+    };
+    DB.with(|cell| *cell.borrow_mut() = Some(db.clone()));
+    Some(db)
+}
+
+/// The text kept for `url`, if any.
+pub async fn get(url: &str) -> Option<Cached> {
+    let db = database().await?;
+    let (key, version) = key(url);
+    let store = db
+        .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readonly)
+        .ok()?
+        .object_store(STORE)
+        .ok()?;
+    let request = store.get(&JsValue::from_str(&key)).ok()?;
+    let json = settle(&request).await.ok()?.as_string()?;
+    let entry: Entry = serde_json::from_str(&json).ok()?;
+    Some(Cached {
+        current: version.is_some() && entry.version == version,
+        text: entry.text,
+        validators: entry.validators,
+    })
+}
+
+/// Keep `text`, just fetched from `url` with `validators`.
+pub async fn put(url: &str, text: &str, validators: &Validators) {
+    let Some(db) = database().await else {
+        return;
+    };
+    let (key, version) = key(url);
+    let entry = Entry {
+        version,
+        text: text.to_string(),
+        validators: validators.clone(),
+    };
+    let Ok(json) = serde_json::to_string(&entry) else {
+        return;
+    };
+    let request = db
+        .transaction_with_str_and_mode(STORE, IdbTransactionMode::Readwrite)
+        .and_then(|t| t.object_store(STORE))
+        .and_then(|store| store.put_with_key(&JsValue::from_str(&json), &JsValue::from_str(&key)));
+    let result = match request {
+        Ok(request) => settle(&request).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        // A full quota only costs the next visit its head start
+        log::warn!("Could not keep {}: {:?}", key, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        assert_eq!(
+            key("/public/projects/P/p1_dip.xml?v=1.4.0"),
+            (
+                "/public/projects/P/p1_dip.xml".to_string(),
+                Some("1.4.0".to_string())
+            )
+        );
+        assert_eq!(
+            key("https://x.org/P/manifest.json?a=1&v=9f3c"),
+            (
+                "https://x.org/P/manifest.json".to_string(),
+                Some("9f3c".to_string())
+            )
+        );
+        assert_eq!(
+            key("/public/projects/index.json"),
+            ("/public/projects/index.json".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_entry() {
+        let entry = Entry {
+            version: Some("3".to_string()),
+            text: "<TEI/>".to_string(),
+            validators: Validators {
+                etag: Some("W/\"1a\"".to_string()),
+                last_modified: None,
+            },
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        assert_eq!(json, r#"{"version":"3","text":"<TEI/>","etag":"W/\"1a\""}"#);
+        assert_eq!(serde_json::from_str::<Entry>(&json).unwrap(), entry);
+        // Entries kept before validators were
+        let old: Entry = serde_json::from_str(r#"{"version":null,"text":"x"}"#).unwrap();
+        assert_eq!(old.validators, Validators::default());
+    }
+}
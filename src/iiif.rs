@@ -0,0 +1,374 @@
+// src/iiif.rs
+// IIIF Presentation manifests (versions 2 and 3) read as projects, so the
+// collections libraries and museums already publish open in the viewer:
+// each canvas is a page, its image service the facsimile (rendered from
+// tiles) and a TEI or ALTO transcription linked through `seeAlso` its
+// diplomatic edition. A canvas without a transcription is still shown, from
+// a stub edition that only names its image.
+use crate::project_config::{ImageVariant, PageInfo, ProjectConfig, ProjectMetadata};
+use crate::url_state;
+use crate::utils::slug;
+use quick_xml::escape::escape;
+use serde_json::Value;
+
+/// Whether `json` is a IIIF Presentation manifest.
+pub fn is_manifest(json: &Value) -> bool {
+    let context = match &json["@context"] {
+        Value::Array(contexts) => contexts.iter().any(is_presentation_context),
+        context => is_presentation_context(context),
+    };
+    let kind = text(
+        json.get("type")
+            .or(json.get("@type"))
+            .unwrap_or(&Value::Null),
+    );
+    context && (kind == "Manifest" || kind == "sc:Manifest")
+}
+
+fn is_presentation_context(context: &Value) -> bool {
+    context
+        .as_str()
+        .is_some_and(|c| c.contains("iiif.io/api/presentation"))
+}
+
+/// The project of a manifest fetched from `url`, its pages' editions served
+/// from the transcriptions the canvases link.
+pub fn import(url: &str, json: &Value) -> Result<ProjectConfig, String> {
+    let label = text(&json["label"]);
+    let mut project = ProjectConfig::new(project_id(&label, url), label.clone());
+    if project.name.is_empty() {
+        project.name = url.to_string();
+    }
+    project.description = plain(&text(
+        json.get("summary")
+            .or(json.get("description"))
+            .unwrap_or(&Value::Null),
+    ));
+    project.metadata = metadata(json);
+
+    let canvases = match json.get("items") {
+        Some(items) => items.as_array(),
+        None => json["sequences"][0]["canvases"].as_array(),
+    };
+    let canvases = canvases.filter(|c| !c.is_empty()).ok_or("no canvases")?;
+
+    for (i, canvas) in canvases.iter().enumerate() {
+        let number = i as u32 + 1;
+        let mut page = PageInfo::new(number).with_translation(false);
+        let label = text(&canvas["label"]);
+        if !label.is_empty() {
+            page.label = label;
+        }
+        let image = image(canvas);
+        page.has_image = image.is_some();
+        if let Some(image) = &image {
+            match &image.service {
+                Some(service) => page.iiif_service = Some(service.clone()),
+                None if image.width > 0 => {
+                    page.image_variants = vec![ImageVariant {
+                        path: image.url.clone(),
+                        width: image.width,
+                    }]
+                }
+                None => {}
+            }
+        }
+        page.thumbnail = first(&canvas["thumbnail"])
+            .map(id)
+            .filter(|t| !t.is_empty());
+
+        let edition = match transcription(canvas) {
+            Some(url) => url,
+            None => {
+                // No transcription: an empty edition naming the image
+                page.has_diplomatic = false;
+                let size = (
+                    canvas["width"].as_u64().unwrap_or(0),
+                    canvas["height"].as_u64().unwrap_or(0),
+                );
+                stub_edition(image.as_ref().map_or("", |i| i.url.as_str()), size)
+            }
+        };
+        project
+            .files
+            .insert(ProjectConfig::page_file(number, "dip"), edition);
+        project.pages.push(page);
+    }
+    Ok(project)
+}
+
+/// The image painted on a canvas
+struct Image {
+    url: String,
+    width: u32,
+    service: Option<String>,
+}
+
+fn image(canvas: &Value) -> Option<Image> {
+    // v3: Canvas > AnnotationPage > Annotation > body; v2: images > resource
+    let resource = match canvas.get("items") {
+        Some(pages) => first(&pages[0]["items"][0]["body"])?,
+        None => &canvas["images"][0]["resource"],
+    };
+    let url = id(resource);
+    if url.is_empty() {
+        return None;
+    }
+    let service = first(&resource["service"])
+        .map(id)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.trim_end_matches('/').to_string());
+    Some(Image {
+        url,
+        width: resource["width"].as_u64().unwrap_or(0) as u32,
+        service,
+    })
+}
+
+/// The TEI or ALTO resource linked from a canvas, TEI preferred.
+fn transcription(canvas: &Value) -> Option<String> {
+    let links: Vec<&Value> = match &canvas["seeAlso"] {
+        Value::Array(links) => links.iter().collect(),
+        Value::Null => Vec::new(),
+        link => vec![link],
+    };
+    let kind = |link: &Value| {
+        format!("{} {}", text(&link["format"]), text(&link["profile"])).to_ascii_lowercase()
+    };
+    ["tei", "alto"].iter().find_map(|wanted| {
+        links
+            .iter()
+            .find(|link| kind(link).contains(wanted))
+            .map(|link| id(link))
+            .filter(|url| url.starts_with("https://") || url.starts_with("http://"))
+    })
+}
+
+/// TEI of a page without a transcription, as a `data:` URL.
+fn stub_edition(image: &str, (width, height): (u64, u64)) -> String {
+    let xml = format!(
+        "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\"><facsimile><surface>\
+         <graphic url=\"{}\" width=\"{}\" height=\"{}\"/></surface></facsimile>\
+         <text><body/></text></TEI>",
+        escape(image),
+        width,
+        height
+    );
+    format!(
+        "data:application/xml;charset=utf-8,{}",
+        url_state::encode(&xml)
+    )
+}
+
+fn metadata(json: &Value) -> ProjectMetadata {
+    let mut metadata = ProjectMetadata {
+        institution: plain(&text(
+            json["requiredStatement"]
+                .get("value")
+                .or(json.get("attribution"))
+                .unwrap_or(&Value::Null),
+        )),
+        // `rights` (v3) or `license` (v2): a license URL
+        license: json["rights"]
+            .as_str()
+            .or(json["license"].as_str())
+            .map(str::to_string),
+        ..ProjectMetadata::default()
+    };
+    for entry in json["metadata"].as_array().into_iter().flatten() {
+        let value = plain(&text(&entry["value"]));
+        match text(&entry["label"]).to_lowercase().as_str() {
+            "author" | "creator" | "autor" => metadata.author = value,
+            "editor" => metadata.editor = value,
+            "date" | "fecha" => metadata.date_range = value,
+            "language" | "idioma" => metadata.language = value,
+            "collection" | "repository" | "colección" => metadata.collection = value,
+            "country" | "país" => metadata.country = value,
+            _ => {}
+        }
+    }
+    metadata
+}
+
+/// Project id from the manifest label, e.g. "iiif-codex-vaticanus-1209".
+fn project_id(label: &str, url: &str) -> String {
+    let source = if label.is_empty() { url } else { label };
+    format!("iiif-{}", slug(source, 40))
+}
+
+/// `id` (v3) or `@id` (v2) of a resource, or the resource if it is a URL.
+fn id(resource: &Value) -> String {
+    resource
+        .get("id")
+        .or(resource.get("@id"))
+        .unwrap_or(resource)
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// A value, or the first of a list of them.
+fn first(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Null => None,
+        Value::Array(values) => values.first(),
+        value => Some(value),
+    }
+}
+
+/// Text of a label or value: a string, a v2 `@value` or list of them, or a
+/// v3 language map (English preferred). DTS uses the same JSON-LD forms.
+pub fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(values) => values
+            .iter()
+            .map(text)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("; "),
+        Value::Object(map) => match map.get("@value") {
+            Some(v) => text(v),
+            None => ["en", "none"]
+                .iter()
+                .find_map(|lang| map.get(*lang))
+                .or(map.values().next())
+                .map(text)
+                .unwrap_or_default(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Text without the HTML markup manifests may use in values.
+fn plain(html: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V3: &str = r#"{
+        "@context": "http://iiif.io/api/presentation/3/context.json",
+        "id": "https://lib.example.org/iiif/ms12/manifest",
+        "type": "Manifest",
+        "label": { "en": ["Ms. 12: Magical Papyrus"] },
+        "summary": { "en": ["<p>A <b>Greek</b> papyrus</p>"] },
+        "requiredStatement": { "label": { "en": ["Attribution"] }, "value": { "en": ["Example Library"] } },
+        "metadata": [ { "label": { "en": ["Date"] }, "value": { "none": ["IV AD"] } } ],
+        "items": [
+            {
+                "id": "https://lib.example.org/iiif/ms12/canvas/1", "type": "Canvas",
+                "label": { "none": ["1r"] }, "width": 4000, "height": 6000,
+                "items": [ { "type": "AnnotationPage", "items": [ { "type": "Annotation", "motivation": "painting",
+                    "body": { "id": "https://img.example.org/ms12_1r/full/max/0/default.jpg", "type": "Image",
+                              "width": 4000, "height": 6000,
+                              "service": [ { "id": "https://img.example.org/ms12_1r/", "type": "ImageService3" } ] } } ] } ],
+                "seeAlso": [
+                    { "id": "https://lib.example.org/ocr/1r.xml", "type": "Dataset", "format": "application/xml",
+                      "profile": "http://www.loc.gov/standards/alto/v4/alto.xsd" },
+                    { "id": "https://lib.example.org/tei/1r.xml", "type": "Dataset", "format": "application/tei+xml" }
+                ]
+            },
+            {
+                "id": "https://lib.example.org/iiif/ms12/canvas/2", "type": "Canvas",
+                "label": { "none": ["1v"] }, "width": 4000, "height": 6000,
+                "items": [ { "type": "AnnotationPage", "items": [ { "type": "Annotation", "motivation": "painting",
+                    "body": { "id": "https://img.example.org/1v.jpg", "type": "Image", "width": 2000 } } ] } ]
+            }
+        ]
+    }"#;
+
+    const V2: &str = r#"{
+        "@context": "http://iiif.io/api/presentation/2/context.json",
+        "@id": "https://lib.example.org/iiif/ms7/manifest.json",
+        "@type": "sc:Manifest",
+        "label": "Ms. 7",
+        "attribution": "Example Library",
+        "sequences": [ { "canvases": [ {
+            "@id": "https://lib.example.org/iiif/ms7/c1", "@type": "sc:Canvas", "label": "f. 1",
+            "width": 1000, "height": 1500,
+            "images": [ { "resource": { "@id": "https://img.example.org/ms7_1/full/full/0/default.jpg",
+                "service": { "@id": "https://img.example.org/ms7_1" } } } ],
+            "seeAlso": { "@id": "https://lib.example.org/alto/c1.xml", "format": "application/alto+xml" }
+        } ] } ]
+    }"#;
+
+    #[test]
+    fn test_is_manifest() {
+        assert!(is_manifest(&serde_json::from_str(V3).unwrap()));
+        assert!(is_manifest(&serde_json::from_str(V2).unwrap()));
+        let project = r#"{"id": "P", "name": "P", "pages": []}"#;
+        assert!(!is_manifest(&serde_json::from_str(project).unwrap()));
+    }
+
+    #[test]
+    fn test_import_v3() {
+        let json = serde_json::from_str(V3).unwrap();
+        let project = import("https://x/manifest", &json).unwrap();
+        assert_eq!(project.id, "iiif-ms-12-magical-papyrus");
+        assert_eq!(project.name, "Ms. 12: Magical Papyrus");
+        assert_eq!(project.description, "A Greek papyrus");
+        assert_eq!(project.metadata.institution, "Example Library");
+        assert_eq!(project.metadata.date_range, "IV AD");
+
+        let page = &project.pages[0];
+        assert_eq!(page.label, "1r");
+        assert!(page.has_diplomatic && !page.has_translation);
+        assert_eq!(
+            page.iiif_service.as_deref(),
+            Some("https://img.example.org/ms12_1r")
+        );
+        // TEI is preferred to ALTO
+        assert_eq!(
+            project.files["p1_dip.xml"],
+            "https://lib.example.org/tei/1r.xml"
+        );
+
+        // No service: the image itself; no transcription: a stub edition
+        let page = &project.pages[1];
+        assert_eq!(page.iiif_service, None);
+        assert_eq!(
+            page.image_variants[0].path,
+            "https://img.example.org/1v.jpg"
+        );
+        assert!(!page.has_diplomatic);
+        assert!(project.files["p2_dip.xml"].starts_with("data:application/xml"));
+    }
+
+    #[test]
+    fn test_import_v2() {
+        let json = serde_json::from_str(V2).unwrap();
+        let project = import("https://x/manifest.json", &json).unwrap();
+        assert_eq!(project.id, "iiif-ms-7");
+        assert_eq!(project.metadata.institution, "Example Library");
+        assert_eq!(project.pages[0].label, "f. 1");
+        assert_eq!(
+            project.pages[0].iiif_service.as_deref(),
+            Some("https://img.example.org/ms7_1")
+        );
+        assert_eq!(
+            project.files["p1_dip.xml"],
+            "https://lib.example.org/alto/c1.xml"
+        );
+    }
+
+    #[test]
+    fn test_text() {
+        let map = serde_json::json!({ "fr": ["Papyrus"], "en": ["Papyrus", "Roll"] });
+        assert_eq!(text(&map), "Papyrus; Roll");
+        assert_eq!(text(&serde_json::json!([{ "@value": "a" }, "b"])), "a; b");
+        assert_eq!(plain("<span>IV <i>AD</i></span>"), "IV AD");
+    }
+}
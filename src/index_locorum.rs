@@ -0,0 +1,209 @@
+// src/index_locorum.rs
+// Index locorum: every passage cited with `<ref>` across the project
+// (Homer, other PGM spells, ...), grouped by target with the lines that
+// cite it, as in the apparatus of a printed critical edition.
+use crate::polytonic;
+use crate::tei_data::{TeiDocument, TextNode};
+use std::cmp::Ordering;
+
+/// A line citing a passage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Citation {
+    pub page: u32,
+    /// Line index in the page's diplomatic edition
+    pub line: usize,
+    /// How the line is referred to (see `TeiDocument::line_label`)
+    pub label: String,
+    /// The reference as written on the line
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locus {
+    /// `@target` of the `<ref>`, or its text when it has none
+    pub target: String,
+    /// `@type` of the first citation, e.g. "bibl"
+    pub ref_type: String,
+    pub citations: Vec<Citation>,
+}
+
+impl Locus {
+    /// Whether the target is a link that can be opened directly.
+    pub fn is_url(&self) -> bool {
+        self.target.starts_with("http://") || self.target.starts_with("https://")
+    }
+}
+
+/// Collect the `<ref>`s of `docs` into loci sorted by target, numbers in
+/// passages compared by value ("Il. 2.10" after "Il. 2.9").
+pub fn build<'a>(docs: impl IntoIterator<Item = (u32, &'a TeiDocument)>) -> Vec<Locus> {
+    let mut loci: Vec<Locus> = Vec::new();
+    for (page, doc) in docs {
+        for (line, l) in doc.lines.iter().enumerate() {
+            let mut refs = Vec::new();
+            collect_refs(&l.content, &mut refs);
+            for (ref_type, target, content) in refs {
+                let target = match target.trim() {
+                    "" => content.trim(),
+                    t => t,
+                };
+                if target.is_empty() {
+                    continue;
+                }
+                let citation = Citation {
+                    page,
+                    line,
+                    label: doc.line_label(line).unwrap_or_default(),
+                    text: content.trim().to_string(),
+                };
+                match loci.iter_mut().find(|l| l.target == target) {
+                    Some(locus) => locus.citations.push(citation),
+                    None => loci.push(Locus {
+                        target: target.to_string(),
+                        ref_type: ref_type.to_string(),
+                        citations: vec![citation],
+                    }),
+                }
+            }
+        }
+    }
+    loci.sort_by(|a, b| natural_cmp(&a.target, &b.target));
+    loci
+}
+
+fn collect_refs<'a>(nodes: &'a [TextNode], out: &mut Vec<(&'a str, &'a str, &'a str)>) {
+    for node in nodes {
+        match node {
+            TextNode::Ref {
+                ref_type,
+                target,
+                content,
+            } => out.push((ref_type, target, content)),
+            TextNode::PersName { content, .. } | TextNode::Hi { content, .. } => {
+                collect_refs(content, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Loci whose target or citing text contains `query`, ignoring accents
+/// and case.
+pub fn filter<'a>(loci: &'a [Locus], query: &str) -> Vec<&'a Locus> {
+    let query = query.trim();
+    loci.iter()
+        .filter(|l| {
+            query.is_empty()
+                || polytonic::contains(&l.target, query, true)
+                || l.citations
+                    .iter()
+                    .any(|c| polytonic::contains(&c.text, query, true))
+        })
+        .collect()
+}
+
+/// Compare case-insensitively, with runs of digits compared as numbers.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let number = |it: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(d) = it.next_if(char::is_ascii_digit) {
+                        digits.push(d);
+                    }
+                    digits
+                };
+                let (x, y) = (number(&mut a), number(&mut b));
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                let order = x.len().cmp(&y.len()).then_with(|| x.cmp(y));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let order = x.to_lowercase().cmp(y.to_lowercase());
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(xml_body: &str) -> TeiDocument {
+        let xml = format!(
+            "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\"><text><body>{}</body></text></TEI>",
+            xml_body
+        );
+        crate::tei_parser::parse_tei_xml(&xml).unwrap()
+    }
+
+    #[test]
+    fn test_build_groups_and_sorts() {
+        let p1 = page(
+            r#"<lb n="1"/><ab>ὡς <ref type="bibl" target="Hom. Il. 2.10">Ὅμηρος</ref></ab>
+               <lb n="2"/><ab><hi rend="italic"><ref target="Hom. Il. 2.9">ἔπος</ref></hi></ab>"#,
+        );
+        let p2 = page(
+            r##"<lb n="1"/><ab><ref type="bibl" target="Hom. Il. 2.10">Ἰλιάς</ref> <ref>PGM IV 100</ref></ab>
+               <lb n="2"/><ab><ref type="note" target="#n1">1</ref></ab>"##,
+        );
+        let loci = build([(1, &p1), (3, &p2)]);
+        let targets: Vec<_> = loci.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(targets, ["Hom. Il. 2.9", "Hom. Il. 2.10", "PGM IV 100"]);
+        assert_eq!(loci[1].ref_type, "bibl");
+        assert_eq!(
+            loci[1].citations,
+            vec![
+                Citation {
+                    page: 1,
+                    line: 0,
+                    label: "1".to_string(),
+                    text: "Ὅμηρος".to_string()
+                },
+                Citation {
+                    page: 3,
+                    line: 0,
+                    label: "1".to_string(),
+                    text: "Ἰλιάς".to_string()
+                },
+            ]
+        );
+        // Note references are not citations
+        assert!(loci.iter().all(|l| l.target != "#n1"));
+    }
+
+    #[test]
+    fn test_filter() {
+        let p1 = page(
+            r#"<lb/><ab><ref target="https://example.org/pgm/4">Ἑρμῆς</ref> <ref target="Hom. Od. 1.1">ἄνδρα</ref></ab>"#,
+        );
+        let loci = build([(1, &p1)]);
+        assert!(loci[1].is_url());
+        assert_eq!(filter(&loci, "").len(), 2);
+        assert_eq!(filter(&loci, "od.")[0].target, "Hom. Od. 1.1");
+        assert_eq!(
+            filter(&loci, "ερμης")[0].target,
+            "https://example.org/pgm/4"
+        );
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("Il. 2.9", "Il. 2.10"), Ordering::Less);
+        assert_eq!(natural_cmp("il. 10", "Il. 010"), Ordering::Equal);
+        assert_eq!(natural_cmp("Od.", "Il."), Ordering::Greater);
+        assert_eq!(natural_cmp("PGM IV", "PGM IV 1"), Ordering::Less);
+    }
+}
@@ -0,0 +1,158 @@
+// src/legend.rs
+// Colors and labels of the text's categories (abbreviations, corrections,
+// names, `<rs>` types…), which a project may set in its manifest to match the
+// conventions of its print edition. Categories are named by the class their
+// text carries; their colors reach the stylesheet as `--color-{class}`.
+use crate::project_config::LegendEntry;
+
+/// Categories whose stylesheet rules read their `--color-{class}` variable;
+/// others declared in the manifest (e.g. a further `rs-demon`) get rules of
+/// their own.
+const THEMED: &[&str] = &[
+    "abbreviation",
+    "correction",
+    "regularised",
+    "number",
+    "person-name",
+    "place-name",
+    "ref",
+    "unclear",
+    "rs-divine",
+    "rs-astral",
+];
+
+/// Selector the project's colors are scoped to
+const SCOPE: &str = ".tei-viewer-container";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendItem {
+    pub class: String,
+    /// Short sample shown in the swatch
+    pub swatch: String,
+    pub label: String,
+}
+
+impl LegendItem {
+    pub fn new(class: &str, swatch: &str, label: &str) -> Self {
+        Self {
+            class: class.to_string(),
+            swatch: swatch.to_string(),
+            label: label.to_string(),
+        }
+    }
+}
+
+/// The viewer's legend with the project's labels, followed by the
+/// categories the project adds.
+pub fn merge(defaults: Vec<LegendItem>, entries: &[LegendEntry]) -> Vec<LegendItem> {
+    let mut items = defaults;
+    for entry in entries.iter().filter(|e| valid_class(&e.category)) {
+        match items.iter_mut().find(|item| item.class == entry.category) {
+            Some(item) => {
+                if let Some(label) = entry.label.as_ref().filter(|l| !l.is_empty()) {
+                    item.label = label.clone();
+                }
+            }
+            None => {
+                let label = entry
+                    .label
+                    .clone()
+                    .filter(|l| !l.is_empty())
+                    .unwrap_or_else(|| entry.category.clone());
+                items.push(LegendItem {
+                    class: entry.category.clone(),
+                    swatch: label.chars().take(2).collect(),
+                    label,
+                });
+            }
+        }
+    }
+    items
+}
+
+/// Stylesheet applying the project's colors, empty when it sets none.
+pub fn style_sheet(entries: &[LegendEntry]) -> String {
+    let colored: Vec<(&str, &str)> = entries
+        .iter()
+        .filter_map(|e| Some((e.category.as_str(), e.color.as_deref()?)))
+        .filter(|(class, color)| valid_class(class) && valid_color(color))
+        .collect();
+    if colored.is_empty() {
+        return String::new();
+    }
+    let variables: String = colored
+        .iter()
+        .map(|(class, color)| format!(" --color-{}: {};", class, color))
+        .collect();
+    let mut css = format!("{} {{{} }}", SCOPE, variables);
+    for (class, _) in colored.iter().filter(|(class, _)| !THEMED.contains(class)) {
+        css.push_str(&format!(
+            "\n{scope} .{class} {{ color: var(--color-{class}); border-bottom: 2px solid var(--color-{class}); }}",
+            scope = SCOPE,
+            class = class
+        ));
+    }
+    css
+}
+
+fn valid_class(class: &str) -> bool {
+    !class.is_empty()
+        && class
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+/// A color value that can't close the declaration or the rule around it:
+/// `#rrggbb`, a name or a function such as `rgb(…)`.
+fn valid_color(color: &str) -> bool {
+    !color.trim().is_empty()
+        && color
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "#(),.% -".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(category: &str, label: Option<&str>, color: Option<&str>) -> LegendEntry {
+        LegendEntry {
+            category: category.to_string(),
+            label: label.map(str::to_string),
+            color: color.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let defaults = vec![
+            LegendItem::new("abbreviation", "Ab", "Abreviatura"),
+            LegendItem::new("rs-divine", "Dv", "Entidad divina"),
+        ];
+        let entries = [
+            entry("rs-divine", Some("Dios"), Some("#c00")),
+            entry("rs-demon", Some("Demonio"), None),
+            entry("bad class", Some("x"), None),
+        ];
+        let items = merge(defaults, &entries);
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].label, "Abreviatura");
+        assert_eq!(items[1].label, "Dios");
+        assert_eq!(items[2], LegendItem::new("rs-demon", "De", "Demonio"));
+    }
+
+    #[test]
+    fn test_style_sheet() {
+        assert_eq!(style_sheet(&[entry("ref", Some("Cita"), None)]), "");
+        let css = style_sheet(&[
+            entry("abbreviation", None, Some("#123456")),
+            entry("rs-demon", None, Some("rgb(10, 20, 30)")),
+            entry("ref", None, Some("red; } body { display: none")),
+        ]);
+        assert_eq!(
+            css,
+            ".tei-viewer-container { --color-abbreviation: #123456; --color-rs-demon: rgb(10, 20, 30); }\n\
+             .tei-viewer-container .rs-demon { color: var(--color-rs-demon); border-bottom: 2px solid var(--color-rs-demon); }"
+        );
+    }
+}
@@ -0,0 +1,398 @@
+// src/local_document.rs
+// Files opened from the reader's computer: a TEI file dropped onto the
+// viewer, shown as the single page of an ad-hoc project, or a whole project
+// folder read through the File System Access API. Nothing is uploaded; the
+// files are registered under the URLs the viewer would fetch them from and
+// last until the page is reloaded, so that encoders can preview their work
+// without deploying it.
+use crate::manifest;
+use crate::offline::cache_key;
+use crate::project_config::{PageInfo, ProjectConfig};
+use crate::tei_data::TeiDocument;
+use crate::utils::window;
+use gloo::file::ObjectUrl;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// Id (and route segment) of the ad-hoc project of a dropped document
+pub const LOCAL_PROJECT: &str = "documento-local";
+
+/// Folders deeper than this inside a project folder are not read
+const MAX_DEPTH: usize = 3;
+
+thread_local! {
+    // Files of each project opened locally, by project id
+    static OPENED: RefCell<HashMap<String, LocalFiles>> = RefCell::new(HashMap::new());
+}
+
+/// Files of a local project, keyed like the offline cache (site-absolute,
+/// without query).
+#[derive(Default)]
+pub struct LocalFiles {
+    /// Folder of the project the files are served under
+    base: String,
+    texts: HashMap<String, String>,
+    /// Images and other media, served from object URLs
+    blobs: HashMap<String, Rc<ObjectUrl>>,
+}
+
+impl LocalFiles {
+    pub fn add_text(&mut self, url: &str, text: String) {
+        self.texts.insert(cache_key(url), text);
+    }
+
+    pub fn add_blob(&mut self, url: &str, blob: Rc<ObjectUrl>) {
+        self.blobs.insert(cache_key(url), blob);
+    }
+}
+
+/// Serve `project` from `files` instead of the server, replacing the files
+/// opened for it before.
+pub fn open(project: &ProjectConfig, mut files: LocalFiles) {
+    files.base = cache_key(&project.file_url(""));
+    OPENED.with(|o| o.borrow_mut().insert(project.id.clone(), files));
+}
+
+/// Whether `project` is served from the reader's files.
+pub fn is_open(project: &str) -> bool {
+    OPENED.with(|o| o.borrow().contains_key(project))
+}
+
+/// Text of a file of a local project: `None` when the URL is not in one,
+/// an error when the project has no such file (nothing is fetched).
+pub fn read(url: &str) -> Option<Result<String, String>> {
+    let key = cache_key(url);
+    OPENED.with(|o| {
+        let opened = o.borrow();
+        let files = opened.values().find(|files| key.starts_with(&files.base))?;
+        Some(
+            files
+                .texts
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| format!("{} is not among the local files", key)),
+        )
+    })
+}
+
+/// Where the browser loads a resource from: the object URL of a local
+/// image or recording, or `url` itself.
+pub fn resolve(url: &str) -> String {
+    let key = cache_key(url);
+    OPENED.with(|o| {
+        o.borrow()
+            .values()
+            .find_map(|files| files.blobs.get(&key))
+            .map(|blob| blob.to_string())
+            .unwrap_or_else(|| url.to_string())
+    })
+}
+
+/// A TEI file dropped onto the viewer, with the image dropped with it
+pub struct LocalDocument {
+    /// File name, shown as the page label
+    pub name: String,
+    pub xml: String,
+    pub doc: TeiDocument,
+    /// Facsimile dropped with it, if any
+    pub image: Option<Rc<ObjectUrl>>,
+}
+
+impl LocalDocument {
+    /// Manifest of the ad-hoc project holding the document
+    pub fn project(&self) -> ProjectConfig {
+        let mut project =
+            ProjectConfig::new(LOCAL_PROJECT.to_string(), "Documento local".to_string());
+        project.pages = vec![PageInfo::new(1)
+            .with_label(self.name.clone())
+            .with_translation(false)
+            .with_image(self.image.is_some())];
+        project
+    }
+
+    /// The document as page 1 of the ad-hoc project; the image is served
+    /// wherever its `<facsimile>` points.
+    pub fn files(&self) -> LocalFiles {
+        let project = self.project();
+        let mut files = LocalFiles::default();
+        files.add_text(&project.file_url("p1_dip.xml"), self.xml.clone());
+        if let Some(image) = &self.image {
+            let url = project.get_facsimile_path(1, &self.doc.facsimile.image_url);
+            files.add_blob(&url, image.clone());
+        }
+        files
+    }
+}
+
+/// What a dropped file is taken for, from its name and MIME type
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DroppedFile {
+    Tei,
+    Image,
+}
+
+impl DroppedFile {
+    pub fn of(name: &str, mime: &str) -> Option<Self> {
+        let extension = name.rsplit_once('.').map(|(_, e)| e.to_lowercase());
+        match extension.as_deref() {
+            Some("xml" | "tei") => Some(DroppedFile::Tei),
+            _ if mime.ends_with("/xml") => Some(DroppedFile::Tei),
+            _ if mime.starts_with("image/") => Some(DroppedFile::Image),
+            Some("jpg" | "jpeg" | "png" | "webp" | "gif" | "tif" | "tiff") => {
+                Some(DroppedFile::Image)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Files of a project folder that are read as text; anything else
+/// (images, recordings) is served from an object URL.
+fn is_text(path: &str) -> bool {
+    let extension = path.rsplit_once('.').map(|(_, e)| e.to_lowercase());
+    matches!(
+        extension.as_deref(),
+        Some("xml" | "tei" | "json" | "html" | "htm" | "txt")
+    )
+}
+
+/// Manifest of a folder without `manifest.json`, from the page files it
+/// holds (`p1_dip.xml`, `p1_trad.xml`, `images/p1.jpg`).
+pub fn project_from_files(folder: &str, paths: &[String]) -> ProjectConfig {
+    let mut pages: BTreeMap<u32, PageInfo> = BTreeMap::new();
+    for path in paths {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let Some((stem, _)) = name.rsplit_once('.') else {
+            continue;
+        };
+        let (number, kind) = stem.split_once('_').unwrap_or((stem, ""));
+        let Some(number) = number.strip_prefix('p').and_then(|n| n.parse::<u32>().ok()) else {
+            continue;
+        };
+        let image = kind.is_empty() && path.starts_with("images/") && !is_text(path);
+        if !matches!(kind, "dip" | "trad") && !image {
+            continue;
+        }
+        let page = pages.entry(number).or_insert_with(|| {
+            PageInfo::new(number)
+                .with_diplomatic(false)
+                .with_translation(false)
+                .with_image(false)
+        });
+        match kind {
+            "dip" => page.has_diplomatic = true,
+            "trad" => page.has_translation = true,
+            _ => page.has_image = true,
+        }
+    }
+    let mut project = ProjectConfig::new(folder.to_string(), folder.to_string());
+    project.pages = pages.into_values().collect();
+    project
+}
+
+/// Whether this browser can open a folder (Chromium-based browsers).
+pub fn folders_supported() -> bool {
+    window().is_some_and(|w| {
+        js_sys::Reflect::has(&w, &JsValue::from_str("showDirectoryPicker")).unwrap_or(false)
+    })
+}
+
+/// A project folder picked by the reader: its manifest (read, or made up
+/// from the page files) and its files. `Ok(None)` when the picker was
+/// dismissed.
+pub async fn pick_folder() -> Result<Option<(ProjectConfig, LocalFiles)>, String> {
+    let window = window().ok_or("No window")?;
+    // Not in web-sys' stable API yet
+    let picker = js_sys::Reflect::get(&window, &JsValue::from_str("showDirectoryPicker"))
+        .ok()
+        .and_then(|f| f.dyn_into::<js_sys::Function>().ok())
+        .ok_or("showDirectoryPicker is not supported")?;
+    let promise: js_sys::Promise = picker
+        .call0(&window)
+        .map_err(|e| format!("{:?}", e))?
+        .unchecked_into();
+    let directory: web_sys::FileSystemDirectoryHandle = match JsFuture::from(promise).await {
+        Ok(handle) => handle.unchecked_into(),
+        Err(e) => {
+            let name = js_sys::Reflect::get(&e, &JsValue::from_str("name")).ok();
+            if name.and_then(|n| n.as_string()).as_deref() == Some("AbortError") {
+                return Ok(None);
+            }
+            return Err(format!("{:?}", e));
+        }
+    };
+
+    let mut found = Vec::new();
+    list_files(&directory, String::new(), 0, &mut found).await?;
+    let manifest = found
+        .iter()
+        .find(|(path, _)| path == "manifest.json")
+        .map(|(_, file)| file.clone());
+    let mut project = match manifest {
+        Some(file) => {
+            let json = read_text(&file).await?;
+            manifest::parse(&json, "manifest.json").map_err(|e| e.to_string())?
+        }
+        None => {
+            let paths: Vec<String> = found.iter().map(|(path, _)| path.clone()).collect();
+            project_from_files(&directory.name(), &paths)
+        }
+    };
+    if project.pages.is_empty() {
+        return Err("no manifest.json and no pN_dip.xml pages".to_string());
+    }
+    project.name = format!("{} (local)", project.name);
+
+    let mut files = LocalFiles::default();
+    for (path, file) in found {
+        let url = project.file_url(&path);
+        if is_text(&path) {
+            files.add_text(&url, read_text(&file).await?);
+        } else {
+            files.add_blob(&url, Rc::new(ObjectUrl::from(gloo::file::File::from(file))));
+        }
+    }
+    Ok(Some((project, files)))
+}
+
+/// Files under `directory`, with their paths relative to the project
+/// folder. Hidden entries are skipped.
+async fn list_files(
+    directory: &web_sys::FileSystemDirectoryHandle,
+    prefix: String,
+    depth: usize,
+    found: &mut Vec<(String, web_sys::File)>,
+) -> Result<(), String> {
+    let entries = directory.values();
+    loop {
+        let next = entries.next().map_err(|e| format!("{:?}", e))?;
+        let next: js_sys::IteratorNext = JsFuture::from(next)
+            .await
+            .map_err(|e| format!("{:?}", e))?
+            .unchecked_into();
+        if next.done() {
+            return Ok(());
+        }
+        let handle: web_sys::FileSystemHandle = next.value().unchecked_into();
+        let name = handle.name();
+        if name.starts_with('.') {
+            continue;
+        }
+        let path = format!("{}{}", prefix, name);
+        match handle.kind() {
+            web_sys::FileSystemHandleKind::Directory if depth < MAX_DEPTH => {
+                let child: web_sys::FileSystemDirectoryHandle = handle.unchecked_into();
+                Box::pin(list_files(&child, format!("{}/", path), depth + 1, found)).await?;
+            }
+            web_sys::FileSystemHandleKind::File => {
+                let handle: web_sys::FileSystemFileHandle = handle.unchecked_into();
+                let file = JsFuture::from(handle.get_file())
+                    .await
+                    .map_err(|e| format!("{}: {:?}", path, e))?;
+                found.push((path, file.unchecked_into()));
+            }
+            _ => {}
+        }
+    }
+}
+
+async fn read_text(file: &web_sys::File) -> Result<String, String> {
+    let file = gloo::file::File::from(file.clone());
+    gloo::file::futures::read_as_text(&file)
+        .await
+        .map_err(|e| format!("{}: {}", file.name(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_file() {
+        assert_eq!(DroppedFile::of("p1_dip.xml", ""), Some(DroppedFile::Tei));
+        assert_eq!(DroppedFile::of("P.Oxy.TEI", ""), Some(DroppedFile::Tei));
+        assert_eq!(
+            DroppedFile::of("edition", "application/xml"),
+            Some(DroppedFile::Tei)
+        );
+        assert_eq!(
+            DroppedFile::of("scan", "image/jpeg"),
+            Some(DroppedFile::Image)
+        );
+        assert_eq!(DroppedFile::of("f2r.JPG", ""), Some(DroppedFile::Image));
+        assert_eq!(DroppedFile::of("notes.txt", "text/plain"), None);
+    }
+
+    #[test]
+    fn test_project() {
+        let local = LocalDocument {
+            name: "p1_dip.xml".to_string(),
+            xml: String::new(),
+            doc: TeiDocument::new(),
+            image: None,
+        };
+        let project = local.project();
+        assert_eq!(project.id, LOCAL_PROJECT);
+        assert_eq!(project.pages.len(), 1);
+        assert_eq!(project.pages[0].label, "p1_dip.xml");
+        assert!(!project.pages[0].has_translation);
+        assert!(!project.pages[0].has_image);
+    }
+
+    #[test]
+    fn test_project_from_files() {
+        let paths: Vec<String> = [
+            "p2_dip.xml",
+            "p1_dip.xml",
+            "p1_trad.xml",
+            "images/p1.jpg",
+            "images/p1_800.jpg",
+            "images/p10.png",
+            "glossary.xml",
+            "p3_notes.xml",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+        let project = project_from_files("PGM-XIII", &paths);
+        assert_eq!(project.id, "PGM-XIII");
+        let pages: Vec<(u32, bool, bool, bool)> = project
+            .pages
+            .iter()
+            .map(|p| (p.number, p.has_diplomatic, p.has_translation, p.has_image))
+            .collect();
+        assert_eq!(
+            pages,
+            vec![
+                (1, true, true, true),
+                (2, true, false, false),
+                (10, false, false, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read() {
+        let mut files = LocalFiles::default();
+        files.add_text("public/projects/P/p1_dip.xml", "<TEI/>".to_string());
+        open(&ProjectConfig::new("P".to_string(), "P".to_string()), files);
+        assert!(is_open("P"));
+        assert_eq!(
+            read("/public/projects/P/p1_dip.xml?v=123"),
+            Some(Ok("<TEI/>".to_string()))
+        );
+        // A file missing from the folder is not fetched
+        assert!(matches!(
+            read("/public/projects/P/p1_trad.xml"),
+            Some(Err(_))
+        ));
+        assert_eq!(read("/public/projects/PGM-XIII/p1_dip.xml"), None);
+        assert_eq!(
+            resolve("/public/projects/P/images/p1.jpg"),
+            "/public/projects/P/images/p1.jpg"
+        );
+    }
+}
@@ -1,28 +1,246 @@
 // src/main.rs
+mod alto;
+mod annotations;
+mod bibliography;
+mod bookmarks;
+mod commentary;
 mod components;
+mod corpus;
+mod corpus_search;
+mod deep_zoom;
+mod diagnostics;
+mod discovery;
+mod doc_cache;
+mod doc_diff;
+mod dts;
+mod embed;
+mod entity_index;
+mod feedback;
+mod gesture;
+mod glossary;
+mod history;
+mod i18n;
+mod idb_cache;
+mod iiif;
+mod index_locorum;
+mod legend;
+mod local_document;
+mod manifest;
+mod mirrors;
+mod net;
+mod offline;
+mod perf;
+mod persistence;
+mod places;
+mod polytonic;
 mod project_config;
+mod remote_project;
+mod route;
+mod settings;
 mod tei_data;
 mod tei_parser;
+mod tei_source;
+mod theme;
+mod timeline;
+mod toc;
+mod tour;
+mod url_state;
 mod utils;
+mod web_annotation;
+mod window_sync;
+mod word_frequency;
+mod zone_editor;
 
-use components::tei_viewer::TeiViewer;
-use gloo_net::http::Request;
-use project_config::ProjectConfig;
+use bookmarks::{Bookmark, Bookmarks};
+use components::dts_browser::DtsBrowser;
+use components::entity_index_view::EntityIndexView;
+use components::error_boundary::ErrorBoundary;
+use components::global_search::GlobalSearch;
+use components::help_dialog::HelpDialog;
+use components::image_viewer::{GalleryImage, ImageViewer};
+use components::onboarding::Onboarding;
+use components::page_grid::PageGrid;
+use components::perf_overlay::PerfOverlay;
+use components::settings_dialog::SettingsDialog;
+use components::tei_viewer::{Comparison, TeiViewer};
+use corpus::Corpus;
+use embed::Embed;
+use gloo::file::{callbacks::FileReader, ObjectUrl};
+use gloo::timers::callback::Interval;
+use gloo_events::{EventListener, EventListenerOptions};
+use history::{time_ago, History, Visit};
+use i18n::Lang;
+use local_document::{DroppedFile, LocalDocument, LocalFiles, LOCAL_PROJECT};
+use net::FetchError;
+use offline::Availability;
+use project_config::{group_pages, ProjectConfig};
+use remote_project::{Origin, Source};
+use route::Route;
+use settings::Settings;
+use std::collections::HashMap;
+use std::rc::Rc;
+use tei_data::TeiDocument;
+use theme::Theme;
+use tour::{Tour, TourStop};
 use utils::resource_url;
+use wasm_bindgen::JsCast;
 use yew::prelude::*;
+use yew_router::prelude::*;
+
+// Seconds each page stays on screen in presentation mode
+const DEFAULT_KIOSK_INTERVAL: u32 = 20;
+const MIN_KIOSK_INTERVAL: u32 = 3;
+const MAX_KIOSK_INTERVAL: u32 = 600;
+// While the collection is being fetched, refresh the views that read it
+// every this many pages (and once the last arrives)
+const CORPUS_REFRESH_EVERY: usize = 20;
 
 pub enum AppMsg {
     ChangePage(u32),
+    // Move to the previous (-1) or next (1) page of the current project
+    TurnPage(i32),
+    // Whether turning past the last page goes back to the first
+    ToggleWrapPages,
+    // Overlay with the thumbnails of every page
+    TogglePageGrid,
+    // Switch between reading the page and the gallery of photographs
+    ToggleGallery,
+    // Leave the gallery for the transcription of a page
+    ReadPage(u32),
     ChangeProject(String),
-    ManifestsLoaded(Vec<ProjectConfig>),
+    // Project whose edition of the same folio is compared ("" for none)
+    SetCompareProject(String),
+    // Projects loaded, and why the manifests of others were rejected
+    ManifestsLoaded(Vec<ProjectConfig>, Vec<manifest::ManifestError>),
     ManifestLoadFailed(String),
+    SetTheme(Theme),
+    SetLang(Lang),
+    ToggleSettings,
+    // Dialog with the shortcuts, gestures and editorial symbols
+    ToggleHelp,
+    // First-visit tour of the interface: replayed from the help, ended
+    // (and remembered) when finished or skipped
+    StartOnboarding,
+    EndOnboarding,
+    // The display options with one of them changed
+    SetSettings(Settings),
+    // Presentation (kiosk) mode: cycle pages full-screen
+    StartKiosk,
+    StopKiosk,
+    KioskTick,
+    SetKioskInterval(u32),
+    // Guided tour of the project (id, tour), absent when it ships none
+    TourLoaded(String, Option<Tour>),
+    // Move to the previous (-1) or next (1) tour stop
+    TourStep(i32),
+    EndTour,
+    // Star or unstar a page or line
+    ToggleBookmark(Bookmark),
+    ToggleBookmarksPanel,
+    // Go to the bookmark at this index of the list
+    OpenBookmark(usize),
+    RemoveBookmark(usize),
+    ToggleHistoryPanel,
+    // Go back to the visit at this index of the history
+    OpenVisit(usize),
+    // Go to a line on another page of the current project
+    OpenLine(TourStop),
+    // Go to a line of any project, from the collection-wide views
+    OpenProjectLine(String, TourStop),
+    // Fetch every page of every project for the collection-wide views
+    LoadCorpus,
+    // (project, page, edition, parsed page)
+    CorpusPageLoaded(String, u32, &'static str, Box<Result<TeiDocument, String>>),
+    // Overlay with the persons, places and divine names of the collection
+    ToggleEntityIndex,
+    // The URL changed: a route was pushed, or back/forward was used
+    RouteChanged,
+    // Whether a project's offline download is still complete
+    OfflineChecked(String, bool),
+    // Download the current project for reading without a connection
+    DownloadOffline,
+    // (project, resources done, total)
+    OfflineProgress(String, usize, usize),
+    // (project, resources that could not be fetched)
+    OfflineDownloaded(String, usize),
+    // Files are being dragged over the window (true) or left it
+    DragFiles(bool),
+    DropFiles(Vec<web_sys::File>),
+    // (file name, image dropped with it, text of the file)
+    LocalFileRead(String, Option<Rc<ObjectUrl>>, Result<String, String>),
+    DismissNotice,
+    // Manifest of the project named by `?project_url=`
+    RemoteProjectLoaded(Box<Result<ProjectConfig, String>>),
+    // Pick a project folder on the reader's computer
+    OpenFolder,
+    // The folder's manifest and files; `None` when the picker was dismissed
+    FolderOpened(Box<Result<Option<(ProjectConfig, LocalFiles)>, String>>),
+    ToggleDtsBrowser,
+    // A resource of the DTS repository was chosen
+    OpenDtsResource(String),
 }
 
 pub struct App {
-    current_project: String,
-    current_page: u32,
+    // Project and page on screen, as named in the URL (see `current`)
+    route: Route,
+    _route_listener: Option<LocationHandle>,
     available_projects: Vec<ProjectConfig>,
     loading: bool,
+    // Why no project could be loaded
+    load_error: Option<String>,
+    theme: Theme,
+    lang: Lang,
+    settings: Settings,
+    show_settings: bool,
+    show_help: bool,
+    show_onboarding: bool,
+    compare_project: Option<String>,
+    kiosk_interval: u32,
+    wrap_pages: bool,
+    show_page_grid: bool,
+    show_entity_index: bool,
+    // Pages of every project, fetched on first use
+    corpus: Corpus,
+    // PageUp/PageDown turn pages and `?` opens the help anywhere outside
+    // form fields
+    _key_listener: Option<EventListener>,
+    // Running slideshow timer and the listener that ends it when the
+    // browser leaves full-screen (e.g. Escape); `None` outside kiosk mode
+    kiosk: Option<(Interval, EventListener)>,
+    tour: Option<Tour>,
+    tour_stop: Option<usize>,
+    bookmarks: Bookmarks,
+    show_bookmarks: bool,
+    // Line of an opened bookmark or glossary occurrence (and its project),
+    // focused once its page loads
+    line_focus: Option<(String, TourStop)>,
+    history: History,
+    show_history: bool,
+    // Offline copy of each project checked or downloaded this session
+    offline: HashMap<String, Availability>,
+    // TEI file dropped onto the window, shown as the "Documento local"
+    // project until the page is reloaded
+    local: Option<LocalDocument>,
+    // Bumped whenever files are opened from the reader's computer
+    local_revision: u32,
+    // Project named by `?project_url=`, `?gh=` or `?dts=`, kept in the
+    // address as pages turn
+    remote: Option<remote_project::Source>,
+    // Browsing the collections of the DTS repository named by `?dts=`
+    show_dts: bool,
+    // Shown inside another site's iframe (`?embed=1`): the viewer alone
+    embed: Option<Embed>,
+    // Accent color of the project on screen, as applied to the document
+    accent: Option<String>,
+    // Its project has not arrived yet: the route may name it
+    remote_pending: bool,
+    // Files are being dragged over the window: show where to drop them
+    dragging_files: bool,
+    // Why dropped files, a folder or a remote project could not be opened
+    notice: Option<String>,
+    _local_reader: Option<FileReader>,
+    // dragover, dragleave and drop on the document
+    _drop_listeners: Vec<EventListener>,
 }
 
 impl Component for App {
@@ -33,58 +251,702 @@ impl Component for App {
         // Start loading manifests
         ctx.link().send_future(async {
             match load_all_manifests().await {
-                Ok(configs) => AppMsg::ManifestsLoaded(configs),
+                Ok((configs, errors)) => AppMsg::ManifestsLoaded(configs, errors),
                 Err(e) => AppMsg::ManifestLoadFailed(e),
             }
         });
 
+        // Without a stored choice the stylesheet follows prefers-color-scheme
+        if let Some(theme) = Theme::stored() {
+            theme.select();
+        }
+        let lang = Lang::current();
+        lang.apply();
+        let settings = Settings::stored();
+        settings.apply();
+
+        let key_listener = web_sys::window()
+            .and_then(|w| w.document())
+            .map(|document| {
+                let link = ctx.link().clone();
+                let options = EventListenerOptions::enable_prevent_default();
+                EventListener::new_with_options(&document, "keydown", options, move |e| {
+                    let Some(e) = e.dyn_ref::<web_sys::KeyboardEvent>() else {
+                        return;
+                    };
+                    let msg = match e.key().as_str() {
+                        "PageUp" => AppMsg::TurnPage(-1),
+                        "PageDown" => AppMsg::TurnPage(1),
+                        "?" => AppMsg::ToggleHelp,
+                        _ => return,
+                    };
+                    let in_field = e
+                        .target()
+                        .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+                        .is_some_and(|el| {
+                            matches!(el.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+                                || el.closest("[contenteditable]").ok().flatten().is_some()
+                        });
+                    if in_field || e.alt_key() || e.ctrl_key() || e.meta_key() {
+                        return;
+                    }
+                    e.prevent_default();
+                    link.send_message(msg);
+                })
+            });
+
+        let embed = embed::requested();
+        let remote = remote_project::requested();
+        // A DTS repository without a resource opens on its collections
+        let show_dts = matches!(
+            remote.as_ref().map(|r| &r.origin),
+            Some(Origin::Dts { resource: None, .. })
+        );
+        if let Some(origin) = remote
+            .as_ref()
+            .map(|r| r.origin.clone())
+            .filter(|_| !show_dts)
+        {
+            ctx.link().send_future(async move {
+                AppMsg::RemoteProjectLoaded(Box::new(remote_project::load(origin).await))
+            });
+        }
+
+        let drop_listeners = web_sys::window()
+            .and_then(|w| w.document())
+            .map(|document| Self::drop_listeners(ctx, &document))
+            .unwrap_or_default();
+
         Self {
-            current_project: String::new(),
-            current_page: 1,
+            route: ctx
+                .link()
+                .route::<Route>()
+                .filter(|route| *route != Route::Home)
+                .or_else(|| embed.as_ref().and_then(Embed::route))
+                .unwrap_or(Route::Home),
+            _route_listener: ctx
+                .link()
+                .add_location_listener(ctx.link().callback(|_| AppMsg::RouteChanged)),
             available_projects: Vec::new(),
             loading: true,
+            load_error: None,
+            theme: Theme::current(),
+            lang,
+            settings,
+            show_settings: false,
+            show_help: false,
+            show_onboarding: !persistence::load(persistence::ONBOARDING_DONE).unwrap_or(false),
+            compare_project: None,
+            kiosk_interval: persistence::load::<u32>(persistence::KIOSK_INTERVAL)
+                .map(|s| s.clamp(MIN_KIOSK_INTERVAL, MAX_KIOSK_INTERVAL))
+                .unwrap_or(DEFAULT_KIOSK_INTERVAL),
+            wrap_pages: persistence::load(persistence::PAGE_WRAP).unwrap_or(false),
+            _key_listener: key_listener,
+            show_page_grid: false,
+            show_entity_index: false,
+            corpus: Corpus::default(),
+            kiosk: None,
+            tour: None,
+            tour_stop: None,
+            bookmarks: Bookmarks::load(),
+            show_bookmarks: false,
+            line_focus: None,
+            history: History::load(),
+            show_history: false,
+            offline: HashMap::new(),
+            local: None,
+            local_revision: 0,
+            remote_pending: remote.is_some() && !show_dts,
+            remote,
+            show_dts,
+            embed,
+            accent: None,
+            dragging_files: false,
+            notice: None,
+            _local_reader: None,
+            _drop_listeners: drop_listeners,
         }
     }
 
-    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             AppMsg::ChangePage(page) => {
-                self.current_page = page;
+                self.navigate(ctx, self.current_project(), page);
+                std::mem::take(&mut self.show_page_grid)
+            }
+            AppMsg::TurnPage(step) => {
+                let target = self
+                    .current()
+                    .and_then(|(p, page)| p.step_page(page, step, self.wrap_pages));
+                if let Some(page) = target {
+                    self.navigate(ctx, self.current_project(), page);
+                }
+                false
+            }
+            AppMsg::TogglePageGrid => {
+                self.show_page_grid = !self.show_page_grid;
+                true
+            }
+            AppMsg::ToggleGallery => {
+                let route = Route::of_page(
+                    self.current_project(),
+                    self.current_page(),
+                    !self.route.is_gallery(),
+                );
+                self.go(ctx, &route, false);
+                false
+            }
+            AppMsg::ReadPage(page) => {
+                self.go(
+                    ctx,
+                    &Route::of_page(self.current_project(), page, false),
+                    false,
+                );
+                false
+            }
+            AppMsg::ToggleWrapPages => {
+                self.wrap_pages = !self.wrap_pages;
+                persistence::save(persistence::PAGE_WRAP, self.wrap_pages);
                 true
             }
             AppMsg::ChangeProject(project) => {
-                self.current_project = project;
-                // Reset to first page when changing projects
-                self.current_page = 1;
+                // Start at the first page of the new project
+                let first = self
+                    .available_projects
+                    .iter()
+                    .find(|p| p.id == project)
+                    .and_then(|p| p.pages.first())
+                    .map_or(1, |p| p.number);
+                self.navigate(ctx, &project, first);
+                false
+            }
+            AppMsg::SetCompareProject(project) => {
+                self.compare_project = (!project.is_empty()).then_some(project);
                 true
             }
-            AppMsg::ManifestsLoaded(configs) => {
-                self.available_projects = configs;
+            AppMsg::ManifestsLoaded(configs, errors) => {
+                if !errors.is_empty() {
+                    let errors: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                    self.notice = Some(
+                        self.lang
+                            .tf("Manifiestos no válidos: {}", &[&errors.join("; ")]),
+                    );
+                }
+                // A remote project may have arrived first
+                for project in std::mem::replace(&mut self.available_projects, configs) {
+                    self.add_project(project);
+                }
                 self.loading = false;
 
-                // Set the first project as current if available
-                if let Some(first) = self.available_projects.first() {
-                    self.current_project = first.id.clone();
+                // Links from before routing name the project and page in
+                // the hash; move them to the path, keeping the rest of the view
+                let mut linked = url_state::current();
+                if self.route == Route::Home {
+                    if let Some(project) = linked.project.take() {
+                        self.route = match linked.page.take() {
+                            Some(page) => Route::Page { project, page },
+                            None => Route::Project { project },
+                        };
+                        self.go(ctx, &self.route, true);
+                        url_state::replace(&linked);
+                    }
                 }
+                self.canonicalize(ctx);
+                self.load_tour(ctx);
+                self.check_offline(ctx);
                 true
             }
             AppMsg::ManifestLoadFailed(error) => {
                 log::error!("Failed to load manifests: {}", error);
                 self.loading = false;
+                self.load_error = Some(error);
                 true
             }
+            AppMsg::SetTheme(theme) => {
+                self.theme = theme;
+                theme.select();
+                true
+            }
+            AppMsg::SetLang(lang) => {
+                self.lang = lang;
+                lang.select();
+                true
+            }
+            AppMsg::ToggleSettings => {
+                self.show_settings = !self.show_settings;
+                true
+            }
+            AppMsg::ToggleHelp => {
+                self.show_help = !self.show_help;
+                true
+            }
+            AppMsg::StartOnboarding => {
+                self.show_help = false;
+                self.show_onboarding = true;
+                true
+            }
+            AppMsg::EndOnboarding => {
+                self.show_onboarding = false;
+                persistence::save(persistence::ONBOARDING_DONE, true);
+                true
+            }
+            AppMsg::SetSettings(settings) => {
+                settings.select();
+                self.settings = settings;
+                true
+            }
+            AppMsg::StartKiosk => {
+                let Some(document) = web_sys::window().and_then(|w| w.document()) else {
+                    return false;
+                };
+                if let Some(root) = document.document_element() {
+                    let _ = root.request_fullscreen();
+                }
+                let link = ctx.link().clone();
+                let timer = Interval::new(self.kiosk_interval * 1000, move || {
+                    link.send_message(AppMsg::KioskTick)
+                });
+                let link = ctx.link().clone();
+                let exit_listener = EventListener::new(&document, "fullscreenchange", move |_| {
+                    let still_fullscreen = web_sys::window()
+                        .and_then(|w| w.document())
+                        .and_then(|d| d.fullscreen_element())
+                        .is_some();
+                    if !still_fullscreen {
+                        link.send_message(AppMsg::StopKiosk);
+                    }
+                });
+                self.kiosk = Some((timer, exit_listener));
+                true
+            }
+            AppMsg::StopKiosk => {
+                if self.kiosk.take().is_none() {
+                    return false;
+                }
+                if let Some(document) = web_sys::window().and_then(|w| w.document()) {
+                    if document.fullscreen_element().is_some() {
+                        document.exit_fullscreen();
+                    }
+                }
+                true
+            }
+            AppMsg::KioskTick => {
+                // Loop back to the first page after the last one
+                let Some((project, page)) = self.current() else {
+                    return false;
+                };
+                let target = project
+                    .adjacent_page(page, 1)
+                    .or_else(|| project.pages.first().map(|first| first.number));
+                // Replace rather than push, so an unattended slideshow does
+                // not fill the browser history
+                if let Some(target) = target.filter(|t| *t != page) {
+                    let route = Route::Page {
+                        project: project.id.clone(),
+                        page: target,
+                    };
+                    self.go(ctx, &route, true);
+                }
+                false
+            }
+            AppMsg::SetKioskInterval(seconds) => {
+                self.kiosk_interval = seconds.clamp(MIN_KIOSK_INTERVAL, MAX_KIOSK_INTERVAL);
+                persistence::save(persistence::KIOSK_INTERVAL, self.kiosk_interval);
+                true
+            }
+            AppMsg::TourLoaded(project, tour) => {
+                if project != self.current_project() {
+                    return false;
+                }
+                self.tour = tour;
+                true
+            }
+            AppMsg::TourStep(step) => {
+                let Some(tour) = &self.tour else {
+                    return false;
+                };
+                let Some(index) = tour.step(self.tour_stop, step) else {
+                    return false;
+                };
+                let page = tour.stops[index].page;
+                self.tour_stop = Some(index);
+                self.line_focus = None;
+                self.navigate(ctx, self.current_project(), page);
+                true
+            }
+            AppMsg::EndTour => {
+                self.tour_stop = None;
+                true
+            }
+            AppMsg::ToggleBookmark(bookmark) => {
+                self.bookmarks.toggle(bookmark);
+                self.bookmarks.save();
+                true
+            }
+            AppMsg::ToggleBookmarksPanel => {
+                self.show_bookmarks = !self.show_bookmarks;
+                self.show_history = false;
+                true
+            }
+            AppMsg::ToggleHistoryPanel => {
+                self.show_history = !self.show_history;
+                self.show_bookmarks = false;
+                true
+            }
+            AppMsg::OpenVisit(index) => {
+                let Some(visit) = self.history.visits.get(index).cloned() else {
+                    return false;
+                };
+                if !self
+                    .available_projects
+                    .iter()
+                    .any(|p| p.id == visit.project)
+                {
+                    return false;
+                }
+                self.navigate(ctx, &visit.project, visit.page);
+                self.tour_stop = None;
+                self.line_focus = None;
+                self.show_history = false;
+                true
+            }
+            AppMsg::OpenBookmark(index) => {
+                let Some(bookmark) = self.bookmarks.items.get(index).cloned() else {
+                    return false;
+                };
+                if !self
+                    .available_projects
+                    .iter()
+                    .any(|p| p.id == bookmark.project)
+                {
+                    return false;
+                }
+                self.navigate(ctx, &bookmark.project, bookmark.page);
+                self.tour_stop = None;
+                self.line_focus = bookmark.line.map(|line| {
+                    let stop = TourStop {
+                        page: bookmark.page,
+                        zone: None,
+                        line: Some(line),
+                        caption: String::new(),
+                    };
+                    (bookmark.project.clone(), stop)
+                });
+                self.show_bookmarks = false;
+                true
+            }
+            AppMsg::OpenLine(stop) => {
+                let project = self.current_project().to_string();
+                Component::update(self, ctx, AppMsg::OpenProjectLine(project, stop))
+            }
+            AppMsg::OpenProjectLine(project, stop) => {
+                self.show_entity_index = false;
+                self.navigate(ctx, &project, stop.page);
+                self.tour_stop = None;
+                self.line_focus = Some((project, stop));
+                true
+            }
+            AppMsg::LoadCorpus => {
+                self.load_corpus(ctx);
+                true
+            }
+            AppMsg::CorpusPageLoaded(project, page, edition, result) => {
+                self.corpus.pending = self.corpus.pending.saturating_sub(1);
+                match *result {
+                    Ok(doc) => self.corpus.insert((project, page, edition), doc),
+                    Err(e) => {
+                        log::warn!("Failed to load {} p{} {}: {}", project, page, edition, e);
+                        self.corpus.failed += 1;
+                    }
+                }
+                // Re-render (and re-scan) in batches rather than per page
+                self.corpus.pending.is_multiple_of(CORPUS_REFRESH_EVERY)
+            }
+            AppMsg::ToggleEntityIndex => {
+                self.show_entity_index = !self.show_entity_index;
+                if self.show_entity_index {
+                    self.load_corpus(ctx);
+                }
+                true
+            }
+            AppMsg::RemoveBookmark(index) => {
+                self.bookmarks.remove(index);
+                self.bookmarks.save();
+                true
+            }
+            AppMsg::RouteChanged => {
+                let previous = self.current_project().to_string();
+                self.route = ctx.link().route::<Route>().unwrap_or(Route::Home);
+                self.canonicalize(ctx);
+                let (project, page) = (self.current_project().to_string(), self.current_page());
+                if project != previous {
+                    self.open_project(ctx);
+                }
+                // A focused line belongs to the page it was opened on
+                if self
+                    .line_focus
+                    .as_ref()
+                    .is_some_and(|(p, stop)| *p != project || stop.page != page)
+                {
+                    self.line_focus = None;
+                }
+                true
+            }
+            AppMsg::OfflineChecked(project, available) => {
+                let state = self.offline.entry(project).or_default();
+                if *state != Availability::Unknown {
+                    return false;
+                }
+                *state = if available {
+                    Availability::Available
+                } else {
+                    Availability::Partial
+                };
+                true
+            }
+            AppMsg::DownloadOffline => {
+                let Some((project, _)) = self.current() else {
+                    return false;
+                };
+                let project = project.clone();
+                let id = project.id.clone();
+                if matches!(
+                    self.offline.get(&id),
+                    Some(Availability::Downloading { .. })
+                ) {
+                    return false;
+                }
+                self.offline
+                    .insert(id.clone(), Availability::Downloading { done: 0, total: 0 });
+                let on_progress = {
+                    let id = id.clone();
+                    ctx.link().callback(move |(done, total)| {
+                        AppMsg::OfflineProgress(id.clone(), done, total)
+                    })
+                };
+                ctx.link().send_future(async move {
+                    let failed = offline::download_project(project, on_progress).await;
+                    AppMsg::OfflineDownloaded(id, failed)
+                });
+                true
+            }
+            AppMsg::OfflineProgress(project, done, total) => {
+                self.offline
+                    .insert(project, Availability::Downloading { done, total });
+                true
+            }
+            AppMsg::OfflineDownloaded(project, failed) => {
+                let state = if failed == 0 {
+                    Availability::Available
+                } else {
+                    Availability::Incomplete { failed }
+                };
+                self.offline.insert(project, state);
+                true
+            }
+            AppMsg::DragFiles(dragging) => {
+                let changed = self.dragging_files != dragging;
+                self.dragging_files = dragging;
+                changed
+            }
+            AppMsg::DropFiles(files) => {
+                self.dragging_files = false;
+                let kind = |file: &web_sys::File| DroppedFile::of(&file.name(), &file.type_());
+                let tei = files.iter().find(|f| kind(f) == Some(DroppedFile::Tei));
+                let image = files
+                    .iter()
+                    .find(|f| kind(f) == Some(DroppedFile::Image))
+                    .map(|f| Rc::new(ObjectUrl::from(gloo::file::File::from(f.clone()))));
+                match (tei, image) {
+                    (Some(file), image) => {
+                        let name = file.name();
+                        let link = ctx.link().clone();
+                        let file = gloo::file::File::from(file.clone());
+                        self._local_reader =
+                            Some(gloo::file::callbacks::read_as_text(&file, move |res| {
+                                link.send_message(AppMsg::LocalFileRead(
+                                    name,
+                                    image,
+                                    res.map_err(|e| e.to_string()),
+                                ))
+                            }));
+                        self.notice = None;
+                    }
+                    // An image alone goes with the document already open
+                    (None, Some(image)) if self.local.is_some() => {
+                        let local = self.local.as_ref().map(|local| LocalDocument {
+                            name: local.name.clone(),
+                            xml: local.xml.clone(),
+                            doc: local.doc.clone(),
+                            image: Some(image),
+                        });
+                        if let Some(local) = local {
+                            self.open_local(ctx, local);
+                        }
+                        self.notice = None;
+                    }
+                    _ => {
+                        self.notice = Some(
+                            self.lang
+                                .t("Suelte un archivo TEI XML y, si lo desea, la imagen de su página.")
+                                .to_string(),
+                        );
+                    }
+                }
+                true
+            }
+            AppMsg::LocalFileRead(name, image, res) => {
+                self._local_reader = None;
+                let parsed =
+                    res.and_then(|xml| tei_parser::parse_tei_xml(&xml).map(|doc| (xml, doc)));
+                match parsed {
+                    Ok((xml, doc)) => {
+                        // Keep the image of the document it replaces
+                        let image =
+                            image.or_else(|| self.local.as_ref().and_then(|l| l.image.clone()));
+                        self.open_local(
+                            ctx,
+                            LocalDocument {
+                                name,
+                                xml,
+                                doc,
+                                image,
+                            },
+                        );
+                    }
+                    Err(e) => {
+                        log::warn!("Could not open {}: {}", name, e);
+                        self.notice = Some(self.lang.tf("No se pudo abrir {}: {}", &[&name, &e]));
+                    }
+                }
+                true
+            }
+            AppMsg::DismissNotice => {
+                self.notice = None;
+                true
+            }
+            AppMsg::RemoteProjectLoaded(result) => {
+                self.remote_pending = false;
+                match *result {
+                    Ok(project) => {
+                        let id = project.id.clone();
+                        let first = project.pages.first().map_or(1, |p| p.number);
+                        self.add_project(project);
+                        if self.route.project() == Some(id.as_str()) {
+                            // Shown in place of the fallback project
+                            self.canonicalize(ctx);
+                            self.open_project(ctx);
+                        } else {
+                            let route = Route::Page {
+                                project: id,
+                                page: first,
+                            };
+                            self.go(ctx, &route, true);
+                        }
+                    }
+                    Err(e) => {
+                        log::warn!("Could not load the remote project: {}", e);
+                        let named = self.remote.as_ref().map(|r| r.name()).unwrap_or_default();
+                        // A DTS repository stays open for another choice
+                        self.remote = self.remote.take().and_then(|r| match r.origin {
+                            Origin::Dts { endpoint, .. } => Some(Source::dts(&endpoint, None)),
+                            Origin::Manifest(_) => None,
+                        });
+                        self.notice = Some(
+                            self.lang
+                                .tf("No se pudo cargar el proyecto de {}: {}", &[&named, &e]),
+                        );
+                        self.canonicalize(ctx);
+                    }
+                }
+                true
+            }
+            AppMsg::ToggleDtsBrowser => {
+                self.show_dts = !self.show_dts;
+                true
+            }
+            AppMsg::OpenDtsResource(resource) => {
+                let Some(Origin::Dts { endpoint, .. }) = self.remote.as_ref().map(|r| &r.origin)
+                else {
+                    return false;
+                };
+                let source = Source::dts(endpoint, Some(&resource));
+                let origin = source.origin.clone();
+                self.remote = Some(source);
+                self.remote_pending = true;
+                self.show_dts = false;
+                ctx.link().send_future(async move {
+                    AppMsg::RemoteProjectLoaded(Box::new(remote_project::load(origin).await))
+                });
+                true
+            }
+            AppMsg::OpenFolder => {
+                ctx.link().send_future(async {
+                    AppMsg::FolderOpened(Box::new(local_document::pick_folder().await))
+                });
+                false
+            }
+            AppMsg::FolderOpened(result) => match *result {
+                Ok(None) => false,
+                Ok(Some((project, files))) => {
+                    local_document::open(&project, files);
+                    self.notice = None;
+                    let first = project.pages.first().map_or(1, |p| p.number);
+                    let id = project.id.clone();
+                    self.add_project(project);
+                    self.navigate(ctx, &id, first);
+                    true
+                }
+                Err(e) => {
+                    log::warn!("Could not open the folder: {}", e);
+                    self.notice = Some(self.lang.tf("No se pudo abrir la carpeta: {}", &[&e]));
+                    true
+                }
+            },
         }
     }
 
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        let accent = self
+            .current()
+            .and_then(|(project, _)| project.branding.accent_color.clone());
+        if accent != self.accent {
+            theme::apply_accent(accent.as_deref());
+            self.accent = accent;
+        }
+        // Log the page on screen; presentation mode cycles pages unattended
+        if self.loading || self.kiosk.is_some() {
+            return;
+        }
+        let Some((project, number)) = self.current() else {
+            return;
+        };
+        // Local files cannot be reopened from the history
+        if local_document::is_open(&project.id) || self.history.is_latest(&project.id, number) {
+            return;
+        }
+        let Some(page) = project.get_page(number) else {
+            return;
+        };
+        self.history.record(Visit {
+            project: project.id.clone(),
+            page: number,
+            page_label: page.label.clone(),
+            visited: js_sys::Date::now(),
+        });
+        self.history.save();
+    }
+
     fn view(&self, ctx: &Context<Self>) -> Html {
+        let lang = self.lang;
         if self.loading {
             return html! {
                 <div class="app-container">
                     <header class="app-header">
-                        <h1>{"Visualizador TEI-XML"}</h1>
+                        <h1>{ lang.t("Visualizador TEI-XML") }</h1>
                     </header>
                     <main class="app-main">
-                        <div class="loading">{"Cargando proyectos..."}</div>
+                        <div class="loading">{ lang.t("Cargando proyectos...") }</div>
                     </main>
                 </div>
             };
@@ -94,10 +956,17 @@ impl Component for App {
             return html! {
                 <div class="app-container">
                     <header class="app-header">
-                        <h1>{"Visualizador TEI-XML"}</h1>
+                        <h1>{ lang.t("Visualizador TEI-XML") }</h1>
                     </header>
                     <main class="app-main">
-                        <div class="error">{"No se encontraron proyectos. Por favor, asegúrese de que los archivos manifest.json estén presentes en la carpeta public/projects/"}</div>
+                        <div class="error">
+                            <p>{ lang.t("No se encontraron proyectos. Por favor, asegúrese de que public/projects/index.json liste las carpetas de los proyectos y que cada una tenga su manifest.json.") }</p>
+                            { if let Some(error) = &self.load_error {
+                                html! { <p>{ error.clone() }</p> }
+                            } else {
+                                html! {}
+                            } }
+                        </div>
                     </main>
                 </div>
             };
@@ -105,37 +974,214 @@ impl Component for App {
 
         let on_page_change = ctx.link().callback(AppMsg::ChangePage);
         let on_project_change = ctx.link().callback(AppMsg::ChangeProject);
+        let on_turn_page = ctx.link().callback(AppMsg::TurnPage);
+        let on_start_kiosk = ctx.link().callback(|_| AppMsg::StartKiosk);
+        let on_kiosk_interval = ctx.link().batch_callback(|e: Event| {
+            e.target_dyn_into::<web_sys::HtmlInputElement>()
+                .and_then(|input| input.value().parse::<u32>().ok())
+                .map(AppMsg::SetKioskInterval)
+        });
 
-        // Find current project config
-        let current_project_config = self
-            .available_projects
-            .iter()
-            .find(|p| p.id == self.current_project)
-            .cloned();
+        // Project and page named in the URL
+        let current_project_config = self.current().map(|(p, _)| p.clone());
+        let current_project = self.current_project().to_string();
+        let current_page = self.current_page();
 
         let current_project_name = current_project_config
             .as_ref()
             .map(|p| p.name.clone())
-            .unwrap_or_else(|| self.current_project.clone());
+            .unwrap_or_else(|| current_project.clone());
+
+        let step_target = |step: i32| {
+            current_project_config
+                .as_ref()
+                .and_then(|p| p.step_page(current_page, step, self.wrap_pages))
+        };
 
         // Get available pages for current project
         let available_pages = current_project_config
             .as_ref()
             .map(|p| p.pages.clone())
             .unwrap_or_default();
+        let sections = current_project_config
+            .as_ref()
+            .map(|p| p.sections.clone())
+            .unwrap_or_default();
+
+        let focus = self
+            .tour
+            .as_ref()
+            .zip(self.tour_stop)
+            .map(|(tour, index)| &tour.stops[index])
+            .or(self.line_focus.as_ref().map(|(_, stop)| stop));
+
+        // Align the compared project's page by label
+        let compare = self.compare_project.as_ref().and_then(|id| {
+            let other = self.available_projects.iter().find(|p| &p.id == id)?;
+            let label = current_project_config
+                .as_ref()
+                .and_then(|p| p.get_page(current_page))
+                .map(|p| p.label.clone())?;
+            Some(Comparison {
+                config: other.clone(),
+                page: other.page_by_label(&label).map(|p| p.number),
+            })
+        });
+
+        let viewer = if self.route.is_gallery() {
+            html! {
+                <ErrorBoundary {lang} name="la galería">
+                <ImageViewer
+                    {lang}
+                    images={self.gallery_images()}
+                    current={current_page}
+                    on_select={on_page_change.clone()}
+                    on_open={ctx.link().callback(AppMsg::ReadPage)}
+                    on_close={ctx.link().callback(|_| AppMsg::ToggleGallery)}
+                />
+                </ErrorBoundary>
+            }
+        } else {
+            html! {
+            <ErrorBoundary
+                {lang}
+                name="el documento"
+                scope={format!("{}/{}", current_project, current_page)}
+            >
+            <TeiViewer
+                {lang}
+                settings={self.settings.clone()}
+                project={current_project.clone()}
+                page={current_page}
+                config={current_project_config.clone().unwrap_or_else(|| {
+                    ProjectConfig::new(current_project.clone(), current_project_name.clone())
+                })}
+                page_info={current_project_config
+                    .as_ref()
+                    .and_then(|p| p.get_page(current_page))
+                    .cloned()}
+                on_turn_page={on_turn_page.clone()}
+                pages={available_pages.iter().map(|p| p.number).collect::<Vec<_>>()}
+                kiosk={self.kiosk.is_some()}
+                focus={focus.cloned()}
+                {compare}
+                bookmarks={self.bookmarks.clone()}
+                on_toggle_bookmark={ctx.link().callback(AppMsg::ToggleBookmark)}
+                on_open_line={ctx.link().callback(AppMsg::OpenLine)}
+                on_follow={ctx.link().callback(|(project, stop)| AppMsg::OpenProjectLine(project, stop))}
+                outline={current_project_config
+                    .as_ref()
+                    .map(|p| p.contents())
+                    .unwrap_or_default()}
+                editions={current_project_config
+                    .as_ref()
+                    .map(|p| p.editions.clone())
+                    .unwrap_or_default()}
+                legend={current_project_config
+                    .as_ref()
+                    .map(|p| p.legend.clone())
+                    .unwrap_or_default()}
+                default_view={current_project_config
+                    .as_ref()
+                    .map(|p| p.default_view.clone())
+                    .unwrap_or_default()}
+                date_range={current_project_config
+                    .as_ref()
+                    .map(|p| p.metadata.date_range.clone())
+                    .unwrap_or_default()}
+                project_name={current_project_name.clone()}
+                project_metadata={current_project_config
+                    .as_ref()
+                    .map(|p| p.metadata.clone())
+                    .unwrap_or_default()}
+                local_revision={self.local_revision}
+                panels={self.embed.as_ref().map(|e| e.panels).unwrap_or_default()}
+            />
+            </ErrorBoundary>
+            }
+        };
+
+        if self.embed.is_some() {
+            let full_view = web_sys::window()
+                .and_then(|w| w.location().href().ok())
+                .map(|href| embed::full_view_url(&href))
+                .unwrap_or_default();
+            return html! {
+                <div class="app-container embed">
+                    { self.render_notice(ctx) }
+                    <main class="app-main">{ viewer }</main>
+                    <a class="embed-full-view" href={full_view} target="_blank" rel="noopener">
+                        { lang.tf("{} · Abrir en el visor ↗", &[&current_project_name]) }
+                    </a>
+                </div>
+            };
+        }
+
+        if self.kiosk.is_some() {
+            let page_label = available_pages
+                .iter()
+                .find(|p| p.number == current_page)
+                .map(|p| p.label.clone())
+                .unwrap_or_else(|| current_page.to_string());
+            let on_stop_kiosk = ctx.link().callback(|_| AppMsg::StopKiosk);
+            return html! {
+                <div class="app-container kiosk">
+                    <main class="app-main">{ viewer }</main>
+                    <div class="kiosk-caption">
+                        <span class="kiosk-page">{ page_label }</span>
+                        <span class="kiosk-project">{ current_project_name }</span>
+                        <button class="kiosk-exit" onclick={on_stop_kiosk} title={lang.t("Salir de la presentación")}>{"✕"}</button>
+                    </div>
+                </div>
+            };
+        }
 
         html! {
             <div class="app-container">
                 <header class="app-header">
-                    <h1>{"Visualizador TEI-XML"}</h1>
-                    <p class="subtitle">{format!("Visualizador interactivo - {}", current_project_name)}</p>
-                    <p class="subtitle">{format!("Gracias Federico uwu")}</p>
+                    <div class="theme-toggles">
+                        <GlobalSearch
+                            {lang}
+                            projects={self.available_projects.clone()}
+                            corpus={self.corpus.clone()}
+                            on_load={ctx.link().callback(|_| AppMsg::LoadCorpus)}
+                            on_open={ctx.link().callback(|(project, stop)| AppMsg::OpenProjectLine(project, stop))}
+                        />
+                        <button
+                            class={classes!("theme-toggle", self.show_entity_index.then_some("active"))}
+                            onclick={ctx.link().callback(|_| AppMsg::ToggleEntityIndex)}
+                            title={lang.t("Personas, lugares y entidades divinas de todos los proyectos")}
+                        >
+                            { lang.t("📇 Índices") }
+                        </button>
+                        { self.render_history(ctx) }
+                        { self.render_bookmarks(ctx) }
+                        <button
+                            class={classes!("theme-toggle", self.show_settings.then_some("active"))}
+                            onclick={ctx.link().callback(|_| AppMsg::ToggleSettings)}
+                            title={lang.t("Tema, tipografía, lecturas, disposición e idioma")}
+                            aria-expanded={self.show_settings.to_string()}
+                        >
+                            { lang.t("⚙ Ajustes") }
+                        </button>
+                        <button
+                            class={classes!("theme-toggle", self.show_help.then_some("active"))}
+                            onclick={ctx.link().callback(|_| AppMsg::ToggleHelp)}
+                            title={lang.t("Ayuda: atajos de teclado, gestos y signos editoriales (?)")}
+                            aria-expanded={self.show_help.to_string()}
+                        >
+                            {"?"}
+                        </button>
+                    </div>
+                    { self.render_branding() }
+                    { self.render_breadcrumbs(ctx) }
                 </header>
 
                 <main class="app-main">
                     <div class="selectors-container">
                         <div class="project-selector">
-                            <label for="project-select">{"Proyecto: "}</label>
+                            { self.render_project_cover() }
+                            <label for="project-select">{ lang.t("Proyecto: ") }</label>
                             <select
                                 id="project-select"
                                 onchange={
@@ -152,17 +1198,81 @@ impl Component for App {
                                     html! {
                                         <option
                                             value={project.id.clone()}
-                                            selected={&self.current_project == &project.id}
+                                            selected={current_project == project.id}
                                         >
                                             {project.name.clone()}
                                         </option>
                                     }
                                 })}
                             </select>
+                            { self.render_offline(ctx) }
+                            { if self.dts_endpoint().is_some() {
+                                html! {
+                                    <button
+                                        class="open-dts"
+                                        onclick={ctx.link().callback(|_| AppMsg::ToggleDtsBrowser)}
+                                        title={lang.t("Elegir otro texto del repositorio DTS")}
+                                    >
+                                        { lang.t("📚 Repositorio") }
+                                    </button>
+                                }
+                            } else {
+                                html! {}
+                            } }
+                            { if local_document::folders_supported() {
+                                html! {
+                                    <button
+                                        class="open-folder"
+                                        onclick={ctx.link().callback(|_| AppMsg::OpenFolder)}
+                                        title={lang.t("Abrir una carpeta de proyecto de este ordenador (manifest.json, XML e imágenes) sin subirla")}
+                                    >
+                                        { lang.t("📂 Abrir carpeta") }
+                                    </button>
+                                }
+                            } else {
+                                html! {}
+                            } }
                         </div>
 
+                        { if self.available_projects.len() > 1 {
+                            let on_compare = ctx.link().batch_callback(|e: Event| {
+                                e.target_dyn_into::<web_sys::HtmlSelectElement>()
+                                    .map(|select| AppMsg::SetCompareProject(select.value()))
+                            });
+                            html! {
+                                <div class="project-selector compare-selector">
+                                    <label for="compare-select">{ lang.t("Comparar con: ") }</label>
+                                    <select id="compare-select" onchange={on_compare}>
+                                        <option value="" selected={self.compare_project.is_none()}>{"—"}</option>
+                                        { for self.available_projects.iter()
+                                            .filter(|p| p.id != current_project && p.id != LOCAL_PROJECT)
+                                            .map(|project| html! {
+                                                <option
+                                                    value={project.id.clone()}
+                                                    selected={self.compare_project.as_ref() == Some(&project.id)}
+                                                >
+                                                    {project.name.clone()}
+                                                </option>
+                                            }) }
+                                    </select>
+                                </div>
+                            }
+                        } else {
+                            html! {}
+                        } }
+
                         <div class="page-selector">
-                            <label for="page-select">{"Página: "}</label>
+                            <label for="page-select">{ lang.t("Página: ") }</label>
+                            <button
+                                class="page-step"
+                                onclick={on_turn_page.reform(|_: MouseEvent| -1)}
+                                disabled={step_target(-1).is_none()}
+                                title={lang.t("Página anterior (RePág)")}
+                                aria-label={lang.t("Página anterior")}
+                            >
+                                {"‹"}
+                                { self.render_step_preview(step_target(-1)) }
+                            </button>
                             <select
                                 id="page-select"
                                 onchange={
@@ -177,24 +1287,195 @@ impl Component for App {
                                     })
                                 }
                             >
-                                {for available_pages.iter().map(|page_info| {
-                                    html! {
-                                        <option
-                                            value={page_info.number.to_string()}
-                                            selected={self.current_page == page_info.number}
-                                        >
-                                            {format!("{}", page_info.label)}
-                                        </option>
+                                {for group_pages(&available_pages, &sections).into_iter().map(|(section, pages)| {
+                                    let options = html! {
+                                        {for pages.into_iter().map(|page_info| {
+                                            // Options hold text only: what the page
+                                            // lacks follows its label
+                                            let missing: Vec<&str> = page_info
+                                                .missing_content()
+                                                .into_iter()
+                                                .map(|m| lang.t(m))
+                                                .collect();
+                                            let label = if missing.is_empty() {
+                                                page_info.label.clone()
+                                            } else {
+                                                format!("{} ({})", page_info.label, missing.join(", "))
+                                            };
+                                            html! {
+                                                <option
+                                                    value={page_info.number.to_string()}
+                                                    selected={current_page == page_info.number}
+                                                    class={classes!((!missing.is_empty()).then_some("incomplete"))}
+                                                >
+                                                    { label }
+                                                </option>
+                                            }
+                                        })}
+                                    };
+                                    match section {
+                                        Some(label) => html! { <optgroup label={label.to_string()}>{ options }</optgroup> },
+                                        None => options,
                                     }
                                 })}
                             </select>
+                            <button
+                                class="page-step"
+                                onclick={on_turn_page.reform(|_: MouseEvent| 1)}
+                                disabled={step_target(1).is_none()}
+                                title={lang.t("Página siguiente (AvPág)")}
+                                aria-label={lang.t("Página siguiente")}
+                            >
+                                {"›"}
+                                { self.render_step_preview(step_target(1)) }
+                            </button>
+                            <label class="page-wrap" title={lang.t("Tras la última página, volver a la primera")}>
+                                <input
+                                    type="checkbox"
+                                    checked={self.wrap_pages}
+                                    onchange={ctx.link().callback(|_| AppMsg::ToggleWrapPages)}
+                                />
+                                { lang.t("Circular") }
+                            </label>
+                            <button
+                                class="page-step"
+                                onclick={ctx.link().callback(|_| AppMsg::TogglePageGrid)}
+                                title={lang.t("Ver todas las páginas")}
+                            >
+                                { lang.t("▦ Ver todas las páginas") }
+                            </button>
+                            <button
+                                class={classes!("page-step", self.route.is_gallery().then_some("active"))}
+                                onclick={ctx.link().callback(|_| AppMsg::ToggleGallery)}
+                                aria-pressed={self.route.is_gallery().to_string()}
+                                title={lang.t("Hojear las fotografías de todas las páginas, sin sus textos")}
+                            >
+                                { lang.t("🖼 Galería") }
+                            </button>
+                        </div>
+
+                        <div class="kiosk-selector">
+                            <label for="kiosk-interval">{ lang.t("Segundos por página: ") }</label>
+                            <input
+                                id="kiosk-interval"
+                                type="number"
+                                min={MIN_KIOSK_INTERVAL.to_string()}
+                                max={MAX_KIOSK_INTERVAL.to_string()}
+                                value={self.kiosk_interval.to_string()}
+                                onchange={on_kiosk_interval}
+                            />
+                            <button onclick={on_start_kiosk} title={lang.t("Recorrer las páginas a pantalla completa, sin controles")}>
+                                { lang.t("▶ Presentación") }
+                            </button>
                         </div>
                     </div>
 
-                    <TeiViewer
-                        project={self.current_project.clone()}
-                        page={self.current_page}
-                    />
+                    { self.render_notice(ctx) }
+
+                    { self.render_tour_bar(ctx) }
+
+                    { match current_project_config.as_ref().filter(|_| self.show_page_grid) {
+                        Some(config) => html! {
+                            <ErrorBoundary {lang} name="la cuadrícula de páginas">
+                            <PageGrid
+                                {lang}
+                                config={config.clone()}
+                                current={current_page}
+                                on_select={on_page_change.clone()}
+                                on_close={ctx.link().callback(|_| AppMsg::TogglePageGrid)}
+                            />
+                            </ErrorBoundary>
+                        },
+                        None => html! {},
+                    } }
+
+                    { if self.show_settings {
+                        html! {
+                            <SettingsDialog
+                                {lang}
+                                theme={self.theme}
+                                settings={self.settings.clone()}
+                                on_theme={ctx.link().callback(AppMsg::SetTheme)}
+                                on_lang={ctx.link().callback(AppMsg::SetLang)}
+                                on_change={ctx.link().callback(AppMsg::SetSettings)}
+                                on_close={ctx.link().callback(|_| AppMsg::ToggleSettings)}
+                            />
+                        }
+                    } else {
+                        html! {}
+                    } }
+
+                    { match self.dts_endpoint().filter(|_| self.show_dts) {
+                        Some(endpoint) => html! {
+                            <DtsBrowser
+                                {lang}
+                                endpoint={endpoint.to_string()}
+                                on_open={ctx.link().callback(AppMsg::OpenDtsResource)}
+                                on_close={ctx.link().callback(|_| AppMsg::ToggleDtsBrowser)}
+                            />
+                        },
+                        None => html! {},
+                    } }
+
+                    { if self.show_help {
+                        html! {
+                            <HelpDialog
+                                {lang}
+                                on_close={ctx.link().callback(|_| AppMsg::ToggleHelp)}
+                                on_onboarding={ctx.link().callback(|_| AppMsg::StartOnboarding)}
+                            />
+                        }
+                    } else {
+                        html! {}
+                    } }
+
+                    { if self.show_entity_index {
+                        html! {
+                            <ErrorBoundary {lang} name="los índices de la colección">
+                            <EntityIndexView
+                                {lang}
+                                projects={self.available_projects.clone()}
+                                corpus={self.corpus.clone()}
+                                on_open={ctx.link().callback(|(project, stop)| AppMsg::OpenProjectLine(project, stop))}
+                                on_close={ctx.link().callback(|_| AppMsg::ToggleEntityIndex)}
+                            />
+                            </ErrorBoundary>
+                        }
+                    } else {
+                        html! {}
+                    } }
+
+                    { viewer }
+
+                    { if self.dragging_files {
+                        html! {
+                            <div class="drop-overlay" aria-hidden="true">
+                                <div class="drop-target">
+                                    <p class="drop-title">{ lang.t("Suelte el archivo TEI para previsualizarlo") }</p>
+                                    <p>{ lang.t("Puede soltar también la imagen de la página. El archivo no se sube a ningún servidor.") }</p>
+                                </div>
+                            </div>
+                        }
+                    } else {
+                        html! {}
+                    } }
+
+                    { if self.show_onboarding {
+                        html! {
+                            <Onboarding
+                                {lang}
+                                on_close={ctx.link().callback(|_| AppMsg::EndOnboarding)}
+                            />
+                        }
+                    } else {
+                        html! {}
+                    } }
+
+                    { if perf::enabled() {
+                        html! { <PerfOverlay {lang} /> }
+                    } else {
+                        html! {}
+                    } }
                 </main>
 
                 <footer class="app-footer">
@@ -231,47 +1512,768 @@ impl Component for App {
     }
 }
 
-async fn load_all_manifests() -> Result<Vec<ProjectConfig>, String> {
-    // List of known project directories to check
-    // In a real implementation, you might want to fetch a directory listing
-    // For now, we'll try to load manifests for known projects
-    let project_ids = vec!["Tractatus"];
+impl App {
+    /// Project and page the route points at. Home, an unknown project or
+    /// a missing page fall back to the first project or page.
+    fn current(&self) -> Option<(&ProjectConfig, u32)> {
+        let named = self
+            .route
+            .project()
+            .and_then(|id| self.available_projects.iter().find(|p| p.id == id));
+        let project = named.or(self.available_projects.first())?;
+        let page = self
+            .route
+            .page()
+            .filter(|n| named.is_some() && project.get_page(*n).is_some())
+            .or_else(|| project.pages.first().map(|p| p.number))
+            .unwrap_or(1);
+        Some((project, page))
+    }
 
-    let mut configs = Vec::new();
+    /// Photographs of the current project's pages, for the gallery
+    fn gallery_images(&self) -> Vec<GalleryImage> {
+        let Some((project, _)) = self.current() else {
+            return Vec::new();
+        };
+        project
+            .pages
+            .iter()
+            .filter_map(|page| {
+                let path = page.gallery_image_path()?;
+                Some(GalleryImage {
+                    page: page.number,
+                    label: page.label.clone(),
+                    src: project
+                        .sources_for(&local_document::resolve(&project.file_url(&path)))
+                        .first()
+                        .to_string(),
+                })
+            })
+            .collect()
+    }
 
-    for project_id in project_ids {
-        let manifest_url = resource_url(&format!("public/projects/{}/manifest.json", project_id));
+    fn current_project(&self) -> &str {
+        self.current().map_or("", |(p, _)| p.id.as_str())
+    }
 
-        match Request::get(&manifest_url).send().await {
-            Ok(resp) => {
-                if resp.ok() {
-                    match resp.json::<ProjectConfig>().await {
-                        Ok(config) => {
-                            log::info!("Loaded manifest for project: {}", project_id);
-                            configs.push(config);
-                        }
-                        Err(e) => {
-                            log::warn!("Failed to parse manifest for {}: {:?}", project_id, e);
+    fn current_page(&self) -> u32 {
+        self.current().map_or(1, |(_, page)| page)
+    }
+
+    /// Push the route of a page, in the gallery if it is open; the view
+    /// follows once the router reports it (`AppMsg::RouteChanged`).
+    fn navigate(&self, ctx: &Context<Self>, project: &str, page: u32) {
+        let route = Route::of_page(project, page, self.route.is_gallery());
+        if route == self.route {
+            return;
+        }
+        self.go(ctx, &route, false);
+    }
+
+    /// Entry point of the DTS repository being read, if any.
+    fn dts_endpoint(&self) -> Option<&str> {
+        match self.remote.as_ref().map(|r| &r.origin) {
+            Some(Origin::Dts { endpoint, .. }) => Some(endpoint),
+            _ => None,
+        }
+    }
+
+    /// Push (or replace) a route, keeping `?project_url=`, `?gh=` or `?dts=`
+    /// so that the address still reopens a remote project, and `?embed=`.
+    fn go(&self, ctx: &Context<Self>, route: &Route, replace: bool) {
+        let Some(nav) = ctx.link().navigator() else {
+            return;
+        };
+        let query: Vec<(&'static str, String)> = self
+            .remote
+            .iter()
+            .flat_map(|remote| remote.query.clone())
+            .chain(self.embed.iter().flat_map(Embed::query))
+            .collect();
+        if query.is_empty() {
+            return if replace {
+                nav.replace(route)
+            } else {
+                nav.push(route)
+            };
+        }
+        let query: Vec<(&str, &str)> = query
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect();
+        let result = if replace {
+            nav.replace_with_query(route, &query)
+        } else {
+            nav.push_with_query(route, &query)
+        };
+        if let Err(e) = result {
+            log::warn!("Could not navigate: {:?}", e);
+        }
+    }
+
+    /// Rewrite a route that does not name an existing page (e.g. `/` or a
+    /// stale link) to the page actually shown, without a history entry.
+    fn canonicalize(&self, ctx: &Context<Self>) {
+        // The route may name the remote project, still on its way
+        if self.remote_pending {
+            return;
+        }
+        let Some((project, page)) = self.current() else {
+            return;
+        };
+        let route = Route::of_page(&project.id, page, self.route.is_gallery());
+        if route != self.route {
+            self.go(ctx, &route, true);
+        }
+    }
+
+    /// The route moved to another project: leave the comparison if it was
+    /// the compared one, and look for its tour.
+    fn open_project(&mut self, ctx: &Context<Self>) {
+        if self.compare_project.as_deref() == Some(self.current_project()) {
+            self.compare_project = None;
+        }
+        self.load_tour(ctx);
+        self.check_offline(ctx);
+    }
+
+    /// Look up, once per session, whether the current project has been
+    /// downloaded for offline reading.
+    fn check_offline(&self, ctx: &Context<Self>) {
+        let project = self.current_project().to_string();
+        if project.is_empty()
+            || local_document::is_open(&project)
+            || !offline::supported()
+            || self.offline.contains_key(&project)
+        {
+            return;
+        }
+        ctx.link().send_future(async move {
+            let available = offline::is_available(&project).await;
+            AppMsg::OfflineChecked(project, available)
+        });
+    }
+
+    /// "Disponible sin conexión" once the current project is downloaded,
+    /// otherwise the action downloading it, with its progress.
+    fn render_offline(&self, ctx: &Context<Self>) -> Html {
+        let lang = self.lang;
+        if !offline::supported() {
+            return html! {};
+        }
+        let on_download = ctx.link().callback(|_| AppMsg::DownloadOffline);
+        match self
+            .offline
+            .get(self.current_project())
+            .copied()
+            .unwrap_or_default()
+        {
+            Availability::Unknown => html! {},
+            Availability::Partial => html! {
+                <button
+                    class="offline-download"
+                    onclick={on_download}
+                    title={lang.t("Guardar todas las páginas, imágenes y el comentario del proyecto en este navegador")}
+                >
+                    { lang.t("⬇ Descargar para offline") }
+                </button>
+            },
+            Availability::Downloading { done, total } => html! {
+                <span class="offline-progress" role="status">
+                    <progress max={total.max(1).to_string()} value={done.to_string()} />
+                    { lang.tf("Descargando {} de {}", &[&done, &total]) }
+                </span>
+            },
+            Availability::Incomplete { failed } => html! {
+                <span class="offline-status incomplete">
+                    { lang.tf("Descarga incompleta: faltan {} archivos", &[&failed]) }
+                    <button class="offline-download" onclick={on_download}>{ lang.t("Reintentar") }</button>
+                </span>
+            },
+            Availability::Available => html! {
+                <span
+                    class="offline-status available"
+                    title={lang.t("Este proyecto se puede leer sin conexión en este navegador")}
+                >
+                    { lang.t("✓ Disponible sin conexión") }
+                </span>
+            },
+        }
+    }
+
+    /// Listen for files dragged onto the window. Only drags carrying files
+    /// are taken, so text dragged within the page is left alone, and file
+    /// inputs (e.g. the notes import) keep their own drops.
+    fn drop_listeners(ctx: &Context<Self>, document: &web_sys::Document) -> Vec<EventListener> {
+        fn carries_files(e: &web_sys::DragEvent) -> bool {
+            let on_input = e
+                .target()
+                .is_some_and(|t| t.dyn_ref::<web_sys::HtmlInputElement>().is_some());
+            !on_input
+                && e.data_transfer()
+                    .is_some_and(|data| data.types().includes(&"Files".into(), 0))
+        }
+        let options = EventListenerOptions::enable_prevent_default();
+        let link = ctx.link().clone();
+        let dragover = EventListener::new_with_options(document, "dragover", options, move |e| {
+            let Some(e) = e
+                .dyn_ref::<web_sys::DragEvent>()
+                .filter(|e| carries_files(e))
+            else {
+                return;
+            };
+            // Accepting the drag is what makes the window a drop target
+            e.prevent_default();
+            link.send_message(AppMsg::DragFiles(true));
+        });
+        let link = ctx.link().clone();
+        let dragleave = EventListener::new(document, "dragleave", move |e| {
+            // Leaving the window, not one of its elements
+            let Some(e) = e.dyn_ref::<web_sys::DragEvent>() else {
+                return;
+            };
+            if e.related_target().is_none() {
+                link.send_message(AppMsg::DragFiles(false));
+            }
+        });
+        let link = ctx.link().clone();
+        let drop = EventListener::new_with_options(document, "drop", options, move |e| {
+            let Some(e) = e
+                .dyn_ref::<web_sys::DragEvent>()
+                .filter(|e| carries_files(e))
+            else {
+                return;
+            };
+            e.prevent_default();
+            let files = e
+                .data_transfer()
+                .and_then(|data| data.files())
+                .map(|list| (0..list.length()).filter_map(|i| list.get(i)).collect())
+                .unwrap_or_default();
+            link.send_message(AppMsg::DropFiles(files));
+        });
+        vec![dragover, dragleave, drop]
+    }
+
+    /// Show a dropped document as the single page of the local project,
+    /// replacing the one dropped before.
+    fn open_local(&mut self, ctx: &Context<Self>, local: LocalDocument) {
+        local_document::open(&local.project(), local.files());
+        self.add_project(local.project());
+        self.local = Some(local);
+        self.navigate(ctx, LOCAL_PROJECT, 1);
+    }
+
+    /// List a project opened from the reader's files or from another host,
+    /// in place of the project of the same id: a folder of a served project
+    /// previews the reader's copy of it.
+    fn add_project(&mut self, project: ProjectConfig) {
+        match self
+            .available_projects
+            .iter_mut()
+            .find(|p| p.id == project.id)
+        {
+            Some(existing) => *existing = project,
+            None => self.available_projects.push(project),
+        }
+        self.local_revision += 1;
+    }
+
+    /// Why the files just dropped could not be opened.
+    fn render_notice(&self, ctx: &Context<Self>) -> Html {
+        let Some(notice) = &self.notice else {
+            return html! {};
+        };
+        html! {
+            <div class="local-notice" role="alert">
+                <span>{ notice.clone() }</span>
+                <button
+                    onclick={ctx.link().callback(|_| AppMsg::DismissNotice)}
+                    title={self.lang.t("Cerrar")}
+                >
+                    {"✕"}
+                </button>
+            </div>
+        }
+    }
+
+    /// Title, subtitle and thanks of the header, with the logo and texts of
+    /// the project on screen when its manifest gives them.
+    fn render_branding(&self) -> Html {
+        let lang = self.lang;
+        let Some((project, _)) = self.current() else {
+            return html! { <h1>{ lang.t("Visualizador TEI-XML") }</h1> };
+        };
+        let branding = &project.branding;
+        let subtitle = branding
+            .subtitle
+            .clone()
+            .unwrap_or_else(|| lang.tf("Visualizador interactivo - {}", &[&project.name]));
+        html! {
+            <>
+                <h1>
+                    if let Some(logo) = &branding.logo {
+                        <img
+                            class="project-logo"
+                            src={project.file_url(logo)}
+                            alt={lang.tf("Logotipo de {}", &[&project.name])}
+                        />
+                    }
+                    { lang.t("Visualizador TEI-XML") }
+                </h1>
+                <p class="subtitle">{ subtitle }</p>
+                if let Some(thanks) = &branding.acknowledgements {
+                    <p class="subtitle acknowledgements">{ thanks.clone() }</p>
+                }
+            </>
+        }
+    }
+
+    /// Cover image of the project on screen, if its manifest gives one.
+    fn render_project_cover(&self) -> Html {
+        let Some((project, _)) = self.current() else {
+            return html! {};
+        };
+        let Some(cover) = &project.thumbnail else {
+            return html! {};
+        };
+        html! {
+            <img
+                class="project-cover"
+                src={local_document::resolve(&project.file_url(cover))}
+                alt={self.lang.tf("Portada de {}", &[&project.name])}
+            />
+        }
+    }
+
+    /// Thumbnail of `page`, shown over the button that turns to it.
+    fn render_step_preview(&self, page: Option<u32>) -> Html {
+        let Some((project, _)) = self.current() else {
+            return html! {};
+        };
+        let Some(path) = page
+            .and_then(|n| project.get_page(n))
+            .and_then(|p| p.thumbnail_path())
+        else {
+            return html! {};
+        };
+        html! {
+            <img
+                class="page-step-preview"
+                src={local_document::resolve(&project.file_url(&path))}
+                alt=""
+                loading="lazy"
+            />
+        }
+    }
+
+    /// Collection › project › sections › page on screen, each leading to
+    /// the start of what it names. Sections come from the manifest outline or
+    /// sections, else from the pages of the collection loaded so far.
+    fn render_breadcrumbs(&self, ctx: &Context<Self>) -> Html {
+        let Some((project, page)) = self.current() else {
+            return html! {};
+        };
+        let lang = self.lang;
+        let contents = project.contents();
+        let sections = if contents.is_empty() {
+            toc::from_sections(
+                self.corpus
+                    .diplomatic(std::slice::from_ref(project))
+                    .map(|(_, page, doc)| (page, doc)),
+            )
+        } else {
+            toc::from_outline(&contents)
+        };
+        let collection = &project.metadata.collection;
+        let first_of_collection = self
+            .available_projects
+            .iter()
+            .find(|p| !collection.is_empty() && &p.metadata.collection == collection)
+            .map(|p| p.id.clone());
+        let page_label = project
+            .get_page(page)
+            .map(|p| p.label.clone())
+            .unwrap_or_else(|| page.to_string());
+        let project_id = project.id.clone();
+        html! {
+            <nav class="breadcrumbs" aria-label={lang.t("Ubicación")}>
+                <ol>
+                    { for first_of_collection.map(|id| html! {
+                        <li>
+                            <button
+                                onclick={ctx.link().callback(move |_| AppMsg::ChangeProject(id.clone()))}
+                                title={lang.t("Primer proyecto de la colección")}
+                            >
+                                { collection.clone() }
+                            </button>
+                        </li>
+                    }) }
+                    <li>
+                        <button
+                            onclick={ctx.link().callback(move |_| AppMsg::ChangeProject(project_id.clone()))}
+                            title={lang.t("Primera página del proyecto")}
+                        >
+                            { project.name.clone() }
+                        </button>
+                    </li>
+                    { for toc::trail(&sections, page).into_iter().map(|section| {
+                        let stop = TourStop {
+                            page: section.page,
+                            zone: None,
+                            line: section.line.clone(),
+                            caption: String::new(),
+                        };
+                        html! {
+                            <li>
+                                <button
+                                    onclick={ctx.link().callback(move |_| AppMsg::OpenLine(stop.clone()))}
+                                    title={lang.t("Inicio de la sección")}
+                                >
+                                    { section.title.clone() }
+                                </button>
+                            </li>
                         }
+                    }) }
+                    <li aria-current="page">{ lang.tf("Folio {}", &[&page_label]) }</li>
+                </ol>
+            </nav>
+        }
+    }
+
+    /// Header menu with the last pages visited, across projects.
+    fn render_history(&self, ctx: &Context<Self>) -> Html {
+        let on_toggle = ctx.link().callback(|_| AppMsg::ToggleHistoryPanel);
+        // The first visit is the page on screen
+        let visits = self.history.visits.iter().enumerate().skip(1);
+        let now = js_sys::Date::now();
+        let lang = self.lang;
+        html! {
+            <div class="header-menu">
+                <button
+                    class={classes!("theme-toggle", self.show_history.then_some("active"))}
+                    onclick={on_toggle}
+                    title={lang.t("Páginas vistas recientemente")}
+                    aria-expanded={self.show_history.to_string()}
+                >
+                    { lang.t("🕘 Recientes") }
+                </button>
+                { if self.show_history {
+                    html! {
+                        <div class="header-dropdown history-panel">
+                            { if self.history.visits.len() < 2 {
+                                html! { <p class="header-dropdown-empty">{ lang.t("Aún no hay páginas recientes.") }</p> }
+                            } else {
+                                html! {
+                                    <ul>
+                                        { for visits.map(|(index, visit)| {
+                                            let on_open = ctx.link().callback(move |_| AppMsg::OpenVisit(index));
+                                            html! {
+                                                <li class="header-dropdown-item">
+                                                    <button class="header-dropdown-link" onclick={on_open}>
+                                                        <span class="header-dropdown-project">{ self.project_name(&visit.project) }</span>
+                                                        <span>{ lang.tf("Página {}", &[&visit.page_label]) }</span>
+                                                        <span class="header-dropdown-excerpt" title={lang.date(visit.visited)}>
+                                                            { time_ago(lang, now, visit.visited) }
+                                                        </span>
+                                                    </button>
+                                                </li>
+                                            }
+                                        }) }
+                                    </ul>
+                                }
+                            } }
+                        </div>
                     }
                 } else {
-                    log::warn!("Manifest not found for project: {}", project_id);
+                    html! {}
+                } }
+            </div>
+        }
+    }
+
+    fn project_name(&self, id: &str) -> String {
+        self.available_projects
+            .iter()
+            .find(|p| p.id == id)
+            .map(|p| p.name.clone())
+            .unwrap_or_else(|| id.to_string())
+    }
+
+    /// Header menu with the reader's bookmarks across all projects.
+    fn render_bookmarks(&self, ctx: &Context<Self>) -> Html {
+        let on_toggle = ctx.link().callback(|_| AppMsg::ToggleBookmarksPanel);
+        let count = self.bookmarks.items.len();
+        let lang = self.lang;
+        html! {
+            <div class="header-menu">
+                <button
+                    class={classes!("theme-toggle", self.show_bookmarks.then_some("active"))}
+                    onclick={on_toggle}
+                    title={lang.t("Páginas y líneas marcadas")}
+                    aria-expanded={self.show_bookmarks.to_string()}
+                >
+                    { lang.tf("★ Marcadores ({})", &[&count]) }
+                </button>
+                { if self.show_bookmarks {
+                    html! {
+                        <div class="header-dropdown bookmarks-panel">
+                            { if count == 0 {
+                                html! { <p class="header-dropdown-empty">{ lang.t("Aún no hay marcadores. Use «☆ Marcar página» en el visor.") }</p> }
+                            } else {
+                                html! {
+                                    <ul>
+                                        { for self.bookmarks.items.iter().enumerate().map(|(index, bookmark)| {
+                                            let project_name = self.project_name(&bookmark.project);
+                                            let target = match &bookmark.line {
+                                                Some(line) => lang.tf("Página {} · línea {}", &[&bookmark.page_label, line]),
+                                                None => lang.tf("Página {}", &[&bookmark.page_label]),
+                                            };
+                                            let on_open = ctx.link().callback(move |_| AppMsg::OpenBookmark(index));
+                                            let on_remove = ctx.link().callback(move |_| AppMsg::RemoveBookmark(index));
+                                            html! {
+                                                <li class="header-dropdown-item">
+                                                    <button class="header-dropdown-link" onclick={on_open}>
+                                                        <span class="header-dropdown-project">{ project_name }</span>
+                                                        <span>{ target }</span>
+                                                        { if let Some(excerpt) = &bookmark.excerpt {
+                                                            html! { <span class="header-dropdown-excerpt">{ excerpt.clone() }</span> }
+                                                        } else {
+                                                            html! {}
+                                                        } }
+                                                    </button>
+                                                    <button class="header-dropdown-remove" onclick={on_remove} title={lang.t("Quitar el marcador")}>{"✕"}</button>
+                                                </li>
+                                            }
+                                        }) }
+                                    </ul>
+                                }
+                            } }
+                        </div>
+                    }
+                } else {
+                    html! {}
+                } }
+            </div>
+        }
+    }
+
+    /// Fetch every page of every project, once.
+    fn load_corpus(&mut self, ctx: &Context<Self>) {
+        if self.corpus.requested {
+            return;
+        }
+        self.corpus.requested = true;
+        for (project, page, edition) in Corpus::documents(&self.available_projects) {
+            let Some(config) = self.available_projects.iter().find(|p| p.id == project) else {
+                continue;
+            };
+            self.corpus.pending += 1;
+            // The collection-wide views read the first translation
+            let file = match config.get_page(page) {
+                Some(info) if edition == "trad" => info.translation_edition(None),
+                _ => edition.to_string(),
+            };
+            let path = config.get_edition_path(page, &file);
+            if let Some(xml) = local_document::read(&path) {
+                let result = xml.and_then(|xml| tei_parser::parse_tei_xml(&xml));
+                ctx.link().send_message(AppMsg::CorpusPageLoaded(
+                    project,
+                    page,
+                    edition,
+                    Box::new(result),
+                ));
+                continue;
+            }
+            let sources = config.sources_for(&path);
+            ctx.link().send_future(async move {
+                let result = match net::get_text(sources).await {
+                    Ok(xml) => tei_parser::parse_tei_xml(&xml),
+                    Err(e) => Err(format!("Failed to load page: {}", e)),
+                };
+                AppMsg::CorpusPageLoaded(project, page, edition, Box::new(result))
+            });
+        }
+    }
+
+    /// Fetch the current project's optional `tour.json`.
+    fn load_tour(&mut self, ctx: &Context<Self>) {
+        self.tour = None;
+        self.tour_stop = None;
+        let Some((config, _)) = self.current() else {
+            return;
+        };
+        let project = config.id.clone();
+        let url = config.versioned_file_url("tour.json");
+        if let Some(json) = local_document::read(&url) {
+            let tour = json.ok().and_then(|json| serde_json::from_str(&json).ok());
+            ctx.link().send_message(AppMsg::TourLoaded(project, tour));
+            return;
+        }
+        let sources = config.sources_for(&url);
+        ctx.link().send_future(async move {
+            let tour = match net::get_json::<Tour>(sources).await {
+                Ok(tour) => Some(tour),
+                Err(FetchError::NotFound { .. }) => None,
+                Err(e) => {
+                    log::warn!("Failed to load tour for {}: {}", project, e);
+                    None
                 }
+            };
+            AppMsg::TourLoaded(project, tour)
+        });
+    }
+
+    fn render_tour_bar(&self, ctx: &Context<Self>) -> Html {
+        let Some(tour) = &self.tour else {
+            return html! {};
+        };
+        if tour.stops.is_empty() {
+            return html! {};
+        }
+        let lang = self.lang;
+        let title = tour
+            .title
+            .clone()
+            .unwrap_or_else(|| lang.t("Visita guiada").to_string());
+        let on_next = ctx.link().callback(|_| AppMsg::TourStep(1));
+
+        let Some(index) = self.tour_stop else {
+            return html! {
+                <div class="tour-bar">
+                    <span class="tour-title">{ title }</span>
+                    <button class="tour-next" onclick={on_next}>{ lang.t("▶ Iniciar visita guiada") }</button>
+                </div>
+            };
+        };
+        let on_prev = ctx.link().callback(|_| AppMsg::TourStep(-1));
+        let on_end = ctx.link().callback(|_| AppMsg::EndTour);
+        let is_last = index + 1 == tour.stops.len();
+
+        html! {
+            <div class="tour-bar active">
+                <span class="tour-title">{ title }</span>
+                <span class="tour-progress">{ lang.tf("Parada {} de {}", &[&(index + 1), &tour.stops.len()]) }</span>
+                <p class="tour-caption">{ tour.stops[index].caption.clone() }</p>
+                <div class="tour-nav">
+                    <button onclick={on_prev} disabled={index == 0}>{ lang.t("◀ Anterior") }</button>
+                    { if is_last {
+                        html! { <button class="tour-next" onclick={on_end.clone()}>{ lang.t("Fin de la visita") }</button> }
+                    } else {
+                        html! { <button class="tour-next" onclick={on_next}>{ lang.t("Siguiente parada ▶") }</button> }
+                    } }
+                    <button class="tour-end" onclick={on_end} title={lang.t("Salir de la visita guiada")}>{"✕"}</button>
+                </div>
+            </div>
+        }
+    }
+}
+
+/// Load the manifest of every project listed in `public/projects/index.json`
+/// (or discovered without it). Projects whose manifest is missing are
+/// skipped (and logged); invalid ones are returned with what is wrong.
+async fn load_all_manifests() -> Result<(Vec<ProjectConfig>, Vec<manifest::ManifestError>), String>
+{
+    let index_url = resource_url("public/projects/index.json");
+    let manifest_urls = match net::get_text(&index_url).await {
+        Ok(json) => {
+            offline::keep_text(&index_url, &json);
+            project_config::parse_project_index(&json)?
+        }
+        Err(FetchError::NotFound { .. }) => match discover_projects().await {
+            Some(urls) => urls,
+            None => {
+                return Err("Falta el índice de proyectos public/projects/index.json \
+                     (ejecute ./sync_projects.sh para generarlo) y no se pudieron \
+                     descubrir las carpetas de los proyectos."
+                    .to_string())
             }
+        },
+        Err(e) => {
+            return Err(format!(
+                "No se pudo cargar public/projects/index.json: {}",
+                e
+            ))
+        }
+    };
+    if manifest_urls.is_empty() {
+        return Err("public/projects/index.json no lista ningún proyecto.".to_string());
+    }
+
+    let mut configs = Vec::new();
+    let mut failed = Vec::new();
+    let mut invalid = Vec::new();
+    for manifest_url in manifest_urls {
+        match net::get_text(&manifest_url).await {
+            Ok(json) => match manifest::parse(&json, &manifest_url) {
+                Ok(config) => {
+                    log::info!("Loaded manifest for project: {}", config.id);
+                    offline::keep_text(&manifest_url, &json);
+                    configs.push(config);
+                }
+                Err(e) => {
+                    log::warn!("Invalid manifest {}: {}", manifest_url, e);
+                    invalid.push(e);
+                }
+            },
             Err(e) => {
-                log::warn!("Failed to fetch manifest for {}: {:?}", project_id, e);
+                log::warn!("Failed to fetch manifest: {}", e);
+                failed.push(manifest_url);
             }
         }
     }
 
     if configs.is_empty() {
-        Err("No project manifests could be loaded".to_string())
+        failed.extend(invalid.iter().map(|e| e.to_string()));
+        Err(format!(
+            "No se pudo cargar ningún manifiesto de proyecto: {}",
+            failed.join(", ")
+        ))
     } else {
-        Ok(configs)
+        Ok((configs, invalid))
+    }
+}
+
+/// Manifest URLs of the project folders found at the discovery endpoint,
+/// which answers with an index-style JSON array or a directory listing.
+async fn discover_projects() -> Option<Vec<String>> {
+    let url = discovery::discovery_url();
+    // Only a fallback: it is not waited for long
+    let options = net::Options {
+        timeout_ms: 5_000,
+        retries: 0,
+    };
+    let body = net::get_text_with(&url, options).await.ok()?;
+    let urls = project_config::parse_project_index(&body)
+        .unwrap_or_else(|_| project_config::manifest_urls(&discovery::parse_listing(&body)));
+    log::info!("Discovered {} projects at {}", urls.len(), url);
+    (!urls.is_empty()).then_some(urls)
+}
+
+/// Router around the app, rooted at the deployment's base path.
+struct Root;
+
+impl Component for Root {
+    type Message = ();
+    type Properties = ();
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self
+    }
+
+    fn view(&self, _ctx: &Context<Self>) -> Html {
+        let basename = Some(utils::get_base_url()).filter(|base| !base.is_empty());
+        html! {
+            <BrowserRouter basename={basename.map(AttrValue::from)}>
+                <App />
+            </BrowserRouter>
+        }
     }
 }
 
 fn main() {
     wasm_logger::init(wasm_logger::Config::default());
-    yew::Renderer::<App>::new().render();
+    diagnostics::install_panic_hook();
+    offline::register_worker();
+    yew::Renderer::<Root>::new().render();
 }
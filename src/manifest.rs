@@ -0,0 +1,310 @@
+// src/manifest.rs
+// Reading a project manifest with errors that say which field is missing or
+// of the wrong kind, and where (`pages[3].has_image`), rather than a bare
+// parse failure. Fields the viewer does not know are ignored, and manifests
+// of an older `schema_version` are migrated once checked.
+use crate::project_config::{ProjectConfig, SCHEMA_VERSION};
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestError {
+    /// Id of the project, when the manifest gives one
+    pub project: Option<String>,
+    /// Where the manifest was read from
+    pub source: String,
+    /// Field at fault, e.g. `pages[3].has_image`; empty for the whole file
+    pub path: String,
+    pub problem: String,
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let who = self.project.as_deref().unwrap_or(&self.source);
+        if self.path.is_empty() {
+            write!(f, "{}: {}", who, self.problem)
+        } else {
+            write!(f, "{}: {}: {}", who, self.path, self.problem)
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    Number,
+    Bool,
+    List,
+    Object,
+    /// A file name or a list of entries
+    TextOrList,
+}
+
+impl Kind {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            Kind::Text => value.is_string(),
+            Kind::Number => value.as_u64().is_some_and(|n| n <= u64::from(u32::MAX)),
+            Kind::Bool => value.is_boolean(),
+            Kind::List => value.is_array(),
+            Kind::Object => value.is_object(),
+            Kind::TextOrList => value.is_string() || value.is_array(),
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            Kind::Text => "se esperaba un texto",
+            Kind::Number => "se esperaba un número entero positivo",
+            Kind::Bool => "se esperaba true o false",
+            Kind::List => "se esperaba una lista",
+            Kind::Object => "se esperaba un objeto",
+            Kind::TextOrList => "se esperaba un texto o una lista",
+        }
+    }
+}
+
+/// (field, kind, required)
+type Schema = &'static [(&'static str, Kind, bool)];
+
+const PROJECT: Schema = &[
+    ("schema_version", Kind::Number, false),
+    ("id", Kind::Text, true),
+    ("name", Kind::Text, true),
+    ("description", Kind::Text, true),
+    ("pages", Kind::List, true),
+    ("metadata", Kind::Object, true),
+    ("outline", Kind::List, false),
+    ("sections", Kind::List, false),
+    ("editions", Kind::List, false),
+    ("legend", Kind::List, false),
+    ("diplomatic_pattern", Kind::Text, false),
+    ("translation_pattern", Kind::Text, false),
+    ("image_pattern", Kind::Text, false),
+    ("iiif_service", Kind::Text, false),
+    ("mirrors", Kind::List, false),
+    ("issues_url", Kind::Text, false),
+    ("thumbnail", Kind::Text, false),
+    ("image_credit", Kind::Text, false),
+    ("rights_holder", Kind::Text, false),
+    ("content_version", Kind::Text, false),
+    ("file_hashes", Kind::Object, false),
+    ("default_view", Kind::Object, false),
+    ("logo", Kind::Text, false),
+    ("accent_color", Kind::Text, false),
+    ("subtitle", Kind::Text, false),
+    ("acknowledgements", Kind::Text, false),
+];
+
+const METADATA: Schema = &[
+    ("author", Kind::Text, true),
+    ("editor", Kind::Text, true),
+    ("collection", Kind::Text, true),
+    ("institution", Kind::Text, true),
+    ("country", Kind::Text, true),
+    ("language", Kind::Text, true),
+    ("date_range", Kind::Text, true),
+    ("license", Kind::Text, false),
+    ("doi", Kind::Text, false),
+    ("how_to_cite", Kind::Text, false),
+];
+
+const PAGE: Schema = &[
+    ("number", Kind::Number, true),
+    ("label", Kind::Text, true),
+    ("has_diplomatic", Kind::Bool, true),
+    ("has_translation", Kind::Bool, true),
+    ("has_image", Kind::Bool, true),
+    ("image_variants", Kind::List, false),
+    ("iiif_service", Kind::Text, false),
+    ("dzi", Kind::Text, false),
+    ("audio", Kind::Object, false),
+    ("thumbnail", Kind::Text, false),
+    ("translations", Kind::List, false),
+    ("images", Kind::List, false),
+    ("commentary", Kind::Text, false),
+    ("bibl", Kind::TextOrList, false),
+    ("image_credit", Kind::Text, false),
+    ("rights_holder", Kind::Text, false),
+];
+
+/// Steps bringing a manifest from the version of their position (1 for the
+/// first) to the next one; none until the format changes
+const MIGRATIONS: &[fn(&mut Value)] = &[];
+
+/// Bring the manifest to the current version, returning the version it was
+/// written in.
+fn migrate(value: &mut Value) -> u64 {
+    let version = value["schema_version"].as_u64().unwrap_or(1);
+    for step in MIGRATIONS.iter().skip(version.saturating_sub(1) as usize) {
+        step(value);
+    }
+    if let Some(project) = value.as_object_mut() {
+        project.insert("schema_version".to_string(), Value::from(SCHEMA_VERSION));
+    }
+    version
+}
+
+/// Parse the manifest read from `source`.
+pub fn parse(json: &str, source: &str) -> Result<ProjectConfig, ManifestError> {
+    let value: Value = serde_json::from_str(json).map_err(|e| ManifestError {
+        project: None,
+        source: source.to_string(),
+        path: String::new(),
+        problem: format!("JSON no válido: {}", e),
+    })?;
+    from_value(value, source)
+}
+
+/// Check and convert a manifest already parsed as JSON.
+pub fn from_value(mut value: Value, source: &str) -> Result<ProjectConfig, ManifestError> {
+    let project = value["id"].as_str().map(str::to_string);
+    let who = project.as_deref().unwrap_or(source);
+    let error = |path: String, problem: String| ManifestError {
+        project: project.clone(),
+        source: source.to_string(),
+        path,
+        problem,
+    };
+    if !value.is_object() {
+        return Err(error(String::new(), Kind::Object.expected().to_string()));
+    }
+    if let Some((path, problem)) = check_manifest(&value) {
+        return Err(error(path, problem));
+    }
+    match migrate(&mut value) {
+        v if v < u64::from(SCHEMA_VERSION) => log::warn!(
+            "{}: manifest schema version {} is outdated; it still loads, but consider \
+             upgrading it to \"schema_version\": {}",
+            who,
+            v,
+            SCHEMA_VERSION
+        ),
+        v if v > u64::from(SCHEMA_VERSION) => log::warn!(
+            "{}: manifest schema version {} is newer than this viewer's ({}); \
+             some fields may be ignored",
+            who,
+            v,
+            SCHEMA_VERSION
+        ),
+        _ => {}
+    }
+    // Deeper fields (outline entries, variants…) are left to serde, which
+    // still says where it stopped
+    let mut config: ProjectConfig = serde_path_to_error::deserialize(value).map_err(|e| {
+        let path = e.path().to_string();
+        let path = if path == "." { String::new() } else { path };
+        error(path, e.into_inner().to_string())
+    })?;
+    config.resolve_iiif_services();
+    Ok(config)
+}
+
+/// First field of the manifest breaking the schema, with what is wrong
+/// with it.
+fn check_manifest(value: &Value) -> Option<(String, String)> {
+    check(value, PROJECT, "")
+        .or_else(|| check(&value["metadata"], METADATA, "metadata"))
+        .or_else(|| {
+            value["pages"]
+                .as_array()?
+                .iter()
+                .enumerate()
+                .find_map(|(i, page)| {
+                    let path = format!("pages[{}]", i);
+                    if page.is_object() {
+                        check(page, PAGE, &path)
+                    } else {
+                        Some((path, Kind::Object.expected().to_string()))
+                    }
+                })
+        })
+}
+
+/// First field of `object` breaking `schema`, with what is wrong with it.
+fn check(object: &Value, schema: Schema, prefix: &str) -> Option<(String, String)> {
+    schema.iter().find_map(|&(field, kind, required)| {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+        match object.get(field) {
+            None if required => Some((path, "falta el campo".to_string())),
+            None | Some(Value::Null) if !required => None,
+            Some(value) if kind.matches(value) => None,
+            _ => Some((path, kind.expected().to_string())),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{"schema_version": 1, "id": "T", "name": "T", "description": "", "pages": [
+            {"number": 1, "label": "1r", "has_diplomatic": true, "has_translation": true,
+             "has_image": true, "notes": "unknown fields are ignored"}],
+        "metadata": {"author": "", "editor": "", "collection": "", "institution": "",
+                     "country": "", "language": "", "date_range": ""},
+        "version": "1.0.0"}"#;
+
+    #[test]
+    fn test_parse() {
+        let config = parse(MANIFEST, "T/manifest.json").unwrap();
+        assert_eq!(config.pages.len(), 1);
+    }
+
+    #[test]
+    fn test_migrate() {
+        let unversioned = MANIFEST.replace(r#""schema_version": 1, "#, "");
+        let config = parse(&unversioned, "T/manifest.json").unwrap();
+        assert_eq!(config.schema_version, SCHEMA_VERSION);
+
+        // Unversioned manifests are checked like current ones, not filled in
+        let broken = unversioned.replace(r#""editor": "","#, "");
+        let e = parse(&broken, "T/manifest.json").unwrap_err();
+        assert_eq!(e.path, "metadata.editor");
+        assert_eq!(e.problem, "falta el campo");
+        let broken = unversioned.replace(r#""label": "1r", "#, "");
+        assert_eq!(
+            parse(&broken, "T/manifest.json").unwrap_err().path,
+            "pages[0].label"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let broken = MANIFEST.replace(r#""has_image": true"#, r#""has_image": "yes""#);
+        let e = parse(&broken, "T/manifest.json").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "T: pages[0].has_image: se esperaba true o false"
+        );
+
+        let broken = MANIFEST.replace(r#""editor": "","#, "");
+        let e = parse(&broken, "T/manifest.json").unwrap_err();
+        assert_eq!(e.path, "metadata.editor");
+        assert_eq!(e.problem, "falta el campo");
+
+        let broken = MANIFEST.replace(r#""id": "T","#, "");
+        let e = parse(&broken, "X/manifest.json").unwrap_err();
+        assert_eq!(e.to_string(), "X/manifest.json: id: falta el campo");
+
+        let broken = MANIFEST.replace(r#""has_image": true"#, r#""has_image": true, "bibl": 3"#);
+        let e = parse(&broken, "T/manifest.json").unwrap_err();
+        assert_eq!(e.path, "pages[0].bibl");
+
+        // Deeper fields are reported where serde finds them
+        let broken = MANIFEST.replace(
+            r#""has_image": true"#,
+            r#""has_image": true, "image_variants": [{"path": "a.jpg", "width": "wide"}]"#,
+        );
+        let e = parse(&broken, "T/manifest.json").unwrap_err();
+        assert_eq!(e.path, "pages[0].image_variants[0].width");
+
+        let e = parse("{\"id\": ", "X/manifest.json").unwrap_err();
+        assert!(e.problem.starts_with("JSON no válido"));
+        assert!(e.path.is_empty());
+    }
+}
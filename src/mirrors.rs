@@ -0,0 +1,95 @@
+// src/mirrors.rs
+// Where a file is fetched from: its URL in the project folder, after the
+// copies of it in the `mirrors` the manifest lists (a CDN, then the raw
+// files of its repository…), tried in that order. `net` fetches texts from
+// the first that answers; images, which the browser fetches, move on to the
+// next source when they fail.
+
+/// A file and the copies of it tried before it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sources {
+    /// The file itself, by which it is kept and reported
+    pub url: String,
+    /// Copies of it, in the order they are tried
+    pub mirrors: Vec<String>,
+}
+
+impl Sources {
+    /// URLs in the order they are tried
+    pub fn candidates(&self) -> impl Iterator<Item = &str> {
+        self.mirrors
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.url.as_str()))
+    }
+
+    /// The URL to request first
+    pub fn first(&self) -> &str {
+        self.mirrors.first().unwrap_or(&self.url)
+    }
+
+    /// Source to try after `failed`, which may itself be any of them.
+    pub fn after(&self, failed: &str) -> Option<&str> {
+        self.candidates().skip_while(|c| *c != failed).nth(1)
+    }
+}
+
+impl From<&str> for Sources {
+    fn from(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            mirrors: Vec::new(),
+        }
+    }
+}
+
+impl From<String> for Sources {
+    fn from(url: String) -> Self {
+        Self {
+            url,
+            mirrors: Vec::new(),
+        }
+    }
+}
+
+impl From<&String> for Sources {
+    fn from(url: &String) -> Self {
+        Self::from(url.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources() {
+        let sources = Sources {
+            url: "/public/projects/P/p1_dip.xml?v=3".to_string(),
+            mirrors: vec![
+                "https://cdn.example.org/P/p1_dip.xml?v=3".to_string(),
+                "https://raw.example.org/P/p1_dip.xml?v=3".to_string(),
+            ],
+        };
+        assert_eq!(
+            sources.candidates().collect::<Vec<_>>(),
+            vec![
+                "https://cdn.example.org/P/p1_dip.xml?v=3",
+                "https://raw.example.org/P/p1_dip.xml?v=3",
+                "/public/projects/P/p1_dip.xml?v=3",
+            ]
+        );
+        assert_eq!(sources.first(), "https://cdn.example.org/P/p1_dip.xml?v=3");
+        assert_eq!(
+            sources.after("https://cdn.example.org/P/p1_dip.xml?v=3"),
+            Some("https://raw.example.org/P/p1_dip.xml?v=3")
+        );
+        assert_eq!(sources.after("/public/projects/P/p1_dip.xml?v=3"), None);
+        assert_eq!(sources.after("https://elsewhere.org/p1.xml"), None);
+
+        // A file without mirrors has itself alone
+        let single = Sources::from("https://iiif.example.org/x/info.json");
+        assert_eq!(single.first(), "https://iiif.example.org/x/info.json");
+        assert_eq!(single.after("https://iiif.example.org/x/info.json"), None);
+    }
+}
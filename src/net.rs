@@ -0,0 +1,648 @@
+// src/net.rs
+// Fetching the viewer's files with timeouts, retries, mirrors and the
+// IndexedDB cache; failures come back as a `FetchError`.
+use crate::idb_cache;
+use crate::mirrors::Sources;
+use crate::perf::{self, Source};
+use crate::utils::window;
+use futures::future::{self, Either, FutureExt, LocalBoxFuture, Shared};
+use gloo::timers::callback::Timeout;
+use gloo_events::EventListener;
+use gloo_net::http::Request;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use web_sys::{AbortController, AbortSignal};
+
+type Text = Shared<LocalBoxFuture<'static, Result<String, FetchError>>>;
+
+/// A request shared by everyone waiting for its URL
+struct InFlight {
+    text: Text,
+    /// Aborts the request once no one waits for it
+    _abort: Abort,
+    waiting: usize,
+}
+
+thread_local! {
+    static IN_FLIGHT: RefCell<HashMap<String, InFlight>> = RefCell::new(HashMap::new());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FetchError {
+    /// The server has no such file (HTTP 404 or 410)
+    NotFound { url: String },
+    /// No answer (offline, server down, CORS…), or an error status other
+    /// than not found
+    Network {
+        url: String,
+        status: Option<u16>,
+        reason: String,
+    },
+    /// The answer could not be read as text, or as the JSON expected
+    Decode { url: String, reason: String },
+    /// No answer within the timeout
+    Timeout { url: String, ms: u32 },
+    /// Cancelled by dropping its `Abort`
+    Aborted { url: String },
+}
+
+impl FetchError {
+    /// Whether trying again may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Timeout { .. } => true,
+            FetchError::Network { status: None, .. } => true,
+            FetchError::Network {
+                status: Some(status),
+                ..
+            } => *status >= 500 || *status == 408 || *status == 429,
+            FetchError::NotFound { .. }
+            | FetchError::Decode { .. }
+            | FetchError::Aborted { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::NotFound { url } => write!(f, "{}: not found", url),
+            FetchError::Network {
+                url,
+                status: Some(status),
+                ..
+            } => write!(f, "{}: HTTP {}", url, status),
+            FetchError::Network { url, reason, .. } => write!(f, "{}: {}", url, reason),
+            FetchError::Decode { url, reason } => {
+                write!(f, "{}: unreadable answer: {}", url, reason)
+            }
+            FetchError::Timeout { url, ms } => write!(f, "{}: no answer after {} ms", url, ms),
+            FetchError::Aborted { url } => write!(f, "{}: cancelled", url),
+        }
+    }
+}
+
+/// How long a request may take and how often it is tried again
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Options {
+    pub timeout_ms: u32,
+    /// Further attempts after a transient failure
+    pub retries: u32,
+}
+
+impl Options {
+    pub const DEFAULT: Options = Options {
+        timeout_ms: 20_000,
+        retries: 2,
+    };
+
+    /// Pause before the attempt after `attempt` (0 for the first) failed
+    fn backoff_ms(attempt: u32) -> u32 {
+        500u32.saturating_mul(1 << attempt.min(6))
+    }
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A request in flight, aborted when this is dropped
+pub struct Abort(Option<AbortController>);
+
+impl Drop for Abort {
+    fn drop(&mut self) {
+        if let Some(controller) = &self.0 {
+            controller.abort();
+        }
+    }
+}
+
+/// What the server gave to check a kept copy against later
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Headers asking `url` to answer 304 while the copy is still good.
+    /// Only the viewer's own server is asked: elsewhere they would need a
+    /// CORS preflight a mirror may refuse.
+    fn conditional_headers(&self, url: &str) -> Vec<(&'static str, &str)> {
+        if !same_origin(url) {
+            return Vec::new();
+        }
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.etag {
+            headers.push(("If-None-Match", etag.as_str()));
+        }
+        if let Some(date) = &self.last_modified {
+            headers.push(("If-Modified-Since", date.as_str()));
+        }
+        headers
+    }
+}
+
+/// Whether `url` is relative to the viewer's own server
+fn same_origin(url: &str) -> bool {
+    !url.starts_with("//") && !url.contains("://")
+}
+
+/// What a request brought back
+#[derive(Debug, Clone, PartialEq)]
+enum Answer {
+    /// A text, with the validators the server sent for it
+    Text(String, Validators),
+    /// The kept copy is still good (HTTP 304)
+    NotModified,
+    /// The response itself, its body unread
+    Whole(web_sys::Response),
+}
+
+/// How the body of an answer is taken
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Body {
+    Text,
+    Whole,
+}
+
+/// The failure, if any, an answer with `status` stands for
+fn check_status(url: &str, status: u16) -> Result<(), FetchError> {
+    match status {
+        200..=299 => Ok(()),
+        404 | 410 => Err(FetchError::NotFound {
+            url: url.to_string(),
+        }),
+        status => Err(FetchError::Network {
+            url: url.to_string(),
+            status: Some(status),
+            reason: format!("HTTP {}", status),
+        }),
+    }
+}
+
+/// Wait `ms` milliseconds without blocking the page.
+async fn sleep(ms: u32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        if let Some(window) = window() {
+            let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(
+                &resolve,
+                ms.min(i32::MAX as u32) as i32,
+            );
+        }
+    });
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// One request for `url`, conditional on `kept` when it has validators,
+/// aborted after the timeout or once `cancel` is.
+async fn attempt(
+    url: &str,
+    timeout_ms: u32,
+    kept: &Validators,
+    cancel: Option<&AbortSignal>,
+    body: Body,
+) -> Result<Answer, FetchError> {
+    let cancelled = || cancel.is_some_and(|s| s.aborted());
+    let aborted = || FetchError::Aborted {
+        url: url.to_string(),
+    };
+    if cancelled() {
+        return Err(aborted());
+    }
+    let controller = AbortController::new().ok();
+    let signal = controller.as_ref().map(|c| c.signal());
+    let _cancel = cancel
+        .zip(controller.clone())
+        .map(|(cancel, c)| EventListener::once(cancel, "abort", move |_| c.abort()));
+    // Dropping the timer cancels it; it covers the body as well
+    let _timer = controller.map(|c| Timeout::new(timeout_ms, move || c.abort()));
+    let timed_out = || signal.as_ref().is_some_and(|s| s.aborted());
+    let timeout = || FetchError::Timeout {
+        url: url.to_string(),
+        ms: timeout_ms,
+    };
+    let conditions = kept.conditional_headers(url);
+    let request = conditions
+        .iter()
+        .fold(Request::get(url), |request, (name, value)| {
+            request.header(name, value)
+        });
+    let response = match request.abort_signal(signal.as_ref()).send().await {
+        Ok(response) => response,
+        Err(_) if cancelled() => return Err(aborted()),
+        Err(_) if timed_out() => return Err(timeout()),
+        Err(e) => {
+            return Err(FetchError::Network {
+                url: url.to_string(),
+                status: None,
+                reason: e.to_string(),
+            })
+        }
+    };
+    if response.status() == 304 && !conditions.is_empty() {
+        return Ok(Answer::NotModified);
+    }
+    check_status(url, response.status())?;
+    if body == Body::Whole {
+        return Ok(Answer::Whole(response.into()));
+    }
+    let headers = response.headers();
+    let validators = Validators {
+        etag: headers.get("ETag"),
+        last_modified: headers.get("Last-Modified"),
+    };
+    match response.text().await {
+        Ok(text) => Ok(Answer::Text(text, validators)),
+        Err(_) if cancelled() => Err(aborted()),
+        Err(_) if timed_out() => Err(timeout()),
+        Err(e) => Err(FetchError::Decode {
+            url: url.to_string(),
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// `url`, tried again after transient failures.
+async fn with_retries(
+    url: &str,
+    options: Options,
+    kept: &Validators,
+    cancel: Option<&AbortSignal>,
+    body: Body,
+) -> Result<Answer, FetchError> {
+    let mut tries = 0;
+    loop {
+        match attempt(url, options.timeout_ms, kept, cancel, body).await {
+            Err(e) if e.is_transient() && tries < options.retries => {
+                let pause = Options::backoff_ms(tries);
+                log::warn!("{}; trying again in {} ms", e, pause);
+                sleep(pause).await;
+                tries += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Keep `text` for `url` in the background.
+fn keep(url: &str, text: &str, validators: Validators) {
+    let (url, text) = (url.to_string(), text.to_string());
+    wasm_bindgen_futures::spawn_local(
+        async move { idb_cache::put(&url, &text, &validators).await },
+    );
+}
+
+/// The text at `url`: kept in its current version, else from the first of
+/// its sources that answers (the kept copy when the server says it has not
+/// changed), else an older copy kept.
+pub async fn get_text_with(
+    sources: impl Into<Sources>,
+    options: Options,
+) -> Result<String, FetchError> {
+    load(&sources.into(), options, None).await
+}
+
+/// `get_text`, aborted when the `Abort` returned with it is dropped.
+pub fn get_text_abortable(
+    sources: Sources,
+) -> (Abort, impl Future<Output = Result<String, FetchError>>) {
+    let controller = AbortController::new().ok();
+    let signal = controller.as_ref().map(|c| c.signal());
+    let text = async move { load(&sources, Options::DEFAULT, signal.as_ref()).await };
+    (Abort(controller), text)
+}
+
+/// The text of the file from the request in flight for it, until `cancel`
+/// is aborted.
+async fn load(
+    sources: &Sources,
+    options: Options,
+    cancel: Option<&AbortSignal>,
+) -> Result<String, FetchError> {
+    let url = sources.url.as_str();
+    let text = join(sources, options);
+    // Counted out however the wait ends, even when dropped
+    let _waiting = Waiting(url);
+    match cancel {
+        Some(cancel) => match future::select(text, aborted(cancel).boxed_local()).await {
+            Either::Left((result, _)) => result,
+            Either::Right(_) => Err(FetchError::Aborted {
+                url: url.to_string(),
+            }),
+        },
+        None => text.await,
+    }
+}
+
+/// Wait for the request for the file in flight, starting it (with
+/// `options`) if there is none.
+fn join(sources: &Sources, options: Options) -> Text {
+    IN_FLIGHT.with(|f| {
+        let mut f = f.borrow_mut();
+        let flight = f.entry(sources.url.clone()).or_insert_with(|| {
+            // No controller outside the browser
+            let controller = window().and_then(|_| AbortController::new().ok());
+            let signal = controller.as_ref().map(|c| c.signal());
+            let sources = sources.clone();
+            let text = async move { fetch_text(&sources, options, signal.as_ref()).await };
+            InFlight {
+                text: text.boxed_local().shared(),
+                _abort: Abort(controller),
+                waiting: 0,
+            }
+        });
+        flight.waiting += 1;
+        flight.text.clone()
+    })
+}
+
+/// One waiting for the request for a URL; when the last one goes the
+/// request is forgotten, and aborted if it had not finished.
+struct Waiting<'a>(&'a str);
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        IN_FLIGHT.with(|f| {
+            let mut f = f.borrow_mut();
+            if let Some(flight) = f.get_mut(self.0) {
+                flight.waiting = flight.waiting.saturating_sub(1);
+                if flight.waiting == 0 {
+                    f.remove(self.0);
+                }
+            }
+        });
+    }
+}
+
+/// Settle once `signal` is aborted.
+async fn aborted(signal: &AbortSignal) {
+    if signal.aborted() {
+        return;
+    }
+    let promise = js_sys::Promise::new(&mut |resolve, _| signal.set_onabort(Some(&resolve)));
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+async fn fetch_text(
+    sources: &Sources,
+    options: Options,
+    cancel: Option<&AbortSignal>,
+) -> Result<String, FetchError> {
+    let url = sources.url.as_str();
+    let start = perf::start();
+    let cached = idb_cache::get(url).await;
+    if let Some(cached) = cached.as_ref().filter(|c| c.current) {
+        perf::fetched(url, start, Source::Kept);
+        return Ok(cached.text.clone());
+    }
+    let kept = cached
+        .as_ref()
+        .map(|c| c.validators.clone())
+        .unwrap_or_default();
+    match from_sources(sources, options, &kept, cancel, Body::Text).await {
+        Ok(Answer::Text(text, validators)) => {
+            keep(url, &text, validators);
+            perf::fetched(url, start, Source::Network);
+            Ok(text)
+        }
+        Ok(Answer::Whole(_)) => unreachable!("asked for the text"),
+        Ok(Answer::NotModified) => match cached {
+            Some(cached) => {
+                log::info!("{} has not changed; using the copy kept", url);
+                perf::fetched(url, start, Source::Revalidated);
+                // Kept again under the version now asked for
+                keep(url, &cached.text, cached.validators);
+                Ok(cached.text)
+            }
+            None => Err(FetchError::Network {
+                url: url.to_string(),
+                status: Some(304),
+                reason: "HTTP 304".to_string(),
+            }),
+        },
+        Err(e) if e.is_transient() => match cached {
+            Some(cached) => {
+                log::warn!("{}; showing the copy kept from an earlier visit", e);
+                perf::fetched(url, start, Source::Stale);
+                Ok(cached.text)
+            }
+            None => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
+/// The file from the first of its sources that answers.
+async fn from_sources(
+    sources: &Sources,
+    options: Options,
+    kept: &Validators,
+    cancel: Option<&AbortSignal>,
+    body: Body,
+) -> Result<Answer, FetchError> {
+    first_answer(sources, |candidate| async move {
+        with_retries(&candidate, options, kept, cancel, body).await
+    })
+    .await
+}
+
+/// What `ask` answers for the first of `sources` that does not fail; an
+/// aborted request is not tried elsewhere.
+async fn first_answer<T, F>(
+    sources: &Sources,
+    mut ask: impl FnMut(String) -> F,
+) -> Result<T, FetchError>
+where
+    F: Future<Output = Result<T, FetchError>>,
+{
+    let last = sources.mirrors.len();
+    for (i, candidate) in sources.candidates().enumerate() {
+        let result = ask(candidate.to_string()).await;
+        match &result {
+            Ok(_) if i < last => log::info!("{} served from {}", sources.url, candidate),
+            Err(e) if i < last && !matches!(e, FetchError::Aborted { .. }) => {
+                log::warn!("{}; trying the next source", e);
+                continue;
+            }
+            _ => {}
+        }
+        return result;
+    }
+    unreachable!("a file has at least one source")
+}
+
+pub async fn get_text(sources: impl Into<Sources>) -> Result<String, FetchError> {
+    get_text_with(sources, Options::DEFAULT).await
+}
+
+/// The file as it came, for files kept whole (images, recordings) rather
+/// than read as text; not kept in IndexedDB.
+pub async fn get_response(sources: impl Into<Sources>) -> Result<web_sys::Response, FetchError> {
+    let sources = sources.into();
+    let kept = Validators::default();
+    match from_sources(&sources, Options::DEFAULT, &kept, None, Body::Whole).await? {
+        Answer::Whole(response) => Ok(response),
+        _ => unreachable!("asked for the whole response"),
+    }
+}
+
+/// The JSON of the file, read as a `T`.
+pub async fn get_json<T: DeserializeOwned>(sources: impl Into<Sources>) -> Result<T, FetchError> {
+    let sources = sources.into();
+    let text = get_text(sources.clone()).await?;
+    serde_json::from_str(&text).map_err(|e| FetchError::Decode {
+        url: sources.url,
+        reason: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_status() {
+        assert_eq!(check_status("a.xml", 200), Ok(()));
+        let missing = check_status("a.xml", 404).unwrap_err();
+        assert_eq!(missing.to_string(), "a.xml: not found");
+        assert!(!missing.is_transient());
+        let unavailable = check_status("a.xml", 503).unwrap_err();
+        assert_eq!(unavailable.to_string(), "a.xml: HTTP 503");
+        assert!(unavailable.is_transient());
+        assert!(check_status("a.xml", 429).unwrap_err().is_transient());
+        assert!(!check_status("a.xml", 403).unwrap_err().is_transient());
+    }
+
+    #[test]
+    fn test_transient() {
+        let timeout = FetchError::Timeout {
+            url: "a.xml".to_string(),
+            ms: 20_000,
+        };
+        assert!(timeout.is_transient());
+        assert_eq!(timeout.to_string(), "a.xml: no answer after 20000 ms");
+        let offline = FetchError::Network {
+            url: "a.xml".to_string(),
+            status: None,
+            reason: "TypeError: Failed to fetch".to_string(),
+        };
+        assert!(offline.is_transient());
+        assert_eq!(offline.to_string(), "a.xml: TypeError: Failed to fetch");
+        let decode = FetchError::Decode {
+            url: "t.json".to_string(),
+            reason: "expected value".to_string(),
+        };
+        assert!(!decode.is_transient());
+        let aborted = FetchError::Aborted {
+            url: "p2_dip.xml".to_string(),
+        };
+        assert!(!aborted.is_transient());
+        assert_eq!(aborted.to_string(), "p2_dip.xml: cancelled");
+    }
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(Options::backoff_ms(0), 500);
+        assert_eq!(Options::backoff_ms(2), 2000);
+        assert_eq!(Options::backoff_ms(40), Options::backoff_ms(6));
+    }
+
+    #[test]
+    fn test_in_flight() {
+        let url = "/public/projects/P/p2_dip.xml?v=3";
+        let waiting = || IN_FLIGHT.with(|f| f.borrow().get(url).map(|f| f.waiting));
+        let first = join(&url.into(), Options::DEFAULT);
+        let second = join(&url.into(), Options::DEFAULT);
+        // One request for both
+        assert!(first.ptr_eq(&second));
+        assert_eq!(waiting(), Some(2));
+        drop(Waiting(url));
+        assert_eq!(waiting(), Some(1));
+        drop(Waiting(url));
+        assert_eq!(waiting(), None);
+        // A later request starts afresh
+        assert!(!join(&url.into(), Options::DEFAULT).ptr_eq(&first));
+        drop(Waiting(url));
+    }
+
+    #[test]
+    fn test_first_answer() {
+        let sources = Sources {
+            url: "/public/projects/P/p1_dip.xml".to_string(),
+            mirrors: vec![
+                "https://cdn.example.org/P/p1_dip.xml".to_string(),
+                "https://raw.example.org/P/p1_dip.xml".to_string(),
+            ],
+        };
+        let ask = |answers: Vec<Result<&'static str, FetchError>>| {
+            let asked = RefCell::new(Vec::new());
+            let mut answers = answers.into_iter();
+            let result = first_answer(&sources, |url| {
+                asked.borrow_mut().push(url);
+                future::ready(answers.next().unwrap())
+            })
+            .now_or_never()
+            .unwrap();
+            (result, asked.into_inner())
+        };
+        let down = |url: &str| FetchError::Network {
+            url: url.to_string(),
+            status: Some(503),
+            reason: "Service Unavailable".to_string(),
+        };
+
+        // The CDN is down: the raw files answer
+        let (result, asked) = ask(vec![Err(down("cdn")), Ok("<TEI/>")]);
+        assert_eq!(result, Ok("<TEI/>"));
+        assert_eq!(asked, sources.candidates().take(2).collect::<Vec<_>>());
+
+        // Every source is asked before the file is given up
+        let (result, asked) = ask(vec![
+            Err(down("cdn")),
+            Err(down("raw")),
+            Err(FetchError::NotFound {
+                url: sources.url.clone(),
+            }),
+        ]);
+        assert!(matches!(result, Err(FetchError::NotFound { .. })));
+        assert_eq!(asked.last(), Some(&sources.url));
+
+        // An aborted request stays aborted
+        let (result, asked) = ask(vec![Err(FetchError::Aborted {
+            url: "cdn".to_string(),
+        })]);
+        assert!(matches!(result, Err(FetchError::Aborted { .. })));
+        assert_eq!(asked.len(), 1);
+    }
+
+    #[test]
+    fn test_conditional_headers() {
+        let kept = Validators {
+            etag: Some("\"5f2-1a\"".to_string()),
+            last_modified: Some("Tue, 03 Mar 2026 10:00:00 GMT".to_string()),
+        };
+        assert_eq!(
+            kept.conditional_headers("/public/projects/P/p1_dip.xml?v=3"),
+            vec![
+                ("If-None-Match", "\"5f2-1a\""),
+                ("If-Modified-Since", "Tue, 03 Mar 2026 10:00:00 GMT"),
+            ]
+        );
+        // Mirrors are not asked
+        assert!(kept
+            .conditional_headers("https://cdn.example.org/P/p1_dip.xml?v=3")
+            .is_empty());
+        assert!(Validators::default()
+            .conditional_headers("/public/projects/P/p1_dip.xml")
+            .is_empty());
+    }
+}
@@ -0,0 +1,369 @@
+// src/offline.rs
+// Offline copies of the projects, kept in Cache Storage. The pages read are
+// kept as they load, and a whole project can be downloaded at once; the
+// service worker in static/sw.js answers from this cache when the network
+// fails. Deep-zoom tiles are not kept: a project served through IIIF or DZI
+// needs its plain page images for offline reading.
+use crate::mirrors::Sources;
+use crate::net::{self, FetchError};
+use crate::project_config::{PageBibliography, ProjectConfig};
+use crate::tei_parser;
+use crate::utils::{resource_url, window};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::Cache;
+use yew::Callback;
+
+/// Cache shared with the service worker
+const CACHE_NAME: &str = "tei-viewer-offline";
+
+/// How much of a project can be read without a connection
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Availability {
+    /// Not checked yet, or no Cache Storage in this browser
+    #[default]
+    Unknown,
+    /// Not downloaded: only the pages visited so far, if any
+    Partial,
+    Downloading {
+        done: usize,
+        total: usize,
+    },
+    /// Downloaded, except `failed` resources that could not be fetched
+    Incomplete {
+        failed: usize,
+    },
+    Available,
+}
+
+/// Resources of a project named by its manifest alone. The image of a page
+/// with a diplomatic edition is named by that edition's `<facsimile>`, so
+/// it is only known once the XML has been read.
+#[derive(Debug, Default, PartialEq)]
+pub struct Resources {
+    /// (page, URL) of the diplomatic editions, read for their facsimile
+    pub diplomatic: Vec<(u32, String)>,
+    /// Pages whose image is named by their diplomatic edition
+    pub facsimiles: Vec<u32>,
+    /// Translations and the commentaries
+    pub texts: Vec<String>,
+    pub images: Vec<String>,
+}
+
+impl Resources {
+    pub fn of(project: &ProjectConfig) -> Self {
+        let mut resources = Self::default();
+        for page in &project.pages {
+            let n = page.number;
+            if page.has_diplomatic {
+                resources
+                    .diplomatic
+                    .push((n, project.file_url(&ProjectConfig::page_file(n, "dip"))));
+                if page.has_image {
+                    resources.facsimiles.push(n);
+                }
+            } else if page.has_image {
+                resources.images.push(project.get_facsimile_path(n, ""));
+            }
+            if page.has_translation {
+                for edition in page.translation_editions() {
+                    resources
+                        .texts
+                        .push(project.file_url(&ProjectConfig::page_file(n, &edition)));
+                }
+            }
+            for variant in &page.image_variants {
+                resources.images.push(project.file_url(&variant.path));
+            }
+            if let Some(thumbnail) = &page.thumbnail {
+                resources.images.push(project.file_url(thumbnail));
+            }
+            if let PageBibliography::File(file) = &page.bibl {
+                resources.texts.push(project.file_url(file));
+            }
+        }
+        if let Some(cover) = &project.thumbnail {
+            resources.images.push(project.file_url(cover));
+        }
+        for page in &project.pages {
+            let commentary = project.file_url(page.commentary_path());
+            if !resources.texts.contains(&commentary) {
+                resources.texts.push(commentary);
+            }
+        }
+        resources
+    }
+
+    /// Resources to fetch, the images named by the editions included
+    pub fn total(&self) -> usize {
+        self.diplomatic.len() + self.facsimiles.len() + self.texts.len() + self.images.len()
+    }
+}
+
+/// Key a resource is kept under: site-absolute, without the cache-busting
+/// query (the service worker ignores queries when answering).
+pub fn cache_key(url: &str) -> String {
+    let url = url.split('?').next().unwrap_or(url);
+    if url.starts_with('/') || url.contains("://") {
+        url.to_string()
+    } else {
+        resource_url(url)
+    }
+}
+
+/// Entry listing what a project download kept, so that its availability
+/// can be checked later. Never requested from the network.
+fn list_key(project: &str) -> String {
+    resource_url(&format!("offline/{}.json", project))
+}
+
+/// Cache Storage is only offered on secure origins (HTTPS, localhost).
+pub fn supported() -> bool {
+    window()
+        .is_some_and(|w| js_sys::Reflect::has(&w, &JsValue::from_str("caches")).unwrap_or(false))
+}
+
+/// Start the service worker that answers from the cache when offline.
+pub fn register_worker() {
+    let Some(navigator) = window().map(|w| w.navigator()) else {
+        return;
+    };
+    if !js_sys::Reflect::has(&navigator, &JsValue::from_str("serviceWorker")).unwrap_or(false) {
+        return;
+    }
+    let registration = navigator.service_worker().register(&resource_url("sw.js"));
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(registration).await {
+            log::warn!("Service worker not registered: {:?}", e);
+        }
+    });
+}
+
+async fn open() -> Result<Cache, JsValue> {
+    let caches = window().ok_or(JsValue::NULL)?.caches()?;
+    JsFuture::from(caches.open(CACHE_NAME)).await?.dyn_into()
+}
+
+async fn put_text(cache: &Cache, url: &str, body: &str) -> Result<(), JsValue> {
+    let response = web_sys::Response::new_with_opt_str(Some(body))?;
+    JsFuture::from(cache.put_with_str(&cache_key(url), &response)).await?;
+    Ok(())
+}
+
+/// Text of a kept resource, if there is one.
+async fn kept_text(cache: &Cache, url: &str) -> Option<String> {
+    let found = JsFuture::from(cache.match_with_str(url)).await.ok()?;
+    let response: web_sys::Response = found.dyn_into().ok()?;
+    JsFuture::from(response.text().ok()?)
+        .await
+        .ok()?
+        .as_string()
+}
+
+/// Fetch a file from `sources` into the cache and return its key, or `None`
+/// when the server has no such file: optional resources such as the
+/// commentary need not exist.
+async fn fetch_into(cache: &Cache, sources: Sources) -> Result<Option<String>, String> {
+    let key = cache_key(&sources.url);
+    let response = match net::get_response(sources).await {
+        Ok(response) => response,
+        Err(FetchError::NotFound { .. }) => return Ok(None),
+        Err(e) => return Err(e.to_string()),
+    };
+    JsFuture::from(cache.put_with_str(&key, &response))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    Ok(Some(key))
+}
+
+/// Keep a text resource that has just been downloaded, such as the XML of
+/// the page being read.
+pub fn keep_text(url: &str, body: &str) {
+    if !supported() {
+        return;
+    }
+    let (url, body) = (url.to_string(), body.to_string());
+    spawn_local(async move {
+        let result = match open().await {
+            Ok(cache) => put_text(&cache, &url, &body).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            log::warn!("Could not keep {} offline: {:?}", url, e);
+        }
+    });
+}
+
+/// Keep resources the browser has just loaded, such as the page image;
+/// fetching them again is normally answered by its HTTP cache. Images read
+/// from the reader's own files (`blob:` URLs) are not kept.
+pub fn keep(urls: Vec<String>) {
+    let urls: Vec<String> = urls
+        .into_iter()
+        .filter(|url| !url.starts_with("blob:") && !url.starts_with("data:"))
+        .collect();
+    if urls.is_empty() || !supported() {
+        return;
+    }
+    spawn_local(async move {
+        let Ok(cache) = open().await else {
+            return;
+        };
+        for url in urls {
+            if let Err(e) = fetch_into(&cache, url.as_str().into()).await {
+                log::warn!("Could not keep {} offline: {}", url, e);
+            }
+        }
+    });
+}
+
+/// Download every page of a project, reporting `(done, total)` as the
+/// resources arrive. Returns how many could not be fetched.
+pub async fn download_project(
+    project: ProjectConfig,
+    on_progress: Callback<(usize, usize)>,
+) -> usize {
+    let resources = Resources::of(&project);
+    let total = resources.total();
+    let Ok(cache) = open().await else {
+        return total;
+    };
+    let mut done = 0;
+    let mut failed = 0;
+    let mut kept = Vec::new();
+    let mut images = resources.images;
+    let mut step = |result: Result<Option<String>, String>, url: &str, kept: &mut Vec<String>| {
+        match result {
+            Ok(key) => kept.extend(key),
+            Err(e) => {
+                log::warn!("Could not download {}: {}", url, e);
+                failed += 1;
+            }
+        }
+        done += 1;
+        on_progress.emit((done, total));
+    };
+
+    for (page, url) in &resources.diplomatic {
+        let result = match net::get_text(project.sources_for(url)).await {
+            Ok(xml) => {
+                if resources.facsimiles.contains(page) {
+                    let declared = tei_parser::parse_tei_xml(&xml)
+                        .map(|doc| doc.facsimile.image_url)
+                        .unwrap_or_default();
+                    images.push(project.get_facsimile_path(*page, &declared));
+                }
+                put_text(&cache, url, &xml)
+                    .await
+                    .map(|_| Some(cache_key(url)))
+                    .map_err(|e| format!("{:?}", e))
+            }
+            Err(FetchError::NotFound { .. }) => {
+                if resources.facsimiles.contains(page) {
+                    images.push(project.get_facsimile_path(*page, ""));
+                }
+                Ok(None)
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        step(result, url, &mut kept);
+    }
+    for url in resources.texts.iter().chain(&images) {
+        let result = fetch_into(&cache, project.sources_for(url)).await;
+        step(result, url, &mut kept);
+    }
+    if let Ok(list) = serde_json::to_string(&kept) {
+        if let Err(e) = put_text(&cache, &list_key(&project.id), &list).await {
+            log::warn!("Could not record the download of {}: {:?}", project.id, e);
+        }
+    }
+    failed
+}
+
+/// Whether a project download is still complete in the cache, which the
+/// browser may have cleared since.
+pub async fn is_available(project: &str) -> bool {
+    let Ok(cache) = open().await else {
+        return false;
+    };
+    let Some(list) = kept_text(&cache, &list_key(project)).await else {
+        return false;
+    };
+    let Ok(urls) = serde_json::from_str::<Vec<String>>(&list) else {
+        return false;
+    };
+    for url in urls {
+        match JsFuture::from(cache.match_with_str(&url)).await {
+            Ok(found) if !found.is_undefined() => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project_config::{ImageVariant, PageInfo};
+
+    #[test]
+    fn test_cache_key() {
+        assert_eq!(
+            cache_key("/public/projects/P/p1_dip.xml?v=123"),
+            "/public/projects/P/p1_dip.xml"
+        );
+        assert_eq!(
+            cache_key("public/projects/P/p1_dip.xml?v=123"),
+            "/public/projects/P/p1_dip.xml"
+        );
+        assert_eq!(
+            cache_key("https://iiif.example.org/p1.jpg"),
+            "https://iiif.example.org/p1.jpg"
+        );
+    }
+
+    #[test]
+    fn test_resources() {
+        let mut project = ProjectConfig::new("P".to_string(), "P".to_string());
+        project.pages = vec![
+            PageInfo::new(1),
+            PageInfo::new(2)
+                .with_diplomatic(false)
+                .with_translation(false),
+            PageInfo::new(3).with_image(false),
+        ];
+        project.pages[2].commentary = Some("notes/p3.md".to_string());
+        project.pages[0].image_variants = vec![ImageVariant {
+            path: "images/p1_800.jpg".to_string(),
+            width: 800,
+        }];
+
+        let resources = Resources::of(&project);
+        assert_eq!(
+            resources.diplomatic,
+            vec![
+                (1, "/public/projects/P/p1_dip.xml".to_string()),
+                (3, "/public/projects/P/p3_dip.xml".to_string()),
+            ]
+        );
+        assert_eq!(
+            resources.texts,
+            vec![
+                "/public/projects/P/p1_trad.xml",
+                "/public/projects/P/p3_trad.xml",
+                "/public/projects/P/commentary.html",
+                "/public/projects/P/notes/p3.md",
+            ]
+        );
+        // Page 2 has no edition to name its image: the default file name
+        assert_eq!(
+            resources.images,
+            vec![
+                "/public/projects/P/images/p1_800.jpg",
+                "/public/projects/P/images/p2.jpg",
+            ]
+        );
+        assert_eq!(resources.facsimiles, vec![1]);
+        assert_eq!(resources.total(), 2 + 1 + 4 + 2);
+    }
+}
@@ -0,0 +1,191 @@
+// src/perf.rs
+// Timings for diagnosing slow projects, shown by the overlay that `?perf=1`
+// turns on: how long each resource took to fetch and where it came from
+// (the network, a copy kept in IndexedDB, the viewer's parsed documents…),
+// how long its TEI took to parse, and how long a page took to render once
+// its texts had come. Nothing is measured while the overlay is off.
+use crate::url_state::query_param;
+use crate::utils::window;
+use std::cell::RefCell;
+
+/// Query parameter turning the overlay on
+pub const PARAM: &str = "perf";
+
+/// Resources listed, the most recent ones
+const KEPT: usize = 40;
+
+thread_local! {
+    static ENABLED: bool = requested();
+    static ENTRIES: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Where a resource came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Network,
+    /// Kept in IndexedDB in its current version
+    Kept,
+    /// Kept, and found unchanged by the server (HTTP 304)
+    Revalidated,
+    /// An older copy kept, standing in for a failed request
+    Stale,
+    /// The viewer's cache of parsed documents
+    Memory,
+}
+
+impl Source {
+    /// Whether the resource was served without downloading it
+    pub fn is_hit(self) -> bool {
+        self != Source::Network
+    }
+
+    /// Name shown (Spanish, translated when shown)
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::Network => "Red",
+            Source::Kept => "IndexedDB",
+            Source::Revalidated => "Sin cambios (304)",
+            Source::Stale => "Copia anterior",
+            Source::Memory => "Memoria",
+        }
+    }
+}
+
+/// Timings of a resource, in milliseconds
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Entry {
+    /// URL without its query, or the page for render timings
+    pub resource: String,
+    pub fetch_ms: Option<f64>,
+    pub source: Option<Source>,
+    pub parse_ms: Option<f64>,
+    pub render_ms: Option<f64>,
+}
+
+fn requested() -> bool {
+    window()
+        .and_then(|w| w.location().search().ok())
+        .and_then(|search| query_param(&search, PARAM))
+        .is_some_and(|value| value != "0")
+}
+
+pub fn enabled() -> bool {
+    ENABLED.with(|e| *e)
+}
+
+/// Start of a measurement, when measuring.
+pub fn start() -> Option<f64> {
+    enabled().then(js_sys::Date::now)
+}
+
+fn since(start: Option<f64>) -> Option<f64> {
+    start.map(|start| js_sys::Date::now() - start)
+}
+
+/// Fold `update` into the entry of `resource`, moved to the end.
+fn record(resource: &str, update: impl FnOnce(&mut Entry)) {
+    let resource = resource.split('?').next().unwrap_or(resource);
+    ENTRIES.with(|e| {
+        let mut entries = e.borrow_mut();
+        let mut entry = match entries.iter().position(|e| e.resource == resource) {
+            Some(i) => entries.remove(i),
+            None => Entry {
+                resource: resource.to_string(),
+                ..Entry::default()
+            },
+        };
+        update(&mut entry);
+        entries.push(entry);
+        let excess = entries.len().saturating_sub(KEPT);
+        entries.drain(..excess);
+    });
+}
+
+/// `url` came from `source`, fetched since `start`.
+pub fn fetched(url: &str, start: Option<f64>, source: Source) {
+    if let Some(ms) = since(start) {
+        record(url, |e| {
+            e.fetch_ms = Some(ms);
+            e.source = Some(source);
+        });
+    }
+}
+
+/// `url` was already parsed.
+pub fn remembered(url: &str) {
+    if enabled() {
+        record(url, |e| {
+            e.fetch_ms = Some(0.0);
+            e.source = Some(Source::Memory);
+            e.parse_ms = None;
+        });
+    }
+}
+
+/// `url` was parsed since `start`.
+pub fn parsed(url: &str, start: Option<f64>) {
+    if let Some(ms) = since(start) {
+        record(url, |e| e.parse_ms = Some(ms));
+    }
+}
+
+/// `page` was rendered since `start`.
+pub fn rendered(page: &str, start: Option<f64>) {
+    if let Some(ms) = since(start) {
+        record(page, |e| e.render_ms = Some(ms));
+    }
+}
+
+/// Entries recorded, oldest first.
+pub fn entries() -> Vec<Entry> {
+    ENTRIES.with(|e| e.borrow().clone())
+}
+
+/// (served without downloading, fetched) among `entries`
+pub fn hits(entries: &[Entry]) -> (usize, usize) {
+    let sources = entries.iter().filter_map(|e| e.source);
+    let hits = sources.clone().filter(|s| s.is_hit()).count();
+    (hits, sources.count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        record("/public/projects/P/p1_dip.xml?v=3", |e| {
+            e.fetch_ms = Some(120.0);
+            e.source = Some(Source::Network);
+        });
+        record("/public/projects/P/p1_trad.xml?v=3", |e| {
+            e.fetch_ms = Some(2.0);
+            e.source = Some(Source::Kept);
+        });
+        record("/public/projects/P/p1_dip.xml?v=4", |e| {
+            e.parse_ms = Some(35.0)
+        });
+        record("P · 1", |e| e.render_ms = Some(18.0));
+        let entries = entries();
+        assert_eq!(entries.len(), 3);
+        // Timings of a file add up in one entry, moved to the end
+        assert_eq!(
+            entries[1],
+            Entry {
+                resource: "/public/projects/P/p1_dip.xml".to_string(),
+                fetch_ms: Some(120.0),
+                source: Some(Source::Network),
+                parse_ms: Some(35.0),
+                render_ms: None,
+            }
+        );
+        assert_eq!(hits(&entries), (1, 2));
+
+        for i in 0..KEPT {
+            record(&format!("p{}.xml", i), |e| e.parse_ms = Some(1.0));
+        }
+        let entries = super::entries();
+        assert_eq!(entries.len(), KEPT);
+        assert_eq!(entries[0].resource, "p0.xml");
+    }
+}
@@ -0,0 +1,63 @@
+// src/persistence.rs
+// Reader preferences kept in localStorage across sessions: display options
+// under fixed keys, plus the zoom and panel state of each project page.
+use gloo::storage::{LocalStorage, Storage};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+pub const THEME: &str = "tei-viewer.theme";
+pub const LANG: &str = "tei-viewer.lang";
+pub const SETTINGS: &str = "tei-viewer.settings";
+pub const PANEL_WIDTH: &str = "tei-viewer.image_panel_width";
+pub const PANEL_HEIGHT: &str = "tei-viewer.image_panel_height";
+pub const SCALE_BAR: &str = "tei-viewer.scale_bar";
+pub const HEATMAP: &str = "tei-viewer.heatmap";
+pub const KIOSK_INTERVAL: &str = "tei-viewer.kiosk_interval";
+pub const PAGE_WRAP: &str = "tei-viewer.page_wrap";
+pub const BOOKMARKS: &str = "tei-viewer.bookmarks";
+pub const HISTORY: &str = "tei-viewer.history";
+pub const ONBOARDING_DONE: &str = "tei-viewer.onboarding_done";
+pub const TRANSLATION_LANG: &str = "tei-viewer.translation_lang";
+
+/// Stored value under `key`, if present and readable as `T`.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    LocalStorage::get(key).ok()
+}
+
+/// Store a value; failures (private mode, quota) are only logged.
+pub fn save<T: Serialize>(key: &str, value: T) {
+    if let Err(e) = LocalStorage::set(key, value) {
+        log::warn!("Failed to store {}: {:?}", key, e);
+    }
+}
+
+/// How a reader left a page: facsimile zoom and pan, text view and legend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageView {
+    pub scale: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub view: String,
+    pub show_legend: bool,
+}
+
+fn page_view_key(project: &str, page: u32) -> String {
+    format!("tei-viewer.view.{}.{}", project, page)
+}
+
+pub fn load_page_view(project: &str, page: u32) -> Option<PageView> {
+    load(&page_view_key(project, page))
+}
+
+pub fn save_page_view(project: &str, page: u32, view: &PageView) {
+    save(&page_view_key(project, page), view);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_view_key() {
+        assert_eq!(page_view_key("PGM-XIII", 3), "tei-viewer.view.PGM-XIII.3");
+    }
+}
@@ -0,0 +1,333 @@
+// src/places.rs
+// Places of a project located on a map. A `<placeName>` gets its
+// coordinates from a `<geo>` child ("lat long", the TEI default), or else
+// from the project's optional `places.json` gazetteer, matched by `@ref`
+// (e.g. a Pleiades URI) or by name:
+//
+//     [{ "name": "Ἀλεξάνδρεια", "ref": "https://pleiades.stoa.org/places/727070",
+//        "lat": 31.2, "lon": 29.9 }]
+//
+// The map itself is drawn in Web Mercator over OpenStreetMap tiles.
+use crate::polytonic;
+use crate::tei_data::{TeiDocument, TextNode};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+/// Side of a map tile, in pixels
+pub const TILE_SIZE: f64 = 256.0;
+/// Closest zoom used to fit the places, so a single place still shows
+/// its surroundings
+const MAX_ZOOM: u32 = 9;
+
+/// An entry of `places.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GazetteerEntry {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "ref")]
+    pub ref_uri: Option<String>,
+    pub lat: f64,
+    pub lon: f64,
+}
+
+/// A line naming a place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attestation {
+    pub page: u32,
+    /// Line index in the page's diplomatic edition
+    pub line: usize,
+    /// How the line is referred to (see `TeiDocument::line_label`)
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapPlace {
+    pub name: String,
+    pub lat: f64,
+    pub lon: f64,
+    pub attestations: Vec<Attestation>,
+}
+
+/// Parse `places.json`.
+pub fn parse_gazetteer(json: &str) -> Result<Vec<GazetteerEntry>, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid places.json: {}", e))
+}
+
+/// Coordinates of a `<geo>` value: "lat long", or "lat, long".
+pub fn parse_geo(geo: &str) -> Option<(f64, f64)> {
+    let mut parts = geo
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty());
+    let lat: f64 = parts.next()?.parse().ok()?;
+    let lon: f64 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon)
+    {
+        return None;
+    }
+    Some((lat, lon))
+}
+
+/// Place names of `docs` with coordinates, grouped by gazetteer reference
+/// (or name) in order of first mention, and the names left without
+/// coordinates.
+pub fn locate<'a>(
+    docs: impl IntoIterator<Item = (u32, &'a TeiDocument)>,
+    gazetteer: &[GazetteerEntry],
+) -> (Vec<MapPlace>, Vec<String>) {
+    let mut places: Vec<MapPlace> = Vec::new();
+    // Grouping key of each place, parallel to `places`
+    let mut keys: Vec<String> = Vec::new();
+    let mut unlocated: Vec<String> = Vec::new();
+    for (page, doc) in docs {
+        for (line, l) in doc.lines.iter().enumerate() {
+            for (name, geo, ref_uri) in place_names(&l.content) {
+                let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+                if name.is_empty() {
+                    continue;
+                }
+                let key = ref_uri.clone().unwrap_or_else(|| polytonic::fold(&name));
+                let attestation = Attestation {
+                    page,
+                    line,
+                    label: doc.line_label(line).unwrap_or_default(),
+                };
+                if let Some(i) = keys.iter().position(|k| *k == key) {
+                    places[i].attestations.push(attestation);
+                    continue;
+                }
+                let coords = geo.and_then(parse_geo).or_else(|| {
+                    lookup(gazetteer, ref_uri.as_deref(), &name).map(|e| (e.lat, e.lon))
+                });
+                match coords {
+                    Some((lat, lon)) => {
+                        keys.push(key);
+                        places.push(MapPlace {
+                            name,
+                            lat,
+                            lon,
+                            attestations: vec![attestation],
+                        });
+                    }
+                    None => {
+                        if !unlocated.contains(&name) {
+                            unlocated.push(name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (places, unlocated)
+}
+
+fn place_names(nodes: &[TextNode]) -> Vec<(&str, Option<&str>, Option<String>)> {
+    let mut out = Vec::new();
+    for node in nodes {
+        match node {
+            TextNode::PlaceName {
+                name,
+                attrs,
+                ref_uri,
+            } => out.push((
+                name.as_str(),
+                attrs.get("geo").map(String::as_str),
+                ref_uri.clone(),
+            )),
+            TextNode::PersName { content, .. } | TextNode::Hi { content, .. } => {
+                out.extend(place_names(content))
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// The gazetteer entry for a place: by reference, else by name ignoring
+/// accents and case.
+fn lookup<'a>(
+    gazetteer: &'a [GazetteerEntry],
+    ref_uri: Option<&str>,
+    name: &str,
+) -> Option<&'a GazetteerEntry> {
+    let by_ref = ref_uri.and_then(|r| {
+        gazetteer
+            .iter()
+            .find(|e| e.ref_uri.as_deref().map(str::trim) == Some(r))
+    });
+    by_ref.or_else(|| {
+        let name = polytonic::fold(name);
+        gazetteer
+            .iter()
+            .find(|e| e.name.as_deref().map(polytonic::fold) == Some(name.clone()))
+    })
+}
+
+/// Position of a coordinate in the Web Mercator square, both axes in 0..1
+/// from the top left.
+fn mercator(lat: f64, lon: f64) -> (f64, f64) {
+    let lat = lat.clamp(-85.0511, 85.0511).to_radians();
+    let x = (lon + 180.0) / 360.0;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / PI) / 2.0;
+    (x, y)
+}
+
+/// Zoom and offset of a `width` × `height` map showing every place.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MapView {
+    pub zoom: u32,
+    /// World pixel at the top left corner of the map
+    origin: (f64, f64),
+    width: f64,
+    height: f64,
+}
+
+impl MapView {
+    /// The closest view fitting `places` with `padding` pixels around them.
+    pub fn fit(places: &[MapPlace], width: f64, height: f64, padding: f64) -> Self {
+        let points: Vec<(f64, f64)> = places.iter().map(|p| mercator(p.lat, p.lon)).collect();
+        let (mut min, mut max) = ((1.0f64, 1.0f64), (0.0f64, 0.0f64));
+        for &(x, y) in &points {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        if points.is_empty() {
+            min = (0.0, 0.0);
+            max = (1.0, 1.0);
+        }
+        let zoom = (0..=MAX_ZOOM)
+            .rev()
+            .find(|&z| {
+                let scale = TILE_SIZE * 2f64.powi(z as i32);
+                (max.0 - min.0) * scale <= width - 2.0 * padding
+                    && (max.1 - min.1) * scale <= height - 2.0 * padding
+            })
+            .unwrap_or(0);
+        let scale = TILE_SIZE * 2f64.powi(zoom as i32);
+        let center = ((min.0 + max.0) / 2.0 * scale, (min.1 + max.1) / 2.0 * scale);
+        Self {
+            zoom,
+            origin: (center.0 - width / 2.0, center.1 - height / 2.0),
+            width,
+            height,
+        }
+    }
+
+    /// Map pixel of a coordinate.
+    pub fn project(&self, lat: f64, lon: f64) -> (f64, f64) {
+        let (x, y) = mercator(lat, lon);
+        let scale = TILE_SIZE * 2f64.powi(self.zoom as i32);
+        (x * scale - self.origin.0, y * scale - self.origin.1)
+    }
+
+    /// Tiles covering the map: (tile x, tile y, map pixel of its top left
+    /// corner). Columns wrap around the antimeridian.
+    pub fn tiles(&self) -> Vec<(u32, u32, f64, f64)> {
+        let count = 2i64.pow(self.zoom);
+        let first = |origin: f64| (origin / TILE_SIZE).floor() as i64;
+        let last = |origin: f64, size: f64| ((origin + size) / TILE_SIZE).ceil() as i64;
+        let mut tiles = Vec::new();
+        for ty in first(self.origin.1).max(0)..last(self.origin.1, self.height).min(count) {
+            for tx in first(self.origin.0)..last(self.origin.0, self.width) {
+                tiles.push((
+                    tx.rem_euclid(count) as u32,
+                    ty as u32,
+                    tx as f64 * TILE_SIZE - self.origin.0,
+                    ty as f64 * TILE_SIZE - self.origin.1,
+                ));
+            }
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(xml_body: &str) -> TeiDocument {
+        let xml = format!(
+            "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\"><text><body>{}</body></text></TEI>",
+            xml_body
+        );
+        crate::tei_parser::parse_tei_xml(&xml).unwrap()
+    }
+
+    #[test]
+    fn test_parse_geo() {
+        assert_eq!(parse_geo("31.2 29.9"), Some((31.2, 29.9)));
+        assert_eq!(parse_geo(" 31.2, 29.9 "), Some((31.2, 29.9)));
+        assert_eq!(parse_geo("31.2"), None);
+        assert_eq!(parse_geo("131.2 29.9"), None);
+        assert_eq!(parse_geo("Alejandría"), None);
+    }
+
+    #[test]
+    fn test_locate() {
+        let gazetteer = parse_gazetteer(
+            r#"[{ "ref": "https://pleiades.stoa.org/places/727070", "lat": 31.2, "lon": 29.9 },
+                { "name": "Θῆβαι", "lat": 25.7, "lon": 32.6 }]"#,
+        )
+        .unwrap();
+        let p1 = page(
+            r#"<lb n="1"/><ab>ἐν <placeName ref="https://pleiades.stoa.org/places/727070">Ἀλεξανδρείᾳ</placeName></ab>
+               <lb n="2"/><ab><placeName>Θηβαι</placeName> <placeName>Ἄβυδος</placeName></ab>"#,
+        );
+        let p2 = page(
+            r#"<lb n="5"/><ab><placeName>Κόπτος<geo>26.0 32.8</geo></placeName> <placeName ref="https://pleiades.stoa.org/places/727070">Ἀλεξάνδρεια</placeName></ab>"#,
+        );
+        let (places, unlocated) = locate([(1, &p1), (2, &p2)], &gazetteer);
+        let summary: Vec<_> = places
+            .iter()
+            .map(|p| (p.name.as_str(), p.lat, p.attestations.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Ἀλεξανδρείᾳ", 31.2, 2),
+                ("Θηβαι", 25.7, 1),
+                ("Κόπτος", 26.0, 1)
+            ]
+        );
+        assert_eq!(places[0].attestations[1].page, 2);
+        assert_eq!(places[0].attestations[1].label, "5");
+        assert_eq!(unlocated, ["Ἄβυδος"]);
+    }
+
+    #[test]
+    fn test_map_view() {
+        let place = |lat, lon| MapPlace {
+            name: String::new(),
+            lat,
+            lon,
+            attestations: Vec::new(),
+        };
+        let places = [place(31.2, 29.9), place(25.7, 32.6)];
+        let view = MapView::fit(&places, 320.0, 240.0, 20.0);
+        for p in &places {
+            let (x, y) = view.project(p.lat, p.lon);
+            assert!((20.0..=300.0).contains(&x) && (20.0..=220.0).contains(&y));
+        }
+        // One zoom closer no longer fits
+        let closer = MapView {
+            zoom: view.zoom + 1,
+            ..view
+        };
+        let (x1, y1) = closer.project(31.2, 29.9);
+        let (x2, y2) = closer.project(25.7, 32.6);
+        assert!((x2 - x1).abs() > 280.0 || (y2 - y1).abs() > 200.0);
+
+        // A single place gets the closest zoom, centered
+        let single = MapView::fit(&places[..1], 320.0, 240.0, 20.0);
+        assert_eq!(single.zoom, MAX_ZOOM);
+        let (x, y) = single.project(31.2, 29.9);
+        assert!((x - 160.0).abs() < 1e-6 && (y - 120.0).abs() < 1e-6);
+
+        // The tiles cover the map
+        let tiles = view.tiles();
+        assert!(tiles.iter().all(|&(_, _, x, y)| x < 320.0 && y < 240.0));
+        assert!(tiles.iter().any(|&(_, _, x, y)| x <= 0.0 && y <= 0.0));
+        assert!(tiles
+            .iter()
+            .any(|&(_, _, x, y)| x + TILE_SIZE >= 320.0 && y + TILE_SIZE >= 240.0));
+    }
+}
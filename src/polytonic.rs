@@ -0,0 +1,50 @@
+// src/polytonic.rs
+// Normalization of polytonic Greek for searching: accents, breathings,
+// diaeresis and iota subscripts are dropped, case is folded and the final
+// and lunate sigma forms are unified, so "λογος" matches "Λόγου".
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Fold `text` to a diacritic- and case-insensitive search key.
+pub fn fold(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(char::to_lowercase)
+        .map(|c| match c {
+            'ς' | 'ϲ' => 'σ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// Whether `haystack` contains `needle`, ignoring diacritics and case when
+/// `ignore_diacritics` is set and matching exactly otherwise.
+pub fn contains(haystack: &str, needle: &str, ignore_diacritics: bool) -> bool {
+    if ignore_diacritics {
+        fold(haystack).contains(&fold(needle))
+    } else {
+        haystack.contains(needle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_strips_diacritics_and_case() {
+        assert_eq!(fold("λόγος"), "λογοσ");
+        assert_eq!(fold("Λόγου"), "λογου");
+        assert_eq!(fold("ἐν ἀρχῇ ἦν"), "εν αρχη ην");
+        // Lunate sigma, common in papyrus editions
+        assert_eq!(fold("ΘΕΟϹ"), "θεοσ");
+    }
+
+    #[test]
+    fn test_contains() {
+        assert!(contains("ἐν ἀρχῇ ἦν ὁ Λόγος", "λογος", true));
+        assert!(contains("τοῦ Λόγου", "λογου", true));
+        assert!(!contains("τοῦ Λόγου", "λογου", false));
+        assert!(contains("τοῦ Λόγου", "Λόγου", false));
+    }
+}
@@ -1,14 +1,270 @@
 // src/project_config.rs
+use crate::deep_zoom::iiif_image_url;
+use crate::mirrors::Sources;
+use crate::utils::{cache_busted, resource_url};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+// Width (px) of IIIF renditions requested as page thumbnails
+const THUMBNAIL_WIDTH: u32 = 200;
+/// Box a IIIF image is fitted into for the gallery, in pixels
+const GALLERY_SIZE: u32 = 2000;
+
+/// Version of the manifest format this viewer reads (see `manifest`)
+pub const SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProjectConfig {
+    /// Version of the manifest format; manifests without one are version 1
+    #[serde(default = "first_schema_version")]
+    pub schema_version: u32,
     pub id: String,
     pub name: String,
     pub description: String,
     pub pages: Vec<PageInfo>,
     pub metadata: ProjectMetadata,
+    /// Table of contents, when declared instead of read from the `<div>`s
+    #[serde(default)]
+    pub outline: Vec<OutlineEntry>,
+    /// Pages grouped under labels (a hymn, the recipes…)
+    #[serde(default)]
+    pub sections: Vec<PageSection>,
+    /// Names of the page files, when not the default ones
+    #[serde(flatten)]
+    pub file_patterns: FilePatterns,
+    /// IIIF Image API base URL of the pages without one, `{page}` for the number
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iiif_service: Option<String>,
+    /// Copies of the project folder, tried in order before it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<String>,
+    /// "New issue" page where readers report problems with the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issues_url: Option<String>,
+    /// Cover image of the project, relative to its folder or absolute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// Version of the project's files; a new one is fetched afresh
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_version: Option<String>,
+    /// Hashes of single files by path, in place of `content_version`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub file_hashes: HashMap<String, String>,
+    /// Editions of the pages besides the diplomatic one and the translation
+    #[serde(default)]
+    pub editions: Vec<EditionInfo>,
+    /// Colors and labels of the text's categories, overriding the viewer's
+    #[serde(default)]
+    pub legend: Vec<LegendEntry>,
+    /// Logo, accent color and header texts of the project
+    #[serde(flatten)]
+    pub branding: Branding,
+    /// Credit of the page images that give none themselves
+    #[serde(flatten)]
+    pub image_rights: ImageRights,
+    /// How the viewer first shows the project
+    #[serde(default)]
+    pub default_view: ViewDefaults,
+    /// Folder of a project served from another host, ending in '/'
+    #[serde(skip)]
+    pub folder: Option<String>,
+    /// Files served from URLs of their own, by path
+    #[serde(skip)]
+    pub files: HashMap<String, String>,
+}
+
+/// How a project first shows itself
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewDefaults {
+    /// Text view: "diplomatic", "translation", "both", "diff" or
+    /// "edition-{id}" for an edition of `editions`
+    #[serde(default)]
+    pub view: Option<String>,
+    /// Share of the viewer given to the facsimile, in percent
+    #[serde(default)]
+    pub image_panel: Option<f64>,
+    /// Starting zoom of the facsimile, 1 being its layout size
+    #[serde(default)]
+    pub zoom: Option<f32>,
+    /// Fit the facsimile to the panel instead of a fixed zoom
+    #[serde(default)]
+    pub fit: Option<Fit>,
+    /// Whether the commentary opens with the viewer (it does by default)
+    #[serde(default)]
+    pub commentary: Option<bool>,
+}
+
+impl ViewDefaults {
+    /// Whether the commentary of the project's first page opens by itself
+    pub fn opens_commentary(&self) -> bool {
+        self.commentary != Some(false)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Fit {
+    /// The facsimile's width fills the panel
+    Width,
+    /// The whole facsimile is visible
+    Page,
+}
+
+/// How a project presents itself while selected
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Branding {
+    /// Logo shown in the header, relative to the project folder or absolute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logo: Option<String>,
+    /// CSS color replacing the theme's accent, e.g. "#8e44ad"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>,
+    /// Header subtitle, instead of the viewer's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtitle: Option<String>,
+    /// Thanks shown under the subtitle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acknowledgements: Option<String>,
+}
+
+/// Credit the holding institution asks for where its scans are shown
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageRights {
+    /// e.g. "Foto: Rijksmuseum Amsterdam"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_credit: Option<String>,
+    /// Owner of the rights on the images, shown after "©"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rights_holder: Option<String>,
+}
+
+/// A category of the text in the color legend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LegendEntry {
+    /// Class of the category's text, e.g. "abbreviation"
+    pub category: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// CSS color, e.g. "#8e44ad"
+    #[serde(default)]
+    pub color: Option<String>,
+}
+
+/// A further edition of the pages (normalized, critical…)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditionInfo {
+    /// Short name, also the end of its file names: `p{n}_{id}.xml`
+    pub id: String,
+    /// Name of its panel and view toggle
+    pub label: String,
+    /// Kind of edition, e.g. "normalized" or "critical"
+    #[serde(default, rename = "type")]
+    pub kind: String,
+}
+
+/// Names of a project's page files, with `{page}`, `{page:03}` and `{lang}`
+/// placeholders; unset ones keep the default names
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FilePatterns {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diplomatic_pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translation_pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_pattern: Option<String>,
+}
+
+impl FilePatterns {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The file a default name stands for, e.g. "p3_dip.xml" → "folio-003-dipl.xml"
+    pub fn resolve(&self, path: &str) -> Option<String> {
+        let (pattern, page) = if let Some(name) = path.strip_prefix("images/") {
+            let page = name.strip_prefix('p')?.strip_suffix(".jpg")?;
+            (self.image_pattern.as_ref()?, page)
+        } else {
+            let name = path.strip_prefix('p')?.strip_suffix(".xml")?;
+            let (page, edition) = name.split_once('_')?;
+            let pattern = match edition.split_once('_') {
+                None if edition == "dip" => self.diplomatic_pattern.as_ref()?,
+                None if edition == "trad" => self.translation_pattern.as_ref()?,
+                Some(("trad", lang)) => {
+                    let pattern = self.translation_pattern.as_ref()?;
+                    let page: u32 = page.parse().ok()?;
+                    return Some(expand(&pattern.replace("{lang}", lang), page));
+                }
+                _ => return None,
+            };
+            (pattern, page)
+        };
+        let page: u32 = page.parse().ok()?;
+        Some(expand(pattern, page))
+    }
+}
+
+fn is_absolute(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// `pattern` with its `{page}` and `{page:0N}` placeholders filled in.
+fn expand(pattern: &str, page: u32) -> String {
+    let mut out = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find("{page") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let width = rest[start + 5..start + end]
+            .strip_prefix(":0")
+            .and_then(|w| w.parse().ok());
+        match width {
+            Some(width) => out.push_str(&format!("{:0width$}", page, width = width)),
+            None => out.push_str(&page.to_string()),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutlineEntry {
+    pub title: String,
+    pub page: u32,
+    /// Line where the entry starts, resolved like a tour stop's
+    #[serde(default)]
+    pub line: Option<String>,
+    #[serde(default)]
+    pub children: Vec<OutlineEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageSection {
+    pub label: String,
+    /// Page numbers in the section
+    pub pages: Vec<u32>,
+}
+
+/// `pages` in runs under the same section label (`None` outside sections).
+pub fn group_pages<'a>(
+    pages: &'a [PageInfo],
+    sections: &'a [PageSection],
+) -> Vec<(Option<&'a str>, Vec<&'a PageInfo>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&PageInfo>)> = Vec::new();
+    for page in pages {
+        let label = sections
+            .iter()
+            .find(|s| s.pages.contains(&page.number))
+            .map(|s| s.label.as_str());
+        match groups.last_mut() {
+            Some((last, run)) if *last == label => run.push(page),
+            _ => groups.push((label, vec![page])),
+        }
+    }
+    groups
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -18,6 +274,123 @@ pub struct PageInfo {
     pub has_diplomatic: bool,
     pub has_translation: bool,
     pub has_image: bool,
+    /// Alternative resolutions of the page image, used to build `srcset`
+    #[serde(default)]
+    pub image_variants: Vec<ImageVariant>,
+    /// IIIF Image API service base URL; the facsimile is then tiled
+    #[serde(default)]
+    pub iiif_service: Option<String>,
+    /// Deep Zoom `.dzi` descriptor
+    #[serde(default)]
+    pub dzi: Option<String>,
+    /// Scan resolution in the `<graphic>` pixel space, for measurements
+    #[serde(default)]
+    pub dpi: Option<f64>,
+    /// Recorded reading of the page, played with line highlighting
+    #[serde(default)]
+    pub audio: Option<AudioReading>,
+    /// Small image for the page overview
+    #[serde(default)]
+    pub thumbnail: Option<String>,
+    /// Languages of the translations, each in `p{n}_trad_{lang}.xml`
+    #[serde(default)]
+    pub translations: Vec<String>,
+    /// Further images of the page (details, the verso, UV light…)
+    #[serde(default)]
+    pub images: Vec<PageImage>,
+    /// Commentary (`.html`, `.md` or TEI `.xml`), else the project's `commentary.html`
+    #[serde(default)]
+    pub commentary: Option<String>,
+    /// Works cited for the page: the entries, or a file listing them
+    #[serde(default)]
+    pub bibl: PageBibliography,
+    /// Credit of the page image, over the project's
+    #[serde(flatten)]
+    pub image_rights: ImageRights,
+}
+
+/// A page's bibliography: the entries, or a file listing them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PageBibliography {
+    Entries(Vec<BiblEntry>),
+    /// Relative to the project folder or absolute
+    File(String),
+}
+
+impl Default for PageBibliography {
+    fn default() -> Self {
+        PageBibliography::Entries(Vec::new())
+    }
+}
+
+/// A work cited, alone or with a link to it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BiblEntry {
+    Citation(String),
+    Linked { text: String, url: String },
+}
+
+impl BiblEntry {
+    pub fn text(&self) -> &str {
+        match self {
+            BiblEntry::Citation(text) | BiblEntry::Linked { text, .. } => text,
+        }
+    }
+
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            BiblEntry::Citation(_) => None,
+            BiblEntry::Linked { url, .. } => Some(url),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageImage {
+    pub label: String,
+    /// Path relative to the project folder or an absolute URL
+    pub path: String,
+    /// Whether the line zones can be drawn over the image
+    #[serde(default)]
+    pub zones: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioReading {
+    /// Path relative to the project folder (e.g. "audio/p1.mp3") or an absolute URL
+    pub path: String,
+    /// When each line is read, in playback order
+    #[serde(default)]
+    pub timings: Vec<LineTiming>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineTiming {
+    /// Line reference, resolved like a tour stop's
+    pub line: String,
+    /// Start and end of the line in the recording, in seconds
+    pub start: f64,
+    pub end: f64,
+}
+
+impl AudioReading {
+    /// Line being read `time` seconds into the recording.
+    pub fn line_at(&self, time: f64) -> Option<&str> {
+        self.timings
+            .iter()
+            .find(|t| time >= t.start && time < t.end)
+            .map(|t| t.line.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageVariant {
+    /// Path relative to the project folder (e.g. "images/p1_800.jpg") or an absolute URL
+    pub path: String,
+    /// Intrinsic width of this variant in pixels
+    pub width: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -29,37 +402,313 @@ pub struct ProjectMetadata {
     pub country: String,
     pub language: String,
     pub date_range: String,
+    /// License of the edition, e.g. "CC BY 4.0" or its URL
+    #[serde(default)]
+    pub license: Option<String>,
+    /// DOI of the edition, bare ("10.5281/zenodo.123") or as a URL
+    #[serde(default)]
+    pub doi: Option<String>,
+    /// The editors' preferred citation
+    #[serde(default)]
+    pub how_to_cite: Option<String>,
+}
+
+impl ProjectMetadata {
+    /// Address the DOI resolves at.
+    pub fn doi_url(&self) -> Option<String> {
+        let doi = self
+            .doi
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())?;
+        Some(
+            if doi.starts_with("http://") || doi.starts_with("https://") {
+                doi.to_string()
+            } else {
+                format!("https://doi.org/{}", doi.trim_start_matches("doi:"))
+            },
+        )
+    }
+
+    /// How to cite the edition: the editors' wording, else one from the metadata.
+    pub fn citation(&self, name: &str, url: &str) -> String {
+        if let Some(cite) = self.how_to_cite.as_ref().filter(|c| !c.trim().is_empty()) {
+            return cite.trim().to_string();
+        }
+        let editor = (!self.editor.is_empty()).then(|| format!("{} (ed.)", self.editor));
+        let parts: Vec<String> = editor
+            .into_iter()
+            .chain([name.to_string(), self.institution.clone()])
+            .filter(|p| !p.is_empty())
+            .collect();
+        let link = self.doi_url().unwrap_or_else(|| url.to_string());
+        format!("{}. {}", parts.join(". "), link)
+    }
+}
+
+fn first_schema_version() -> u32 {
+    1
 }
 
 impl ProjectConfig {
     pub fn new(id: String, name: String) -> Self {
         Self {
+            schema_version: SCHEMA_VERSION,
             id,
             name,
             description: String::new(),
             pages: Vec::new(),
             metadata: ProjectMetadata::default(),
+            outline: Vec::new(),
+            sections: Vec::new(),
+            file_patterns: FilePatterns::default(),
+            iiif_service: None,
+            mirrors: Vec::new(),
+            issues_url: None,
+            thumbnail: None,
+            content_version: None,
+            file_hashes: HashMap::new(),
+            editions: Vec::new(),
+            legend: Vec::new(),
+            branding: Branding::default(),
+            image_rights: ImageRights::default(),
+            default_view: ViewDefaults::default(),
+            folder: None,
+            files: HashMap::new(),
+        }
+    }
+
+    /// Table of contents: the outline, else each section at its first page.
+    pub fn contents(&self) -> Vec<OutlineEntry> {
+        if !self.outline.is_empty() {
+            return self.outline.clone();
         }
+        self.sections
+            .iter()
+            .filter_map(|section| {
+                let page = self
+                    .pages
+                    .iter()
+                    .find(|p| section.pages.contains(&p.number))?;
+                Some(OutlineEntry {
+                    title: section.label.clone(),
+                    page: page.number,
+                    line: None,
+                    children: Vec::new(),
+                })
+            })
+            .collect()
     }
 
     pub fn get_page(&self, page_num: u32) -> Option<&PageInfo> {
         self.pages.iter().find(|p| p.number == page_num)
     }
 
+    /// Page labelled `label`, ignoring case and surrounding whitespace.
+    pub fn page_by_label(&self, label: &str) -> Option<&PageInfo> {
+        let label = label.trim();
+        self.pages
+            .iter()
+            .find(|p| p.label.trim().eq_ignore_ascii_case(label))
+    }
+
+    /// Page `step` positions before or after `page_num` in manifest order
+    pub fn adjacent_page(&self, page_num: u32, step: i32) -> Option<u32> {
+        let pos = self.pages.iter().position(|p| p.number == page_num)?;
+        let target = pos.checked_add_signed(step as isize)?;
+        self.pages.get(target).map(|p| p.number)
+    }
+
+    /// Next page with an image file the browser can fetch ahead
+    pub fn next_image_page(&self, page_num: u32) -> Option<&PageInfo> {
+        let next = self.adjacent_page(page_num, 1)?;
+        self.get_page(next)
+            .filter(|p| p.has_image && p.dzi.is_none() && self.iiif_service(next).is_none())
+    }
+
+    /// Page one step back (-1) or forward (1); with `wrap`, around the ends.
+    pub fn step_page(&self, page_num: u32, step: i32, wrap: bool) -> Option<u32> {
+        self.adjacent_page(page_num, step).or_else(|| {
+            let edge = if step > 0 {
+                self.pages.first()
+            } else {
+                self.pages.last()
+            };
+            edge.map(|p| p.number)
+                .filter(|n| wrap && *n != page_num && self.get_page(page_num).is_some())
+        })
+    }
+
     pub fn get_page_count(&self) -> usize {
         self.pages.len()
     }
 
+    /// Whether the project is served from another host.
+    pub fn is_remote(&self) -> bool {
+        self.folder.is_some()
+    }
+
+    /// Default name of a page's file of `edition`, before the file patterns.
+    pub fn page_file(page_num: u32, edition: &str) -> String {
+        format!("p{}_{}.xml", page_num, edition)
+    }
+
+    /// The file a default page file name (e.g. "p3_dip.xml") stands for.
+    fn file_path(&self, path: &str) -> String {
+        self.file_patterns
+            .resolve(path)
+            .unwrap_or_else(|| path.to_string())
+    }
+
+    /// Whether `path` is served from a URL of its own.
+    pub fn has_file(&self, path: &str) -> bool {
+        self.files.contains_key(path)
+    }
+
+    /// URL of `path`, relative to the project folder unless absolute.
+    pub fn file_url(&self, path: &str) -> String {
+        if is_absolute(path) {
+            return path.to_string();
+        }
+        let path = path.trim_start_matches('/');
+        if let Some(url) = self.files.get(path) {
+            return url.clone();
+        }
+        let path = self.file_path(path);
+        if is_absolute(&path) {
+            return path;
+        }
+        let path = path.trim_start_matches('/');
+        match &self.folder {
+            Some(folder) => format!("{}{}", folder, path),
+            None => resource_url(&format!("public/projects/{}/{}", self.id, path)),
+        }
+    }
+
+    /// Version declared for `path`: its hash, else the content version.
+    fn content_version_of(&self, path: &str) -> Option<&str> {
+        let path = path.trim_start_matches('/');
+        self.file_hashes
+            .get(path)
+            .or_else(|| self.file_hashes.get(&self.file_path(path)))
+            .or(self.content_version.as_ref())
+            .map(String::as_str)
+    }
+
+    /// `file_url` with the version the manifest declares for the file.
+    pub fn versioned_file_url(&self, path: &str) -> String {
+        let url = self.file_url(path);
+        match self.content_version_of(path) {
+            Some(version) => cache_busted(&url, version),
+            None => url,
+        }
+    }
+
+    /// `url`, as `file_url` gives it, with its copies in the mirrors.
+    pub fn sources_for(&self, url: &str) -> Sources {
+        let folder = self.file_url("");
+        let mirrors = match url.strip_prefix(folder.as_str()) {
+            Some(path) => self
+                .mirrors
+                .iter()
+                .map(|m| m.trim())
+                .filter(|m| !m.is_empty())
+                .map(|m| format!("{}/{}", m.trim_end_matches('/'), path))
+                .collect(),
+            None => Vec::new(),
+        };
+        Sources {
+            url: url.to_string(),
+            mirrors,
+        }
+    }
+
+    pub fn get_edition_path(&self, page_num: u32, edition: &str) -> String {
+        self.versioned_file_url(&Self::page_file(page_num, edition))
+    }
+
     pub fn get_diplomatic_path(&self, page_num: u32) -> String {
-        format!("projects/{}/p{}_dip.xml", self.id, page_num)
+        self.get_edition_path(page_num, "dip")
+    }
+
+    /// Translation of the page in `lang`, else in its first language.
+    pub fn get_translation_path(&self, page_num: u32, lang: Option<&str>) -> String {
+        let edition = self
+            .get_page(page_num)
+            .map_or_else(|| "trad".to_string(), |p| p.translation_edition(lang));
+        self.get_edition_path(page_num, &edition)
     }
 
-    pub fn get_translation_path(&self, page_num: u32) -> String {
-        format!("projects/{}/p{}_trad.xml", self.id, page_num)
+    pub fn get_commentary_path(&self, page_num: u32) -> String {
+        self.versioned_file_url(
+            self.get_page(page_num)
+                .map_or(crate::commentary::DEFAULT_PATH, PageInfo::commentary_path),
+        )
     }
 
+    /// Image of the page: a full-size IIIF rendition, else its image file.
     pub fn get_image_path(&self, page_num: u32) -> String {
-        format!("projects/{}/images/p{}.jpg", self.id, page_num)
+        match self.iiif_service(page_num) {
+            Some(service) => iiif_image_url(&service, "full", "max"),
+            None => self.file_url(&format!("images/p{}.jpg", page_num)),
+        }
+    }
+
+    /// Image of the page as its TEI `<graphic>` declares it, else its image file.
+    pub fn get_facsimile_path(&self, page_num: u32, declared: &str) -> String {
+        let raw = declared.trim();
+        if is_absolute(raw) || raw.starts_with('/') {
+            raw.to_string()
+        } else if raw.starts_with("public/") {
+            format!("/{}", raw)
+        } else if raw.is_empty() {
+            self.file_url(&format!("images/p{}.jpg", page_num))
+        } else {
+            // A file name or relative path: looked for under `images/`
+            let name = raw.rsplit('/').next().unwrap_or(raw);
+            self.file_url(&format!("images/{}", name))
+        }
+    }
+
+    /// Caption crediting the page image, e.g. "Foto: X · © Y".
+    pub fn image_attribution(&self, page_num: u32) -> Option<String> {
+        let page = self.get_page(page_num).map(|p| &p.image_rights);
+        let credit = page
+            .and_then(|r| r.image_credit.as_deref())
+            .or(self.image_rights.image_credit.as_deref())
+            .map(str::trim)
+            .filter(|c| !c.is_empty());
+        let holder = page
+            .and_then(|r| r.rights_holder.as_deref())
+            .or(self.image_rights.rights_holder.as_deref())
+            .map(str::trim)
+            .filter(|h| !h.is_empty());
+        match (credit, holder) {
+            (Some(credit), Some(holder)) => Some(format!("{} · © {}", credit, holder)),
+            (Some(credit), None) => Some(credit.to_string()),
+            (None, Some(holder)) => Some(format!("© {}", holder)),
+            (None, None) => None,
+        }
+    }
+
+    /// IIIF Image API base URL of the page: its own, else the project's.
+    pub fn iiif_service(&self, page_num: u32) -> Option<String> {
+        self.get_page(page_num)
+            .and_then(|p| p.iiif_service.clone())
+            .or_else(|| Some(expand(self.iiif_service.as_deref()?, page_num)))
+    }
+
+    /// Give the pages without a IIIF service of their own the project's.
+    pub fn resolve_iiif_services(&mut self) {
+        if self.iiif_service.is_none() {
+            return;
+        }
+        for number in self.pages.iter().map(|p| p.number).collect::<Vec<_>>() {
+            let service = self.iiif_service(number);
+            if let Some(page) = self.pages.iter_mut().find(|p| p.number == number) {
+                page.iiif_service = service;
+            }
+        }
     }
 }
 
@@ -73,6 +722,9 @@ impl Default for ProjectMetadata {
             country: String::new(),
             language: String::from("grc"),
             date_range: String::new(),
+            license: None,
+            doi: None,
+            how_to_cite: None,
         }
     }
 }
@@ -85,6 +737,17 @@ impl PageInfo {
             has_diplomatic: true,
             has_translation: true,
             has_image: true,
+            image_variants: Vec::new(),
+            iiif_service: None,
+            dzi: None,
+            dpi: None,
+            audio: None,
+            thumbnail: None,
+            translations: Vec::new(),
+            images: Vec::new(),
+            commentary: None,
+            bibl: PageBibliography::default(),
+            image_rights: ImageRights::default(),
         }
     }
 
@@ -107,53 +770,119 @@ impl PageInfo {
         self.has_image = has;
         self
     }
-}
 
-// Predefined project configurations
-pub struct ProjectRegistry;
+    /// Editions of the page's translations: "trad_es", "trad_en"… or "trad".
+    pub fn translation_editions(&self) -> Vec<String> {
+        if self.translations.is_empty() {
+            return vec!["trad".to_string()];
+        }
+        self.translations
+            .iter()
+            .map(|lang| format!("trad_{}", lang))
+            .collect()
+    }
+
+    /// Edition of the translation in `preferred` if the page has it, else the first.
+    pub fn translation_edition(&self, preferred: Option<&str>) -> String {
+        let lang = preferred
+            .and_then(|p| self.translations.iter().find(|l| l.as_str() == p))
+            .or(self.translations.first());
+        match lang {
+            Some(lang) => format!("trad_{}", lang),
+            None => "trad".to_string(),
+        }
+    }
 
-impl ProjectRegistry {
-    pub fn get_all_projects() -> HashMap<String, ProjectConfig> {
-        let mut projects = HashMap::new();
+    /// What the manifest says the page lacks, as interface strings
+    pub fn missing_content(&self) -> Vec<&'static str> {
+        [
+            (self.has_diplomatic, "sin diplomática"),
+            (self.has_translation, "sin traducción"),
+            (self.has_image, "sin imagen"),
+        ]
+        .into_iter()
+        .filter(|(has, _)| !has)
+        .map(|(_, missing)| missing)
+        .collect()
+    }
 
-        // PGM XIII Project
-        let mut pgm_xiii = ProjectConfig::new(
-            "PGM-XIII".to_string(),
-            "Papyri Graecae Magicae XIII".to_string(),
-        );
-        pgm_xiii.description = "Magical papyrus from the Greek Magical Papyri corpus, \
-                                housed at the Rijksmuseum Amsterdam (AMS76)."
-            .to_string();
-        pgm_xiii.metadata = ProjectMetadata {
-            author: "Anonymous".to_string(),
-            editor: "Robert W. Daniel".to_string(),
-            collection: "Papyri Graecae Magicae".to_string(),
-            institution: "Rijksmuseum Amsterdam".to_string(),
-            country: "Netherlands".to_string(),
-            language: "Ancient Greek (grc)".to_string(),
-            date_range: "1st c. BCE – 4th c. CE".to_string(),
-        };
+    /// File of the page's commentary.
+    pub fn commentary_path(&self) -> &str {
+        self.commentary
+            .as_deref()
+            .unwrap_or(crate::commentary::DEFAULT_PATH)
+    }
 
-        // Add pages (adjust based on your actual pages)
-        for i in 1..=25 {
-            pgm_xiii.pages.push(
-                PageInfo::new(i)
-                    .with_label(format!("Folio {}", i))
-                    .with_translation(i == 1), // Only page 1 has translation for now
-            );
+    /// Image shown for the page in overviews: its thumbnail, else the smallest
+    /// rendition or the page image.
+    pub fn thumbnail_path(&self) -> Option<String> {
+        if let Some(path) = &self.thumbnail {
+            return Some(path.clone());
         }
+        if let Some(variant) = self.image_variants.iter().min_by_key(|v| v.width) {
+            return Some(variant.path.clone());
+        }
+        if let Some(service) = &self.iiif_service {
+            return Some(iiif_image_url(
+                service,
+                "full",
+                &format!("{},", THUMBNAIL_WIDTH),
+            ));
+        }
+        self.has_image
+            .then(|| format!("images/p{}.jpg", self.number))
+    }
 
-        projects.insert(pgm_xiii.id.clone(), pgm_xiii);
-
-        projects
+    /// Image shown for the page in the gallery: its largest rendition or the
+    /// page image; `None` without one.
+    pub fn gallery_image_path(&self) -> Option<String> {
+        if !self.has_image || self.dzi.is_some() {
+            return None;
+        }
+        if let Some(variant) = self.image_variants.iter().max_by_key(|v| v.width) {
+            return Some(variant.path.clone());
+        }
+        if let Some(service) = &self.iiif_service {
+            let size = format!("!{},{}", GALLERY_SIZE, GALLERY_SIZE);
+            return Some(iiif_image_url(service, "full", &size));
+        }
+        Some(format!("images/p{}.jpg", self.number))
     }
 
-    pub fn get_project(id: &str) -> Option<ProjectConfig> {
-        Self::get_all_projects().get(id).cloned()
+    /// Width of the largest declared image variant, if any
+    pub fn max_variant_width(&self) -> Option<u32> {
+        self.image_variants.iter().map(|v| v.width).max()
     }
+}
+
+/// Manifest URLs listed by `public/projects/index.json`: project ids or
+/// manifest paths.
+pub fn parse_project_index(json: &str) -> Result<Vec<String>, String> {
+    let entries: Vec<String> =
+        serde_json::from_str(json).map_err(|e| format!("index.json no es válido: {}", e))?;
+    Ok(manifest_urls(&entries))
+}
+
+/// Manifest URL of each index entry, skipping blank ones.
+pub fn manifest_urls(entries: &[String]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(manifest_url)
+        .collect()
+}
 
-    pub fn get_project_ids() -> Vec<String> {
-        Self::get_all_projects().keys().cloned().collect()
+fn manifest_url(entry: &str) -> String {
+    if entry.starts_with("http://") || entry.starts_with("https://") {
+        entry.to_string()
+    } else if entry.ends_with(".json") {
+        resource_url(&format!(
+            "public/projects/{}",
+            entry.trim_start_matches('/')
+        ))
+    } else {
+        resource_url(&format!("public/projects/{}/manifest.json", entry))
     }
 }
 
@@ -168,6 +897,111 @@ mod tests {
         assert_eq!(config.name, "Test Project");
     }
 
+    #[test]
+    fn test_editions() {
+        let json = r#"{"id": "T", "name": "T", "description": "", "pages": [],
+            "metadata": {"author": "", "editor": "", "collection": "", "institution": "",
+                         "country": "", "language": "", "date_range": ""},
+            "editions": [{"id": "norm", "label": "Texto normalizado", "type": "normalized"},
+                         {"id": "crit", "label": "Edición crítica"}]}"#;
+        let config: ProjectConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.editions.len(), 2);
+        assert_eq!(config.editions[0].kind, "normalized");
+        assert_eq!(config.editions[1].id, "crit");
+        assert_eq!(config.editions[1].kind, "");
+    }
+
+    #[test]
+    fn test_citation() {
+        let mut metadata = ProjectMetadata {
+            editor: "Robert W. Daniel".to_string(),
+            institution: "Rijksmuseum Amsterdam".to_string(),
+            ..ProjectMetadata::default()
+        };
+        assert_eq!(
+            metadata.citation("PGM XIII", "https://x.org/PGM-XIII/3"),
+            "Robert W. Daniel (ed.). PGM XIII. Rijksmuseum Amsterdam. https://x.org/PGM-XIII/3"
+        );
+        metadata.doi = Some("10.5281/zenodo.123".to_string());
+        assert_eq!(
+            metadata.doi_url().as_deref(),
+            Some("https://doi.org/10.5281/zenodo.123")
+        );
+        assert!(metadata
+            .citation("PGM XIII", "https://x.org/PGM-XIII/3")
+            .ends_with("Amsterdam. https://doi.org/10.5281/zenodo.123"));
+        metadata.how_to_cite = Some("Daniel, R. W. (ed.), PGM XIII.".to_string());
+        assert_eq!(
+            metadata.citation("PGM XIII", ""),
+            "Daniel, R. W. (ed.), PGM XIII."
+        );
+    }
+
+    #[test]
+    fn test_default_view() {
+        let json = r#"{"view": "translation", "image_panel": 60, "fit": "page",
+                       "commentary": false}"#;
+        let view: ViewDefaults = serde_json::from_str(json).unwrap();
+        assert_eq!(view.view.as_deref(), Some("translation"));
+        assert_eq!(view.image_panel, Some(60.0));
+        assert_eq!(view.fit, Some(Fit::Page));
+        assert_eq!(view.zoom, None);
+        assert_eq!(view.commentary, Some(false));
+        assert!(!view.opens_commentary());
+        assert!(ViewDefaults::default().opens_commentary());
+    }
+
+    #[test]
+    fn test_sections() {
+        let mut config = ProjectConfig::new("T".to_string(), "T".to_string());
+        config.pages = (1..=5).map(PageInfo::new).collect();
+        config.sections = vec![
+            PageSection {
+                label: "Himno".to_string(),
+                pages: vec![2, 3],
+            },
+            PageSection {
+                label: "Recetas".to_string(),
+                pages: vec![5, 4],
+            },
+        ];
+        let groups: Vec<(Option<&str>, Vec<u32>)> = group_pages(&config.pages, &config.sections)
+            .into_iter()
+            .map(|(label, pages)| (label, pages.iter().map(|p| p.number).collect()))
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (None, vec![1]),
+                (Some("Himno"), vec![2, 3]),
+                (Some("Recetas"), vec![4, 5])
+            ]
+        );
+        let contents = config.contents();
+        assert_eq!(contents.len(), 2);
+        assert_eq!(
+            (contents[1].title.as_str(), contents[1].page),
+            ("Recetas", 4)
+        );
+    }
+
+    #[test]
+    fn test_branding() {
+        let json = r##"{"id": "T", "name": "T", "description": "", "pages": [],
+            "metadata": {"author": "", "editor": "", "collection": "", "institution": "",
+                         "country": "", "language": "", "date_range": ""},
+            "logo": "logo.svg", "accent_color": "#8e44ad",
+            "acknowledgements": "Con el apoyo de la Fundación X"}"##;
+        let config: ProjectConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.branding.logo.as_deref(), Some("logo.svg"));
+        assert_eq!(config.branding.accent_color.as_deref(), Some("#8e44ad"));
+        assert_eq!(config.branding.subtitle, None);
+        assert_eq!(
+            ProjectConfig::new("T".to_string(), "T".to_string()).branding,
+            Branding::default()
+        );
+    }
+
     #[test]
     fn test_page_info() {
         let page = PageInfo::new(1).with_label("First Page".to_string());
@@ -176,23 +1010,471 @@ mod tests {
         assert!(page.has_diplomatic);
         assert!(page.has_translation);
         assert!(page.has_image);
+        assert!(page.missing_content().is_empty());
+        let page = page.with_translation(false).with_image(false);
+        assert_eq!(page.missing_content(), ["sin traducción", "sin imagen"]);
+    }
+
+    #[test]
+    fn test_translations() {
+        let mut page = PageInfo::new(1);
+        assert_eq!(page.translation_editions(), ["trad"]);
+        assert_eq!(page.translation_edition(Some("en")), "trad");
+        page.translations = vec!["es".to_string(), "en".to_string()];
+        assert_eq!(page.translation_editions(), ["trad_es", "trad_en"]);
+        assert_eq!(page.translation_edition(Some("en")), "trad_en");
+        assert_eq!(page.translation_edition(Some("de")), "trad_es");
+        assert_eq!(page.translation_edition(None), "trad_es");
+    }
+
+    #[test]
+    fn test_images() {
+        let json = r#"{"number": 1, "label": "1r", "has_diplomatic": true,
+                       "has_translation": true, "has_image": true, "images": [
+                           {"label": "UV", "path": "images/p1_uv.jpg", "zones": true},
+                           {"label": "Detalle", "path": "images/p1_detail.jpg"}]}"#;
+        let page: PageInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(page.images.len(), 2);
+        assert!(page.images[0].zones);
+        assert!(!page.images[1].zones);
+        assert!(PageInfo::new(1).images.is_empty());
+    }
+
+    #[test]
+    fn test_bibl() {
+        let json = r#"{"number": 1, "label": "1r", "has_diplomatic": true,
+                       "has_translation": true, "has_image": true,
+                       "bibl": ["Betz 1986", {"text": "PGM XIII", "url": "https://x.org"}]}"#;
+        let page: PageInfo = serde_json::from_str(json).unwrap();
+        let PageBibliography::Entries(entries) = &page.bibl else {
+            panic!("expected entries");
+        };
+        assert_eq!(entries[0].url(), None);
+        assert_eq!(
+            (entries[1].text(), entries[1].url()),
+            ("PGM XIII", Some("https://x.org"))
+        );
+
+        let json = json.replace(
+            r#"["Betz 1986", {"text": "PGM XIII", "url": "https://x.org"}]"#,
+            r#""bibl/p1.xml""#,
+        );
+        let page: PageInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(page.bibl, PageBibliography::File("bibl/p1.xml".to_string()));
+        assert_eq!(PageInfo::new(1).bibl, PageBibliography::Entries(Vec::new()));
+    }
+
+    #[test]
+    fn test_image_variants() {
+        let mut page = PageInfo::new(1);
+        page.image_variants = vec![
+            ImageVariant {
+                path: "images/p1_800.jpg".to_string(),
+                width: 800,
+            },
+            ImageVariant {
+                path: "images/p1_2400.jpg".to_string(),
+                width: 2400,
+            },
+        ];
+        assert_eq!(page.image_variants.len(), 2);
+        assert_eq!(page.max_variant_width(), Some(2400));
+        assert_eq!(PageInfo::new(2).max_variant_width(), None);
+
+        // Manifests without variants keep deserializing
+        let json = r#"{"number": 1, "label": "Folio 1", "has_diplomatic": true,
+                       "has_translation": false, "has_image": true}"#;
+        let parsed: PageInfo = serde_json::from_str(json).unwrap();
+        assert!(parsed.image_variants.is_empty());
+    }
+
+    #[test]
+    fn test_audio_reading() {
+        let json = r#"{"number": 1, "label": "Folio 1", "has_diplomatic": true,
+                       "has_translation": false, "has_image": true,
+                       "audio": {"path": "audio/p1.mp3", "timings": [
+                           {"line": "341", "start": 0.0, "end": 4.5},
+                           {"line": "342", "start": 4.5, "end": 9.0}
+                       ]}}"#;
+        let parsed: PageInfo = serde_json::from_str(json).unwrap();
+        let audio = parsed.audio.unwrap();
+        assert_eq!(audio.path, "audio/p1.mp3");
+        assert_eq!(audio.line_at(0.0), Some("341"));
+        assert_eq!(audio.line_at(4.5), Some("342"));
+        assert_eq!(audio.line_at(9.0), None);
+        assert!(PageInfo::new(1).audio.is_none());
+    }
+
+    #[test]
+    fn test_parse_project_index() {
+        let urls = parse_project_index(
+            r#"["PGM-XIII", " ", "otros/PGM-IV.json", "https://example.org/manifest.json"]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            urls,
+            [
+                "/public/projects/PGM-XIII/manifest.json",
+                "/public/projects/otros/PGM-IV.json",
+                "https://example.org/manifest.json",
+            ]
+        );
+        assert!(parse_project_index(r#"{"projects": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_adjacent_page() {
+        let mut config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
+        config.pages = vec![PageInfo::new(1), PageInfo::new(2), PageInfo::new(5)];
+        assert_eq!(config.adjacent_page(2, 1), Some(5));
+        assert_eq!(config.adjacent_page(2, -1), Some(1));
+        assert_eq!(config.adjacent_page(1, -1), None);
+        assert_eq!(config.adjacent_page(5, 1), None);
+        assert_eq!(config.adjacent_page(3, 1), None);
+    }
+
+    #[test]
+    fn test_next_image_page() {
+        let mut config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
+        config.pages = vec![PageInfo::new(1), PageInfo::new(2), PageInfo::new(3)];
+        config.pages[2].dzi = Some("images/p3.dzi".to_string());
+        assert_eq!(config.next_image_page(1).map(|p| p.number), Some(2));
+        // Tiled, and past the last page
+        assert!(config.next_image_page(2).is_none());
+        assert!(config.next_image_page(3).is_none());
+        config.pages[1].has_image = false;
+        assert!(config.next_image_page(1).is_none());
+        config.pages[1].has_image = true;
+        config.iiif_service = Some("https://iiif.example.org/iiif/p{page}".to_string());
+        assert!(config.next_image_page(1).is_none());
+    }
+
+    #[test]
+    fn test_thumbnail_path() {
+        let mut page = PageInfo::new(4);
+        assert_eq!(page.thumbnail_path().as_deref(), Some("images/p4.jpg"));
+        page.iiif_service = Some("https://iiif.example.org/iiif/p4/".to_string());
+        assert_eq!(
+            page.thumbnail_path().as_deref(),
+            Some("https://iiif.example.org/iiif/p4/full/200,/0/default.jpg")
+        );
+        page.image_variants = vec![
+            ImageVariant {
+                path: "images/p4_2400.jpg".to_string(),
+                width: 2400,
+            },
+            ImageVariant {
+                path: "images/p4_800.jpg".to_string(),
+                width: 800,
+            },
+        ];
+        assert_eq!(page.thumbnail_path().as_deref(), Some("images/p4_800.jpg"));
+        page.thumbnail = Some("thumbs/p4.jpg".to_string());
+        assert_eq!(page.thumbnail_path().as_deref(), Some("thumbs/p4.jpg"));
+        let page = PageInfo::new(5).with_image(false);
+        assert_eq!(page.thumbnail_path(), None);
+    }
+
+    #[test]
+    fn test_gallery_image_path() {
+        let mut page = PageInfo::new(4);
+        assert_eq!(page.gallery_image_path().as_deref(), Some("images/p4.jpg"));
+        page.iiif_service = Some("https://iiif.example.org/iiif/p4/".to_string());
+        assert_eq!(
+            page.gallery_image_path().as_deref(),
+            Some("https://iiif.example.org/iiif/p4/full/!2000,2000/0/default.jpg")
+        );
+        page.image_variants = vec![
+            ImageVariant {
+                path: "images/p4_2400.jpg".to_string(),
+                width: 2400,
+            },
+            ImageVariant {
+                path: "images/p4_800.jpg".to_string(),
+                width: 800,
+            },
+        ];
+        assert_eq!(
+            page.gallery_image_path().as_deref(),
+            Some("images/p4_2400.jpg")
+        );
+        page.dzi = Some("images/p4.dzi".to_string());
+        assert_eq!(page.gallery_image_path(), None);
+        assert_eq!(
+            PageInfo::new(5).with_image(false).gallery_image_path(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_step_page() {
+        let mut config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
+        // Declared order, not numeric order
+        config.pages = vec![PageInfo::new(3), PageInfo::new(1), PageInfo::new(2)];
+        assert_eq!(config.step_page(3, 1, false), Some(1));
+        assert_eq!(config.step_page(2, 1, false), None);
+        assert_eq!(config.step_page(2, 1, true), Some(3));
+        assert_eq!(config.step_page(3, -1, true), Some(2));
+        assert_eq!(config.step_page(9, 1, true), None);
+        config.pages.truncate(1);
+        assert_eq!(config.step_page(3, 1, true), None);
+    }
+
+    #[test]
+    fn test_page_by_label() {
+        let mut config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
+        let mut folio = PageInfo::new(7);
+        folio.label = "Col. III".to_string();
+        config.pages = vec![PageInfo::new(1), folio];
+        assert_eq!(config.page_by_label(" col. iii").map(|p| p.number), Some(7));
+        assert!(config.page_by_label("Col. IV").is_none());
+    }
+
+    #[test]
+    fn test_file_url() {
+        let mut config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
+        assert_eq!(
+            config.file_url("images/p1_800.jpg"),
+            "/public/projects/TEST/images/p1_800.jpg"
+        );
+        assert_eq!(
+            config.file_url("https://cdn.example.org/p1.jpg"),
+            "https://cdn.example.org/p1.jpg"
+        );
+        assert_eq!(
+            config.get_facsimile_path(1, "https://example.org/p1.jpg"),
+            "https://example.org/p1.jpg"
+        );
+        assert_eq!(
+            config.get_facsimile_path(1, "public/x/p1.jpg"),
+            "/public/x/p1.jpg"
+        );
+        assert_eq!(
+            config.get_facsimile_path(2, "../scans/f2r.jpg"),
+            "/public/projects/TEST/images/f2r.jpg"
+        );
+        assert_eq!(
+            config.get_facsimile_path(3, " "),
+            "/public/projects/TEST/images/p3.jpg"
+        );
+
+        config.folder = Some("https://repo.example.org/editions/R/".to_string());
+        assert!(config.is_remote());
+        assert_eq!(
+            config.file_url("p1_dip.xml"),
+            "https://repo.example.org/editions/R/p1_dip.xml"
+        );
+        assert_eq!(
+            config.get_facsimile_path(2, ""),
+            "https://repo.example.org/editions/R/images/p2.jpg"
+        );
+        config.files.insert(
+            "p3_dip.xml".to_string(),
+            "https://ocr.example.org/f3.xml".to_string(),
+        );
+        assert!(config.has_file("p3_dip.xml"));
+        assert_eq!(
+            config.file_url("p3_dip.xml"),
+            "https://ocr.example.org/f3.xml"
+        );
+
+        let mut config = ProjectConfig::new("PATTERNS".to_string(), "Test".to_string());
+        config.file_patterns = FilePatterns {
+            translation_pattern: Some("trad/{page}.xml".to_string()),
+            image_pattern: Some("https://img.example.org/P/{page:04}.jpg".to_string()),
+            ..FilePatterns::default()
+        };
+        assert_eq!(
+            config.file_url("p2_trad.xml"),
+            "/public/projects/PATTERNS/trad/2.xml"
+        );
+        assert_eq!(
+            config.get_facsimile_path(2, ""),
+            "https://img.example.org/P/0002.jpg"
+        );
+        assert_eq!(
+            config.file_url("p2_dip.xml"),
+            "/public/projects/PATTERNS/p2_dip.xml"
+        );
     }
 
     #[test]
-    fn test_project_registry() {
-        let projects = ProjectRegistry::get_all_projects();
-        assert!(!projects.is_empty());
+    fn test_versioned_file_url() {
+        let mut config = ProjectConfig::new("VERSIONED".to_string(), "Test".to_string());
+        assert_eq!(
+            config.versioned_file_url("p1_dip.xml"),
+            "/public/projects/VERSIONED/p1_dip.xml"
+        );
+        config.content_version = Some("1.4.0".to_string());
+        config.file_hashes = HashMap::from([("p2_dip.xml".to_string(), "9f3c".to_string())]);
+        assert_eq!(
+            config.versioned_file_url("p1_dip.xml"),
+            "/public/projects/VERSIONED/p1_dip.xml?v=1.4.0"
+        );
+        assert_eq!(
+            config.versioned_file_url("p2_dip.xml"),
+            "/public/projects/VERSIONED/p2_dip.xml?v=9f3c"
+        );
+    }
 
-        let pgm = ProjectRegistry::get_project("PGM-XIII");
-        assert!(pgm.is_some());
-        assert_eq!(pgm.unwrap().name, "Papyri Graecae Magicae XIII");
+    #[test]
+    fn test_sources_for() {
+        let mut config = ProjectConfig::new("MIRRORED".to_string(), "Test".to_string());
+        let url = config.get_diplomatic_path(1);
+        assert_eq!(config.sources_for(&url), Sources::from(&url));
+        config.mirrors = vec![
+            "https://cdn.example.org/P".to_string(),
+            " ".to_string(),
+            "https://raw.example.org/P/".to_string(),
+        ];
+        config.content_version = Some("3".to_string());
+        let url = config.get_diplomatic_path(1);
+        assert_eq!(
+            config.sources_for(&url).candidates().collect::<Vec<_>>(),
+            vec![
+                "https://cdn.example.org/P/p1_dip.xml?v=3",
+                "https://raw.example.org/P/p1_dip.xml?v=3",
+                "/public/projects/MIRRORED/p1_dip.xml?v=3",
+            ]
+        );
+        // Files outside the folder have no copies
+        let iiif = "https://iiif.example.org/x/info.json";
+        assert!(config.sources_for(iiif).mirrors.is_empty());
     }
 
     #[test]
     fn test_paths() {
-        let config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
-        assert_eq!(config.get_diplomatic_path(1), "projects/TEST/p1_dip.xml");
-        assert_eq!(config.get_translation_path(1), "projects/TEST/p1_trad.xml");
-        assert_eq!(config.get_image_path(1), "projects/TEST/images/p1.jpg");
+        let mut config = ProjectConfig::new("PATHS".to_string(), "Test".to_string());
+        let mut page = PageInfo::new(2);
+        page.translations = vec!["es".to_string(), "en".to_string()];
+        page.commentary = Some("notes/p2.md".to_string());
+        config.pages = vec![PageInfo::new(1), page];
+        config.content_version = Some("3".to_string());
+        assert_eq!(
+            config.get_diplomatic_path(1),
+            "/public/projects/PATHS/p1_dip.xml?v=3"
+        );
+        assert_eq!(
+            config.get_translation_path(1, Some("en")),
+            "/public/projects/PATHS/p1_trad.xml?v=3"
+        );
+        assert_eq!(
+            config.get_translation_path(2, Some("en")),
+            "/public/projects/PATHS/p2_trad_en.xml?v=3"
+        );
+        assert_eq!(
+            config.get_commentary_path(1),
+            "/public/projects/PATHS/commentary.html?v=3"
+        );
+        assert_eq!(
+            config.get_commentary_path(2),
+            "/public/projects/PATHS/notes/p2.md?v=3"
+        );
+        assert_eq!(
+            config.get_image_path(1),
+            "/public/projects/PATHS/images/p1.jpg"
+        );
+        assert_eq!(
+            config.get_facsimile_path(1, "scan.tif"),
+            "/public/projects/PATHS/images/scan.tif"
+        );
+    }
+
+    #[test]
+    fn test_image_attribution() {
+        let json = r#"{"id": "T", "name": "T", "description": "", "pages": [
+                {"number": 1, "label": "1", "has_diplomatic": true, "has_translation": false,
+                 "has_image": true},
+                {"number": 2, "label": "2", "has_diplomatic": true, "has_translation": false,
+                 "has_image": true, "image_credit": "Foto: B. Smith", "rights_holder": " "}],
+            "metadata": {"author": "", "editor": "", "collection": "", "institution": "",
+                         "country": "", "language": "", "date_range": ""},
+            "image_credit": "Foto: Rijksmuseum", "rights_holder": "Rijksmuseum Amsterdam"}"#;
+        let mut config: ProjectConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.image_attribution(1).as_deref(),
+            Some("Foto: Rijksmuseum · © Rijksmuseum Amsterdam")
+        );
+        // A blank field of the page leaves its image without that part
+        assert_eq!(
+            config.image_attribution(2).as_deref(),
+            Some("Foto: B. Smith")
+        );
+        config.image_rights.image_credit = None;
+        assert_eq!(
+            config.image_attribution(1).as_deref(),
+            Some("© Rijksmuseum Amsterdam")
+        );
+        config.image_rights = ImageRights::default();
+        assert_eq!(config.image_attribution(1), None);
+    }
+
+    #[test]
+    fn test_iiif_service() {
+        let mut config = ProjectConfig::new("TEST".to_string(), "Test".to_string());
+        config.pages = vec![PageInfo::new(1), PageInfo::new(2)];
+        config.pages[1].iiif_service = Some("https://other.org/iiif/b".to_string());
+        assert_eq!(config.iiif_service(1), None);
+
+        config.iiif_service = Some("https://iiif.org/iiif/3/pgm-{page:03}/".to_string());
+        assert_eq!(
+            config.get_image_path(1),
+            "https://iiif.org/iiif/3/pgm-001/full/max/0/default.jpg"
+        );
+        assert_eq!(
+            config.iiif_service(2).as_deref(),
+            Some("https://other.org/iiif/b")
+        );
+        config.resolve_iiif_services();
+        assert_eq!(
+            config.pages[0].iiif_service.as_deref(),
+            Some("https://iiif.org/iiif/3/pgm-001/")
+        );
+        assert_eq!(
+            config.pages[1].iiif_service.as_deref(),
+            Some("https://other.org/iiif/b")
+        );
+    }
+
+    #[test]
+    fn test_file_patterns() {
+        let json = r#"{"id": "T", "name": "T", "description": "", "pages": [],
+            "metadata": {"author": "", "editor": "", "collection": "", "institution": "",
+                         "country": "", "language": "", "date_range": ""},
+            "diplomatic_pattern": "folio-{page:03}-dipl.xml",
+            "image_pattern": "scans/{page}.tif"}"#;
+        let config: ProjectConfig = serde_json::from_str(json).unwrap();
+        assert!(config.editions.is_empty());
+        let patterns = &config.file_patterns;
+        assert!(!patterns.is_default());
+        assert_eq!(
+            patterns.resolve("p7_dip.xml").as_deref(),
+            Some("folio-007-dipl.xml")
+        );
+        assert_eq!(
+            patterns.resolve("images/p12.jpg").as_deref(),
+            Some("scans/12.tif")
+        );
+        // No pattern for translations, and other files keep their names
+        assert_eq!(patterns.resolve("p7_trad.xml"), None);
+        assert_eq!(patterns.resolve("images/p1_800.jpg"), None);
+        assert_eq!(patterns.resolve("commentary.html"), None);
+        assert_eq!(
+            config.get_diplomatic_path(1),
+            "/public/projects/T/folio-001-dipl.xml"
+        );
+        assert_eq!(
+            config.get_translation_path(1, None),
+            "/public/projects/T/p1_trad.xml"
+        );
+        assert_eq!(expand("{page}{page:02}x{page", 5), "505x{page");
+        let patterns = FilePatterns {
+            translation_pattern: Some("{lang}/{page}.xml".to_string()),
+            ..FilePatterns::default()
+        };
+        assert_eq!(
+            patterns.resolve("p4_trad_en.xml").as_deref(),
+            Some("en/4.xml")
+        );
     }
 }
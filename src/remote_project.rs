@@ -0,0 +1,333 @@
+// src/remote_project.rs
+// A project served from another host, named in the link that opens the
+// viewer: `?project_url=https://…/manifest.json`. Its pages, images and
+// commentary are read from the folder of that manifest, which has to allow
+// cross-origin requests (CORS). A project in a GitHub repository can be named
+// by branch instead, `?gh=owner/repo@branch/path`, to preview a pull
+// request's edition before it is merged. A IIIF Presentation manifest is
+// read as a project too (see iiif.rs), and `?dts=` names the entry point of
+// a DTS repository whose texts are browsed and read (see dts.rs). The
+// commentary is HTML from a host this site does not control, so it is
+// sanitized before it is shown.
+use crate::dts;
+use crate::iiif;
+use crate::manifest;
+use crate::net::{self, FetchError};
+use crate::project_config::ProjectConfig;
+use crate::url_state;
+use crate::utils::window;
+use wasm_bindgen::JsCast;
+
+/// Query parameter naming the manifest
+pub const PARAM: &str = "project_url";
+/// Query parameter naming a GitHub repository, branch and folder
+pub const GITHUB_PARAM: &str = "gh";
+/// Query parameters naming a DTS entry point and one of its resources
+pub const DTS_PARAM: &str = "dts";
+pub const DTS_RESOURCE_PARAM: &str = "dts_resource";
+
+/// Files of a GitHub repository, by branch, tag or commit
+const GITHUB_RAW: &str = "https://raw.githubusercontent.com";
+
+/// A remote project as named in the address: the query, kept there as
+/// pages turn, and where the project comes from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Source {
+    pub query: Vec<(&'static str, String)>,
+    pub origin: Origin,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    /// URL of a project manifest or a IIIF manifest
+    Manifest(String),
+    /// DTS entry point, and the resource to read once one is chosen
+    Dts {
+        endpoint: String,
+        resource: Option<String>,
+    },
+}
+
+impl Source {
+    /// A resource of a DTS repository, or the repository to browse.
+    pub fn dts(endpoint: &str, resource: Option<&str>) -> Self {
+        let mut query = vec![(DTS_PARAM, endpoint.to_string())];
+        query.extend(resource.map(|r| (DTS_RESOURCE_PARAM, r.to_string())));
+        Self {
+            query,
+            origin: Origin::Dts {
+                endpoint: endpoint.to_string(),
+                resource: resource.map(str::to_string),
+            },
+        }
+    }
+
+    /// How the reader named the project, for messages.
+    pub fn name(&self) -> String {
+        self.query
+            .iter()
+            .map(|(_, value)| value.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Remote project named in the address of the page, if any.
+pub fn requested() -> Option<Source> {
+    let search = window()?.location().search().ok()?;
+    source(&search)
+}
+
+/// The remote project of a query string: a `project_url` that is an
+/// http(s) URL, a `gh` repository or a `dts` entry point.
+pub fn source(search: &str) -> Option<Source> {
+    let value = |param: &str| url_state::query_param(search, param);
+    let is_http = |url: &String| url.starts_with("https://") || url.starts_with("http://");
+    if let Some(url) = value(PARAM).filter(is_http) {
+        return Some(Source {
+            origin: Origin::Manifest(url.clone()),
+            query: vec![(PARAM, url)],
+        });
+    }
+    if let Some(endpoint) = value(DTS_PARAM).filter(is_http) {
+        return Some(Source::dts(&endpoint, value(DTS_RESOURCE_PARAM).as_deref()));
+    }
+    let spec = value(GITHUB_PARAM)?;
+    Some(Source {
+        origin: Origin::Manifest(github_manifest(&spec)?),
+        query: vec![(GITHUB_PARAM, spec)],
+    })
+}
+
+/// Manifest of `owner/repo@branch/path` on raw.githubusercontent.com. The
+/// branch may be a tag or a commit; without one the default branch is read,
+/// and without a path the root of the repository. A path ending in `.json`
+/// names the manifest itself.
+pub fn github_manifest(spec: &str) -> Option<String> {
+    let (repository, rest) = spec.split_once('@').unwrap_or((spec, ""));
+    let (owner, repo) = repository.split_once('/')?;
+    let valid = |name: &str| {
+        !name.is_empty()
+            && name != "."
+            && name != ".."
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    if !valid(owner) || !valid(repo) || rest.split('/').any(|part| part == "..") {
+        return None;
+    }
+    // A branch containing '/' reads the same: GitHub resolves the longest
+    // branch name that matches
+    let rest = rest.trim_matches('/');
+    let rest = if rest.is_empty() { "HEAD" } else { rest };
+    let manifest = if rest.ends_with(".json") {
+        String::new()
+    } else {
+        "/manifest.json".to_string()
+    };
+    Some(format!(
+        "{}/{}/{}/{}{}",
+        GITHUB_RAW, owner, repo, rest, manifest
+    ))
+}
+
+/// Folder holding a manifest: its URL up to the last '/'.
+pub fn folder_of(manifest_url: &str) -> String {
+    let url = manifest_url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(manifest_url);
+    match url.rfind('/') {
+        Some(slash) if slash > url.find("://").map_or(0, |s| s + 2) => url[..=slash].to_string(),
+        _ => format!("{}/", url),
+    }
+}
+
+/// The project of a source; a DTS source must name its resource.
+pub async fn load(origin: Origin) -> Result<ProjectConfig, String> {
+    match origin {
+        Origin::Manifest(url) => load_manifest(&url).await.map_err(|e| e.to_string()),
+        Origin::Dts {
+            endpoint,
+            resource: Some(resource),
+        } => dts::load(&endpoint, &resource)
+            .await
+            .map_err(|e| e.to_string()),
+        Origin::Dts { resource: None, .. } => Err("no DTS resource chosen".to_string()),
+    }
+}
+
+/// Fetch the manifest and serve its project from the manifest's folder. A
+/// IIIF manifest is imported, its pages served from the resources it links.
+async fn load_manifest(manifest_url: &str) -> Result<ProjectConfig, FetchError> {
+    let json: serde_json::Value = net::get_json(manifest_url).await?;
+    let imported = if iiif::is_manifest(&json) {
+        iiif::import(manifest_url, &json)
+    } else {
+        manifest::from_value(json, manifest_url).map_err(|e| e.to_string())
+    };
+    let mut project = imported.map_err(|reason| FetchError::Decode {
+        url: manifest_url.to_string(),
+        reason,
+    })?;
+    project.folder = Some(folder_of(manifest_url));
+    log::info!("Loaded remote project {} from {}", project.id, manifest_url);
+    Ok(project)
+}
+
+/// Elements dropped from remote HTML along with their content
+const UNSAFE_ELEMENTS: &str = "script, style, iframe, frame, object, embed, link, meta, base, form";
+
+/// Remote HTML without scripts: unsafe elements, event handler attributes
+/// and `javascript:` URLs are removed. The markup is parsed by the browser
+/// into an inert document, so nothing runs while it is cleaned.
+pub fn sanitize(html: &str) -> String {
+    let Ok(parser) = web_sys::DomParser::new() else {
+        return String::new();
+    };
+    let Ok(document) = parser.parse_from_string(html, web_sys::SupportedType::TextHtml) else {
+        return String::new();
+    };
+    if let Ok(unsafe_elements) = document.query_selector_all(UNSAFE_ELEMENTS) {
+        for i in 0..unsafe_elements.length() {
+            if let Some(node) = unsafe_elements.get(i) {
+                if let Some(parent) = node.parent_node() {
+                    let _ = parent.remove_child(&node);
+                }
+            }
+        }
+    }
+    if let Ok(elements) = document.query_selector_all("*") {
+        for i in 0..elements.length() {
+            let Some(element) = elements
+                .get(i)
+                .and_then(|n| n.dyn_into::<web_sys::Element>().ok())
+            else {
+                continue;
+            };
+            for name in element.get_attribute_names().iter() {
+                let Some(name) = name.as_string() else {
+                    continue;
+                };
+                let value = element.get_attribute(&name).unwrap_or_default();
+                if unsafe_attribute(&name, &value) {
+                    let _ = element.remove_attribute(&name);
+                }
+            }
+        }
+    }
+    document
+        .body()
+        .map(|body| body.inner_html())
+        .unwrap_or_default()
+}
+
+/// Event handlers, and URLs that would run script when followed.
+fn unsafe_attribute(name: &str, value: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    let value: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    name.starts_with("on")
+        || name == "srcdoc"
+        || value.starts_with("javascript:")
+        || value.starts_with("vbscript:")
+        || (value.starts_with("data:") && !value.starts_with("data:image/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source() {
+        let manifest = |search: &str| match source(search).map(|s| s.origin) {
+            Some(Origin::Manifest(url)) => Some(url),
+            _ => None,
+        };
+        assert_eq!(
+            manifest("?project_url=https%3A%2F%2Frepo.example.org%2FP%2Fmanifest.json"),
+            Some("https://repo.example.org/P/manifest.json".to_string())
+        );
+        assert_eq!(
+            manifest("?lang=en&project_url=https://s3.example.com/b/P/manifest.json"),
+            Some("https://s3.example.com/b/P/manifest.json".to_string())
+        );
+        assert_eq!(manifest("?project_url=javascript:alert(1)"), None);
+        assert_eq!(manifest(""), None);
+
+        assert_eq!(
+            source("?gh=ed%2Fpgm%40fix-13%2Fprojects%2FP"),
+            Some(Source {
+                query: vec![(GITHUB_PARAM, "ed/pgm@fix-13/projects/P".to_string())],
+                origin: Origin::Manifest(
+                    "https://raw.githubusercontent.com/ed/pgm/fix-13/projects/P/manifest.json"
+                        .to_string()
+                ),
+            })
+        );
+
+        let dts = source("?dts=https%3A%2F%2Ftexts.example.org%2Fapi%2Fdts&dts_resource=urn%3Ax")
+            .unwrap();
+        assert_eq!(
+            dts.origin,
+            Origin::Dts {
+                endpoint: "https://texts.example.org/api/dts".to_string(),
+                resource: Some("urn:x".to_string()),
+            }
+        );
+        assert_eq!(dts.name(), "https://texts.example.org/api/dts urn:x");
+        assert_eq!(
+            source("?dts=https://texts.example.org/api/dts").map(|s| s.origin),
+            Some(Origin::Dts {
+                endpoint: "https://texts.example.org/api/dts".to_string(),
+                resource: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_github_manifest() {
+        assert_eq!(
+            github_manifest("ed/pgm@main/public/projects/P/").as_deref(),
+            Some("https://raw.githubusercontent.com/ed/pgm/main/public/projects/P/manifest.json")
+        );
+        assert_eq!(
+            github_manifest("ed/pgm@v1.2/P/edition.json").as_deref(),
+            Some("https://raw.githubusercontent.com/ed/pgm/v1.2/P/edition.json")
+        );
+        assert_eq!(
+            github_manifest("ed/pgm").as_deref(),
+            Some("https://raw.githubusercontent.com/ed/pgm/HEAD/manifest.json")
+        );
+        assert_eq!(github_manifest("pgm@main"), None);
+        assert_eq!(github_manifest("ed/../x@main"), None);
+        assert_eq!(github_manifest("ed/pgm@main/../../other"), None);
+    }
+
+    #[test]
+    fn test_folder_of() {
+        assert_eq!(
+            folder_of("https://repo.example.org/editions/P/manifest.json?v=2"),
+            "https://repo.example.org/editions/P/"
+        );
+        assert_eq!(
+            folder_of("https://repo.example.org"),
+            "https://repo.example.org/"
+        );
+    }
+
+    #[test]
+    fn test_unsafe_attribute() {
+        assert!(unsafe_attribute("onerror", "alert(1)"));
+        assert!(unsafe_attribute("href", " JavaScript:alert(1)"));
+        assert!(unsafe_attribute("href", "java\tscript:alert(1)"));
+        assert!(unsafe_attribute("src", "data:text/html,<script>"));
+        assert!(!unsafe_attribute("src", "data:image/png;base64,AAAA"));
+        assert!(!unsafe_attribute("href", "https://example.org/"));
+        assert!(!unsafe_attribute("class", "note"));
+    }
+}
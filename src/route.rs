@@ -0,0 +1,98 @@
+// src/route.rs
+// Routes of the app: the project and page on screen live in the URL path
+// (e.g. `/PGM-XIII/3`), so the back button, reloads and shared links all
+// reopen them. The rest of the view stays in the hash (see `url_state`).
+use yew_router::prelude::*;
+
+#[derive(Routable, Debug, Clone, PartialEq)]
+pub enum Route {
+    /// First page of the first project
+    #[at("/")]
+    Home,
+    /// First page of a project
+    #[at("/:project")]
+    Project { project: String },
+    #[at("/:project/:page")]
+    Page { project: String, page: u32 },
+    /// Photographs of a project, without their texts, from a page on
+    #[at("/:project/gallery/:page")]
+    Gallery { project: String, page: u32 },
+    #[not_found]
+    #[at("/404")]
+    NotFound,
+}
+
+impl Route {
+    /// Route of a page, read or in the gallery
+    pub fn of_page(project: &str, page: u32, gallery: bool) -> Self {
+        let project = project.to_string();
+        if gallery {
+            Route::Gallery { project, page }
+        } else {
+            Route::Page { project, page }
+        }
+    }
+
+    pub fn project(&self) -> Option<&str> {
+        match self {
+            Route::Project { project }
+            | Route::Page { project, .. }
+            | Route::Gallery { project, .. } => Some(project),
+            Route::Home | Route::NotFound => None,
+        }
+    }
+
+    pub fn page(&self) -> Option<u32> {
+        match self {
+            Route::Page { page, .. } | Route::Gallery { page, .. } => Some(*page),
+            _ => None,
+        }
+    }
+
+    pub fn is_gallery(&self) -> bool {
+        matches!(self, Route::Gallery { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognize() {
+        assert_eq!(Route::recognize("/"), Some(Route::Home));
+        assert_eq!(
+            Route::recognize("/PGM-XIII/3"),
+            Some(Route::Page {
+                project: "PGM-XIII".to_string(),
+                page: 3
+            })
+        );
+        assert_eq!(
+            Route::recognize("/PGM-XIII").and_then(|r| r.project().map(str::to_string)),
+            Some("PGM-XIII".to_string())
+        );
+        assert_eq!(
+            Route::recognize("/PGM-XIII/gallery/3"),
+            Some(Route::of_page("PGM-XIII", 3, true))
+        );
+        assert_eq!(
+            Route::recognize("/PGM-XIII/gallery/3").and_then(|r| r.page()),
+            Some(3)
+        );
+        assert_eq!(
+            Route::of_page("PGM-XIII", 3, true).to_path(),
+            "/PGM-XIII/gallery/3"
+        );
+        // Page numbers must be numbers
+        assert_eq!(Route::recognize("/PGM-XIII/iii"), Some(Route::NotFound));
+        assert_eq!(
+            Route::Page {
+                project: "PGM-XIII".to_string(),
+                page: 3
+            }
+            .to_path(),
+            "/PGM-XIII/3"
+        );
+    }
+}
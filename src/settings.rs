@@ -0,0 +1,200 @@
+// src/settings.rs
+// Display options gathered in the settings dialog. Font, text size and
+// overlay colour are applied to the document like the theme; the layout and
+// the readings shown are passed down to the viewer.
+use crate::persistence;
+use crate::utils::window;
+use serde::{Deserialize, Serialize};
+
+pub const MIN_TEXT_SCALE: u32 = 80;
+pub const MAX_TEXT_SCALE: u32 = 160;
+pub const TEXT_SCALE_STEP: u32 = 10;
+
+/// Typeface of the transcription and translation
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Font {
+    /// Self-hosted Iosevka with the Unicode fallbacks
+    #[default]
+    Iosevka,
+    Gentium,
+    Sans,
+}
+
+impl Font {
+    pub const ALL: [Font; 3] = [Font::Iosevka, Font::Gentium, Font::Sans];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Font::Iosevka => "iosevka",
+            Font::Gentium => "gentium",
+            Font::Sans => "sans",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Font::Iosevka => "Iosevka",
+            Font::Gentium => "Gentium Plus",
+            Font::Sans => "Noto Sans",
+        }
+    }
+}
+
+/// Colour of the zone highlighted on the facsimile
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverlayColor {
+    /// The colour of the theme
+    #[default]
+    Theme,
+    Yellow,
+    Cyan,
+    Magenta,
+    Green,
+}
+
+impl OverlayColor {
+    pub const ALL: [OverlayColor; 5] = [
+        OverlayColor::Theme,
+        OverlayColor::Yellow,
+        OverlayColor::Cyan,
+        OverlayColor::Magenta,
+        OverlayColor::Green,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OverlayColor::Theme => "theme",
+            OverlayColor::Yellow => "yellow",
+            OverlayColor::Cyan => "cyan",
+            OverlayColor::Magenta => "magenta",
+            OverlayColor::Green => "green",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            OverlayColor::Theme => "Del tema",
+            OverlayColor::Yellow => "Amarillo",
+            OverlayColor::Cyan => "Cian",
+            OverlayColor::Magenta => "Magenta",
+            OverlayColor::Green => "Verde",
+        }
+    }
+}
+
+/// Arrangement of the image and text panels
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Layout {
+    /// Image left, text right, vertical splitter
+    #[default]
+    SideBySide,
+    /// Image above, text below, horizontal splitter
+    Stacked,
+}
+
+/// Which reading of an editorial `<choice>` the transcription shows; the
+/// other one goes in the tooltip.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Reading {
+    /// `<expan>` in place of `<abbr>`
+    pub expansions: bool,
+    /// `<corr>` in place of `<sic>`
+    pub corrections: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub font: Font,
+    /// Size of the text panels, in percent
+    pub text_scale: u32,
+    pub overlay: OverlayColor,
+    pub expansions: bool,
+    pub corrections: bool,
+    pub layout: Layout,
+    /// Edition-style gutter: only every 5th line number is visible
+    pub sparse_line_numbers: bool,
+    /// Fetch the next page's facsimile while the current one is read
+    pub prefetch_next: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            font: Font::default(),
+            text_scale: 100,
+            overlay: OverlayColor::default(),
+            expansions: false,
+            corrections: false,
+            layout: Layout::default(),
+            sparse_line_numbers: false,
+            prefetch_next: true,
+        }
+    }
+}
+
+impl Settings {
+    /// Options saved in a previous session, or the defaults
+    pub fn stored() -> Self {
+        let mut settings: Self = persistence::load(persistence::SETTINGS).unwrap_or_default();
+        settings.text_scale = settings.text_scale.clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+        settings
+    }
+
+    pub fn reading(&self) -> Reading {
+        Reading {
+            expansions: self.expansions,
+            corrections: self.corrections,
+        }
+    }
+
+    /// Apply font, text size and overlay colour to the document: data
+    /// attributes on `<html>` and a custom property on `<body>`.
+    pub fn apply(&self) {
+        let Some(document) = window().and_then(|w| w.document()) else {
+            return;
+        };
+        if let Some(root) = document.document_element() {
+            let _ = root.set_attribute("data-font", self.font.as_str());
+            let _ = root.set_attribute("data-overlay", self.overlay.as_str());
+        }
+        if let Some(body) = document.body() {
+            let _ = body.style().set_property(
+                "--text-scale",
+                &format!("{}", f64::from(self.text_scale) / 100.0),
+            );
+        }
+    }
+
+    /// Apply the options and remember them across sessions.
+    pub fn select(&self) {
+        self.apply();
+        persistence::save(persistence::SETTINGS, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_record() {
+        // Records saved before an option existed keep its default
+        let settings: Settings =
+            serde_json::from_str(r#"{"font":"gentium","layout":"stacked"}"#).unwrap();
+        assert_eq!(settings.font, Font::Gentium);
+        assert_eq!(settings.layout, Layout::Stacked);
+        assert_eq!(settings.text_scale, 100);
+        assert_eq!(settings.overlay, OverlayColor::Theme);
+
+        let json = serde_json::to_string(&Settings::default()).unwrap();
+        assert!(json.contains(r#""layout":"side-by-side""#));
+        assert_eq!(
+            serde_json::from_str::<Settings>(&json).unwrap(),
+            Settings::default()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 // src/tei_data.rs
+use crate::glossary::GlossEntry;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -8,9 +9,42 @@ pub struct TeiDocument {
     pub facsimile: Facsimile,
     pub lines: Vec<Line>,
     pub footnotes: Vec<Footnote>,
+    /// Entries of any `<list type="gloss">` in the document
+    #[serde(default)]
+    pub glossary: Vec<GlossEntry>,
+    /// `<div>`s of the transcription starting on this page
+    #[serde(default)]
+    pub sections: Vec<Section>,
+    /// Dated events of the object's history from the msDesc: `<origDate>`,
+    /// `<provenance>` and `<acquisition>`
+    #[serde(default)]
+    pub history: Vec<HistoryEvent>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    /// Element the event comes from: "origDate", "provenance" or
+    /// "acquisition"
+    pub kind: String,
+    pub text: String,
+    /// `@when`, `@notBefore` or `@from` of the element or its `<date>`
+    pub from: Option<String>,
+    /// `@when`, `@notAfter` or `@to`
+    pub to: Option<String>,
+}
+
+/// A structural division of the transcription (a section, a recipe, ...).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    /// `<head>` text, else `@type` and `@n` (e.g. "recipe 3")
+    pub title: String,
+    /// Nesting level, 0 for top-level divisions
+    pub depth: usize,
+    /// Index of the first line of the section
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Metadata {
     pub title: String,
     pub author: String,
@@ -22,9 +56,12 @@ pub struct Metadata {
     pub institution: Option<String>,
     pub collection: Option<String>,
     pub siglum: Option<String>,
+    /// Physical size of the object from msDesc `<dimensions>`, in cm
+    pub width_cm: Option<f64>,
+    pub height_cm: Option<f64>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
 pub struct Facsimile {
     pub surface_id: String,
     pub image_url: String,
@@ -43,6 +80,9 @@ pub struct Zone {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Line {
     pub facs: String, // Reference to zone id
+    /// Source line number from `<lb n="...">`, when encoded
+    #[serde(default)]
+    pub n: Option<String>,
     pub content: Vec<TextNode>,
 }
 
@@ -89,7 +129,10 @@ pub enum TextNode {
     },
     PlaceName {
         name: String,
+        // Text of child elements (`<country>`, `<geo>`, ...) by element name
         attrs: HashMap<String, String>,
+        // @ref: gazetteer URI (e.g. Pleiades)
+        ref_uri: Option<String>,
     },
     Ref {
         ref_type: String,
@@ -104,6 +147,19 @@ pub enum TextNode {
         rs_type: String,
         content: String,
     },
+    /// `<date>`; `from`/`to` are its `@when`, `@notBefore`/`@notAfter` or
+    /// `@from`/`@to` (ISO 8601, e.g. "-0150" or "0312-05")
+    Date {
+        from: Option<String>,
+        to: Option<String>,
+        content: String,
+    },
+    /// `<term>`; `key` is its `@ref` (without `#`) or `@key`, naming a
+    /// glossary entry
+    Term {
+        key: Option<String>,
+        content: String,
+    },
     NoteRef {
         note_id: String,
         n: String, // The displayed number/marker
@@ -125,39 +181,116 @@ impl TeiDocument {
             facsimile: Facsimile::default(),
             lines: Vec::new(),
             footnotes: Vec::new(),
+            glossary: Vec::new(),
+            sections: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+    /// Resolve a line reference as typed by a reader: the source `@n` label
+    /// of a line, or else its 1-based position. Returns the line index.
+    pub fn find_line(&self, reference: &str) -> Option<usize> {
+        let reference = reference.trim();
+        if reference.is_empty() {
+            return None;
+        }
+        if let Some(index) = self
+            .lines
+            .iter()
+            .position(|l| l.n.as_deref().map(str::trim) == Some(reference))
+        {
+            return Some(index);
         }
+        match reference.parse::<usize>() {
+            Ok(n) if n >= 1 && n <= self.lines.len() => Some(n - 1),
+            _ => None,
+        }
+    }
+
+    /// How a reader refers to a line: its source `@n` label, or else its
+    /// 1-based position. `find_line` resolves it back to the line.
+    pub fn line_label(&self, index: usize) -> Option<String> {
+        let line = self.lines.get(index)?;
+        Some(match &line.n {
+            Some(n) => n.trim().to_string(),
+            None => (index + 1).to_string(),
+        })
     }
 }
 
-impl Default for Metadata {
-    fn default() -> Self {
-        Self {
-            title: String::new(),
-            author: String::new(),
-            editor: String::new(),
-            edition_type: String::new(),
-            language: String::new(),
-            country: None,
-            settlement: None,
-            institution: None,
-            collection: None,
-            siglum: None,
+/// Which side of `<choice>`-style pairs to read: the text as written on
+/// the papyrus (abbr/sic/orig) or the editor's normalization
+/// (expan/corr/reg).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reading {
+    Diplomatic,
+    Normalized,
+}
+
+impl Line {
+    /// The line's text in the given reading, without note markers.
+    pub fn text(&self, reading: Reading) -> String {
+        let mut out = String::new();
+        for node in &self.content {
+            node.push_text(reading, &mut out);
         }
+        out
+    }
+
+    /// Number of editorial interventions on the line: unclear readings,
+    /// corrections and notes. Drives the markup density heatmap.
+    pub fn markup_count(&self) -> usize {
+        self.content.iter().map(TextNode::markup_count).sum()
     }
 }
 
-impl Default for Facsimile {
-    fn default() -> Self {
-        Self {
-            surface_id: String::new(),
-            image_url: String::new(),
-            width: 0,
-            height: 0,
-            zones: HashMap::new(),
+impl TextNode {
+    /// The node's text in the given reading, e.g. a name inside `<persName>`.
+    pub fn text(&self, reading: Reading) -> String {
+        let mut out = String::new();
+        self.push_text(reading, &mut out);
+        out
+    }
+
+    fn markup_count(&self) -> usize {
+        match self {
+            TextNode::Unclear { .. }
+            | TextNode::Choice { .. }
+            | TextNode::NoteRef { .. }
+            | TextNode::InlineNote { .. } => 1,
+            TextNode::PersName { content, .. } | TextNode::Hi { content, .. } => {
+                content.iter().map(TextNode::markup_count).sum()
+            }
+            _ => 0,
+        }
+    }
+
+    fn push_text(&self, reading: Reading, out: &mut String) {
+        let normalized = reading == Reading::Normalized;
+        match self {
+            TextNode::Text { content }
+            | TextNode::Ref { content, .. }
+            | TextNode::Unclear { content, .. }
+            | TextNode::RsType { content, .. }
+            | TextNode::Date { content, .. }
+            | TextNode::Term { content, .. } => out.push_str(content),
+            TextNode::Abbr { abbr, expan } => out.push_str(if normalized { expan } else { abbr }),
+            TextNode::Choice { sic, corr } => out.push_str(if normalized { corr } else { sic }),
+            TextNode::Regularised { orig, reg } => {
+                out.push_str(if normalized { reg } else { orig })
+            }
+            TextNode::Num { text, .. } => out.push_str(text),
+            TextNode::PlaceName { name, .. } => out.push_str(name),
+            TextNode::PersName { content, .. } | TextNode::Hi { content, .. } => {
+                for node in content {
+                    node.push_text(reading, out);
+                }
+            }
+            TextNode::NoteRef { .. } | TextNode::InlineNote { .. } => {}
         }
     }
 }
 
+#[allow(dead_code)]
 impl Zone {
     pub fn parse_points(points_str: &str) -> Vec<(u32, u32)> {
         points_str
@@ -194,3 +327,62 @@ impl Zone {
         (min_x, min_y, max_x, max_y)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(n: Option<&str>) -> Line {
+        Line {
+            facs: String::new(),
+            n: n.map(str::to_string),
+            content: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_line() {
+        let mut doc = TeiDocument::new();
+        doc.lines = vec![line(Some("341")), line(Some("342")), line(None)];
+        // Source numbering takes precedence
+        assert_eq!(doc.find_line("342"), Some(1));
+        // Otherwise sequential, 1-based
+        assert_eq!(doc.find_line("3"), Some(2));
+        assert_eq!(doc.find_line(" 341 "), Some(0));
+        assert_eq!(doc.find_line("4"), None);
+        assert_eq!(doc.find_line("0"), None);
+        assert_eq!(doc.find_line(""), None);
+
+        assert_eq!(doc.line_label(1).as_deref(), Some("342"));
+        assert_eq!(doc.line_label(2).as_deref(), Some("3"));
+        assert_eq!(doc.line_label(3), None);
+    }
+
+    #[test]
+    fn test_markup_count() {
+        let text = |s: &str| TextNode::Text {
+            content: s.to_string(),
+        };
+        let mut l = line(None);
+        l.content = vec![
+            text("καὶ "),
+            TextNode::Unclear {
+                reason: "damage".to_string(),
+                content: "τ".to_string(),
+            },
+            TextNode::Hi {
+                rend: "sup".to_string(),
+                content: vec![TextNode::Choice {
+                    sic: "ο".to_string(),
+                    corr: "ω".to_string(),
+                }],
+            },
+            TextNode::NoteRef {
+                note_id: "n1".to_string(),
+                n: "1".to_string(),
+            },
+        ];
+        assert_eq!(l.markup_count(), 3);
+        assert_eq!(line(None).markup_count(), 0);
+    }
+}
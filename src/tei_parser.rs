@@ -1,7 +1,7 @@
 // CORRECTED STRUCTURE for TEI XML parsing
 
 use crate::tei_data::*;
-use quick_xml::events::Event;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 use std::collections::HashMap;
 
@@ -30,6 +30,14 @@ fn normalize_whitespace(s: &str) -> String {
 }
 
 pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
+    // OCR published as ALTO reads as the TEI it maps to
+    if crate::alto::is_alto(xml_content) {
+        return parse_tei_xml(&crate::alto::to_tei(xml_content)?);
+    }
+    // A passage served by a DTS repository, wrapped in <dts:wrapper>
+    if crate::dts::is_passage(xml_content) {
+        return parse_tei_xml(&crate::dts::passage_to_tei(xml_content)?);
+    }
     let mut reader = Reader::from_str(xml_content);
     // Let the parser deliver raw text nodes; normalize whitespace explicitly.
     reader.trim_text(false);
@@ -48,6 +56,15 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
     let mut in_body = false;
     let mut in_facsimile = false;
     let mut in_notes_div = false;
+    // Open body <div>s, and the one whose <head> is being read
+    let mut div_depth = 0usize;
+    let mut sections: Vec<Section> = Vec::new();
+    let mut in_head: Option<usize> = None;
+    // msDesc <dimensions>: centimetres per declared unit, while inside one
+    let mut dimensions_unit: Option<f64> = None;
+    // msDesc <origDate>/<provenance>/<acquisition> being read
+    let mut history: Vec<HistoryEvent> = Vec::new();
+    let mut history_event: Option<HistoryEvent> = None;
 
     // SINGLE, FLAT EVENT LOOP - no nested parsers fighting each other
     loop {
@@ -60,59 +77,53 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                     "facsimile" => {
                         in_facsimile = true;
                     }
-                    "surface" => {
-                        if in_facsimile {
-                            for attr in e.attributes().flatten() {
-                                let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
-                                let value = String::from_utf8_lossy(&attr.value).to_string();
-                                if key == "xml:id" {
-                                    temp_facsimile.surface_id = value;
-                                }
+                    "surface" if in_facsimile => {
+                        for attr in e.attributes().flatten() {
+                            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                            let value = String::from_utf8_lossy(&attr.value).to_string();
+                            if key == "xml:id" {
+                                temp_facsimile.surface_id = value;
                             }
                         }
                     }
-                    "graphic" => {
-                        if in_facsimile {
-                            for attr in e.attributes().flatten() {
-                                let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
-                                let value = String::from_utf8_lossy(&attr.value).to_string();
-                                match key.as_str() {
-                                    "url" => {
-                                        temp_facsimile.image_url = value;
-                                    }
-                                    "width" => {
-                                        temp_facsimile.width = value.parse().unwrap_or(0);
-                                    }
-                                    "height" => {
-                                        temp_facsimile.height = value.parse().unwrap_or(0);
-                                    }
-                                    _ => {}
+                    "graphic" if in_facsimile => {
+                        for attr in e.attributes().flatten() {
+                            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                            let value = String::from_utf8_lossy(&attr.value).to_string();
+                            match key.as_str() {
+                                "url" => {
+                                    temp_facsimile.image_url = value;
+                                }
+                                "width" => {
+                                    temp_facsimile.width = value.parse().unwrap_or(0);
+                                }
+                                "height" => {
+                                    temp_facsimile.height = value.parse().unwrap_or(0);
                                 }
+                                _ => {}
                             }
                         }
                     }
-                    "zone" => {
-                        if in_facsimile {
-                            let mut zone = Zone {
-                                id: String::new(),
-                                zone_type: String::new(),
-                                points: Vec::new(),
-                            };
-                            for attr in e.attributes().flatten() {
-                                let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
-                                let value = String::from_utf8_lossy(&attr.value).to_string();
-                                match key.as_str() {
-                                    "xml:id" => zone.id = value,
-                                    "type" => zone.zone_type = value,
-                                    "points" => zone.points = parse_points_allow_float(&value),
-                                    _ => {}
-                                }
-                            }
-                            if !zone.id.is_empty() {
-                                let zone_id_clone = zone.id.clone();
-                                zones.insert(zone_id_clone.clone(), zone);
+                    "zone" if in_facsimile => {
+                        let mut zone = Zone {
+                            id: String::new(),
+                            zone_type: String::new(),
+                            points: Vec::new(),
+                        };
+                        for attr in e.attributes().flatten() {
+                            let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                            let value = String::from_utf8_lossy(&attr.value).to_string();
+                            match key.as_str() {
+                                "xml:id" => zone.id = value,
+                                "type" => zone.zone_type = value,
+                                "points" => zone.points = parse_points_allow_float(&value),
+                                _ => {}
                             }
                         }
+                        if !zone.id.is_empty() {
+                            let zone_id_clone = zone.id.clone();
+                            zones.insert(zone_id_clone.clone(), zone);
+                        }
                     }
 
                     // ===== BODY/TRANSCRIPTION SECTION =====
@@ -133,22 +144,26 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
 
                         // Start new line
                         let mut facs = String::new();
+                        let mut n = None;
                         for attr in e.attributes().flatten() {
                             let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
                             let value = String::from_utf8_lossy(&attr.value).to_string();
-                            if key == "facs" {
-                                facs = value.trim_start_matches('#').to_string();
+                            match key.as_str() {
+                                "facs" => facs = value.trim_start_matches('#').to_string(),
+                                "n" => n = Some(value),
+                                _ => {}
                             }
                         }
                         current_line = Some(Line {
                             facs,
+                            n,
                             content: Vec::new(),
                         });
                         text_buffer.clear();
                     }
                     "ab" if in_body && current_line.is_some() && !in_notes_div => {
                         // Parse inline content for <ab>
-                        let ab_nodes = parse_inline_nodes(&mut reader, &mut buf, "ab");
+                        let ab_nodes = parse_inline_nodes(&mut reader, "ab");
                         if let Some(line) = current_line.as_mut() {
                             line.content.extend(ab_nodes);
                         }
@@ -156,20 +171,44 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                     "div" => {
                         // Check if this is a notes div (accept both "notes" and "note")
                         // This can occur in <body> or <back>
+                        let mut div_type = String::new();
+                        let mut n = String::new();
                         for attr in e.attributes().flatten() {
                             let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
                             let value = String::from_utf8_lossy(&attr.value).to_string();
-                            if key == "type" && (value == "notes" || value == "note") {
-                                in_notes_div = true;
-                                break;
+                            match key.as_str() {
+                                "type" => div_type = value,
+                                "n" => n = value,
+                                _ => {}
                             }
                         }
+                        if div_type == "notes" || div_type == "note" {
+                            in_notes_div = true;
+                        } else if in_body && !in_notes_div {
+                            // Its first line is the next <lb>
+                            sections.push(Section {
+                                title: format!("{} {}", div_type, n).trim().to_string(),
+                                depth: div_depth,
+                                line: lines.len() + usize::from(current_line.is_some()),
+                            });
+                            div_depth += 1;
+                        }
+                    }
+                    // The <head> of the innermost open division
+                    "head"
+                        if in_body
+                            && !in_notes_div
+                            && div_depth > 0
+                            && sections.last().is_some_and(|s| s.depth == div_depth - 1) =>
+                    {
+                        in_head = Some(sections.len() - 1);
+                        text_buffer.clear();
                     }
                     "note" if in_notes_div => {
                         // Parse a note in the notes div
                         let mut note_id = String::new();
                         let mut n = String::new();
-                        let mut note_counter = footnotes.len() + 1; // Auto-number if n not provided
+                        let note_counter = footnotes.len() + 1; // Auto-number if n not provided
                         for attr in e.attributes().flatten() {
                             let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
                             let value = String::from_utf8_lossy(&attr.value).to_string();
@@ -233,6 +272,42 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                     | "institution" | "collection" => {
                         text_buffer.clear();
                     }
+                    "dimensions" => {
+                        let mut unit = String::from("cm");
+                        for attr in e.attributes().flatten() {
+                            if attr.key.as_ref() == b"unit" {
+                                unit = String::from_utf8_lossy(&attr.value).to_string();
+                            }
+                        }
+                        dimensions_unit = match unit.as_str() {
+                            "mm" => Some(0.1),
+                            "cm" => Some(1.0),
+                            "m" => Some(100.0),
+                            "in" => Some(2.54),
+                            _ => None,
+                        };
+                    }
+                    "height" | "width" if dimensions_unit.is_some() => {
+                        text_buffer.clear();
+                    }
+                    "origDate" | "provenance" | "acquisition" if !in_body => {
+                        let (from, to) = date_range(e);
+                        history_event = Some(HistoryEvent {
+                            kind: name.clone(),
+                            text: String::new(),
+                            from,
+                            to,
+                        });
+                        text_buffer.clear();
+                    }
+                    "date" if !in_body => {
+                        // A <date> inside <provenance> dates it
+                        if let Some(event) = history_event.as_mut() {
+                            let (from, to) = date_range(e);
+                            event.from = event.from.take().or(from);
+                            event.to = event.to.take().or(to);
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -244,10 +319,21 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                     "facsimile" => {
                         in_facsimile = false;
                     }
-                    "div" => {
-                        if in_notes_div {
-                            in_notes_div = false;
+                    "div" if in_notes_div => {
+                        in_notes_div = false;
+                    }
+                    "div" if in_body && div_depth > 0 => {
+                        div_depth -= 1;
+                    }
+                    "head" if in_head.is_some() => {
+                        if let Some(index) = in_head.take() {
+                            let head = text_buffer.join("");
+                            let head = head.split_whitespace().collect::<Vec<_>>().join(" ");
+                            if !head.is_empty() {
+                                sections[index].title = head;
+                            }
                         }
+                        text_buffer.clear();
                     }
                     "body" => {
                         if let Some(line) = current_line.take() {
@@ -310,6 +396,40 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                         }
                         text_buffer.clear();
                     }
+                    "dimensions" => {
+                        dimensions_unit = None;
+                    }
+                    "origDate" | "provenance" | "acquisition"
+                        if history_event.as_ref().is_some_and(|h| h.kind == name) =>
+                    {
+                        if let Some(mut event) = history_event.take() {
+                            let text = text_buffer.join("");
+                            event.text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                            if !event.text.is_empty() || event.from.is_some() {
+                                history.push(event);
+                            }
+                        }
+                        text_buffer.clear();
+                    }
+                    "height" | "width" if dimensions_unit.is_some() => {
+                        let value = text_buffer
+                            .join("")
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                            .zip(dimensions_unit)
+                            .map(|(v, unit)| v * unit);
+                        // Keep the first (outermost) dimensions given
+                        let slot = if name == "height" {
+                            &mut temp_metadata.height_cm
+                        } else {
+                            &mut temp_metadata.width_cm
+                        };
+                        if slot.is_none() {
+                            *slot = value;
+                        }
+                        text_buffer.clear();
+                    }
                     _ => {}
                 }
             }
@@ -356,6 +476,12 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                     if !zone.id.is_empty() {
                         zones.insert(zone.id.clone(), zone);
                     }
+                } else if name == "date" && !in_body {
+                    if let Some(event) = history_event.as_mut() {
+                        let (from, to) = date_range(e);
+                        event.from = event.from.take().or(from);
+                        event.to = event.to.take().or(to);
+                    }
                 } else if name == "lb" && in_body {
                     // Self-closing <lb/>
                     if let Some(line) = current_line.take() {
@@ -363,16 +489,20 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
                     }
 
                     let mut facs = String::new();
+                    let mut n = None;
                     for attr in e.attributes().flatten() {
                         let key = String::from_utf8_lossy(attr.key.as_ref()).to_string();
                         let value = String::from_utf8_lossy(&attr.value).to_string();
-                        if key == "facs" {
-                            facs = value.trim_start_matches('#').to_string();
+                        match key.as_str() {
+                            "facs" => facs = value.trim_start_matches('#').to_string(),
+                            "n" => n = Some(value),
+                            _ => {}
                         }
                     }
 
                     current_line = Some(Line {
                         facs,
+                        n,
                         content: Vec::new(),
                     });
                     text_buffer.clear();
@@ -399,6 +529,20 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
     doc.facsimile = temp_facsimile;
     doc.lines = lines;
     doc.footnotes = footnotes;
+    doc.glossary = crate::glossary::parse_entries(xml_content);
+    doc.history = history;
+    // Divisions without a line on this page continue on the next one
+    let line_count = doc.lines.len();
+    doc.sections = sections
+        .into_iter()
+        .filter(|s| s.line < line_count)
+        .map(|mut s| {
+            if s.title.is_empty() {
+                s.title = "Sección".to_string();
+            }
+            s
+        })
+        .collect();
 
     Ok(doc)
 }
@@ -406,7 +550,6 @@ pub fn parse_tei_xml(xml_content: &str) -> Result<TeiDocument, String> {
 /// Parse inline nodes within elements like <ab>, <choice>, etc.
 fn parse_inline_nodes<R: std::io::BufRead>(
     reader: &mut Reader<R>,
-    buf: &mut Vec<u8>,
     break_tag: &str,
 ) -> Vec<TextNode> {
     let mut nodes = Vec::new();
@@ -501,7 +644,7 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                             }
                         }
                         // Recursively parse nested content and preserve the nested nodes
-                        let inner = parse_inline_nodes(reader, buf, "hi");
+                        let inner = parse_inline_nodes(reader, "hi");
                         nodes.push(TextNode::Hi {
                             rend,
                             content: inner,
@@ -509,7 +652,7 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                     }
                     "u" => {
                         // Handle <u> tag as underline formatting
-                        let inner = parse_inline_nodes(reader, buf, "u");
+                        let inner = parse_inline_nodes(reader, "u");
                         nodes.push(TextNode::Hi {
                             rend: "underline".to_string(),
                             content: inner,
@@ -580,7 +723,7 @@ fn parse_inline_nodes<R: std::io::BufRead>(
 
                         // Parse the nested inline nodes inside <persName> until its end.
                         // Reuse parse_inline_nodes recursively with break_tag = "persName".
-                        let inner_nodes = parse_inline_nodes(reader, buf, "persName");
+                        let inner_nodes = parse_inline_nodes(reader, "persName");
 
                         // Ensure we always store a Vec<TextNode> (even if empty).
                         nodes.push(TextNode::PersName {
@@ -597,6 +740,13 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                         let mut name = String::new();
                         let mut attrs = HashMap::new();
                         let mut place_buf = Vec::new();
+                        // @ref points at a gazetteer entry (e.g. Pleiades)
+                        let ref_uri = e
+                            .attributes()
+                            .flatten()
+                            .find(|attr| attr.key.as_ref() == b"ref")
+                            .map(|attr| String::from_utf8_lossy(&attr.value).trim().to_string())
+                            .filter(|r| !r.is_empty());
 
                         loop {
                             match reader.read_event_into(&mut place_buf) {
@@ -648,7 +798,11 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                             }
                             place_buf.clear();
                         }
-                        nodes.push(TextNode::PlaceName { name, attrs });
+                        nodes.push(TextNode::PlaceName {
+                            name,
+                            attrs,
+                            ref_uri,
+                        });
                     }
                     "rs" => {
                         let mut rs_type = String::new();
@@ -680,6 +834,38 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                         }
                         nodes.push(TextNode::RsType { rs_type, content });
                     }
+                    "term" => {
+                        let mut key = None;
+                        for attr in e.attributes().flatten() {
+                            let k = String::from_utf8_lossy(attr.key.as_ref()).to_string();
+                            let val = String::from_utf8_lossy(&attr.value).to_string();
+                            match k.as_str() {
+                                "ref" => key = Some(val.trim_start_matches('#').to_string()),
+                                "key" if key.is_none() => key = Some(val),
+                                _ => {}
+                            }
+                        }
+                        let mut content = String::new();
+                        let mut term_buf = Vec::new();
+                        loop {
+                            match reader.read_event_into(&mut term_buf) {
+                                Ok(Event::Text(ce)) => {
+                                    content.push_str(&ce.unescape().unwrap_or_default());
+                                }
+                                Ok(Event::End(ref ce)) => {
+                                    let cname = String::from_utf8_lossy(ce.local_name().as_ref())
+                                        .to_string();
+                                    if cname == "term" {
+                                        break;
+                                    }
+                                }
+                                Ok(Event::Eof) => break,
+                                _ => {}
+                            }
+                            term_buf.clear();
+                        }
+                        nodes.push(TextNode::Term { key, content });
+                    }
                     "note" => {
                         // Could be inline note or note reference (with target attribute)
                         let mut n = String::new();
@@ -794,6 +980,29 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                             });
                         }
                     }
+                    "date" => {
+                        let (from, to) = date_range(e);
+                        let mut content = String::new();
+                        let mut date_buf = Vec::new();
+                        loop {
+                            match reader.read_event_into(&mut date_buf) {
+                                Ok(Event::Text(ce)) => {
+                                    content.push_str(&ce.unescape().unwrap_or_default());
+                                }
+                                Ok(Event::End(ref ce)) => {
+                                    let cname = String::from_utf8_lossy(ce.local_name().as_ref())
+                                        .to_string();
+                                    if cname == "date" {
+                                        break;
+                                    }
+                                }
+                                Ok(Event::Eof) => break,
+                                _ => {}
+                            }
+                            date_buf.clear();
+                        }
+                        nodes.push(TextNode::Date { from, to, content });
+                    }
                     "unclear" => {
                         let mut reason = String::new();
                         for attr in e.attributes().flatten() {
@@ -826,7 +1035,7 @@ fn parse_inline_nodes<R: std::io::BufRead>(
                     }
                     _ => {
                         // Unknown tag: recurse
-                        let _ = parse_inline_nodes(reader, buf, &name);
+                        let _ = parse_inline_nodes(reader, &name);
                     }
                 }
             }
@@ -852,6 +1061,25 @@ fn parse_inline_nodes<R: std::io::BufRead>(
     nodes
 }
 
+/// Dating attributes of a `<date>`-like element: `@when` for both ends,
+/// else `@notBefore`/`@notAfter` or `@from`/`@to`.
+fn date_range(e: &BytesStart) -> (Option<String>, Option<String>) {
+    let (mut from, mut to) = (None, None);
+    for attr in e.attributes().flatten() {
+        let value = String::from_utf8_lossy(&attr.value).trim().to_string();
+        if value.is_empty() {
+            continue;
+        }
+        match attr.key.as_ref() {
+            b"when" => return (Some(value.clone()), Some(value)),
+            b"notBefore" | b"from" => from = Some(value),
+            b"notAfter" | b"to" => to = Some(value),
+            _ => {}
+        }
+    }
+    (from, to)
+}
+
 fn parse_points_allow_float(points_str: &str) -> Vec<(u32, u32)> {
     points_str
         .split_whitespace()
@@ -880,3 +1108,52 @@ fn parse_points_allow_float(points_str: &str) -> Vec<(u32, u32)> {
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let xml = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><body>
+            <lb n="1"/><ab>prooemium</ab>
+            <div type="recipe" n="1">
+                <head>Ἀγωγή  <hi rend="italic">erotica</hi></head>
+                <lb n="2"/><ab>α</ab>
+                <div type="ingredients"><lb n="3"/><ab>β</ab></div>
+            </div>
+            <div type="notes"><note n="1">nota</note></div>
+            <div><lb n="4"/><ab>γ</ab></div>
+            <div type="recipe" n="2"><head>continúa</head></div>
+        </body></text></TEI>"#;
+        let doc = parse_tei_xml(xml).unwrap();
+        let sections: Vec<_> = doc
+            .sections
+            .iter()
+            .map(|s| (s.title.as_str(), s.depth, s.line))
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                ("Ἀγωγή erotica", 0, 1),
+                ("ingredients", 1, 2),
+                ("Sección", 0, 3),
+            ]
+        );
+        assert_eq!(doc.footnotes.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_dimensions() {
+        let xml = r#"<TEI><teiHeader><fileDesc><sourceDesc><msDesc><physDesc>
+            <objectDesc><supportDesc><extent>
+                <dimensions unit="mm"><height>215</height><width>120</width></dimensions>
+                <dimensions type="written" unit="mm"><height>180</height><width>100</width></dimensions>
+            </extent></supportDesc></objectDesc>
+        </physDesc></msDesc></sourceDesc></fileDesc></teiHeader>
+        <text><body><ab><lb n="1"/>text</ab></body></text></TEI>"#;
+        let doc = parse_tei_xml(xml).unwrap();
+        assert_eq!(doc.metadata.height_cm, Some(21.5));
+        assert_eq!(doc.metadata.width_cm, Some(12.0));
+    }
+}
@@ -0,0 +1,267 @@
+// src/tei_source.rs
+// Support for editing a page's raw TEI in the browser: a lightweight XML
+// tokenizer for syntax highlighting, consistency warnings on the parsed
+// result that the parser itself tolerates silently, and the image credit
+// written into the exported file.
+use crate::i18n::Lang;
+use crate::tei_data::TeiDocument;
+use quick_xml::escape::escape;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Token {
+    Text,
+    /// Tag delimiters and element names
+    Tag,
+    AttrName,
+    AttrValue,
+    Comment,
+    Entity,
+    /// XML declaration, processing instructions, DOCTYPE and CDATA
+    Meta,
+}
+
+impl Token {
+    pub fn class(self) -> &'static str {
+        match self {
+            Token::Text => "tok-text",
+            Token::Tag => "tok-tag",
+            Token::AttrName => "tok-attr",
+            Token::AttrValue => "tok-value",
+            Token::Comment => "tok-comment",
+            Token::Entity => "tok-entity",
+            Token::Meta => "tok-meta",
+        }
+    }
+}
+
+/// Split XML source into highlighted spans. Never fails: malformed input
+/// still yields spans that concatenate back to the source.
+pub fn highlight(src: &str) -> Vec<(Token, &str)> {
+    let mut out = Vec::new();
+    let mut rest = src;
+    while !rest.is_empty() {
+        let (token, len) = if rest.starts_with("<!--") {
+            (Token::Comment, end_after(rest, "-->"))
+        } else if rest.starts_with("<![CDATA[") {
+            (Token::Meta, end_after(rest, "]]>"))
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            (Token::Meta, end_after(rest, ">"))
+        } else if rest.starts_with('<') {
+            let len = tag(rest, &mut out);
+            rest = &rest[len..];
+            continue;
+        } else if let Some(len) = entity(rest) {
+            (Token::Entity, len)
+        } else {
+            // Up to the next markup, keeping a lone '&' as text
+            let len = rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| *c == '<' || *c == '&')
+                .map_or(rest.len(), |(i, _)| i);
+            (Token::Text, len)
+        };
+        out.push((token, &rest[..len]));
+        rest = &rest[len..];
+    }
+    out
+}
+
+fn end_after(s: &str, terminator: &str) -> usize {
+    s.find(terminator).map_or(s.len(), |i| i + terminator.len())
+}
+
+fn entity(s: &str) -> Option<usize> {
+    let body = s.strip_prefix('&')?;
+    let end = body.find(';')?;
+    let name = &body[..end];
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '#' || c == '_' || c == '-' || c == '.');
+    valid.then_some(end + 2)
+}
+
+/// Tokenize one start/end tag at the start of `s`; returns its length.
+fn tag<'a>(s: &'a str, out: &mut Vec<(Token, &'a str)>) -> usize {
+    let is_delim = |c: char| c.is_whitespace() || c == '>' || c == '/' || c == '=';
+    let open = if s.starts_with("</") { 2 } else { 1 };
+    let name_end = s[open..].find(is_delim).map_or(s.len(), |i| i + open);
+    out.push((Token::Tag, &s[..name_end]));
+    let mut pos = name_end;
+    while pos < s.len() {
+        let rest = &s[pos..];
+        let c = rest.chars().next().unwrap_or_default();
+        let (token, len) = if rest.starts_with("/>") {
+            (Token::Tag, 2)
+        } else if c == '>' {
+            (Token::Tag, 1)
+        } else if c == '<' {
+            // Unclosed tag: let the caller resume at the next one
+            return pos;
+        } else if c == '/' {
+            (Token::Text, 1)
+        } else if c.is_whitespace() || c == '=' {
+            let len = rest
+                .find(|ch: char| !(ch.is_whitespace() || ch == '='))
+                .unwrap_or(rest.len());
+            (Token::Text, len)
+        } else if c == '"' || c == '\'' {
+            let len = rest[1..].find(c).map_or(rest.len(), |i| i + 2);
+            (Token::AttrValue, len)
+        } else {
+            let len = rest
+                .find(|ch: char| is_delim(ch) || ch == '<')
+                .unwrap_or(rest.len());
+            (Token::AttrName, len)
+        };
+        out.push((token, &rest[..len]));
+        pos += len;
+        if token == Token::Tag {
+            break;
+        }
+    }
+    pos
+}
+
+/// Inconsistencies the viewer would show as missing highlights or stray
+/// zones: lines without a usable zone, zones without points or lines,
+/// and zones shared by several lines.
+pub fn warnings(lang: Lang, doc: &TeiDocument) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut seen = HashSet::new();
+    for (index, line) in doc.lines.iter().enumerate() {
+        let label = doc.line_label(index).unwrap_or_default();
+        if line.facs.is_empty() {
+            warnings.push(lang.tf("Línea {}: <lb> sin @facs", &[&label]));
+        } else if !doc.facsimile.zones.contains_key(&line.facs) {
+            warnings.push(lang.tf(
+                "Línea {}: la zona #{} no existe en <facsimile>",
+                &[&label, &line.facs],
+            ));
+        } else if !seen.insert(line.facs.as_str()) {
+            warnings.push(lang.tf(
+                "Línea {}: la zona #{} ya está asignada a otra línea",
+                &[&label, &line.facs],
+            ));
+        }
+    }
+    let mut zones: Vec<_> = doc.facsimile.zones.values().collect();
+    zones.sort_by(|a, b| a.id.cmp(&b.id));
+    for zone in zones {
+        if zone.points.is_empty() {
+            warnings.push(lang.tf("Zona #{}: sin @points", &[&zone.id]));
+        } else if !seen.contains(zone.id.as_str()) {
+            warnings.push(lang.tf("Zona #{}: ninguna línea la referencia", &[&zone.id]));
+        }
+    }
+    warnings
+}
+
+/// `src` with `credit` as the `<desc>` of its first `<graphic>`, as in
+/// the exported `<facsimile>`; unchanged when that graphic has a `<desc>`.
+pub fn with_image_credit(src: &str, credit: &str) -> String {
+    let Some(start) = src.find("<graphic") else {
+        return src.to_string();
+    };
+    let Some(end) = src[start..].find('>').map(|i| start + i) else {
+        return src.to_string();
+    };
+    let desc = format!("<desc>{}</desc>", escape(credit));
+    if src[..end].ends_with('/') {
+        let open = src[start..end - 1].trim_end();
+        return format!(
+            "{}{}>{}</graphic>{}",
+            &src[..start],
+            open,
+            desc,
+            &src[end + 1..]
+        );
+    }
+    let inner = src[end..]
+        .find("</graphic>")
+        .map_or("", |i| &src[end..end + i]);
+    if inner.contains("<desc") {
+        return src.to_string();
+    }
+    format!("{}{}{}", &src[..=end], desc, &src[end + 1..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(spans: &[(Token, &str)]) -> String {
+        spans.iter().map(|(_, s)| *s).collect()
+    }
+
+    #[test]
+    fn test_highlight_tokens() {
+        let src = r##"<?xml version="1.0"?><lb facs="#z1" n='2'/>καὶ &amp; <!-- c --></p>"##;
+        let spans = highlight(src);
+        assert_eq!(joined(&spans), src);
+        assert_eq!(spans[0], (Token::Meta, r#"<?xml version="1.0"?>"#));
+        assert!(spans.contains(&(Token::Tag, "<lb")));
+        assert!(spans.contains(&(Token::AttrName, "facs")));
+        assert!(spans.contains(&(Token::AttrValue, "\"#z1\"")));
+        assert!(spans.contains(&(Token::AttrValue, "'2'")));
+        assert!(spans.contains(&(Token::Tag, "/>")));
+        assert!(spans.contains(&(Token::Entity, "&amp;")));
+        assert!(spans.contains(&(Token::Comment, "<!-- c -->")));
+        assert!(spans.contains(&(Token::Tag, "</p")));
+    }
+
+    #[test]
+    fn test_highlight_malformed_round_trips() {
+        for src in [
+            "<p a=\"unterminated",
+            "<p <q>",
+            "a & b",
+            "<",
+            "<!-- open",
+            "x/y",
+            "<lb / >",
+        ] {
+            assert_eq!(joined(&highlight(src)), src, "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_warnings() {
+        let xml = r##"<TEI xmlns="http://www.tei-c.org/ns/1.0">
+            <facsimile><surface><graphic url="p1.jpg" width="100" height="100"/>
+                <zone xml:id="z1" points="0,0 10,0 10,10"/>
+                <zone xml:id="z2" points="0,20 10,20 10,30"/>
+                <zone xml:id="z3"/>
+            </surface></facsimile>
+            <text><body><p><lb facs="#z1" n="1"/>a<lb facs="#z9" n="2"/>b<lb facs="#z1" n="3"/>c</p></body></text>
+        </TEI>"##;
+        let doc = crate::tei_parser::parse_tei_xml(xml).unwrap();
+        assert_eq!(
+            warnings(Lang::Es, &doc),
+            vec![
+                "Línea 2: la zona #z9 no existe en <facsimile>",
+                "Línea 3: la zona #z1 ya está asignada a otra línea",
+                "Zona #z2: ninguna línea la referencia",
+                "Zona #z3: sin @points",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_image_credit() {
+        let credit = "Foto: B & W · © Museo";
+        assert_eq!(
+            with_image_credit(r#"<surface><graphic url="p1.jpg" /></surface>"#, credit),
+            r#"<surface><graphic url="p1.jpg"><desc>Foto: B &amp; W · © Museo</desc></graphic></surface>"#
+        );
+        assert_eq!(
+            with_image_credit(r#"<graphic url="p1.jpg"></graphic>"#, "Foto"),
+            r#"<graphic url="p1.jpg"><desc>Foto</desc></graphic>"#
+        );
+        let described = r#"<graphic url="p1.jpg"><desc>Scan</desc></graphic>"#;
+        assert_eq!(with_image_credit(described, "Foto"), described);
+        assert_eq!(with_image_credit("<TEI/>", "Foto"), "<TEI/>");
+    }
+}
@@ -0,0 +1,108 @@
+// src/theme.rs
+use crate::persistence;
+use crate::utils::window;
+
+/// Colour theme, applied as a `data-theme` attribute on `<html>` so the
+/// stylesheet can switch its palette.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Black background, thick outlines and pattern-coded semantic markup
+    HighContrast,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::HighContrast => "high-contrast",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            "high-contrast" => Some(Theme::HighContrast),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Light => "☀️ Claro",
+            Theme::Dark => "🌙 Oscuro",
+            Theme::HighContrast => "◐ Alto contraste",
+        }
+    }
+
+    /// Theme picked by the reader in a previous session, if any
+    pub fn stored() -> Option<Self> {
+        persistence::load::<String>(persistence::THEME).and_then(|t| Self::parse(&t))
+    }
+
+    /// Theme requested through `prefers-color-scheme`; dark when unknown
+    pub fn preferred() -> Self {
+        let prefers_light = window()
+            .and_then(|w| w.match_media("(prefers-color-scheme: light)").ok())
+            .flatten()
+            .is_some_and(|mq| mq.matches());
+        if prefers_light {
+            Theme::Light
+        } else {
+            Theme::Dark
+        }
+    }
+
+    /// Stored choice, falling back to the system preference
+    pub fn current() -> Self {
+        Self::stored().unwrap_or_else(Self::preferred)
+    }
+
+    /// Apply the theme to the document and remember it across sessions.
+    pub fn select(self) {
+        if let Some(root) = window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.document_element())
+        {
+            let _ = root.set_attribute("data-theme", self.as_str());
+        }
+        persistence::save(persistence::THEME, self.as_str());
+    }
+}
+
+/// Use a project's accent color instead of the theme's (but for high
+/// contrast), or go back to the theme's with `None`.
+pub fn apply_accent(color: Option<&str>) {
+    let Some(body) = window().and_then(|w| w.document()).and_then(|d| d.body()) else {
+        return;
+    };
+    let style = body.style();
+    match color {
+        Some(color) => {
+            let _ = style.set_property("--project-accent", color);
+            let _ = body.set_attribute("data-accent", "project");
+        }
+        None => {
+            let _ = style.remove_property("--project-accent");
+            let _ = body.remove_attribute("data-accent");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trip() {
+        for theme in Theme::ALL {
+            assert_eq!(Theme::parse(theme.as_str()), Some(theme));
+        }
+        assert_eq!(Theme::parse("sepia"), None);
+    }
+}
@@ -0,0 +1,372 @@
+// src/timeline.rs
+// Chronology of a project: the dating of the object (manifest
+// `date_range`, msDesc `<origDate>`), its provenance history
+// (`<provenance>`, `<acquisition>`) and the `<date>`s mentioned in the
+// text, as year ranges that can be laid out on one axis.
+use crate::i18n::Lang;
+use crate::tei_data::{TeiDocument, TextNode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    Dating,
+    Provenance,
+    Mention,
+}
+
+impl EventKind {
+    pub const ALL: [EventKind; 3] = [EventKind::Dating, EventKind::Provenance, EventKind::Mention];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EventKind::Dating => "Datación",
+            EventKind::Provenance => "Procedencia",
+            EventKind::Mention => "Fechas en el texto",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimelineEvent {
+    pub kind: EventKind,
+    pub label: String,
+    /// Years, negative before the common era
+    pub from: i32,
+    pub to: i32,
+    /// First page stating the event, absent for the manifest dating
+    pub page: Option<u32>,
+    /// Line reference (`@n` label or 1-based position) of a mention
+    pub line: Option<String>,
+}
+
+/// Year of an ISO 8601 date as encoded in TEI attributes: "-0150",
+/// "0312-05-01", "1897".
+pub fn parse_year(value: &str) -> Option<i32> {
+    let value = value.trim();
+    let (sign, rest) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value),
+    };
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    if digits.is_empty() {
+        return None;
+    }
+    digits.parse::<i32>().ok().map(|year| sign * year)
+}
+
+/// Year range of a free-text dating such as "1st c. BCE – 4th c. CE",
+/// "s. III–IV d. C." or "300-350 CE". An era written only at the end
+/// applies to both ends.
+pub fn parse_range(text: &str) -> Option<(i32, i32)> {
+    let text = text.replace(['—', '―'], "–").replace(" to ", "–");
+    // A hyphen between two numbers ("300-350", "III-IV") separates them
+    let mut normalized = String::new();
+    let chars: Vec<char> = text.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        let between = i > 0
+            && chars[i - 1].is_alphanumeric()
+            && chars.get(i + 1).is_some_and(|n| n.is_alphanumeric());
+        normalized.push(if c == '-' && between { '–' } else { c });
+    }
+    let parts: Vec<&str> = normalized.split('–').map(str::trim).collect();
+    if parts.is_empty() || parts.len() > 2 {
+        return None;
+    }
+    let century = parts.iter().any(|p| is_century(p));
+    let last_era = parts.last().and_then(|p| era(p));
+    let mut ranges = Vec::new();
+    for part in &parts {
+        let bce = era(part).or(last_era) == Some(true);
+        let n = number(part)?;
+        ranges.push(match (century, bce) {
+            (true, false) => (100 * (n - 1) + 1, 100 * n),
+            (true, true) => (-100 * n, -100 * (n - 1) - 1),
+            (false, false) => (n, n),
+            (false, true) => (-n, -n),
+        });
+    }
+    let from = ranges.iter().map(|r| r.0).min()?;
+    let to = ranges.iter().map(|r| r.1).max()?;
+    Some((from, to))
+}
+
+/// Whether `part` names its era: Some(true) before the common era.
+fn era(part: &str) -> Option<bool> {
+    let compact: String = part
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    if ["bce", "bc", "ac", "aec", "adc"]
+        .iter()
+        .any(|e| compact.ends_with(e))
+    {
+        Some(true)
+    } else if ["ce", "ad", "dc", "dec"]
+        .iter()
+        .any(|e| compact.ends_with(e))
+        || compact.starts_with("ad")
+    {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Whether `part` counts centuries: "1st c.", "4th century", "s. III",
+/// "siglo IV" or a bare Roman numeral.
+fn is_century(part: &str) -> bool {
+    let lower = part.to_lowercase();
+    let words: Vec<&str> = lower.split_whitespace().collect();
+    let ordinal = |w: &str| {
+        ["st", "nd", "rd", "th", "º", "°"]
+            .iter()
+            .any(|s| w.ends_with(s))
+    };
+    words.iter().enumerate().any(|(i, w)| {
+        w.starts_with("cent")
+            || w.starts_with("siglo")
+            || *w == "s."
+            || (*w == "c." && i > 0 && ordinal(words[i - 1]))
+    }) || roman_token(part).is_some()
+}
+
+/// The first number of `part`, in Arabic or Roman numerals.
+fn number(part: &str) -> Option<i32> {
+    let digits: String = part
+        .chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(char::is_ascii_digit)
+        .collect();
+    if !digits.is_empty() {
+        return digits.parse().ok();
+    }
+    roman_token(part)
+}
+
+/// Value of the first word of `part` written in upper-case Roman numerals.
+fn roman_token(part: &str) -> Option<i32> {
+    part.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty() && w.chars().all(|c| "IVXL".contains(c)))
+        .find_map(|w| {
+            let value = |c| match c {
+                'I' => 1,
+                'V' => 5,
+                'X' => 10,
+                _ => 50,
+            };
+            let digits: Vec<i32> = w.chars().map(value).collect();
+            let total = digits
+                .iter()
+                .enumerate()
+                .map(|(i, &d)| {
+                    if digits.get(i + 1).is_some_and(|&next| next > d) {
+                        -d
+                    } else {
+                        d
+                    }
+                })
+                .sum::<i32>();
+            (total > 0).then_some(total)
+        })
+}
+
+/// Events of the project: the manifest's dating, the msDesc history of
+/// the pages (each event once, at the first page stating it) and the
+/// dates mentioned on every line, in chronological order.
+pub fn build<'a>(
+    date_range: &str,
+    docs: impl IntoIterator<Item = (u32, &'a TeiDocument)>,
+) -> Vec<TimelineEvent> {
+    let mut events = Vec::new();
+    if let Some((from, to)) = parse_range(date_range) {
+        events.push(TimelineEvent {
+            kind: EventKind::Dating,
+            label: date_range.trim().to_string(),
+            from,
+            to,
+            page: None,
+            line: None,
+        });
+    }
+    for (page, doc) in docs {
+        for h in &doc.history {
+            let Some((from, to)) =
+                years(h.from.as_deref(), h.to.as_deref()).or_else(|| parse_range(&h.text))
+            else {
+                continue;
+            };
+            let kind = if h.kind == "origDate" {
+                EventKind::Dating
+            } else {
+                EventKind::Provenance
+            };
+            let label = match (h.text.as_str(), h.kind.as_str()) {
+                ("", "origDate") => "Fecha de origen".to_string(),
+                ("", "acquisition") => "Adquisición".to_string(),
+                ("", _) => "Procedencia".to_string(),
+                (text, _) => text.to_string(),
+            };
+            let seen = events
+                .iter()
+                .any(|e: &TimelineEvent| e.kind == kind && e.label == label && e.from == from);
+            if !seen {
+                events.push(TimelineEvent {
+                    kind,
+                    label,
+                    from,
+                    to,
+                    page: Some(page),
+                    line: None,
+                });
+            }
+        }
+        for (index, line) in doc.lines.iter().enumerate() {
+            let mut dates = Vec::new();
+            collect_dates(&line.content, &mut dates);
+            for (from, to, content) in dates {
+                let Some((from, to)) = years(from, to).or_else(|| parse_range(content)) else {
+                    continue;
+                };
+                events.push(TimelineEvent {
+                    kind: EventKind::Mention,
+                    label: content.trim().to_string(),
+                    from,
+                    to,
+                    page: Some(page),
+                    line: doc.line_label(index),
+                });
+            }
+        }
+    }
+    events.sort_by_key(|e| (e.from, e.to));
+    events
+}
+
+/// Years of a pair of attribute values, one end standing for both when
+/// the other is missing.
+fn years(from: Option<&str>, to: Option<&str>) -> Option<(i32, i32)> {
+    let from = from.and_then(parse_year);
+    let to = to.and_then(parse_year);
+    match (from, to) {
+        (Some(from), Some(to)) => Some((from.min(to), from.max(to))),
+        (Some(year), None) | (None, Some(year)) => Some((year, year)),
+        (None, None) => None,
+    }
+}
+
+fn collect_dates<'a>(
+    nodes: &'a [TextNode],
+    out: &mut Vec<(Option<&'a str>, Option<&'a str>, &'a str)>,
+) {
+    for node in nodes {
+        match node {
+            TextNode::Date { from, to, content } => {
+                out.push((from.as_deref(), to.as_deref(), content))
+            }
+            TextNode::PersName { content, .. } | TextNode::Hi { content, .. } => {
+                collect_dates(content, out)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// First and last year of `events`.
+pub fn bounds(events: &[TimelineEvent]) -> Option<(i32, i32)> {
+    let from = events.iter().map(|e| e.from).min()?;
+    let to = events.iter().map(|e| e.to).max()?;
+    Some((from, to))
+}
+
+/// Era abbreviation of a year: "a. C."/"d. C.", "BCE"/"CE".
+fn era_label(lang: Lang, year: i32) -> &'static str {
+    lang.t(if year < 0 { "a. C." } else { "d. C." })
+}
+
+/// A year with its era: "150 a. C.", "312 CE".
+pub fn format_year(lang: Lang, year: i32) -> String {
+    format!("{} {}", year.abs(), era_label(lang, year))
+}
+
+/// A range of years, a single year when both ends agree.
+pub fn format_range(lang: Lang, from: i32, to: i32) -> String {
+    if from == to {
+        format_year(lang, from)
+    } else if (from < 0) == (to < 0) {
+        format!("{}–{} {}", from.abs(), to.abs(), era_label(lang, to))
+    } else {
+        format!("{} – {}", format_year(lang, from), format_year(lang, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(parse_year("-0150"), Some(-150));
+        assert_eq!(parse_year("0312-05-01"), Some(312));
+        assert_eq!(parse_year("1897"), Some(1897));
+        assert_eq!(parse_year("ca. 300"), None);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("1st c. BCE – 4th c. CE"), Some((-100, 400)));
+        assert_eq!(parse_range("s. III–IV d. C."), Some((201, 400)));
+        assert_eq!(parse_range("300-350 CE"), Some((300, 350)));
+        assert_eq!(parse_range("2nd c. BCE"), Some((-200, -101)));
+        assert_eq!(parse_range("IV d.C."), Some((301, 400)));
+        assert_eq!(parse_range("150 a. C."), Some((-150, -150)));
+        assert_eq!(parse_range("300 d.C."), Some((300, 300)));
+        assert_eq!(parse_range(""), None);
+        assert_eq!(parse_range("desconocida"), None);
+    }
+
+    #[test]
+    fn test_build() {
+        let xml = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><teiHeader><fileDesc><sourceDesc><msDesc>
+            <history>
+              <origin><origDate notBefore="0300" notAfter="0350">s. IV</origDate></origin>
+              <provenance>Comprado en Tebas, <date when="1828">1828</date></provenance>
+              <acquisition when="1832">Leiden</acquisition>
+            </history>
+            </msDesc></sourceDesc></fileDesc></teiHeader>
+            <text><body>
+              <lb n="1"/><ab>ἔτους <date when="0312">κʹ</date></ab>
+              <lb n="2"/><ab>sin fecha <date>pronto</date></ab>
+            </body></text></TEI>"#;
+        let doc = crate::tei_parser::parse_tei_xml(xml).unwrap();
+        assert_eq!(doc.history.len(), 3);
+        assert_eq!(doc.history[1].from.as_deref(), Some("1828"));
+        assert_eq!(doc.history[1].text, "Comprado en Tebas, 1828");
+
+        // The same header on another page is not repeated
+        let events = build("III–IV d. C.", [(1, &doc), (2, &doc)]);
+        let summary: Vec<_> = events
+            .iter()
+            .map(|e| (e.kind, e.from, e.to, e.page, e.line.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (EventKind::Dating, 201, 400, None, None),
+                (EventKind::Dating, 300, 350, Some(1), None),
+                (EventKind::Mention, 312, 312, Some(1), Some("1")),
+                (EventKind::Mention, 312, 312, Some(2), Some("1")),
+                (EventKind::Provenance, 1828, 1828, Some(1), None),
+                (EventKind::Provenance, 1832, 1832, Some(1), None),
+            ]
+        );
+        assert_eq!(bounds(&events), Some((201, 1832)));
+    }
+
+    #[test]
+    fn test_format_range() {
+        assert_eq!(format_range(Lang::Es, -150, -150), "150 a. C.");
+        assert_eq!(format_range(Lang::Es, 201, 400), "201–400 d. C.");
+        assert_eq!(format_range(Lang::Es, -100, 400), "100 a. C. – 400 d. C.");
+        assert_eq!(format_range(Lang::En, -100, 400), "100 BCE – 400 CE");
+    }
+}
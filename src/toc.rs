@@ -0,0 +1,175 @@
+// src/toc.rs
+// Table of contents of a project: the manifest's `outline` when declared,
+// else the `<div>` sections parsed from every page, flattened with their
+// nesting depth so the panel can collapse them.
+use crate::project_config::OutlineEntry;
+use crate::tei_data::TeiDocument;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocEntry {
+    pub title: String,
+    pub depth: usize,
+    pub page: u32,
+    /// Line reference (`@n` label or 1-based position) on the page
+    pub line: Option<String>,
+}
+
+/// Flatten a manifest outline, parents before their children.
+pub fn from_outline(outline: &[OutlineEntry]) -> Vec<TocEntry> {
+    fn walk(entries: &[OutlineEntry], depth: usize, out: &mut Vec<TocEntry>) {
+        for entry in entries {
+            out.push(TocEntry {
+                title: entry.title.clone(),
+                depth,
+                page: entry.page,
+                line: entry.line.clone(),
+            });
+            walk(&entry.children, depth + 1, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(outline, 0, &mut out);
+    out
+}
+
+/// Sections of `docs` in page order.
+pub fn from_sections<'a>(docs: impl IntoIterator<Item = (u32, &'a TeiDocument)>) -> Vec<TocEntry> {
+    docs.into_iter()
+        .flat_map(|(page, doc)| {
+            doc.sections.iter().map(move |s| TocEntry {
+                title: s.title.clone(),
+                depth: s.depth,
+                page,
+                line: doc.line_label(s.line),
+            })
+        })
+        .collect()
+}
+
+/// Whether the entry at `index` has nested entries, i.e. can be collapsed.
+pub fn has_children(entries: &[TocEntry], index: usize) -> bool {
+    match (entries.get(index), entries.get(index + 1)) {
+        (Some(entry), Some(next)) => next.depth > entry.depth,
+        _ => false,
+    }
+}
+
+/// Indices of the entries shown when those in `collapsed` hide their
+/// descendants.
+pub fn visible(entries: &[TocEntry], collapsed: &HashSet<usize>) -> Vec<usize> {
+    let mut shown = Vec::new();
+    // Depth of the collapsed entry whose descendants are being skipped
+    let mut hiding: Option<usize> = None;
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(depth) = hiding {
+            if entry.depth > depth {
+                continue;
+            }
+            hiding = None;
+        }
+        shown.push(index);
+        if collapsed.contains(&index) {
+            hiding = Some(entry.depth);
+        }
+    }
+    shown
+}
+
+/// Sections open at `page`, outermost first: the last entry started on
+/// or before it at each depth.
+pub fn trail(entries: &[TocEntry], page: u32) -> Vec<&TocEntry> {
+    let mut open: Vec<&TocEntry> = Vec::new();
+    for entry in entries.iter().take_while(|e| e.page <= page) {
+        open.retain(|e| e.depth < entry.depth);
+        open.push(entry);
+    }
+    open
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outline() -> Vec<OutlineEntry> {
+        let entry = |title: &str, page, children| OutlineEntry {
+            title: title.to_string(),
+            page,
+            line: None,
+            children,
+        };
+        vec![
+            entry(
+                "Octava de Moisés",
+                1,
+                vec![
+                    entry("Primera ceremonia", 1, vec![]),
+                    entry("Segunda", 2, vec![]),
+                ],
+            ),
+            entry("Apéndice", 3, vec![]),
+        ]
+    }
+
+    #[test]
+    fn test_from_outline() {
+        let entries = from_outline(&outline());
+        let flat: Vec<_> = entries
+            .iter()
+            .map(|e| (e.title.as_str(), e.depth, e.page))
+            .collect();
+        assert_eq!(
+            flat,
+            [
+                ("Octava de Moisés", 0, 1),
+                ("Primera ceremonia", 1, 1),
+                ("Segunda", 1, 2),
+                ("Apéndice", 0, 3),
+            ]
+        );
+        assert!(has_children(&entries, 0));
+        assert!(!has_children(&entries, 2));
+        assert!(!has_children(&entries, 3));
+    }
+
+    #[test]
+    fn test_visible() {
+        let entries = from_outline(&outline());
+        assert_eq!(visible(&entries, &HashSet::new()), [0, 1, 2, 3]);
+        assert_eq!(visible(&entries, &HashSet::from([0])), [0, 3]);
+    }
+
+    #[test]
+    fn test_trail() {
+        let entries = from_outline(&outline());
+        let titles = |page| -> Vec<&str> {
+            trail(&entries, page)
+                .iter()
+                .map(|e| e.title.as_str())
+                .collect()
+        };
+        assert_eq!(titles(1), ["Octava de Moisés", "Primera ceremonia"]);
+        assert_eq!(titles(2), ["Octava de Moisés", "Segunda"]);
+        assert_eq!(titles(5), ["Apéndice"]);
+        assert!(titles(0).is_empty());
+    }
+
+    #[test]
+    fn test_from_sections() {
+        let xml = r#"<TEI xmlns="http://www.tei-c.org/ns/1.0"><text><body>
+            <lb n="1"/><ab>α</ab>
+            <div type="recipe" n="4"><lb n="2"/><ab>β</ab></div>
+        </body></text></TEI>"#;
+        let doc = crate::tei_parser::parse_tei_xml(xml).unwrap();
+        let entries = from_sections([(7, &doc)]);
+        assert_eq!(
+            entries,
+            vec![TocEntry {
+                title: "recipe 4".to_string(),
+                depth: 0,
+                page: 7,
+                line: Some("2".to_string()),
+            }]
+        );
+    }
+}
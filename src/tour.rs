@@ -0,0 +1,81 @@
+// src/tour.rs
+// Guided tours: an optional `tour.json` in a project folder lists curated
+// stops (a page, optionally a zone or line to zoom to, and a caption) that
+// the viewer steps through with "Siguiente parada".
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tour {
+    #[serde(default)]
+    pub title: Option<String>,
+    pub stops: Vec<TourStop>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TourStop {
+    pub page: u32,
+    /// Facsimile zone id to zoom to (without the leading `#`)
+    #[serde(default)]
+    pub zone: Option<String>,
+    /// Line reference, resolved like the "Ir a línea" box: the source
+    /// `@n` label or the 1-based position. Ignored when `zone` is given.
+    #[serde(default)]
+    pub line: Option<String>,
+    pub caption: String,
+}
+
+impl Tour {
+    /// Index of the stop `step` positions from `current`; with no current
+    /// stop, any step forward starts the tour. `None` past either end.
+    pub fn step(&self, current: Option<usize>, step: i32) -> Option<usize> {
+        let target = match current {
+            Some(index) => index.checked_add_signed(step as isize)?,
+            None if step > 0 => 0,
+            None => return None,
+        };
+        (target < self.stops.len()).then_some(target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tour() {
+        let json = r#"{
+            "title": "Lo esencial",
+            "stops": [
+                { "page": 1, "caption": "Inicio" },
+                { "page": 2, "zone": "z12", "caption": "Una corrección" },
+                { "page": 2, "line": "14", "caption": "Nombre divino" }
+            ]
+        }"#;
+        let tour: Tour = serde_json::from_str(json).unwrap();
+        assert_eq!(tour.title.as_deref(), Some("Lo esencial"));
+        assert_eq!(tour.stops.len(), 3);
+        assert_eq!(tour.stops[0].zone, None);
+        assert_eq!(tour.stops[1].zone.as_deref(), Some("z12"));
+        assert_eq!(tour.stops[2].line.as_deref(), Some("14"));
+    }
+
+    #[test]
+    fn test_step() {
+        let stop = |page| TourStop {
+            page,
+            zone: None,
+            line: None,
+            caption: String::new(),
+        };
+        let tour = Tour {
+            title: None,
+            stops: vec![stop(1), stop(2)],
+        };
+        assert_eq!(tour.step(None, 1), Some(0));
+        assert_eq!(tour.step(None, -1), None);
+        assert_eq!(tour.step(Some(0), 1), Some(1));
+        assert_eq!(tour.step(Some(1), 1), None);
+        assert_eq!(tour.step(Some(1), -1), Some(0));
+        assert_eq!(tour.step(Some(0), -1), None);
+    }
+}
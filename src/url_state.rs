@@ -0,0 +1,200 @@
+// src/url_state.rs
+// The exact view of a page (text view, locked zone, zoom and pan) encoded
+// in the URL hash, e.g. `#view=both&zone=z12&zoom=1.50&x=-120&y=-40`, so
+// any view can be bookmarked and shared. The project and page are in the
+// path (see `route`); links from before routing carry them in the hash
+// too (`#project=PGM-XIII&page=3&...`).
+use crate::utils::window;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UrlState {
+    pub project: Option<String>,
+    pub page: Option<u32>,
+    pub view: Option<String>,
+    pub zone: Option<String>,
+    pub zoom: Option<f32>,
+    pub offset_x: Option<f32>,
+    pub offset_y: Option<f32>,
+}
+
+impl UrlState {
+    /// Read a location hash (with or without the leading `#`). Unknown
+    /// keys and unparsable values are ignored.
+    pub fn parse(hash: &str) -> Self {
+        let mut state = Self::default();
+        for pair in hash.trim_start_matches('#').split('&') {
+            let Some((key, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let value = decode(value);
+            if value.is_empty() {
+                continue;
+            }
+            match key {
+                "project" => state.project = Some(value),
+                "page" => state.page = value.parse().ok(),
+                "view" => state.view = Some(value),
+                "zone" => state.zone = Some(value),
+                "zoom" => state.zoom = value.parse().ok(),
+                "x" => state.offset_x = value.parse().ok(),
+                "y" => state.offset_y = value.parse().ok(),
+                _ => {}
+            }
+        }
+        state
+    }
+
+    /// Hash for the state, including the leading `#`.
+    pub fn to_hash(&self) -> String {
+        let mut pairs = Vec::new();
+        if let Some(project) = &self.project {
+            pairs.push(format!("project={}", encode(project)));
+        }
+        if let Some(page) = self.page {
+            pairs.push(format!("page={}", page));
+        }
+        if let Some(view) = &self.view {
+            pairs.push(format!("view={}", encode(view)));
+        }
+        if let Some(zone) = &self.zone {
+            pairs.push(format!("zone={}", encode(zone)));
+        }
+        if let Some(zoom) = self.zoom {
+            pairs.push(format!("zoom={:.2}", zoom));
+        }
+        if let Some(x) = self.offset_x {
+            pairs.push(format!("x={:.0}", x));
+        }
+        if let Some(y) = self.offset_y {
+            pairs.push(format!("y={:.0}", y));
+        }
+        format!("#{}", pairs.join("&"))
+    }
+}
+
+/// State encoded in the current page URL.
+pub fn current() -> UrlState {
+    window()
+        .and_then(|w| w.location().hash().ok())
+        .map(|hash| UrlState::parse(&hash))
+        .unwrap_or_default()
+}
+
+/// Rewrite the URL hash in place, without adding a history entry.
+pub fn replace(state: &UrlState) {
+    let Some(window) = window() else {
+        return;
+    };
+    if let Ok(history) = window.history() {
+        let _ = history.replace_state_with_url(
+            &wasm_bindgen::JsValue::NULL,
+            "",
+            Some(&state.to_hash()),
+        );
+    }
+}
+
+/// Set the URL hash as a new history entry, so back returns to the
+/// previous view.
+pub fn push(state: &UrlState) {
+    let Some(window) = window() else {
+        return;
+    };
+    if let Ok(history) = window.history() {
+        let _ =
+            history.push_state_with_url(&wasm_bindgen::JsValue::NULL, "", Some(&state.to_hash()));
+    }
+}
+
+/// Decoded value of `key` in a query string (with or without the `?`).
+pub fn query_param(search: &str, key: &str) -> Option<String> {
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| decode(value))
+}
+
+/// Percent-encode everything but unreserved characters.
+pub fn encode(value: &str) -> String {
+    let mut out = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+/// Undo `encode` (and `+` for spaces).
+pub fn decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let state = UrlState {
+            project: Some("PGM XIII".to_string()),
+            page: Some(3),
+            view: Some("both".to_string()),
+            zone: Some("z12".to_string()),
+            zoom: Some(1.5),
+            offset_x: Some(-120.0),
+            offset_y: Some(40.0),
+        };
+        let hash = state.to_hash();
+        assert_eq!(
+            hash,
+            "#project=PGM%20XIII&page=3&view=both&zone=z12&zoom=1.50&x=-120&y=40"
+        );
+        assert_eq!(UrlState::parse(&hash), state);
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let state = UrlState::parse("page=x&zoom=2&zone=&other=1&project=A");
+        assert_eq!(state.project.as_deref(), Some("A"));
+        assert_eq!(state.page, None);
+        assert_eq!(state.zone, None);
+        assert_eq!(state.zoom, Some(2.0));
+        assert_eq!(UrlState::parse(""), UrlState::default());
+    }
+
+    #[test]
+    fn test_query_param() {
+        let search = "?embed=1&project=PGM%20XIII&page=3";
+        assert_eq!(query_param(search, "project").as_deref(), Some("PGM XIII"));
+        assert_eq!(query_param(search, "embed").as_deref(), Some("1"));
+        assert_eq!(query_param(search, "view"), None);
+    }
+}
@@ -1,19 +1,57 @@
 // src/utils.rs
-use web_sys::window;
+use crate::project_config::Fit;
+use crate::url_state::encode;
+use std::fmt;
+use wasm_bindgen::JsCast;
 
-/// Get the base URL for the application
-/// This handles both local development and GitHub Pages deployment
+/// The browser window, `None` off wasm: `web_sys::window()` panics there,
+/// and the helpers that read the page run under `cargo test` too.
+pub fn window() -> Option<web_sys::Window> {
+    if cfg!(target_arch = "wasm32") {
+        web_sys::window()
+    } else {
+        None
+    }
+}
+
+/// `public_url` the app was built with: `trunk build --public-url` does not
+/// reach the compiler, so deployments under a path set it in the
+/// environment, which Trunk reads too
+const PUBLIC_URL: Option<&str> = option_env!("TRUNK_BUILD_PUBLIC_URL");
+
+/// Get the base URL for the application (e.g. "/tei-viewer" on GitHub
+/// Pages, empty at the root of a host): the document's `<base href>` if it
+/// has one, else the `public_url` it was built with.
 pub fn get_base_url() -> String {
-    if let Some(window) = window() {
-        if let Some(location) = window.location().pathname().ok() {
-            // Check if we're on GitHub Pages (path starts with /tei-viewer/)
-            if location.starts_with("/tei-viewer/") {
-                return "/tei-viewer".to_string();
-            }
-        }
+    let base_href = window()
+        .and_then(|w| w.document())
+        .filter(|d| d.query_selector("base[href]").ok().flatten().is_some())
+        .and_then(|d| d.base_uri().ok().flatten());
+    base_path(base_href.as_deref(), PUBLIC_URL)
+}
+
+/// Path of the base (`base_href`, absolute as the browser resolves it, or
+/// the `public_url`), without the trailing '/'; empty at the root or for a
+/// relative public URL.
+fn base_path(base_href: Option<&str>, public_url: Option<&str>) -> String {
+    let base = base_href.or(public_url).unwrap_or_default().trim();
+    let path = match base.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("", |i| &rest[i..]),
+        None if base.starts_with('/') => base,
+        None => "",
+    };
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    // A `<base href>` may name a document of the folder
+    let path = match path.rsplit_once('/') {
+        Some((folder, file)) if file.contains('.') => folder,
+        _ => path,
+    };
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        String::new()
+    } else {
+        format!("/{}", path)
     }
-    // Local development - no base path needed
-    String::new()
 }
 
 /// Build a resource URL with the correct base path
@@ -28,10 +66,242 @@ pub fn resource_url(path: &str) -> String {
     }
 }
 
+/// Absolute form of a site-relative URL, for data that leaves the app.
+pub fn absolute_url(url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return url.to_string();
+    }
+    let origin = window()
+        .and_then(|w| w.location().origin().ok())
+        .unwrap_or_default();
+    format!("{}{}", origin, url)
+}
+
+/// Make the browser download `url` (e.g. an object URL) as `filename`.
+pub fn download(url: &str, filename: &str) {
+    let Some(document) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    let Ok(link) = document.create_element("a") else {
+        return;
+    };
+    let _ = link.set_attribute("href", url);
+    let _ = link.set_attribute("download", filename);
+    if let Ok(link) = link.dyn_into::<web_sys::HtmlElement>() {
+        link.click();
+    }
+}
+
+/// Lowercase ASCII words of `text` joined by '-', at most `max` characters,
+/// for ids made from titles (e.g. "ms-12-magical-papyrus").
+pub fn slug(text: &str, max: usize) -> String {
+    let mut slug = String::new();
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug: String = slug.chars().take(max).collect();
+    slug.trim_end_matches('-').to_string()
+}
+
+/// `url` with a cache-busting `v` parameter, so an edited file is fetched
+/// again. `data:` URLs are their own content and are left as they are.
+pub fn cache_busted(url: &str, version: &str) -> String {
+    if url.starts_with("data:") {
+        url.to_string()
+    } else {
+        Url::new(url).param("v", version).to_string()
+    }
+}
+
+/// A URL made from a base, which may already have a query and a fragment,
+/// with path segments and query parameters added in their place. Values are
+/// percent-encoded, and a parameter set again replaces the earlier one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Url {
+    path: String,
+    /// `key=value` pairs, encoded
+    query: Vec<String>,
+    fragment: String,
+}
+
+impl Url {
+    pub fn new(base: &str) -> Self {
+        let (base, fragment) = base.split_once('#').unwrap_or((base, ""));
+        let (path, query) = base.split_once('?').unwrap_or((base, ""));
+        Url {
+            path: path.to_string(),
+            query: query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(str::to_string)
+                .collect(),
+            fragment: fragment.to_string(),
+        }
+    }
+
+    /// The path followed by `segment`, as given (IIIF regions and sizes
+    /// keep their commas)
+    pub fn segment(mut self, segment: impl fmt::Display) -> Self {
+        self.path = format!("{}/{}", self.path.trim_end_matches('/'), segment);
+        self
+    }
+
+    /// `key` set to `value`
+    pub fn param(self, key: &str, value: &str) -> Self {
+        let mut url = self.without(key);
+        url.query.push(format!("{}={}", encode(key), encode(value)));
+        url
+    }
+
+    /// `key` left out of the query
+    pub fn without(mut self, key: &str) -> Self {
+        let key = encode(key);
+        self.query
+            .retain(|pair| pair.split('=').next() != Some(key.as_str()));
+        self
+    }
+
+    /// The fragment, already encoded (as `UrlState::to_hash` gives it)
+    pub fn fragment(mut self, fragment: &str) -> Self {
+        self.fragment = fragment.trim_start_matches('#').to_string();
+        self
+    }
+}
+
+impl fmt::Display for Url {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path)?;
+        if !self.query.is_empty() {
+            write!(f, "?{}", self.query.join("&"))?;
+        }
+        if !self.fragment.is_empty() {
+            write!(f, "#{}", self.fragment)?;
+        }
+        Ok(())
+    }
+}
+
+/// Half-open range of rows to render for a scrolled list of `total` rows of
+/// roughly `row_height` pixels, with `overscan` extra rows on each side.
+pub fn visible_range(
+    total: usize,
+    scroll_top: f64,
+    viewport_h: f64,
+    row_height: f64,
+    overscan: usize,
+) -> (usize, usize) {
+    if total == 0 || row_height <= 0.0 {
+        return (0, total);
+    }
+    let first = (scroll_top.max(0.0) / row_height).floor() as usize;
+    let count = (viewport_h.max(0.0) / row_height).ceil() as usize + 1;
+    let start = first.saturating_sub(overscan).min(total);
+    let end = (first + count + overscan).min(total);
+    (start, end.max(start))
+}
+
+/// Pan/zoom transform `(scale, offset_x, offset_y)` that centres the
+/// rectangle `(x, y, w, h)` (layout pixels) in a `view_w` x `view_h`
+/// viewport, filling `fill` (0..1) of it, within the viewer's zoom limits.
+pub fn fit_rect(
+    rect: (f32, f32, f32, f32),
+    view_w: f32,
+    view_h: f32,
+    fill: f32,
+) -> (f32, f32, f32) {
+    let (x, y, w, h) = rect;
+    let (w, h) = (w.max(1.0), h.max(1.0));
+    let scale = ((view_w / w).min(view_h / h) * fill).clamp(0.2, 8.0);
+    let offset_x = view_w / 2.0 - (x + w / 2.0) * scale;
+    let offset_y = view_h / 2.0 - (y + h / 2.0) * scale;
+    (scale, offset_x, offset_y)
+}
+
+/// Pan/zoom transform `(scale, offset_x, offset_y)` fitting a `w` x `h`
+/// image to a `view_w` x `view_h` viewport: centred and whole for
+/// `Fit::Page`, across the full width and from its top for `Fit::Width`.
+pub fn fit_image(fit: Fit, w: f32, h: f32, view_w: f32, view_h: f32) -> (f32, f32, f32) {
+    match fit {
+        Fit::Page => fit_rect((0.0, 0.0, w, h), view_w, view_h, 1.0),
+        Fit::Width => {
+            let scale = (view_w / w.max(1.0)).clamp(0.2, 8.0);
+            (scale, (view_w - w * scale) / 2.0, 0.0)
+        }
+    }
+}
+
+/// Pixels per centimetre in the facsimile's declared pixel space: from a
+/// manifest-declared scan DPI, or else by spreading the object's physical
+/// width (msDesc) over the image width, which assumes a tightly cropped scan.
+pub fn pixels_per_cm(dpi: Option<f64>, object_width_cm: Option<f64>, image_w: u32) -> Option<f64> {
+    match (dpi, object_width_cm) {
+        (Some(dpi), _) if dpi > 0.0 => Some(dpi / 2.54),
+        (_, Some(width)) if width > 0.0 && image_w > 0 => Some(image_w as f64 / width),
+        _ => None,
+    }
+}
+
+/// Length in centimetres of the facsimile scale bar: 1 cm, unless that
+/// would be shorter than `min_px` on screen, in which case 5 cm.
+pub fn scale_bar_cm(screen_px_per_cm: f64, min_px: f64) -> f64 {
+    if screen_px_per_cm >= min_px {
+        1.0
+    } else {
+        5.0
+    }
+}
+
+/// Classify a finished touch gesture by its displacement. Returns `1` for a
+/// swipe to the left (advance), `-1` for a swipe to the right (go back) and
+/// `0` when the gesture was too short or mostly vertical (i.e. a scroll).
+pub fn swipe_step(dx: i32, dy: i32, min_distance: i32) -> i32 {
+    if dx.abs() < min_distance || dx.abs() < dy.abs() * 2 {
+        return 0;
+    }
+    if dx < 0 {
+        1
+    } else {
+        -1
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_base_path() {
+        // The document's <base href>, as the browser resolves it
+        assert_eq!(
+            base_path(
+                Some("https://user.github.io/fork-name/"),
+                Some("/tei-viewer/")
+            ),
+            "/fork-name"
+        );
+        assert_eq!(
+            base_path(Some("https://x.org/a/b/index.html?q=1"), None),
+            "/a/b"
+        );
+        assert_eq!(base_path(Some("http://localhost:8080/"), None), "");
+        // Else the public URL of the build
+        assert_eq!(base_path(None, Some("/tei-viewer/")), "/tei-viewer");
+        assert_eq!(base_path(None, Some("/")), "");
+        assert_eq!(base_path(None, Some("./")), "");
+        assert_eq!(base_path(None, None), "");
+    }
+
+    #[test]
+    fn test_scale_bar_cm() {
+        assert_eq!(scale_bar_cm(120.0, 40.0), 1.0);
+        assert_eq!(scale_bar_cm(40.0, 40.0), 1.0);
+        assert_eq!(scale_bar_cm(12.0, 40.0), 5.0);
+    }
+
     #[test]
     fn test_resource_url_formatting() {
         // Note: These tests won't actually detect the window location
@@ -45,4 +315,107 @@ mod tests {
         let url2 = resource_url("public/projects/test.xml");
         assert!(url2.contains("public/projects/test.xml"));
     }
+
+    #[test]
+    fn test_visible_range() {
+        // Top of the list: no rows above, viewport plus overscan below
+        assert_eq!(visible_range(500, 0.0, 480.0, 48.0, 5), (0, 16));
+        // Scrolled to row 100
+        assert_eq!(visible_range(500, 4800.0, 480.0, 48.0, 5), (95, 116));
+        // Past the end clamps to the list
+        assert_eq!(visible_range(500, 1.0e6, 480.0, 48.0, 5), (500, 500));
+        assert_eq!(visible_range(0, 0.0, 480.0, 48.0, 5), (0, 0));
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Ms. 12: Magical Papyrus", 40), "ms-12-magical-papyrus");
+        assert_eq!(
+            slug("urn:cts:greekLit:tlg0012.tlg001", 40),
+            "urn-cts-greeklit-tlg0012-tlg001"
+        );
+        assert_eq!(slug("Ἰλιάς", 40), "");
+        assert_eq!(slug("abc def", 4), "abc");
+    }
+
+    #[test]
+    fn test_cache_busted() {
+        assert_eq!(cache_busted("/p1_dip.xml", "7"), "/p1_dip.xml?v=7");
+        assert_eq!(
+            cache_busted("https://x.org/tei?page=1", "7"),
+            "https://x.org/tei?page=1&v=7"
+        );
+        assert_eq!(cache_busted("data:,<TEI/>", "7"), "data:,<TEI/>");
+        // A version already there is replaced
+        assert_eq!(
+            cache_busted("/p1_dip.xml?v=6#top", "7"),
+            "/p1_dip.xml?v=7#top"
+        );
+    }
+
+    #[test]
+    fn test_url() {
+        assert_eq!(
+            Url::new("https://x.org/search?q=a%20b")
+                .param("lang", "grc & la")
+                .param("q", "ἄγγελος")
+                .to_string(),
+            "https://x.org/search?lang=grc%20%26%20la&q=%E1%BC%84%CE%B3%CE%B3%CE%B5%CE%BB%CE%BF%CF%82"
+        );
+        assert_eq!(
+            Url::new("https://iiif.example.org/iiif/p1/?token=t")
+                .segment("0,0,512,512")
+                .segment("256,")
+                .to_string(),
+            "https://iiif.example.org/iiif/p1/0,0,512,512/256,?token=t"
+        );
+        assert_eq!(
+            Url::new("/P/1?embed=1&gh=a%2Fb#zoom=2.00")
+                .without("embed")
+                .fragment("#view=text")
+                .to_string(),
+            "/P/1?gh=a%2Fb#view=text"
+        );
+    }
+
+    #[test]
+    fn test_swipe_step() {
+        assert_eq!(swipe_step(-120, 10, 60), 1);
+        assert_eq!(swipe_step(120, -10, 60), -1);
+        // Too short
+        assert_eq!(swipe_step(-40, 0, 60), 0);
+        // Diagonal drags are treated as scrolling
+        assert_eq!(swipe_step(-120, 80, 60), 0);
+    }
+
+    #[test]
+    fn test_fit_rect() {
+        // A 100x50 line in a 400x400 viewport: limited by width
+        let (scale, ox, oy) = fit_rect((100.0, 200.0, 100.0, 50.0), 400.0, 400.0, 0.8);
+        assert!((scale - 3.2).abs() < 1e-4);
+        assert!((ox - (200.0 - 150.0 * 3.2)).abs() < 1e-3);
+        assert!((oy - (200.0 - 225.0 * 3.2)).abs() < 1e-3);
+        // Tiny zones are capped at the maximum zoom
+        assert_eq!(fit_rect((0.0, 0.0, 1.0, 1.0), 400.0, 400.0, 0.8).0, 8.0);
+    }
+
+    #[test]
+    fn test_fit_image() {
+        // A 1000x2000 page in a 500x500 panel
+        assert_eq!(
+            fit_image(Fit::Width, 1000.0, 2000.0, 500.0, 500.0),
+            (0.5, 0.0, 0.0)
+        );
+        let (scale, ox, oy) = fit_image(Fit::Page, 1000.0, 2000.0, 500.0, 500.0);
+        assert_eq!(scale, 0.25);
+        assert_eq!((ox, oy), (125.0, 0.0));
+    }
+
+    #[test]
+    fn test_pixels_per_cm() {
+        assert_eq!(pixels_per_cm(Some(254.0), Some(10.0), 1000), Some(100.0));
+        assert_eq!(pixels_per_cm(None, Some(10.0), 1000), Some(100.0));
+        assert_eq!(pixels_per_cm(None, Some(10.0), 0), None);
+        assert_eq!(pixels_per_cm(None, None, 1000), None);
+    }
 }
@@ -0,0 +1,187 @@
+// src/web_annotation.rs
+// Export of reader notes to the W3C Web Annotation data model (JSON-LD), so
+// they can be loaded into Recogito, Mirador or an annotation server. Each
+// note targets its TEI line (by the line's facsimile reference) and, when
+// known, the zone on the page image, whose credit is a describing body.
+use crate::annotations::{Annotation, AnnotationSet};
+use crate::project_config::ProjectConfig;
+use crate::utils::absolute_url;
+use serde_json::{json, Value};
+
+/// AnnotationCollection with every note of the set, plus an optional
+/// highlight of the current selection, on the files of `project`.
+pub fn collection(
+    set: &AnnotationSet,
+    project: &ProjectConfig,
+    selection: Option<&Annotation>,
+) -> Value {
+    let items: Vec<Value> = set
+        .notes
+        .iter()
+        .map(|note| annotation(project, note, "commenting"))
+        .chain(selection.map(|s| annotation(project, s, "highlighting")))
+        .collect();
+    json!({
+        "@context": "http://www.w3.org/ns/anno.jsonld",
+        "type": "AnnotationCollection",
+        "label": format!("Notas de lectura · {}", set.project),
+        "total": items.len(),
+        "first": {
+            "type": "AnnotationPage",
+            "items": items,
+        },
+    })
+}
+
+fn annotation(project: &ProjectConfig, note: &Annotation, motivation: &str) -> Value {
+    let mut body: Vec<Value> = if note.text.is_empty() {
+        Vec::new()
+    } else {
+        vec![json!({
+            "type": "TextualBody",
+            "value": note.text,
+            "format": "text/plain",
+            "purpose": "commenting",
+        })]
+    };
+
+    let mut line_selector = json!({
+        "type": "XPathSelector",
+        "value": format!("//lb[@facs='#{}']", note.zone),
+    });
+    if let Some(quote) = &note.quote {
+        line_selector["refinedBy"] = json!({ "type": "TextQuoteSelector", "exact": quote });
+    }
+    let mut targets = vec![json!({
+        "source": absolute_url(&project.file_url(&ProjectConfig::page_file(note.page, "dip"))),
+        "format": "application/tei+xml",
+        "selector": line_selector,
+    })];
+    if let (Some([x, y, w, h]), Some(image)) = (note.region, &note.image) {
+        targets.push(json!({
+            "source": image,
+            "type": "Image",
+            "selector": {
+                "type": "FragmentSelector",
+                "conformsTo": "http://www.w3.org/TR/media-frags/",
+                "value": format!("xywh={},{},{},{}", x, y, w, h),
+            },
+        }));
+        // The credit its holder asks for when the image is shown
+        if let Some(attribution) = project.image_attribution(note.page) {
+            body.push(json!({
+                "type": "TextualBody",
+                "value": attribution,
+                "format": "text/plain",
+                "purpose": "describing",
+            }));
+        }
+    }
+
+    let mut value = json!({
+        "id": format!("urn:tei-viewer:{}:{}", project.id, note.id),
+        "type": "Annotation",
+        "motivation": motivation,
+        "created": iso8601(note.created),
+        "target": targets,
+    });
+    if !body.is_empty() {
+        value["body"] = Value::Array(body);
+    }
+    value
+}
+
+/// UTC timestamp in ISO 8601 from milliseconds since the epoch.
+fn iso8601(ms: f64) -> String {
+    let secs = (ms / 1000.0).floor() as i64;
+    let (days, rem) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+    // Civil date from days since 1970-01-01 (H. Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_iso8601() {
+        assert_eq!(iso8601(0.0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601(1_700_000_000_000.0), "2023-11-14T22:13:20Z");
+        assert_eq!(iso8601(951_782_400_000.0), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_collection() {
+        let mut set = AnnotationSet::new("PGM");
+        set.upsert(Annotation {
+            id: "n1".to_string(),
+            page: 2,
+            line: 4,
+            zone: "z5".to_string(),
+            quote: Some("θεοῦ".to_string()),
+            color: "#f7d154".to_string(),
+            text: "Abreviatura dudosa".to_string(),
+            created: 0.0,
+            region: Some([10, 20, 300, 40]),
+            image: Some("https://example.org/p2.jpg".to_string()),
+        });
+        let mut project = ProjectConfig::new("PGM".to_string(), "PGM".to_string());
+        project.folder = Some("https://example.org/projects/PGM/".to_string());
+        project.image_rights.rights_holder = Some("Museo".to_string());
+        let value = collection(&set, &project, None);
+        assert_eq!(value["total"], 1);
+        let note = &value["first"]["items"][0];
+        assert_eq!(note["id"], "urn:tei-viewer:PGM:n1");
+        assert_eq!(note["body"][0]["value"], "Abreviatura dudosa");
+        assert_eq!(
+            note["target"][0]["source"],
+            "https://example.org/projects/PGM/p2_dip.xml"
+        );
+        assert_eq!(note["target"][0]["selector"]["value"], "//lb[@facs='#z5']");
+        assert_eq!(note["target"][0]["selector"]["refinedBy"]["exact"], "θεοῦ");
+        assert_eq!(note["target"][1]["selector"]["value"], "xywh=10,20,300,40");
+        assert_eq!(note["body"][1]["value"], "© Museo");
+        assert_eq!(note["body"][1]["purpose"], "describing");
+    }
+
+    #[test]
+    fn test_selection_without_body() {
+        let set = AnnotationSet::new("PGM");
+        let selection = Annotation {
+            id: "selection".to_string(),
+            page: 1,
+            line: 0,
+            zone: "z1".to_string(),
+            quote: None,
+            color: String::new(),
+            text: String::new(),
+            created: 0.0,
+            region: None,
+            image: None,
+        };
+        let mut project = ProjectConfig::new("PGM".to_string(), "PGM".to_string());
+        project.folder = Some("https://example.org/".to_string());
+        let value = collection(&set, &project, Some(&selection));
+        let item = &value["first"]["items"][0];
+        assert_eq!(item["motivation"], "highlighting");
+        assert!(item.get("body").is_none());
+        assert_eq!(item["target"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,95 @@
+// src/window_sync.rs
+// Several windows of the viewer kept on the same page and line, e.g. the
+// facsimile full-screen on one monitor and the text popped out on another:
+// each window announces where it is on a BroadcastChannel and follows what
+// the others announce.
+use crate::embed;
+use crate::url_state::UrlState;
+use crate::utils::{window, Url};
+use gloo_events::EventListener;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use web_sys::{BroadcastChannel, MessageEvent};
+use yew::Callback;
+
+const CHANNEL: &str = "tei-viewer-sync";
+
+/// Name of the window the text is popped out into, so that popping it out
+/// again reuses it
+pub const POPOUT_TARGET: &str = "tei-viewer-text";
+
+/// Where a window of the viewer is
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub project: String,
+    pub page: u32,
+    /// Locked line, by its facsimile zone
+    pub zone: Option<String>,
+}
+
+pub struct WindowSync {
+    channel: BroadcastChannel,
+    _listener: EventListener,
+}
+
+impl WindowSync {
+    /// Join the windows of the viewer open in this browser; `None` where
+    /// BroadcastChannel is not available.
+    pub fn open(on_position: Callback<Position>) -> Option<Self> {
+        // No channel outside the browser
+        window()?;
+        let channel = BroadcastChannel::new(CHANNEL).ok()?;
+        let listener = EventListener::new(&channel, "message", move |e| {
+            let position = e
+                .dyn_ref::<MessageEvent>()
+                .and_then(|e| e.data().as_string())
+                .and_then(|data| serde_json::from_str(&data).ok());
+            if let Some(position) = position {
+                on_position.emit(position);
+            }
+        });
+        Some(Self {
+            channel,
+            _listener: listener,
+        })
+    }
+
+    /// Tell the other windows where this one is.
+    pub fn post(&self, position: &Position) {
+        if let Ok(data) = serde_json::to_string(position) {
+            let _ = self.channel.post_message(&data.into());
+        }
+    }
+}
+
+/// Address of the text alone (see `embed`) for the page at `url`, opened on
+/// the view and line of `state`.
+pub fn popout_url(url: &str, state: &UrlState) -> String {
+    Url::new(url)
+        .param(embed::PARAM, "1")
+        .param("view", "text")
+        .fragment(&state.to_hash())
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_popout_url() {
+        let state = UrlState {
+            view: Some("translation".to_string()),
+            zone: Some("z3".to_string()),
+            ..UrlState::default()
+        };
+        assert_eq!(
+            popout_url("https://x.org/PGM-XIII/3#view=both&zoom=2.00", &state),
+            "https://x.org/PGM-XIII/3?embed=1&view=text#view=translation&zone=z3"
+        );
+        assert_eq!(
+            popout_url("https://x.org/P/1?gh=a%2Fb&embed=1&view=image", &state),
+            "https://x.org/P/1?gh=a%2Fb&embed=1&view=text#view=translation&zone=z3"
+        );
+    }
+}
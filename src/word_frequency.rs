@@ -0,0 +1,104 @@
+// src/word_frequency.rs
+// Word frequencies of the Greek text: a tokenizer for polytonic Greek and
+// counts grouped by the search key of `polytonic::fold`, so spellings
+// differing only in accents, breathings or case count as one word.
+use crate::polytonic;
+use std::collections::HashMap;
+
+/// A word and the number of times it occurs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordCount {
+    /// Most frequent spelling of the word
+    pub word: String,
+    /// Folded form all its spellings share
+    pub key: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    /// Most frequent first
+    Count,
+    /// Alphabetical, ignoring accents
+    Word,
+}
+
+fn is_greek(c: char) -> bool {
+    matches!(c, '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}')
+}
+
+/// Greek words of `text`: runs of letters (and the combining marks on
+/// them) containing at least one Greek letter. Numbers, punctuation and
+/// editorial sigla split words.
+pub fn tokens(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !(c.is_alphabetic() || is_combining(c)))
+        .filter(|t| t.chars().any(is_greek))
+}
+
+fn is_combining(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}')
+}
+
+/// Count the words of `texts`, most frequent first (ties alphabetical).
+pub fn count<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<WordCount> {
+    // folded key -> (total, count of each spelling)
+    let mut words: HashMap<String, (usize, HashMap<&'a str, usize>)> = HashMap::new();
+    for text in texts {
+        for token in tokens(text) {
+            let entry = words.entry(polytonic::fold(token)).or_default();
+            entry.0 += 1;
+            *entry.1.entry(token).or_default() += 1;
+        }
+    }
+    let mut counts: Vec<WordCount> = words
+        .into_iter()
+        .map(|(key, (count, spellings))| {
+            let word = spellings
+                .into_iter()
+                .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(w, _)| w.to_string())
+                .unwrap_or_default();
+            WordCount { word, key, count }
+        })
+        .collect();
+    sort(&mut counts, SortBy::Count);
+    counts
+}
+
+pub fn sort(words: &mut [WordCount], by: SortBy) {
+    match by {
+        SortBy::Count => {
+            words.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)))
+        }
+        SortBy::Word => words.sort_by(|a, b| a.key.cmp(&b.key)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens() {
+        let words: Vec<_> = tokens("καὶ [τὸν] θεόν· 12 ΙΑΩ, abc ἦν-ἐν").collect();
+        assert_eq!(words, ["καὶ", "τὸν", "θεόν", "ΙΑΩ", "ἦν", "ἐν"]);
+        // Decomposed accents stay part of the word
+        let words: Vec<_> = tokens("λο\u{301}γος").collect();
+        assert_eq!(words, ["λο\u{301}γος"]);
+    }
+
+    #[test]
+    fn test_count_groups_spellings() {
+        let counts = count(["Θεὸς καὶ θεὸς", "καὶ θεὸς", "ἄλλος"]);
+        assert_eq!(counts[0].key, "θεοσ");
+        assert_eq!(counts[0].count, 3);
+        assert_eq!(counts[0].word, "θεὸς");
+        assert_eq!(counts[1].word, "καὶ");
+        assert_eq!(counts[1].count, 2);
+
+        let mut counts = counts;
+        sort(&mut counts, SortBy::Word);
+        let words: Vec<_> = counts.iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, ["ἄλλος", "θεὸς", "καὶ"]);
+    }
+}
@@ -0,0 +1,334 @@
+// src/zone_editor.rs
+// Editing state for the in-browser zone editor: the page's facsimile zones
+// as editable polygons (in the facsimile's declared pixel space), their
+// links to diplomatic lines, and serialization back to a TEI `<facsimile>`.
+use crate::tei_data::{Facsimile, Line};
+use quick_xml::escape::escape;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditZone {
+    pub id: String,
+    pub points: Vec<(u32, u32)>,
+    /// Index of the diplomatic line whose `<lb facs>` points at this zone
+    pub line: Option<usize>,
+}
+
+/// What the pointer is holding while dragging in the editor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grab {
+    Vertex(usize, usize),
+    /// A whole zone, with the last pointer position
+    Zone(usize, (u32, u32)),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneEditor {
+    pub surface_id: String,
+    pub image_url: String,
+    pub width: u32,
+    pub height: u32,
+    pub zones: Vec<EditZone>,
+    /// Zone receiving new vertices on click
+    pub selected: Option<usize>,
+    pub grab: Option<Grab>,
+}
+
+impl ZoneEditor {
+    /// Start editing the zones of a page, in line order; zones no line
+    /// points at follow, sorted by id.
+    pub fn new(facsimile: &Facsimile, lines: &[Line]) -> Self {
+        let mut zones: Vec<EditZone> = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let zone = facsimile.zones.get(&line.facs)?;
+                Some(EditZone {
+                    id: zone.id.clone(),
+                    points: zone.points.clone(),
+                    line: Some(index),
+                })
+            })
+            .collect();
+        let mut unlinked: Vec<EditZone> = facsimile
+            .zones
+            .values()
+            .filter(|z| !zones.iter().any(|e| e.id == z.id))
+            .map(|z| EditZone {
+                id: z.id.clone(),
+                points: z.points.clone(),
+                line: None,
+            })
+            .collect();
+        unlinked.sort_by(|a, b| a.id.cmp(&b.id));
+        zones.extend(unlinked);
+        Self {
+            surface_id: facsimile.surface_id.clone(),
+            image_url: facsimile.image_url.clone(),
+            width: facsimile.width,
+            height: facsimile.height,
+            zones,
+            selected: None,
+            grab: None,
+        }
+    }
+
+    /// Add an empty zone with a fresh `z{n}` id and select it.
+    pub fn add_zone(&mut self) -> usize {
+        let id = (1..)
+            .map(|n| format!("z{}", n))
+            .find(|id| !self.zones.iter().any(|z| &z.id == id))
+            .unwrap_or_default();
+        self.zones.push(EditZone {
+            id,
+            points: Vec::new(),
+            line: None,
+        });
+        let index = self.zones.len() - 1;
+        self.selected = Some(index);
+        index
+    }
+
+    pub fn remove_zone(&mut self, index: usize) {
+        if index >= self.zones.len() {
+            return;
+        }
+        self.zones.remove(index);
+        self.grab = None;
+        self.selected = match self.selected {
+            Some(s) if s == index => None,
+            Some(s) if s > index => Some(s - 1),
+            other => other,
+        };
+    }
+
+    /// Link a zone to a line, unlinking any other zone from that line.
+    pub fn link_line(&mut self, index: usize, line: Option<usize>) {
+        if line.is_some() {
+            for zone in self.zones.iter_mut().filter(|z| z.line == line) {
+                zone.line = None;
+            }
+        }
+        if let Some(zone) = self.zones.get_mut(index) {
+            zone.line = line;
+        }
+    }
+
+    /// Append a vertex to the selected zone.
+    pub fn add_point(&mut self, point: (u32, u32)) -> bool {
+        let point = self.clamp(point);
+        match self.selected.and_then(|s| self.zones.get_mut(s)) {
+            Some(zone) => {
+                zone.points.push(point);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove_point(&mut self, index: usize, vertex: usize) {
+        if let Some(zone) = self.zones.get_mut(index) {
+            if vertex < zone.points.len() {
+                zone.points.remove(vertex);
+            }
+        }
+    }
+
+    /// Move whatever is grabbed to follow the pointer.
+    pub fn drag_to(&mut self, point: (u32, u32)) -> bool {
+        let point = self.clamp(point);
+        match self.grab {
+            Some(Grab::Vertex(index, vertex)) => {
+                match self
+                    .zones
+                    .get_mut(index)
+                    .and_then(|z| z.points.get_mut(vertex))
+                {
+                    Some(p) => {
+                        *p = point;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Some(Grab::Zone(index, last)) => {
+                let dx = point.0 as i64 - last.0 as i64;
+                let dy = point.1 as i64 - last.1 as i64;
+                let (max_x, max_y) = self.max_point();
+                let Some(zone) = self.zones.get_mut(index) else {
+                    return false;
+                };
+                for p in zone.points.iter_mut() {
+                    p.0 = (p.0 as i64 + dx).clamp(0, max_x as i64) as u32;
+                    p.1 = (p.1 as i64 + dy).clamp(0, max_y as i64) as u32;
+                }
+                self.grab = Some(Grab::Zone(index, point));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn max_point(&self) -> (u32, u32) {
+        let bound = |v: u32| if v > 0 { v } else { u32::MAX };
+        (bound(self.width), bound(self.height))
+    }
+
+    fn clamp(&self, (x, y): (u32, u32)) -> (u32, u32) {
+        let (max_x, max_y) = self.max_point();
+        (x.min(max_x), y.min(max_y))
+    }
+
+    /// The `<facsimile>` section for the edited page. Each zone's `@n`
+    /// carries the label of the line it is linked to (its `<lb n>`, or the
+    /// line's position), so the body's `<lb facs>` can be matched up. The
+    /// image's `credit` goes along as the `<desc>` of its `<graphic>`.
+    pub fn to_xml(&self, lines: &[Line], credit: Option<&str>) -> String {
+        let mut xml = String::from("<facsimile>\n");
+        xml.push_str(&format!(
+            "  <surface xml:id=\"{}\" ulx=\"0\" uly=\"0\" lrx=\"{}\" lry=\"{}\">\n",
+            escape(&self.surface_id),
+            self.width,
+            self.height
+        ));
+        let graphic = format!(
+            "<graphic url=\"{}\" width=\"{}\" height=\"{}\"",
+            escape(&self.image_url),
+            self.width,
+            self.height
+        );
+        match credit {
+            Some(credit) => xml.push_str(&format!(
+                "    {}>\n      <desc>{}</desc>\n    </graphic>\n",
+                graphic,
+                escape(credit)
+            )),
+            None => xml.push_str(&format!("    {}/>\n", graphic)),
+        }
+        for zone in self.zones.iter().filter(|z| !z.points.is_empty()) {
+            let points = zone
+                .points
+                .iter()
+                .map(|(x, y)| format!("{},{}", x, y))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let n = zone
+                .line
+                .and_then(|i| lines.get(i).map(|l| (i, l)))
+                .map(|(i, line)| match &line.n {
+                    Some(n) => format!(" n=\"{}\"", escape(n.trim())),
+                    None => format!(" n=\"{}\"", i + 1),
+                })
+                .unwrap_or_default();
+            xml.push_str(&format!(
+                "    <zone xml:id=\"{}\" type=\"line\"{} points=\"{}\"/>\n",
+                escape(&zone.id),
+                n,
+                points
+            ));
+        }
+        xml.push_str("  </surface>\n</facsimile>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tei_data::Zone;
+
+    fn facsimile() -> Facsimile {
+        let mut facsimile = Facsimile {
+            surface_id: "s1".to_string(),
+            image_url: "p1.jpg".to_string(),
+            width: 1000,
+            height: 800,
+            ..Default::default()
+        };
+        for (id, points) in [("z2", vec![(10, 10), (90, 10), (90, 30)]), ("z9", vec![])] {
+            facsimile.zones.insert(
+                id.to_string(),
+                Zone {
+                    id: id.to_string(),
+                    zone_type: "line".to_string(),
+                    points,
+                },
+            );
+        }
+        facsimile
+    }
+
+    fn lines() -> Vec<Line> {
+        vec![Line {
+            facs: "z2".to_string(),
+            n: Some("341".to_string()),
+            content: Vec::new(),
+        }]
+    }
+
+    #[test]
+    fn test_new_links_lines() {
+        let editor = ZoneEditor::new(&facsimile(), &lines());
+        assert_eq!(editor.zones.len(), 2);
+        assert_eq!(editor.zones[0].id, "z2");
+        assert_eq!(editor.zones[0].line, Some(0));
+        assert_eq!(editor.zones[1].line, None);
+    }
+
+    #[test]
+    fn test_add_zone_and_points() {
+        let mut editor = ZoneEditor::new(&facsimile(), &lines());
+        let index = editor.add_zone();
+        assert_eq!(editor.zones[index].id, "z1");
+        assert!(editor.add_point((5, 5)));
+        // Clamped to the surface
+        assert!(editor.add_point((5000, 900)));
+        assert_eq!(editor.zones[index].points, vec![(5, 5), (1000, 800)]);
+        editor.remove_zone(0);
+        assert_eq!(editor.selected, Some(index - 1));
+    }
+
+    #[test]
+    fn test_drag() {
+        let mut editor = ZoneEditor::new(&facsimile(), &lines());
+        editor.grab = Some(Grab::Vertex(0, 1));
+        editor.drag_to((100, 12));
+        assert_eq!(editor.zones[0].points[1], (100, 12));
+
+        editor.grab = Some(Grab::Zone(0, (50, 20)));
+        editor.drag_to((45, 25));
+        assert_eq!(editor.zones[0].points, vec![(5, 15), (95, 17), (85, 35)]);
+        // Moving off the surface stops at the edge
+        editor.drag_to((0, 25));
+        assert_eq!(editor.zones[0].points[0], (0, 15));
+    }
+
+    #[test]
+    fn test_link_line_is_exclusive() {
+        let mut editor = ZoneEditor::new(&facsimile(), &lines());
+        editor.link_line(1, Some(0));
+        assert_eq!(editor.zones[0].line, None);
+        assert_eq!(editor.zones[1].line, Some(0));
+    }
+
+    #[test]
+    fn test_to_xml_round_trips() {
+        let editor = ZoneEditor::new(&facsimile(), &lines());
+        let xml = editor.to_xml(&lines(), None);
+        assert!(xml.contains(
+            "<zone xml:id=\"z2\" type=\"line\" n=\"341\" points=\"10,10 90,10 90,30\"/>"
+        ));
+        // Empty zones are left out
+        assert!(!xml.contains("z9"));
+
+        let tei = format!(
+            "<TEI xmlns=\"http://www.tei-c.org/ns/1.0\">{}<text><body><p><lb facs=\"#z2\"/>a</p></body></text></TEI>",
+            xml
+        );
+        let doc = crate::tei_parser::parse_tei_xml(&tei).unwrap();
+        assert_eq!(doc.facsimile.width, 1000);
+        assert_eq!(doc.facsimile.zones["z2"].points, editor.zones[0].points);
+
+        let xml = editor.to_xml(&lines(), Some("Foto: A & B"));
+        assert!(xml.contains("\">\n      <desc>Foto: A &amp; B</desc>\n    </graphic>\n"));
+    }
+}
@@ -0,0 +1,39 @@
+// static/sw.js
+// Offline fallback. The application decides which project files are kept
+// (src/offline.rs stores them in the "tei-viewer-offline" cache); this worker
+// only answers from the caches when the network fails, and keeps the
+// application itself so that the viewer can start without a connection.
+const SHELL_CACHE = "tei-viewer-shell";
+
+self.addEventListener("install", () => self.skipWaiting());
+self.addEventListener("activate", (event) => event.waitUntil(self.clients.claim()));
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    if (request.method !== "GET") {
+        return;
+    }
+    const url = new URL(request.url);
+    // Page files are left to the application; everything else served from
+    // this origin (index.html, wasm, scripts, styles, fonts) is the shell
+    const shell = url.origin === self.location.origin && !url.pathname.includes("/public/projects/");
+
+    event.respondWith(
+        fetch(request)
+            .then((response) => {
+                if (shell && response.ok) {
+                    const copy = response.clone();
+                    // Every route of the viewer is served by index.html
+                    const key = request.mode === "navigate" ? self.registration.scope : request;
+                    caches.open(SHELL_CACHE).then((cache) => cache.put(key, copy));
+                }
+                return response;
+            })
+            .catch(async () => {
+                const cached =
+                    (await caches.match(request, { ignoreSearch: true })) ||
+                    (request.mode === "navigate" && (await caches.match(self.registration.scope)));
+                return cached || Response.error();
+            }),
+    );
+});
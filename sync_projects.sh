@@ -44,12 +44,23 @@ for project_dir in "${PROJECTS_SRC}"/*; do
             echo "    Warning: No commentary.html found for ${project_name}"
         fi
 
+        # Copy places.json (coordinates for the map panel) if it exists
+        if [ -f "${project_dir}/places.json" ]; then
+            cp -v "${project_dir}/places.json" "${dest_dir}/"
+        fi
+
         # Copy images directory if it exists
         if [ -d "${project_dir}/images" ]; then
             mkdir -p "${dest_dir}/images"
             cp -rv "${project_dir}/images/"* "${dest_dir}/images/" 2>/dev/null || echo "    No images to copy"
         fi
 
+        # Copy audio directory if it exists
+        if [ -d "${project_dir}/audio" ]; then
+            mkdir -p "${dest_dir}/audio"
+            cp -rv "${project_dir}/audio/"* "${dest_dir}/audio/" 2>/dev/null || echo "    No audio to copy"
+        fi
+
         # Copy README.md if it exists
         if [ -f "${project_dir}/README.md" ]; then
             cp -v "${project_dir}/README.md" "${dest_dir}/"
@@ -57,6 +68,24 @@ for project_dir in "${PROJECTS_SRC}"/*; do
     fi
 done
 
+# Project index read by the viewer at startup: a hand-written
+# projects/index.json wins, else every synced project with a manifest
+if [ -f "${PROJECTS_SRC}/index.json" ]; then
+    cp -v "${PROJECTS_SRC}/index.json" "${PROJECTS_DEST}/index.json"
+else
+    echo "  Writing project index"
+    {
+        printf '['
+        separator=""
+        for manifest in "${PROJECTS_DEST}"/*/manifest.json; do
+            [ -f "${manifest}" ] || continue
+            printf '%s"%s"' "${separator}" "$(basename "$(dirname "${manifest}")")"
+            separator=", "
+        done
+        printf ']\n'
+    } > "${PROJECTS_DEST}/index.json"
+fi
+
 echo "Project sync complete!"
 echo ""
 echo "Projects synced:"
@@ -0,0 +1,465 @@
+// xtask/src/main.rs
+// Maintenance tasks of the viewer, run with `cargo xtask <task>`.
+//
+// `cargo xtask scan [DIR]` writes the manifests of the project folders in DIR
+// (`public/projects` by default) from the files they hold, so that page lists
+// need not be kept by hand: `p3_dip.xml`, `p3_trad.xml` (or `p3_trad_en.xml`),
+// `images/p3.jpg` (and `images/p3_800.jpg` variants, `images/thumbs/p3.jpg`)
+// and `p3_commentary.md` (or `commentary/p3.html`) make page 3. Existing
+// manifests keep their fields and the order and labels of their pages; only
+// what the files say is updated. `index.json` lists every project folder.
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+
+/// Manifest version written; see `SCHEMA_VERSION` in `src/project_config.rs`
+const SCHEMA_VERSION: u64 = 2;
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "gif", "tif", "tiff"];
+const COMMENTARY_EXTENSIONS: &[&str] = &["html", "htm", "md", "markdown", "xml"];
+
+/// Metadata fields a manifest must have
+const METADATA_FIELDS: &[&str] = &[
+    "author",
+    "editor",
+    "collection",
+    "institution",
+    "country",
+    "language",
+    "date_range",
+];
+
+/// Manifest fields naming the page files otherwise than by default
+const PATTERN_FIELDS: &[&str] = &["diplomatic_pattern", "translation_pattern", "image_pattern"];
+
+/// What the files of a project folder say about one page
+#[derive(Debug, Default, PartialEq)]
+struct PageFiles {
+    diplomatic: bool,
+    translation: bool,
+    /// Languages of `pN_trad_{lang}.xml`
+    translations: BTreeSet<String>,
+    /// Path of `images/pN.*`
+    image: Option<String>,
+    /// (path, width) of `images/pN_{width}.*`
+    variants: Vec<(String, u32)>,
+    thumbnail: Option<String>,
+    commentary: Option<String>,
+}
+
+fn extension(name: &str) -> Option<String> {
+    name.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase())
+}
+
+/// Page number of a file stem such as `p12` or `p12_dip`, with the rest
+fn page_stem(stem: &str) -> Option<(u32, &str)> {
+    let (number, rest) = stem.split_once('_').unwrap_or((stem, ""));
+    let number = number.strip_prefix('p')?;
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((number.parse().ok()?, rest))
+}
+
+/// Pages named by `paths`, relative to the project folder with '/'.
+fn page_files(paths: &[String]) -> BTreeMap<u32, PageFiles> {
+    let mut pages: BTreeMap<u32, PageFiles> = BTreeMap::new();
+    for path in paths {
+        let (dir, name) = path.rsplit_once('/').unwrap_or(("", path));
+        let Some((stem, _)) = name.rsplit_once('.') else {
+            continue;
+        };
+        let Some((number, rest)) = page_stem(stem) else {
+            continue;
+        };
+        let ext = extension(name).unwrap_or_default();
+        let image = IMAGE_EXTENSIONS.contains(&ext.as_str());
+        let commentary = COMMENTARY_EXTENSIONS.contains(&ext.as_str());
+        match (dir, rest) {
+            ("", "dip") if ext == "xml" => pages.entry(number).or_default().diplomatic = true,
+            ("", "trad") if ext == "xml" => pages.entry(number).or_default().translation = true,
+            ("", "commentary") if commentary => {
+                pages.entry(number).or_default().commentary = Some(path.clone())
+            }
+            ("", rest) if ext == "xml" && rest.starts_with("trad_") => {
+                let entry = pages.entry(number).or_default();
+                entry.translations.insert(rest["trad_".len()..].to_string());
+            }
+            ("images", "") if image => pages.entry(number).or_default().image = Some(path.clone()),
+            ("images", width) if image => {
+                if let Ok(width) = width.parse() {
+                    let entry = pages.entry(number).or_default();
+                    entry.variants.push((path.clone(), width));
+                }
+            }
+            ("images/thumbs", "") if image => {
+                pages.entry(number).or_default().thumbnail = Some(path.clone())
+            }
+            ("commentary", "") if commentary => {
+                pages.entry(number).or_default().commentary = Some(path.clone())
+            }
+            _ => {}
+        }
+    }
+    for page in pages.values_mut() {
+        page.variants.sort_by_key(|(_, width)| *width);
+    }
+    pages
+}
+
+/// Bring the manifest entry of a page in line with its files.
+fn update_page(entry: &mut Map<String, Value>, files: &PageFiles) {
+    // Images served from elsewhere are not in the folder
+    let image_elsewhere = ["iiif_service", "dzi"]
+        .iter()
+        .any(|field| entry.get(*field).is_some_and(|v| !v.is_null()));
+    entry.insert("has_diplomatic".into(), files.diplomatic.into());
+    entry.insert(
+        "has_translation".into(),
+        (files.translation || !files.translations.is_empty()).into(),
+    );
+    entry.insert(
+        "has_image".into(),
+        (files.image.is_some() || image_elsewhere).into(),
+    );
+    if !files.translations.is_empty() {
+        entry.insert("translations".into(), json!(files.translations));
+    }
+    if !files.variants.is_empty() {
+        let variants: Vec<Value> = files
+            .variants
+            .iter()
+            .map(|(path, width)| json!({ "path": path, "width": width }))
+            .collect();
+        entry.insert("image_variants".into(), variants.into());
+    }
+    if let Some(thumbnail) = &files.thumbnail {
+        entry.insert("thumbnail".into(), thumbnail.as_str().into());
+    }
+    if let Some(commentary) = &files.commentary {
+        entry.insert("commentary".into(), commentary.as_str().into());
+    }
+}
+
+fn new_page(number: u32) -> Map<String, Value> {
+    let mut entry = Map::new();
+    entry.insert("number".into(), number.into());
+    entry.insert("label".into(), format!("Page {}", number).into());
+    entry
+}
+
+/// The manifest of `folder` with its pages as `pages` says; `manifest` is
+/// the one already written, if any.
+fn update_manifest(
+    manifest: Option<Value>,
+    folder: &str,
+    pages: &BTreeMap<u32, PageFiles>,
+) -> Result<Value, String> {
+    let mut manifest = match manifest {
+        Some(Value::Object(manifest)) => manifest,
+        Some(_) => return Err("the manifest is not a JSON object".to_string()),
+        None => {
+            let mut manifest = Map::new();
+            manifest.insert("schema_version".into(), SCHEMA_VERSION.into());
+            manifest.insert("id".into(), folder.into());
+            manifest.insert("name".into(), folder.into());
+            manifest.insert("description".into(), "".into());
+            let metadata: Map<String, Value> = METADATA_FIELDS
+                .iter()
+                .map(|field| (field.to_string(), Value::from("")))
+                .collect();
+            manifest.insert("metadata".into(), metadata.into());
+            manifest
+        }
+    };
+    if let Some(field) = PATTERN_FIELDS.iter().find(|f| manifest.contains_key(**f)) {
+        return Err(format!(
+            "its files are named by `{}`; update its pages by hand",
+            field
+        ));
+    }
+    let Value::Array(entries) = manifest
+        .entry("pages")
+        .or_insert_with(|| Value::Array(Vec::new()))
+    else {
+        return Err("`pages` is not a list".to_string());
+    };
+    let listed: BTreeSet<u32> = entries
+        .iter()
+        .filter_map(|e| e["number"].as_u64())
+        .map(|n| n as u32)
+        .collect();
+    let empty = PageFiles::default();
+    for entry in entries.iter_mut().filter_map(Value::as_object_mut) {
+        let Some(number) = entry.get("number").and_then(Value::as_u64) else {
+            continue;
+        };
+        let files = pages.get(&(number as u32)).unwrap_or(&empty);
+        if files == &empty {
+            eprintln!("  {}: page {} has no files", folder, number);
+        }
+        update_page(entry, files);
+    }
+    // New pages go before the first listed page after them
+    for (&number, files) in pages.iter().filter(|(n, _)| !listed.contains(n)) {
+        let mut entry = new_page(number);
+        update_page(&mut entry, files);
+        let at = entries
+            .iter()
+            .position(|e| e["number"].as_u64().is_some_and(|n| n > u64::from(number)))
+            .unwrap_or(entries.len());
+        entries.insert(at, entry.into());
+    }
+    Ok(manifest.into())
+}
+
+/// The project index listing `folders`: the entries of `index` first, in
+/// their order, then the folders it misses.
+fn update_index(index: Option<Value>, folders: &[String]) -> Value {
+    let mut entries: Vec<Value> = match index {
+        Some(Value::Array(entries)) => entries,
+        _ => Vec::new(),
+    };
+    for folder in folders {
+        let listed = entries
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|entry| entry == folder || entry.starts_with(&format!("{}/", folder)));
+        if !listed {
+            entries.push(folder.as_str().into());
+        }
+    }
+    entries.into()
+}
+
+/// Paths of the files under `dir`, relative to `base` with '/'; hidden
+/// entries are skipped.
+fn list_files(base: &Path, dir: &Path, paths: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            list_files(base, &path, paths)?;
+        } else if let Ok(relative) = path.strip_prefix(base) {
+            let parts: Vec<String> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            paths.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+fn read_json(path: &Path) -> Result<Option<Value>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn write_json(path: &Path, value: &Value) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    fs::write(path, text + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn scan(dir: &Path) -> Result<(), String> {
+    let mut folders: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?
+        .filter_map(Result::ok)
+        .filter(|e| e.path().is_dir())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    folders.sort();
+    let mut written = Vec::new();
+    for folder in folders {
+        let path = dir.join(&folder);
+        let mut paths = Vec::new();
+        list_files(&path, &path, &mut paths).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let pages = page_files(&paths);
+        let manifest_path = path.join("manifest.json");
+        let manifest = read_json(&manifest_path)?;
+        if manifest.is_none() && pages.is_empty() {
+            continue;
+        }
+        match update_manifest(manifest, &folder, &pages) {
+            Ok(manifest) => {
+                write_json(&manifest_path, &manifest)?;
+                println!(
+                    "  {}: {} pages",
+                    folder,
+                    manifest["pages"].as_array().map_or(0, Vec::len)
+                );
+            }
+            Err(e) => eprintln!("  {}: left as it is: {}", folder, e),
+        }
+        written.push(folder);
+    }
+    let index_path = dir.join("index.json");
+    let index = read_json(&index_path).unwrap_or(None);
+    write_json(&index_path, &update_index(index, &written))?;
+    println!(
+        "Wrote {} ({} projects)",
+        index_path.display(),
+        written.len()
+    );
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("scan") => {
+            let dir = args.get(1).map_or("public/projects", String::as_str);
+            scan(Path::new(dir))
+        }
+        _ => Err("usage: cargo xtask scan [DIR]".to_string()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_page_files() {
+        let pages = page_files(&paths(&[
+            "p1_dip.xml",
+            "p1_trad.xml",
+            "images/p1.jpg",
+            "images/p1_2400.jpg",
+            "images/p1_800.jpg",
+            "images/thumbs/p1.jpg",
+            "p1_commentary.md",
+            "p2_dip.xml",
+            "p2_trad_es.xml",
+            "p2_trad_en.xml",
+            "commentary/p2.html",
+            "images/p10.png",
+            "glossary.xml",
+            "images/cover.jpg",
+            "p3_notes.txt",
+        ]));
+        assert_eq!(pages.keys().copied().collect::<Vec<_>>(), vec![1, 2, 10]);
+        let p1 = &pages[&1];
+        assert!(p1.diplomatic && p1.translation);
+        assert_eq!(p1.image.as_deref(), Some("images/p1.jpg"));
+        assert_eq!(
+            p1.variants,
+            vec![
+                ("images/p1_800.jpg".to_string(), 800),
+                ("images/p1_2400.jpg".to_string(), 2400)
+            ]
+        );
+        assert_eq!(p1.thumbnail.as_deref(), Some("images/thumbs/p1.jpg"));
+        assert_eq!(p1.commentary.as_deref(), Some("p1_commentary.md"));
+        let p2 = &pages[&2];
+        assert!(!p2.translation);
+        assert_eq!(p2.translations.iter().collect::<Vec<_>>(), vec!["en", "es"]);
+        assert_eq!(p2.commentary.as_deref(), Some("commentary/p2.html"));
+        assert!(!pages[&10].diplomatic);
+    }
+
+    #[test]
+    fn test_new_manifest() {
+        let pages = page_files(&paths(&["p2_dip.xml", "p1_dip.xml", "images/p1.jpg"]));
+        let manifest = update_manifest(None, "PGM-XIII", &pages).unwrap();
+        assert_eq!(manifest["id"], "PGM-XIII");
+        assert_eq!(manifest["metadata"]["date_range"], "");
+        assert_eq!(
+            manifest["pages"],
+            json!([
+                { "number": 1, "label": "Page 1", "has_diplomatic": true,
+                  "has_translation": false, "has_image": true },
+                { "number": 2, "label": "Page 2", "has_diplomatic": true,
+                  "has_translation": false, "has_image": false }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_update_manifest() {
+        let manifest = json!({
+            "id": "P", "name": "Papiro", "description": "Hechizos",
+            "pages": [
+                { "number": 3, "label": "3r", "has_diplomatic": false,
+                  "has_translation": false, "has_image": false, "iiif_service": "https://x.org/3" },
+                { "number": 1, "label": "1r", "has_diplomatic": true,
+                  "has_translation": true, "has_image": true }
+            ],
+            "metadata": {}
+        });
+        let pages = page_files(&paths(&[
+            "p1_dip.xml",
+            "p2_dip.xml",
+            "p3_dip.xml",
+            "p4_dip.xml",
+        ]));
+        let manifest = update_manifest(Some(manifest), "P", &pages).unwrap();
+        // Fields keep their order, pages their order and labels
+        let keys: Vec<&String> = manifest.as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["id", "name", "description", "pages", "metadata"]);
+        let pages: Vec<(u64, &str, bool, bool, bool)> = manifest["pages"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| {
+                (
+                    p["number"].as_u64().unwrap(),
+                    p["label"].as_str().unwrap(),
+                    p["has_diplomatic"].as_bool().unwrap(),
+                    p["has_translation"].as_bool().unwrap(),
+                    p["has_image"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            pages,
+            vec![
+                (2, "Page 2", true, false, false),
+                (3, "3r", true, false, true),
+                (1, "1r", true, false, false),
+                (4, "Page 4", true, false, false),
+            ]
+        );
+
+        let patterned = json!({ "id": "P", "diplomatic_pattern": "dip/{page}.xml" });
+        assert!(update_manifest(Some(patterned), "P", &BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_update_index() {
+        let index = json!(["PGM-XIII", "B/manifest.json", "https://x.org/manifest.json"]);
+        let folders = ["A".to_string(), "B".to_string(), "PGM-XIII".to_string()];
+        assert_eq!(
+            update_index(Some(index), &folders),
+            json!([
+                "PGM-XIII",
+                "B/manifest.json",
+                "https://x.org/manifest.json",
+                "A"
+            ])
+        );
+        assert_eq!(update_index(None, &folders[..1]), json!(["A"]));
+    }
+}